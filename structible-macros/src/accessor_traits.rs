@@ -0,0 +1,168 @@
+use proc_macro2::TokenStream;
+use quote::{ToTokens, format_ident, quote};
+use syn::{Generics, Ident, Visibility};
+
+use crate::parse::{FieldInfo, StructibleConfig};
+
+/// Checks that `mock` is only given to a struct with `accessor_traits`.
+pub fn check_accessor_traits_config(
+    struct_name: &Ident,
+    config: &StructibleConfig,
+) -> syn::Result<()> {
+    if config.mock && !config.accessor_traits {
+        return Err(syn::Error::new_spanned(
+            struct_name,
+            "`mock` requires `accessor_traits`",
+        ));
+    }
+    Ok(())
+}
+
+/// Returns the names of the reader and writer traits of a struct.
+pub fn accessor_trait_names(struct_name: &Ident) -> (Ident, Ident) {
+    (
+        format_ident!("{}Reader", struct_name),
+        format_ident!("{}Writer", struct_name),
+    )
+}
+
+/// Returns true if an accessor of visibility `accessor_vis` may appear in a trait
+/// of visibility `vis`, i.e. it is public or as visible as the trait.
+fn is_exposed(accessor_vis: &Visibility, vis: &Visibility) -> bool {
+    matches!(accessor_vis, Visibility::Public(_))
+        || accessor_vis.to_token_stream().to_string() == vis.to_token_stream().to_string()
+}
+
+/// Generate the `<Struct>Reader` and `<Struct>Writer` traits and their impls for
+/// the struct, if `accessor_traits` is set.
+///
+/// The reader holds the getters of the known fields, and the writer their setters
+/// and removers, each delegating to the struct's own method. Lazy fields, which
+/// load through a context, are left out of the reader, and accessors less visible
+/// than the struct are left out of both. A writer without methods isn't generated.
+///
+/// Setters of transparent fields take the newtype rather than `impl Into<_>`, so
+/// that the writer stays dyn-compatible.
+///
+/// With `mock`, both traits get `#[cfg_attr(test, ::mockall::automock)]`, so test
+/// builds of the user's crate (which must depend on `mockall`) also get
+/// `Mock<Struct>Reader` and `Mock<Struct>Writer`.
+pub fn generate_accessor_traits(
+    struct_name: &Ident,
+    vis: &Visibility,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    if !config.accessor_traits {
+        return quote! {};
+    }
+    let (reader, writer) = accessor_trait_names(struct_name);
+    let automock = if config.mock {
+        quote! { #[cfg_attr(test, ::mockall::automock)] }
+    } else {
+        quote! {}
+    };
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let known: Vec<_> = fields.iter().filter(|f| !f.is_unknown_field()).collect();
+
+    let mut reader_decls = Vec::new();
+    let mut reader_impls = Vec::new();
+    for f in known
+        .iter()
+        .filter(|f| !f.is_lazy() && is_exposed(f.getter_vis(), vis))
+    {
+        let getter = f.getter_name();
+        let method_attrs = f.method_attrs();
+        let doc = format!("See [`{}::{}`].", struct_name, getter);
+        // mockall only accepts borrows nested in the return type, as in
+        // `Option<&T>`, under an explicit lifetime
+        let signature = if f.has_optional_getter() && !f.is_weak() && !f.is_transparent() {
+            let target = f.getter_target();
+            quote! { fn #getter<'__a>(&'__a self) -> Option<&'__a #target> }
+        } else {
+            let ret = f.getter_type();
+            quote! { fn #getter(&self) -> #ret }
+        };
+        reader_decls.push(quote! {
+            #[doc = #doc]
+            #(#method_attrs)*
+            #signature;
+        });
+        reader_impls.push(quote! {
+            #signature {
+                Self::#getter(self)
+            }
+        });
+    }
+
+    let mut writer_decls = Vec::new();
+    let mut writer_impls = Vec::new();
+    for f in &known {
+        let method_attrs = f.method_attrs();
+        if !config.no_setters && is_exposed(f.setter_vis(), vis) {
+            let setter = f.setter_name();
+            let value_ty = &f.inner_ty;
+            let doc = format!("See [`{}::{}`].", struct_name, setter);
+            writer_decls.push(quote! {
+                #[doc = #doc]
+                #(#method_attrs)*
+                fn #setter(&mut self, value: #value_ty);
+            });
+            writer_impls.push(quote! {
+                fn #setter(&mut self, value: #value_ty) {
+                    Self::#setter(self, value)
+                }
+            });
+        }
+        if f.is_optional && !config.no_removers && is_exposed(f.remover_vis(), vis) {
+            let remover = f.remover_name();
+            let inner_ty = &f.inner_ty;
+            let doc = format!("See [`{}::{}`].", struct_name, remover);
+            writer_decls.push(quote! {
+                #[doc = #doc]
+                #(#method_attrs)*
+                fn #remover(&mut self) -> Option<#inner_ty>;
+            });
+            writer_impls.push(quote! {
+                fn #remover(&mut self) -> Option<#inner_ty> {
+                    Self::#remover(self)
+                }
+            });
+        }
+    }
+
+    let reader_doc = format!("Read access to the fields of [`{}`].", struct_name);
+    let writer_doc = format!("Write access to the fields of [`{}`].", struct_name);
+    let writer_trait = if writer_decls.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            #[doc = #writer_doc]
+            #automock
+            #vis trait #writer #generics #where_clause {
+                #(#writer_decls)*
+            }
+
+            #[allow(deprecated)]
+            impl #impl_generics #writer #ty_generics for #struct_name #ty_generics #where_clause {
+                #(#writer_impls)*
+            }
+        }
+    };
+
+    quote! {
+        #[doc = #reader_doc]
+        #automock
+        #vis trait #reader #generics #where_clause {
+            #(#reader_decls)*
+        }
+
+        #[allow(deprecated)]
+        impl #impl_generics #reader #ty_generics for #struct_name #ty_generics #where_clause {
+            #(#reader_impls)*
+        }
+
+        #writer_trait
+    }
+}
@@ -0,0 +1,258 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::{Generics, Ident, Visibility};
+
+use crate::codegen::{field_enum_type, value_enum_name};
+use crate::parse::{FieldInfo, StructibleConfig};
+use crate::util::{to_pascal_case, type_mentions_type_param};
+
+/// Returns the name of the array builder type of a struct.
+pub fn array_builder_name(struct_name: &Ident) -> Ident {
+    format_ident!("{}ArrayBuilder", struct_name)
+}
+
+/// Checks that a struct with `arrow` implements `Structible`, which reading
+/// record batches builds on.
+pub fn check_arrow_config(struct_name: &Ident, config: &StructibleConfig) -> syn::Result<()> {
+    if config.arrow && config.no_structible {
+        return Err(syn::Error::new_spanned(
+            struct_name,
+            "`arrow` requires the `Structible` impl and cannot be combined with `no_structible`",
+        ));
+    }
+    Ok(())
+}
+
+/// Generate `arrow_schema()`, the array builder type, and the `ArrowRecord` impl,
+/// if `arrow` is set.
+///
+/// Each known field is a column typed by its `ArrowValue` impl, nullable if the
+/// field is optional. The builder keeps one `ArrowValue::Builder` per column and
+/// appends absent (or expired) fields as nulls, and `from_record_batch` reads the
+/// columns back by name into a backing per row. Catch-all entries have no column.
+pub fn generate_arrow_impl(
+    struct_name: &Ident,
+    vis: &Visibility,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    if !config.arrow {
+        return quote! {};
+    }
+    let builder = array_builder_name(struct_name);
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let arrow = quote! { ::structible::arrow };
+    let arrow_value = quote! { ::structible::arrow::ArrowValue };
+    let arrow_array = quote! { ::structible::__private::arrow_array };
+    let arrow_schema = quote! { ::structible::__private::arrow_schema };
+    let struct_doc = format!(
+        "Appends [`{}`]s column-wise into Arrow arrays, see `{}::arrow_schema()`.",
+        struct_name, struct_name
+    );
+
+    let known_fields: Vec<_> = fields.iter().filter(|f| !f.is_unknown_field()).collect();
+    let columns: Vec<_> = known_fields.iter().map(|f| &f.name).collect();
+
+    let schema_fields = known_fields.iter().map(|f| {
+        let name_str = f.name.unraw().to_string();
+        let ty = &f.inner_ty;
+        let nullable = f.is_optional;
+        quote! {
+            #arrow_schema::Field::new(#name_str, <#ty as #arrow_value>::data_type(), #nullable)
+        }
+    });
+    let builder_fields = known_fields.iter().zip(&columns).map(|(f, column)| {
+        let ty = &f.inner_ty;
+        quote! { #column: <#ty as #arrow_value>::Builder }
+    });
+    let appends = known_fields.iter().zip(&columns).map(|(f, column)| {
+        let ty = &f.inner_ty;
+        let variant = to_pascal_case(&f.name);
+        // Expired values read as absent, so they are appended as nulls
+        let expired_check = if config.ttl && f.is_optional {
+            quote! { if !value.__structible_expired(&#field_enum::#variant) }
+        } else {
+            quote! {}
+        };
+        quote! {
+            <#ty as #arrow_value>::append(
+                &mut self.#column,
+                match ::structible::BackingMap::get(&value.inner, &#field_enum::#variant) {
+                    ::std::option::Option::Some(#value_enum::#variant(v)) #expired_check => {
+                        ::std::option::Option::Some(v)
+                    }
+                    _ => ::std::option::Option::None,
+                },
+            );
+        }
+    });
+
+    let column_vars: Vec<_> = known_fields
+        .iter()
+        .map(|f| format_ident!("column_{}", f.name.unraw()))
+        .collect();
+    let read_columns = known_fields.iter().zip(&column_vars).map(|(f, var)| {
+        let name_str = f.name.unraw().to_string();
+        if f.is_optional {
+            quote! {
+                let #var = batch.column_by_name(#name_str);
+            }
+        } else {
+            quote! {
+                let #var = #arrow::__required_column(batch, #name_str)?;
+            }
+        }
+    });
+    let read_values = known_fields.iter().zip(&column_vars).map(|(f, var)| {
+        let name_str = f.name.unraw().to_string();
+        let ty = &f.inner_ty;
+        let variant = to_pascal_case(&f.name);
+        if f.is_optional {
+            quote! {
+                if let ::std::option::Option::Some(column) = #var
+                    && let ::std::option::Option::Some(value) =
+                        <#ty as #arrow_value>::read(column.as_ref(), index)?
+                {
+                    ::structible::BackingMap::insert(
+                        &mut backing,
+                        #field_enum::#variant,
+                        #value_enum::#variant(value),
+                    );
+                }
+            }
+        } else {
+            quote! {
+                ::structible::BackingMap::insert(
+                    &mut backing,
+                    #field_enum::#variant,
+                    #value_enum::#variant(#arrow::__read_required::<#ty>(#var, #name_str, index)?),
+                );
+            }
+        }
+    });
+
+    // Generic field types must choose their column type
+    let type_param_idents: Vec<_> = generics.type_params().map(|tp| &tp.ident).collect();
+    let arrow_types: Vec<_> = known_fields
+        .iter()
+        .map(|f| &f.inner_ty)
+        .filter(|ty| type_mentions_type_param(ty, &type_param_idents))
+        .collect();
+    let bounds = quote! { #(#arrow_types: #arrow_value,)* };
+    let where_clause = match where_clause {
+        Some(wc) => {
+            let existing = &wc.predicates;
+            quote! { where #bounds #existing }
+        }
+        None if !arrow_types.is_empty() => quote! { where #bounds },
+        None => quote! {},
+    };
+
+    quote! {
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            /// Returns the Arrow schema of the struct's known fields, in declaration
+            /// order. Optional fields are nullable.
+            pub fn arrow_schema() -> #arrow_schema::Schema {
+                #arrow_schema::Schema::new(::std::vec![#(#schema_fields),*])
+            }
+        }
+
+        #[doc = #struct_doc]
+        #vis struct #builder #generics #where_clause {
+            #(#builder_fields,)*
+            __len: usize,
+            __marker: ::std::marker::PhantomData<fn() -> #struct_name #ty_generics>,
+        }
+
+        impl #impl_generics #builder #ty_generics #where_clause {
+            /// Creates a builder without rows.
+            pub fn new() -> Self {
+                Self {
+                    #(#columns: ::std::default::Default::default(),)*
+                    __len: 0,
+                    __marker: ::std::marker::PhantomData,
+                }
+            }
+
+            /// Appends a row holding `value`'s fields, with nulls for absent fields.
+            pub fn append(&mut self, value: &#struct_name #ty_generics) {
+                #(#appends)*
+                self.__len += 1;
+            }
+
+            /// Returns the number of rows appended since the builder was created or
+            /// last finished.
+            pub fn len(&self) -> usize {
+                self.__len
+            }
+
+            /// Returns true if no rows were appended since the builder was created or
+            /// last finished.
+            pub fn is_empty(&self) -> bool {
+                self.__len == 0
+            }
+
+            /// Returns the appended rows as a record batch, and resets the builder.
+            pub fn finish(
+                &mut self,
+            ) -> ::std::result::Result<#arrow_array::RecordBatch, #arrow_schema::ArrowError> {
+                let options = #arrow_array::RecordBatchOptions::new()
+                    .with_row_count(::std::option::Option::Some(self.__len));
+                self.__len = 0;
+                #arrow_array::RecordBatch::try_new_with_options(
+                    ::std::sync::Arc::new(<#struct_name #ty_generics>::arrow_schema()),
+                    ::std::vec![#(#arrow_array::builder::ArrayBuilder::finish(&mut self.#columns)),*],
+                    &options,
+                )
+            }
+        }
+
+        impl #impl_generics #arrow::ArrowRecord for #struct_name #ty_generics #where_clause {
+            fn arrow_schema() -> #arrow_schema::Schema {
+                <#struct_name #ty_generics>::arrow_schema()
+            }
+
+            fn to_record_batch<'__a, __I>(
+                records: __I,
+            ) -> ::std::result::Result<#arrow_array::RecordBatch, #arrow_schema::ArrowError>
+            where
+                __I: ::std::iter::IntoIterator<Item = &'__a Self>,
+                Self: '__a,
+            {
+                let mut builder = #builder::new();
+                for record in records {
+                    builder.append(record);
+                }
+                builder.finish()
+            }
+
+            fn from_record_batch(
+                batch: &#arrow_array::RecordBatch,
+            ) -> ::std::result::Result<::std::vec::Vec<Self>, #arrow_schema::ArrowError> {
+                #(#read_columns)*
+                (0..batch.num_rows())
+                    .map(|index| {
+                        let mut backing = <<Self as ::structible::Structible>::Backing
+                            as ::structible::BackingMap<_, _>>::new();
+                        #(#read_values)*
+                        <Self as ::structible::Structible>::try_from_backing(backing).map_err(|err| {
+                            #arrow_schema::ArrowError::InvalidArgumentError(
+                                ::std::string::ToString::to_string(&err),
+                            )
+                        })
+                    })
+                    .collect()
+            }
+        }
+
+        impl #impl_generics ::std::default::Default for #builder #ty_generics #where_clause {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{Generics, Ident, Type};
+
+use crate::parse::{FieldInfo, StructibleConfig};
+use crate::util::type_mentions_generics;
+
+/// Generate compile-time checks that each field's type implements the traits
+/// the generated impls require of it.
+///
+/// The value enum's impls only bound field types mentioning the struct's
+/// generics, so a concrete type missing a trait fails inside them with an error
+/// that doesn't name the field. The checks in `structible::__private::assert`
+/// report it on the field's type instead, naming the field and how to opt out.
+/// Types mentioning the struct's generics are checked where the struct is used.
+pub fn generate_field_assertions(
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    let is_concrete = |ty: &Type| !type_mentions_generics(ty, generics);
+    let mut markers = Vec::new();
+    let mut checks = Vec::new();
+    for f in fields {
+        let name = &f.name;
+        let ty = &f.inner_ty;
+        let mut check = |ty: &Type, check: &str| {
+            let check = Ident::new(check, ty.span());
+            let mut marker = name.clone();
+            marker.set_span(ty.span());
+            checks.push(quote_spanned! {ty.span()=>
+                #ty: ::structible::__private::assert::#check<__structible_fields::#marker>
+            });
+        };
+        if is_concrete(ty) {
+            check(ty, "FieldDebug");
+            if !config.no_clone {
+                if f.config.skip_clone && !f.is_unknown_field() {
+                    check(ty, "FieldDefault");
+                } else {
+                    check(ty, "FieldClone");
+                }
+            }
+            if !config.no_partial_eq && !f.skips_eq() {
+                check(ty, "FieldPartialEq");
+            }
+        }
+        if let Some(key_type) = f.unknown_key_type().filter(|ty| is_concrete(ty)) {
+            match config.backing.last_ident() {
+                Some(backing) if backing == "HashMap" => check(key_type, "KeyHash"),
+                Some(backing) if backing == "BTreeMap" => check(key_type, "KeyOrd"),
+                _ => {}
+            }
+        }
+        markers.push(name);
+    }
+    if checks.is_empty() {
+        return quote! {};
+    }
+
+    quote! {
+        const _: () = {
+            #[allow(dead_code, non_camel_case_types)]
+            mod __structible_fields {
+                #(pub struct #markers;)*
+            }
+
+            #[allow(dead_code)]
+            fn __structible_assert()
+            where
+                #(#checks,)*
+            {
+            }
+        };
+    }
+}
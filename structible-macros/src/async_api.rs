@@ -0,0 +1,364 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::{Attribute, Generics, Ident, Visibility};
+
+use crate::codegen::{field_enum_name, field_enum_type, value_enum_name};
+use crate::parse::{FieldInfo, StructibleConfig};
+use crate::util::{extract_doc_comments, format_method_doc, to_pascal_case};
+
+/// Checks that the struct only uses features supported in `async` mode.
+///
+/// Features that need to borrow from or iterate over the backing map, or that
+/// run synchronous checks against it, have no async counterpart.
+pub fn check_async_config(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+) -> syn::Result<()> {
+    let unsupported = [
+        (config.with_len, "with_len"),
+        (config.from_tuple, "from_tuple"),
+        (config.invariant.is_some(), "invariant"),
+        (config.track_changes, "track_changes"),
+        (config.lww, "lww"),
+        (config.ttl, "ttl"),
+        (config.tombstones, "tombstones"),
+        (config.provenance, "provenance"),
+        (config.timestamps, "timestamps"),
+        (config.no_setters, "no_setters"),
+        (config.no_removers, "no_removers"),
+        (config.no_mut_getters, "no_mut_getters"),
+        (config.par_visit, "par_visit"),
+        (config.debug_json, "debug_json"),
+        (config.csv, "csv"),
+        (config.ffi, "ffi"),
+        (config.arrow, "arrow"),
+        (config.envelope, "envelope"),
+        (config.example, "example"),
+        (config.sample, "sample"),
+        (config.fake, "fake"),
+        (config.test_serialize, "test_serialize"),
+        (config.serde, "serde"),
+        (config.instrument, "instrument"),
+        (config.trace, "trace"),
+        (config.record_fields, "record_fields"),
+        (config.labels, "labels"),
+        (config.transactional, "transactional"),
+        (config.patch, "patch"),
+        (config.builder.is_some(), "builder"),
+        (config.openapi, "openapi"),
+        (config.axum, "axum"),
+        (config.graphql, "graphql"),
+        (config.minimal, "minimal"),
+        (config.accessor_traits, "accessor_traits"),
+        (config.no_panic, "no_panic"),
+    ];
+    if let Some((_, name)) = unsupported.iter().find(|(used, _)| *used) {
+        return Err(syn::Error::new_spanned(
+            struct_name,
+            format!("`{}` is not supported with `async`", name),
+        ));
+    }
+
+    for field in fields {
+        if field.has_constraints() {
+            return Err(syn::Error::new_spanned(
+                &field.name,
+                "field constraints are not supported with `async`",
+            ));
+        }
+        if field.config.multi {
+            return Err(syn::Error::new_spanned(
+                &field.name,
+                "`multi` is not supported with `async`",
+            ));
+        }
+        if let Some(validate_key) = &field.config.validate_key {
+            return Err(syn::Error::new_spanned(
+                validate_key,
+                "`validate_key` is not supported with `async`",
+            ));
+        }
+        if let Some(alias) = field.config.aliases.first() {
+            return Err(syn::Error::new_spanned(
+                alias,
+                "`alias` is not supported with `async`",
+            ));
+        }
+        if let Some(raw) = &field.config.transparent {
+            return Err(syn::Error::new_spanned(
+                raw,
+                "`transparent` is not supported with `async`",
+            ));
+        }
+        if let Some(variant) = field.config.variants.first() {
+            return Err(syn::Error::new_spanned(
+                variant,
+                "`variants` is not supported with `async`",
+            ));
+        }
+        if let Some(context) = &field.config.required_context {
+            return Err(syn::Error::new_spanned(
+                context,
+                "`required_context` is not supported with `async`",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Generate the struct definition and impl block for `async` mode.
+///
+/// The struct is a handle over an `AsyncBackingMap`: it is attached to an
+/// existing backing with `from_backing`, or created with the async constructor,
+/// which writes the required fields. Since the backing may be shared with other
+/// processes, required fields are not guaranteed to be present, so every getter
+/// returns an `Option`.
+pub fn generate_async_struct(
+    struct_name: &Ident,
+    vis: &Visibility,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    attrs: &[Attribute],
+    generics: &Generics,
+) -> TokenStream {
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+    let map_type = config.backing.to_tokens();
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let backing_ty = quote! { #map_type<#field_enum, #value_enum #ty_generics> };
+    let error_ty = quote! {
+        <#backing_ty as ::structible::AsyncBackingMap<#field_enum, #value_enum #ty_generics>>::Error
+    };
+
+    let constructor =
+        generate_async_constructor(struct_name, fields, config, &backing_ty, &error_ty);
+    let accessors = fields
+        .iter()
+        .filter(|f| !f.is_unknown_field())
+        .map(|f| generate_async_accessors(struct_name, f, &error_ty));
+    let unknown_methods = fields
+        .iter()
+        .find(|f| f.is_unknown_field())
+        .map(|f| generate_async_unknown_methods(struct_name, f, &error_ty));
+
+    quote! {
+        #(#attrs)*
+        #vis struct #struct_name #generics #where_clause {
+            inner: #backing_ty,
+        }
+
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            #constructor
+
+            /// Wraps an existing backing without writing to it.
+            ///
+            /// The backing may be missing required fields, e.g. if it was never
+            /// written by this struct's constructor.
+            pub fn from_backing(inner: #backing_ty) -> Self {
+                Self { inner }
+            }
+
+            /// Returns a reference to the backing.
+            pub fn backing(&self) -> &#backing_ty {
+                &self.inner
+            }
+
+            /// Consumes this struct, returning the backing.
+            pub fn into_backing(self) -> #backing_ty {
+                self.inner
+            }
+
+            #(#accessors)*
+            #unknown_methods
+        }
+    }
+}
+
+/// Generate the async constructor, which writes the required fields to a backing.
+fn generate_async_constructor(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    backing_ty: &TokenStream,
+    error_ty: &TokenStream,
+) -> TokenStream {
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+    let constructor_name = config.constructor_name(struct_name);
+
+    let required: Vec<_> = fields
+        .iter()
+        .filter(|f| !f.is_optional && !f.is_unknown_field())
+        .collect();
+    let params = required.iter().map(|f| {
+        let name = &f.name;
+        let ty = &f.ty;
+        quote! { #name: #ty }
+    });
+    let inserts = required.iter().map(|f| {
+        let name = &f.name;
+        let variant = to_pascal_case(name);
+        quote! {
+            ::structible::AsyncBackingMap::insert(&mut inner, #field_enum::#variant, #value_enum::#variant(#name)).await?;
+        }
+    });
+
+    quote! {
+        /// Writes all required fields to the given backing and wraps it.
+        pub async fn #constructor_name(mut inner: #backing_ty #(, #params)*) -> ::std::result::Result<Self, #error_ty> {
+            #(#inserts)*
+            ::std::result::Result::Ok(Self { inner })
+        }
+    }
+}
+
+/// Generate the async getter, setter, and (for optional fields) remover of a field.
+fn generate_async_accessors(
+    struct_name: &Ident,
+    f: &FieldInfo,
+    error_ty: &TokenStream,
+) -> TokenStream {
+    let field_enum = field_enum_name(struct_name);
+    let value_enum = value_enum_name(struct_name);
+    let name = &f.name;
+    let variant = to_pascal_case(name);
+    let getter_name = f.getter_name();
+    let setter_name = f.setter_name();
+    let get_vis = f.getter_vis();
+    let set_vis = f.setter_vis();
+    let field_docs = extract_doc_comments(&f.attrs);
+    let method_attrs = f.method_attrs();
+    let value_ty = if f.is_optional { &f.inner_ty } else { &f.ty };
+
+    let name_str = name.unraw().to_string();
+    let get_doc = format_method_doc(
+        &format!("Fetches the `{}` value if present.", name_str),
+        &field_docs,
+    );
+    let set_doc = format_method_doc(&format!("Sets the `{}` value.", name_str), &field_docs);
+
+    let remover = f.is_optional.then(|| {
+        let remover_name = f.remover_name();
+        let remove_vis = f.remover_vis();
+        let remove_doc = format_method_doc(
+            &format!(
+                "Removes the `{}` field and returns the value if it was present.",
+                name_str
+            ),
+            &field_docs,
+        );
+        quote! {
+            #remove_doc
+            #(#method_attrs)*
+            #remove_vis async fn #remover_name(&mut self) -> ::std::result::Result<Option<#value_ty>, #error_ty> {
+                match ::structible::AsyncBackingMap::remove(&mut self.inner, &#field_enum::#variant).await? {
+                    Some(#value_enum::#variant(v)) => ::std::result::Result::Ok(Some(v)),
+                    _ => ::std::result::Result::Ok(None),
+                }
+            }
+        }
+    });
+
+    quote! {
+        #get_doc
+        #(#method_attrs)*
+        #get_vis async fn #getter_name(&self) -> ::std::result::Result<Option<#value_ty>, #error_ty> {
+            match ::structible::AsyncBackingMap::get(&self.inner, &#field_enum::#variant).await? {
+                Some(#value_enum::#variant(v)) => ::std::result::Result::Ok(Some(v)),
+                _ => ::std::result::Result::Ok(None),
+            }
+        }
+
+        #set_doc
+        #(#method_attrs)*
+        #set_vis async fn #setter_name(&mut self, value: #value_ty) -> ::std::result::Result<(), #error_ty> {
+            ::structible::AsyncBackingMap::insert(&mut self.inner, #field_enum::#variant, #value_enum::#variant(value)).await
+        }
+
+        #remover
+    }
+}
+
+/// Generate async lookup, insertion, and removal for the unknown fields catch-all.
+///
+/// Iteration is not available, since `AsyncBackingMap` cannot enumerate keys.
+fn generate_async_unknown_methods(
+    struct_name: &Ident,
+    f: &FieldInfo,
+    error_ty: &TokenStream,
+) -> TokenStream {
+    let field_enum = field_enum_name(struct_name);
+    let value_enum = value_enum_name(struct_name);
+    let name = &f.name;
+    let key_type = f.unknown_key_type().unwrap();
+    let value_type = &f.inner_ty;
+    let get_vis = f.getter_vis();
+    let set_vis = f.setter_vis();
+    let remove_vis = f.remover_vis();
+    let field_docs = extract_doc_comments(&f.attrs);
+    let method_attrs = f.method_attrs();
+
+    let insert_method = format_ident!("insert_{}", name);
+    let remove_method = format_ident!("remove_{}", name);
+    let owned_key = f.normalized_key(quote! { key.to_owned() });
+    let inserted_key = f.normalized_key(quote! { key });
+
+    let name_str = name.unraw().to_string();
+    let get_doc = format_method_doc(
+        &format!("Fetches the `{}` value for the given key.", name_str),
+        &field_docs,
+    );
+    let insert_doc = format_method_doc(
+        &format!(
+            "Inserts an unknown `{}` field with the given key and value.",
+            name_str
+        ),
+        &field_docs,
+    );
+    let remove_doc = format_method_doc(
+        &format!(
+            "Removes the `{}` field for the given key and returns the value if present.",
+            name_str
+        ),
+        &field_docs,
+    );
+
+    quote! {
+        #get_doc
+        #(#method_attrs)*
+        #get_vis async fn #name<__Q>(&self, key: &__Q) -> ::std::result::Result<Option<#value_type>, #error_ty>
+        where
+            #key_type: ::std::borrow::Borrow<__Q>,
+            __Q: ::std::borrow::ToOwned<Owned = #key_type> + ?Sized,
+        {
+            let key = #field_enum::Unknown(#owned_key);
+            match ::structible::AsyncBackingMap::get(&self.inner, &key).await? {
+                Some(#value_enum::Unknown(v)) => ::std::result::Result::Ok(Some(v)),
+                _ => ::std::result::Result::Ok(None),
+            }
+        }
+
+        #insert_doc
+        #(#method_attrs)*
+        #set_vis async fn #insert_method(&mut self, key: #key_type, value: #value_type) -> ::std::result::Result<(), #error_ty> {
+            ::structible::AsyncBackingMap::insert(&mut self.inner, #field_enum::Unknown(#inserted_key), #value_enum::Unknown(value)).await
+        }
+
+        #remove_doc
+        #(#method_attrs)*
+        #remove_vis async fn #remove_method<__Q>(&mut self, key: &__Q) -> ::std::result::Result<Option<#value_type>, #error_ty>
+        where
+            #key_type: ::std::borrow::Borrow<__Q>,
+            __Q: ::std::borrow::ToOwned<Owned = #key_type> + ?Sized,
+        {
+            let key = #field_enum::Unknown(#owned_key);
+            match ::structible::AsyncBackingMap::remove(&mut self.inner, &key).await? {
+                Some(#value_enum::Unknown(v)) => ::std::result::Result::Ok(Some(v)),
+                _ => ::std::result::Result::Ok(None),
+            }
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{Generics, Ident};
+
+use crate::codegen::{field_enum_type, value_enum_name};
+use crate::parse::{FieldInfo, StructibleConfig};
+use crate::util::to_pascal_case;
+
+/// Checks that a struct with `bench` can be cloned for each measured write.
+pub fn check_bench_config(
+    struct_name: &Ident,
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> syn::Result<()> {
+    if !config.bench {
+        return Ok(());
+    }
+    if !generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            generics,
+            "`bench` is not supported on generic structs",
+        ));
+    }
+    let conflict = [
+        (config.is_async, "`async`"),
+        (config.no_clone, "`no_clone`"),
+    ];
+    if let Some((_, name)) = conflict.iter().find(|(conflicts, _)| *conflicts) {
+        return Err(syn::Error::new_spanned(
+            struct_name,
+            format!("`bench` cannot be combined with {}", name),
+        ));
+    }
+    Ok(())
+}
+
+/// Generate `bench_accessors()`, if `bench` is set.
+///
+/// Every getter, mutable getter, setter, and remover of a known field is measured
+/// on `value`, in a Criterion group named after the struct, with ids of the form
+/// `<field>/<accessor>`. Writes are measured on clones of `value`, made and dropped
+/// outside the measurement, and setters write the value stored in `value`, so
+/// fields absent from it (or left out of clones) get no setter benchmark. Lazy
+/// fields, whose getters need a context, and the catch-all are not measured.
+pub fn generate_bench_impl(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+) -> TokenStream {
+    if !config.bench {
+        return quote! {};
+    }
+    let criterion = quote! { ::structible::__private::criterion };
+    let batch = quote! { #criterion::BatchSize::SmallInput };
+    let group_name = struct_name.unraw().to_string();
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+
+    let benches = fields
+        .iter()
+        .filter(|f| !f.is_unknown_field() && !f.is_lazy())
+        .map(|f| {
+            let name_str = f.name.unraw().to_string();
+            let getter = f.getter_name();
+            let get_id = format!("{}/get", name_str);
+            let get = quote! {
+                group.bench_function(#get_id, |b| {
+                    b.iter(|| ::std::hint::black_box(value).#getter())
+                });
+            };
+
+            let get_mut = if config.has_mut_getters() {
+                let getter_mut = f.getter_mut_name();
+                let id = format!("{}/get_mut", name_str);
+                quote! {
+                    group.bench_function(#id, |b| {
+                        b.iter_batched_ref(
+                            || ::std::clone::Clone::clone(value),
+                            |v| {
+                                ::std::hint::black_box(v.#getter_mut());
+                            },
+                            #batch,
+                        )
+                    });
+                }
+            } else {
+                quote! {}
+            };
+
+            // The value to write is read from the backing, since getters may
+            // return another type than the setter takes
+            let set = if !config.no_setters && !f.config.skip_clone {
+                let setter = f.setter_name();
+                let id = format!("{}/set", name_str);
+                let variant = to_pascal_case(&f.name);
+                quote! {
+                    if let ::std::option::Option::Some(#value_enum::#variant(stored)) =
+                        ::structible::BackingMap::get(&value.inner, &#field_enum::#variant)
+                    {
+                        group.bench_function(#id, |b| {
+                            b.iter_batched(
+                                || {
+                                    (
+                                        ::std::clone::Clone::clone(value),
+                                        ::std::clone::Clone::clone(stored),
+                                    )
+                                },
+                                |(mut v, stored)| {
+                                    v.#setter(::std::hint::black_box(stored));
+                                    v
+                                },
+                                #batch,
+                            )
+                        });
+                    }
+                }
+            } else {
+                quote! {}
+            };
+
+            let remove = if f.is_optional && !config.no_removers {
+                let remover = f.remover_name();
+                let id = format!("{}/remove", name_str);
+                quote! {
+                    group.bench_function(#id, |b| {
+                        b.iter_batched(
+                            || ::std::clone::Clone::clone(value),
+                            |mut v| {
+                                ::std::hint::black_box(v.#remover());
+                                v
+                            },
+                            #batch,
+                        )
+                    });
+                }
+            } else {
+                quote! {}
+            };
+
+            quote! {
+                #get
+                #get_mut
+                #set
+                #remove
+            }
+        });
+
+    quote! {
+        impl #struct_name {
+            /// Benchmarks the accessors of each field on `value` with Criterion,
+            /// in a group named after the struct.
+            ///
+            /// Writes are measured on clones of `value`, and setters write the
+            /// value stored in it, so fields absent from `value` are only read
+            /// and removed. Compare backings by declaring the same fields on
+            /// structs with different backings and benchmarking each.
+            pub fn bench_accessors(c: &mut #criterion::Criterion, value: &Self) {
+                let mut group = c.benchmark_group(#group_name);
+                #(#benches)*
+                group.finish();
+            }
+        }
+    }
+}
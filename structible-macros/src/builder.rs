@@ -0,0 +1,344 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::{GenericParam, Generics, Ident, Visibility};
+
+use crate::instrument::trace_bounds;
+use crate::parse::{BuilderMode, FieldInfo, StructibleConfig};
+use crate::promote::promote_bounds;
+use crate::util::type_mentions_type_param;
+
+/// Returns the name of the builder type of a struct.
+pub fn builder_name(struct_name: &Ident) -> Ident {
+    format_ident!("{}Builder", struct_name)
+}
+
+/// Checks that a struct with `builder` can build itself through its setters.
+pub fn check_builder_config(struct_name: &Ident, config: &StructibleConfig) -> syn::Result<()> {
+    if config.builder.is_some() && config.no_setters {
+        return Err(syn::Error::new_spanned(
+            struct_name,
+            "`builder` cannot be combined with `no_setters`",
+        ));
+    }
+    Ok(())
+}
+
+/// Generate the builder type and `<Struct>::builder()`, if `builder` is set.
+///
+/// The builder holds the values of the fields until `build()`, which passes them
+/// to the constructor and the setters, so that built structs are normalized and
+/// recorded like any other. A required field left unset makes `build()` return
+/// `ValidationError::MissingRequired`, or panic with `builder = panic`.
+///
+/// With `builder = typestate`, the builder instead takes a `const bool` parameter per
+/// required field, defaulting to `false`, which its method sets to `true`; `build()`
+/// is only implemented once they all are, so missing fields fail to compile.
+pub fn generate_builder(
+    struct_name: &Ident,
+    vis: &Visibility,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    let Some(mode) = config.builder else {
+        return quote! {};
+    };
+    let builder = builder_name(struct_name);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let struct_doc = match mode {
+        BuilderMode::Typestate => format!(
+            "A builder of [`{}`], taking its fields in any order, whose type tracks which required fields are set.\n\n\
+             Each required field has a `const bool` parameter, after the struct's own \
+             generics, which is `false` until the field's method is called, and `build()` \
+             only exists once they are all `true`.",
+            struct_name
+        ),
+        _ => format!(
+            "A builder of [`{}`], taking its fields in any order.",
+            struct_name
+        ),
+    };
+    let struct_doc = format!(
+        "{}\n\n\
+         `build()` goes through the constructor and the setters, so the writes are \
+         recorded and normalized like any other, but doesn't run the struct's validation.",
+        struct_doc
+    );
+
+    let known_fields: Vec<_> = fields.iter().filter(|f| !f.is_unknown_field()).collect();
+    let catch_all = fields.iter().find(|f| f.is_unknown_field());
+
+    let slots = known_fields.iter().map(|f| {
+        let name = &f.name;
+        let value_ty = if f.is_optional { &f.inner_ty } else { &f.ty };
+        quote! { #name: ::std::option::Option<#value_ty>, }
+    });
+    let catch_all_slot = catch_all.map(|f| {
+        let name = &f.name;
+        let key_type = f.unknown_key_type().unwrap();
+        let value_type = &f.inner_ty;
+        quote! { #name: ::std::vec::Vec<(#key_type, #value_type)>, }
+    });
+    let empty_slots = fields.iter().map(|f| {
+        let name = &f.name;
+        if f.is_unknown_field() {
+            quote! { #name: ::std::vec::Vec::new(), }
+        } else {
+            quote! { #name: ::std::option::Option::None, }
+        }
+    });
+
+    // Required fields are passed to the constructor, in declaration order
+    let required: Vec<_> = known_fields.iter().filter(|f| !f.is_optional).collect();
+
+    // The typestate parameters trail the struct's own, since they have defaults
+    let states: Vec<_> = match mode {
+        BuilderMode::Typestate => required
+            .iter()
+            .map(|f| format_ident!("__{}", f.name.unraw().to_string().to_uppercase()))
+            .collect(),
+        _ => Vec::new(),
+    };
+    let mut builder_generics = generics.clone();
+    for state in &states {
+        builder_generics
+            .params
+            .push(syn::parse_quote!(const #state: bool = false));
+    }
+    let (open_impl_generics, open_ty_generics, _) = builder_generics.split_for_impl();
+    let args: Vec<_> = generics
+        .params
+        .iter()
+        .map(|param| match param {
+            GenericParam::Lifetime(lt) => {
+                let lifetime = &lt.lifetime;
+                quote! { #lifetime }
+            }
+            GenericParam::Type(tp) => {
+                let ident = &tp.ident;
+                quote! { #ident }
+            }
+            GenericParam::Const(cp) => {
+                let ident = &cp.ident;
+                quote! { #ident }
+            }
+        })
+        .collect();
+    let builder_ty = |values: &[TokenStream]| {
+        if args.is_empty() && values.is_empty() {
+            quote! { #builder }
+        } else {
+            quote! { #builder<#(#args,)* #(#values),*> }
+        }
+    };
+    let start_ty = builder_ty(&vec![quote! { false }; states.len()]);
+    let done_ty = builder_ty(&vec![quote! { true }; states.len()]);
+    let slot_names: Vec<_> = fields.iter().map(|f| &f.name).collect();
+
+    let methods = known_fields.iter().map(|f| {
+        let name = &f.name;
+        let vis = f.setter_vis();
+        let value_ty = if f.is_optional { &f.inner_ty } else { &f.ty };
+        let method_attrs = f.method_attrs();
+        let doc = format!("Sets the `{}` value.", name.unraw());
+        // Setting a required field of a typestate builder changes its type
+        let state = match mode {
+            BuilderMode::Typestate => required.iter().position(|r| r.name == f.name),
+            _ => None,
+        };
+        match state {
+            Some(i) => {
+                let values: Vec<_> = states
+                    .iter()
+                    .enumerate()
+                    .map(|(j, state)| {
+                        if i == j {
+                            quote! { true }
+                        } else {
+                            quote! { #state }
+                        }
+                    })
+                    .collect();
+                let next_ty = builder_ty(&values);
+                let others = slot_names.iter().filter(|other| **other != name);
+                quote! {
+                    #[doc = #doc]
+                    #(#method_attrs)*
+                    #vis fn #name(self, value: #value_ty) -> #next_ty {
+                        #builder {
+                            #name: ::std::option::Option::Some(value),
+                            #(#others: self.#others,)*
+                        }
+                    }
+                }
+            }
+            None => quote! {
+                #[doc = #doc]
+                #(#method_attrs)*
+                #vis fn #name(mut self, value: #value_ty) -> Self {
+                    self.#name = ::std::option::Option::Some(value);
+                    self
+                }
+            },
+        }
+    });
+    let catch_all_method = catch_all.map(|f| {
+        let name = &f.name;
+        let key_type = f.unknown_key_type().unwrap();
+        let value_type = &f.inner_ty;
+        let insert_method = format_ident!("insert_{}", name);
+        let vis = f.setter_vis();
+        let method_attrs = f.method_attrs();
+        let doc = format!("Inserts an unknown `{}` field.", name.unraw());
+        quote! {
+            #[doc = #doc]
+            #(#method_attrs)*
+            #vis fn #insert_method(mut self, key: #key_type, value: #value_type) -> Self {
+                self.#name.push((key, value));
+                self
+            }
+        }
+    });
+
+    let required_values = required.iter().map(|f| {
+        let name = &f.name;
+        let name_str = name.unraw().to_string();
+        let missing = match mode {
+            BuilderMode::Fallible => quote! {
+                return ::std::result::Result::Err(
+                    ::structible::ValidationError::MissingRequired { field: #name_str },
+                )
+            },
+            BuilderMode::Panicking => {
+                let message = format!("missing required field `{}`", name_str);
+                quote! { ::std::panic!(#message) }
+            }
+            BuilderMode::Typestate => {
+                let message = format!("`{}` is set in this builder state", name_str);
+                quote! { ::std::unreachable!(#message) }
+            }
+        };
+        quote! {
+            match self.#name {
+                ::std::option::Option::Some(value) => value,
+                ::std::option::Option::None => #missing,
+            }
+        }
+    });
+    let constructor_name = config.constructor_name(struct_name);
+    let set_optional = known_fields.iter().filter(|f| f.is_optional).map(|f| {
+        let name = &f.name;
+        let setter_name = f.setter_name();
+        quote! {
+            if let ::std::option::Option::Some(value) = self.#name {
+                this.#setter_name(value);
+            }
+        }
+    });
+    let insert_catch_all = catch_all.map(|f| {
+        let name = &f.name;
+        let insert_method = format_ident!("insert_{}", name);
+        quote! {
+            for (key, value) in self.#name {
+                this.#insert_method(key, value);
+            }
+        }
+    });
+    let build_body = quote! {
+        #[allow(unused_mut)]
+        let mut this = #struct_name::#constructor_name(#(#required_values),*);
+        #(#set_optional)*
+        #insert_catch_all
+    };
+    let build = match mode {
+        BuilderMode::Fallible => quote! {
+            /// Builds the struct, failing if a required field was not set.
+            pub fn build(
+                self,
+            ) -> ::std::result::Result<#struct_name #ty_generics, ::structible::ValidationError> {
+                #build_body
+                ::std::result::Result::Ok(this)
+            }
+        },
+        BuilderMode::Panicking => quote! {
+            /// Builds the struct.
+            ///
+            /// # Panics
+            ///
+            /// Panics if a required field was not set.
+            pub fn build(self) -> #struct_name #ty_generics {
+                #build_body
+                this
+            }
+        },
+        BuilderMode::Typestate => quote! {
+            /// Builds the struct, once all required fields are set.
+            pub fn build(self) -> #struct_name #ty_generics {
+                #build_body
+                this
+            }
+        },
+    };
+
+    // Building goes through the constructor and the setters, which may need
+    // further bounds
+    let clone_bounds = if config.records_writes() {
+        let type_param_idents: Vec<_> = generics.type_params().map(|tp| &tp.ident).collect();
+        let inner_types = fields
+            .iter()
+            .map(|f| &f.inner_ty)
+            .filter(|ty| type_mentions_type_param(ty, &type_param_idents));
+        quote! { #(#inner_types: ::std::clone::Clone,)* }
+    } else {
+        quote! {}
+    };
+    let promote_bounds = promote_bounds(fields, generics);
+    let trace_bounds = trace_bounds(fields, config, generics);
+    let bounds = quote! { #clone_bounds #promote_bounds #trace_bounds };
+    let build_where = match where_clause {
+        Some(wc) => {
+            let existing = &wc.predicates;
+            quote! { where #bounds #existing }
+        }
+        None if !bounds.is_empty() => quote! { where #bounds },
+        None => quote! {},
+    };
+
+    quote! {
+        #[doc = #struct_doc]
+        #vis struct #builder #builder_generics #where_clause {
+            #(#slots)*
+            #catch_all_slot
+        }
+
+        impl #impl_generics #start_ty #where_clause {
+            /// Creates a builder with no fields set.
+            pub fn new() -> Self {
+                Self { #(#empty_slots)* }
+            }
+        }
+
+        impl #open_impl_generics #builder #open_ty_generics #where_clause {
+            #(#methods)*
+            #catch_all_method
+        }
+
+        impl #impl_generics #done_ty #build_where {
+            #build
+        }
+
+        impl #impl_generics ::std::default::Default for #start_ty #where_clause {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            /// Returns a builder with no fields set.
+            pub fn builder() -> #start_ty {
+                #builder::new()
+            }
+        }
+    }
+}
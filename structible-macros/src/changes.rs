@@ -0,0 +1,306 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{Generics, Ident};
+
+use crate::codegen::{entry_patterns, field_enum_type, value_enum_name};
+use crate::instrument::{trace_stmt, write_counter};
+use crate::merge::stamp_stmt;
+use crate::parse::{FieldInfo, StructibleConfig};
+use crate::provenance::clear_source_stmt;
+use crate::timestamps::touch_stmt;
+use crate::tombstone::clear_tombstone_stmt;
+use crate::transaction::undo_stmt;
+use crate::ttl::clear_expiry_stmt;
+use crate::util::to_pascal_case;
+use crate::validate::has_validation;
+
+/// Checks that the struct can record its changes.
+pub fn check_change_log_config(struct_name: &Ident, config: &StructibleConfig) -> syn::Result<()> {
+    if config.track_changes && config.no_clone {
+        return Err(syn::Error::new_spanned(
+            struct_name,
+            "`track_changes` records cloned values and cannot be combined with `no_clone`",
+        ));
+    }
+    Ok(())
+}
+
+/// Returns the type of the change log of a struct with `track_changes`.
+fn change_log_type(
+    struct_name: &Ident,
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+    let (_, ty_generics, _) = generics.split_for_impl();
+    quote! {
+        ::std::vec::Vec<::structible::FieldChange<#field_enum, #value_enum #ty_generics>>
+    }
+}
+
+/// Returns the change log field declaration, if `track_changes` is set.
+pub fn change_log_field(
+    struct_name: &Ident,
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    if !config.track_changes {
+        return quote! {};
+    }
+    let log_ty = change_log_type(struct_name, config, generics);
+    quote! { changes: #log_ty, }
+}
+
+/// Returns the initializer of an empty change log, if `track_changes` is set.
+pub fn change_log_init(config: &StructibleConfig) -> TokenStream {
+    if !config.track_changes {
+        return quote! {};
+    }
+    quote! { changes: ::std::vec::Vec::new(), }
+}
+
+/// Returns an expression inserting `key` and `value` into the map of `recv`.
+///
+/// With `track_changes`, the insertion is also appended to the change log of
+/// `recv`, with `lww` it is stamped, with `ttl`, `tombstones`, and `provenance` it
+/// clears the field's expiry, tombstone, and source, with `timestamps` its time is
+/// recorded, with `instrument` it is counted, and with `trace` it is traced. With `transactional`, the previous
+/// entry is saved to the undo log of a running transaction.
+/// The expression evaluates to the previous value, like `BackingMap::insert`.
+pub fn insert_expr(
+    config: &StructibleConfig,
+    recv: TokenStream,
+    key: TokenStream,
+    value: TokenStream,
+) -> TokenStream {
+    if !config.has_write_hooks() {
+        return quote! {
+            ::structible::BackingMap::insert(&mut #recv.inner, #key, #value)
+        };
+    }
+    let record = config.track_changes.then(|| {
+        quote! {
+            #recv.changes.push(::structible::FieldChange::Set(
+                ::std::clone::Clone::clone(&key),
+                ::std::clone::Clone::clone(&value),
+            ));
+        }
+    });
+    let stamp = stamp_stmt(config, &recv, quote! { ::std::clone::Clone::clone(&key) });
+    let clear_expiry = clear_expiry_stmt(config, &recv);
+    let clear_tombstone = clear_tombstone_stmt(config, &recv);
+    let clear_source = clear_source_stmt(config, &recv);
+    let touch = touch_stmt(config, &recv);
+    let count = write_counter(config);
+    let trace = trace_stmt(
+        config,
+        "set",
+        quote! { ::std::option::Option::Some(&value) },
+    );
+    let undo = undo_stmt(config, &recv);
+    quote! {
+        {
+            let key = #key;
+            let value = #value;
+            #count
+            #trace
+            #record
+            #stamp
+            #clear_expiry
+            #clear_tombstone
+            #clear_source
+            #touch
+            #undo
+            ::structible::BackingMap::insert(&mut #recv.inner, key, value)
+        }
+    }
+}
+
+/// Returns an expression removing the owned `key` from `self.inner`.
+///
+/// With `track_changes`, a removal that found a value is appended to the change
+/// log, with `lww` it is stamped, with `timestamps` its time is recorded, with
+/// `instrument` it is counted, and with `trace` it is traced; with `ttl` and `provenance` any expiry and source of the key are
+/// cleared, and with `transactional` the previous entry is saved to the undo log of
+/// a running transaction.
+/// The expression evaluates to the removed value, like `BackingMap::remove`.
+pub fn remove_expr(config: &StructibleConfig, key: TokenStream) -> TokenStream {
+    if !config.has_write_hooks() {
+        return quote! {
+            ::structible::BackingMap::remove(&mut self.inner, &#key)
+        };
+    }
+    let record = config.track_changes.then(|| {
+        quote! {
+            self.changes.push(::structible::FieldChange::Remove(::std::clone::Clone::clone(&key)));
+        }
+    });
+    let stamp = stamp_stmt(config, &quote! { self }, quote! { key });
+    let clear_expiry = clear_expiry_stmt(config, &quote! { self });
+    let clear_source = clear_source_stmt(config, &quote! { self });
+    let touch = touch_stmt(config, &quote! { self });
+    let count = write_counter(config);
+    let trace = trace_stmt(config, "remove", quote! { removed.as_ref() });
+    let undo = undo_stmt(config, &quote! { self });
+    quote! {
+        {
+            let key = #key;
+            #clear_expiry
+            #clear_source
+            #undo
+            let removed = ::structible::BackingMap::remove(&mut self.inner, &key);
+            if removed.is_some() {
+                #count
+                #trace
+                #record
+                #stamp
+                #touch
+            }
+            removed
+        }
+    }
+}
+
+/// Generate the change log accessors and replay methods, if `track_changes` is set.
+pub fn generate_change_log_methods(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    if !config.track_changes {
+        return quote! {};
+    }
+    let log_ty = change_log_type(struct_name, config, generics);
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+    let (_, ty_generics, _) = generics.split_for_impl();
+    let apply_methods = generate_apply_changes(struct_name, fields, config, generics);
+
+    quote! {
+        #apply_methods
+
+        /// Returns the changes recorded since the change log was last taken.
+        pub fn change_log(&self) -> &[::structible::FieldChange<#field_enum, #value_enum #ty_generics>] {
+            &self.changes
+        }
+
+        /// Takes the changes recorded since the change log was last taken, in order.
+        pub fn take_change_log(&mut self) -> #log_ty {
+            ::std::mem::take(&mut self.changes)
+        }
+    }
+}
+
+/// Generate `apply_changes` and `try_apply_changes`, which replay a change log.
+///
+/// Both apply changes through the same path as the setters and removers, so the
+/// replayed changes are recorded in this struct's own change log.
+fn generate_apply_changes(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+    let (_, ty_generics, _) = generics.split_for_impl();
+    let change_ty = quote! { ::structible::FieldChange<#field_enum, #value_enum #ty_generics> };
+    let error_ty = config.error_type();
+
+    let name_arms = fields.iter().map(|f| {
+        let name_str = f.name.unraw().to_string();
+        if f.is_unknown_field() {
+            quote! { #field_enum::Unknown(_) => #name_str }
+        } else {
+            let variant = to_pascal_case(&f.name);
+            quote! { #field_enum::#variant => #name_str }
+        }
+    });
+    let consistent_arms = entry_patterns(struct_name, fields);
+
+    let insert = insert_expr(config, quote! { self }, quote! { key }, quote! { value });
+    let remove = remove_expr(config, quote! { key });
+
+    let required_checks = fields
+        .iter()
+        .filter(|f| !f.is_optional && !f.is_unknown_field())
+        .map(|f| {
+            let variant = to_pascal_case(&f.name);
+            let name_str = f.name.unraw().to_string();
+            quote! {
+                if ::structible::BackingMap::get(&next.inner, &#field_enum::#variant).is_none() {
+                    return ::std::result::Result::Err(::std::convert::From::from(
+                        ::structible::ValidationError::MissingRequired { field: #name_str },
+                    ));
+                }
+            }
+        });
+    let validate_call = has_validation(fields, config).then(|| quote! { next.validate()?; });
+
+    quote! {
+        #[doc(hidden)]
+        #[allow(unreachable_patterns)]
+        fn __structible_apply_change(
+            &mut self,
+            change: #change_ty,
+        ) -> ::std::result::Result<(), ::structible::ValidationError> {
+            match change {
+                ::structible::FieldChange::Set(key, value) => {
+                    if !::std::matches!((&key, &value), #(#consistent_arms)|*) {
+                        let field = match &key {
+                            #(#name_arms,)*
+                        };
+                        return ::std::result::Result::Err(
+                            ::structible::ValidationError::MismatchedChange { field },
+                        );
+                    }
+                    #insert;
+                }
+                ::structible::FieldChange::Remove(key) => {
+                    #remove;
+                }
+            }
+            ::std::result::Result::Ok(())
+        }
+
+        /// Replays the given changes in order.
+        ///
+        /// Removing a required field leaves the struct without it, so its getter will
+        /// panic; use `try_apply_changes` to reject such change logs.
+        ///
+        /// # Panics
+        ///
+        /// Panics if a change sets a field to another field's value.
+        pub fn apply_changes<I>(&mut self, changes: I)
+        where
+            I: ::std::iter::IntoIterator<Item = #change_ty>,
+        {
+            for change in changes {
+                if let ::std::result::Result::Err(err) = self.__structible_apply_change(change) {
+                    ::std::panic!("{}", err);
+                }
+            }
+        }
+
+        /// Replays the given changes in order, then checks that all required fields
+        /// are present and that the struct passes its declared validation.
+        ///
+        /// On error the struct is left unchanged.
+        pub fn try_apply_changes<I>(&mut self, changes: I) -> ::std::result::Result<(), #error_ty>
+        where
+            I: ::std::iter::IntoIterator<Item = #change_ty>,
+        {
+            let mut next = ::std::clone::Clone::clone(self);
+            for change in changes {
+                next.__structible_apply_change(change)?;
+            }
+            #(#required_checks)*
+            #validate_call
+            *self = next;
+            ::std::result::Result::Ok(())
+        }
+    }
+}
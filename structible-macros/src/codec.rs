@@ -0,0 +1,159 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{Generics, Ident};
+
+use crate::codegen::{field_enum_type, value_enum_name};
+use crate::parse::{FieldInfo, StructibleConfig};
+use crate::util::{to_pascal_case, type_mentions_type_param};
+
+/// Checks that a struct with `value_codec` also implements `Structible`.
+pub fn check_codec_config(struct_name: &Ident, config: &StructibleConfig) -> syn::Result<()> {
+    if config.value_codec && (config.no_structible || config.is_async) {
+        let other = if config.is_async {
+            "async"
+        } else {
+            "no_structible"
+        };
+        return Err(syn::Error::new_spanned(
+            struct_name,
+            format!(
+                "`value_codec` requires the `Structible` impl and cannot be combined with `{}`",
+                other
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Generate the `ValueCodec` impl, if `value_codec` is set.
+///
+/// Fields are named as in the struct definition, and catch-all entries as
+/// `<catch-all>.<key>`. Values go through the runtime's JSON helpers.
+pub fn generate_value_codec_impl(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    if !config.value_codec {
+        return quote! {};
+    }
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let codec = quote! { ::structible::codec };
+    let error = quote! { ::structible::codec::CodecError };
+
+    // Generic field and catch-all key types must be encodable
+    let type_param_idents: Vec<_> = generics.type_params().map(|tp| &tp.ident).collect();
+    let serde_types: Vec<_> = fields
+        .iter()
+        .map(|f| &f.inner_ty)
+        .chain(fields.iter().filter_map(|f| f.unknown_key_type()))
+        .filter(|ty| type_mentions_type_param(ty, &type_param_idents))
+        .collect();
+    let serde_bounds = quote! {
+        #(#serde_types: ::structible::__private::serde::Serialize
+            + ::structible::__private::serde::de::DeserializeOwned,)*
+    };
+    let where_clause = if let Some(wc) = where_clause {
+        let existing = &wc.predicates;
+        quote! { where #serde_bounds #existing }
+    } else if !serde_types.is_empty() {
+        quote! { where #serde_bounds }
+    } else {
+        quote! {}
+    };
+
+    let mut name_arms = Vec::new();
+    let mut parse_arms = Vec::new();
+    let mut encode_arms = Vec::new();
+    let mut decode_arms = Vec::new();
+    let mut parse_unknown = quote! {};
+    for f in fields {
+        let name_str = f.name.unraw().to_string();
+        if f.is_unknown_field() {
+            let prefix = format!("{}.", name_str);
+            name_arms.push(quote! {
+                #field_enum::Unknown(key) => #codec::__unknown_field_name(#name_str, key)
+            });
+            parse_unknown = quote! {
+                if let ::std::option::Option::Some(key) = name.strip_prefix(#prefix) {
+                    return ::std::result::Result::Ok(#field_enum::Unknown(
+                        #codec::__parse_unknown_key(key)?,
+                    ));
+                }
+            };
+            encode_arms.push(quote! {
+                #value_enum::Unknown(value) => #codec::__encode(value)
+            });
+            decode_arms.push(quote! {
+                #field_enum::Unknown(_) => #value_enum::Unknown(#codec::__decode(bytes)?)
+            });
+        } else {
+            let variant = to_pascal_case(&f.name);
+            name_arms.push(quote! {
+                #field_enum::#variant => ::std::result::Result::Ok(::std::string::String::from(#name_str))
+            });
+            let aliases = f.alias_names();
+            parse_arms.push(quote! {
+                #name_str #(| #aliases)* => ::std::result::Result::Ok(#field_enum::#variant)
+            });
+            encode_arms.push(quote! {
+                #value_enum::#variant(value) => #codec::__encode(value)
+            });
+            let decode = match &f.config.upgrade_with {
+                Some(with) => quote! { #codec::__decode_upgrade(bytes, #with)? },
+                None => quote! { #codec::__decode(bytes)? },
+            };
+            decode_arms.push(quote! {
+                #field_enum::#variant => #value_enum::#variant(#decode)
+            });
+        }
+    }
+    if config.generic_key.is_some() {
+        encode_arms.push(quote! { #value_enum::__Key(never, _) => match *never {} });
+    }
+
+    quote! {
+        impl #impl_generics ::structible::ValueCodec for #struct_name #ty_generics #where_clause {
+            fn field_name(
+                key: &Self::Field,
+            ) -> ::std::result::Result<::std::string::String, #error> {
+                match key {
+                    #(#name_arms,)*
+                }
+            }
+
+            fn parse_field_name(name: &str) -> ::std::result::Result<Self::Field, #error> {
+                match name {
+                    #(#parse_arms,)*
+                    _ => {
+                        #parse_unknown
+                        ::std::result::Result::Err(#error::UnknownField(
+                            ::std::string::ToString::to_string(name),
+                        ))
+                    }
+                }
+            }
+
+            fn encode_value(
+                value: &Self::Value,
+            ) -> ::std::result::Result<::std::vec::Vec<u8>, #error> {
+                match value {
+                    #(#encode_arms,)*
+                }
+            }
+
+            fn decode_value(
+                key: &Self::Field,
+                bytes: &[u8],
+            ) -> ::std::result::Result<Self::Value, #error> {
+                ::std::result::Result::Ok(match key {
+                    #(#decode_arms,)*
+                })
+            }
+        }
+    }
+}
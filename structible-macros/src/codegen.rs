@@ -1,9 +1,42 @@
 use proc_macro2::TokenStream;
-use quote::{format_ident, quote};
-use syn::{Attribute, Generics, Ident, Visibility};
-
-use crate::parse::{FieldInfo, StructibleConfig};
-use crate::util::{extract_doc_comments, format_method_doc, to_pascal_case, type_mentions_type_param};
+use quote::{ToTokens, format_ident, quote};
+use syn::ext::IdentExt;
+use syn::punctuated::Punctuated;
+use syn::{Attribute, Generics, Ident, Meta, Path, Token, Type, Visibility};
+
+use crate::changes::{
+    change_log_field, change_log_init, generate_change_log_methods, insert_expr, remove_expr,
+};
+use crate::collection::generate_collection_methods;
+use crate::frozen::generate_freeze_method;
+use crate::instrument::{read_counter, trace_bounds};
+use crate::lazy::generate_lazy_getters;
+use crate::mapper::generate_map_value_method;
+use crate::merge::{generate_merge_methods, stamp_clone, stamp_fields, stamp_init};
+use crate::migrate::{generate_upgrade_fns, upgrade_from_serde_attr};
+use crate::numeric::generate_numeric_methods;
+use crate::parallel::generate_par_visit_method;
+use crate::parse::{BuilderMode, FieldInfo, StructibleConfig};
+use crate::promote::{generate_promotion_methods, route_entry_method, with_promote_bounds};
+use crate::provenance::{generate_provenance_methods, source_clone, source_field, source_init};
+use crate::snapshot::{generate_field_paths, generate_fields_in_order};
+use crate::string::generate_string_methods;
+use crate::timestamps::{
+    generate_timestamp_methods, timestamp_clone, timestamp_field, timestamp_init,
+};
+use crate::tombstone::{
+    bury_stmt, generate_tombstone_methods, tombstone_clone, tombstone_field, tombstone_init,
+};
+use crate::transaction::{generate_transaction_methods, undo_log_field, undo_log_init};
+use crate::ttl::{
+    expired_guard, expiry_clone, expiry_field, expiry_init, generate_ttl_methods, ttl_eq_body,
+};
+use crate::util::{
+    doc_summary, extract_doc_comments, format_method_doc, to_pascal_case, type_mentions_type_param,
+    type_text,
+};
+use crate::validate::{generate_validation_methods, unchecked_requires_note};
+use crate::variants::generate_variant_predicates;
 
 /// Returns the hidden field enum name for a struct.
 pub fn field_enum_name(struct_name: &Ident) -> Ident {
@@ -15,27 +48,80 @@ pub fn value_enum_name(struct_name: &Ident) -> Ident {
     format_ident!("__StructibleValue_{}", struct_name)
 }
 
+/// Returns the field enum as named in generated code.
+///
+/// A field enum whose key type mentions the struct's generics has no default key
+/// type, so it is named with the key type, in turbofish form so that the name can
+/// start expressions and patterns as well as name the type.
+pub fn field_enum_type(struct_name: &Ident, config: &StructibleConfig) -> TokenStream {
+    let enum_name = field_enum_name(struct_name);
+    match &config.generic_key {
+        Some(key_type) => quote! { #enum_name::<#key_type> },
+        None => quote! { #enum_name },
+    }
+}
+
 /// Returns the companion fields struct name for ownership extraction.
 pub fn fields_struct_name(struct_name: &Ident) -> Ident {
     format_ident!("{}Fields", struct_name)
 }
 
+/// Returns `#[non_exhaustive]` if the generated enums should carry it.
+///
+/// This lets fields be added without breaking downstream matches on keys or
+/// values, which then need a wildcard arm.
+fn non_exhaustive_attr(config: &StructibleConfig) -> TokenStream {
+    if config.non_exhaustive {
+        quote! { #[non_exhaustive] }
+    } else {
+        quote! {}
+    }
+}
+
 /// Generate the field enum (used as map keys).
-pub fn generate_field_enum(struct_name: &Ident, fields: &[FieldInfo]) -> TokenStream {
+pub fn generate_field_enum(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+) -> TokenStream {
     let enum_name = field_enum_name(struct_name);
+    let serde_derive = serde_backing_derive(config);
+    let non_exhaustive = non_exhaustive_attr(config);
 
     // Find unknown field if present
     let unknown_field = fields.iter().find(|f| f.is_unknown_field());
 
+    // Fields are named as declared, like the serde renames of `serde_backing`
+    let name_arms: Vec<_> = fields
+        .iter()
+        .map(|f| {
+            let name_str = f.name.unraw().to_string();
+            if f.is_unknown_field() {
+                quote! { Self::Unknown(_) => #name_str }
+            } else {
+                let variant = to_pascal_case(&f.name);
+                quote! { Self::#variant => #name_str }
+            }
+        })
+        .collect();
+    let map_value = generate_map_value_method(config);
+    let as_str_doc = if unknown_field.is_some() {
+        "Returns the name of the field as declared, without `r#`, or the name of the catch-all for unknown fields."
+    } else {
+        "Returns the name of the field as declared, without `r#`."
+    };
+
     // Generate variants for known fields only
     let known_variants: Vec<_> = fields
         .iter()
         .filter(|f| !f.is_unknown_field())
         .map(|f| {
             let variant = to_pascal_case(&f.name);
-            let attrs = &f.attrs;
+            let attrs = f.variant_attrs();
+            let rename = serde_backing_rename(config, f);
             quote! {
                 #(#attrs)*
+                #rename
                 #variant
             }
         })
@@ -44,14 +130,46 @@ pub fn generate_field_enum(struct_name: &Ident, fields: &[FieldInfo]) -> TokenSt
     if let Some(uf) = unknown_field {
         // Generate generic enum with Unknown variant
         let key_type = uf.unknown_key_type().unwrap();
+        let rename = serde_backing_rename(config, uf);
+        // A key type mentioning the struct's generics can't be a default, since
+        // the field enum doesn't take those generics
+        let key_param = if config.generic_key.is_some() {
+            quote! { __K }
+        } else {
+            quote! { __K = #key_type }
+        };
         quote! {
             #[doc(hidden)]
             #[allow(non_camel_case_types, clippy::enum_variant_names)]
             #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
-            pub enum #enum_name<__K = #key_type> {
+            #serde_derive
+            #non_exhaustive
+            pub enum #enum_name<#key_param> {
                 #(#known_variants,)*
+                #rename
                 Unknown(__K),
             }
+
+            impl<__K> #enum_name<__K> {
+                #[doc = #as_str_doc]
+                pub fn as_str(&self) -> &'static str {
+                    match *self {
+                        #(#name_arms,)*
+                    }
+                }
+
+                #map_value
+            }
+
+            /// Formats the field's name, and `<catch-all>.<key>` for unknown fields.
+            impl<__K: ::std::fmt::Display> ::std::fmt::Display for #enum_name<__K> {
+                fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                    match self {
+                        Self::Unknown(key) => ::std::write!(f, "{}.{}", self.as_str(), key),
+                        _ => f.write_str(self.as_str()),
+                    }
+                }
+            }
         }
     } else {
         // No unknown field - generate simple enum with Copy
@@ -59,11 +177,97 @@ pub fn generate_field_enum(struct_name: &Ident, fields: &[FieldInfo]) -> TokenSt
             #[doc(hidden)]
             #[allow(non_camel_case_types, clippy::enum_variant_names)]
             #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+            #serde_derive
+            #non_exhaustive
             pub enum #enum_name {
                 #(#known_variants),*
             }
+
+            impl #enum_name {
+                #[doc = #as_str_doc]
+                pub fn as_str(&self) -> &'static str {
+                    match *self {
+                        #(#name_arms,)*
+                    }
+                }
+
+                #map_value
+            }
+
+            /// Formats the field's name.
+            impl ::std::fmt::Display for #enum_name {
+                fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                    f.write_str(self.as_str())
+                }
+            }
+        }
+    }
+}
+
+/// Generate public aliases naming the field and value enums.
+///
+/// A struct `Person` gets `PersonField` and `PersonValue`, so that its change log,
+/// field stamps, visited fields, and field names can be named in signatures.
+pub fn generate_enum_aliases(
+    struct_name: &Ident,
+    vis: &Visibility,
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+    let field_alias = format_ident!("{}Field", struct_name);
+    let value_alias = format_ident!("{}Value", struct_name);
+
+    // Type aliases don't enforce bounds, so declare the parameters without them,
+    // but keep their defaults
+    let mut params = generics.clone();
+    params.where_clause = None;
+    for param in params.params.iter_mut() {
+        match param {
+            syn::GenericParam::Type(tp) => {
+                tp.bounds.clear();
+                tp.colon_token = None;
+            }
+            syn::GenericParam::Lifetime(lp) => {
+                lp.bounds.clear();
+                lp.colon_token = None;
+            }
+            syn::GenericParam::Const(_) => {}
         }
     }
+    let (_, ty_generics, _) = generics.split_for_impl();
+
+    // The field alias only takes the parameters its key type mentions, since type
+    // aliases reject unused type parameters
+    let mut field_params = params.clone();
+    field_params.params = params
+        .params
+        .iter()
+        .filter(|param| {
+            let Some(key_type) = &config.generic_key else {
+                return false;
+            };
+            let ident = match param {
+                syn::GenericParam::Type(tp) => &tp.ident,
+                syn::GenericParam::Lifetime(lp) => &lp.lifetime.ident,
+                syn::GenericParam::Const(cp) => &cp.ident,
+            };
+            type_mentions_type_param(key_type, &[ident])
+        })
+        .cloned()
+        .collect();
+
+    let field_doc = format!("The field keys of [`{}`].", struct_name);
+    let value_doc = format!("The field values of [`{}`].", struct_name);
+
+    quote! {
+        #[doc = #field_doc]
+        #vis type #field_alias #field_params = #field_enum;
+
+        #[doc = #value_doc]
+        #vis type #value_alias #params = #value_enum #ty_generics;
+    }
 }
 
 /// Generate the value enum (used as map values).
@@ -75,6 +279,7 @@ pub fn generate_value_enum(
 ) -> TokenStream {
     let enum_name = value_enum_name(struct_name);
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let non_exhaustive = non_exhaustive_attr(config);
 
     // Find unknown field if present
     let unknown_field = fields.iter().find(|f| f.is_unknown_field());
@@ -86,16 +291,38 @@ pub fn generate_value_enum(
         .map(|f| {
             let variant = to_pascal_case(&f.name);
             let ty = &f.inner_ty; // Always use inner type (unwrapped for Option)
-            quote! { #variant(#ty) }
+            let rename = serde_backing_rename(config, f);
+            let upgrade = upgrade_from_serde_attr(struct_name, f, config, generics);
+            quote! { #rename #upgrade #variant(#ty) }
         })
         .collect();
 
     // Add Unknown variant if there's an unknown field
     if let Some(uf) = unknown_field {
         let value_ty = &uf.inner_ty;
-        variants.push(quote! { Unknown(#value_ty) });
+        let rename = serde_backing_rename(config, uf);
+        variants.push(quote! { #rename Unknown(#value_ty) });
     }
 
+    // A key type mentioning the struct's generics may mention generics that no
+    // value type does, so an uninhabited variant marks the enum as using them
+    let key_marker = config.generic_key.as_ref().map(|key_type| {
+        let skip = if config.serde_backing {
+            quote! { #[serde(skip)] }
+        } else {
+            quote! {}
+        };
+        quote! {
+            #[doc(hidden)]
+            #skip
+            __Key(::std::convert::Infallible, ::std::marker::PhantomData<fn() -> #key_type>)
+        }
+    });
+    let marker_arm = key_marker
+        .as_ref()
+        .map(|_| quote! { Self::__Key(never, _) => match *never {}, });
+    variants.extend(key_marker);
+
     let total_variants = variants.len();
 
     // Collect inner types that mention at least one of the struct's type parameters.
@@ -142,6 +369,7 @@ pub fn generate_value_enum(
                 match self {
                     #(#debug_arms,)*
                     #(#unknown_debug_arm,)*
+                    #marker_arm
                 }
             }
         }
@@ -149,11 +377,16 @@ pub fn generate_value_enum(
 
     // Clone impl
     let clone_impl = if !config.no_clone {
+        // Values of fields that skip cloning are replaced by their default
         let clone_arms: Vec<_> = known_fields
             .iter()
             .map(|f| {
                 let variant = to_pascal_case(&f.name);
-                quote! { Self::#variant(v) => Self::#variant(::std::clone::Clone::clone(v)) }
+                if f.config.skip_clone {
+                    quote! { Self::#variant(_) => Self::#variant(::std::default::Default::default()) }
+                } else {
+                    quote! { Self::#variant(v) => Self::#variant(::std::clone::Clone::clone(v)) }
+                }
             })
             .collect();
         let unknown_clone_arm: Vec<_> = unknown_field
@@ -162,7 +395,16 @@ pub fn generate_value_enum(
                 quote! { Self::Unknown(v) => Self::Unknown(::std::clone::Clone::clone(v)) }
             })
             .collect();
-        let clone_bounds = quote! { #(#inner_types: ::std::clone::Clone,)* };
+        let (default_types, clone_types): (Vec<_>, Vec<_>) = fields
+            .iter()
+            .filter(|f| type_mentions_type_param(&f.inner_ty, &type_param_idents))
+            .partition(|f| f.config.skip_clone);
+        let default_types = default_types.iter().map(|f| &f.inner_ty);
+        let clone_types: Vec<_> = clone_types.iter().map(|f| &f.inner_ty).collect();
+        let clone_bounds = quote! {
+            #(#clone_types: ::std::clone::Clone,)*
+            #(#default_types: ::std::default::Default,)*
+        };
         let clone_where = if let Some(wc) = where_clause {
             let existing = &wc.predicates;
             quote! { where #clone_bounds #existing }
@@ -177,6 +419,7 @@ pub fn generate_value_enum(
                     match self {
                         #(#clone_arms,)*
                         #(#unknown_clone_arm,)*
+                        #marker_arm
                     }
                 }
             }
@@ -187,27 +430,44 @@ pub fn generate_value_enum(
 
     // PartialEq impl
     let partial_eq_impl = if !config.no_partial_eq {
+        // Values of fields that skip equality are equal whenever both are present
         let eq_arms: Vec<_> = known_fields
             .iter()
             .map(|f| {
                 let variant = to_pascal_case(&f.name);
-                quote! { (Self::#variant(a), Self::#variant(b)) => a == b }
+                if f.skips_eq() {
+                    quote! { (Self::#variant(_), Self::#variant(_)) => true }
+                } else {
+                    quote! { (Self::#variant(a), Self::#variant(b)) => a == b }
+                }
             })
             .collect();
         let unknown_eq_arm: Vec<_> = unknown_field
             .iter()
-            .map(|_| quote! { (Self::Unknown(a), Self::Unknown(b)) => a == b })
+            .map(|f| {
+                if f.skips_eq() {
+                    quote! { (Self::Unknown(_), Self::Unknown(_)) => true }
+                } else {
+                    quote! { (Self::Unknown(a), Self::Unknown(b)) => a == b }
+                }
+            })
+            .collect();
+        let eq_types: Vec<_> = fields
+            .iter()
+            .filter(|f| !f.skips_eq())
+            .map(|f| &f.inner_ty)
+            .filter(|ty| type_mentions_type_param(ty, &type_param_idents))
             .collect();
         let catchall = if total_variants > 1 {
             quote! { _ => false, }
         } else {
             quote! {}
         };
-        let eq_bounds = quote! { #(#inner_types: ::std::cmp::PartialEq,)* };
+        let eq_bounds = quote! { #(#eq_types: ::std::cmp::PartialEq,)* };
         let eq_where = if let Some(wc) = where_clause {
             let existing = &wc.predicates;
             quote! { where #eq_bounds #existing }
-        } else if !inner_types.is_empty() {
+        } else if !eq_types.is_empty() {
             quote! { where #eq_bounds }
         } else {
             quote! {}
@@ -227,16 +487,47 @@ pub fn generate_value_enum(
         quote! {}
     };
 
+    let serde_derive = serde_backing_derive(config);
+    let upgrade_fns = generate_upgrade_fns(struct_name, fields, config, generics);
+
     quote! {
         #[doc(hidden)]
         #[allow(non_camel_case_types, clippy::enum_variant_names)]
-        pub enum #enum_name #impl_generics #where_clause {
+        #serde_derive
+        #non_exhaustive
+        pub enum #enum_name #generics #where_clause {
             #(#variants),*
         }
         #debug_impl
         #clone_impl
         #partial_eq_impl
+        #upgrade_fns
+    }
+}
+
+/// Returns the serde derives for the hidden enums if `serde_backing` is set.
+///
+/// The derives go through the runtime crate's re-export of serde, so that users
+/// don't need a direct serde dependency.
+fn serde_backing_derive(config: &StructibleConfig) -> TokenStream {
+    if !config.serde_backing {
+        return quote! {};
+    }
+    quote! {
+        #[derive(::structible::__private::serde::Serialize, ::structible::__private::serde::Deserialize)]
+        #[serde(crate = "::structible::__private::serde")]
+    }
+}
+
+/// Returns serde attributes naming a hidden enum variant after its field, and
+/// accepting its aliases when deserializing.
+fn serde_backing_rename(config: &StructibleConfig, field: &FieldInfo) -> TokenStream {
+    if !config.serde_backing {
+        return quote! {};
     }
+    let name = field.name.unraw().to_string();
+    let aliases = field.alias_names();
+    quote! { #[serde(rename = #name #(, alias = #aliases)*)] }
 }
 
 /// Generate the companion fields struct for ownership extraction.
@@ -249,16 +540,30 @@ pub fn generate_fields_struct(
     vis: &Visibility,
     _fields: &[FieldInfo],
     config: &StructibleConfig,
+    attrs: &[Attribute],
     generics: &Generics,
 ) -> TokenStream {
     let fields_struct = fields_struct_name(struct_name);
-    let field_enum = field_enum_name(struct_name);
+    let field_enum = field_enum_type(struct_name, config);
     let value_enum = value_enum_name(struct_name);
-    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, where_clause) = generics.split_for_impl();
     let map_type = config.backing.to_tokens();
+    let relation = format!(
+        "Companion struct of [`{0}`] for extracting owned values, returned by [`{0}::into_fields`].",
+        struct_name
+    );
+    // Only the summary is copied, as examples in the struct's docs are about the struct
+    let summary = doc_summary(attrs);
+    let summary = (!summary.is_empty()).then(|| {
+        quote! {
+            ///
+            #(#[doc = #summary])*
+        }
+    });
 
     quote! {
-        /// Companion struct for extracting owned values from fields.
+        #[doc = #relation]
+        #summary
         ///
         /// This struct contains all field values transferred from the original struct.
         /// Use `take_*` methods to extract values. All fields return `Option<T>`,
@@ -266,7 +571,7 @@ pub fn generate_fields_struct(
         /// struct was valid).
         ///
         /// This is a "reverse builder" pattern - fields can only be extracted, not inserted.
-        #vis struct #fields_struct #impl_generics #where_clause {
+        #vis struct #fields_struct #generics #where_clause {
             inner: #map_type<#field_enum, #value_enum #ty_generics>,
         }
     }
@@ -283,7 +588,7 @@ pub fn generate_fields_impl(
     generics: &Generics,
 ) -> TokenStream {
     let fields_struct = fields_struct_name(struct_name);
-    let field_enum = field_enum_name(struct_name);
+    let field_enum = field_enum_type(struct_name, config);
     let value_enum = value_enum_name(struct_name);
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
@@ -296,15 +601,17 @@ pub fn generate_fields_impl(
             let take_name = format_ident!("take_{}", name);
             let variant = to_pascal_case(name);
             let inner_ty = &f.inner_ty;
-            let vis = &f.vis;
+            let vis = f.getter_vis();
             let field_docs = extract_doc_comments(&f.attrs);
+            let method_attrs = f.method_attrs();
 
-            let name_str = name.to_string();
+            let name_str = name.unraw().to_string();
             let auto_doc = format!("Removes and returns the `{}` field value if present.", name_str);
             let doc_attr = format_method_doc(&auto_doc, &field_docs);
 
             quote! {
                 #doc_attr
+                #(#method_attrs)*
                 #vis fn #take_name(&mut self) -> Option<#inner_ty> {
                     match ::structible::BackingMap::remove(&mut self.inner, &#field_enum::#variant) {
                         Some(#value_enum::#variant(v)) => Some(v),
@@ -337,21 +644,23 @@ fn generate_fields_unknown_methods(
         return quote! {};
     };
 
-    let field_enum = field_enum_name(struct_name);
+    let field_enum = field_enum_type(struct_name, config);
     let value_enum = value_enum_name(struct_name);
     let name = &unknown_field.name;
     let key_type = unknown_field.unknown_key_type().unwrap();
     let value_type = &unknown_field.inner_ty;
-    let vis = &unknown_field.vis;
+    let get_vis = unknown_field.getter_vis();
+    let set_vis = unknown_field.setter_vis();
     let map_type = config.backing.to_tokens();
     let field_docs = extract_doc_comments(&unknown_field.attrs);
+    let method_attrs = unknown_field.method_attrs();
 
     let take_method = format_ident!("take_{}", name);
     let iter_method = format_ident!("{}_iter", name);
     let iter_mut_method = format_ident!("{}_iter_mut", name);
     let drain_method = format_ident!("drain_{}", name);
 
-    let name_str = name.to_string();
+    let name_str = name.unraw().to_string();
     let take_doc = format_method_doc(
         &format!(
             "Removes and returns the `{}` value for the given key.",
@@ -371,15 +680,17 @@ fn generate_fields_unknown_methods(
         &format!("Drains all `{}` fields into a new map.", name_str),
         &field_docs,
     );
+    let owned_key = unknown_field.normalized_key(quote! { key.to_owned() });
 
     quote! {
         #take_doc
-        #vis fn #take_method<__Q>(&mut self, key: &__Q) -> Option<#value_type>
+        #(#method_attrs)*
+        #get_vis fn #take_method<__Q>(&mut self, key: &__Q) -> Option<#value_type>
         where
             #key_type: ::std::borrow::Borrow<__Q>,
             __Q: ::std::borrow::ToOwned<Owned = #key_type> + ::std::hash::Hash + ::std::cmp::Eq + ?Sized,
         {
-            let owned_key: #key_type = key.to_owned();
+            let owned_key: #key_type = #owned_key;
             match ::structible::BackingMap::remove(&mut self.inner, &#field_enum::Unknown(owned_key)) {
                 Some(#value_enum::Unknown(v)) => Some(v),
                 _ => None,
@@ -387,7 +698,9 @@ fn generate_fields_unknown_methods(
         }
 
         #iter_doc
-        #vis fn #iter_method(&self) -> impl Iterator<Item = (&#key_type, &#value_type)> {
+
+        #(#method_attrs)*
+        #get_vis fn #iter_method(&self) -> impl Iterator<Item = (&#key_type, &#value_type)> {
             ::structible::IterableMap::iter(&self.inner).filter_map(|(k, v)| {
                 match (k, v) {
                     (#field_enum::Unknown(key), #value_enum::Unknown(value)) => Some((key, value)),
@@ -397,7 +710,9 @@ fn generate_fields_unknown_methods(
         }
 
         #iter_mut_doc
-        #vis fn #iter_mut_method(&mut self) -> impl Iterator<Item = (&#key_type, &mut #value_type)> {
+
+        #(#method_attrs)*
+        #set_vis fn #iter_mut_method(&mut self) -> impl Iterator<Item = (&#key_type, &mut #value_type)> {
             ::structible::IterableMap::iter_mut(&mut self.inner).filter_map(|(k, v)| {
                 match (k, v) {
                     (#field_enum::Unknown(key), #value_enum::Unknown(value)) => Some((key, value)),
@@ -407,7 +722,9 @@ fn generate_fields_unknown_methods(
         }
 
         #drain_doc
-        #vis fn #drain_method(&mut self) -> #map_type<#key_type, #value_type> {
+
+        #(#method_attrs)*
+        #get_vis fn #drain_method(&mut self) -> #map_type<#key_type, #value_type> {
             let keys: ::std::vec::Vec<#key_type> = ::structible::IterableMap::iter(&self.inner)
                 .filter_map(|(k, _)| {
                     if let #field_enum::Unknown(key) = k {
@@ -433,20 +750,287 @@ fn generate_fields_unknown_methods(
 pub fn generate_struct(
     struct_name: &Ident,
     vis: &Visibility,
+    fields: &[FieldInfo],
     config: &StructibleConfig,
     attrs: &[Attribute],
     generics: &Generics,
 ) -> TokenStream {
-    let field_enum = field_enum_name(struct_name);
+    let field_enum = field_enum_type(struct_name, config);
     let value_enum = value_enum_name(struct_name);
     let map_type = config.backing.to_tokens();
-    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, where_clause) = generics.split_for_impl();
+    let change_log = change_log_field(struct_name, config, generics);
+    let stamps = stamp_fields(struct_name, config);
+    let expiries = expiry_field(struct_name, config);
+    let tombstones = tombstone_field(struct_name, config);
+    let sources = source_field(struct_name, config);
+    let modified = timestamp_field(struct_name, config);
+    let undo_log = undo_log_field(struct_name, config, generics);
+    let overview = accessor_overview(vis, fields, config);
 
     quote! {
         #(#attrs)*
-        #vis struct #struct_name #impl_generics #where_clause {
+        #overview
+        #vis struct #struct_name #generics #where_clause {
             inner: #map_type<#field_enum, #value_enum #ty_generics>,
+            #change_log
+            #stamps
+            #expiries
+            #tombstones
+            #sources
+            #modified
+            #undo_log
+        }
+    }
+}
+
+/// Returns the "Accessors" section appended to the struct's docs, a table of each
+/// field's declared type and the accessors generated for it.
+///
+/// Accessors less visible than a public struct are left out, so that its docs
+/// don't name methods its users can't call.
+fn accessor_overview(
+    vis: &Visibility,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+) -> TokenStream {
+    let shown = |method_vis: &Visibility| {
+        !matches!(vis, Visibility::Public(_)) || matches!(method_vis, Visibility::Public(_))
+    };
+    let cell = |vis: &Visibility, methods: &[Ident]| {
+        if shown(vis) {
+            let methods: Vec<_> = methods.iter().map(|m| format!("`{}`", m)).collect();
+            methods.join(", ")
+        } else {
+            String::new()
+        }
+    };
+    let mut lines = vec![
+        String::new(),
+        " # Accessors".to_string(),
+        String::new(),
+        " | Field | Type | Get | Set | Remove |".to_string(),
+        " |---|---|---|---|---|".to_string(),
+    ];
+    for f in fields {
+        let name = f.name.unraw();
+        let ty = type_text(&f.ty);
+        let row = if let Some(key_type) = f.unknown_key_type() {
+            format!(
+                " | `{}` (by `{}`) | `{}` | {} | {} | {} |",
+                name,
+                type_text(key_type),
+                ty,
+                cell(
+                    f.getter_vis(),
+                    &[f.name.clone(), format_ident!("{}_iter", f.name)]
+                ),
+                cell(f.setter_vis(), &[format_ident!("insert_{}", f.name)]),
+                cell(f.remover_vis(), &[format_ident!("remove_{}", f.name)]),
+            )
+        } else {
+            let set = if config.no_setters {
+                String::new()
+            } else {
+                cell(f.setter_vis(), &[f.setter_name()])
+            };
+            let remove = if !f.is_optional || config.no_removers {
+                String::new()
+            } else {
+                cell(f.remover_vis(), &[f.remover_name()])
+            };
+            format!(
+                " | `{}` | `{}` | {} | {} | {} |",
+                name,
+                ty,
+                cell(f.getter_vis(), &[f.getter_name()]),
+                set,
+                remove,
+            )
+        };
+        lines.push(row);
+    }
+    quote! { #(#[doc = #lines])* }
+}
+
+/// Checks the attributes written on the struct, which are copied to the
+/// generated struct in the order written.
+///
+/// Derives that conflict with the generated impls, or that would see the
+/// backing map instead of the fields, are rejected, including inside `cfg_attr`.
+pub fn check_struct_attrs(
+    attrs: &[Attribute],
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+) -> syn::Result<()> {
+    let generates_impls = !config.is_async && !config.minimal;
+    let generates_default = generates_impls
+        && fields
+            .iter()
+            .filter(|f| !f.is_unknown_field())
+            .all(|f| f.is_optional);
+    for attr in attrs {
+        let mut derives = Vec::new();
+        collect_derives(&attr.meta, &mut derives)?;
+        for path in derives {
+            let Some(name) = path.segments.last().map(|s| s.ident.to_string()) else {
+                continue;
+            };
+            let message = match name.as_str() {
+                "Debug" if generates_impls => {
+                    "`#[structible]` already implements `Debug`, showing only present fields"
+                }
+                "Clone" if !config.is_async && !config.no_clone => {
+                    "`#[structible]` already implements `Clone`; use `no_clone` to leave it out"
+                }
+                "PartialEq" if !config.is_async && !config.no_partial_eq => {
+                    "`#[structible]` already implements `PartialEq`; use `no_partial_eq` to leave it out"
+                }
+                "Default" if generates_default => {
+                    "`#[structible]` already implements `Default`, since all fields are optional"
+                }
+                "Copy" => "map-backed structs can't be `Copy`",
+                "Serialize" | "Deserialize" => {
+                    "deriving serde here would encode the backing map; use `serde`, or `serde_backing` with `structible::serde_adapter`, instead"
+                }
+                "Builder" => {
+                    "deriving a builder here would build the backing map; use `builder`, or the constructor and setters"
+                }
+                _ => continue,
+            };
+            return Err(syn::Error::new_spanned(path, message));
+        }
+    }
+    Ok(())
+}
+
+/// Collects the paths derived by an attribute, looking into `cfg_attr`.
+fn collect_derives(meta: &Meta, derives: &mut Vec<Path>) -> syn::Result<()> {
+    if meta.path().is_ident("derive") {
+        let paths = meta
+            .require_list()?
+            .parse_args_with(Punctuated::<Path, Token![,]>::parse_terminated)?;
+        derives.extend(paths);
+    } else if meta.path().is_ident("cfg_attr") {
+        let args = meta
+            .require_list()?
+            .parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?;
+        // The first argument is the predicate, the rest are the conditional attributes
+        for nested in args.iter().skip(1) {
+            collect_derives(nested, derives)?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the types to bound in the struct's trait impls: the field inner types
+/// and catch-all key type that mention the struct's type parameters.
+fn bounded_types<'a>(
+    fields: impl IntoIterator<Item = &'a FieldInfo>,
+    config: &'a StructibleConfig,
+    generics: &Generics,
+) -> Vec<&'a Type> {
+    let type_param_idents: Vec<_> = generics.type_params().map(|tp| &tp.ident).collect();
+    fields
+        .into_iter()
+        .map(|f| &f.inner_ty)
+        .chain(&config.generic_key)
+        .filter(|ty| type_mentions_type_param(ty, &type_param_idents))
+        .collect()
+}
+
+/// Returns the bounds of the struct's `Clone` impls on the types that mention the
+/// struct's type parameters: fields that skip cloning need a default instead.
+fn clone_bounds(
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    let type_param_idents: Vec<_> = generics.type_params().map(|tp| &tp.ident).collect();
+    let clone_types = bounded_types(
+        fields.iter().filter(|f| !f.config.skip_clone),
+        config,
+        generics,
+    );
+    let default_types = fields
+        .iter()
+        .filter(|f| f.config.skip_clone)
+        .map(|f| &f.inner_ty)
+        .filter(|ty| type_mentions_type_param(ty, &type_param_idents));
+    quote! {
+        #(#clone_types: ::std::clone::Clone,)*
+        #(#default_types: ::std::default::Default,)*
+    }
+}
+
+/// Returns an expression cloning the backing map `self.inner`.
+///
+/// Optional fields that skip cloning are left out of the clone, and required ones
+/// hold their default through the value enum's `Clone` impl.
+pub fn inner_clone(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+) -> TokenStream {
+    let field_enum = field_enum_type(struct_name, config);
+    let removals: Vec<_> = fields
+        .iter()
+        .filter(|f| f.config.skip_clone && f.is_optional)
+        .map(|f| {
+            let variant = to_pascal_case(&f.name);
+            quote! { ::structible::BackingMap::remove(&mut inner, &#field_enum::#variant); }
+        })
+        .collect();
+    if removals.is_empty() {
+        return quote! { ::std::clone::Clone::clone(&self.inner) };
+    }
+    quote! {
+        {
+            let mut inner = ::std::clone::Clone::clone(&self.inner);
+            #(#removals)*
+            inner
+        }
+    }
+}
+
+/// Returns the body of `PartialEq::eq` for a struct or companion holding its
+/// fields in `self.inner`.
+///
+/// The backing maps are compared directly, unless fields skip equality, in which
+/// case the entries of the other fields are compared one by one.
+pub fn eq_body(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+) -> TokenStream {
+    let field_enum = field_enum_type(struct_name, config);
+    let skipped: Vec<_> = fields
+        .iter()
+        .filter(|f| f.config.skip_eq)
+        .map(|f| {
+            if f.is_unknown_field() {
+                quote! { #field_enum::Unknown(_) }
+            } else {
+                let variant = to_pascal_case(&f.name);
+                quote! { #field_enum::#variant }
+            }
+        })
+        .collect();
+    if skipped.is_empty() {
+        return quote! { self.inner == other.inner };
+    }
+    quote! {
+        let compared = |key: &#field_enum| !::std::matches!(key, #(#skipped)|*);
+        let mut count = 0usize;
+        for (key, value) in ::structible::IterableMap::iter(&self.inner) {
+            if compared(key) {
+                if ::structible::BackingMap::get(&other.inner, key) != ::std::option::Option::Some(value) {
+                    return false;
+                }
+                count += 1;
+            }
         }
+        count == ::structible::IterableMap::iter(&other.inner).filter(|(key, _)| compared(key)).count()
     }
 }
 
@@ -461,15 +1045,22 @@ pub fn generate_struct_trait_impls(
     generics: &Generics,
 ) -> TokenStream {
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
-    let type_param_idents: Vec<_> = generics.type_params().map(|tp| &tp.ident).collect();
-    let inner_types: Vec<_> = fields
-        .iter()
-        .map(|f| &f.inner_ty)
-        .filter(|ty| type_mentions_type_param(ty, &type_param_idents))
-        .collect();
+    let inner_types = bounded_types(fields, config, generics);
 
     let clone_impl = if !config.no_clone {
-        let clone_bounds = quote! { #(#inner_types: ::std::clone::Clone,)* };
+        let inner_clone = inner_clone(struct_name, fields, config);
+        let clone_changes = if config.track_changes {
+            quote! { changes: ::std::clone::Clone::clone(&self.changes), }
+        } else {
+            quote! {}
+        };
+        let clone_stamps = stamp_clone(config);
+        let clone_expiries = expiry_clone(config);
+        let clone_tombstones = tombstone_clone(config);
+        let clone_sources = source_clone(config);
+        let clone_modified = timestamp_clone(config);
+        let undo_log_init = undo_log_init(config);
+        let clone_bounds = clone_bounds(fields, config, generics);
         let clone_where = if let Some(wc) = where_clause {
             let existing = &wc.predicates;
             quote! { where #clone_bounds #existing }
@@ -481,7 +1072,16 @@ pub fn generate_struct_trait_impls(
         quote! {
             impl #impl_generics ::std::clone::Clone for #struct_name #ty_generics #clone_where {
                 fn clone(&self) -> Self {
-                    Self { inner: ::std::clone::Clone::clone(&self.inner) }
+                    Self {
+                        inner: #inner_clone,
+                        #clone_changes
+                        #clone_stamps
+                        #clone_expiries
+                        #clone_tombstones
+                        #clone_sources
+                        #clone_modified
+                        #undo_log_init
+                    }
                 }
             }
         }
@@ -490,11 +1090,19 @@ pub fn generate_struct_trait_impls(
     };
 
     let partial_eq_impl = if !config.no_partial_eq {
-        let eq_bounds = quote! { #(#inner_types: ::std::cmp::PartialEq,)* };
+        // Expired values are compared as absent, like the getters read them
+        let eq_body = if config.ttl {
+            ttl_eq_body(struct_name, fields, config)
+        } else {
+            eq_body(struct_name, fields, config)
+        };
+        // Values of fields that skip equality aren't compared
+        let eq_types = bounded_types(fields.iter().filter(|f| !f.skips_eq()), config, generics);
+        let eq_bounds = quote! { #(#eq_types: ::std::cmp::PartialEq,)* };
         let eq_where = if let Some(wc) = where_clause {
             let existing = &wc.predicates;
             quote! { where #eq_bounds #existing }
-        } else if !inner_types.is_empty() {
+        } else if !eq_types.is_empty() {
             quote! { where #eq_bounds }
         } else {
             quote! {}
@@ -502,7 +1110,7 @@ pub fn generate_struct_trait_impls(
         quote! {
             impl #impl_generics ::std::cmp::PartialEq for #struct_name #ty_generics #eq_where {
                 fn eq(&self, other: &Self) -> bool {
-                    self.inner == other.inner
+                    #eq_body
                 }
             }
         }
@@ -528,15 +1136,11 @@ pub fn generate_fields_struct_trait_impls(
 ) -> TokenStream {
     let fields_struct = fields_struct_name(struct_name);
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
-    let type_param_idents: Vec<_> = generics.type_params().map(|tp| &tp.ident).collect();
-    let inner_types: Vec<_> = fields
-        .iter()
-        .map(|f| &f.inner_ty)
-        .filter(|ty| type_mentions_type_param(ty, &type_param_idents))
-        .collect();
+    let inner_types = bounded_types(fields, config, generics);
 
     let clone_impl = if !config.no_clone {
-        let clone_bounds = quote! { #(#inner_types: ::std::clone::Clone,)* };
+        let inner_clone = inner_clone(struct_name, fields, config);
+        let clone_bounds = clone_bounds(fields, config, generics);
         let clone_where = if let Some(wc) = where_clause {
             let existing = &wc.predicates;
             quote! { where #clone_bounds #existing }
@@ -548,7 +1152,7 @@ pub fn generate_fields_struct_trait_impls(
         quote! {
             impl #impl_generics ::std::clone::Clone for #fields_struct #ty_generics #clone_where {
                 fn clone(&self) -> Self {
-                    Self { inner: ::std::clone::Clone::clone(&self.inner) }
+                    Self { inner: #inner_clone }
                 }
             }
         }
@@ -557,11 +1161,14 @@ pub fn generate_fields_struct_trait_impls(
     };
 
     let partial_eq_impl = if !config.no_partial_eq {
-        let eq_bounds = quote! { #(#inner_types: ::std::cmp::PartialEq,)* };
+        let eq_body = eq_body(struct_name, fields, config);
+        // Values of fields that skip equality aren't compared
+        let eq_types = bounded_types(fields.iter().filter(|f| !f.skips_eq()), config, generics);
+        let eq_bounds = quote! { #(#eq_types: ::std::cmp::PartialEq,)* };
         let eq_where = if let Some(wc) = where_clause {
             let existing = &wc.predicates;
             quote! { where #eq_bounds #existing }
-        } else if !inner_types.is_empty() {
+        } else if !eq_types.is_empty() {
             quote! { where #eq_bounds }
         } else {
             quote! {}
@@ -569,7 +1176,7 @@ pub fn generate_fields_struct_trait_impls(
         quote! {
             impl #impl_generics ::std::cmp::PartialEq for #fields_struct #ty_generics #eq_where {
                 fn eq(&self, other: &Self) -> bool {
-                    self.inner == other.inner
+                    #eq_body
                 }
             }
         }
@@ -589,6 +1196,7 @@ pub fn generate_fields_struct_trait_impls(
 pub fn generate_debug_impl(
     struct_name: &Ident,
     fields: &[FieldInfo],
+    config: &StructibleConfig,
     generics: &Generics,
 ) -> TokenStream {
     let field_enum = field_enum_name(struct_name);
@@ -601,12 +1209,7 @@ pub fn generate_debug_impl(
     // associated types (e.g. V::String) are correctly bounded rather than requiring
     // `V: Debug`. Types without any type param reference (e.g. `&'a str`) already
     // have blanket impls and must be excluded to avoid E0283 ambiguity.
-    let type_param_idents: Vec<_> = generics.type_params().map(|tp| &tp.ident).collect();
-    let inner_types: Vec<_> = fields
-        .iter()
-        .map(|f| &f.inner_ty)
-        .filter(|ty| type_mentions_type_param(ty, &type_param_idents))
-        .collect();
+    let inner_types = bounded_types(fields, config, generics);
     let debug_bounds = quote! { #(#inner_types: ::std::fmt::Debug,)* };
 
     // Combine existing where clause with Debug bounds
@@ -625,11 +1228,19 @@ pub fn generate_debug_impl(
         .filter(|f| !f.is_unknown_field())
         .map(|f| {
             let name = &f.name;
-            let name_str = name.to_string();
+            let name_str = name.unraw().to_string();
             let variant = to_pascal_case(name);
+            // Expired values read as absent, so they aren't shown either
+            let expired_check = if config.ttl && f.is_optional {
+                quote! { if !self.__structible_expired(&#field_enum::#variant) }
+            } else {
+                quote! {}
+            };
             quote! {
                 if let Some(#value_enum::#variant(v)) = ::structible::BackingMap::get(&self.inner, &#field_enum::#variant) {
-                    debug_struct.field(#name_str, v);
+                    #expired_check {
+                        debug_struct.field(#name_str, v);
+                    }
                 }
             }
         })
@@ -638,13 +1249,7 @@ pub fn generate_debug_impl(
     // Handle unknown fields if present
     let unknown_field = fields.iter().find(|f| f.is_unknown_field());
     let unknown_entries = if unknown_field.is_some() {
-        quote! {
-            for (k, v) in ::structible::IterableMap::iter(&self.inner) {
-                if let (#field_enum::Unknown(key), #value_enum::Unknown(value)) = (k, v) {
-                    debug_struct.field(&format!("{:?}", key), value);
-                }
-            }
-        }
+        debug_unknown_entries(&field_enum, &value_enum)
     } else {
         quote! {}
     };
@@ -661,12 +1266,35 @@ pub fn generate_debug_impl(
     }
 }
 
+/// Generate the Debug entries of the catch-all, given a `debug_struct` builder.
+///
+/// Entries are sorted by their key's Debug output, so that the output doesn't depend
+/// on the backing's iteration order.
+pub fn debug_unknown_entries(field_enum: &impl ToTokens, value_enum: &Ident) -> TokenStream {
+    quote! {
+        let mut unknown: ::std::vec::Vec<(::std::string::String, _)> =
+            ::structible::IterableMap::iter(&self.inner)
+                .filter_map(|(k, v)| match (k, v) {
+                    (#field_enum::Unknown(key), #value_enum::Unknown(value)) => {
+                        Some((format!("{:?}", key), value))
+                    }
+                    _ => None,
+                })
+                .collect();
+        unknown.sort_by(|a, b| a.0.cmp(&b.0));
+        for (key, value) in &unknown {
+            debug_struct.field(key, value);
+        }
+    }
+}
+
 /// Generate a custom Debug impl for the Fields struct.
 ///
 /// Only shows fields that are currently present in the backing map.
 pub fn generate_fields_debug_impl(
     struct_name: &Ident,
     fields: &[FieldInfo],
+    config: &StructibleConfig,
     generics: &Generics,
 ) -> TokenStream {
     let fields_struct = fields_struct_name(struct_name);
@@ -680,12 +1308,7 @@ pub fn generate_fields_debug_impl(
     // associated types (e.g. V::String) are correctly bounded rather than requiring
     // `V: Debug`. Types without any type param reference (e.g. `&'a str`) already
     // have blanket impls and must be excluded to avoid E0283 ambiguity.
-    let type_param_idents: Vec<_> = generics.type_params().map(|tp| &tp.ident).collect();
-    let inner_types: Vec<_> = fields
-        .iter()
-        .map(|f| &f.inner_ty)
-        .filter(|ty| type_mentions_type_param(ty, &type_param_idents))
-        .collect();
+    let inner_types = bounded_types(fields, config, generics);
     let debug_bounds = quote! { #(#inner_types: ::std::fmt::Debug,)* };
 
     // Combine existing where clause with Debug bounds
@@ -704,7 +1327,7 @@ pub fn generate_fields_debug_impl(
         .filter(|f| !f.is_unknown_field())
         .map(|f| {
             let name = &f.name;
-            let name_str = name.to_string();
+            let name_str = name.unraw().to_string();
             let variant = to_pascal_case(name);
             quote! {
                 if let Some(#value_enum::#variant(v)) = ::structible::BackingMap::get(&self.inner, &#field_enum::#variant) {
@@ -717,13 +1340,7 @@ pub fn generate_fields_debug_impl(
     // Handle unknown fields if present
     let unknown_field = fields.iter().find(|f| f.is_unknown_field());
     let unknown_entries = if unknown_field.is_some() {
-        quote! {
-            for (k, v) in ::structible::IterableMap::iter(&self.inner) {
-                if let (#field_enum::Unknown(key), #value_enum::Unknown(value)) = (k, v) {
-                    debug_struct.field(&format!("{:?}", key), value);
-                }
-            }
-        }
+        debug_unknown_entries(&field_enum, &value_enum)
     } else {
         quote! {}
     };
@@ -748,15 +1365,78 @@ pub fn generate_impl(
     generics: &Generics,
 ) -> TokenStream {
     let constructor = generate_constructor(struct_name, fields, config, generics);
-    let getters = generate_getters(struct_name, fields, generics);
-    let getters_mut = generate_getters_mut(struct_name, fields, generics);
-    let setters = generate_setters(struct_name, fields, generics);
-    let removers = generate_removers(struct_name, fields, generics);
+    let getters = generate_getters(struct_name, fields, config, generics);
+    let lazy_getters = generate_lazy_getters(struct_name, fields, config);
+    let getters_mut = generate_getters_mut(struct_name, fields, config, generics);
+    let setters = generate_setters(struct_name, fields, config, generics);
+    let removers = generate_removers(struct_name, fields, config, generics);
+    let scoped_takes = generate_scoped_takes(struct_name, fields, config);
+    let mappers = generate_mappers(struct_name, fields, config);
+    let promotions = generate_promotion_methods(struct_name, fields, config);
+    let aliases = generate_alias_accessors(fields, config);
+    let variant_predicates = generate_variant_predicates(fields);
+    let or_err_getters = generate_or_err_getters(fields);
+    let collection_methods = generate_collection_methods(struct_name, fields, config);
+    let string_methods = generate_string_methods(struct_name, fields, config);
+    let numeric_methods = generate_numeric_methods(struct_name, fields, config);
     let into_fields = generate_into_fields(struct_name, fields, config, generics);
-    let unknown_methods = generate_unknown_field_methods(struct_name, fields, generics);
+    let unknown_methods = generate_unknown_field_methods(struct_name, fields, config, generics);
+    let validation_methods = generate_validation_methods(struct_name, fields, config);
+    let change_log_methods = generate_change_log_methods(struct_name, fields, config, generics);
+    let merge_methods = generate_merge_methods(struct_name, fields, config);
+    let ttl_methods = generate_ttl_methods(struct_name, fields, config);
+    let tombstone_methods = generate_tombstone_methods(struct_name, fields, config);
+    let provenance_methods = generate_provenance_methods(struct_name, fields, config);
+    let timestamp_methods = generate_timestamp_methods(struct_name, fields, config);
+    let transaction_methods = generate_transaction_methods(config);
+    let freeze_method = generate_freeze_method(struct_name, config, generics);
+    let (field_paths, fields_in_order) = if config.minimal {
+        Default::default()
+    } else {
+        (
+            generate_field_paths(fields),
+            generate_fields_in_order(struct_name, fields, config, generics),
+        )
+    };
+    let par_visit_method = generate_par_visit_method(struct_name, fields, config, generics);
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
-    let len_methods = if config.with_len {
+    // Recording or merging a write clones the value, so generic field types must be
+    // `Clone`, and tracing it formats the value, so they must be `Debug`
+    let clone_bounds = if config.records_writes() {
+        let type_param_idents: Vec<_> = generics.type_params().map(|tp| &tp.ident).collect();
+        let inner_types = fields
+            .iter()
+            .map(|f| &f.inner_ty)
+            .filter(|ty| type_mentions_type_param(ty, &type_param_idents));
+        quote! { #(#inner_types: ::std::clone::Clone,)* }
+    } else {
+        quote! {}
+    };
+    let trace_bounds = trace_bounds(fields, config, generics);
+    let where_clause = if let Some(wc) = where_clause {
+        let existing = &wc.predicates;
+        quote! { where #clone_bounds #trace_bounds #existing }
+    } else if !clone_bounds.is_empty() || !trace_bounds.is_empty() {
+        quote! { where #clone_bounds #trace_bounds }
+    } else {
+        quote! {}
+    };
+
+    let len_methods = if config.with_len && config.ttl {
+        // Expired values are stored until purged, but read as absent
+        quote! {
+            /// Returns the number of fields currently present, not counting expired ones.
+            pub fn len(&self) -> usize {
+                ::structible::BackingMap::len(&self.inner) - self.__structible_expired_count()
+            }
+
+            /// Returns true if no fields are present, not counting expired ones.
+            pub fn is_empty(&self) -> bool {
+                self.len() == 0
+            }
+        }
+    } else if config.with_len {
         quote! {
             /// Returns the number of fields currently present.
             pub fn len(&self) -> usize {
@@ -775,13 +1455,36 @@ pub fn generate_impl(
     quote! {
         impl #impl_generics #struct_name #ty_generics #where_clause {
             #constructor
+            #validation_methods
             #(#getters)*
+            #(#lazy_getters)*
             #(#getters_mut)*
             #(#setters)*
             #(#removers)*
+            #(#scoped_takes)*
+            #(#mappers)*
+            #(#promotions)*
+            #(#aliases)*
+
+            #(#variant_predicates)*
+            #(#or_err_getters)*
+            #(#collection_methods)*
+            #(#string_methods)*
+            #(#numeric_methods)*
             #into_fields
+            #field_paths
+            #fields_in_order
+            #freeze_method
+            #par_visit_method
             #unknown_methods
             #len_methods
+            #change_log_methods
+            #merge_methods
+            #ttl_methods
+            #tombstone_methods
+            #provenance_methods
+            #timestamp_methods
+            #transaction_methods
         }
     }
 }
@@ -803,29 +1506,215 @@ pub fn generate_default_impl(
         return None;
     }
 
-    let field_enum = field_enum_name(struct_name);
+    let field_enum = field_enum_type(struct_name, config);
     let value_enum = value_enum_name(struct_name);
     let map_type = config.backing.to_tokens();
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let change_log_init = change_log_init(config);
+    let stamp_init = stamp_init(struct_name, config);
+    let expiry_init = expiry_init(struct_name, config);
+    let tombstone_init = tombstone_init(struct_name, config);
+    let source_init = source_init(struct_name, config);
+    let timestamp_init = timestamp_init(struct_name, config);
+    let undo_log_init = undo_log_init(config);
 
     Some(quote! {
         impl #impl_generics ::std::default::Default for #struct_name #ty_generics #where_clause {
             fn default() -> Self {
                 Self {
                     inner: <#map_type<#field_enum, #value_enum #ty_generics> as ::structible::BackingMap<#field_enum, #value_enum #ty_generics>>::new(),
+                    #change_log_init
+                    #stamp_init
+                    #expiry_init
+                    #tombstone_init
+                    #source_init
+                    #timestamp_init
+                    #undo_log_init
                 }
             }
         }
     })
 }
 
+/// Returns the patterns matching a `(&key, &value)` pair whose value belongs to the key's field.
+pub fn entry_patterns(struct_name: &Ident, fields: &[FieldInfo]) -> Vec<TokenStream> {
+    let field_enum = field_enum_name(struct_name);
+    let value_enum = value_enum_name(struct_name);
+    fields
+        .iter()
+        .map(|f| {
+            if f.is_unknown_field() {
+                quote! { (#field_enum::Unknown(_), #value_enum::Unknown(_)) }
+            } else {
+                let variant = to_pascal_case(&f.name);
+                quote! { (#field_enum::#variant, #value_enum::#variant(_)) }
+            }
+        })
+        .collect()
+}
+
+/// Checks that a struct with `minimal` needs none of the skipped machinery.
+///
+/// Catch-all fields and the flags building on the `Structible` impl or the
+/// generated `Debug` impl are rejected.
+pub fn check_minimal_config(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+) -> syn::Result<()> {
+    if !config.minimal {
+        return Ok(());
+    }
+    if let Some(f) = fields.iter().find(|f| f.is_unknown_field()) {
+        return Err(syn::Error::new_spanned(
+            &f.name,
+            "`minimal` cannot be combined with a catch-all field",
+        ));
+    }
+    let conflicts = [
+        (config.value_codec, "value_codec"),
+        (config.csv, "csv"),
+        (config.arrow, "arrow"),
+        (config.envelope, "envelope"),
+        (config.fuzz, "fuzz"),
+        (config.axum, "axum"),
+        (config.serde, "serde"),
+        (config.cow, "cow"),
+    ];
+    if let Some((_, name)) = conflicts.iter().find(|(used, _)| *used) {
+        return Err(syn::Error::new_spanned(
+            struct_name,
+            format!("`minimal` cannot be combined with `{}`", name),
+        ));
+    }
+    Ok(())
+}
+
+/// Generate the `Structible` impl naming the struct's backing map, field enum, and value enum.
+///
+/// The impl exposes the backing map type, so it is skipped with `no_structible`,
+/// e.g. for a public struct over a private backing, and with `minimal`.
+pub fn generate_structible_impl(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    if config.no_structible || config.minimal {
+        return quote! {};
+    }
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+    let map_type = config.backing.to_tokens();
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let entry_patterns = entry_patterns(struct_name, fields);
+    let change_log_init = change_log_init(config);
+    let stamp_init = stamp_init(struct_name, config);
+    let expiry_init = expiry_init(struct_name, config);
+    let tombstone_init = tombstone_init(struct_name, config);
+    let source_init = source_init(struct_name, config);
+    let timestamp_init = timestamp_init(struct_name, config);
+    let undo_log_init = undo_log_init(config);
+    let structible_where = with_promote_bounds(where_clause, fields, generics);
+    let route_entry = route_entry_method(fields);
+
+    let required_checks = fields
+        .iter()
+        .filter(|f| !f.is_optional && !f.is_unknown_field())
+        .map(|f| {
+            let variant = to_pascal_case(&f.name);
+            let name_str = f.name.unraw().to_string();
+            quote! {
+                if ::structible::BackingMap::get(&backing, &#field_enum::#variant).is_none() {
+                    return ::std::result::Result::Err(
+                        ::structible::ValidationError::MissingRequired { field: #name_str },
+                    );
+                }
+            }
+        });
+
+    quote! {
+        impl #impl_generics ::structible::Structible for #struct_name #ty_generics #structible_where {
+            type Backing = #map_type<#field_enum, #value_enum #ty_generics>;
+            type Field = #field_enum;
+            type Value = #value_enum #ty_generics;
+
+            fn backing(&self) -> &Self::Backing {
+                &self.inner
+            }
+
+            fn into_backing(self) -> Self::Backing {
+                self.inner
+            }
+
+            fn try_from_backing(
+                backing: Self::Backing,
+            ) -> ::std::result::Result<Self, ::structible::ValidationError> {
+                #(#required_checks)*
+                ::std::result::Result::Ok(Self {
+                    inner: backing,
+                    #change_log_init
+                    #stamp_init
+                    #expiry_init
+                    #tombstone_init
+                    #source_init
+                    #timestamp_init
+                    #undo_log_init
+                })
+            }
+
+            fn is_valid_entry(key: &Self::Field, value: &Self::Value) -> bool {
+                ::std::matches!((key, value), #(#entry_patterns)|*)
+            }
+
+            #route_entry
+        }
+    }
+}
+
+/// Generate a `From` impl taking the required fields as a tuple, in declaration order.
+pub fn generate_from_tuple_impl(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> syn::Result<Option<TokenStream>> {
+    if !config.from_tuple {
+        return Ok(None);
+    }
+
+    let required: Vec<_> = fields
+        .iter()
+        .filter(|f| !f.is_optional && !f.is_unknown_field())
+        .collect();
+    if required.is_empty() {
+        return Err(syn::Error::new_spanned(
+            struct_name,
+            "`from_tuple` requires at least one required field",
+        ));
+    }
+
+    let tys: Vec<_> = required.iter().map(|f| &f.ty).collect();
+    let indices = (0..required.len()).map(syn::Index::from);
+    let constructor_name = config.constructor_name(struct_name);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(Some(quote! {
+        impl #impl_generics ::std::convert::From<(#(#tys,)*)> for #struct_name #ty_generics #where_clause {
+            fn from(tuple: (#(#tys,)*)) -> Self {
+                Self::#constructor_name(#(tuple.#indices),*)
+            }
+        }
+    }))
+}
+
 fn generate_constructor(
     struct_name: &Ident,
     fields: &[FieldInfo],
     config: &StructibleConfig,
     generics: &Generics,
 ) -> TokenStream {
-    let field_enum = field_enum_name(struct_name);
+    let field_enum = field_enum_type(struct_name, config);
     let value_enum = value_enum_name(struct_name);
     let map_type = config.backing.to_tokens();
     let (_, ty_generics, _) = generics.split_for_impl();
@@ -841,7 +1730,11 @@ fn generate_constructor(
         .map(|f| {
             let name = &f.name;
             let ty = &f.ty;
-            quote! { #name: #ty }
+            if f.is_transparent() {
+                quote! { #name: impl ::std::convert::Into<#ty> }
+            } else {
+                quote! { #name: #ty }
+            }
         })
         .collect();
 
@@ -850,25 +1743,53 @@ fn generate_constructor(
         .map(|f| {
             let name = &f.name;
             let variant = to_pascal_case(&f.name);
-            quote! {
-                ::structible::BackingMap::insert(&mut inner, #field_enum::#variant, #value_enum::#variant(#name));
-            }
+            let value = if f.is_transparent() {
+                quote! { ::std::convert::Into::into(#name) }
+            } else {
+                quote! { #name }
+            };
+            let insert = insert_expr(
+                config,
+                quote! { this },
+                quote! { #field_enum::#variant },
+                quote! { #value_enum::#variant(#value) },
+            );
+            quote! { #insert; }
         })
         .collect();
 
-    let constructor_name = config
-        .constructor
-        .clone()
-        .unwrap_or_else(|| format_ident!("new"));
+    let constructor_name = config.constructor_name(struct_name);
 
     let required_count = required.len();
 
+    // The required fields are recorded as the first changes of a tracked struct
+    let changes_init = if config.track_changes {
+        quote! { changes: ::std::vec::Vec::with_capacity(#required_count), }
+    } else {
+        quote! {}
+    };
+    let stamp_init = stamp_init(struct_name, config);
+    let expiry_init = expiry_init(struct_name, config);
+    let tombstone_init = tombstone_init(struct_name, config);
+    let source_init = source_init(struct_name, config);
+    let timestamp_init = timestamp_init(struct_name, config);
+    let undo_log_init = undo_log_init(config);
+
     quote! {
         /// Creates a new instance with all required fields.
         pub fn #constructor_name(#(#params),*) -> Self {
-            let mut inner = <#map_type<#field_enum, #value_enum #ty_generics> as ::structible::BackingMap<#field_enum, #value_enum #ty_generics>>::with_capacity(#required_count);
+            let mut this = Self {
+                inner: <#map_type<#field_enum, #value_enum #ty_generics> as ::structible::BackingMap<#field_enum, #value_enum #ty_generics>>::with_capacity(#required_count),
+                #changes_init
+                #stamp_init
+                #expiry_init
+                #tombstone_init
+                #source_init
+                #timestamp_init
+                #undo_log_init
+            };
             #(#inserts)*
-            Self { inner }
+            this
         }
     }
 }
@@ -876,49 +1797,198 @@ fn generate_constructor(
 fn generate_getters(
     struct_name: &Ident,
     fields: &[FieldInfo],
+    config: &StructibleConfig,
     _generics: &Generics,
 ) -> Vec<TokenStream> {
-    let field_enum = field_enum_name(struct_name);
+    let field_enum = field_enum_type(struct_name, config);
     let value_enum = value_enum_name(struct_name);
 
     fields
         .iter()
-        .filter(|f| !f.is_unknown_field())
+        .filter(|f| !f.is_unknown_field() && !f.is_lazy())
         .map(|f| {
             let name = &f.name;
-            let getter_name = f.config.get.clone().unwrap_or_else(|| name.clone());
+            let missing = missing_required(config, name);
+            // The getter of an upgraded field returns the strong reference, and the
+            // weak reference itself is read with `<getter>_weak`; likewise, the getter
+            // of a transparent field converts, and the newtype is read with `<getter>_newtype`
+            let getter_name = if f.is_weak() {
+                f.weak_getter_name()
+            } else if f.is_transparent() {
+                f.newtype_getter_name()
+            } else {
+                f.getter_name()
+            };
             let variant = to_pascal_case(name);
 
-            let vis = &f.vis;
+            let vis = f.getter_vis();
             let field_docs = extract_doc_comments(&f.attrs);
-
-            let name_str = name.to_string();
-            if f.is_optional {
-                let inner_ty = &f.inner_ty;
-                let auto_doc = format!("Returns the `{}` value if present.", name_str);
+            let method_attrs = f.method_attrs();
+
+            let name_str = name.unraw().to_string();
+            let count = read_counter(config, name);
+            // The getter of a `Box<dyn Trait>` field borrows the trait object
+            let target = f.getter_target();
+            let borrow = if f.trait_object().is_some() {
+                quote! { &**v }
+            } else {
+                quote! { v }
+            };
+            let getter = if f.is_optional {
+                let auto_doc = config.accessor_doc(
+                    f,
+                    &getter_name,
+                    format!("Returns the `{}` value if present.", name_str),
+                );
                 let doc_attr = format_method_doc(&auto_doc, &field_docs);
+                let expired =
+                    expired_guard(config, quote! { #field_enum::#variant }, quote! { None });
+                let body = no_panic_body(
+                    struct_name,
+                    config,
+                    &getter_name,
+                    quote! {
+                        #count
+                        #expired
+                        match ::structible::BackingMap::get(&self.inner, &#field_enum::#variant) {
+                            Some(#value_enum::#variant(v)) => Some(#borrow),
+                            _ => None,
+                        }
+                    },
+                );
                 quote! {
                     #doc_attr
-                    #vis fn #getter_name(&self) -> Option<&#inner_ty> {
+                    #(#method_attrs)*
+                    #vis fn #getter_name(&self) -> Option<&#target> {
+                        #body
+                    }
+                }
+            } else if config.no_panic {
+                let auto_doc = config.accessor_doc(
+                    f,
+                    &getter_name,
+                    format!(
+                        "Returns a reference to the `{}` value, or `None` if it is missing.",
+                        name_str
+                    ),
+                );
+                let doc_attr = format_method_doc(&auto_doc, &field_docs);
+                let body = no_panic_body(
+                    struct_name,
+                    config,
+                    &getter_name,
+                    quote! {
+                        #count
                         match ::structible::BackingMap::get(&self.inner, &#field_enum::#variant) {
-                            Some(#value_enum::#variant(v)) => Some(v),
+                            Some(#value_enum::#variant(v)) => Some(#borrow),
                             _ => None,
                         }
+                    },
+                );
+                quote! {
+                    #doc_attr
+                    #(#method_attrs)*
+                    #vis fn #getter_name(&self) -> Option<&#target> {
+                        #body
                     }
                 }
             } else {
-                let ty = &f.ty;
-                let auto_doc = format!("Returns a reference to the `{}` value.", name_str);
+                let auto_doc = config.accessor_doc(
+                    f,
+                    &getter_name,
+                    format!("Returns a reference to the `{}` value.", name_str),
+                );
                 let doc_attr = format_method_doc(&auto_doc, &field_docs);
-                quote! {
-                    #doc_attr
-                    #vis fn #getter_name(&self) -> &#ty {
+                let body = no_panic_body(
+                    struct_name,
+                    config,
+                    &getter_name,
+                    quote! {
+                        #count
                         match ::structible::BackingMap::get(&self.inner, &#field_enum::#variant) {
-                            Some(#value_enum::#variant(v)) => v,
-                            _ => panic!("required field `{}` not present", stringify!(#name)),
+                            Some(#value_enum::#variant(v)) => #borrow,
+                            _ => #missing,
                         }
+                    },
+                );
+                quote! {
+                    #doc_attr
+                    #(#method_attrs)*
+                    #vis fn #getter_name(&self) -> &#target {
+                        #body
                     }
                 }
+            };
+            if let Some(raw) = &f.config.transparent {
+                let convert_name = f.getter_name();
+                let (ret, convert) = if f.has_optional_getter() {
+                    (
+                        quote! { Option<#raw> },
+                        quote! {
+                            self.#getter_name()
+                                .map(|v| ::std::convert::Into::into(::std::clone::Clone::clone(v)))
+                        },
+                    )
+                } else {
+                    (
+                        quote! { #raw },
+                        quote! {
+                            ::std::convert::Into::into(::std::clone::Clone::clone(self.#getter_name()))
+                        },
+                    )
+                };
+                let convert_doc = format_method_doc(
+                    &config.accessor_doc(
+                        f,
+                        &convert_name,
+                        format!(
+                            "Returns the `{}` value converted out of its newtype{}.",
+                            name_str,
+                            if f.has_optional_getter() { ", if present" } else { "" }
+                        ),
+                    ),
+                    &field_docs,
+                );
+                return quote! {
+                    #getter
+
+                    #convert_doc
+                    #(#method_attrs)*
+                    #vis fn #convert_name(&self) -> #ret {
+                        #convert
+                    }
+                };
+            }
+            if !f.is_weak() {
+                return getter;
+            }
+
+            let inner_ty = &f.inner_ty;
+            let upgrade_name = f.getter_name();
+            let upgrade = if f.has_optional_getter() {
+                quote! { self.#getter_name().and_then(::structible::Upgrade::upgrade) }
+            } else {
+                quote! { ::structible::Upgrade::upgrade(self.#getter_name()) }
+            };
+            let upgrade_doc = format_method_doc(
+                &config.accessor_doc(
+                    f,
+                    &upgrade_name,
+                    format!(
+                        "Returns a strong reference to the `{}` value, or `None` if it is absent or no longer alive.",
+                        name_str
+                    ),
+                ),
+                &field_docs,
+            );
+            quote! {
+                #getter
+
+                #upgrade_doc
+                #(#method_attrs)*
+                #vis fn #upgrade_name(&self) -> Option<<#inner_ty as ::structible::Upgrade>::Strong> {
+                    #upgrade
+                }
             }
         })
         .collect()
@@ -927,52 +1997,129 @@ fn generate_getters(
 fn generate_getters_mut(
     struct_name: &Ident,
     fields: &[FieldInfo],
+    config: &StructibleConfig,
     _generics: &Generics,
 ) -> Vec<TokenStream> {
-    let field_enum = field_enum_name(struct_name);
+    // Writes through a mutable reference can't be recorded or stamped
+    if !config.has_mut_getters() {
+        return Vec::new();
+    }
+
+    let field_enum = field_enum_type(struct_name, config);
     let value_enum = value_enum_name(struct_name);
 
     fields
         .iter()
-        .filter(|f| !f.is_unknown_field())
+        .filter(|f| !f.is_unknown_field() && !f.is_lazy())
         .map(|f| {
             let name = &f.name;
-            let getter_mut_name = f
-                .config
-                .get_mut
-                .clone()
-                .unwrap_or_else(|| format_ident!("{}_mut", name));
+            let missing = missing_required(config, name);
+            let getter_mut_name = f.getter_mut_name();
             let variant = to_pascal_case(name);
-            let vis = &f.vis;
+            let vis = f.setter_vis();
             let field_docs = extract_doc_comments(&f.attrs);
+            let method_attrs = f.method_attrs();
 
-            let name_str = name.to_string();
+            let name_str = name.unraw().to_string();
+            let count = read_counter(config, name);
             if f.is_optional {
                 let inner_ty = &f.inner_ty;
-                let auto_doc = format!(
-                    "Returns a mutable reference to the `{}` value if present.",
-                    name_str
+                let auto_doc = config.accessor_doc(
+                    f,
+                    &getter_mut_name,
+                    format!(
+                        "Returns a mutable reference to the `{}` value if present.",
+                        name_str
+                    ),
                 );
                 let doc_attr = format_method_doc(&auto_doc, &field_docs);
+                let expired_return =
+                    expired_guard(config, quote! { #field_enum::#variant }, quote! { None });
+
+                let or_default_name = format_ident!("{}_or_default_mut", f.getter_name());
+                let auto_doc = config.accessor_doc(
+                    f,
+                    &or_default_name,
+                    format!(
+                        "Returns a mutable reference to the `{}` value, inserting its default if absent.{}",
+                        name_str,
+                        if config.no_panic {
+                            "\n\n`None` means the backing map didn't keep the inserted value."
+                        } else {
+                            ""
+                        }
+                    ),
+                );
+                let or_default_doc = format_method_doc(&auto_doc, &field_docs);
+                let value_mut = value_or_default_mut(struct_name, f, config);
+                let or_default_ty = if config.no_panic {
+                    quote! { Option<&mut #inner_ty> }
+                } else {
+                    quote! { &mut #inner_ty }
+                };
+                // The higher-ranked bound is only checked where the method is called,
+                // so it can name concrete types that don't implement `Default`
+                let or_default = quote! {
+                    #or_default_doc
+                    #(#method_attrs)*
+                    #vis fn #or_default_name(&mut self) -> #or_default_ty
+                    where
+                        for<'__structible> #inner_ty: ::std::default::Default,
+                    {
+                        #count
+                        #value_mut
+                    }
+                };
                 quote! {
                     #doc_attr
+                    #(#method_attrs)*
                     #vis fn #getter_mut_name(&mut self) -> Option<&mut #inner_ty> {
+                        #count
+                        #expired_return
                         match ::structible::BackingMap::get_mut(&mut self.inner, &#field_enum::#variant) {
                             Some(#value_enum::#variant(v)) => Some(v),
                             _ => None,
                         }
                     }
+
+                    #or_default
                 }
             } else {
                 let ty = &f.ty;
-                let auto_doc = format!("Returns a mutable reference to the `{}` value.", name_str);
+                let auto_doc = config.accessor_doc(
+                    f,
+                    &getter_mut_name,
+                    if config.no_panic {
+                        format!(
+                            "Returns a mutable reference to the `{}` value, or `None` if it is missing.",
+                            name_str
+                        )
+                    } else {
+                        format!("Returns a mutable reference to the `{}` value.", name_str)
+                    },
+                );
                 let doc_attr = format_method_doc(&auto_doc, &field_docs);
+                if config.no_panic {
+                    return quote! {
+                        #doc_attr
+                        #(#method_attrs)*
+                        #vis fn #getter_mut_name(&mut self) -> Option<&mut #ty> {
+                            #count
+                            match ::structible::BackingMap::get_mut(&mut self.inner, &#field_enum::#variant) {
+                                Some(#value_enum::#variant(v)) => Some(v),
+                                _ => None,
+                            }
+                        }
+                    };
+                }
                 quote! {
                     #doc_attr
+                    #(#method_attrs)*
                     #vis fn #getter_mut_name(&mut self) -> &mut #ty {
+                        #count
                         match ::structible::BackingMap::get_mut(&mut self.inner, &#field_enum::#variant) {
                             Some(#value_enum::#variant(v)) => v,
-                            _ => panic!("required field `{}` not present", stringify!(#name)),
+                            _ => #missing,
                         }
                     }
                 }
@@ -981,12 +2128,158 @@ fn generate_getters_mut(
         .collect()
 }
 
+/// Returns an expression diverging because the required field `name` is absent:
+/// a call to the struct's `on_missing_required` hook, or a panic without one.
+pub fn missing_required(config: &StructibleConfig, name: &Ident) -> TokenStream {
+    match &config.on_missing_required {
+        Some(hook) => {
+            let name_str = name.unraw().to_string();
+            quote! { #hook(#name_str) }
+        }
+        None => quote! { panic!("required field `{}` not present", stringify!(#name)) },
+    }
+}
+
+/// Checks that a struct with `no_panic` generates no method that panics.
+///
+/// Everything else returns `None` or does nothing where it would panic, but
+/// `apply_changes` panics on changes pairing a field with another field's value,
+/// the getters of `ttl` structs read the clock, `builder = panic` and
+/// `on_missing_required` diverge by design, and typestate builders, `ffi`, lazy
+/// getters, and the `collection`, `string`, and `numeric` helpers expect a value
+/// they can't do without.
+pub fn check_no_panic_config(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+) -> syn::Result<()> {
+    if !config.no_panic {
+        return Ok(());
+    }
+    let conflicts = [
+        (config.track_changes, "track_changes"),
+        (config.ttl, "ttl"),
+        (config.ffi, "ffi"),
+        (
+            config.builder == Some(BuilderMode::Panicking),
+            "builder = panic",
+        ),
+        (
+            config.builder == Some(BuilderMode::Typestate),
+            "builder = typestate",
+        ),
+        (config.on_missing_required.is_some(), "on_missing_required"),
+    ];
+    if let Some((_, name)) = conflicts.iter().find(|(used, _)| *used) {
+        return Err(syn::Error::new_spanned(
+            struct_name,
+            format!("`no_panic` cannot be combined with `{}`", name),
+        ));
+    }
+    for f in fields {
+        let conflicts = [
+            (f.is_lazy(), "lazy"),
+            (f.config.collection, "collection"),
+            (f.config.string, "string"),
+            (f.config.numeric, "numeric"),
+        ];
+        if let Some((_, name)) = conflicts.iter().find(|(used, _)| *used) {
+            return Err(syn::Error::new_spanned(
+                &f.name,
+                format!("`{}` fields are not supported with `no_panic`", name),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Returns the body of the `&self` getter `getter_name`, wrapped in
+/// `structible::__no_panic!` if `no_panic` is set, so that the `no-panic` feature
+/// can check that it doesn't panic.
+fn no_panic_body(
+    struct_name: &Ident,
+    config: &StructibleConfig,
+    getter_name: &Ident,
+    body: TokenStream,
+) -> TokenStream {
+    if !config.no_panic {
+        return body;
+    }
+    let name_str = format!("{}::{}", struct_name, getter_name.unraw());
+    quote! {
+        ::structible::__no_panic!(#name_str, move || { #body })
+    }
+}
+
+/// Returns a block evaluating to a mutable reference to the value of `f` in `self`.
+///
+/// An absent optional field, or an expired one with `ttl`, is first set to its
+/// default through `insert_expr`, so the block requires the field's type to
+/// implement `Default`; a required field that isn't present panics. With
+/// `no_panic`, the block evaluates to an `Option` instead, which is `None` where
+/// it would panic.
+pub fn value_or_default_mut(
+    struct_name: &Ident,
+    f: &FieldInfo,
+    config: &StructibleConfig,
+) -> TokenStream {
+    let name = &f.name;
+    let missing = missing_required(config, name);
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+    let variant = to_pascal_case(name);
+    let (found, not_found) = if config.no_panic {
+        (quote! { Some(v) }, quote! { None })
+    } else if f.is_optional {
+        (quote! { v }, quote! { unreachable!() })
+    } else {
+        (quote! { v }, missing)
+    };
+    let insert = if f.is_optional {
+        // Expired values are replaced like absent ones
+        let expired = if config.ttl {
+            quote! { self.__structible_expired(&#field_enum::#variant) || }
+        } else {
+            quote! {}
+        };
+        let insert = insert_expr(
+            config,
+            quote! { self },
+            quote! { #field_enum::#variant },
+            quote! { #value_enum::#variant(::std::default::Default::default()) },
+        );
+        quote! {
+            if #expired !::std::matches!(
+                ::structible::BackingMap::get(&self.inner, &#field_enum::#variant),
+                Some(#value_enum::#variant(_))
+            ) {
+                #insert;
+            }
+        }
+    } else {
+        quote! {}
+    };
+    quote! {
+        {
+            #insert
+            match ::structible::BackingMap::get_mut(&mut self.inner, &#field_enum::#variant) {
+                Some(#value_enum::#variant(v)) => #found,
+                _ => #not_found,
+            }
+        }
+    }
+}
+
 fn generate_setters(
     struct_name: &Ident,
     fields: &[FieldInfo],
+    config: &StructibleConfig,
     _generics: &Generics,
 ) -> Vec<TokenStream> {
-    let field_enum = field_enum_name(struct_name);
+    if config.no_setters {
+        return Vec::new();
+    }
+    let field_enum = field_enum_type(struct_name, config);
     let value_enum = value_enum_name(struct_name);
 
     fields
@@ -994,24 +2287,198 @@ fn generate_setters(
         .filter(|f| !f.is_unknown_field())
         .map(|f| {
             let name = &f.name;
-            let setter_name = f
-                .config
-                .set
-                .clone()
-                .unwrap_or_else(|| format_ident!("set_{}", name));
+            let setter_name = f.setter_name();
             let variant = to_pascal_case(name);
-            let vis = &f.vis;
+            let vis = f.setter_vis();
             let field_docs = extract_doc_comments(&f.attrs);
+            let method_attrs = f.method_attrs();
 
-            let name_str = name.to_string();
-            let auto_doc = format!("Sets the `{}` value.", name_str);
+            let name_str = name.unraw().to_string();
+            let mut auto_doc =
+                config.accessor_doc(f, &setter_name, format!("Sets the `{}` value.", name_str));
+            auto_doc.extend(unchecked_requires_note(f, fields, false));
             let doc_attr = format_method_doc(&auto_doc, &field_docs);
-            // Use inner_ty for optional fields, ty for required fields
+            // Use inner_ty for optional fields, ty for required fields; transparent
+            // fields accept anything converting into their newtype
             let value_ty = if f.is_optional { &f.inner_ty } else { &f.ty };
+            let (value_ty, value) = if f.is_transparent() {
+                (
+                    quote! { impl ::std::convert::Into<#value_ty> },
+                    quote! { ::std::convert::Into::into(value) },
+                )
+            } else {
+                (quote! { #value_ty }, quote! { value })
+            };
+            let insert = insert_expr(
+                config,
+                quote! { self },
+                quote! { #field_enum::#variant },
+                quote! { #value_enum::#variant(#value) },
+            );
             quote! {
                 #doc_attr
+                #(#method_attrs)*
                 #vis fn #setter_name(&mut self, value: #value_ty) {
-                    ::structible::BackingMap::insert(&mut self.inner, #field_enum::#variant, #value_enum::#variant(value));
+                    #insert;
+                }
+            }
+        })
+        .collect()
+}
+
+/// Generate `take_<field>_scoped()` for required fields.
+///
+/// The value is removed without recording the removal, and put back through
+/// `insert_expr` when the returned guard is dropped, so the put-back counts as a set.
+fn generate_scoped_takes(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+) -> Vec<TokenStream> {
+    if config.no_setters || config.minimal {
+        return Vec::new();
+    }
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+
+    fields
+        .iter()
+        .filter(|f| !f.is_optional && !f.is_unknown_field())
+        .map(|f| {
+            let name = &f.name;
+            let missing = missing_required(config, name);
+            let take_name = format_ident!("take_{}_scoped", name);
+            let variant = to_pascal_case(name);
+            let vis = f.setter_vis();
+            let ty = &f.ty;
+            let method_attrs = f.method_attrs();
+            let doc = format!(
+                "Takes the `{}` value out of the struct until the returned guard is dropped, which puts the guard's value back.",
+                name.unraw()
+            );
+            let insert = insert_expr(
+                config,
+                quote! { this },
+                quote! { #field_enum::#variant },
+                quote! { #value_enum::#variant(value) },
+            );
+            let guard = quote! {
+                ::structible::FieldGuard::__new(self, value, |this: &mut Self, value: #ty| {
+                    #insert;
+                })
+            };
+            // A missing value has no guard to take it, rather than panicking
+            if config.no_panic {
+                let doc = format!("{}\n\nReturns `None` if the value is missing.", doc);
+                return quote! {
+                    #[doc = #doc]
+                    #(#method_attrs)*
+                    #vis fn #take_name(&mut self) -> Option<::structible::FieldGuard<'_, Self, #ty>> {
+                        let value = match ::structible::BackingMap::remove(&mut self.inner, &#field_enum::#variant) {
+                            Some(#value_enum::#variant(v)) => v,
+                            _ => return None,
+                        };
+                        Some(#guard)
+                    }
+                };
+            }
+            quote! {
+                #[doc = #doc]
+                #(#method_attrs)*
+                #vis fn #take_name(&mut self) -> ::structible::FieldGuard<'_, Self, #ty> {
+                    let value = match ::structible::BackingMap::remove(&mut self.inner, &#field_enum::#variant) {
+                        Some(#value_enum::#variant(v)) => v,
+                        _ => #missing,
+                    };
+                    #guard
+                }
+            }
+        })
+        .collect()
+}
+
+/// Generate `map_<field>()`, transforming a field's value in place by ownership.
+///
+/// Like scoped takes, the value is removed without recording the removal, and the
+/// transformed value is inserted through `insert_expr`.
+fn generate_mappers(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+) -> Vec<TokenStream> {
+    if config.no_setters || config.minimal {
+        return Vec::new();
+    }
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+
+    fields
+        .iter()
+        .filter(|f| !f.is_unknown_field())
+        .map(|f| {
+            let name = &f.name;
+            let missing = missing_required(config, name);
+            let map_name = format_ident!("map_{}", name);
+            let variant = to_pascal_case(name);
+            let vis = f.setter_vis();
+            let method_attrs = f.method_attrs();
+            let insert = insert_expr(
+                config,
+                quote! { self },
+                quote! { #field_enum::#variant },
+                quote! { #value_enum::#variant(f(value)) },
+            );
+            let remove = quote! {
+                ::structible::BackingMap::remove(&mut self.inner, &#field_enum::#variant)
+            };
+            if f.is_optional {
+                let inner_ty = &f.inner_ty;
+                let doc = format!(
+                    "Replaces the `{}` value, if present, with `f` applied to it.\n\nIf `f` panics, the field is left absent.",
+                    name.unraw()
+                );
+                let expired_guard = expired_guard(config, quote! { #field_enum::#variant }, quote! {});
+                quote! {
+                    #[doc = #doc]
+                    #(#method_attrs)*
+                    #vis fn #map_name(&mut self, f: impl ::std::ops::FnOnce(#inner_ty) -> #inner_ty) {
+                        #expired_guard
+                        if let Some(#value_enum::#variant(value)) = #remove {
+                            #insert;
+                        }
+                    }
+                }
+            } else if config.no_panic {
+                let ty = &f.ty;
+                let doc = format!(
+                    "Replaces the `{}` value with `f` applied to it, unless it is missing.\n\nIf `f` panics, the required field is left absent; use `take_{}_scoped()` if that matters.",
+                    name.unraw(), name
+                );
+                quote! {
+                    #[doc = #doc]
+                    #(#method_attrs)*
+                    #vis fn #map_name(&mut self, f: impl ::std::ops::FnOnce(#ty) -> #ty) {
+                        if let Some(#value_enum::#variant(value)) = #remove {
+                            #insert;
+                        }
+                    }
+                }
+            } else {
+                let ty = &f.ty;
+                let doc = format!(
+                    "Replaces the `{}` value with `f` applied to it.\n\nIf `f` panics, the required field is left absent; use `take_{}_scoped()` if that matters.",
+                    name.unraw(), name
+                );
+                quote! {
+                    #[doc = #doc]
+                    #(#method_attrs)*
+                    #vis fn #map_name(&mut self, f: impl ::std::ops::FnOnce(#ty) -> #ty) {
+                        let value = match #remove {
+                            Some(#value_enum::#variant(v)) => v,
+                            _ => #missing,
+                        };
+                        #insert;
+                    }
                 }
             }
         })
@@ -1022,19 +2489,23 @@ fn generate_setters(
 fn generate_unknown_field_methods(
     struct_name: &Ident,
     fields: &[FieldInfo],
+    config: &StructibleConfig,
     _generics: &Generics,
 ) -> TokenStream {
     let Some(unknown_field) = fields.iter().find(|f| f.is_unknown_field()) else {
         return quote! {};
     };
 
-    let field_enum = field_enum_name(struct_name);
+    let field_enum = field_enum_type(struct_name, config);
     let value_enum = value_enum_name(struct_name);
     let name = &unknown_field.name;
     let key_type = unknown_field.unknown_key_type().unwrap();
     let value_type = &unknown_field.inner_ty;
-    let vis = &unknown_field.vis;
+    let get_vis = unknown_field.getter_vis();
+    let set_vis = unknown_field.setter_vis();
+    let remove_vis = unknown_field.remover_vis();
     let field_docs = extract_doc_comments(&unknown_field.attrs);
+    let method_attrs = unknown_field.method_attrs();
 
     // Method names derived from field name
     let insert_method = format_ident!("insert_{}", name);
@@ -1044,7 +2515,7 @@ fn generate_unknown_field_methods(
     let iter_method = format_ident!("{}_iter", name);
     let iter_mut_method = format_ident!("{}_iter_mut", name);
 
-    let name_str = name.to_string();
+    let name_str = name.unraw().to_string();
     let insert_doc = format_method_doc(
         &format!(
             "Inserts an unknown `{}` field with the given key and value. Returns the previous value if the key was already present.",
@@ -1082,25 +2553,173 @@ fn generate_unknown_field_methods(
         &field_docs,
     );
 
-    quote! {
-        #insert_doc
-        #vis fn #insert_method(&mut self, key: #key_type, value: #value_type) -> Option<#value_type> {
-            match ::structible::BackingMap::insert(
-                &mut self.inner,
-                #field_enum::Unknown(key),
-                #value_enum::Unknown(value)
-            ) {
-                Some(#value_enum::Unknown(v)) => Some(v),
-                _ => None,
+    let insert = insert_expr(
+        config,
+        quote! { self },
+        quote! { #field_enum::Unknown(key) },
+        quote! { #value_enum::Unknown(value) },
+    );
+    let remove = remove_expr(config, quote! { #field_enum::Unknown(owned_key) });
+    let owned_key = unknown_field.normalized_key(quote! { key.to_owned() });
+    let normalize_insert = unknown_field
+        .config
+        .normalize
+        .as_ref()
+        .map(|normalize| quote! { let key = #normalize(key); });
+    let (lookup_bound, normalize_lookup) = unknown_field.normalize_lookup();
+    let count = read_counter(config, name);
+
+    // Writes through mutable references can't be recorded or stamped
+    let mut_methods = if !config.has_mut_getters() {
+        quote! {}
+    } else {
+        quote! {
+            #get_mut_doc
+
+            #(#method_attrs)*
+            #set_vis fn #get_mut_method<__Q>(&mut self, key: &__Q) -> Option<&mut #value_type>
+            where
+                #key_type: ::std::borrow::Borrow<__Q>,
+                __Q: #lookup_bound ::std::hash::Hash + ::std::cmp::Eq + ?Sized,
+            {
+                #count
+                #normalize_lookup
+                for (k, v) in ::structible::IterableMap::iter_mut(&mut self.inner) {
+                    if let #field_enum::Unknown(stored_key) = k {
+                        if <#key_type as ::std::borrow::Borrow<__Q>>::borrow(stored_key) == key {
+                            if let #value_enum::Unknown(val) = v {
+                                return Some(val);
+                            }
+                        }
+                    }
+                }
+                None
+            }
+
+            #iter_mut_doc
+
+            #(#method_attrs)*
+            #set_vis fn #iter_mut_method(&mut self) -> impl Iterator<Item = (&#key_type, &mut #value_type)> {
+                ::structible::IterableMap::iter_mut(&mut self.inner).filter_map(|(k, v)| {
+                    match (k, v) {
+                        (#field_enum::Unknown(key), #value_enum::Unknown(value)) => Some((key, value)),
+                        _ => None,
+                    }
+                })
             }
         }
+    };
+
+    let multi_methods = unknown_field.multi_value_type().map(|element_type| {
+        let all_method = format_ident!("{}_all", name);
+        let append_method = format_ident!("append_{}", name);
+        let all_doc = format_method_doc(
+            &format!(
+                "Returns all `{}` values for the given key, in the order they were appended.",
+                name_str
+            ),
+            &field_docs,
+        );
+        let append_doc = format_method_doc(
+            &format!(
+                "Appends a value to the `{}` values for the given key.",
+                name_str
+            ),
+            &field_docs,
+        );
+        let key = unknown_field.normalized_key(quote! { key });
+        let append_insert = insert_expr(
+            config,
+            quote! { self },
+            quote! { #field_enum::Unknown(key) },
+            quote! { #value_enum::Unknown(values) },
+        );
+        let append = if config.no_setters {
+            quote! {}
+        } else {
+            quote! {
+                #append_doc
+                #(#method_attrs)*
+                #set_vis fn #append_method(&mut self, key: #key_type, value: #element_type) {
+                    let key: #key_type = #key;
+                    let mut values = match ::structible::BackingMap::remove(
+                        &mut self.inner,
+                        &#field_enum::Unknown(::std::clone::Clone::clone(&key)),
+                    ) {
+                        Some(#value_enum::Unknown(values)) => values,
+                        _ => ::std::vec::Vec::new(),
+                    };
+                    values.push(value);
+                    #append_insert;
+                }
+            }
+        };
+        quote! {
+            #all_doc
+            #(#method_attrs)*
+            #get_vis fn #all_method<__Q>(&self, key: &__Q) -> &[#element_type]
+            where
+                #key_type: ::std::borrow::Borrow<__Q>,
+                __Q: #lookup_bound ::std::hash::Hash + ::std::cmp::Eq + ?Sized,
+            {
+                self.#get_method(key).map_or(&[], |values| values.as_slice())
+            }
+
+            #append
+        }
+    });
+
+    // A routing insert is generated with the routing function, see `promote.rs`
+    let insert_method = if config.no_setters || unknown_field.config.promote_known {
+        quote! {}
+    } else {
+        quote! {
+            #insert_doc
+            #(#method_attrs)*
+            #set_vis fn #insert_method(&mut self, key: #key_type, value: #value_type) -> Option<#value_type> {
+                #normalize_insert
+                match #insert {
+                    Some(#value_enum::Unknown(v)) => Some(v),
+                    _ => None,
+                }
+            }
+        }
+    };
+
+    let remove_method = if config.no_removers {
+        quote! {}
+    } else {
+        quote! {
+            #remove_doc
+
+            #(#method_attrs)*
+            #remove_vis fn #remove_method<__Q>(&mut self, key: &__Q) -> Option<#value_type>
+            where
+                #key_type: ::std::borrow::Borrow<__Q>,
+                __Q: ::std::borrow::ToOwned<Owned = #key_type> + ::std::hash::Hash + ::std::cmp::Eq + ?Sized,
+            {
+                let owned_key: #key_type = #owned_key;
+                match #remove {
+                    Some(#value_enum::Unknown(v)) => Some(v),
+                    _ => None,
+                }
+            }
+        }
+    };
+
+    quote! {
+        #insert_method
 
         #get_doc
-        #vis fn #get_method<__Q>(&self, key: &__Q) -> Option<&#value_type>
+
+        #(#method_attrs)*
+        #get_vis fn #get_method<__Q>(&self, key: &__Q) -> Option<&#value_type>
         where
             #key_type: ::std::borrow::Borrow<__Q>,
-            __Q: ::std::hash::Hash + ::std::cmp::Eq + ?Sized,
+            __Q: #lookup_bound ::std::hash::Hash + ::std::cmp::Eq + ?Sized,
         {
+            #count
+            #normalize_lookup
             // We need to iterate and find because the map's get requires the exact key type
             // For borrowed lookups, we compare via Borrow
             for (k, v) in ::structible::IterableMap::iter(&self.inner) {
@@ -1115,39 +2734,14 @@ fn generate_unknown_field_methods(
             None
         }
 
-        #get_mut_doc
-        #vis fn #get_mut_method<__Q>(&mut self, key: &__Q) -> Option<&mut #value_type>
-        where
-            #key_type: ::std::borrow::Borrow<__Q>,
-            __Q: ::std::hash::Hash + ::std::cmp::Eq + ?Sized,
-        {
-            for (k, v) in ::structible::IterableMap::iter_mut(&mut self.inner) {
-                if let #field_enum::Unknown(stored_key) = k {
-                    if <#key_type as ::std::borrow::Borrow<__Q>>::borrow(stored_key) == key {
-                        if let #value_enum::Unknown(val) = v {
-                            return Some(val);
-                        }
-                    }
-                }
-            }
-            None
-        }
+        #remove_method
 
-        #remove_doc
-        #vis fn #remove_method<__Q>(&mut self, key: &__Q) -> Option<#value_type>
-        where
-            #key_type: ::std::borrow::Borrow<__Q>,
-            __Q: ::std::borrow::ToOwned<Owned = #key_type> + ::std::hash::Hash + ::std::cmp::Eq + ?Sized,
-        {
-            let owned_key: #key_type = key.to_owned();
-            match ::structible::BackingMap::remove(&mut self.inner, &#field_enum::Unknown(owned_key)) {
-                Some(#value_enum::Unknown(v)) => Some(v),
-                _ => None,
-            }
-        }
+        #multi_methods
 
         #iter_doc
-        #vis fn #iter_method(&self) -> impl Iterator<Item = (&#key_type, &#value_type)> {
+
+        #(#method_attrs)*
+        #get_vis fn #iter_method(&self) -> impl Iterator<Item = (&#key_type, &#value_type)> {
             ::structible::IterableMap::iter(&self.inner).filter_map(|(k, v)| {
                 match (k, v) {
                     (#field_enum::Unknown(key), #value_enum::Unknown(value)) => Some((key, value)),
@@ -1156,24 +2750,20 @@ fn generate_unknown_field_methods(
             })
         }
 
-        #iter_mut_doc
-        #vis fn #iter_mut_method(&mut self) -> impl Iterator<Item = (&#key_type, &mut #value_type)> {
-            ::structible::IterableMap::iter_mut(&mut self.inner).filter_map(|(k, v)| {
-                match (k, v) {
-                    (#field_enum::Unknown(key), #value_enum::Unknown(value)) => Some((key, value)),
-                    _ => None,
-                }
-            })
-        }
+        #mut_methods
     }
 }
 
 fn generate_removers(
     struct_name: &Ident,
     fields: &[FieldInfo],
+    config: &StructibleConfig,
     _generics: &Generics,
 ) -> Vec<TokenStream> {
-    let field_enum = field_enum_name(struct_name);
+    if config.no_removers {
+        return Vec::new();
+    }
+    let field_enum = field_enum_type(struct_name, config);
     let value_enum = value_enum_name(struct_name);
 
     // Only optional fields can be removed, and skip unknown fields
@@ -1182,36 +2772,207 @@ fn generate_removers(
         .filter(|f| f.is_optional && !f.is_unknown_field())
         .map(|f| {
             let name = &f.name;
-            let remover_name = f
-                .config
-                .remove
-                .clone()
-                .unwrap_or_else(|| format_ident!("remove_{}", name));
+            let remover_name = f.remover_name();
             let variant = to_pascal_case(name);
             let inner_ty = &f.inner_ty;
-            let vis = &f.vis;
+            let vis = f.remover_vis();
             let field_docs = extract_doc_comments(&f.attrs);
-
-            let name_str = name.to_string();
-            let auto_doc = format!(
-                "Removes the `{}` field and returns the value if it was present.",
-                name_str
+            let method_attrs = f.method_attrs();
+            let remove = remove_expr(config, quote! { #field_enum::#variant });
+            let bury = bury_stmt(config, quote! { #field_enum::#variant });
+
+            let name_str = name.unraw().to_string();
+            let mut auto_doc = config.accessor_doc(
+                f,
+                &remover_name,
+                format!(
+                    "Removes the `{}` field and returns the value if it was present.",
+                    name_str
+                ),
             );
+            auto_doc.extend(unchecked_requires_note(f, fields, true));
             let doc_attr = format_method_doc(&auto_doc, &field_docs);
 
-            quote! {
-                #doc_attr
-                #vis fn #remover_name(&mut self) -> Option<#inner_ty> {
-                    match ::structible::BackingMap::remove(&mut self.inner, &#field_enum::#variant) {
+            // An expired value is removed too, but isn't returned
+            let body = if config.ttl {
+                quote! {
+                    let expired = self.__structible_expired(&#field_enum::#variant);
+                    match #remove {
+                        Some(#value_enum::#variant(v)) if !expired => Some(v),
+                        _ => None,
+                    }
+                }
+            } else {
+                quote! {
+                    match #remove {
                         Some(#value_enum::#variant(v)) => Some(v),
                         _ => None,
                     }
                 }
+            };
+
+            quote! {
+                #doc_attr
+                #(#method_attrs)*
+                #vis fn #remover_name(&mut self) -> Option<#inner_ty> {
+                    #bury
+                    #body
+                }
             }
         })
         .collect()
 }
 
+/// Generate the `<getter>_or_err()` of each optional field declaring a
+/// `required_context`.
+///
+/// Each one calls the field's getter, so upgraded weak references and transparent
+/// fields are returned as their getter returns them, and reports an absent value
+/// as `ValidationError::Absent` with the declared context.
+fn generate_or_err_getters(fields: &[FieldInfo]) -> Vec<TokenStream> {
+    fields
+        .iter()
+        .filter_map(|f| {
+            let context = f.config.required_context.as_ref()?;
+            let getter = f.getter_name();
+            let method_name = format_ident!("{}_or_err", getter);
+            let vis = f.getter_vis();
+            let method_attrs = f.method_attrs();
+            let name_str = f.name.unraw().to_string();
+            let ty = if f.is_weak() {
+                let inner_ty = &f.inner_ty;
+                quote! { <#inner_ty as ::structible::Upgrade>::Strong }
+            } else if let Some(raw) = &f.config.transparent {
+                quote! { #raw }
+            } else {
+                let target = f.getter_target();
+                quote! { &#target }
+            };
+            let doc_attr = format_method_doc(
+                &format!(
+                    "Returns the `{}` value like `{}()`, or `ValidationError::Absent` if it is not present.",
+                    name_str, getter
+                ),
+                &extract_doc_comments(&f.attrs),
+            );
+            Some(quote! {
+                #doc_attr
+                #(#method_attrs)*
+                #vis fn #method_name(&self) -> ::std::result::Result<#ty, ::structible::ValidationError> {
+                    self.#getter().ok_or(::structible::ValidationError::Absent {
+                        field: #name_str,
+                        context: #context,
+                    })
+                }
+            })
+        })
+        .collect()
+}
+
+/// Generate deprecated accessors for each `alias` of a renamed field.
+///
+/// Every alias gets the default-named getter, mutable getter, setter, and (for
+/// optional fields) remover, following the struct's accessor prefixes, each
+/// delegating to the accessor of the current name.
+fn generate_alias_accessors(fields: &[FieldInfo], config: &StructibleConfig) -> Vec<TokenStream> {
+    fields
+        .iter()
+        .filter(|f| !f.is_unknown_field())
+        .flat_map(|f| {
+            f.config.aliases.iter().map(move |alias| {
+                let get_vis = f.getter_vis();
+                let set_vis = f.setter_vis();
+                let remove_vis = f.remover_vis();
+                let note = format!("renamed to `{}`", f.name.unraw());
+                // Alias accessors carry their own `#[deprecated]`, so only plain lint
+                // attributes are forwarded to them.
+                let lint_attrs: Vec<_> = f
+                    .method_attrs()
+                    .into_iter()
+                    .filter(|a| !a.path().is_ident("deprecated") && !a.path().is_ident("cfg_attr"))
+                    .collect();
+
+                let getter = f.getter_name();
+                let getter_mut = f.getter_mut_name();
+                let setter = f.setter_name();
+                let alias_getter = config.getter_name(alias);
+                let alias_getter_mut = config.getter_mut_name(alias);
+                let alias_setter = config.setter_name(alias);
+
+                let getter_doc = format!("Deprecated alias for [`Self::{}`].", getter);
+                let getter_mut_doc = format!("Deprecated alias for [`Self::{}`].", getter_mut);
+                let setter_doc = format!("Deprecated alias for [`Self::{}`].", setter);
+
+                let inner_ty = &f.inner_ty;
+                let mut_ty = if f.has_optional_getter() {
+                    quote! { Option<&mut #inner_ty> }
+                } else {
+                    quote! { &mut #inner_ty }
+                };
+                let ref_ty = f.getter_type();
+                let value_ty = f.setter_type();
+
+                let setter = if config.no_setters {
+                    quote! {}
+                } else {
+                    quote! {
+                        #[doc = #setter_doc]
+                        #[deprecated(note = #note)]
+                        #(#lint_attrs)*
+                        #set_vis fn #alias_setter(&mut self, value: #value_ty) {
+                            self.#setter(value)
+                        }
+                    }
+                };
+
+                // Tracked and stamped structs have no mutable getters to delegate to
+                let getter_mut = if !config.has_mut_getters() {
+                    quote! {}
+                } else {
+                    quote! {
+                        #[doc = #getter_mut_doc]
+                        #[deprecated(note = #note)]
+                        #(#lint_attrs)*
+                        #set_vis fn #alias_getter_mut(&mut self) -> #mut_ty {
+                            self.#getter_mut()
+                        }
+                    }
+                };
+
+                let remover = if f.is_optional && !config.no_removers {
+                    let inner_ty = &f.inner_ty;
+                    let remover = f.remover_name();
+                    let alias_remover = config.remover_name(alias);
+                    let remover_doc = format!("Deprecated alias for [`Self::{}`].", remover);
+                    quote! {
+                        #[doc = #remover_doc]
+                        #[deprecated(note = #note)]
+                        #(#lint_attrs)*
+                        #remove_vis fn #alias_remover(&mut self) -> Option<#inner_ty> {
+                            self.#remover()
+                        }
+                    }
+                } else {
+                    quote! {}
+                };
+
+                quote! {
+                    #[doc = #getter_doc]
+                    #[deprecated(note = #note)]
+                    #(#lint_attrs)*
+                    #get_vis fn #alias_getter(&self) -> #ref_ty {
+                        self.#getter()
+                    }
+
+                    #getter_mut
+                    #setter
+                    #remover
+                }
+            })
+        })
+        .collect()
+}
+
 /// Generate the `into_fields` method for full ownership extraction.
 ///
 /// This method consumes the struct and transfers ownership of the inner map
@@ -1219,11 +2980,20 @@ fn generate_removers(
 fn generate_into_fields(
     struct_name: &Ident,
     _fields: &[FieldInfo],
-    _config: &StructibleConfig,
+    config: &StructibleConfig,
     generics: &Generics,
 ) -> TokenStream {
+    if config.minimal {
+        return quote! {};
+    }
     let fields_struct = fields_struct_name(struct_name);
     let (_, ty_generics, _) = generics.split_for_impl();
+    // Expired values read as absent, so they aren't handed over either
+    let (receiver, purge) = if config.ttl {
+        (quote! { mut self }, quote! { self.purge_expired(); })
+    } else {
+        (quote! { self }, quote! {})
+    };
 
     quote! {
         /// Consumes this struct and returns a companion struct for extracting owned values.
@@ -1238,7 +3008,8 @@ fn generate_into_fields(
         /// let name = fields.take_name().expect("required field");
         /// let email = fields.take_email(); // Optional field, may be None
         /// ```
-        pub fn into_fields(self) -> #fields_struct #ty_generics {
+        pub fn into_fields(#receiver) -> #fields_struct #ty_generics {
+            #purge
             #fields_struct { inner: self.inner }
         }
     }
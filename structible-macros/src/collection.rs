@@ -0,0 +1,239 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::{GenericArgument, Ident, PathArguments, Type};
+
+use crate::codegen::value_or_default_mut;
+use crate::instrument::read_counter;
+use crate::parse::{FieldInfo, StructibleConfig};
+
+/// The standard collection a `collection` field stores.
+enum Collection<'a> {
+    /// A `Vec` or `VecDeque` of the given elements, pushed with the given method.
+    Sequence(&'a Type, Ident),
+    /// A `HashSet` or `BTreeSet` of the given elements, which must satisfy the
+    /// given bound.
+    Set(&'a Type, TokenStream),
+    /// A `HashMap` or `BTreeMap` of the given keys, which must satisfy the given
+    /// bound, and values.
+    Map(&'a Type, &'a Type, TokenStream),
+}
+
+impl<'a> Collection<'a> {
+    /// Returns the collection `ty` is, detected from the last segment of its path.
+    ///
+    /// Hashed collections with a custom hasher aren't detected.
+    fn of(ty: &'a Type) -> Option<Self> {
+        let Type::Path(type_path) = ty else {
+            return None;
+        };
+        if type_path.qself.is_some() {
+            return None;
+        }
+        let segment = type_path.path.segments.last()?;
+        let PathArguments::AngleBracketed(args) = &segment.arguments else {
+            return None;
+        };
+        let types: Vec<_> = args
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+            .collect();
+        let hash = quote! { ::std::cmp::Eq + ::std::hash::Hash };
+        let ord = quote! { ::std::cmp::Ord };
+        match (segment.ident.to_string().as_str(), types.as_slice()) {
+            ("Vec", [element]) => Some(Collection::Sequence(element, format_ident!("push"))),
+            ("VecDeque", [element]) => {
+                Some(Collection::Sequence(element, format_ident!("push_back")))
+            }
+            ("HashSet", [element]) => Some(Collection::Set(element, hash)),
+            ("BTreeSet", [element]) => Some(Collection::Set(element, ord)),
+            ("HashMap", [key, value]) => Some(Collection::Map(key, value, hash)),
+            ("BTreeMap", [key, value]) => Some(Collection::Map(key, value, ord)),
+            _ => None,
+        }
+    }
+}
+
+/// Checks that `collection` fields store a standard collection that can be
+/// modified in place.
+pub fn check_collection_config(fields: &[FieldInfo], config: &StructibleConfig) -> syn::Result<()> {
+    for f in fields.iter().filter(|f| f.config.collection) {
+        if f.is_unknown_field() || f.is_lazy() || f.is_weak() || f.is_transparent() {
+            return Err(syn::Error::new_spanned(
+                &f.name,
+                "`collection` is not supported on the unknown fields catch-all, lazy, weak, or transparent fields",
+            ));
+        }
+        if Collection::of(&f.inner_ty).is_none() {
+            return Err(syn::Error::new_spanned(
+                &f.inner_ty,
+                "`collection` requires a `Vec`, `VecDeque`, `HashSet`, `BTreeSet`, `HashMap`, or `BTreeMap` field",
+            ));
+        }
+        if config.is_async {
+            return Err(syn::Error::new_spanned(
+                &f.name,
+                "`collection` is not supported with `async`",
+            ));
+        }
+        // Like mutable getters, the helpers modify the stored collection in place
+        if config.records_writes() {
+            return Err(syn::Error::new_spanned(
+                &f.name,
+                "`collection` is not supported with `track_changes`, `lww`, or `transactional`, which can't record writes in place",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Generate `push_<field>()`, `extend_<field>()`, and `<getter>_contains()` for
+/// fields declaring `collection`.
+///
+/// The writing helpers modify the stored collection in place, first inserting an
+/// empty one if an optional field is absent, and are skipped with `no_setters`.
+/// `<getter>_contains()` reads the collection through the getter, so an absent
+/// field contains nothing. Bounds the collection needs are declared on each method
+/// with a higher-ranked `where` clause, which is only checked where the method is
+/// called.
+pub fn generate_collection_methods(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+) -> Vec<TokenStream> {
+    fields
+        .iter()
+        .filter(|f| f.config.collection)
+        .map(|f| {
+            let name = &f.name;
+            let getter = f.getter_name();
+            let push_name = format_ident!("push_{}", name);
+            let extend_name = format_ident!("extend_{}", name);
+            let contains_name = format_ident!("{}_contains", getter);
+            let get_vis = f.getter_vis();
+            let set_vis = f.setter_vis();
+            let method_attrs = f.method_attrs();
+            let count = read_counter(config, name);
+            let value_mut = value_or_default_mut(struct_name, f, config);
+            let inner_ty = &f.inner_ty;
+            let name_str = name.unraw().to_string();
+
+            // Absent optional fields are created empty
+            let default_bound = if f.is_optional {
+                quote! { for<'__structible> #inner_ty: ::std::default::Default, }
+            } else {
+                quote! {}
+            };
+            let (push, item, contains_ty, contains, bound) = match Collection::of(inner_ty).unwrap()
+            {
+                Collection::Sequence(element, push) => (
+                    quote! {
+                        #set_vis fn #push_name(&mut self, value: #element)
+                        where
+                            #default_bound
+                        {
+                            #count
+                            #value_mut.#push(value);
+                        }
+                    },
+                    quote! { #element },
+                    element,
+                    quote! { contains },
+                    quote! { for<'__structible> #element: ::std::cmp::PartialEq, },
+                ),
+                Collection::Set(element, bound) => (
+                    quote! {
+                        ///
+                        /// Returns whether the value was newly inserted.
+                        #set_vis fn #push_name(&mut self, value: #element) -> bool
+                        where
+                            #default_bound
+                            for<'__structible> #element: #bound,
+                        {
+                            #count
+                            #value_mut.insert(value)
+                        }
+                    },
+                    quote! { #element },
+                    element,
+                    quote! { contains },
+                    quote! { for<'__structible> #element: #bound, },
+                ),
+                Collection::Map(key, value, bound) => (
+                    quote! {
+                        ///
+                        /// Returns the value previously stored under the key, if any.
+                        #set_vis fn #push_name(
+                            &mut self,
+                            key: #key,
+                            value: #value,
+                        ) -> ::std::option::Option<#value>
+                        where
+                            #default_bound
+                            for<'__structible> #key: #bound,
+                        {
+                            #count
+                            #value_mut.insert(key, value)
+                        }
+                    },
+                    quote! { (#key, #value) },
+                    key,
+                    quote! { contains_key },
+                    quote! { for<'__structible> #key: #bound, },
+                ),
+            };
+            let lookup = if f.is_optional {
+                quote! { self.#getter().is_some_and(|c| c.#contains(value)) }
+            } else {
+                quote! { self.#getter().#contains(value) }
+            };
+
+            let push_doc = format!("Adds an element to the `{}` collection.", name_str);
+            let extend_doc = format!(
+                "Adds the elements of an iterator to the `{}` collection.",
+                name_str
+            );
+            let contains_doc = format!(
+                "Returns true if the `{}` collection contains the value.",
+                name_str
+            );
+            let contains = quote! {
+                #[doc = #contains_doc]
+                #(#method_attrs)*
+                #get_vis fn #contains_name(&self, value: &#contains_ty) -> bool
+                where
+                    #bound
+                {
+                    #lookup
+                }
+            };
+            if config.no_setters {
+                return contains;
+            }
+            quote! {
+                #[doc = #push_doc]
+                #(#method_attrs)*
+                #push
+
+                #[doc = #extend_doc]
+                #(#method_attrs)*
+                #set_vis fn #extend_name(
+                    &mut self,
+                    values: impl ::std::iter::IntoIterator<Item = #item>,
+                ) where
+                    #default_bound
+                    for<'__structible> #inner_ty: ::std::iter::Extend<#item>,
+                {
+                    #count
+                    ::std::iter::Extend::extend(#value_mut, values);
+                }
+
+                #contains
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,67 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{Generics, Ident};
+
+use crate::parse::{FieldInfo, StructibleConfig};
+use crate::util::type_text;
+
+/// Generate the `Schema` impl describing the fields, and `compat_report()`.
+///
+/// Types are described as written, so the same type spelled differently (e.g.
+/// through an alias) counts as a retyping. Structs without generics also register
+/// themselves with `structible::registry`, which does nothing unless the
+/// `inventory` feature is enabled.
+pub fn generate_schema_impl(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    if config.minimal {
+        return quote! {};
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let struct_name_str = struct_name.unraw().to_string();
+    let descriptors = fields.iter().map(|f| {
+        let name = f.name.unraw().to_string();
+        let ty = type_text(&f.ty);
+        let key = match f.unknown_key_type() {
+            Some(key_type) => {
+                let key = type_text(key_type);
+                quote! { ::std::option::Option::Some(#key) }
+            }
+            None => quote! { ::std::option::Option::None },
+        };
+        let optional = f.is_optional;
+        quote! {
+            ::structible::FieldDescriptor {
+                name: #name,
+                ty: #ty,
+                key: #key,
+                optional: #optional,
+            }
+        }
+    });
+    let register = generics
+        .params
+        .is_empty()
+        .then(|| quote! { ::structible::__register_schema!(#struct_name); });
+
+    quote! {
+        #register
+
+        impl #impl_generics ::structible::Schema for #struct_name #ty_generics #where_clause {
+            const NAME: &'static str = #struct_name_str;
+            const FIELDS: &'static [::structible::FieldDescriptor] = &[#(#descriptors),*];
+        }
+
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            /// Lists the fields added, removed, and retyped since `__Old`, an
+            /// earlier version of this struct.
+            pub fn compat_report<__Old: ::structible::Schema>() -> ::structible::CompatReport {
+                ::structible::CompatReport::between::<__Old, Self>()
+            }
+        }
+    }
+}
@@ -0,0 +1,262 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::{Generics, Ident, Visibility};
+
+use crate::instrument::trace_bounds;
+use crate::parse::{FieldInfo, StructibleConfig};
+use crate::promote::promote_bounds;
+
+/// Returns the name of the copy-on-write handle type of a struct.
+pub fn cow_struct_name(struct_name: &Ident) -> Ident {
+    format_ident!("{}Cow", struct_name)
+}
+
+/// Checks that a struct with `cow` can be cloned on write.
+pub fn check_cow_config(struct_name: &Ident, config: &StructibleConfig) -> syn::Result<()> {
+    if config.cow && (config.no_clone || config.is_async) {
+        let other = if config.is_async { "async" } else { "no_clone" };
+        return Err(syn::Error::new_spanned(
+            struct_name,
+            format!("`cow` cannot be combined with `{}`", other),
+        ));
+    }
+    Ok(())
+}
+
+/// Generate the copy-on-write handle type, if `cow` is set.
+///
+/// The handle shares the struct behind an `Arc`, so that cloning it is cheap. Reads
+/// go through `Deref`, and writes through `make_mut()`, which clones the struct
+/// first if it is shared. Setters and removers are delegated for convenience.
+pub fn generate_cow_struct(
+    struct_name: &Ident,
+    vis: &Visibility,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    if !config.cow {
+        return quote! {};
+    }
+    let cow_struct = cow_struct_name(struct_name);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let struct_doc = format!(
+        "A cheaply cloneable, copy-on-write handle to a [`{}`].\n\n\
+         Getters are reached through `Deref`. Setters, removers, and catch-all inserts \
+         and removes are delegated to `make_mut()`, which clones the struct first if it \
+         is shared.",
+        struct_name
+    );
+
+    let setters = fields
+        .iter()
+        .filter(|f| !f.is_unknown_field() && !config.no_setters)
+        .map(|f| {
+            let setter_name = f.setter_name();
+            let vis = f.setter_vis();
+            let value_ty = if f.is_optional { &f.inner_ty } else { &f.ty };
+            let method_attrs = f.method_attrs();
+            let doc = format!(
+                "Sets the `{}` value, cloning the shared struct first if needed.",
+                f.name.unraw()
+            );
+            quote! {
+                #[doc = #doc]
+                #(#method_attrs)*
+                #vis fn #setter_name(&mut self, value: #value_ty) {
+                    self.make_mut().#setter_name(value)
+                }
+            }
+        });
+
+    let removers = fields
+        .iter()
+        .filter(|f| f.is_optional && !f.is_unknown_field() && !config.no_removers)
+        .map(|f| {
+            let remover_name = f.remover_name();
+            let vis = f.remover_vis();
+            let inner_ty = &f.inner_ty;
+            let method_attrs = f.method_attrs();
+            let doc = format!(
+                "Removes the `{}` field and returns the value if it was present, cloning the shared struct first if needed.",
+                f.name.unraw()
+            );
+            quote! {
+                #[doc = #doc]
+                #(#method_attrs)*
+                #vis fn #remover_name(&mut self) -> Option<#inner_ty> {
+                    self.make_mut().#remover_name()
+                }
+            }
+        });
+
+    let unknown_methods = fields.iter().find(|f| f.is_unknown_field()).map(|f| {
+        let name = &f.name;
+        let key_type = f.unknown_key_type().unwrap();
+        let value_type = &f.inner_ty;
+        let insert_method = format_ident!("insert_{}", name);
+        let remove_method = format_ident!("remove_{}", name);
+        let set_vis = f.setter_vis();
+        let remove_vis = f.remover_vis();
+        let method_attrs = f.method_attrs();
+        let insert_doc = format!(
+            "Inserts an unknown `{}` field, cloning the shared struct first if needed.",
+            name.unraw()
+        );
+        let remove_doc = format!(
+            "Removes the `{}` field for the given key, cloning the shared struct first if needed.",
+            name.unraw()
+        );
+        let insert = if config.no_setters {
+            quote! {}
+        } else {
+            quote! {
+                #[doc = #insert_doc]
+                #(#method_attrs)*
+                #set_vis fn #insert_method(&mut self, key: #key_type, value: #value_type) -> Option<#value_type> {
+                    self.make_mut().#insert_method(key, value)
+                }
+            }
+        };
+        let remove = if config.no_removers {
+            quote! {}
+        } else {
+            quote! {
+                #[doc = #remove_doc]
+                #(#method_attrs)*
+                #remove_vis fn #remove_method<__Q>(&mut self, key: &__Q) -> Option<#value_type>
+                where
+                    #key_type: ::std::borrow::Borrow<__Q>,
+                    __Q: ::std::borrow::ToOwned<Owned = #key_type> + ::std::hash::Hash + ::std::cmp::Eq + ?Sized,
+                {
+                    self.make_mut().#remove_method(key)
+                }
+            }
+        };
+        let append = match f.multi_value_type() {
+            Some(element_type) if !config.no_setters => {
+                let append_method = format_ident!("append_{}", name);
+                let append_doc = format!(
+                    "Appends a value to the `{}` values for the given key, cloning the shared struct first if needed.",
+                    name.unraw()
+                );
+                quote! {
+                    #[doc = #append_doc]
+                    #(#method_attrs)*
+                    #set_vis fn #append_method(&mut self, key: #key_type, value: #element_type) {
+                        self.make_mut().#append_method(key, value)
+                    }
+                }
+            }
+            _ => quote! {},
+        };
+        quote! {
+            #insert
+            #remove
+            #append
+        }
+    });
+
+    // Cloning on write needs the struct to be `Clone`, a routing catch-all insert its
+    // conversion bounds, and traced writes their `Debug` bounds
+    let promote_bounds = promote_bounds(fields, generics);
+    let trace_bounds = trace_bounds(fields, config, generics);
+    let clone_bound = quote! {
+        #promote_bounds #trace_bounds #struct_name #ty_generics: ::std::clone::Clone
+    };
+    let write_where = if let Some(wc) = where_clause {
+        let existing = &wc.predicates;
+        quote! { where #clone_bound, #existing }
+    } else {
+        quote! { where #clone_bound }
+    };
+    let eq_where = if let Some(wc) = where_clause {
+        let existing = &wc.predicates;
+        quote! { where #struct_name #ty_generics: ::std::cmp::PartialEq, #existing }
+    } else {
+        quote! { where #struct_name #ty_generics: ::std::cmp::PartialEq }
+    };
+    let debug_where = if let Some(wc) = where_clause {
+        let existing = &wc.predicates;
+        quote! { where #struct_name #ty_generics: ::std::fmt::Debug, #existing }
+    } else {
+        quote! { where #struct_name #ty_generics: ::std::fmt::Debug }
+    };
+
+    let partial_eq_impl = if config.no_partial_eq {
+        quote! {}
+    } else {
+        quote! {
+            impl #impl_generics ::std::cmp::PartialEq for #cow_struct #ty_generics #eq_where {
+                fn eq(&self, other: &Self) -> bool {
+                    ::std::sync::Arc::ptr_eq(&self.inner, &other.inner) || *self.inner == *other.inner
+                }
+            }
+        }
+    };
+
+    quote! {
+        #[doc = #struct_doc]
+        #vis struct #cow_struct #generics #where_clause {
+            inner: ::std::sync::Arc<#struct_name #ty_generics>,
+        }
+
+        impl #impl_generics #cow_struct #ty_generics #where_clause {
+            /// Creates a handle owning the given struct.
+            pub fn new(value: #struct_name #ty_generics) -> Self {
+                Self { inner: ::std::sync::Arc::new(value) }
+            }
+
+            /// Returns true if no other handle shares this handle's struct.
+            pub fn is_unique(&self) -> bool {
+                ::std::sync::Arc::strong_count(&self.inner) == 1
+            }
+        }
+
+        impl #impl_generics #cow_struct #ty_generics #write_where {
+            /// Returns a mutable reference to the struct, cloning it first if it is
+            /// shared with other handles.
+            pub fn make_mut(&mut self) -> &mut #struct_name #ty_generics {
+                ::std::sync::Arc::make_mut(&mut self.inner)
+            }
+
+            /// Returns the struct, cloning it if it is shared with other handles.
+            pub fn into_inner(self) -> #struct_name #ty_generics {
+                ::std::sync::Arc::unwrap_or_clone(self.inner)
+            }
+
+            #(#setters)*
+            #(#removers)*
+            #unknown_methods
+        }
+
+        impl #impl_generics ::std::clone::Clone for #cow_struct #ty_generics #where_clause {
+            fn clone(&self) -> Self {
+                Self { inner: ::std::clone::Clone::clone(&self.inner) }
+            }
+        }
+
+        impl #impl_generics ::std::ops::Deref for #cow_struct #ty_generics #where_clause {
+            type Target = #struct_name #ty_generics;
+
+            fn deref(&self) -> &Self::Target {
+                &self.inner
+            }
+        }
+
+        impl #impl_generics ::std::convert::From<#struct_name #ty_generics> for #cow_struct #ty_generics #where_clause {
+            fn from(value: #struct_name #ty_generics) -> Self {
+                Self::new(value)
+            }
+        }
+
+        #partial_eq_impl
+
+        impl #impl_generics ::std::fmt::Debug for #cow_struct #ty_generics #debug_where {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                ::std::fmt::Debug::fmt(&*self.inner, f)
+            }
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{Generics, Ident};
+
+use crate::codegen::{field_enum_type, value_enum_name};
+use crate::parse::{FieldInfo, StructibleConfig};
+use crate::util::{to_pascal_case, type_mentions_type_param};
+
+/// Checks that a struct with `csv` implements `Structible` and holds one value per key.
+pub fn check_csv_config(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+) -> syn::Result<()> {
+    if !config.csv {
+        return Ok(());
+    }
+    if config.no_structible {
+        return Err(syn::Error::new_spanned(
+            struct_name,
+            "`csv` requires the `Structible` impl and cannot be combined with `no_structible`",
+        ));
+    }
+    if let Some(f) = fields.iter().find(|f| f.config.multi) {
+        return Err(syn::Error::new_spanned(
+            &f.name,
+            "`csv` cannot be combined with a `multi` catch-all",
+        ));
+    }
+    Ok(())
+}
+
+/// Generate `csv_headers()`, `to_csv_record()`, and `from_csv_record()`, if `csv`
+/// is set.
+///
+/// Columns are named like the fields in `ValueCodec`, and are mapped to field keys
+/// by the hidden `__structible_csv_column`, so that both directions follow the
+/// header row rather than the declaration order. Aliases name the same column as
+/// their field, as in the serde decoder.
+pub fn generate_csv_impl(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    if !config.csv {
+        return quote! {};
+    }
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let csv = quote! { ::structible::csv };
+    let error = quote! { ::structible::csv::CsvError };
+    let record = quote! { ::structible::__private::csv::StringRecord };
+
+    // Generic field and catch-all key types must be written and parsed as text
+    let type_param_idents: Vec<_> = generics.type_params().map(|tp| &tp.ident).collect();
+    let text_types: Vec<_> = fields
+        .iter()
+        .map(|f| &f.inner_ty)
+        .chain(fields.iter().filter_map(|f| f.unknown_key_type()))
+        .filter(|ty| type_mentions_type_param(ty, &type_param_idents))
+        .collect();
+    let text_bounds = quote! {
+        #(#text_types: ::std::fmt::Display + ::std::str::FromStr,
+            <#text_types as ::std::str::FromStr>::Err: ::std::fmt::Display,)*
+    };
+    let where_clause = if let Some(wc) = where_clause {
+        let existing = &wc.predicates;
+        quote! { where #text_bounds #existing }
+    } else if !text_types.is_empty() {
+        quote! { where #text_bounds }
+    } else {
+        quote! {}
+    };
+
+    let mut headers = Vec::new();
+    let mut column_arms = Vec::new();
+    let mut column_unknown = quote! {};
+    let mut format_arms = Vec::new();
+    let mut parse_arms = Vec::new();
+    for f in fields {
+        let name_str = f.name.unraw().to_string();
+        if f.is_unknown_field() {
+            let prefix = format!("{}.", name_str);
+            let key = f.normalized_key(quote! { #csv::__parse(header, key)? });
+            column_unknown = quote! {
+                if let ::std::option::Option::Some(key) = header.strip_prefix(#prefix) {
+                    return ::std::result::Result::Ok(#field_enum::Unknown(#key));
+                }
+            };
+            format_arms.push(quote! {
+                ::std::option::Option::Some(#value_enum::Unknown(value)) => {
+                    ::std::string::ToString::to_string(value)
+                }
+            });
+            parse_arms.push(quote! {
+                #field_enum::Unknown(_) if cell.is_empty() => continue,
+                #field_enum::Unknown(_) => #value_enum::Unknown(#csv::__parse(header, cell)?)
+            });
+            continue;
+        }
+        let variant = to_pascal_case(&f.name);
+        // Former names of the field are read as its column too
+        let aliases = f.alias_names();
+        headers.push(name_str.clone());
+        column_arms.push(quote! {
+            #name_str #(| #aliases)* => ::std::result::Result::Ok(#field_enum::#variant)
+        });
+        format_arms.push(quote! {
+            ::std::option::Option::Some(#value_enum::#variant(value)) => {
+                ::std::string::ToString::to_string(value)
+            }
+        });
+        // Empty cells are absent optional fields, but may be values of required ones
+        let skip_empty = f.is_optional.then(|| {
+            quote! { #field_enum::#variant if cell.is_empty() => continue, }
+        });
+        parse_arms.push(quote! {
+            #skip_empty
+            #field_enum::#variant => #value_enum::#variant(#csv::__parse(header, cell)?)
+        });
+    }
+
+    // Expired values read as absent, so they are written as empty cells
+    let lookup = if config.ttl {
+        quote! {
+            ::structible::BackingMap::get(&self.inner, &key)
+                .filter(|_| !self.__structible_expired(&key))
+        }
+    } else {
+        quote! { ::structible::BackingMap::get(&self.inner, &key) }
+    };
+
+    quote! {
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            /// Returns a header row naming the known fields, in declaration order.
+            pub fn csv_headers() -> #record {
+                #record::from(::std::vec![#(#headers),*])
+            }
+
+            /// Returns the struct as a CSV record with a cell for each of `headers`.
+            ///
+            /// Absent fields are written as empty cells. Fails with
+            /// `CsvError::UnknownColumn` if a header names no field, and with
+            /// `CsvError::DuplicateColumn` if two headers name the same one.
+            pub fn to_csv_record(
+                &self,
+                headers: &#record,
+            ) -> ::std::result::Result<#record, #error> {
+                let mut record = #record::with_capacity(0, headers.len());
+                for key in Self::__structible_csv_columns(headers)? {
+                    let cell = match #lookup {
+                        #(#format_arms)*
+                        _ => ::std::string::String::new(),
+                    };
+                    record.push_field(&cell);
+                }
+                ::std::result::Result::Ok(record)
+            }
+
+            /// Reads a struct from a CSV record whose cells are named by `headers`.
+            ///
+            /// Empty cells of optional fields and catch-all columns are left absent.
+            /// Fails if a header names no field or the same field as another header,
+            /// if a cell doesn't parse, or if a required field has no column.
+            pub fn from_csv_record(
+                headers: &#record,
+                record: &#record,
+            ) -> ::std::result::Result<Self, #error> {
+                #csv::__check_lengths(headers.len(), record.len())?;
+                let mut backing =
+                    <<Self as ::structible::Structible>::Backing as ::structible::BackingMap<_, _>>::new();
+                let keys = Self::__structible_csv_columns(headers)?;
+                for ((key, header), cell) in keys.into_iter().zip(headers).zip(record) {
+                    let value = match key {
+                        #(#parse_arms,)*
+                    };
+                    let (key, value) = <Self as ::structible::Structible>::route_entry(key, value);
+                    ::structible::BackingMap::insert(&mut backing, key, value);
+                }
+                ::std::result::Result::Ok(
+                    <Self as ::structible::Structible>::try_from_backing(backing)?,
+                )
+            }
+
+            /// Returns the keys of the fields named by a header row, in order.
+            #[doc(hidden)]
+            fn __structible_csv_columns(
+                headers: &#record,
+            ) -> ::std::result::Result<::std::vec::Vec<#field_enum>, #error> {
+                let mut keys = ::std::vec::Vec::with_capacity(headers.len());
+                for header in headers {
+                    let key = Self::__structible_csv_column(header)?;
+                    // A field's name and its aliases are the same column
+                    if keys.contains(&key) {
+                        return ::std::result::Result::Err(#error::DuplicateColumn(
+                            ::std::string::ToString::to_string(header),
+                        ));
+                    }
+                    keys.push(key);
+                }
+                ::std::result::Result::Ok(keys)
+            }
+
+            /// Returns the key of the field named by a CSV header.
+            #[doc(hidden)]
+            fn __structible_csv_column(header: &str) -> ::std::result::Result<#field_enum, #error> {
+                match header {
+                    #(#column_arms,)*
+                    _ => {
+                        #column_unknown
+                        ::std::result::Result::Err(#error::UnknownColumn(
+                            ::std::string::ToString::to_string(header),
+                        ))
+                    }
+                }
+            }
+        }
+    }
+}
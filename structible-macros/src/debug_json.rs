@@ -0,0 +1,106 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{Generics, Ident};
+
+use crate::codegen::{field_enum_type, value_enum_name};
+use crate::parse::{FieldInfo, StructibleConfig};
+use crate::util::{to_pascal_case, type_mentions_type_param};
+
+/// Generate the `DebugJson` impl and `to_debug_json()`, if `debug_json` is set.
+///
+/// Present fields are written as an object keyed by field name, with catch-all
+/// entries nested under the catch-all's name. The runtime sorts object keys.
+pub fn generate_debug_json_impl(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    if !config.debug_json {
+        return quote! {};
+    }
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let debug_json = quote! { ::structible::debug_json };
+
+    let known_entries = fields.iter().filter(|f| !f.is_unknown_field()).map(|f| {
+        let name_str = f.name.unraw().to_string();
+        let variant = to_pascal_case(&f.name);
+        // Expired values read as absent, so they aren't written either
+        let expired_check = if config.ttl && f.is_optional {
+            quote! { if !self.__structible_expired(&#field_enum::#variant) }
+        } else {
+            quote! {}
+        };
+        quote! {
+            if let Some(#value_enum::#variant(value)) = ::structible::BackingMap::get(&self.inner, &#field_enum::#variant) {
+                #expired_check {
+                    entries.push((::std::string::String::from(#name_str), value));
+                }
+            }
+        }
+    });
+
+    let unknown_entries = fields.iter().find(|f| f.is_unknown_field()).map(|f| {
+        let name_str = f.name.unraw().to_string();
+        quote! {
+            let unknown = #debug_json::__Object::new(
+                ::structible::IterableMap::iter(&self.inner)
+                    .filter_map(|(k, v)| match (k, v) {
+                        (#field_enum::Unknown(key), #value_enum::Unknown(value)) => Some((
+                            ::structible::DebugJson::debug_json_key(key),
+                            value as &dyn ::structible::DebugJson,
+                        )),
+                        _ => None,
+                    })
+                    .collect(),
+            );
+            if !unknown.is_empty() {
+                entries.push((::std::string::String::from(#name_str), &unknown));
+            }
+        }
+    });
+
+    // Generic field and catch-all key types must be renderable
+    let type_param_idents: Vec<_> = generics.type_params().map(|tp| &tp.ident).collect();
+    let bounded_types: Vec<_> = fields
+        .iter()
+        .map(|f| &f.inner_ty)
+        .chain(fields.iter().filter_map(|f| f.unknown_key_type()))
+        .filter(|ty| type_mentions_type_param(ty, &type_param_idents))
+        .collect();
+    let bounds = quote! { #(#bounded_types: ::structible::DebugJson,)* };
+    let where_clause = if let Some(wc) = where_clause {
+        let existing = &wc.predicates;
+        quote! { where #bounds #existing }
+    } else if !bounded_types.is_empty() {
+        quote! { where #bounds }
+    } else {
+        quote! {}
+    };
+
+    quote! {
+        impl #impl_generics ::structible::DebugJson for #struct_name #ty_generics #where_clause {
+            fn write_debug_json(&self, out: &mut ::std::string::String, indent: usize) {
+                let mut entries: ::std::vec::Vec<(::std::string::String, &dyn ::structible::DebugJson)> =
+                    ::std::vec::Vec::new();
+                #unknown_entries
+                #(#known_entries)*
+                #debug_json::__Object::new(entries).write_debug_json(out, indent)
+            }
+        }
+
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            /// Returns the present fields as pretty-printed, JSON-like text with sorted keys,
+            /// e.g. for golden-file tests.
+            ///
+            /// Catch-all entries are nested under the catch-all's name, and values
+            /// expired under `ttl` are skipped.
+            pub fn to_debug_json(&self) -> ::std::string::String {
+                #debug_json::to_string(self)
+            }
+        }
+    }
+}
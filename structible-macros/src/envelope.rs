@@ -0,0 +1,112 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{Generics, Ident, LitInt};
+
+use crate::codegen::{field_enum_type, value_enum_name};
+use crate::parse::{FieldInfo, StructibleConfig};
+use crate::util::{fnv1a, type_text};
+
+/// Checks that a struct with `envelope` can encode and decode its payload.
+pub fn check_envelope_config(struct_name: &Ident, config: &StructibleConfig) -> syn::Result<()> {
+    if !config.envelope {
+        return Ok(());
+    }
+    let conflict = [
+        (config.is_async, "cannot be combined with `async`"),
+        (
+            config.no_structible,
+            "cannot be combined with `no_structible`",
+        ),
+        (!config.serde_backing, "requires `serde_backing`"),
+    ];
+    if let Some((_, message)) = conflict.iter().find(|(conflicts, _)| *conflicts) {
+        return Err(syn::Error::new_spanned(
+            struct_name,
+            format!("`envelope` {}", message),
+        ));
+    }
+    Ok(())
+}
+
+/// Returns the schema fingerprint of a struct's fields.
+///
+/// This is the 64-bit FNV-1a hash of each field's name (as serialized) and
+/// declared type, sorted by name, so that it is stable across compiler versions
+/// and doesn't change when fields are reordered. The catch-all also contributes
+/// its key type.
+pub fn schema_fingerprint(fields: &[FieldInfo]) -> u64 {
+    let mut entries: Vec<_> = fields
+        .iter()
+        .map(|f| {
+            let name = f.name.unraw().to_string();
+            match f.unknown_key_type() {
+                Some(key_type) => format!(
+                    "{}[{}]:{}",
+                    name,
+                    type_text(key_type),
+                    type_text(&f.inner_ty)
+                ),
+                None => format!("{}:{}", name, type_text(&f.ty)),
+            }
+        })
+        .collect();
+    entries.sort();
+    fnv1a(&entries.join(";"))
+}
+
+/// Generate `SCHEMA_FINGERPRINT`, `to_envelope()`, and `from_envelope()`, if
+/// `envelope` is set.
+///
+/// The payload is the struct's `serde_adapter` JSON encoding, behind a header
+/// written and checked by the hidden helpers in `structible::envelope`.
+pub fn generate_envelope_impl(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    if !config.envelope {
+        return quote! {};
+    }
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let serde = quote! { ::structible::__private::serde };
+    let error = quote! { ::structible::envelope::EnvelopeError };
+    let fingerprint = LitInt::new(
+        &format!("0x{:016x}", schema_fingerprint(fields)),
+        Span::call_site(),
+    );
+    let existing = where_clause.map(|wc| &wc.predicates);
+
+    quote! {
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            /// The fingerprint of the struct's schema, carried by its envelopes.
+            ///
+            /// It hashes the names and declared types of the fields, so adding,
+            /// removing, renaming, or retyping a field changes it, but reordering
+            /// fields doesn't.
+            pub const SCHEMA_FINGERPRINT: u64 = #fingerprint;
+        }
+
+        impl #impl_generics #struct_name #ty_generics
+        where
+            #field_enum: #serde::Serialize + #serde::de::DeserializeOwned,
+            #value_enum #ty_generics: #serde::Serialize + #serde::de::DeserializeOwned,
+            #existing
+        {
+            /// Encodes the struct as JSON, prefixed with a header carrying
+            /// `SCHEMA_FINGERPRINT`.
+            pub fn to_envelope(&self) -> ::std::result::Result<::std::vec::Vec<u8>, #error> {
+                ::structible::envelope::__encode(Self::SCHEMA_FINGERPRINT, self)
+            }
+
+            /// Decodes a struct written by `to_envelope()`, failing with
+            /// `EnvelopeError::SchemaMismatch` if it was written with another schema.
+            pub fn from_envelope(bytes: &[u8]) -> ::std::result::Result<Self, #error> {
+                ::structible::envelope::__decode(Self::SCHEMA_FINGERPRINT, bytes)
+            }
+        }
+    }
+}
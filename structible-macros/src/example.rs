@@ -0,0 +1,191 @@
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{Generics, Ident};
+
+use crate::changes::insert_expr;
+use crate::codegen::{field_enum_type, value_enum_name};
+use crate::parse::{FieldInfo, StructibleConfig};
+use crate::util::{to_pascal_case, type_mentions_type_param};
+
+/// Checks that fields only declare `example` values for an `example` struct, and
+/// that `sample` is only set alongside `example`.
+pub fn check_example_config(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+) -> syn::Result<()> {
+    if config.sample && !config.example {
+        return Err(syn::Error::new_spanned(
+            struct_name,
+            "`sample` requires `example`",
+        ));
+    }
+    for f in fields {
+        let Some(value) = &f.config.example else {
+            continue;
+        };
+        if f.is_unknown_field() {
+            return Err(syn::Error::new_spanned(
+                value,
+                "`example` is not supported on the catch-all",
+            ));
+        }
+        if !config.example {
+            return Err(syn::Error::new_spanned(
+                value,
+                "`example` on a field requires `#[structible(example)]` on the struct",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Returns the `example` value of a field, of its stored type, or that type's
+/// default.
+///
+/// Like the setter, a transparent field takes a value of the wrapped type.
+fn example_value(f: &FieldInfo) -> TokenStream {
+    let ty = if f.is_optional { &f.inner_ty } else { &f.ty };
+    match (&f.config.example, &f.config.transparent) {
+        (Some(value), Some(wrapped)) => quote_spanned! {value.span()=>
+            <#wrapped as ::std::convert::Into<#ty>>::into(#value)
+        },
+        (Some(value), None) => {
+            quote_spanned! {value.span()=> ::std::convert::identity::<#ty>(#value) }
+        }
+        (None, _) => quote_spanned! {ty.span()=> <#ty as ::std::default::Default>::default() },
+    }
+}
+
+/// Returns the stored types of the given fields that have no `example` value and
+/// mention the struct's type parameters, which must then implement `Default`.
+fn default_bounds<'a>(
+    fields: impl IntoIterator<Item = &'a FieldInfo>,
+    generics: &Generics,
+) -> TokenStream {
+    let type_param_idents: Vec<_> = generics.type_params().map(|tp| &tp.ident).collect();
+    let types = fields
+        .into_iter()
+        .filter(|f| f.config.example.is_none())
+        .map(|f| if f.is_optional { &f.inner_ty } else { &f.ty })
+        .filter(|ty| type_mentions_type_param(ty, &type_param_idents));
+    quote! { #(#types: ::std::default::Default,)* }
+}
+
+/// Generate `example()`, if `example` is set, and `sample_partial()`, if `sample`
+/// is set.
+///
+/// Required fields take their `example` value, or their type's default, and are
+/// passed to the constructor. Optional fields are set with the same write as
+/// their setter: by `example()` if they declare an `example` value, and by
+/// `sample_partial()` at random, to that value or their type's default.
+pub fn generate_example_impl(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    if !config.example {
+        return quote! {};
+    }
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+    let constructor_name = config.constructor_name(struct_name);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let existing = where_clause.map(|wc| &wc.predicates);
+
+    let required: Vec<_> = fields
+        .iter()
+        .filter(|f| !f.is_optional && !f.is_unknown_field())
+        .collect();
+    let args: Vec<_> = required.iter().map(|f| example_value(f)).collect();
+    let required_bounds = default_bounds(required.iter().copied(), generics);
+
+    let optional: Vec<_> = fields
+        .iter()
+        .filter(|f| f.is_optional && !f.is_unknown_field())
+        .collect();
+    let insert = |f: &FieldInfo| {
+        let variant = to_pascal_case(&f.name);
+        let value = example_value(f);
+        insert_expr(
+            config,
+            quote! { this },
+            quote! { #field_enum::#variant },
+            quote! { #value_enum::#variant(#value) },
+        )
+    };
+    let inserts = optional
+        .iter()
+        .filter(|f| f.config.example.is_some())
+        .map(|f| insert(f));
+
+    // Lazy fields are loaded rather than sampled, unless they declare a value
+    let sampled: Vec<_> = optional
+        .iter()
+        .copied()
+        .filter(|f| !f.is_lazy() || f.config.example.is_some())
+        .collect();
+    let sample_impl = config.sample.then(|| {
+        let rand = quote! { ::structible::__private::rand };
+        let sample_bounds = default_bounds(sampled.iter().copied(), generics);
+        let inserts = sampled.iter().map(|f| {
+            let insert = insert(f);
+            quote! {
+                if #rand::Rng::random_bool(rng, density) {
+                    #insert;
+                }
+            }
+        });
+        quote! {
+            impl #impl_generics #struct_name #ty_generics
+            where
+                #required_bounds
+                #sample_bounds
+                #existing
+            {
+                /// Returns an instance whose required fields hold their `example`
+                /// values, and whose optional fields are each present with
+                /// probability `density`, holding their `example` value or their
+                /// type's default. Lazy fields without an `example` value are left
+                /// absent, as is the catch-all.
+                ///
+                /// # Panics
+                ///
+                /// Panics if `density` is not between 0 and 1.
+                pub fn sample_partial<__R: #rand::Rng + ?::std::marker::Sized>(
+                    rng: &mut __R,
+                    density: f64,
+                ) -> Self {
+                    #[allow(unused_mut)]
+                    let mut this = Self::#constructor_name(#(#args),*);
+                    #(#inserts)*
+                    this
+                }
+            }
+        }
+    });
+
+    quote! {
+        #sample_impl
+
+        impl #impl_generics #struct_name #ty_generics
+        where
+            #required_bounds
+            #existing
+        {
+            /// Returns an example instance, holding each field's `example` value.
+            ///
+            /// Required fields without one hold their type's default, and optional
+            /// fields without one are absent. Values are written like the setters
+            /// write them, so they are recorded by change tracking and timestamps.
+            pub fn example() -> Self {
+                #[allow(unused_mut)]
+                let mut this = Self::#constructor_name(#(#args),*);
+                #(#inserts;)*
+                this
+            }
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use std::path::PathBuf;
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{Generics, Ident, Item, LitInt};
+
+use crate::parse::StructibleConfig;
+use crate::util::fnv1a;
+
+/// Moves the `impl` blocks of an expansion into a `#[doc(hidden)] const _`
+/// block, along with an `__STRUCTIBLE_EXPANSION_HASH` constant on the struct
+/// holding the FNV-1a hash of the expansion.
+///
+/// Unlike a module, the block sees the items of the scope the struct is declared
+/// in, including those local to a function body, and the impls inside it still
+/// apply everywhere. Expansions that don't parse are returned as they are.
+pub fn scope_impls(struct_name: &Ident, generics: &Generics, expanded: TokenStream) -> TokenStream {
+    let Ok(file) = syn::parse2::<syn::File>(expanded.clone()) else {
+        return expanded;
+    };
+    let hash = LitInt::new(
+        &format!("0x{:016x}", fnv1a(&expanded.to_string())),
+        struct_name.span(),
+    );
+    let (items, impls): (Vec<_>, Vec<_>) = file
+        .items
+        .into_iter()
+        .partition(|item| !matches!(item, Item::Impl(_)));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        #(#items)*
+
+        #[doc(hidden)]
+        #[allow(non_upper_case_globals, unused_attributes, unused_qualifications)]
+        const _: () = {
+            impl #impl_generics #struct_name #ty_generics #where_clause {
+                /// The hash of the code generated for this struct.
+                #[doc(hidden)]
+                pub const __STRUCTIBLE_EXPANSION_HASH: u64 = #hash;
+            }
+
+            #(#impls)*
+        };
+    }
+}
+
+/// Formats generated code like rustfmt would, falling back to the plain tokens
+/// if they don't parse as a file.
+fn format_expansion(expanded: &TokenStream) -> String {
+    match syn::parse2::<syn::File>(expanded.clone()) {
+        Ok(file) => prettyplease::unparse(&file),
+        Err(_) => expanded.to_string(),
+    }
+}
+
+/// Prints the formatted expansion of a struct to stderr, and writes it to
+/// `$OUT_DIR/structible/<Struct>.rs` if the crate has a build script, if
+/// `debug_expand` is set.
+///
+/// The file is left untouched if it already holds the same expansion.
+pub fn emit_expansion(struct_name: &Ident, config: &StructibleConfig, expanded: &TokenStream) {
+    if !config.debug_expand {
+        return;
+    }
+    let formatted = format_expansion(expanded);
+    eprintln!(
+        "// structible expansion of `{}`\n{}",
+        struct_name.unraw(),
+        formatted
+    );
+
+    let Some(out_dir) = std::env::var_os("OUT_DIR") else {
+        return;
+    };
+    let dir = PathBuf::from(out_dir).join("structible");
+    let path = dir.join(format!("{}.rs", struct_name.unraw()));
+    // Rewriting an unchanged expansion would only bump its modification time,
+    // which makes tools watching `OUT_DIR` rebuild for nothing
+    if std::fs::read_to_string(&path).is_ok_and(|existing| existing == formatted) {
+        return;
+    }
+    if let Err(err) = std::fs::create_dir_all(&dir).and_then(|()| std::fs::write(&path, formatted))
+    {
+        eprintln!(
+            "structible: failed to write expansion to {}: {}",
+            path.display(),
+            err
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::format_ident;
+
+    use super::*;
+
+    #[test]
+    fn test_scope_impls() {
+        let name = format_ident!("Person");
+        let expanded = quote! {
+            pub struct Person { name: String }
+            impl Person { pub fn name(&self) -> &String { &self.name } }
+        };
+        let scoped = scope_impls(&name, &Generics::default(), expanded.clone()).to_string();
+        assert!(scoped.starts_with("pub struct Person"));
+        assert!(scoped.contains("const _ : () = { impl Person {"));
+        assert!(scoped.contains("pub const __STRUCTIBLE_EXPANSION_HASH : u64 = 0x"));
+        assert!(
+            scoped.ends_with(
+                "impl Person { pub fn name (& self) -> & String { & self . name } } } ;"
+            )
+        );
+        assert_eq!(
+            scoped,
+            scope_impls(&name, &Generics::default(), expanded).to_string()
+        );
+
+        let unparsed = quote! { impl };
+        assert_eq!(
+            scope_impls(&name, &Generics::default(), unparsed.clone()).to_string(),
+            unparsed.to_string()
+        );
+    }
+
+    #[test]
+    fn test_format_expansion() {
+        let expanded = quote! { impl Person { pub fn name(&self) -> &String { &self.name } } };
+        assert_eq!(
+            format_expansion(&expanded),
+            "impl Person {\n    pub fn name(&self) -> &String {\n        &self.name\n    }\n}\n"
+        );
+    }
+}
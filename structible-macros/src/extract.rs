@@ -0,0 +1,180 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{Generics, Ident, parse_quote};
+
+use crate::codegen::{field_enum_type, value_enum_name};
+use crate::instrument::trace_bounds;
+use crate::parse::{FieldInfo, StructibleConfig};
+use crate::promote::promote_bounds;
+use crate::util::{to_pascal_case, type_mentions_type_param};
+use crate::validate::has_validation;
+
+/// Checks that a struct with `axum` can be built from a decoded backing.
+pub fn check_extract_config(struct_name: &Ident, config: &StructibleConfig) -> syn::Result<()> {
+    if config.axum && config.no_structible {
+        return Err(syn::Error::new_spanned(
+            struct_name,
+            "`axum` cannot be combined with `no_structible`",
+        ));
+    }
+    Ok(())
+}
+
+/// Generate the axum `FromRequest` impl, if `axum` is set.
+///
+/// The body is decoded as a JSON object with a property per known field, and
+/// other properties as catch-all entries. Every missing, invalid, or unknown
+/// property and every validation violation is collected before rejecting.
+pub fn generate_from_request_impl(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    if !config.axum {
+        return quote! {};
+    }
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+    let (_, ty_generics, where_clause) = generics.split_for_impl();
+    let axum = quote! { ::structible::__private::axum };
+    let serde = quote! { ::structible::__private::serde };
+    let extract = quote! { ::structible::axum };
+
+    let known_arms = fields.iter().filter(|f| !f.is_unknown_field()).map(|f| {
+        let name_str = f.name.unraw().to_string();
+        let aliases = f.alias_names();
+        let variant = to_pascal_case(&f.name);
+        let inner_ty = &f.inner_ty;
+        quote! {
+            #name_str #(| #aliases)* => {
+                if let ::std::option::Option::Some(value) =
+                    #extract::__decode::<#inner_ty>(&key, value, &mut errors)
+                {
+                    ::structible::BackingMap::insert(&mut backing, #field_enum::#variant, #value_enum::#variant(value));
+                }
+            }
+        }
+    });
+    let other_arm = match fields.iter().find(|f| f.is_unknown_field()) {
+        Some(f) => {
+            let key_type = f.unknown_key_type().unwrap();
+            let inner_ty = &f.inner_ty;
+            let normalized = f.normalized_key(quote! { unknown_key });
+            quote! {
+                _ => {
+                    let ::std::option::Option::Some(unknown_key) =
+                        #extract::__decode_key::<#key_type>(&key, &mut errors)
+                    else {
+                        continue;
+                    };
+                    if let ::std::option::Option::Some(value) =
+                        #extract::__decode::<#inner_ty>(&key, value, &mut errors)
+                    {
+                        let (field, value) = <Self as ::structible::Structible>::route_entry(
+                            #field_enum::Unknown(#normalized),
+                            #value_enum::Unknown(value),
+                        );
+                        ::structible::BackingMap::insert(&mut backing, field, value);
+                    }
+                }
+            }
+        }
+        None => quote! {
+            _ => errors.unknown.push(key),
+        },
+    };
+    let missing_checks = fields
+        .iter()
+        .filter(|f| !f.is_unknown_field() && !f.is_optional)
+        .map(|f| {
+            let name_str = f.name.unraw().to_string();
+            let variant = to_pascal_case(&f.name);
+            quote! {
+                if ::structible::BackingMap::get(&backing, &#field_enum::#variant).is_none() {
+                    errors.missing.push(#name_str);
+                }
+            }
+        });
+    let validation = if has_validation(fields, config) {
+        quote! {
+            if let ::std::result::Result::Err(issues) = value.validate_all() {
+                errors.violations.extend(issues.iter().map(::std::string::ToString::to_string));
+                return ::std::result::Result::Err(#extract::Rejection::Fields(errors));
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    // Generic field and catch-all key types must be decodable, and building and
+    // validating the struct may need the bounds of its impls
+    let type_param_idents: Vec<_> = generics.type_params().map(|tp| &tp.ident).collect();
+    let decoded_types: Vec<_> = fields
+        .iter()
+        .map(|f| &f.inner_ty)
+        .chain(fields.iter().filter_map(|f| f.unknown_key_type()))
+        .filter(|ty| type_mentions_type_param(ty, &type_param_idents))
+        .collect();
+    let clone_bounds = if config.records_writes() {
+        let inner_types = fields
+            .iter()
+            .map(|f| &f.inner_ty)
+            .filter(|ty| type_mentions_type_param(ty, &type_param_idents));
+        quote! { #(#inner_types: ::std::clone::Clone,)* }
+    } else {
+        quote! {}
+    };
+    let promote_bounds = promote_bounds(fields, generics);
+    let trace_bounds = trace_bounds(fields, config, generics);
+    let existing = where_clause.map(|wc| &wc.predicates);
+    let from_request_where = quote! {
+        where
+            __S: ::std::marker::Send + ::std::marker::Sync,
+            #(#decoded_types: #serde::de::DeserializeOwned,)*
+            #clone_bounds
+            #promote_bounds
+            #trace_bounds
+            #existing
+    };
+
+    // The state type is an extra parameter of the impl
+    let mut impl_generics = generics.clone();
+    impl_generics.params.push(parse_quote!(__S));
+    let (impl_generics, _, _) = impl_generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics #axum::extract::FromRequest<__S> for #struct_name #ty_generics #from_request_where {
+            type Rejection = #extract::Rejection;
+
+            async fn from_request(
+                req: #axum::extract::Request,
+                state: &__S,
+            ) -> ::std::result::Result<Self, Self::Rejection> {
+                let object = #extract::__json_object(req, state).await?;
+                let mut errors = #extract::FieldErrors::default();
+                let mut backing = <<Self as ::structible::Structible>::Backing as ::structible::BackingMap<_, _>>::new();
+                for (key, value) in object {
+                    match key.as_str() {
+                        #(#known_arms)*
+                        #other_arm
+                    }
+                }
+                #(#missing_checks)*
+                if !errors.is_empty() {
+                    return ::std::result::Result::Err(#extract::Rejection::Fields(errors));
+                }
+                let value = match <Self as ::structible::Structible>::try_from_backing(backing) {
+                    ::std::result::Result::Ok(value) => value,
+                    ::std::result::Result::Err(error) => {
+                        errors.violations.push(::std::string::ToString::to_string(&error));
+                        return ::std::result::Result::Err(#extract::Rejection::Fields(errors));
+                    }
+                };
+                #validation
+                ::std::result::Result::Ok(value)
+            }
+        }
+    }
+}
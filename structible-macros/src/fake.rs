@@ -0,0 +1,146 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Generics, Ident, Type};
+
+use crate::changes::insert_expr;
+use crate::codegen::{field_enum_type, value_enum_name};
+use crate::parse::{FieldInfo, StructibleConfig};
+use crate::util::{to_pascal_case, type_mentions_type_param};
+
+/// Checks that fields only declare fakers for a `fake` struct.
+pub fn check_fake_config(fields: &[FieldInfo], config: &StructibleConfig) -> syn::Result<()> {
+    for f in fields {
+        let Some(faker) = &f.config.fake else {
+            continue;
+        };
+        if f.is_unknown_field() {
+            return Err(syn::Error::new_spanned(
+                faker,
+                "`fake` is not supported on the catch-all",
+            ));
+        }
+        if !config.fake {
+            return Err(syn::Error::new_spanned(
+                faker,
+                "`fake` on a field requires `#[structible(fake)]` on the struct",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Generate `fake()` and `fake_with_rng()`, if `fake` is set.
+///
+/// Each field is generated by its `fake` faker, or by `Faker` otherwise, which
+/// leaves optional fields absent at random. Like `example()`, required fields are
+/// passed to the constructor, and optional fields are set with their setter's
+/// write. The catch-all and lazy fields without a faker are left empty.
+pub fn generate_fake_impl(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    if !config.fake {
+        return quote! {};
+    }
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+    let constructor_name = config.constructor_name(struct_name);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let type_param_idents: Vec<_> = generics.type_params().map(|tp| &tp.ident).collect();
+    let fake = quote! { ::structible::__private::fake };
+
+    // Generates a value of `ty`, converted to `target` for transparent fields
+    let generate = |f: &FieldInfo, ty: &Type, target: &Type| {
+        let faker = match &f.config.fake {
+            Some(faker) => quote! { &(#faker) },
+            None => quote! { &#fake::Faker },
+        };
+        let value = quote! { #fake::Fake::fake_with_rng::<#ty, _>(#faker, rng) };
+        if f.is_transparent() {
+            quote! { <#ty as ::std::convert::Into<#target>>::into(#value) }
+        } else {
+            value
+        }
+    };
+    let generated_type = |f: &FieldInfo| {
+        let ty = if f.is_optional { &f.inner_ty } else { &f.ty };
+        f.config.transparent.as_ref().unwrap_or(ty).clone()
+    };
+
+    let args = fields
+        .iter()
+        .filter(|f| !f.is_optional && !f.is_unknown_field())
+        .map(|f| generate(f, &generated_type(f), &f.ty));
+    let inserts = fields
+        .iter()
+        .filter(|f| f.is_optional && !f.is_unknown_field())
+        .filter(|f| !f.is_lazy() || f.config.fake.is_some())
+        .map(|f| {
+            let variant = to_pascal_case(&f.name);
+            let insert = insert_expr(
+                config,
+                quote! { this },
+                quote! { #field_enum::#variant },
+                quote! { #value_enum::#variant(value) },
+            );
+            let ty = generated_type(f);
+            if f.config.fake.is_some() {
+                let value = generate(f, &ty, &f.inner_ty);
+                quote! {
+                    let value = #value;
+                    #insert;
+                }
+            } else {
+                let inner_ty = &f.inner_ty;
+                let option: Type = syn::parse_quote! { ::std::option::Option<#ty> };
+                let value = quote! { #fake::Fake::fake_with_rng::<#option, _>(&#fake::Faker, rng) };
+                let convert = if f.is_transparent() {
+                    quote! { let value = <#ty as ::std::convert::Into<#inner_ty>>::into(value); }
+                } else {
+                    quote! {}
+                };
+                quote! {
+                    if let ::std::option::Option::Some(value) = #value {
+                        #convert
+                        #insert;
+                    }
+                }
+            }
+        });
+
+    // Generic types generated by `Faker` must implement `Dummy<Faker>`
+    let dummy_types = fields
+        .iter()
+        .filter(|f| !f.is_unknown_field() && !f.is_lazy() && f.config.fake.is_none())
+        .map(generated_type)
+        .filter(|ty| type_mentions_type_param(ty, &type_param_idents));
+    let existing = where_clause.map(|wc| &wc.predicates);
+
+    quote! {
+        impl #impl_generics #struct_name #ty_generics
+        where
+            #(#dummy_types: #fake::Dummy<#fake::Faker>,)*
+            #existing
+        {
+            /// Returns a struct of fake values, generated with the thread-local RNG.
+            pub fn fake() -> Self {
+                Self::fake_with_rng(&mut #fake::rand::rng())
+            }
+
+            /// Returns a struct of fake values, generated with `rng`.
+            ///
+            /// Fields are generated by their `fake` faker, or by `Faker`, which leaves
+            /// optional fields absent at random. Optional fields with a faker are
+            /// always present, while the catch-all and lazy fields without a faker
+            /// are left empty.
+            pub fn fake_with_rng<__R: #fake::Rng + ?::std::marker::Sized>(rng: &mut __R) -> Self {
+                #[allow(unused_mut)]
+                let mut this = Self::#constructor_name(#(#args),*);
+                #(#inserts)*
+                this
+            }
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::{Generics, Ident, Visibility};
+
+use crate::changes::insert_expr;
+use crate::codegen::{field_enum_type, missing_required, value_enum_name};
+use crate::parse::{FieldInfo, StructibleConfig};
+use crate::util::{extract_doc_comments, to_pascal_case};
+
+/// Returns the name of the C-compatible facade type of a struct.
+pub fn ffi_struct_name(struct_name: &Ident) -> Ident {
+    format_ident!("{}Ffi", struct_name)
+}
+
+/// Checks that a struct with `ffi` has a fixed set of concrete fields.
+pub fn check_ffi_config(
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> syn::Result<()> {
+    if !config.ffi {
+        return Ok(());
+    }
+    if !generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            generics,
+            "`ffi` is not supported on generic structs, whose layout isn't fixed",
+        ));
+    }
+    if let Some(f) = fields.iter().find(|f| f.is_unknown_field()) {
+        return Err(syn::Error::new_spanned(
+            &f.name,
+            "`ffi` cannot be combined with a catch-all, which has no C layout",
+        ));
+    }
+    Ok(())
+}
+
+/// Generate the `#[repr(C)]` facade type, `to_ffi()`, `from_ffi()`, and the
+/// `FfiValue` impl, if `ffi` is set.
+///
+/// Each facade field holds the `FfiValue::Repr` of its field's stored type, in an
+/// `FfiOption` for optional fields. `to_ffi()` reads the backing directly, like
+/// the CSV writer, so lazy fields that weren't loaded are absent, and `from_ffi()`
+/// builds the struct like `example()`: required fields through the constructor,
+/// and optional fields with their setter's write.
+pub fn generate_ffi_struct(
+    struct_name: &Ident,
+    vis: &Visibility,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+) -> TokenStream {
+    if !config.ffi {
+        return quote! {};
+    }
+    let ffi_struct = ffi_struct_name(struct_name);
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+    let constructor_name = config.constructor_name(struct_name);
+    let ffi = quote! { ::structible::ffi };
+    let struct_doc = format!(
+        "A C-compatible view of a [`{}`], created with `to_ffi()`.",
+        struct_name
+    );
+
+    let mut facade_fields = Vec::new();
+    let mut to_fields = Vec::new();
+    let mut args = Vec::new();
+    let mut inserts = Vec::new();
+    for f in fields {
+        let name = &f.name;
+        let missing = missing_required(config, name);
+        let name_str = name.unraw().to_string();
+        let variant = to_pascal_case(name);
+        let inner_ty = &f.inner_ty;
+        let docs = extract_doc_comments(&f.attrs);
+        let repr = quote! { <#inner_ty as #ffi::FfiValue>::Repr<'ffi> };
+        let from = quote! {
+            <#inner_ty as #ffi::FfiValue>::from_ffi(value)
+                .map_err(|e| e.in_field(#name_str))?
+        };
+        // Expired values read as absent
+        let lookup = if config.ttl {
+            quote! {
+                ::structible::BackingMap::get(&self.inner, &#field_enum::#variant)
+                    .filter(|_| !self.__structible_expired(&#field_enum::#variant))
+            }
+        } else {
+            quote! { ::structible::BackingMap::get(&self.inner, &#field_enum::#variant) }
+        };
+
+        if f.is_optional {
+            facade_fields.push(quote! {
+                #(#[doc = #docs])*
+                pub #name: #ffi::FfiOption<#repr>
+            });
+            to_fields.push(quote! {
+                #name: match #lookup {
+                    ::std::option::Option::Some(#value_enum::#variant(value)) => {
+                        #ffi::FfiOption::some(#ffi::FfiValue::to_ffi(value))
+                    }
+                    _ => #ffi::FfiOption::none(),
+                }
+            });
+            let insert = insert_expr(
+                config,
+                quote! { this },
+                quote! { #field_enum::#variant },
+                quote! { #value_enum::#variant(value) },
+            );
+            inserts.push(quote! {
+                if let ::std::option::Option::Some(value) = &ffi.#name.get() {
+                    let value = #from;
+                    #insert;
+                }
+            });
+        } else {
+            facade_fields.push(quote! {
+                #(#[doc = #docs])*
+                pub #name: #repr
+            });
+            to_fields.push(quote! {
+                #name: match #lookup {
+                    ::std::option::Option::Some(#value_enum::#variant(value)) => {
+                        #ffi::FfiValue::to_ffi(value)
+                    }
+                    _ => #missing,
+                }
+            });
+            args.push(quote! {
+                {
+                    let value = &ffi.#name;
+                    #from
+                }
+            });
+        }
+    }
+
+    let to_ffi_doc = if config.ttl {
+        "Expired values and lazy fields that weren't loaded are absent."
+    } else {
+        "Lazy fields that weren't loaded are absent."
+    };
+
+    quote! {
+        #[doc = #struct_doc]
+        ///
+        /// Pointers in the facade borrow the struct, which must outlive it.
+        #[repr(C)]
+        #[derive(Clone, Copy, Debug)]
+        #vis struct #ffi_struct<'ffi> {
+            #(#facade_fields,)*
+        }
+
+        impl #struct_name {
+            /// Returns a C-compatible view of this struct, borrowing its values.
+            ///
+            #[doc = #to_ffi_doc]
+            pub fn to_ffi(&self) -> #ffi_struct<'_> {
+                #ffi_struct {
+                    #(#to_fields,)*
+                }
+            }
+
+            /// Creates a struct from a C-compatible view, copying the values it
+            /// points to.
+            ///
+            /// Fails with an `FfiError` naming the field if a pointer is null with a
+            /// non-zero length, or if a string isn't valid UTF-8.
+            ///
+            /// # Safety
+            ///
+            /// Every pointer in `ffi` with a non-zero length must be properly aligned
+            /// and valid for reads of that many initialized values of its element
+            /// type, which must not be mutated during the call.
+            pub unsafe fn from_ffi(
+                ffi: &#ffi_struct<'_>,
+            ) -> ::std::result::Result<Self, #ffi::FfiError> {
+                // SAFETY: guaranteed by the caller
+                unsafe {
+                    #[allow(unused_mut)]
+                    let mut this = Self::#constructor_name(#(#args),*);
+                    #(#inserts)*
+                    ::std::result::Result::Ok(this)
+                }
+            }
+        }
+
+        impl #ffi::FfiValue for #struct_name {
+            type Repr<'ffi> = #ffi_struct<'ffi>;
+
+            fn to_ffi(&self) -> #ffi_struct<'_> {
+                #struct_name::to_ffi(self)
+            }
+
+            unsafe fn from_ffi(
+                repr: &#ffi_struct<'_>,
+            ) -> ::std::result::Result<Self, #ffi::FfiError> {
+                // SAFETY: guaranteed by the caller
+                unsafe { #struct_name::from_ffi(repr) }
+            }
+        }
+    }
+}
@@ -0,0 +1,354 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::{Generics, Ident, Visibility};
+
+use crate::changes::change_log_init;
+use crate::codegen::{
+    debug_unknown_entries, eq_body, field_enum_type, inner_clone, missing_required, value_enum_name,
+};
+use crate::merge::stamp_init;
+use crate::parse::{FieldInfo, StructibleConfig};
+use crate::provenance::source_init;
+use crate::timestamps::timestamp_init;
+use crate::tombstone::tombstone_init;
+use crate::transaction::undo_log_init;
+use crate::ttl::expiry_init;
+use crate::util::{
+    extract_doc_comments, format_method_doc, to_pascal_case, type_mentions_type_param,
+};
+
+/// Returns the name of the frozen snapshot type of a struct.
+pub fn frozen_struct_name(struct_name: &Ident) -> Ident {
+    format_ident!("Frozen{}", struct_name)
+}
+
+/// Checks that a struct with `freeze` can be compared and hashed.
+pub fn check_freeze_config(struct_name: &Ident, config: &StructibleConfig) -> syn::Result<()> {
+    if config.freeze && (config.no_partial_eq || config.is_async) {
+        let other = if config.is_async {
+            "async"
+        } else {
+            "no_partial_eq"
+        };
+        return Err(syn::Error::new_spanned(
+            struct_name,
+            format!("`freeze` cannot be combined with `{}`", other),
+        ));
+    }
+    Ok(())
+}
+
+/// Generate `freeze()` on the main struct, if `freeze` is set.
+pub fn generate_freeze_method(
+    struct_name: &Ident,
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    if !config.freeze {
+        return quote! {};
+    }
+    let frozen_struct = frozen_struct_name(struct_name);
+    let (_, ty_generics, _) = generics.split_for_impl();
+    // Expired values can't be dropped once frozen, so they are dropped beforehand
+    let purge = if config.ttl {
+        quote! { self.purge_expired(); }
+    } else {
+        quote! {}
+    };
+    let receiver = if config.ttl {
+        quote! { mut self }
+    } else {
+        quote! { self }
+    };
+    let purge_doc = if config.ttl {
+        quote! {
+            ///
+            /// Expired values are purged first, and expiries are not kept.
+        }
+    } else {
+        quote! {}
+    };
+
+    quote! {
+        /// Consumes this struct and returns an immutable snapshot of its fields.
+        ///
+        /// The snapshot only has getters, and implements `Eq` and `Hash`, e.g. for
+        /// use as a cache key. Use `thaw()` to get a mutable struct back.
+        #purge_doc
+        pub fn freeze(#receiver) -> #frozen_struct #ty_generics {
+            #purge
+            #frozen_struct { inner: self.inner }
+        }
+    }
+}
+
+/// Generate the frozen snapshot type, its getters, and its trait impls, if `freeze` is set.
+pub fn generate_frozen_struct(
+    struct_name: &Ident,
+    vis: &Visibility,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    if !config.freeze {
+        return quote! {};
+    }
+    let frozen_struct = frozen_struct_name(struct_name);
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+    let map_type = config.backing.to_tokens();
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let struct_doc = format!(
+        "An immutable snapshot of a [`{}`], created with `freeze()`.\n\n\
+         Catch-all entries are hashed independently of the backing's iteration order.",
+        struct_name
+    );
+
+    let getters = fields.iter().filter(|f| !f.is_unknown_field()).map(|f| {
+        let name = &f.name;
+        let missing = missing_required(config, name);
+        let getter_name = f.getter_name();
+        let variant = to_pascal_case(name);
+        let vis = f.getter_vis();
+        let field_docs = extract_doc_comments(&f.attrs);
+        let method_attrs = f.method_attrs();
+        let name_str = name.unraw().to_string();
+
+        // Like the struct's own, these return `None` for a missing required value
+        // with `no_panic`
+        if f.has_optional_getter() {
+            let inner_ty = &f.inner_ty;
+            let doc_attr = format_method_doc(
+                &format!("Returns the `{}` value if present.", name_str),
+                &field_docs,
+            );
+            quote! {
+                #doc_attr
+                #(#method_attrs)*
+                #vis fn #getter_name(&self) -> Option<&#inner_ty> {
+                    match ::structible::BackingMap::get(&self.inner, &#field_enum::#variant) {
+                        Some(#value_enum::#variant(v)) => Some(v),
+                        _ => None,
+                    }
+                }
+            }
+        } else {
+            let ty = &f.ty;
+            let doc_attr = format_method_doc(
+                &format!("Returns a reference to the `{}` value.", name_str),
+                &field_docs,
+            );
+            quote! {
+                #doc_attr
+                #(#method_attrs)*
+                #vis fn #getter_name(&self) -> &#ty {
+                    match ::structible::BackingMap::get(&self.inner, &#field_enum::#variant) {
+                        Some(#value_enum::#variant(v)) => v,
+                        _ => #missing,
+                    }
+                }
+            }
+        }
+    });
+
+    let unknown_field = fields.iter().find(|f| f.is_unknown_field());
+    let unknown_methods = unknown_field.map(|f| {
+        let name = &f.name;
+        let key_type = f.unknown_key_type().unwrap();
+        let value_type = &f.inner_ty;
+        let vis = f.getter_vis();
+        let iter_method = format_ident!("{}_iter", name);
+        let name_str = name.unraw().to_string();
+        let field_docs = extract_doc_comments(&f.attrs);
+        let method_attrs = f.method_attrs();
+        let (lookup_bound, normalize_lookup) = f.normalize_lookup();
+        let get_doc = format_method_doc(
+            &format!(
+                "Returns a reference to the `{}` value for the given key.",
+                name_str
+            ),
+            &field_docs,
+        );
+        let iter_doc = format_method_doc(
+            &format!("Returns an iterator over all `{}` fields.", name_str),
+            &field_docs,
+        );
+        quote! {
+            #get_doc
+            #(#method_attrs)*
+            #vis fn #name<__Q>(&self, key: &__Q) -> Option<&#value_type>
+            where
+                #key_type: ::std::borrow::Borrow<__Q>,
+                __Q: #lookup_bound ::std::hash::Hash + ::std::cmp::Eq + ?Sized,
+            {
+                #normalize_lookup
+                self.#iter_method()
+                    .find(|(k, _)| <#key_type as ::std::borrow::Borrow<__Q>>::borrow(k) == key)
+                    .map(|(_, v)| v)
+            }
+
+            #iter_doc
+            #(#method_attrs)*
+            #vis fn #iter_method(&self) -> impl Iterator<Item = (&#key_type, &#value_type)> {
+                ::structible::IterableMap::iter(&self.inner).filter_map(|(k, v)| {
+                    match (k, v) {
+                        (#field_enum::Unknown(key), #value_enum::Unknown(value)) => Some((key, value)),
+                        _ => None,
+                    }
+                })
+            }
+        }
+    });
+
+    // Known fields are hashed in declaration order through their getters. Catch-all
+    // entries are hashed one by one and summed, since the backing may iterate them
+    // in any order. Like equality, the hash leaves out fields that skip it, and the
+    // values of fields compared by presence only.
+    let hash_fields = fields
+        .iter()
+        .filter(|f| !f.is_unknown_field() && !f.config.skip_eq)
+        .map(|f| {
+            let getter_name = f.getter_name();
+            if !f.skips_eq() {
+                quote! { ::std::hash::Hash::hash(&self.#getter_name(), state); }
+            } else if f.is_optional {
+                quote! { ::std::hash::Hash::hash(&self.#getter_name().is_some(), state); }
+            } else {
+                quote! {}
+            }
+        });
+    let hash_unknown = unknown_field.filter(|f| !f.config.skip_eq).map(|f| {
+        let iter_method = format_ident!("{}_iter", f.name);
+        let entry = if f.skips_eq() {
+            quote! { entry.0 }
+        } else {
+            quote! { entry }
+        };
+        quote! {
+            let mut count = 0usize;
+            let mut sum = 0u64;
+            for entry in self.#iter_method() {
+                let mut hasher = ::std::hash::DefaultHasher::new();
+                ::std::hash::Hash::hash(&#entry, &mut hasher);
+                sum = sum.wrapping_add(::std::hash::Hasher::finish(&hasher));
+                count += 1;
+            }
+            ::std::hash::Hash::hash(&count, state);
+            ::std::hash::Hash::hash(&sum, state);
+        }
+    });
+
+    // Bound the field types (and catch-all key type) that mention type parameters
+    let type_param_idents: Vec<_> = generics.type_params().map(|tp| &tp.ident).collect();
+    let bounded_types: Vec<_> = fields
+        .iter()
+        .map(|f| &f.inner_ty)
+        .chain(fields.iter().filter_map(|f| f.unknown_key_type()))
+        .filter(|ty| type_mentions_type_param(ty, &type_param_idents))
+        .collect();
+    let bounded_where = |bound: TokenStream| {
+        let bounds = quote! { #(#bounded_types: #bound,)* };
+        if let Some(wc) = where_clause {
+            let existing = &wc.predicates;
+            quote! { where #bounds #existing }
+        } else if !bounded_types.is_empty() {
+            quote! { where #bounds }
+        } else {
+            quote! {}
+        }
+    };
+    let clone_where = bounded_where(quote! { ::std::clone::Clone });
+    let eq_where = bounded_where(quote! { ::std::cmp::Eq });
+    let hash_where = bounded_where(quote! { ::std::hash::Hash });
+    let debug_where = bounded_where(quote! { ::std::fmt::Debug });
+
+    let inner_clone = inner_clone(struct_name, fields, config);
+    let eq_body = eq_body(struct_name, fields, config);
+    let clone_impl = if config.no_clone {
+        quote! {}
+    } else {
+        quote! {
+            impl #impl_generics ::std::clone::Clone for #frozen_struct #ty_generics #clone_where {
+                fn clone(&self) -> Self {
+                    Self { inner: #inner_clone }
+                }
+            }
+        }
+    };
+
+    let debug_fields = fields.iter().filter(|f| !f.is_unknown_field()).map(|f| {
+        let name_str = f.name.unraw().to_string();
+        let variant = to_pascal_case(&f.name);
+        quote! {
+            if let Some(#value_enum::#variant(v)) = ::structible::BackingMap::get(&self.inner, &#field_enum::#variant) {
+                debug_struct.field(#name_str, v);
+            }
+        }
+    });
+    let debug_unknown = unknown_field.map(|_| debug_unknown_entries(&field_enum, &value_enum));
+    let frozen_name_str = frozen_struct.to_string();
+
+    let change_log_init = change_log_init(config);
+    let stamp_init = stamp_init(struct_name, config);
+    let expiry_init = expiry_init(struct_name, config);
+    let tombstone_init = tombstone_init(struct_name, config);
+    let source_init = source_init(struct_name, config);
+    let timestamp_init = timestamp_init(struct_name, config);
+    let undo_log_init = undo_log_init(config);
+
+    quote! {
+        #[doc = #struct_doc]
+        #vis struct #frozen_struct #generics #where_clause {
+            inner: #map_type<#field_enum, #value_enum #ty_generics>,
+        }
+
+        impl #impl_generics #frozen_struct #ty_generics #where_clause {
+            #(#getters)*
+            #unknown_methods
+
+            /// Consumes this snapshot and returns a mutable struct with the same fields.
+            ///
+            /// State kept next to the fields, such as a change log or write stamps,
+            /// starts out empty.
+            pub fn thaw(self) -> #struct_name #ty_generics {
+                #struct_name {
+                    inner: self.inner,
+                    #change_log_init
+                    #stamp_init
+                    #expiry_init
+                    #tombstone_init
+                    #source_init
+                    #timestamp_init
+                    #undo_log_init
+                }
+            }
+        }
+
+        #clone_impl
+
+        impl #impl_generics ::std::cmp::PartialEq for #frozen_struct #ty_generics #eq_where {
+            fn eq(&self, other: &Self) -> bool {
+                #eq_body
+            }
+        }
+
+        impl #impl_generics ::std::cmp::Eq for #frozen_struct #ty_generics #eq_where {}
+
+        impl #impl_generics ::std::hash::Hash for #frozen_struct #ty_generics #hash_where {
+            fn hash<__H: ::std::hash::Hasher>(&self, state: &mut __H) {
+                #(#hash_fields)*
+                #hash_unknown
+            }
+        }
+
+        impl #impl_generics ::std::fmt::Debug for #frozen_struct #ty_generics #debug_where {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                let mut debug_struct = f.debug_struct(#frozen_name_str);
+                #(#debug_fields)*
+                #debug_unknown
+                debug_struct.finish()
+            }
+        }
+    }
+}
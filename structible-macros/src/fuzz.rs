@@ -0,0 +1,134 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Generics, Ident};
+
+use crate::codegen::{field_enum_type, value_enum_name};
+use crate::parse::{FieldInfo, StructibleConfig};
+use crate::util::to_pascal_case;
+
+/// Checks that a struct with `fuzz` can be encoded, decoded, and compared.
+pub fn check_fuzz_config(struct_name: &Ident, config: &StructibleConfig) -> syn::Result<()> {
+    if !config.fuzz {
+        return Ok(());
+    }
+    let conflict = [
+        (config.is_async, "cannot be combined with `async`"),
+        (
+            config.no_structible,
+            "cannot be combined with `no_structible`",
+        ),
+        (
+            config.no_partial_eq,
+            "cannot be combined with `no_partial_eq`",
+        ),
+        (!config.serde_backing, "requires `serde_backing`"),
+    ];
+    if let Some((_, message)) = conflict.iter().find(|(conflicts, _)| *conflicts) {
+        return Err(syn::Error::new_spanned(
+            struct_name,
+            format!("`fuzz` {}", message),
+        ));
+    }
+    Ok(())
+}
+
+/// Generate the `Arbitrary` impl and `fuzz_roundtrip()`, if `fuzz` is set.
+///
+/// Arbitrary structs are built from an arbitrary value per required field, an
+/// arbitrary `Option` per optional field, and an arbitrary list of catch-all entries.
+pub fn generate_fuzz_impl(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    if !config.fuzz {
+        return quote! {};
+    }
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+    let (_, ty_generics, where_clause) = generics.split_for_impl();
+    let arbitrary = quote! { ::structible::__private::arbitrary };
+    let serde = quote! { ::structible::__private::serde };
+
+    let inserts = fields.iter().map(|f| {
+        let inner_ty = &f.inner_ty;
+        if f.is_unknown_field() {
+            let key_type = f.unknown_key_type().unwrap();
+            quote! {
+                let entries: ::std::vec::Vec<(#key_type, #inner_ty)> = #arbitrary::Arbitrary::arbitrary(u)?;
+                for (key, value) in entries {
+                    ::structible::BackingMap::insert(&mut backing, #field_enum::Unknown(key), #value_enum::Unknown(value));
+                }
+            }
+        } else {
+            let variant = to_pascal_case(&f.name);
+            if f.is_optional {
+                quote! {
+                    let value: ::std::option::Option<#inner_ty> = #arbitrary::Arbitrary::arbitrary(u)?;
+                    if let Some(value) = value {
+                        ::structible::BackingMap::insert(&mut backing, #field_enum::#variant, #value_enum::#variant(value));
+                    }
+                }
+            } else {
+                quote! {
+                    ::structible::BackingMap::insert(
+                        &mut backing,
+                        #field_enum::#variant,
+                        #value_enum::#variant(#arbitrary::Arbitrary::arbitrary(u)?),
+                    );
+                }
+            }
+        }
+    });
+
+    // Every field and catch-all key type must be arbitrary, which also keeps the
+    // bounds from being trivial for structs without type parameters
+    let field_types: Vec<_> = fields
+        .iter()
+        .map(|f| &f.inner_ty)
+        .chain(fields.iter().filter_map(|f| f.unknown_key_type()))
+        .collect();
+    let existing = where_clause.map(|wc| &wc.predicates);
+
+    let mut arbitrary_generics = generics.clone();
+    arbitrary_generics.params.insert(0, syn::parse_quote!('__a));
+    let (arbitrary_impl_generics, _, _) = arbitrary_generics.split_for_impl();
+    let (impl_generics, _, _) = generics.split_for_impl();
+
+    quote! {
+        impl #arbitrary_impl_generics #arbitrary::Arbitrary<'__a> for #struct_name #ty_generics
+        where
+            #(#field_types: #arbitrary::Arbitrary<'__a>,)*
+            #existing
+        {
+            fn arbitrary(u: &mut #arbitrary::Unstructured<'__a>) -> #arbitrary::Result<Self> {
+                let mut backing: <Self as ::structible::Structible>::Backing = ::structible::BackingMap::new();
+                #(#inserts)*
+                ::structible::Structible::try_from_backing(backing)
+                    .map_err(|_| #arbitrary::Error::IncorrectFormat)
+            }
+        }
+
+        impl #impl_generics #struct_name #ty_generics
+        where
+            #(#field_types: for<'__a> #arbitrary::Arbitrary<'__a>,)*
+            #field_enum: #serde::Serialize + #serde::de::DeserializeOwned,
+            #value_enum #ty_generics: #serde::Serialize + #serde::de::DeserializeOwned,
+            Self: ::std::cmp::PartialEq + ::std::fmt::Debug,
+            #existing
+        {
+            /// Builds a struct from arbitrary bytes, encodes and decodes it, and checks that
+            /// the result is equal to it, for use in fuzz targets.
+            ///
+            /// Bytes that don't describe a struct are accepted without checking anything.
+            pub fn fuzz_roundtrip(data: &[u8]) -> ::std::result::Result<(), ::structible::fuzz::RoundtripError> {
+                let mut u = #arbitrary::Unstructured::new(data);
+                match <Self as #arbitrary::Arbitrary>::arbitrary(&mut u) {
+                    ::std::result::Result::Ok(value) => ::structible::fuzz::__roundtrip(&value),
+                    ::std::result::Result::Err(_) => ::std::result::Result::Ok(()),
+                }
+            }
+        }
+    }
+}
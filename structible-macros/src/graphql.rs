@@ -0,0 +1,124 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::{Generics, Ident};
+
+use crate::parse::{FieldInfo, StructibleConfig};
+use crate::util::{extract_doc_comments, type_mentions_type_param};
+
+/// Returns the GraphQL name of a field, its unraw name in camelCase.
+fn graphql_field_name(name: &Ident) -> String {
+    let name = name.unraw().to_string();
+    let mut parts = name.split('_').filter(|part| !part.is_empty());
+    let mut camel = parts.next().unwrap_or_default().to_string();
+    for part in parts {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            camel.extend(first.to_uppercase());
+            camel.push_str(chars.as_str());
+        }
+    }
+    camel
+}
+
+/// Generate the async-graphql `Object` resolvers, if `graphql` is set.
+///
+/// Each known field is resolved through its getter, so absent optional fields
+/// resolve to null. Lazy fields resolve to their loaded value, and weak references
+/// are left out. The catch-all is resolved through its getter as a field taking
+/// the entry's `key` as an argument.
+pub fn generate_graphql_impl(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    if !config.graphql {
+        return quote! {};
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let graphql = quote! { ::structible::__private::async_graphql };
+    let crate_path = graphql.to_string().replace(' ', "");
+    let struct_name_str = struct_name.unraw().to_string();
+
+    let exposed: Vec<_> = fields.iter().filter(|f| !f.is_weak()).collect();
+    let resolvers = exposed.iter().map(|f| {
+        let resolver = format_ident!("__graphql_{}", f.name.unraw());
+        let name_str = graphql_field_name(&f.name);
+        let inner_ty = &f.inner_ty;
+        // Transparent fields are resolved as their newtype
+        let getter = if f.is_transparent() {
+            f.newtype_getter_name()
+        } else {
+            f.getter_name()
+        };
+        let docs = extract_doc_comments(&f.attrs);
+        if f.is_unknown_field() {
+            let key_type = f.unknown_key_type().unwrap();
+            quote! {
+                #(#[doc = #docs])*
+                #[graphql(name = #name_str)]
+                async fn #resolver(&self, key: #key_type) -> ::std::option::Option<&#inner_ty> {
+                    self.#getter(&key)
+                }
+            }
+        } else if f.is_lazy() {
+            let if_loaded = format_ident!("{}_if_loaded", getter);
+            quote! {
+                #(#[doc = #docs])*
+                #[graphql(name = #name_str)]
+                async fn #resolver(&self) -> ::std::option::Option<&#inner_ty> {
+                    self.#if_loaded()
+                }
+            }
+        } else if f.has_optional_getter() {
+            quote! {
+                #(#[doc = #docs])*
+                #[graphql(name = #name_str)]
+                async fn #resolver(&self) -> ::std::option::Option<&#inner_ty> {
+                    self.#getter()
+                }
+            }
+        } else {
+            quote! {
+                #(#[doc = #docs])*
+                #[graphql(name = #name_str)]
+                async fn #resolver(&self) -> &#inner_ty {
+                    self.#getter()
+                }
+            }
+        }
+    });
+
+    // Resolved types must be output types, and catch-all keys input types
+    let type_param_idents: Vec<_> = generics.type_params().map(|tp| &tp.ident).collect();
+    let output_types: Vec<_> = exposed
+        .iter()
+        .map(|f| &f.inner_ty)
+        .filter(|ty| type_mentions_type_param(ty, &type_param_idents))
+        .collect();
+    let input_types: Vec<_> = exposed
+        .iter()
+        .filter_map(|f| f.unknown_key_type())
+        .filter(|ty| type_mentions_type_param(ty, &type_param_idents))
+        .collect();
+    let bounds = quote! {
+        #(#output_types: #graphql::OutputType,)*
+        #(#input_types: #graphql::InputType,)*
+    };
+    let where_clause = if let Some(wc) = where_clause {
+        let existing = &wc.predicates;
+        quote! { where #bounds #existing }
+    } else if !output_types.is_empty() || !input_types.is_empty() {
+        quote! { where #bounds }
+    } else {
+        quote! {}
+    };
+
+    quote! {
+        #[#graphql::Object(crate = #crate_path, name = #struct_name_str)]
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            #(#resolvers)*
+        }
+    }
+}
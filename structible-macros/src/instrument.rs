@@ -0,0 +1,320 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{Generics, Ident};
+
+use crate::codegen::{field_enum_type, value_enum_name};
+use crate::parse::{FieldInfo, StructibleConfig};
+use crate::util::{to_pascal_case, type_mentions_type_param};
+
+/// Generate the hidden constant naming the struct in metrics labels, if `instrument`
+/// is set.
+///
+/// Writes are counted inside shared write helpers, which don't know the struct's
+/// name, so they read it from `Self`.
+pub fn generate_instrument_impl(
+    struct_name: &Ident,
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    if !config.instrument {
+        return quote! {};
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let name_str = struct_name.to_string();
+    quote! {
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            #[doc(hidden)]
+            const __STRUCTIBLE_NAME: &'static str = #name_str;
+        }
+    }
+}
+
+/// Returns a statement counting a read of the named field, if `instrument` is set.
+pub fn read_counter(config: &StructibleConfig, field_name: &Ident) -> TokenStream {
+    if !config.instrument {
+        return quote! {};
+    }
+    let name_str = field_name.unraw().to_string();
+    quote! {
+        ::structible::__private::metrics::counter!(
+            "structible_field_reads_total",
+            "struct" => Self::__STRUCTIBLE_NAME,
+            "field" => #name_str,
+        )
+        .increment(1);
+    }
+}
+
+/// Returns a statement counting a write of the owned field enum `key`, if
+/// `instrument` is set.
+///
+/// Unknown fields are labeled with the name of the catch-all rather than their
+/// key, so that the number of series stays bounded.
+pub fn write_counter(config: &StructibleConfig) -> TokenStream {
+    if !config.instrument {
+        return quote! {};
+    }
+    quote! {
+        ::structible::__private::metrics::counter!(
+            "structible_field_writes_total",
+            "struct" => Self::__STRUCTIBLE_NAME,
+            "field" => key.as_str(),
+        )
+        .increment(1);
+    }
+}
+
+/// Checks that `redact` is only used on the fields of a struct with `trace` or
+/// `record_fields`.
+pub fn check_trace_config(fields: &[FieldInfo], config: &StructibleConfig) -> syn::Result<()> {
+    if let Some(f) = fields.iter().find(|f| f.config.redact)
+        && !config.trace
+        && !config.record_fields
+    {
+        return Err(syn::Error::new_spanned(
+            &f.name,
+            "`redact` requires the struct to be declared with `trace` or `record_fields`",
+        ));
+    }
+    Ok(())
+}
+
+/// Returns the `Debug` bounds that tracing needs on generic field and key types, if
+/// `trace` is set.
+pub fn trace_bounds(
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    if !config.trace {
+        return quote! {};
+    }
+    debug_bounds(fields, generics)
+}
+
+/// Returns `Debug` bounds for generic field and key types.
+///
+/// The values of redacted fields are never formatted, so their types are left out.
+fn debug_bounds(fields: &[FieldInfo], generics: &Generics) -> TokenStream {
+    let type_param_idents: Vec<_> = generics.type_params().map(|tp| &tp.ident).collect();
+    let value_types = fields
+        .iter()
+        .filter(|f| !f.config.redact)
+        .map(|f| &f.inner_ty);
+    let key_types = fields.iter().filter_map(|f| f.unknown_key_type());
+    let traced_types = value_types
+        .chain(key_types)
+        .filter(|ty| type_mentions_type_param(ty, &type_param_idents));
+    quote! { #(#traced_types: ::std::fmt::Debug,)* }
+}
+
+/// Returns a statement emitting the trace event of a write of the owned field enum
+/// `key`, if `trace` is set.
+///
+/// `value` is an `Option` of a reference to the written or removed value.
+pub fn trace_stmt(config: &StructibleConfig, op: &str, value: TokenStream) -> TokenStream {
+    if !config.trace {
+        return quote! {};
+    }
+    quote! {
+        Self::__structible_trace(#op, &key, #value);
+    }
+}
+
+/// Generate the hidden function emitting the trace events of `trace`.
+///
+/// Events are emitted at the `TRACE` level with the `structible` target, and carry
+/// the struct and field names, the key of unknown fields, and the value, unless the
+/// field is declared with `redact`.
+pub fn generate_trace_impl(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    if !config.trace {
+        return quote! {};
+    }
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let bounds = trace_bounds(fields, config, generics);
+    let where_clause = match where_clause {
+        Some(wc) => {
+            let existing = &wc.predicates;
+            quote! { where #bounds #existing }
+        }
+        None if !bounds.is_empty() => quote! { where #bounds },
+        None => quote! {},
+    };
+    let struct_str = struct_name.to_string();
+    let tracing = quote! { ::structible::__private::tracing };
+
+    let arms = fields.iter().map(|f| {
+        let name_str = f.name.unraw().to_string();
+        let (binding, traced) = if f.config.redact {
+            (quote! { _ }, quote! { "<redacted>" })
+        } else {
+            (quote! { value }, quote! { ?value })
+        };
+        if f.is_unknown_field() {
+            quote! {
+                (#field_enum::Unknown(key), ::std::option::Option::Some(#value_enum::Unknown(#binding))) => {
+                    #tracing::trace!(
+                        target: "structible",
+                        r#struct = #struct_str,
+                        field = #name_str,
+                        key = ?key,
+                        value = #traced,
+                        "{}",
+                        op,
+                    );
+                }
+            }
+        } else {
+            let variant = to_pascal_case(&f.name);
+            quote! {
+                (#field_enum::#variant, ::std::option::Option::Some(#value_enum::#variant(#binding))) => {
+                    #tracing::trace!(
+                        target: "structible",
+                        r#struct = #struct_str,
+                        field = #name_str,
+                        value = #traced,
+                        "{}",
+                        op,
+                    );
+                }
+            }
+        }
+    });
+    let field_ty = match fields.iter().find_map(|f| f.unknown_key_type()) {
+        Some(key_type) if config.generic_key.is_none() => quote! { #field_enum<#key_type> },
+        _ => quote! { #field_enum },
+    };
+
+    quote! {
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            #[doc(hidden)]
+            fn __structible_trace(
+                op: &'static str,
+                key: &#field_ty,
+                value: ::std::option::Option<&#value_enum #ty_generics>,
+            ) {
+                match (key, value) {
+                    #(#arms)*
+                    _ => {
+                        #tracing::trace!(
+                            target: "structible",
+                            r#struct = #struct_str,
+                            field = key.as_str(),
+                            "{}",
+                            op,
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Generate `record_fields()`, if `record_fields` is set.
+///
+/// Present fields are recorded with `Visit::record_debug` under their names (or
+/// `"<redacted>"` with `record_str`, for `redact` fields), and catch-all entries as
+/// one map under the catch-all's name. The `tracing::field::Field`s come from a
+/// static callsite that is never registered, as tracing's own macros build them.
+pub fn generate_record_fields_impl(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    if !config.record_fields {
+        return quote! {};
+    }
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let bounds = debug_bounds(fields, generics);
+    let where_clause = match where_clause {
+        Some(wc) => {
+            let existing = &wc.predicates;
+            quote! { where #bounds #existing }
+        }
+        None if !bounds.is_empty() => quote! { where #bounds },
+        None => quote! {},
+    };
+    let struct_str = struct_name.to_string();
+    let tracing = quote! { ::structible::__private::tracing };
+
+    let names: Vec<_> = fields.iter().map(|f| f.name.unraw().to_string()).collect();
+    let records = fields.iter().zip(&names).map(|(f, name_str)| {
+        let (binding, record) = if f.config.redact {
+            (quote! { _ }, quote! { visitor.record_str(&field, "<redacted>") })
+        } else {
+            (quote! { value }, quote! { visitor.record_debug(&field, value) })
+        };
+        if f.is_unknown_field() {
+            return quote! {
+                let entries: ::std::vec::Vec<_> = ::structible::IterableMap::iter(&self.inner)
+                    .filter_map(|(k, v)| match (k, v) {
+                        (#field_enum::Unknown(key), #value_enum::Unknown(value)) => Some((key, value)),
+                        _ => None,
+                    })
+                    .collect();
+                if !entries.is_empty()
+                    && let Some(field) = fields.field(#name_str)
+                {
+                    let #binding = &::structible::record::__Entries(entries);
+                    #record;
+                }
+            };
+        }
+        let variant = to_pascal_case(&f.name);
+        // Expired values read as absent, so they aren't recorded either
+        let expired_check = if config.ttl && f.is_optional {
+            quote! { && !self.__structible_expired(&#field_enum::#variant) }
+        } else {
+            quote! {}
+        };
+        quote! {
+            if let Some(#value_enum::#variant(#binding)) =
+                ::structible::BackingMap::get(&self.inner, &#field_enum::#variant)
+                #expired_check
+                && let Some(field) = fields.field(#name_str)
+            {
+                #record;
+            }
+        }
+    });
+
+    quote! {
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            /// Records each present field on `visitor` as a `tracing` field named after it.
+            ///
+            /// Values are recorded with `record_debug`, except `redact` fields, which
+            /// are recorded as `<redacted>`, and catch-all entries are recorded
+            /// together under the catch-all's name.
+            pub fn record_fields(&self, visitor: &mut dyn #tracing::field::Visit) {
+                static CALLSITE: #tracing::callsite::DefaultCallsite =
+                    #tracing::callsite::DefaultCallsite::new(&METADATA);
+                static METADATA: #tracing::Metadata<'static> = #tracing::Metadata::new(
+                    #struct_str,
+                    "structible",
+                    #tracing::Level::TRACE,
+                    ::std::option::Option::None,
+                    ::std::option::Option::None,
+                    ::std::option::Option::None,
+                    #tracing::field::FieldSet::new(
+                        &[#(#names),*],
+                        #tracing::callsite::Identifier(&CALLSITE),
+                    ),
+                    #tracing::metadata::Kind::EVENT,
+                );
+                let fields = METADATA.fields();
+                #(#records)*
+            }
+        }
+    }
+}
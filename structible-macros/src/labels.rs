@@ -0,0 +1,124 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Generics, Ident, Type};
+
+use crate::codegen::{field_enum_type, value_enum_name};
+use crate::parse::{FieldInfo, StructibleConfig};
+use crate::util::{to_pascal_case, type_mentions_type_param, type_text};
+
+/// Checks that every field of a struct with `labels` holds one value per key.
+pub fn check_labels_config(fields: &[FieldInfo], config: &StructibleConfig) -> syn::Result<()> {
+    if !config.labels {
+        return Ok(());
+    }
+    if let Some(f) = fields.iter().find(|f| f.config.multi) {
+        return Err(syn::Error::new_spanned(
+            &f.name,
+            "`labels` cannot be combined with a `multi` catch-all",
+        ));
+    }
+    Ok(())
+}
+
+/// Returns the label of `value`, borrowing it if `ty` is `String`.
+fn label_expr(ty: &Type) -> TokenStream {
+    if type_text(ty) == "String" {
+        quote! { ::std::borrow::Cow::Borrowed(value.as_str()) }
+    } else {
+        quote! { ::std::borrow::Cow::Owned(::std::string::ToString::to_string(value)) }
+    }
+}
+
+/// Generate `label_values()`, if `labels` is set.
+///
+/// Each requested field is formatted with `Display`, and catch-all entries are
+/// looked up by the requested key. An absent field fails with
+/// `ValidationError::MissingLabel` rather than producing an empty label.
+pub fn generate_labels_impl(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    if !config.labels {
+        return quote! {};
+    }
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let arms = fields.iter().map(|f| {
+        let label = label_expr(&f.inner_ty);
+        if f.is_unknown_field() {
+            let key = f.normalized_key(quote! { ::std::clone::Clone::clone(key) });
+            return quote! {
+                #field_enum::Unknown(key) => match ::structible::BackingMap::get(
+                    &self.inner,
+                    &#field_enum::Unknown(#key),
+                ) {
+                    Some(#value_enum::Unknown(value)) => Some(#label),
+                    _ => None,
+                }
+            };
+        }
+        let variant = to_pascal_case(&f.name);
+        // Expired values read as absent, so they aren't labels either
+        let expired_check = if config.ttl && f.is_optional {
+            quote! { if !self.__structible_expired(field) }
+        } else {
+            quote! {}
+        };
+        quote! {
+            #field_enum::#variant => match ::structible::BackingMap::get(&self.inner, field) {
+                Some(#value_enum::#variant(value)) #expired_check => Some(#label),
+                _ => None,
+            }
+        }
+    });
+
+    // Generic field types must be displayable
+    let type_param_idents: Vec<_> = generics.type_params().map(|tp| &tp.ident).collect();
+    let bounded_types: Vec<_> = fields
+        .iter()
+        .map(|f| &f.inner_ty)
+        .filter(|ty| type_mentions_type_param(ty, &type_param_idents))
+        .collect();
+    let bounds = quote! { #(#bounded_types: ::std::fmt::Display,)* };
+    let where_clause = if let Some(wc) = where_clause {
+        let existing = &wc.predicates;
+        quote! { where #bounds #existing }
+    } else if !bounded_types.is_empty() {
+        quote! { where #bounds }
+    } else {
+        quote! {}
+    };
+
+    quote! {
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            /// Returns the values of `fields` as metric labels, in order.
+            ///
+            /// Values are formatted with `Display`, except `String` values, which are
+            /// borrowed. Catch-all entries are requested by key. Fails with
+            /// `ValidationError::MissingLabel` if a requested field is absent.
+            pub fn label_values(
+                &self,
+                fields: &[#field_enum],
+            ) -> ::std::result::Result<
+                ::std::vec::Vec<::std::borrow::Cow<'_, str>>,
+                ::structible::ValidationError,
+            > {
+                fields
+                    .iter()
+                    .map(|field| {
+                        let label = match field {
+                            #(#arms,)*
+                        };
+                        label.ok_or(::structible::ValidationError::MissingLabel {
+                            field: field.as_str(),
+                        })
+                    })
+                    .collect()
+            }
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::Ident;
+use syn::ext::IdentExt;
+
+use crate::changes::insert_expr;
+use crate::codegen::{field_enum_type, value_enum_name};
+use crate::instrument::read_counter;
+use crate::parse::{FieldInfo, StructibleConfig};
+use crate::util::{extract_doc_comments, format_method_doc, to_pascal_case};
+
+/// Checks that lazy fields have a context to load from.
+pub fn check_lazy_config(fields: &[FieldInfo], config: &StructibleConfig) -> syn::Result<()> {
+    for f in fields {
+        let Some(loader) = &f.config.lazy else {
+            continue;
+        };
+        if f.is_unknown_field() {
+            return Err(syn::Error::new_spanned(
+                loader,
+                "`lazy` is not supported on unknown fields catch-all",
+            ));
+        }
+        if let Some(alias) = f.config.aliases.first() {
+            return Err(syn::Error::new_spanned(
+                alias,
+                "`alias` is not supported on lazy fields",
+            ));
+        }
+        if config.is_async {
+            return Err(syn::Error::new_spanned(
+                loader,
+                "lazy fields are not supported with `async`",
+            ));
+        }
+        if config.context.is_none() {
+            return Err(syn::Error::new_spanned(
+                loader,
+                "lazy fields require a struct-level `context = Type` for their loader",
+            ));
+        }
+    }
+    if let Some(context) = &config.context
+        && !fields.iter().any(|f| f.is_lazy())
+    {
+        return Err(syn::Error::new_spanned(
+            context,
+            "`context` requires a lazy field to be declared",
+        ));
+    }
+    Ok(())
+}
+
+/// Generate the getters of lazy fields.
+///
+/// The getter of a lazy field takes the struct's context and, if the field is
+/// absent, calls the field's loader and stores the result through the usual write
+/// path before returning it. `<field>_if_loaded` reads the field without loading it.
+pub fn generate_lazy_getters(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+) -> Vec<TokenStream> {
+    let Some(context) = &config.context else {
+        return Vec::new();
+    };
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+
+    fields
+        .iter()
+        .filter_map(|f| Some((f, f.config.lazy.as_ref()?)))
+        .map(|(f, loader)| {
+            let name = &f.name;
+            let getter_name = f.getter_name();
+            let if_loaded_name = format_ident!("{}_if_loaded", getter_name);
+            let variant = to_pascal_case(name);
+            let ty = &f.ty;
+            let get_vis = f.getter_vis();
+            let field_docs = extract_doc_comments(&f.attrs);
+            let method_attrs = f.method_attrs();
+            let name_str = name.unraw().to_string();
+
+            let insert = insert_expr(
+                config,
+                quote! { self },
+                quote! { #field_enum::#variant },
+                quote! { #value_enum::#variant(#loader(context)) },
+            );
+            let count = read_counter(config, name);
+            let load = quote! {
+                #count
+                if ::structible::BackingMap::get(&self.inner, &#field_enum::#variant).is_none() {
+                    #insert;
+                }
+            };
+            let unreachable =
+                quote! { unreachable!("lazy field `{}` was just loaded", stringify!(#name)) };
+
+            let getter_doc = format_method_doc(
+                &format!(
+                    "Returns a reference to the `{}` value, loading it from `context` if absent.",
+                    name_str
+                ),
+                &field_docs,
+            );
+            let if_loaded_doc = format_method_doc(
+                &format!(
+                    "Returns the `{}` value if it is present, without loading it.",
+                    name_str
+                ),
+                &field_docs,
+            );
+
+            // Writes through a mutable reference can't be recorded or stamped
+            let getter_mut = if !config.has_mut_getters() {
+                quote! {}
+            } else {
+                let getter_mut_name = f.getter_mut_name();
+                let set_vis = f.setter_vis();
+                let getter_mut_doc = format_method_doc(
+                    &format!(
+                        "Returns a mutable reference to the `{}` value, loading it from `context` if absent.",
+                        name_str
+                    ),
+                    &field_docs,
+                );
+                quote! {
+                    #getter_mut_doc
+                    #(#method_attrs)*
+                    #set_vis fn #getter_mut_name(&mut self, context: &#context) -> &mut #ty {
+                        #load
+                        match ::structible::BackingMap::get_mut(&mut self.inner, &#field_enum::#variant) {
+                            Some(#value_enum::#variant(v)) => v,
+                            _ => #unreachable,
+                        }
+                    }
+                }
+            };
+
+            quote! {
+                #getter_doc
+                #(#method_attrs)*
+                #get_vis fn #getter_name(&mut self, context: &#context) -> &#ty {
+                    #load
+                    match ::structible::BackingMap::get(&self.inner, &#field_enum::#variant) {
+                        Some(#value_enum::#variant(v)) => v,
+                        _ => #unreachable,
+                    }
+                }
+
+                #if_loaded_doc
+                #(#method_attrs)*
+                #get_vis fn #if_loaded_name(&self) -> Option<&#ty> {
+                    match ::structible::BackingMap::get(&self.inner, &#field_enum::#variant) {
+                        Some(#value_enum::#variant(v)) => Some(v),
+                        _ => None,
+                    }
+                }
+
+                #getter_mut
+            }
+        })
+        .collect()
+}
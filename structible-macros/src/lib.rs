@@ -13,6 +13,7 @@
 //! - A **fields struct** for ownership extraction via `into_fields()`
 //! - The **main struct** backed by the chosen map type
 //! - An **impl block** with accessors for all fields
+//! - A **`Structible` impl** naming the backing map and both enums at the type level
 //!
 //! # Invariants
 //!
@@ -33,20 +34,108 @@
 
 extern crate proc_macro;
 
+mod accessor_traits;
+mod arrow;
+mod assertions;
+mod async_api;
+mod bench;
+mod builder;
+mod changes;
+mod codec;
 mod codegen;
+mod collection;
+mod compat;
+mod cow;
+mod csv;
+mod debug_json;
+mod envelope;
+mod example;
+mod expand;
+mod extract;
+mod fake;
+mod ffi;
+mod frozen;
+mod fuzz;
+mod graphql;
+mod instrument;
+mod labels;
+mod lazy;
+mod mapper;
+mod merge;
+mod migrate;
+mod numeric;
+mod openapi;
+mod parallel;
 mod parse;
+mod patch;
+mod promote;
+mod provenance;
+mod serde_impl;
+mod snapshot;
+mod string;
+mod timestamps;
+mod tombstone;
+mod transaction;
+mod ttl;
 mod util;
+mod validate;
+mod variants;
 
 use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
-use syn::{ItemStruct, parse_macro_input};
+use syn::ItemStruct;
 
+use crate::accessor_traits::{check_accessor_traits_config, generate_accessor_traits};
+use crate::arrow::{check_arrow_config, generate_arrow_impl};
+use crate::assertions::generate_field_assertions;
+use crate::async_api::{check_async_config, generate_async_struct};
+use crate::bench::{check_bench_config, generate_bench_impl};
+use crate::builder::{check_builder_config, generate_builder};
+use crate::changes::check_change_log_config;
+use crate::codec::{check_codec_config, generate_value_codec_impl};
 use crate::codegen::{
-    generate_debug_impl, generate_default_impl, generate_field_enum, generate_fields_debug_impl,
-    generate_fields_impl, generate_fields_struct, generate_fields_struct_trait_impls, generate_impl,
-    generate_struct, generate_struct_trait_impls, generate_value_enum,
+    check_minimal_config, check_no_panic_config, check_struct_attrs, generate_debug_impl,
+    generate_default_impl, generate_enum_aliases, generate_field_enum, generate_fields_debug_impl,
+    generate_fields_impl, generate_fields_struct, generate_fields_struct_trait_impls,
+    generate_from_tuple_impl, generate_impl, generate_struct, generate_struct_trait_impls,
+    generate_structible_impl, generate_value_enum,
 };
-use crate::parse::{StructibleConfig, parse_struct_fields};
+use crate::collection::check_collection_config;
+use crate::compat::generate_schema_impl;
+use crate::cow::{check_cow_config, generate_cow_struct};
+use crate::csv::{check_csv_config, generate_csv_impl};
+use crate::debug_json::generate_debug_json_impl;
+use crate::envelope::{check_envelope_config, generate_envelope_impl};
+use crate::example::{check_example_config, generate_example_impl};
+use crate::expand::{emit_expansion, scope_impls};
+use crate::extract::{check_extract_config, generate_from_request_impl};
+use crate::fake::{check_fake_config, generate_fake_impl};
+use crate::ffi::{check_ffi_config, generate_ffi_struct};
+use crate::frozen::{check_freeze_config, generate_frozen_struct};
+use crate::fuzz::{check_fuzz_config, generate_fuzz_impl};
+use crate::graphql::generate_graphql_impl;
+use crate::instrument::{
+    check_trace_config, generate_instrument_impl, generate_record_fields_impl, generate_trace_impl,
+};
+use crate::labels::{check_labels_config, generate_labels_impl};
+use crate::lazy::check_lazy_config;
+use crate::mapper::generate_map_field_impl;
+use crate::merge::check_merge_config;
+use crate::migrate::check_upgrade_from_config;
+use crate::numeric::check_numeric_config;
+use crate::openapi::generate_to_schema_impl;
+use crate::parse::{FieldInfo, StructibleConfig, parse_struct_fields};
+use crate::patch::{check_patch_config, generate_patch_builder};
+use crate::promote::{check_promotable_config, check_promote_config, generate_promote_impl};
+use crate::serde_impl::{check_serde_config, generate_serde_impls};
+use crate::snapshot::generate_test_serialize_impl;
+use crate::string::check_string_config;
+use crate::timestamps::check_timestamps_config;
+use crate::transaction::check_transaction_config;
+use crate::ttl::check_ttl_config;
+use crate::util::{remap_self, type_mentions_generics};
+use crate::validate::check_validation_config;
 
 /// Transforms a struct into a map-backed type with generated accessors.
 ///
@@ -69,6 +158,7 @@ use crate::parse::{StructibleConfig, parse_struct_fields};
 /// - A `PersonFields` struct for ownership extraction
 /// - The `Person` struct backed by `HashMap`
 /// - Getters, setters, and removers for each field
+/// - An impl of the `Structible` trait naming the backing map and both enums
 ///
 /// # Optional Fields
 ///
@@ -86,40 +176,606 @@ use crate::parse::{StructibleConfig, parse_struct_fields};
 /// - `name()` returns `&String` (not `Option`)
 /// - `set_name(v)` replaces the value
 /// - Use `into_fields()` then `take_name()` to extract owned value
+///
+/// Accessors finding a required field absent, e.g. after the guard of a scoped
+/// take was leaked, panic unless the struct names a diverging
+/// `on_missing_required` function. It is called with the unraw field name by
+/// getters, mutable getters, `take_<field>_scoped()`, `map_<field>()`,
+/// `to_ffi()`, and the getters of frozen snapshots.
+///
+/// # Async Mode
+///
+/// With `#[structible(async)]`, the backing must implement `AsyncBackingMap`
+/// and all accessors are fallible `async fn`s returning owned values.
+///
+/// - `new(backing, required...)` writes the required fields, while
+///   `from_backing(backing)` attaches without writing; `backing()` and
+///   `into_backing()` give the backing back
+/// - Getters return `Result<Option<T>, E>`, even for required fields, since
+///   another process may have removed them
+/// - There are no `_mut` getters, and a catch-all gets lookups, inserts, and
+///   removes but no iteration
+/// - `into_fields()`, the `Debug`, `Clone`, `PartialEq`, and `Default` impls,
+///   `with_len`, `from_tuple`, aliases, and validation are not available
+///
+/// # Merging
+///
+/// With `#[structible(lww)]`, every write is stamped with a Lamport timestamp and
+/// the id of the writing replica, and `merge(&other)` keeps the latest write to
+/// each field. Fields declared with `merge_with = path` combine both replicas'
+/// values with `path(&ours, &theirs)` instead.
+///
+/// - Stamps are ordered by timestamp, then by actor id, and removals are stamped
+///   too, so a later removal wins over an earlier set
+/// - A `merge_with` resolver is called whenever both replicas hold the field,
+///   and should be commutative, associative, and idempotent (a union, a
+///   maximum, ...) for replicas to converge
+/// - `field_stamp(&field)` returns the stamp of a field's last write
+/// - Mutable getters are not generated, since writes through them can't be
+///   stamped
+/// - The stamps are ignored by `PartialEq` and `Debug`, and dropped by
+///   `into_fields()`; with `track_changes`, merged writes are recorded
+/// - The backing must implement `IterableMap`
+///
+/// # Validation
+///
+/// With `invariant = path`, `validate()` and `try_<constructor>()` call
+/// `path(&self) -> Result<(), E>`. Both return `ValidationError` unless another
+/// type is given with `error = MyError`, into which `E` is converted with `From`.
+///
+/// Fields declaring constraints (`requires`, `conflicts_with`, ...) get a `try_set_<field>()` that
+/// checks them before inserting the value, and `validate()` checks them for the
+/// whole struct. The plain setters and removers never check constraints, so
+/// e.g. removing a field another field `requires` succeeds silently. With a
+/// custom `error` type, constraint violations are converted from
+/// `ValidationError` with `From`.
+///
+/// `validate()` stops at the first violation, while `validate_all()` runs the
+/// same checks and returns every violation, in the same order.
+///
+/// The bounds of `range` are compared with `PartialOrd`, so `NaN` is out of
+/// every range.
+///
+/// `matches` patterns are parsed when the macro expands and compiled the first
+/// time a value is checked against them, and values are matched through
+/// `AsRef<str>`.
+///
+/// # Accessor Visibility
+///
+/// Accessors use the field's visibility, overridden by the struct's
+/// `accessor_vis`, which is in turn overridden by the field's own `vis`.
+///
+/// `get_vis`, `set_vis` (setters, `_mut` getters, and `try_set_*`), and
+/// `remove_vis` override these per accessor kind, falling back to `vis` and
+/// then to the field's visibility. On a catch-all, lookups and iteration follow
+/// `get_vis`, insertion and mutable access `set_vis`, and removal `remove_vis`.
+///
+/// # Change Tracking
+///
+/// With `#[structible(track_changes)]`, the constructor records the required
+/// fields, so a log replays from an empty struct, and removing an absent field
+/// isn't recorded. Mutable getters are not generated, since writes through them
+/// can't be recorded. The change log is ignored by `PartialEq` and `Debug`, and
+/// dropped by `into_fields()`.
+///
+/// `apply_changes()` replays a log as-is, panicking on a `Set` whose value
+/// belongs to another field, while `try_apply_changes()` works on a copy and
+/// only commits it if it is valid. Replayed changes are recorded in the
+/// struct's own change log.
+///
+/// # Lazy Fields
+///
+/// A field declared with `lazy = loader` is loaded with `loader(&context)` the
+/// first time it is read while absent, where the context type is named by the
+/// struct's `context = Type`. Lazy fields are declared without `Option`, but are
+/// stored like optional fields: they may be absent, can be set and removed, and
+/// don't prevent `Default`. The loaded value is stored like a set, so it is
+/// recorded by `track_changes` and stamped by `lww`. Lazy fields can't be
+/// catch-alls, have aliases, or be used with `async`.
+///
+/// # Read-Only Structs
+///
+/// `no_setters` also skips `try_set_*`, scoped takes, maps, and catch-all
+/// inserts, `no_removers` catch-all removes, and `no_mut_getters` the catch-all's
+/// `_mut` and `_iter_mut` methods, including those of alias accessors. Optional
+/// fields can still be filled in by other means, such as `Default`, lazy loading,
+/// or `apply_changes()`, and `into_fields()` still hands out owned values. The
+/// flags are not supported with `async`.
+///
+/// # Snapshot Tests
+///
+/// With `#[structible(test_serialize)]`, field types (and generic catch-all key
+/// types) must implement `Serialize`. Catch-all entries are nested under the
+/// catch-all's name in the backing's iteration order, so use insta's `sort_maps`
+/// with a `HashMap` backing. Values expired under `ttl` are skipped.
+///
+/// # Legacy Representations
+///
+/// A field declared with `upgrade_from = Old, with = path` is decoded as its own
+/// type if possible, and otherwise as `Old`, converted with
+/// `path(Old) -> Result<T, E>`; a conversion error fails decoding. This applies
+/// to the serde adapter (with `serde_backing`) and to `ValueCodec` (with
+/// `value_codec`). The fallback relies on serde's untagged enums, so it needs a
+/// self-describing format.
+///
+/// # Catch-All Keys
+///
+/// Keys of a catch-all declared with `normalize = path` are passed through
+/// `path(K) -> K` by the generated inserts and lookups, which clone a borrowed key
+/// into an owned one to normalize it. Keys that enter the backing in other ways,
+/// such as `try_from_backing()` or deserialization, are kept as they are.
+///
+/// With `validate_key = path`, `validate()` and `try_insert_<catch-all>()` reject
+/// keys naming a known field with `ValidationError::ReservedKey`, and pass other
+/// keys to `path(&K) -> Result<(), ValidationError>`, so that deref coercion
+/// applies. The key type must borrow as `str`.
+///
+/// A catch-all declared `promote_known` moves entries whose key names a known
+/// field into that field, when they are inserted with `insert_<catch-all>()`
+/// (which then returns `None`) and when they are decoded by the serde adapter or
+/// `ValueCodec`, through `Structible::route_entry()`. The key type must borrow
+/// as `str`, and the value is converted to the field's type through serde;
+/// values that don't convert stay in the catch-all. A decoded entry routed to a
+/// field that is also present on its own fails as a duplicate field.
+///
+/// A field declared `promotable` gets `promote_<catch-all>_to_<field>(key)`,
+/// moving the catch-all entry for `key` into the field, and, if optional,
+/// `demote_<field>_to_<catch-all>(key)`, moving the field's value into the
+/// catch-all under `key`. Values are converted from a clone with `TryInto`, and
+/// stay where they were if that fails. Both return `Ok(false)` if there was
+/// nothing to move.
+///
+/// The key type may mention the struct's generic parameters. It must satisfy
+/// the backing map's bounds (`Hash + Eq` for `HashMap`, `Ord` for `BTreeMap`),
+/// which the struct declares itself, and its parameters are bounded like those
+/// of the value types in the generated impls, e.g. `K: Debug` for `Debug`.
+///
+/// # Metrics
+///
+/// With `#[structible(instrument)]`, writes are counted by setters, removers that
+/// found a value, catch-all inserts and removes, and the constructor, and reads
+/// by getters and `_mut` getters. Catch-all entries are labeled with the
+/// catch-all's name rather than their key, so the number of series stays
+/// bounded.
+///
+/// # Tracing
+///
+/// With `#[structible(trace)]`, events have the `structible` target and carry the
+/// key of catch-all entries. Values are formatted with `Debug`, so generic field
+/// and key types must implement it unless the field is redacted. Removals are
+/// only traced when a value was found.
+///
+/// # Transactions
+///
+/// With `#[structible(transactional)]`, each write made while `transaction(f)`
+/// runs saves the previous entry of its key to an undo log, which is restored in
+/// reverse order if `f` returns an error, and dropped otherwise. Transactions can
+/// be nested, and a failing inner transaction only undoes its own writes. With
+/// `track_changes`, the changes of a failed transaction are dropped from the
+/// change log. Mutable getters are not generated, since writes through them
+/// can't be undone, and writes are not undone if `f` panics.
+///
+/// # OpenAPI
+///
+/// The schema of `#[structible(openapi)]` has a property per known field, named
+/// after the field and required if the field is. The bounds of a field's `range`
+/// become its `minimum` and `maximum`, those of `len` or `non_empty` its
+/// `minLength`/`maxLength` or `minItems`/`maxItems`, and a `matches` pattern its
+/// `pattern`. Catch-all entries are described by `additionalProperties`, which is
+/// false for structs without a catch-all. Field schemas are inlined, and the
+/// schemas they reference are collected by `ToSchema::schemas()`, so generic field
+/// types must implement `ToSchema`.
+///
+/// # GraphQL
+///
+/// The object of `#[structible(graphql)]` has a camelCase field per known field,
+/// described by the field's doc comment, and a catch-all field taking the entry's
+/// key as an argument. Lazy fields resolve to their value if it was loaded and
+/// `null` otherwise, and weak references are left out. Generic field types must
+/// implement `OutputType`, and catch-all key types `InputType`.
+///
+/// # Minimal Structs
+///
+/// `#[structible(minimal)]` skips the `<Struct>Fields` companion and
+/// `into_fields()`, `take_<field>_scoped()`, `map_<field>()`, `field_paths()`,
+/// `fields_in_order()`, `compat_report()`, the `Debug` and `Default` impls, and
+/// the `Structible` and `Schema` impls. `Clone` and `PartialEq` are still derived
+/// unless disabled, and flags like `track_changes`, `freeze`, or `patch` keep
+/// working. Catch-all fields are not supported, and the flags building on the
+/// skipped impls (`value_codec`, `csv`, `arrow`, `envelope`, `fuzz`, `axum`, and
+/// `cow`) are rejected.
+///
+/// # Tombstones
+///
+/// With `#[structible(tombstones)]`, removing an optional field, even an absent
+/// one, leaves a tombstone until a value is written to it again, including by
+/// `merge()`. Expiries, `apply_changes()`, and `merge()` don't record tombstones,
+/// and neither does the catch-all. Tombstones are cloned, but ignored by
+/// `PartialEq` and `Debug`, and dropped by `into_backing()` and `freeze()`.
+///
+/// # Transparent Fields
+///
+/// The getter of a `#[structible(transparent = T)]` field returns a `T` converted
+/// with `Into` from a clone of the newtype, so the newtype must implement `Clone`
+/// and `Into<T>`, and `<field>_newtype()` returns a reference to the newtype
+/// itself. The constructor and setter take any `impl Into<Newtype>`, while mutable
+/// getters, removers, and the other methods work on the newtype.
+///
+/// # Variant Predicates
+///
+/// `#[structible(variants(A, B))]` generates an `is_<getter>_<variant>()`
+/// predicate per variant, named with the variant in snake case, which matches the
+/// variant whatever its kind and returns false for an absent optional field. The
+/// field's type must be a path to the enum.
+///
+/// # Accessor Docs
+///
+/// A `doc_template` documents the getters, mutable getters, setters, and removers
+/// in place of their fixed sentence, and the field's docs are still appended to
+/// it. `{field}` is replaced by the field name, `{type}` by its declared type, and
+/// `{method}` by the accessor's name; other placeholders are rejected.
+///
+/// The struct's own docs are followed by an "Accessors" table of each field's
+/// declared type and the names of its getter, setter, and remover, leaving out
+/// accessors less visible than a public struct. The `<Struct>Fields` companion
+/// is documented with the first paragraph of the struct's docs.
+///
+/// # Trait Objects
+///
+/// Fields whose type is named `Box<dyn Trait>`, optional or not, get getters
+/// returning `&dyn Trait`, while their mutable getters still return the box so it
+/// can be replaced. The trait must have `Debug` as a supertrait. These fields, and
+/// any other field declared `no_eq`, are only compared by their presence: two
+/// structs are equal if they agree on every other field and both or neither hold
+/// this one.
+///
+/// # Skipping Fields
+///
+/// A `skip_clone` field is left out of clones if optional, and set to its default
+/// if required, so its type must implement `Default` rather than `Clone`. A
+/// `skip_eq` field is ignored by comparisons altogether, which then iterate the
+/// backing, so it must implement `IterableMap`. Frozen snapshots hash fields the
+/// way they compare them. `skip_clone` isn't allowed on the catch-all nor with
+/// `no_clone`, and `skip_eq` isn't allowed with `no_partial_eq`.
+///
+/// # Accessor Traits
+///
+/// The `<Struct>Reader` and `<Struct>Writer` traits of `accessor_traits` have the
+/// struct's visibility and generics. Accessors less visible than the struct, lazy
+/// getters, the catch-all's methods, and the families skipped by `no_setters` and
+/// `no_removers` are left out, and no writer trait is generated without any
+/// writer methods.
+///
+/// With `mock`, the traits are mocked by `mockall` in test builds. Optional
+/// getters are declared with an explicit lifetime, so that mockall can mock
+/// them, and their mocks return `'static` references.
+///
+/// # Attribute Ordering
+///
+/// Attributes written on the struct are copied to the generated struct in the
+/// order written, so derives and attribute macros placed after `#[structible]`
+/// apply to the map-backed struct, and ones placed before it see the declared
+/// fields. Derives of `Debug`, `Clone`, `PartialEq`, and `Default` conflict with
+/// the generated impls, and `Copy`, serde's `Serialize` and `Deserialize`, and
+/// `derive_builder`'s `Builder` would work on the backing map, so all of them are
+/// rejected, also inside `cfg_attr`. Use `serde`, `serde_backing`, or `builder`
+/// instead.
+///
+/// # Accessor Names
+///
+/// `getter_prefix` (none by default) prefixes getters and `_mut` getters,
+/// `setter_prefix` (`set_` by default) setters, and `remover_prefix` (`remove_`
+/// by default) removers, including the deprecated accessors of `alias` names. A
+/// field's own `get`, `get_mut`, `set`, or `remove` name takes precedence, and the
+/// catch-all's methods keep their names.
+///
+/// # Field Helpers
+///
+/// The helpers of a `collection` field depend on its type: `Vec` and `VecDeque`
+/// fields push to the back, sets insert and return whether the value was new, and
+/// maps take a key and a value, return the previous value, and check for a key in
+/// `<field>_contains()`. `extend_<field>()` takes any `IntoIterator` of elements,
+/// or of key-value pairs for maps. Each method declares the bounds it needs, so
+/// fields of other element types can be declared as usual. `push_` and `extend_`
+/// are skipped with `no_setters`, and hashed collections with a custom hasher
+/// aren't supported.
+///
+/// `append_<field>()` of a `string` field treats an absent optional string as
+/// empty, while `clear_<field>()` leaves it absent. `set_<field>_str()`, named
+/// after the setter, is only generated for optional fields. String helpers are
+/// rejected with `no_setters`.
+///
+/// `incr_<field>()` and `decr_<field>()` of a `numeric` field use `+=` and
+/// `-=`, so they overflow like them. Integer fields also get saturating and
+/// checked variants, and the checked ones return the new value, or `None`
+/// without touching the field on overflow. An absent optional field counts as
+/// zero. Numeric helpers are rejected with `no_setters`.
+///
+/// Helpers are not supported on the catch-all, lazy, weak, or transparent fields,
+/// nor with `async` or on structs recording writes (`track_changes`, `lww`,
+/// `transactional`).
+///
+/// # Serde
+///
+/// With `#[structible(serde)]`, the struct is encoded like a plain struct whose
+/// absent optional fields are omitted, and missing or `null` optional fields
+/// decode as absent. Fields are named without a raw identifier prefix, e.g. `type`
+/// for `r#type`. A catch-all's key must be `String`, and serializing fails on a
+/// key that names a field, rather than writing it twice.
+///
+/// Decoding fails on missing required fields, duplicate field names, and values
+/// of the wrong type, but doesn't run the struct's validation. Names of no field
+/// are decoded into the catch-all, like `#[serde(flatten)]`, and rejected by
+/// structs without one.
+///
+/// # No-Panic Mode
+///
+/// With `#[structible(no_panic)]`, the getters of required fields return
+/// `Option`, and so do their mutable getters, scoped takes, and
+/// `<field>_or_default_mut()`. `map_<field>()` leaves a missing value missing,
+/// and patches skip mismatched changes. `no_panic` is incompatible with
+/// `track_changes`, `ttl`, `ffi`, `builder = panic`, `builder = typestate`,
+/// `on_missing_required`, `async`, and lazy, `collection`, `string`, and
+/// `numeric` fields.
+///
+/// With the `no-panic` feature, linking fails if the optimizer can't prove that a
+/// `&self` getter never unwinds. The check needs optimizations, so the feature
+/// fails to compile in builds with debug assertions. It covers the getters
+/// themselves, including the backing map's `get`, so a custom backing or a
+/// key type's `Hash` or `Ord` impl that may panic fails it.
+///
+/// # Expiring Fields
+///
+/// With `#[structible(ttl)]`, getters, mutable getters, removers, `len()`,
+/// `PartialEq`, `Debug`, and `into_fields()` treat expired values as absent.
+/// Expired values stay in the backing map until `purge_expired()` or a remover
+/// drops them, so validation and the `Structible` trait still see them. Plain
+/// setters store values that never expire, as does a TTL too long to represent as
+/// an `Instant`. Required fields and the catch-all never expire.
 #[proc_macro_attribute]
 pub fn structible(attr: TokenStream, item: TokenStream) -> TokenStream {
-    let config = match syn::parse::<StructibleConfig>(attr) {
+    expand_structible(attr.into(), item.into()).into()
+}
+
+/// Generates a map-backed struct from the fields of the annotated struct.
+///
+/// This is an alternative to the attribute macro for code where attribute
+/// macros get in the way, e.g. of rustfmt or IDEs. A derive can't replace the
+/// struct it is applied to, so the annotated struct only declares the fields,
+/// and the map-backed struct is generated next to it under the name given by
+/// `name = ...`:
+///
+/// ```ignore
+/// use structible::Structible;
+///
+/// #[derive(Structible)]
+/// #[structible(name = Person, backing = BTreeMap)]
+/// pub struct PersonDef {
+///     pub name: String,
+///     pub age: Option<u32>,
+///     #[structible(key = String)]
+///     pub extra: Option<String>,
+/// }
+/// ```
+///
+/// The struct-level `#[structible(...)]` attributes are combined and take the
+/// same arguments as the attribute macro, and field attributes are the same
+/// too. Only doc comments are copied to the generated struct.
+#[proc_macro_derive(Structible, attributes(structible))]
+pub fn derive_structible(item: TokenStream) -> TokenStream {
+    expand_derive(item.into()).into()
+}
+
+/// Expands `#[structible]` on a struct, or into the compile error it causes.
+///
+/// The expansion depends only on the attribute and the struct's tokens, so that
+/// identical definitions expand to identical code and incremental builds can
+/// reuse the work done on them.
+fn expand_structible(attr: TokenStream2, item: TokenStream2) -> TokenStream2 {
+    let config = match syn::parse2::<StructibleConfig>(attr) {
+        Ok(c) => c,
+        Err(e) => return e.to_compile_error(),
+    };
+
+    let input = match syn::parse2::<ItemStruct>(item) {
+        Ok(i) => i,
+        Err(e) => return e.to_compile_error(),
+    };
+    if let Some(name) = &config.name {
+        return syn::Error::new_spanned(
+            name,
+            "`name` is only supported by `#[derive(Structible)]`",
+        )
+        .to_compile_error();
+    }
+    expand_struct(config, input)
+}
+
+/// Expands `#[derive(Structible)]` on a struct, or into the compile error it causes.
+fn expand_derive(item: TokenStream2) -> TokenStream2 {
+    let mut input = match syn::parse2::<ItemStruct>(item) {
+        Ok(i) => i,
+        Err(e) => return e.to_compile_error(),
+    };
+    let mut args = Vec::new();
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|a| a.path().is_ident("structible"))
+    {
+        match attr.meta.require_list() {
+            Ok(list) if !list.tokens.is_empty() => args.push(list.tokens.clone()),
+            Ok(_) => {}
+            Err(e) => return e.to_compile_error(),
+        }
+    }
+    let config = match syn::parse2::<StructibleConfig>(quote! { #(#args),* }) {
         Ok(c) => c,
-        Err(e) => return e.to_compile_error().into(),
+        Err(e) => return e.to_compile_error(),
     };
+    let Some(name) = config.name.clone() else {
+        return syn::Error::new_spanned(
+            &input.ident,
+            "`#[derive(Structible)]` requires `#[structible(name = ...)]` naming the generated struct",
+        )
+        .to_compile_error();
+    };
+    input.ident = name;
+    input.attrs.retain(|a| a.path().is_ident("doc"));
+    expand_struct(config, input)
+}
+
+/// Checks that the struct's configuration and fields are supported together,
+/// returning the first error found.
+fn validate_all(
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    input: &ItemStruct,
+) -> syn::Result<()> {
+    let name = &input.ident;
+    check_validation_config(fields, config)?;
+    check_change_log_config(name, config)?;
+    check_merge_config(name, fields, config)?;
+    check_codec_config(name, config)?;
+    check_csv_config(name, fields, config)?;
+    check_arrow_config(name, config)?;
+    check_collection_config(fields, config)?;
+    check_string_config(fields, config)?;
+    check_numeric_config(fields, config)?;
+    check_lazy_config(fields, config)?;
+    check_upgrade_from_config(fields, config)?;
+    check_promote_config(name, fields, config)?;
+    check_promotable_config(fields, config)?;
+    check_ttl_config(name, fields, config)?;
+    check_freeze_config(name, config)?;
+    check_cow_config(name, config)?;
+    check_ffi_config(fields, config, &input.generics)?;
+    check_bench_config(name, config, &input.generics)?;
+    check_fuzz_config(name, config)?;
+    check_serde_config(name, fields, config)?;
+    check_envelope_config(name, config)?;
+    check_example_config(name, fields, config)?;
+    check_fake_config(fields, config)?;
+    check_trace_config(fields, config)?;
+    check_labels_config(fields, config)?;
+    check_transaction_config(name, config)?;
+    check_patch_config(name, config)?;
+    check_builder_config(name, config)?;
+    check_extract_config(name, config)?;
+    check_minimal_config(name, fields, config)?;
+    check_no_panic_config(name, fields, config)?;
+    check_timestamps_config(config)?;
+    check_accessor_traits_config(name, config)?;
+    check_struct_attrs(&input.attrs, fields, config)?;
+    if config.is_async {
+        check_async_config(name, fields, config)?;
+    }
+    Ok(())
+}
 
-    let input = parse_macro_input!(item as ItemStruct);
+/// Generates the items of a struct with the given configuration.
+fn expand_struct(mut config: StructibleConfig, mut input: ItemStruct) -> TokenStream2 {
+    if let Err(e) = remap_self(&mut input) {
+        return e.to_compile_error();
+    }
 
-    let fields = match parse_struct_fields(&input) {
+    let fields = match parse_struct_fields(&input, &config) {
         Ok(f) => f,
-        Err(e) => return e.to_compile_error().into(),
+        Err(e) => return e.to_compile_error(),
     };
+    config.generic_key = fields
+        .iter()
+        .find_map(|f| f.unknown_key_type())
+        .filter(|ty| type_mentions_generics(ty, &input.generics))
+        .cloned();
+
+    if let Err(e) = validate_all(&fields, &config, &input) {
+        return e.to_compile_error();
+    }
 
     let name = &input.ident;
     let vis = &input.vis;
     let attrs = &input.attrs;
     let generics = &input.generics;
 
-    let field_enum = generate_field_enum(name, &fields);
+    let field_enum = generate_field_enum(name, &fields, &config);
     let value_enum = generate_value_enum(name, &fields, &config, generics);
-    let fields_struct = generate_fields_struct(name, vis, &fields, &config, generics);
-    let fields_impl = generate_fields_impl(name, &fields, &config, generics);
-    let fields_debug_impl = generate_fields_debug_impl(name, &fields, generics);
-    let fields_trait_impls = generate_fields_struct_trait_impls(name, &fields, &config, generics);
-    let struct_def = generate_struct(name, vis, &config, attrs, generics);
-    let debug_impl = generate_debug_impl(name, &fields, generics);
+    let enum_aliases = generate_enum_aliases(name, vis, &config, generics);
+    let field_assertions = generate_field_assertions(&fields, &config, generics);
+
+    if config.is_async {
+        let async_struct = generate_async_struct(name, vis, &fields, &config, attrs, generics);
+        let expanded = quote! {
+            #field_enum
+            #value_enum
+            #enum_aliases
+            #field_assertions
+            #async_struct
+        };
+        let expanded = scope_impls(name, generics, expanded);
+        emit_expansion(name, &config, &expanded);
+        return expanded;
+    }
+
+    // Minimal structs skip the fields struct, the `Debug` impls, and `Default`
+    let (fields_struct, fields_impl, fields_debug_impl, fields_trait_impls, debug_impl) =
+        if config.minimal {
+            Default::default()
+        } else {
+            (
+                generate_fields_struct(name, vis, &fields, &config, attrs, generics),
+                generate_fields_impl(name, &fields, &config, generics),
+                generate_fields_debug_impl(name, &fields, &config, generics),
+                generate_fields_struct_trait_impls(name, &fields, &config, generics),
+                generate_debug_impl(name, &fields, &config, generics),
+            )
+        };
+    let struct_def = generate_struct(name, vis, &fields, &config, attrs, generics);
     let struct_trait_impls = generate_struct_trait_impls(name, &fields, &config, generics);
     let impl_block = generate_impl(name, &fields, &config, generics);
-    let default_impl = generate_default_impl(name, &fields, &config, generics);
+    let instrument_impl = generate_instrument_impl(name, &config, generics);
+    let trace_impl = generate_trace_impl(name, &fields, &config, generics);
+    let record_fields_impl = generate_record_fields_impl(name, &fields, &config, generics);
+    let labels_impl = generate_labels_impl(name, &fields, &config, generics);
+    let structible_impl = generate_structible_impl(name, &fields, &config, generics);
+    let map_field_impl = generate_map_field_impl(name, &fields, &config, generics);
+    let promote_impl = generate_promote_impl(name, &fields, &config, generics);
+    let value_codec_impl = generate_value_codec_impl(name, &fields, &config, generics);
+    let csv_impl = generate_csv_impl(name, &fields, &config, generics);
+    let arrow_impl = generate_arrow_impl(name, vis, &fields, &config, generics);
+    let debug_json_impl = generate_debug_json_impl(name, &fields, &config, generics);
+    let test_serialize_impl = generate_test_serialize_impl(name, &fields, &config, generics);
+    let serde_impls = generate_serde_impls(name, &fields, &config, generics);
+    let schema_impl = generate_schema_impl(name, &fields, &config, generics);
+    let envelope_impl = generate_envelope_impl(name, &fields, &config, generics);
+    let example_impl = generate_example_impl(name, &fields, &config, generics);
+    let fake_impl = generate_fake_impl(name, &fields, &config, generics);
+    let fuzz_impl = generate_fuzz_impl(name, &fields, &config, generics);
+    let bench_impl = generate_bench_impl(name, &fields, &config);
+    let to_schema_impl = generate_to_schema_impl(name, &fields, &config, generics);
+    let from_request_impl = generate_from_request_impl(name, &fields, &config, generics);
+    let graphql_impl = generate_graphql_impl(name, &fields, &config, generics);
+    let frozen_struct = generate_frozen_struct(name, vis, &fields, &config, generics);
+    let cow_struct = generate_cow_struct(name, vis, &fields, &config, generics);
+    let ffi_struct = generate_ffi_struct(name, vis, &fields, &config);
+    let patch_builder = generate_patch_builder(name, vis, &fields, &config, generics);
+    let builder = generate_builder(name, vis, &fields, &config, generics);
+    let accessor_traits = generate_accessor_traits(name, vis, &fields, &config, generics);
+    let default_impl = if config.minimal {
+        None
+    } else {
+        generate_default_impl(name, &fields, &config, generics)
+    };
+    let from_tuple_impl = match generate_from_tuple_impl(name, &fields, &config, generics) {
+        Ok(i) => i,
+        Err(e) => return e.to_compile_error(),
+    };
 
     let expanded = quote! {
         #field_enum
         #value_enum
+        #enum_aliases
+        #field_assertions
         #fields_struct
         #fields_impl
         #fields_debug_impl
@@ -128,8 +784,608 @@ pub fn structible(attr: TokenStream, item: TokenStream) -> TokenStream {
         #debug_impl
         #struct_trait_impls
         #impl_block
+        #instrument_impl
+        #trace_impl
+        #record_fields_impl
+        #labels_impl
+        #structible_impl
+        #map_field_impl
+        #promote_impl
+        #value_codec_impl
+        #csv_impl
+        #arrow_impl
+        #debug_json_impl
+        #test_serialize_impl
+        #serde_impls
+        #schema_impl
+        #envelope_impl
+        #example_impl
+        #fake_impl
+        #fuzz_impl
+        #bench_impl
+        #to_schema_impl
+        #from_request_impl
+        #graphql_impl
+        #frozen_struct
+        #cow_struct
+        #ffi_struct
+        #patch_builder
+        #builder
+        #accessor_traits
         #default_impl
+        #from_tuple_impl
     };
 
-    expanded.into()
+    let expanded = scope_impls(name, generics, expanded);
+    emit_expansion(name, &config, &expanded);
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+
+    use super::*;
+
+    #[test]
+    fn test_expansion_is_deterministic() {
+        let attr = quote! { backing = BTreeMap, track_changes, with_len, debug_json };
+        let item = quote! {
+            pub struct Person<T: Clone> {
+                pub name: String,
+                #[structible(requires = name)]
+                pub age: Option<u32>,
+                pub tag: Option<T>,
+                #[structible(key = String)]
+                pub extra: Option<String>,
+            }
+        };
+        let first = expand_structible(attr.clone(), item.clone()).to_string();
+        let second = expand_structible(attr, item).to_string();
+        assert!(!first.contains("compile_error"));
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_doc_template() {
+        let attr = quote! { doc_template = "`{method}` accesses the {field} field ({type})." };
+        let item = quote! {
+            pub struct Person {
+                /// The person's name.
+                pub name: String,
+                pub tags: Option<Vec<String>>,
+            }
+        };
+        let output = expand_structible(attr, item).to_string();
+        assert!(output.contains(r#""`name` accesses the name field (String).\n\n## Field Documentation\n The person's name.""#));
+        assert!(output.contains(r#""`set_tags` accesses the tags field (Option<Vec<String>>).""#));
+        assert!(
+            output.contains(r#""`remove_tags` accesses the tags field (Option<Vec<String>>).""#)
+        );
+    }
+
+    #[test]
+    fn test_unchecked_requires_notes() {
+        let item = quote! {
+            pub struct Location {
+                pub coordinates: Option<String>,
+                #[structible(requires = coordinates)]
+                pub relative_to: Option<String>,
+            }
+        };
+        let output = expand_structible(quote! {}, item).to_string();
+        assert!(output.contains(
+            "This doesn't check that `coordinates` is present, as `requires` demands; only `try_set_relative_to` and `validate()` enforce it."
+        ));
+        assert!(output.contains(
+            "This doesn't check the fields that require it (`relative_to`); only `validate()` reports them."
+        ));
+    }
+
+    #[test]
+    fn test_non_exhaustive_enums() {
+        let item = quote! {
+            pub struct Person {
+                pub name: String,
+                #[structible(key = String)]
+                pub extra: Option<String>,
+            }
+        };
+        let count = |attr| {
+            expand_structible(attr, item.clone())
+                .to_string()
+                .matches("# [non_exhaustive]")
+                .count()
+        };
+        assert_eq!(count(quote! { non_exhaustive }), 2);
+        assert_eq!(count(quote! {}), 0);
+    }
+
+    #[test]
+    fn test_doc_template_rejects_unknown_placeholder() {
+        let attr = quote! { doc_template = "Accesses {name}." };
+        let item = quote! {
+            pub struct Person {
+                pub name: String,
+            }
+        };
+        let output = expand_structible(attr, item).to_string();
+        assert!(output.contains("unknown placeholder `{name}` in `doc_template`"));
+    }
+
+    #[test]
+    fn test_accessor_prefix_must_start_an_identifier() {
+        let item = quote! {
+            pub struct Person {
+                pub name: String,
+            }
+        };
+        let output = expand_structible(quote! { getter_prefix = "get-" }, item.clone()).to_string();
+        assert!(output.contains("accessor prefixes may only contain"));
+        let output = expand_structible(quote! { setter_prefix = "2_" }, item).to_string();
+        assert!(output.contains("accessor prefixes may only contain"));
+    }
+
+    #[test]
+    fn test_required_context_requires_optional_field() {
+        let item = quote! {
+            pub struct Person {
+                #[structible(required_context = "always set")]
+                pub name: String,
+            }
+        };
+        let output = expand_structible(quote! {}, item).to_string();
+        assert!(output.contains("`required_context` is only supported on optional fields"));
+    }
+
+    #[test]
+    fn test_collection_requires_std_collection() {
+        let item = quote! {
+            pub struct Page {
+                #[structible(collection)]
+                pub links: Option<LinkedList<String>>,
+            }
+        };
+        let output = expand_structible(quote! {}, item).to_string();
+        assert!(output.contains("`collection` requires a `Vec`"));
+
+        let item = quote! {
+            pub struct Page {
+                #[structible(collection)]
+                pub links: Vec<String>,
+            }
+        };
+        let output = expand_structible(quote! { track_changes }, item).to_string();
+        assert!(output.contains("`collection` is not supported with `track_changes`"));
+    }
+
+    #[test]
+    fn test_string_requires_string_field() {
+        let item = quote! {
+            pub struct Message {
+                #[structible(string)]
+                pub body: Option<Vec<u8>>,
+            }
+        };
+        let output = expand_structible(quote! {}, item).to_string();
+        assert!(output.contains("`string` requires a `String` or `Option<String>` field"));
+    }
+
+    #[test]
+    fn test_numeric_requires_primitive_number() {
+        let item = quote! {
+            pub struct Counters {
+                #[structible(numeric)]
+                pub hits: Wrapping<u32>,
+            }
+        };
+        let output = expand_structible(quote! {}, item).to_string();
+        assert!(output.contains("`numeric` requires a primitive integer or float field"));
+    }
+
+    #[test]
+    fn test_bench_config() {
+        let item = quote! {
+            pub struct Person {
+                pub name: String,
+                pub email: Option<String>,
+            }
+        };
+        let output = expand_structible(quote! { bench }, item).to_string();
+        assert!(output.contains("pub fn bench_accessors"));
+        assert!(output.contains("\"email/remove\""));
+        assert!(output.contains("\"name/set\""));
+        assert!(!output.contains("\"name/remove\""));
+
+        let item = quote! {
+            pub struct Wrapper<T> {
+                pub value: T,
+            }
+        };
+        let output = expand_structible(quote! { bench }, item).to_string();
+        assert!(output.contains("`bench` is not supported on generic structs"));
+    }
+
+    #[test]
+    fn test_skip_clone_rejected() {
+        let item = quote! {
+            pub struct Person {
+                #[structible(skip_clone)]
+                pub cache: Option<String>,
+            }
+        };
+        let output = expand_structible(quote! { no_clone }, item).to_string();
+        assert!(output.contains("`skip_clone` has no effect with `no_clone`"));
+
+        let item = quote! {
+            pub struct Person {
+                #[structible(key = String, skip_clone)]
+                pub extra: Option<String>,
+            }
+        };
+        let output = expand_structible(quote! {}, item).to_string();
+        assert!(output.contains("`skip_clone` is not supported on the unknown fields catch-all"));
+    }
+
+    #[test]
+    fn test_mock_requires_accessor_traits() {
+        let item = quote! {
+            pub struct Person {
+                pub name: String,
+            }
+        };
+        let output = expand_structible(quote! { mock }, item).to_string();
+        assert!(output.contains("`mock` requires `accessor_traits`"));
+    }
+
+    #[test]
+    fn test_labels_rejects_multi_catch_all() {
+        let item = quote! {
+            pub struct Request {
+                pub method: String,
+                #[structible(key = String, multi)]
+                pub extra: Option<Vec<String>>,
+            }
+        };
+        let output = expand_structible(quote! { labels }, item).to_string();
+        assert!(output.contains("`labels` cannot be combined with a `multi` catch-all"));
+    }
+
+    #[test]
+    fn test_struct_attrs_keep_their_order() {
+        let item = quote! {
+            #[derive(Hash)]
+            #[allow(missing_docs)]
+            #[cfg_attr(test, derive(Eq))]
+            pub struct Person {
+                pub name: String,
+            }
+        };
+        let output = expand_structible(quote! {}, item).to_string();
+        let hash = output.find("derive (Hash)").unwrap();
+        let allow = output.find("allow (missing_docs)").unwrap();
+        let eq = output.find("cfg_attr (test , derive (Eq))").unwrap();
+        let definition = output.find("pub struct Person {").unwrap();
+        assert!(hash < allow && allow < eq && eq < definition);
+    }
+
+    #[test]
+    fn test_example_config() {
+        let item = quote! {
+            pub struct Person {
+                #[structible(example = "Alice".into())]
+                pub name: String,
+            }
+        };
+        let output = expand_structible(quote! {}, item).to_string();
+        assert!(output.contains("requires `#[structible(example)]` on the struct"));
+
+        let item = quote! {
+            pub struct Person {
+                #[structible(key = String, example = [])]
+                pub extra: Option<String>,
+            }
+        };
+        let output = expand_structible(quote! { example }, item).to_string();
+        assert!(output.contains("`example` is not supported on the catch-all"));
+
+        let item = quote! {
+            pub struct Person {
+                pub name: String,
+            }
+        };
+        let output = expand_structible(quote! { sample }, item).to_string();
+        assert!(output.contains("`sample` requires `example`"));
+    }
+
+    #[test]
+    fn test_fake_config() {
+        let item = quote! {
+            pub struct Person {
+                #[structible(fake = "Name()")]
+                pub name: String,
+            }
+        };
+        let output = expand_structible(quote! {}, item.clone()).to_string();
+        assert!(output.contains("requires `#[structible(fake)]` on the struct"));
+        let output = expand_structible(quote! { fake }, item).to_string();
+        assert!(output.contains("fake_with_rng :: < String , _ > (& (Name ()) , rng)"));
+    }
+
+    #[test]
+    fn test_struct_docs() {
+        let item = quote! {
+            /// A person in the directory.
+            /// Looked up by name.
+            ///
+            /// ```
+            /// let person = Person::new("Alice".into());
+            /// ```
+            pub struct Person {
+                pub name: String,
+                #[structible(set = rename, set_vis = pub(crate))]
+                pub age: Option<u32>,
+                #[structible(key = String)]
+                pub extra: Option<u32>,
+            }
+        };
+        let output = expand_structible(quote! {}, item).to_string();
+        assert!(output.contains(
+            "# [doc = \"Companion struct of [`Person`] for extracting owned values, returned by [`Person::into_fields`].\"] \
+             # [doc = r\"\"] # [doc = \" A person in the directory.\"] # [doc = \" Looked up by name.\"] # [doc = r\"\"] \
+             # [doc = r\" This struct contains"
+        ));
+        // The example stays on the struct
+        assert_eq!(output.matches("let person = Person").count(), 1);
+        assert!(output.contains("# [doc = \" # Accessors\"]"));
+        assert!(output.contains("# [doc = \" | `name` | `String` | `name` | `set_name` |  |\"]"));
+        // The crate-visible setter isn't listed on a public struct
+        assert!(
+            output.contains("# [doc = \" | `age` | `Option<u32>` | `age` |  | `remove_age` |\"]")
+        );
+        assert!(output.contains(
+            "# [doc = \" | `extra` (by `String`) | `Option<u32>` | `extra`, `extra_iter` | `insert_extra` | `remove_extra` |\"]"
+        ));
+    }
+
+    #[test]
+    fn test_ffi_config() {
+        let generic = quote! {
+            pub struct Wrapper<T> {
+                pub value: T,
+            }
+        };
+        let output = expand_structible(quote! { ffi }, generic).to_string();
+        assert!(output.contains("`ffi` is not supported on generic structs"));
+
+        let catch_all = quote! {
+            pub struct Person {
+                pub name: String,
+                #[structible(key = String)]
+                pub extra: Option<String>,
+            }
+        };
+        let output = expand_structible(quote! { ffi }, catch_all).to_string();
+        assert!(output.contains("`ffi` cannot be combined with a catch-all"));
+    }
+
+    #[test]
+    fn test_no_panic_config() {
+        let item = quote! {
+            pub struct Person {
+                pub name: String,
+                #[structible(matches = "^[a-z]+$")]
+                pub email: Option<String>,
+            }
+        };
+        for attr in [
+            quote! { no_panic },
+            quote! { no_panic, patch },
+            quote! { no_panic, builder },
+            quote! { no_panic, freeze },
+        ] {
+            let output = expand_structible(attr, item.clone()).to_string();
+            assert!(!output.contains("compile_error"));
+            let guards = output.matches("__no_panic !").count();
+            assert_eq!(output.matches("panic !").count(), guards);
+            assert!(!output.contains("unreachable !"));
+            assert!(!output.contains(". expect ("));
+        }
+        let output = expand_structible(quote! { no_panic }, item.clone()).to_string();
+        assert!(output.contains(":: structible :: __no_panic ! (\"Person::name\""));
+        assert!(output.contains("fn name (& self) -> Option < & String >"));
+
+        for (attr, name) in [
+            (quote! { no_panic, ttl }, "ttl"),
+            (quote! { no_panic, builder = panic }, "builder = panic"),
+            (
+                quote! { no_panic, builder = typestate },
+                "builder = typestate",
+            ),
+            (
+                quote! { no_panic, on_missing_required = fail },
+                "on_missing_required",
+            ),
+        ] {
+            let output = expand_structible(attr, item.clone()).to_string();
+            let message = format!("`no_panic` cannot be combined with `{}`", name);
+            assert!(output.contains(&message), "{}", output);
+        }
+
+        let lazy = quote! {
+            pub struct Profile {
+                #[structible(lazy = load_avatar)]
+                pub avatar: String,
+            }
+        };
+        let output = expand_structible(quote! { no_panic, context = Db }, lazy).to_string();
+        assert!(output.contains("`lazy` fields are not supported with `no_panic`"));
+    }
+
+    #[test]
+    fn test_builder_config() {
+        let item = quote! {
+            pub struct Person {
+                pub name: String,
+            }
+        };
+        let error = |attr| expand_structible(attr, item.clone()).to_string();
+        assert!(
+            error(quote! { builder, no_setters })
+                .contains("`builder` cannot be combined with `no_setters`")
+        );
+        assert!(
+            error(quote! { builder = lenient })
+                .contains("expected `builder`, `builder = panic`, or `builder = typestate`")
+        );
+        assert!(
+            error(quote! { builder, async }).contains("`builder` is not supported with `async`")
+        );
+    }
+
+    #[test]
+    fn test_field_assertions() {
+        let item = quote! {
+            pub struct Person<T> {
+                pub name: String,
+                #[structible(skip_clone, skip_eq)]
+                pub cache: Option<Cache>,
+                pub tag: Option<T>,
+                #[structible(key = String)]
+                pub extra: Option<u32>,
+            }
+        };
+        let output = expand_structible(quote! { backing = BTreeMap }, item).to_string();
+        let checks = |ty: &str| {
+            output
+                .matches(&format!("{} : :: structible :: __private :: assert ::", ty))
+                .count()
+        };
+        assert!(output.contains("String : :: structible :: __private :: assert :: FieldClone < __structible_fields :: name >"));
+        assert!(output.contains("Cache : :: structible :: __private :: assert :: FieldDefault < __structible_fields :: cache >"));
+        assert!(output.contains("String : :: structible :: __private :: assert :: KeyOrd < __structible_fields :: extra >"));
+        // `name` and the catch-all key, `Debug` and `Default` for `cache`
+        assert_eq!(checks("String"), 4);
+        assert_eq!(checks("Cache"), 2);
+        assert_eq!(checks("T"), 0);
+    }
+
+    #[test]
+    fn test_struct_attrs_reject_conflicting_derives() {
+        let rejected = [
+            (quote! { #[derive(Debug)] }, "already implements `Debug`"),
+            (
+                quote! { #[derive(Clone, Copy)] },
+                "already implements `Clone`",
+            ),
+            (
+                quote! { #[derive(std::cmp::PartialEq)] },
+                "already implements `PartialEq`",
+            ),
+            (
+                quote! { #[derive(Default)] },
+                "already implements `Default`",
+            ),
+            (
+                quote! { #[cfg_attr(feature = "serde", derive(serde::Serialize))] },
+                "deriving serde",
+            ),
+            (
+                quote! { #[derive(derive_builder::Builder)] },
+                "deriving a builder",
+            ),
+        ];
+        for (attr, message) in rejected {
+            let item = quote! {
+                #attr
+                pub struct Person {
+                    pub name: Option<String>,
+                }
+            };
+            let output = expand_structible(quote! {}, item).to_string();
+            assert!(output.contains(message), "{}", output);
+        }
+
+        // Opting out of the generated impls allows deriving them
+        let item = quote! {
+            #[derive(Clone, PartialEq)]
+            pub struct Person {
+                pub name: String,
+            }
+        };
+        let output = expand_structible(quote! { no_clone, no_partial_eq }, item).to_string();
+        assert!(!output.contains("compile_error"));
+    }
+
+    #[test]
+    fn test_derive_requires_name() {
+        let item = quote! {
+            #[structible(backing = BTreeMap)]
+            pub struct PersonDef {
+                pub name: String,
+            }
+        };
+        let output = expand_derive(item).to_string();
+        assert!(output.contains("requires `#[structible(name = ...)]`"));
+    }
+
+    #[test]
+    fn test_name_rejected_by_attribute() {
+        let item = quote! {
+            pub struct Person {
+                pub name: String,
+            }
+        };
+        let output = expand_structible(quote! { name = Other }, item).to_string();
+        assert!(output.contains("`name` is only supported by `#[derive(Structible)]`"));
+    }
+
+    #[test]
+    fn test_derive_renames_struct() {
+        let item = quote! {
+            #[structible(name = Person)]
+            #[serde(rename_all = "camelCase")]
+            pub struct PersonDef {
+                pub name: String,
+            }
+        };
+        let output = expand_derive(item).to_string();
+        assert!(output.contains("pub struct Person"));
+        assert!(!output.contains("PersonDef"));
+        assert!(!output.contains("serde"));
+    }
+
+    #[test]
+    fn test_envelope_requires_serde_backing() {
+        let item = quote! {
+            pub struct Message {
+                pub id: u64,
+            }
+        };
+        let output = expand_structible(quote! { envelope }, item).to_string();
+        assert!(output.contains("`envelope` requires `serde_backing`"));
+    }
+
+    #[test]
+    fn test_serde_requires_string_catch_all_keys() {
+        let item = quote! {
+            pub struct Counts {
+                pub total: u64,
+                #[structible(key = u32)]
+                pub buckets: Option<u64>,
+            }
+        };
+        let output = expand_structible(quote! { serde }, item).to_string();
+        assert!(output.contains("`serde` requires the catch-all key to be `String`"));
+
+        let item = quote! {
+            pub struct Counts {
+                pub total: u64,
+                #[structible(key = String)]
+                pub buckets: Option<u64>,
+            }
+        };
+        let output = expand_structible(quote! { serde }, item).to_string();
+        assert!(!output.contains("compile_error"));
+    }
 }
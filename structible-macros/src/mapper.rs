@@ -0,0 +1,145 @@
+use proc_macro2::TokenStream;
+use quote::{ToTokens, quote};
+use syn::ext::IdentExt;
+use syn::visit_mut::VisitMut;
+use syn::{Generics, Ident, Lifetime, Type, parse_quote};
+
+use crate::codegen::{field_enum_type, value_enum_name};
+use crate::parse::{FieldInfo, StructibleConfig};
+use crate::util::to_pascal_case;
+
+/// Generate the field enum's `map_value()`, applying a `FieldMapper` to the field's
+/// value in a struct through the struct's `MapField` impl.
+pub fn generate_map_value_method(config: &StructibleConfig) -> TokenStream {
+    if config.is_async {
+        return quote! {};
+    }
+    quote! {
+        /// Applies `mapper` to the value of this field in `value`, typed as the
+        /// field's type, or to `None` if the field is absent.
+        ///
+        /// `mapper` must implement [`FieldMapper`](::structible::FieldMapper) for the
+        /// types of all fields, so that generic per-field code doesn't have to match
+        /// on the field itself.
+        pub fn map_value<__S, __M, __R>(&self, value: &__S, mapper: __M) -> __R
+        where
+            __S: ::structible::__private::MapField<Self, __M, __R>,
+        {
+            ::structible::__private::MapField::map_field(value, self, mapper)
+        }
+    }
+}
+
+/// Generate the `MapField` impl dispatching a field to the mapper with its typed value.
+///
+/// The mapper is bounded by `FieldMapper` for every field type; expired values of
+/// `ttl` structs are passed as absent.
+pub fn generate_map_field_impl(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+    let (_, ty_generics, where_clause) = generics.split_for_impl();
+    let mapper = quote! { ::structible::FieldMapper };
+
+    let arms = fields.iter().map(|f| {
+        let inner_ty = &f.inner_ty;
+        let name_str = f.name.unraw().to_string();
+        let (pattern, variant) = if f.is_unknown_field() {
+            (quote! { #field_enum::Unknown(_) }, quote! { Unknown })
+        } else {
+            let variant = to_pascal_case(&f.name);
+            (quote! { #field_enum::#variant }, quote! { #variant })
+        };
+        let expired = if config.ttl && f.is_optional && !f.is_unknown_field() {
+            quote! { _ if self.__structible_expired(field) => None, }
+        } else {
+            quote! {}
+        };
+        quote! {
+            #pattern => {
+                let value = match ::structible::BackingMap::get(&self.inner, field) {
+                    #expired
+                    Some(#value_enum::#variant(v)) => Some(v),
+                    _ => None,
+                };
+                <__M as #mapper<#inner_ty, __R>>::map(mapper, #name_str, value)
+            }
+        }
+    });
+    let bounds = mapper_bounds(fields);
+    let existing = where_clause.map(|wc| &wc.predicates);
+
+    let mut map_generics = generics.clone();
+    map_generics.params.push(syn::parse_quote!(__M));
+    map_generics.params.push(syn::parse_quote!(__R));
+    let (map_impl_generics, _, _) = map_generics.split_for_impl();
+
+    quote! {
+        impl #map_impl_generics ::structible::__private::MapField<#field_enum, __M, __R> for #struct_name #ty_generics
+        where
+            #(#bounds,)*
+            #existing
+        {
+            fn map_field(&self, field: &#field_enum, mapper: __M) -> __R {
+                match *field {
+                    #(#arms)*
+                }
+            }
+        }
+    }
+}
+
+/// Returns the `FieldMapper` bounds of the mapper, one per distinct field type.
+///
+/// Bounds on types differing only in lifetimes, e.g. `&'a str` and `&'b str`, would
+/// be ambiguous, so such types get a single bound over all lifetimes instead.
+fn mapper_bounds(fields: &[FieldInfo]) -> Vec<TokenStream> {
+    let mut groups: Vec<(String, Vec<&Type>)> = Vec::new();
+    for f in fields {
+        let erased = with_lifetimes(&f.inner_ty, &parse_quote!('__m));
+        let key = erased.to_token_stream().to_string();
+        let text = f.inner_ty.to_token_stream().to_string();
+        match groups.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, types)) => {
+                if !types
+                    .iter()
+                    .any(|ty| ty.to_token_stream().to_string() == text)
+                {
+                    types.push(&f.inner_ty);
+                }
+            }
+            None => groups.push((key, vec![&f.inner_ty])),
+        }
+    }
+    groups
+        .into_iter()
+        .map(|(_, types)| match types.as_slice() {
+            [ty] => quote! { __M: ::structible::FieldMapper<#ty, __R> },
+            _ => {
+                let erased = with_lifetimes(types[0], &parse_quote!('__m));
+                quote! { __M: for<'__m> ::structible::FieldMapper<#erased, __R> }
+            }
+        })
+        .collect()
+}
+
+/// Returns `ty` with every lifetime other than `'static` replaced by `lifetime`.
+fn with_lifetimes(ty: &Type, lifetime: &Lifetime) -> Type {
+    struct Replace<'a>(&'a Lifetime);
+
+    impl VisitMut for Replace<'_> {
+        fn visit_lifetime_mut(&mut self, lt: &mut Lifetime) {
+            if lt.ident != "static" {
+                *lt = self.0.clone();
+            }
+        }
+    }
+
+    let mut ty = ty.clone();
+    Replace(lifetime).visit_type_mut(&mut ty);
+    ty
+}
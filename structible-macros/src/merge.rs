@@ -0,0 +1,254 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Ident;
+
+use crate::codegen::{field_enum_type, value_enum_name};
+use crate::parse::{FieldInfo, StructibleConfig};
+use crate::provenance::{clear_source_stmt, set_source_stmt};
+use crate::timestamps::touch_stmt;
+use crate::tombstone::clear_tombstone_stmt;
+use crate::util::to_pascal_case;
+
+/// Checks that the merge-related configuration is coherent.
+pub fn check_merge_config(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+) -> syn::Result<()> {
+    if config.lww && config.no_clone {
+        return Err(syn::Error::new_spanned(
+            struct_name,
+            "`lww` merges cloned values and cannot be combined with `no_clone`",
+        ));
+    }
+    for f in fields {
+        let Some(resolver) = &f.config.merge_with else {
+            continue;
+        };
+        if !config.lww {
+            return Err(syn::Error::new_spanned(
+                resolver,
+                "`merge_with` requires the struct to be declared with `lww`",
+            ));
+        }
+        if f.is_unknown_field() {
+            return Err(syn::Error::new_spanned(
+                resolver,
+                "`merge_with` is not supported on unknown fields catch-all",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Returns the type of the stamp map of a struct with `lww`.
+fn stamp_map_type(struct_name: &Ident, config: &StructibleConfig) -> TokenStream {
+    let field_enum = field_enum_type(struct_name, config);
+    let map_type = config.backing.to_tokens();
+    quote! { #map_type<#field_enum, ::structible::Stamp> }
+}
+
+/// Returns the stamp map, clock and actor field declarations, if `lww` is set.
+///
+/// The stamp map keeps the stamp of the last write to each field, including
+/// removals, so that a removal can win over an older write on another replica.
+pub fn stamp_fields(struct_name: &Ident, config: &StructibleConfig) -> TokenStream {
+    if !config.lww {
+        return quote! {};
+    }
+    let stamp_map = stamp_map_type(struct_name, config);
+    quote! {
+        stamps: #stamp_map,
+        clock: u64,
+        actor: u64,
+    }
+}
+
+/// Returns the initializer of an empty stamp map, if `lww` is set.
+pub fn stamp_init(struct_name: &Ident, config: &StructibleConfig) -> TokenStream {
+    if !config.lww {
+        return quote! {};
+    }
+    let field_enum = field_enum_type(struct_name, config);
+    let stamp_map = stamp_map_type(struct_name, config);
+    quote! {
+        stamps: <#stamp_map as ::structible::BackingMap<#field_enum, ::structible::Stamp>>::new(),
+        clock: 0,
+        actor: 0,
+    }
+}
+
+/// Returns the initializers cloning the stamp map, clock and actor, if `lww` is set.
+pub fn stamp_clone(config: &StructibleConfig) -> TokenStream {
+    if !config.lww {
+        return quote! {};
+    }
+    quote! {
+        stamps: ::std::clone::Clone::clone(&self.stamps),
+        clock: self.clock,
+        actor: self.actor,
+    }
+}
+
+/// Returns a statement stamping a write to the owned `key` of `recv`, if `lww` is set.
+pub fn stamp_stmt(config: &StructibleConfig, recv: &TokenStream, key: TokenStream) -> TokenStream {
+    if !config.lww {
+        return quote! {};
+    }
+    quote! { #recv.__structible_stamp(#key); }
+}
+
+/// Generate the stamp accessors and `merge()`, if `lww` is set.
+pub fn generate_merge_methods(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+) -> TokenStream {
+    if !config.lww {
+        return quote! {};
+    }
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+
+    let resolver_arms: Vec<_> = fields
+        .iter()
+        .filter_map(|f| {
+            let resolver = f.config.merge_with.as_ref()?;
+            let variant = to_pascal_case(&f.name);
+            Some(quote! {
+                (
+                    ::std::option::Option::Some(#value_enum::#variant(ours)),
+                    ::std::option::Option::Some(#value_enum::#variant(theirs)),
+                ) => ::std::option::Option::Some(#value_enum::#variant(#resolver(ours, theirs)))
+            })
+        })
+        .collect();
+    let resolved = if resolver_arms.is_empty() {
+        quote! { ::std::option::Option::None }
+    } else {
+        quote! {
+            match (
+                ::structible::BackingMap::get(&self.inner, key),
+                ::structible::BackingMap::get(&other.inner, key),
+            ) {
+                #(#resolver_arms,)*
+                _ => ::std::option::Option::None,
+            }
+        }
+    };
+
+    // A merged value replaces a local deletion, and comes from another replica
+    let clear_tombstone = clear_tombstone_stmt(config, &quote! { self });
+    let tag_sync = set_source_stmt(config, quote! { ::structible::Source::Sync });
+    let clear_source = clear_source_stmt(config, &quote! { self });
+    let touch = touch_stmt(config, &quote! { self });
+    let (record_set, record_remove) = if config.track_changes {
+        (
+            quote! {
+                self.changes.push(::structible::FieldChange::Set(
+                    ::std::clone::Clone::clone(&key),
+                    ::std::clone::Clone::clone(&value),
+                ));
+            },
+            quote! {
+                self.changes.push(::structible::FieldChange::Remove(::std::clone::Clone::clone(&key)));
+            },
+        )
+    } else {
+        (quote! {}, quote! {})
+    };
+
+    quote! {
+        #[doc(hidden)]
+        fn __structible_stamp(&mut self, key: #field_enum) {
+            self.clock += 1;
+            ::structible::BackingMap::insert(
+                &mut self.stamps,
+                key,
+                ::structible::Stamp::new(self.clock, self.actor),
+            );
+        }
+
+        /// Returns the id of the replica making this struct's writes.
+        pub fn actor_id(&self) -> u64 {
+            self.actor
+        }
+
+        /// Sets the id of the replica making this struct's future writes.
+        ///
+        /// Each replica needs its own id, e.g. set right after cloning a replica.
+        pub fn set_actor_id(&mut self, actor: u64) {
+            self.actor = actor;
+        }
+
+        /// Sets the id of the replica making this struct's writes, including those
+        /// already made, such as by the constructor.
+        ///
+        /// This is meant for replicas created independently rather than cloned from
+        /// one another, which would otherwise stamp their constructors' writes alike.
+        pub fn with_actor_id(mut self, actor: u64) -> Self {
+            for (_, stamp) in ::structible::IterableMap::iter_mut(&mut self.stamps) {
+                stamp.actor = actor;
+            }
+            self.actor = actor;
+            self
+        }
+
+        /// Returns the stamp of the last write to the given field, if it was ever written.
+        ///
+        /// Removed fields keep the stamp of their removal.
+        pub fn field_stamp(&self, key: &#field_enum) -> ::std::option::Option<::structible::Stamp> {
+            ::structible::BackingMap::get(&self.stamps, key).copied()
+        }
+
+        /// Merges the writes of another replica of this struct into this one.
+        ///
+        /// For each field, the write with the latest stamp wins, whether it set or
+        /// removed the field. Fields declared with `merge_with` that are present on
+        /// both replicas are instead set to the result of their resolver, which is
+        /// called even if both values are already equal. Replicas converge as long
+        /// as the resolvers are commutative, associative and idempotent, e.g. a set
+        /// union or a maximum.
+        pub fn merge(&mut self, other: &Self) {
+            for (key, theirs) in ::structible::IterableMap::iter(&other.stamps) {
+                let theirs = *theirs;
+                let ours = ::structible::BackingMap::get(&self.stamps, key).copied();
+                let resolved = #resolved;
+                let (value, stamp) = match resolved {
+                    ::std::option::Option::Some(value) => (
+                        ::std::option::Option::Some(value),
+                        ours.map_or(theirs, |ours| ::std::cmp::max(ours, theirs)),
+                    ),
+                    ::std::option::Option::None if ours.is_some_and(|ours| ours >= theirs) => continue,
+                    ::std::option::Option::None => (
+                        ::structible::BackingMap::get(&other.inner, key).cloned(),
+                        theirs,
+                    ),
+                };
+                let key = ::std::clone::Clone::clone(key);
+                match value {
+                    ::std::option::Option::Some(value) => {
+                        #record_set
+                        #clear_tombstone
+                        ::structible::BackingMap::insert(
+                            &mut self.inner,
+                            ::std::clone::Clone::clone(&key),
+                            value,
+                        );
+                        #tag_sync
+                        #touch
+                    }
+                    ::std::option::Option::None => {
+                        #clear_source
+                        if ::structible::BackingMap::remove(&mut self.inner, &key).is_some() {
+                            #record_remove
+                            #touch
+                        }
+                    }
+                }
+                ::structible::BackingMap::insert(&mut self.stamps, key, stamp);
+                self.clock = ::std::cmp::max(self.clock, stamp.timestamp);
+            }
+        }
+    }
+}
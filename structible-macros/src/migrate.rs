@@ -0,0 +1,118 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::{Generics, Ident};
+
+use crate::codegen::value_enum_name;
+use crate::parse::{FieldInfo, StructibleConfig};
+
+/// Checks that fields declared with `upgrade_from` can be decoded.
+pub fn check_upgrade_from_config(
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+) -> syn::Result<()> {
+    for f in fields {
+        let (old, with) = match (&f.config.upgrade_from, &f.config.upgrade_with) {
+            (Some(old), Some(with)) => (old, with),
+            (Some(old), None) => {
+                return Err(syn::Error::new_spanned(
+                    old,
+                    "`upgrade_from` requires a `with = path` conversion from the legacy type",
+                ));
+            }
+            (None, Some(with)) => {
+                return Err(syn::Error::new_spanned(
+                    with,
+                    "`with` requires an `upgrade_from = Type` legacy type",
+                ));
+            }
+            (None, None) => continue,
+        };
+        if f.is_unknown_field() {
+            return Err(syn::Error::new_spanned(
+                old,
+                "`upgrade_from` is not supported on unknown fields catch-all",
+            ));
+        }
+        if !config.serde_backing && !config.value_codec {
+            return Err(syn::Error::new_spanned(
+                with,
+                "`upgrade_from` only applies when decoding, and requires `serde_backing` or `value_codec`",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Returns the name of the hidden function deserializing an upgradable field.
+fn upgrade_fn_name(field: &FieldInfo) -> Ident {
+    format_ident!("__structible_upgrade_{}", field.name.unraw())
+}
+
+/// Returns the serde attribute deserializing a field's value enum variant through
+/// its upgrade function, if the field is declared with `upgrade_from`.
+pub fn upgrade_from_serde_attr(
+    struct_name: &Ident,
+    field: &FieldInfo,
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    if !config.serde_backing || field.config.upgrade_from.is_none() {
+        return quote! {};
+    }
+    let enum_name = value_enum_name(struct_name);
+    let (_, ty_generics, _) = generics.split_for_impl();
+    let turbofish = ty_generics.as_turbofish();
+    let fn_name = upgrade_fn_name(field);
+    let path = quote! { #enum_name #turbofish :: #fn_name }.to_string();
+    quote! { #[serde(deserialize_with = #path)] }
+}
+
+/// Generate the functions deserializing fields declared with `upgrade_from`, if
+/// `serde_backing` is set.
+///
+/// A value is deserialized as the field's type if possible, and otherwise as the
+/// legacy type, which is then converted with the field's `with` function.
+pub fn generate_upgrade_fns(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    if !config.serde_backing {
+        return quote! {};
+    }
+    let enum_name = value_enum_name(struct_name);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let serde = quote! { ::structible::__private::serde };
+
+    let upgrade_fns: Vec<_> = fields
+        .iter()
+        .filter_map(|f| {
+            let old = f.config.upgrade_from.as_ref()?;
+            let with = f.config.upgrade_with.as_ref()?;
+            let fn_name = upgrade_fn_name(f);
+            let ty = &f.inner_ty;
+            Some(quote! {
+                #[doc(hidden)]
+                fn #fn_name<'__de, __D>(deserializer: __D) -> ::std::result::Result<#ty, __D::Error>
+                where
+                    __D: #serde::Deserializer<'__de>,
+                    #ty: #serde::Deserialize<'__de>,
+                    #old: #serde::Deserialize<'__de>,
+                {
+                    ::structible::serde_adapter::__upgrade_from(deserializer, #with)
+                }
+            })
+        })
+        .collect();
+    if upgrade_fns.is_empty() {
+        return quote! {};
+    }
+
+    quote! {
+        impl #impl_generics #enum_name #ty_generics #where_clause {
+            #(#upgrade_fns)*
+        }
+    }
+}
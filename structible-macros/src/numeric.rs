@@ -0,0 +1,176 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::{Ident, PathArguments, Type};
+
+use crate::codegen::value_or_default_mut;
+use crate::instrument::read_counter;
+use crate::parse::{FieldInfo, StructibleConfig};
+
+/// The kind of primitive number a `numeric` field stores.
+#[derive(Clone, Copy, PartialEq)]
+enum Number {
+    Integer,
+    Float,
+}
+
+impl Number {
+    /// Returns the kind of number `ty` is, detected from the last segment of its path.
+    fn of(ty: &Type) -> Option<Self> {
+        let Type::Path(type_path) = ty else {
+            return None;
+        };
+        if type_path.qself.is_some() {
+            return None;
+        }
+        let segment = type_path.path.segments.last()?;
+        if !matches!(segment.arguments, PathArguments::None) {
+            return None;
+        }
+        match segment.ident.to_string().as_str() {
+            "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32" | "u64"
+            | "u128" | "usize" => Some(Number::Integer),
+            "f32" | "f64" => Some(Number::Float),
+            _ => None,
+        }
+    }
+}
+
+/// Checks that `numeric` fields store a primitive number that can be modified in
+/// place.
+pub fn check_numeric_config(fields: &[FieldInfo], config: &StructibleConfig) -> syn::Result<()> {
+    for f in fields.iter().filter(|f| f.config.numeric) {
+        if f.is_unknown_field() || f.is_lazy() || f.is_weak() || f.is_transparent() {
+            return Err(syn::Error::new_spanned(
+                &f.name,
+                "`numeric` is not supported on the unknown fields catch-all, lazy, weak, or transparent fields",
+            ));
+        }
+        if Number::of(&f.inner_ty).is_none() {
+            return Err(syn::Error::new_spanned(
+                &f.inner_ty,
+                "`numeric` requires a primitive integer or float field",
+            ));
+        }
+        if config.no_setters {
+            return Err(syn::Error::new_spanned(
+                &f.name,
+                "`numeric` has no effect with `no_setters`",
+            ));
+        }
+        if config.is_async {
+            return Err(syn::Error::new_spanned(
+                &f.name,
+                "`numeric` is not supported with `async`",
+            ));
+        }
+        // Like mutable getters, the helpers modify the stored number in place
+        if config.records_writes() {
+            return Err(syn::Error::new_spanned(
+                &f.name,
+                "`numeric` is not supported with `track_changes`, `lww`, or `transactional`, which can't record writes in place",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Generate `incr_<field>()` and `decr_<field>()` for fields declaring `numeric`,
+/// with `saturating_` and `checked_` variants for integers.
+///
+/// An absent optional field counts as zero. The plain and saturating helpers
+/// modify the stored number in place, while the checked ones leave the field
+/// untouched on overflow.
+pub fn generate_numeric_methods(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+) -> Vec<TokenStream> {
+    fields
+        .iter()
+        .filter(|f| f.config.numeric)
+        .flat_map(|f| {
+            let name = &f.name;
+            let getter = f.getter_name();
+            let vis = f.setter_vis();
+            let method_attrs = f.method_attrs();
+            let count = read_counter(config, name);
+            let value_mut = value_or_default_mut(struct_name, f, config);
+            let ty = &f.inner_ty;
+            let name_str = name.unraw().to_string();
+            let number = Number::of(ty).unwrap();
+            let current = if f.is_optional {
+                quote! { self.#getter().copied().unwrap_or_default() }
+            } else {
+                quote! { (*self.#getter()) }
+            };
+            let absent_doc = if f.is_optional {
+                " An absent value counts as zero."
+            } else {
+                ""
+            };
+
+            [("incr", "add", "Adds"), ("decr", "sub", "Subtracts")]
+                .into_iter()
+                .map(move |(op, method, verb)| {
+                    let preposition = if op == "incr" { "to" } else { "from" };
+                    let op_name = format_ident!("{}_{}", op, name);
+                    let op_doc = format!(
+                        "{} `delta` {} the `{}` value.{}",
+                        verb, preposition, name_str, absent_doc
+                    );
+                    let op_assign = if op == "incr" {
+                        quote! { += }
+                    } else {
+                        quote! { -= }
+                    };
+                    let plain = quote! {
+                        #[doc = #op_doc]
+                        #(#method_attrs)*
+                        #vis fn #op_name(&mut self, delta: #ty) {
+                            #count
+                            *#value_mut #op_assign delta;
+                        }
+                    };
+                    if number == Number::Float {
+                        return plain;
+                    }
+
+                    let saturating_name = format_ident!("saturating_{}_{}", op, name);
+                    let saturating_method = format_ident!("saturating_{}", method);
+                    let saturating_doc = format!(
+                        "{} `delta` {} the `{}` value, saturating at the numeric bound \
+                         instead of overflowing.{}",
+                        verb, preposition, name_str, absent_doc
+                    );
+                    let checked_name = format_ident!("checked_{}_{}", op, name);
+                    let checked_method = format_ident!("checked_{}", method);
+                    let checked_doc = format!(
+                        "{} `delta` {} the `{}` value and returns the result, or returns \
+                         `None` and leaves the field untouched if it would overflow.{}",
+                        verb, preposition, name_str, absent_doc
+                    );
+                    quote! {
+                        #plain
+
+                        #[doc = #saturating_doc]
+                        #(#method_attrs)*
+                        #vis fn #saturating_name(&mut self, delta: #ty) {
+                            #count
+                            let value = #value_mut;
+                            *value = value.#saturating_method(delta);
+                        }
+
+                        #[doc = #checked_doc]
+                        #(#method_attrs)*
+                        #vis fn #checked_name(&mut self, delta: #ty) -> ::std::option::Option<#ty> {
+                            let result = #current.#checked_method(delta)?;
+                            *#value_mut = result;
+                            ::std::option::Option::Some(result)
+                        }
+                    }
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
@@ -0,0 +1,165 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::{Expr, Generics, Ident, parse_quote};
+
+use crate::parse::{FieldInfo, StructibleConfig};
+use crate::util::type_mentions_type_param;
+
+/// Generate the `utoipa::ToSchema` impl, if `openapi` is set.
+///
+/// The struct is described as a JSON object with a property per known field, of
+/// which the required fields are required. Field bounds are described by their
+/// property (see `bounds_stmts`). Catch-all entries are described as
+/// `additionalProperties`; structs without a catch-all don't allow any.
+pub fn generate_to_schema_impl(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    if !config.openapi {
+        return quote! {};
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let utoipa = quote! { ::structible::__private::utoipa };
+
+    let properties = fields.iter().filter(|f| !f.is_unknown_field()).map(|f| {
+        let name_str = f.name.unraw().to_string();
+        let inner_ty = &f.inner_ty;
+        let required = (!f.is_optional).then(|| quote! { .required(#name_str) });
+        let schema = quote! { <#inner_ty as #utoipa::PartialSchema>::schema() };
+        let schema = match bounds_stmts(f) {
+            Some(stmts) => quote! {
+                {
+                    let mut schema = #schema;
+                    #stmts
+                    schema
+                }
+            },
+            None => schema,
+        };
+        quote! {
+            .property(#name_str, #schema)
+            #required
+        }
+    });
+    let additional_properties = match fields.iter().find(|f| f.is_unknown_field()) {
+        Some(f) => {
+            let inner_ty = &f.inner_ty;
+            quote! { <#inner_ty as #utoipa::PartialSchema>::schema() }
+        }
+        None => quote! { #utoipa::openapi::schema::AdditionalProperties::FreeForm(false) },
+    };
+
+    // Schemas referenced by the field types are collected from them
+    let field_types: Vec<_> = fields.iter().map(|f| &f.inner_ty).collect();
+
+    let type_param_idents: Vec<_> = generics.type_params().map(|tp| &tp.ident).collect();
+    let bounded_types: Vec<_> = field_types
+        .iter()
+        .filter(|ty| type_mentions_type_param(ty, &type_param_idents))
+        .collect();
+    let bounds = quote! { #(#bounded_types: #utoipa::ToSchema,)* };
+    let where_clause = if let Some(wc) = where_clause {
+        let existing = &wc.predicates;
+        quote! { where #bounds #existing }
+    } else if !bounded_types.is_empty() {
+        quote! { where #bounds }
+    } else {
+        quote! {}
+    };
+
+    quote! {
+        impl #impl_generics #utoipa::PartialSchema for #struct_name #ty_generics #where_clause {
+            fn schema() -> #utoipa::openapi::RefOr<#utoipa::openapi::schema::Schema> {
+                let object = #utoipa::openapi::schema::ObjectBuilder::new()
+                    .schema_type(#utoipa::openapi::schema::Type::Object)
+                    #(#properties)*
+                    .additional_properties(::std::option::Option::Some(#additional_properties));
+                ::std::convert::From::from(object)
+            }
+        }
+
+        impl #impl_generics #utoipa::ToSchema for #struct_name #ty_generics #where_clause {
+            fn schemas(
+                schemas: &mut ::std::vec::Vec<(
+                    ::std::string::String,
+                    #utoipa::openapi::RefOr<#utoipa::openapi::schema::Schema>,
+                )>,
+            ) {
+                #(<#field_types as #utoipa::ToSchema>::schemas(schemas);)*
+            }
+        }
+    }
+}
+
+/// Returns statements adding the bounds of a field's `range`, `len`, `non_empty`,
+/// or `matches` to its property's `schema`, if it declares any.
+///
+/// A `range` sets `minimum` and `maximum`. A `len` or `non_empty` sets
+/// `minLength` and `maxLength` if the field's schema is a string, and `minItems`
+/// and `maxItems` if it is an array. A `matches` pattern sets `pattern`.
+fn bounds_stmts(f: &FieldInfo) -> Option<TokenStream> {
+    let utoipa = quote! { ::structible::__private::utoipa };
+    let inner_ty = &f.inner_ty;
+    let range = f.config.range.as_ref().map(|range| {
+        let minimum = range.start.as_ref().map(|start| {
+            quote! {
+                object.minimum = ::std::option::Option::Some(
+                    ::std::convert::From::<#inner_ty>::from(#start),
+                );
+            }
+        });
+        let maximum = range.end.as_ref().map(|end| {
+            quote! {
+                object.maximum = ::std::option::Option::Some(
+                    ::std::convert::From::<#inner_ty>::from(#end),
+                );
+            }
+        });
+        quote! {
+            if let #utoipa::openapi::RefOr::T(#utoipa::openapi::schema::Schema::Object(object)) = &mut schema {
+                #minimum
+                #maximum
+            }
+        }
+    });
+    let one: Expr = parse_quote! { 1 };
+    let (min_len, max_len) = match &f.config.len {
+        Some(len) => (len.start.as_deref(), len.end.as_deref()),
+        None if f.config.non_empty => (Some(&one), None),
+        None => (None, None),
+    };
+    let len = (min_len.is_some() || max_len.is_some()).then(|| {
+        let set = |field: &str, bound: Option<&Expr>| {
+            bound.map(|bound| {
+                let field = format_ident!("{}", field);
+                quote! { object.#field = ::std::option::Option::Some(#bound); }
+            })
+        };
+        let (min_length, max_length) = (set("min_length", min_len), set("max_length", max_len));
+        let (min_items, max_items) = (set("min_items", min_len), set("max_items", max_len));
+        quote! {
+            match &mut schema {
+                #utoipa::openapi::RefOr::T(#utoipa::openapi::schema::Schema::Object(object)) => {
+                    #min_length
+                    #max_length
+                }
+                #utoipa::openapi::RefOr::T(#utoipa::openapi::schema::Schema::Array(object)) => {
+                    #min_items
+                    #max_items
+                }
+                _ => {}
+            }
+        }
+    });
+    let pattern = f.config.matches.as_ref().map(|pattern| {
+        quote! {
+            if let #utoipa::openapi::RefOr::T(#utoipa::openapi::schema::Schema::Object(object)) = &mut schema {
+                object.pattern = ::std::option::Option::Some(::std::string::String::from(#pattern));
+            }
+        }
+    });
+    (range.is_some() || len.is_some() || pattern.is_some()).then(|| quote! { #range #len #pattern })
+}
@@ -0,0 +1,92 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Generics, Ident};
+
+use crate::codegen::{field_enum_type, value_enum_name};
+use crate::parse::{FieldInfo, StructibleConfig};
+use crate::util::{to_pascal_case, type_mentions_type_param};
+
+/// Generate `par_visit_fields()`, if `par_visit` is set.
+///
+/// Present entries are collected through `BackingMap::get()` for known fields (so the
+/// backing doesn't have to be iterable) and `IterableMap::iter()` for catch-all
+/// entries, then handed to rayon.
+pub fn generate_par_visit_method(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    if !config.par_visit {
+        return quote! {};
+    }
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+    let (_, ty_generics, _) = generics.split_for_impl();
+
+    let known: Vec<_> = fields
+        .iter()
+        .filter(|f| !f.is_unknown_field())
+        .map(|f| {
+            let variant = to_pascal_case(&f.name);
+            quote! { #field_enum::#variant }
+        })
+        .collect();
+    let count = known.len();
+    // Expired values read as absent, so they aren't visited either
+    let expired_check = if config.ttl {
+        quote! {
+            if self.__structible_expired(key) {
+                continue;
+            }
+        }
+    } else {
+        quote! {}
+    };
+    let unknown_entries = fields.iter().find(|f| f.is_unknown_field()).map(|_| {
+        quote! {
+            for (key, value) in ::structible::IterableMap::iter(&self.inner) {
+                if let #field_enum::Unknown(_) = key {
+                    entries.push((key, value));
+                }
+            }
+        }
+    });
+
+    // Values are shared across threads, so generic field and key types must be `Sync`
+    let type_param_idents: Vec<_> = generics.type_params().map(|tp| &tp.ident).collect();
+    let sync_types: Vec<_> = fields
+        .iter()
+        .map(|f| &f.inner_ty)
+        .chain(fields.iter().filter_map(|f| f.unknown_key_type()))
+        .filter(|ty| type_mentions_type_param(ty, &type_param_idents))
+        .collect();
+
+    quote! {
+        /// Calls `f` on every present field in parallel, using rayon's global thread pool.
+        ///
+        /// Fields are visited in no particular order. This is meant for CPU-heavy work on
+        /// each field, such as compressing or validating large values.
+        ///
+        /// Values expired under `ttl` are skipped, and lazy fields are only visited once
+        /// loaded.
+        pub fn par_visit_fields<__F>(&self, f: __F)
+        where
+            __F: ::std::ops::Fn(&#field_enum, &#value_enum #ty_generics) + ::std::marker::Sync + ::std::marker::Send,
+            #(#sync_types: ::std::marker::Sync,)*
+        {
+            use ::structible::__private::rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+            let known: [#field_enum; #count] = [#(#known),*];
+            let mut entries = ::std::vec::Vec::with_capacity(::structible::BackingMap::len(&self.inner));
+            for key in &known {
+                #expired_check
+                if let Some(value) = ::structible::BackingMap::get(&self.inner, key) {
+                    entries.push((key, value));
+                }
+            }
+            #unknown_entries
+            entries.into_par_iter().for_each(|(key, value)| f(key, value));
+        }
+    }
+}
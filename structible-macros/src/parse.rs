@@ -1,8 +1,16 @@
 use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
 use syn::parse::{Parse, ParseStream};
-use syn::{Attribute, Field, Ident, ItemStruct, Token, Type, Visibility};
+use syn::{
+    Attribute, Expr, ExprRange, Field, Ident, ItemStruct, LitStr, Path, RangeLimits, Token, Type,
+    TypeTraitObject, Visibility,
+};
 
-use crate::util::extract_option_inner;
+use crate::util::{
+    boxed_trait_object, enum_path, extract_option_inner, extract_vec_inner, is_method_attr,
+    is_weak_type, type_text,
+};
 
 /// The backing map type specified in the attribute.
 ///
@@ -24,6 +32,14 @@ impl BackingType {
     pub fn from_type(ty: Type) -> Self {
         Self { ty }
     }
+
+    /// Returns the last segment of the backing's path, e.g. `HashMap`.
+    pub fn last_ident(&self) -> Option<&Ident> {
+        match &self.ty {
+            Type::Path(path) => path.path.segments.last().map(|s| &s.ident),
+            _ => None,
+        }
+    }
 }
 
 impl Default for BackingType {
@@ -35,16 +51,286 @@ impl Default for BackingType {
     }
 }
 
+/// How the builder generated by `builder` reports missing required fields.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum BuilderMode {
+    /// `build()` returns a `ValidationError`.
+    Fallible,
+    /// `build()` panics.
+    Panicking,
+    /// `build()` is only implemented once all required fields are set.
+    Typestate,
+}
+
 /// Configuration parsed from `#[structible(...)]` attribute on the struct.
+#[derive(Default)]
 pub struct StructibleConfig {
     pub backing: BackingType,
     pub constructor: Option<Ident>,
+    /// The name of the struct generated by `#[derive(Structible)]`.
+    pub name: Option<Ident>,
     /// If true, generate `len()` and `is_empty()` methods.
     pub with_len: bool,
     /// If true, do not derive `Clone` on generated types.
     pub no_clone: bool,
     /// If true, do not derive `PartialEq` on generated types.
     pub no_partial_eq: bool,
+    /// If true, do not generate setters (including `try_set_*` and catch-all inserts).
+    pub no_setters: bool,
+    /// If true, do not generate removers (including catch-all removes).
+    pub no_removers: bool,
+    /// If true, do not generate mutable getters (including catch-all `_mut` and `_iter_mut`).
+    pub no_mut_getters: bool,
+    /// If true, do not implement the `Structible` trait.
+    pub no_structible: bool,
+    /// If true, implement `From<(T1, T2, ...)>` over the required fields.
+    pub from_tuple: bool,
+    /// If true, generate `async fn` accessors over an `AsyncBackingMap`.
+    pub is_async: bool,
+    /// If true, derive serde traits on the hidden field and value enums.
+    pub serde_backing: bool,
+    /// If true, record set and remove operations in a change log.
+    pub track_changes: bool,
+    /// If true, generate a copy-on-write `<Struct>Cow` handle type.
+    pub cow: bool,
+    /// If true, generate a `#[repr(C)]` `<Struct>Ffi` facade, `to_ffi()`, and `from_ffi()`.
+    pub ffi: bool,
+    /// If true, generate an immutable `Frozen<Struct>` snapshot type and `freeze()`.
+    pub freeze: bool,
+    /// If true, generate `par_visit_fields()` visiting present fields with rayon.
+    pub par_visit: bool,
+    /// If true, implement `DebugJson` and generate `to_debug_json()`.
+    pub debug_json: bool,
+    /// If true, implement `Serialize` in test builds, e.g. for snapshot tests.
+    pub test_serialize: bool,
+    /// If true, implement `Serialize` and `Deserialize` as a map of present fields.
+    pub serde: bool,
+    /// If true, implement `Arbitrary` and generate `fuzz_roundtrip()`.
+    pub fuzz: bool,
+    /// If true, generate `bench_accessors()` measuring the accessors with Criterion.
+    pub bench: bool,
+    /// If true, count field reads and writes with the `metrics` crate.
+    pub instrument: bool,
+    /// If true, emit `tracing` events for every write.
+    pub trace: bool,
+    /// If true, generate `record_fields()` recording present fields on a `tracing` visitor.
+    pub record_fields: bool,
+    /// If true, generate `label_values()` formatting selected fields as metric labels.
+    pub labels: bool,
+    /// If true, keep an undo log during `transaction()` to roll writes back.
+    pub transactional: bool,
+    /// If true, generate a `<Struct>PatchBuilder` collecting changes to check and apply.
+    pub patch: bool,
+    /// If set, generate a `<Struct>Builder` with chainable setters and `build()`.
+    pub builder: Option<BuilderMode>,
+    /// If true, implement `utoipa::ToSchema` describing the struct as a JSON object.
+    pub openapi: bool,
+    /// If true, implement axum's `FromRequest` decoding a JSON object body.
+    pub axum: bool,
+    /// If true, generate async-graphql `Object` resolvers for the fields.
+    pub graphql: bool,
+    /// If true, print the formatted expansion to stderr and write it to `OUT_DIR`.
+    pub debug_expand: bool,
+    /// If true, generate only the accessors, without the fields struct, `Debug`,
+    /// `Default`, or the `Structible` impl.
+    pub minimal: bool,
+    /// If true, mark the field and value enums `#[non_exhaustive]`.
+    pub non_exhaustive: bool,
+    /// If true, generate `<Struct>Reader` and `<Struct>Writer` traits over the
+    /// getters and the setters and removers.
+    pub accessor_traits: bool,
+    /// If true, put `#[cfg_attr(test, mockall::automock)]` on the accessor traits.
+    pub mock: bool,
+    /// If true, generate `example()`, filled with the fields' `example` values.
+    pub example: bool,
+    /// If true, generate `sample_partial()`, which sets optional fields at random.
+    pub sample: bool,
+    /// If true, generate `fake()` and `fake_with_rng()` with the `fake` crate.
+    pub fake: bool,
+    /// If true, implement `ValueCodec` to encode fields one by one.
+    pub value_codec: bool,
+    /// If true, generate methods mapping the struct to and from CSV records.
+    pub csv: bool,
+    /// If true, generate an Arrow schema and a `<Struct>ArrayBuilder`.
+    pub arrow: bool,
+    /// If true, generate a schema fingerprint and methods encoding the struct in
+    /// envelopes carrying it.
+    pub envelope: bool,
+    /// If true, optional fields can be set with a TTL after which they read as absent.
+    pub ttl: bool,
+    /// If true, removing an optional field leaves a tombstone until it is set again.
+    pub tombstones: bool,
+    /// If true, values can be tagged with the `Source` that supplied them.
+    pub provenance: bool,
+    /// If true, record the time of the last write to each field.
+    pub timestamps: bool,
+    /// If true, stamp each write so that `merge()` can resolve concurrent updates.
+    pub lww: bool,
+    /// A function checking struct-level invariants, called by `validate()`.
+    pub invariant: Option<Path>,
+    /// A function called with the name of a required field found absent, instead of
+    /// panicking.
+    pub on_missing_required: Option<Path>,
+    /// If true, generated code aborts instead of panicking, and getters are checked
+    /// not to panic with the `no-panic` feature.
+    pub no_panic: bool,
+    /// The error type returned by `validate()` and the fallible constructor.
+    pub error: Option<Type>,
+    /// The type passed to the loaders of lazy fields.
+    pub context: Option<Type>,
+    /// The `Clock` of the write timestamps, if not `SystemClock`.
+    pub clock: Option<Type>,
+    /// Key type of the catch-all synthesized for a unit struct.
+    pub unit_key: Option<Type>,
+    /// Value type of the catch-all synthesized for a unit struct.
+    pub unit_value: Option<Type>,
+    /// Key normalization function of the catch-all synthesized for a unit struct.
+    pub unit_normalize: Option<Path>,
+    /// Key validation function of the catch-all synthesized for a unit struct.
+    pub unit_validate_key: Option<Path>,
+    /// Default visibility of generated accessors, overriding the fields' own visibility.
+    pub accessor_vis: Option<Visibility>,
+    /// Default visibility of generated getters.
+    pub get_vis: Option<Visibility>,
+    /// Default visibility of generated setters and mutable getters.
+    pub set_vis: Option<Visibility>,
+    /// Default visibility of generated removers.
+    pub remove_vis: Option<Visibility>,
+    /// The doc string of the generated accessors, with `{field}`, `{type}`, and
+    /// `{method}` placeholders.
+    pub doc_template: Option<LitStr>,
+    /// Prefix of the default getter and mutable getter names, none by default.
+    pub getter_prefix: Option<String>,
+    /// Prefix of the default setter names, `set_` by default.
+    pub setter_prefix: Option<String>,
+    /// Prefix of the default remover names, `remove_` by default.
+    pub remover_prefix: Option<String>,
+    /// The catch-all's key type if it mentions the struct's generics, in which
+    /// case the field enum has no default key type and is named with this one.
+    /// Set from the parsed fields rather than from the attribute.
+    pub generic_key: Option<Type>,
+}
+
+impl StructibleConfig {
+    /// Returns the name of the infallible constructor.
+    ///
+    /// The default `new` carries the span of the struct name, so that editors
+    /// navigate from calls to the struct declaration.
+    pub fn constructor_name(&self, struct_name: &Ident) -> Ident {
+        self.constructor
+            .clone()
+            .unwrap_or_else(|| format_ident!("new", span = struct_name.span()))
+    }
+
+    /// Returns the error type used by generated validation methods.
+    pub fn error_type(&self) -> TokenStream {
+        match &self.error {
+            Some(ty) => quote::quote! { #ty },
+            None => quote::quote! { ::structible::ValidationError },
+        }
+    }
+
+    /// Returns true if the generated writes record more than the new value, in
+    /// which case writes through mutable references can't be offered.
+    pub fn records_writes(&self) -> bool {
+        self.track_changes || self.lww || self.transactional
+    }
+
+    /// Returns true if writes do more than update the backing map, so that they
+    /// can't be a bare `BackingMap` call.
+    pub fn has_write_hooks(&self) -> bool {
+        self.records_writes()
+            || self.ttl
+            || self.tombstones
+            || self.provenance
+            || self.timestamps
+            || self.instrument
+            || self.trace
+    }
+
+    /// Returns true if mutable getters are generated.
+    pub fn has_mut_getters(&self) -> bool {
+        !self.records_writes() && !self.no_mut_getters
+    }
+
+    /// Returns the default getter name of a field named `name`, following `getter_prefix`.
+    pub fn getter_name(&self, name: &Ident) -> Ident {
+        match &self.getter_prefix {
+            Some(prefix) => format_ident!("{}{}", prefix, name),
+            None => name.clone(),
+        }
+    }
+
+    /// Returns the default mutable getter name of a field named `name`.
+    pub fn getter_mut_name(&self, name: &Ident) -> Ident {
+        format_ident!("{}_mut", self.getter_name(name))
+    }
+
+    /// Returns the default setter name of a field named `name`, following `setter_prefix`.
+    pub fn setter_name(&self, name: &Ident) -> Ident {
+        format_ident!(
+            "{}{}",
+            self.setter_prefix.as_deref().unwrap_or("set_"),
+            name
+        )
+    }
+
+    /// Returns the default remover name of a field named `name`, following `remover_prefix`.
+    pub fn remover_name(&self, name: &Ident) -> Ident {
+        format_ident!(
+            "{}{}",
+            self.remover_prefix.as_deref().unwrap_or("remove_"),
+            name
+        )
+    }
+
+    /// Returns the doc string of the accessor `method` of `field`: the
+    /// `doc_template` with its placeholders filled in, or `default` without one.
+    pub fn accessor_doc(&self, field: &FieldInfo, method: &Ident, default: String) -> String {
+        let Some(template) = &self.doc_template else {
+            return default;
+        };
+        template
+            .value()
+            .replace("{field}", &field.name.unraw().to_string())
+            .replace("{type}", &type_text(&field.ty))
+            .replace("{method}", &method.unraw().to_string())
+    }
+}
+
+/// Parses an accessor name prefix, which must start an identifier.
+fn parse_accessor_prefix(prefix: LitStr) -> syn::Result<String> {
+    let value = prefix.value();
+    if syn::parse_str::<Ident>(&format!("{}field", value)).is_err() {
+        return Err(syn::Error::new_spanned(
+            prefix,
+            "accessor prefixes may only contain letters, digits, and underscores, and must not start with a digit",
+        ));
+    }
+    Ok(value)
+}
+
+/// Checks that a `doc_template` only uses the placeholders `accessor_doc` fills in.
+fn check_doc_template(template: &LitStr) -> syn::Result<()> {
+    let value = template.value();
+    let mut rest = value.as_str();
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+        let placeholder = &rest[start + 1..start + end];
+        if !["field", "type", "method"].contains(&placeholder) {
+            return Err(syn::Error::new_spanned(
+                template,
+                format!(
+                    "unknown placeholder `{{{}}}` in `doc_template`; expected `{{field}}`, `{{type}}`, or `{{method}}`",
+                    placeholder
+                ),
+            ));
+        }
+        rest = &rest[start + end + 1..];
+    }
+    Ok(())
 }
 
 /// Configuration parsed from `#[structible(...)]` attribute on a field.
@@ -56,61 +342,173 @@ pub struct FieldConfig {
     pub remove: Option<Ident>,
     /// If present, this field is an unknown fields catch-all with the given key type.
     pub unknown_key: Option<Type>,
+    /// Fields that must be present whenever this field is present.
+    pub requires: Vec<Ident>,
+    /// Fields that must be absent whenever this field is present.
+    pub conflicts_with: Vec<Ident>,
+    /// Former names of this field, which get deprecated accessors.
+    pub aliases: Vec<Ident>,
+    /// Visibility of the generated accessors, overriding the field's own visibility.
+    pub vis: Option<Visibility>,
+    /// Visibility of the generated getters, overriding `vis`.
+    pub get_vis: Option<Visibility>,
+    /// Visibility of the generated setters and mutable getters, overriding `vis`.
+    pub set_vis: Option<Visibility>,
+    /// Visibility of the generated removers, overriding `vis`.
+    pub remove_vis: Option<Visibility>,
+    /// A function normalizing the keys of this catch-all on insert and lookup.
+    pub normalize: Option<Path>,
+    /// A function checking the keys of this catch-all in `validate()` and `try_insert_*`.
+    pub validate_key: Option<Path>,
+    /// Whether entries of this catch-all whose key names a known field are moved into it.
+    pub promote_known: bool,
+    /// Whether this field gets methods moving values between it and the catch-all.
+    pub promotable: bool,
+    /// Whether this catch-all holds a `Vec` of values per key, appended to one by one.
+    pub multi: bool,
+    /// Whether trace events and `record_fields()` leave out this field's values.
+    pub redact: bool,
+    /// Whether equality of the struct compares only the presence of this field,
+    /// not its values.
+    pub no_eq: bool,
+    /// Whether clones hold the default value instead of a clone of this field's
+    /// value, or leave the field out if it is optional.
+    pub skip_clone: bool,
+    /// Whether equality of the struct ignores this field altogether.
+    pub skip_eq: bool,
+    /// A function combining both replicas' values of this field in `merge()`.
+    pub merge_with: Option<Path>,
+    /// A function loading this field from the struct's context when it is absent.
+    pub lazy: Option<Path>,
+    /// Whether the getter upgrades the field's weak reference, overriding the
+    /// detection of `Weak<T>` types.
+    pub upgrade: Option<bool>,
+    /// A legacy type this field's values may be decoded from.
+    pub upgrade_from: Option<Type>,
+    /// A function converting a value of the legacy type into the field's type.
+    pub upgrade_with: Option<Path>,
+    /// The type wrapped by this field's newtype, in which its getter and setter work.
+    pub transparent: Option<Type>,
+    /// Variants of this field's enum type that get `is_<field>_<variant>()` predicates.
+    pub variants: Vec<Ident>,
+    /// Whether this field gets `push_`, `extend_`, and `_contains` helpers for the
+    /// collection it stores.
+    pub collection: bool,
+    /// Whether this `String` field gets `append_`, `clear_`, and `_str` helpers.
+    pub string: bool,
+    /// Whether this number field gets `incr_` and `decr_` helpers.
+    pub numeric: bool,
+    /// Why this optional field is expected to be present, reported by its
+    /// `<getter>_or_err()` when it isn't.
+    pub required_context: Option<LitStr>,
+    /// The range this field's values must lie in, with an inclusive upper bound.
+    pub range: Option<ExprRange>,
+    /// The range the length of this field's values must lie in, with an inclusive
+    /// upper bound.
+    pub len: Option<ExprRange>,
+    /// Whether this field's values must not be empty.
+    pub non_empty: bool,
+    /// A regular expression this field's values must match.
+    pub matches: Option<LitStr>,
+    /// The value of this field in `example()`.
+    pub example: Option<Expr>,
+    /// The faker generating this field's values in `fake()`.
+    pub fake: Option<Expr>,
+    /// Whether this field belongs to a `no_panic` struct, whose getters report a
+    /// missing required value as `None` instead of panicking.
+    pub no_panic: bool,
 }
 
+/// Struct-level flags, i.e. attributes that are not followed by `= value`.
+const STRUCT_FLAGS: &[&str] = &[
+    "with_len",
+    "no_clone",
+    "no_partial_eq",
+    "no_structible",
+    "no_setters",
+    "no_removers",
+    "no_mut_getters",
+    "from_tuple",
+    "async",
+    "serde_backing",
+    "track_changes",
+    "lww",
+    "ttl",
+    "tombstones",
+    "provenance",
+    "timestamps",
+    "freeze",
+    "cow",
+    "ffi",
+    "value_codec",
+    "csv",
+    "arrow",
+    "envelope",
+    "par_visit",
+    "debug_json",
+    "test_serialize",
+    "serde",
+    "fuzz",
+    "bench",
+    "no_panic",
+    "instrument",
+    "trace",
+    "record_fields",
+    "labels",
+    "transactional",
+    "patch",
+    "builder",
+    "openapi",
+    "axum",
+    "graphql",
+    "debug_expand",
+    "minimal",
+    "non_exhaustive",
+    "accessor_traits",
+    "mock",
+    "example",
+    "sample",
+    "fake",
+];
+
 impl Parse for StructibleConfig {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         // Default to HashMap if no arguments provided
         if input.is_empty() {
-            return Ok(StructibleConfig {
-                backing: BackingType::default(),
-                constructor: None,
-                with_len: false,
-                no_clone: false,
-                no_partial_eq: false,
-            });
+            return Ok(StructibleConfig::default());
         }
 
         // Try to parse as a shorthand (just a type, not key = value or flag)
         // We detect this by checking if it looks like `backing = ...` or `constructor = ...`
         // or a known flag like `with_len`, `no_clone`, `no_partial_eq`
         let fork = input.fork();
-        if let Ok(first_ident) = fork.parse::<Ident>() {
+        if let Ok(first_ident) = fork.call(Ident::parse_any) {
             let is_key_value = fork.peek(Token![=]);
-            let is_flag = first_ident == "with_len"
-                || first_ident == "no_clone"
-                || first_ident == "no_partial_eq";
+            let is_flag = STRUCT_FLAGS.iter().any(|flag| first_ident == flag);
             let has_more = fork.peek(Token![,]);
             if !is_key_value && !is_flag && !has_more {
                 // This is a shorthand type specification
                 // Parse the full type (could be `HashMap`, `indexmap::IndexMap`, etc.)
                 let ty: Type = input.parse()?;
-                let backing = BackingType::from_type(ty);
                 return Ok(StructibleConfig {
-                    backing,
-                    constructor: None,
-                    with_len: false,
-                    no_clone: false,
-                    no_partial_eq: false,
+                    backing: BackingType::from_type(ty),
+                    ..StructibleConfig::default()
                 });
             }
         }
 
         // Parse as comma-separated items (key-value pairs or flags)
-        let mut backing = None;
-        let mut constructor = None;
-        let mut with_len = false;
-        let mut no_clone = false;
-        let mut no_partial_eq = false;
+        let mut config = StructibleConfig::default();
 
         while !input.is_empty() {
-            let key: Ident = input.parse()?;
+            // `async` is a keyword, so parse keys with `parse_any`
+            let key = input.call(Ident::parse_any)?;
 
             match key.to_string().as_str() {
                 "backing" => {
                     let _: Token![=] = input.parse()?;
                     let ty: Type = input.parse()?;
-                    backing = Some(BackingType::from_type(ty));
+                    config.backing = BackingType::from_type(ty);
                 }
                 "constructor" => {
                     let _: Token![=] = input.parse()?;
@@ -127,16 +525,237 @@ impl Parse for StructibleConfig {
                             ));
                         }
                     };
-                    constructor = Some(ident);
+                    config.constructor = Some(ident);
+                }
+                "name" => {
+                    let _: Token![=] = input.parse()?;
+                    config.name = Some(input.parse()?);
+                }
+                "invariant" => {
+                    let _: Token![=] = input.parse()?;
+                    config.invariant = Some(input.parse()?);
+                }
+                "on_missing_required" => {
+                    let _: Token![=] = input.parse()?;
+                    config.on_missing_required = Some(input.parse()?);
+                }
+                "error" => {
+                    let _: Token![=] = input.parse()?;
+                    config.error = Some(input.parse()?);
+                }
+                "accessor_vis" => {
+                    let _: Token![=] = input.parse()?;
+                    config.accessor_vis = Some(input.parse()?);
+                }
+                "get_vis" => {
+                    let _: Token![=] = input.parse()?;
+                    config.get_vis = Some(input.parse()?);
+                }
+                "set_vis" => {
+                    let _: Token![=] = input.parse()?;
+                    config.set_vis = Some(input.parse()?);
+                }
+                "remove_vis" => {
+                    let _: Token![=] = input.parse()?;
+                    config.remove_vis = Some(input.parse()?);
+                }
+                "context" => {
+                    let _: Token![=] = input.parse()?;
+                    config.context = Some(input.parse()?);
+                }
+                "clock" => {
+                    let _: Token![=] = input.parse()?;
+                    config.clock = Some(input.parse()?);
+                }
+                "doc_template" => {
+                    let _: Token![=] = input.parse()?;
+                    let template: LitStr = input.parse()?;
+                    check_doc_template(&template)?;
+                    config.doc_template = Some(template);
+                }
+                "getter_prefix" | "setter_prefix" | "remover_prefix" => {
+                    let _: Token![=] = input.parse()?;
+                    let prefix = parse_accessor_prefix(input.parse()?)?;
+                    let slot = match key.to_string().as_str() {
+                        "getter_prefix" => &mut config.getter_prefix,
+                        "setter_prefix" => &mut config.setter_prefix,
+                        _ => &mut config.remover_prefix,
+                    };
+                    *slot = Some(prefix);
+                }
+                "key" => {
+                    let _: Token![=] = input.parse()?;
+                    config.unit_key = Some(input.parse()?);
+                }
+                "value" => {
+                    let _: Token![=] = input.parse()?;
+                    config.unit_value = Some(input.parse()?);
+                }
+                "normalize" => {
+                    let _: Token![=] = input.parse()?;
+                    config.unit_normalize = Some(input.parse()?);
+                }
+                "validate_key" => {
+                    let _: Token![=] = input.parse()?;
+                    config.unit_validate_key = Some(input.parse()?);
                 }
                 "with_len" => {
-                    with_len = true;
+                    config.with_len = true;
                 }
                 "no_clone" => {
-                    no_clone = true;
+                    config.no_clone = true;
                 }
                 "no_partial_eq" => {
-                    no_partial_eq = true;
+                    config.no_partial_eq = true;
+                }
+                "no_structible" => {
+                    config.no_structible = true;
+                }
+                "no_setters" => {
+                    config.no_setters = true;
+                }
+                "no_removers" => {
+                    config.no_removers = true;
+                }
+                "no_mut_getters" => {
+                    config.no_mut_getters = true;
+                }
+                "from_tuple" => {
+                    config.from_tuple = true;
+                }
+                "async" => {
+                    config.is_async = true;
+                }
+                "serde_backing" => {
+                    config.serde_backing = true;
+                }
+                "track_changes" => {
+                    config.track_changes = true;
+                }
+                "lww" => {
+                    config.lww = true;
+                }
+                "ttl" => {
+                    config.ttl = true;
+                }
+                "tombstones" => {
+                    config.tombstones = true;
+                }
+                "provenance" => {
+                    config.provenance = true;
+                }
+                "timestamps" => {
+                    config.timestamps = true;
+                }
+                "freeze" => {
+                    config.freeze = true;
+                }
+                "cow" => {
+                    config.cow = true;
+                }
+                "ffi" => {
+                    config.ffi = true;
+                }
+                "value_codec" => {
+                    config.value_codec = true;
+                }
+                "csv" => {
+                    config.csv = true;
+                }
+                "arrow" => {
+                    config.arrow = true;
+                }
+                "envelope" => {
+                    config.envelope = true;
+                }
+                "par_visit" => {
+                    config.par_visit = true;
+                }
+                "debug_json" => {
+                    config.debug_json = true;
+                }
+                "test_serialize" => {
+                    config.test_serialize = true;
+                }
+                "serde" => {
+                    config.serde = true;
+                }
+                "fuzz" => {
+                    config.fuzz = true;
+                }
+                "bench" => {
+                    config.bench = true;
+                }
+                "no_panic" => {
+                    config.no_panic = true;
+                }
+                "instrument" => {
+                    config.instrument = true;
+                }
+                "trace" => {
+                    config.trace = true;
+                }
+                "record_fields" => {
+                    config.record_fields = true;
+                }
+                "labels" => {
+                    config.labels = true;
+                }
+                "transactional" => {
+                    config.transactional = true;
+                }
+                "patch" => {
+                    config.patch = true;
+                }
+                "builder" => {
+                    config.builder = Some(BuilderMode::Fallible);
+                    if input.peek(Token![=]) {
+                        let _: Token![=] = input.parse()?;
+                        let mode = input.call(Ident::parse_any)?;
+                        config.builder = Some(match mode.to_string().as_str() {
+                            "panic" => BuilderMode::Panicking,
+                            "typestate" => BuilderMode::Typestate,
+                            _ => {
+                                return Err(syn::Error::new(
+                                    mode.span(),
+                                    "expected `builder`, `builder = panic`, or `builder = typestate`",
+                                ));
+                            }
+                        });
+                    }
+                }
+                "openapi" => {
+                    config.openapi = true;
+                }
+                "axum" => {
+                    config.axum = true;
+                }
+                "graphql" => {
+                    config.graphql = true;
+                }
+                "debug_expand" => {
+                    config.debug_expand = true;
+                }
+                "minimal" => {
+                    config.minimal = true;
+                }
+                "non_exhaustive" => {
+                    config.non_exhaustive = true;
+                }
+                "accessor_traits" => {
+                    config.accessor_traits = true;
+                }
+                "mock" => {
+                    config.mock = true;
+                }
+                "example" => {
+                    config.example = true;
+                }
+                "sample" => {
+                    config.sample = true;
+                }
+                "fake" => {
+                    config.fake = true;
                 }
                 other => {
                     return Err(syn::Error::new(
@@ -152,16 +771,7 @@ impl Parse for StructibleConfig {
             }
         }
 
-        // Default to HashMap if backing was not specified
-        let backing = backing.unwrap_or_default();
-
-        Ok(StructibleConfig {
-            backing,
-            constructor,
-            with_len,
-            no_clone,
-            no_partial_eq,
-        })
+        Ok(config)
     }
 }
 
@@ -182,11 +792,246 @@ impl FieldInfo {
         self.config.unknown_key.is_some()
     }
 
+    /// Returns true if this field is loaded on first access.
+    ///
+    /// Lazy fields are stored like optional fields, but their getter loads them.
+    pub fn is_lazy(&self) -> bool {
+        self.config.lazy.is_some()
+    }
+
+    /// Returns true if this field's getter upgrades a weak reference.
+    ///
+    /// Fields of type `Weak<T>` or `Option<Weak<T>>` are upgraded unless they
+    /// declare `no_upgrade`, and other fields if they declare `upgrade`.
+    pub fn is_weak(&self) -> bool {
+        if self.is_unknown_field() || self.is_lazy() {
+            return false;
+        }
+        self.config
+            .upgrade
+            .unwrap_or_else(|| is_weak_type(&self.inner_ty))
+    }
+
+    /// Returns the name of the getter of the weak reference of an upgraded field.
+    pub fn weak_getter_name(&self) -> Ident {
+        format_ident!("{}_weak", self.getter_name())
+    }
+
+    /// Returns true if this field's getter and setter convert to and from the type
+    /// wrapped by its newtype.
+    pub fn is_transparent(&self) -> bool {
+        self.config.transparent.is_some()
+    }
+
+    /// Returns the name of the getter of the newtype itself, if this field is
+    /// transparent.
+    pub fn newtype_getter_name(&self) -> Ident {
+        format_ident!("{}_newtype", self.getter_name())
+    }
+
+    /// Returns the trait object of a `Box<dyn Trait>` field, which its getter
+    /// borrows instead of the box.
+    pub fn trait_object(&self) -> Option<&TypeTraitObject> {
+        if self.is_unknown_field() {
+            return None;
+        }
+        boxed_trait_object(&self.inner_ty)
+    }
+
+    /// Returns the type that the reference returned by this field's getter points to.
+    pub fn getter_target(&self) -> TokenStream {
+        match self.trait_object() {
+            Some(object) if object.bounds.len() > 1 => quote! { (#object) },
+            Some(object) => quote! { #object },
+            None => {
+                let inner_ty = &self.inner_ty;
+                quote! { #inner_ty }
+            }
+        }
+    }
+
+    /// Returns true if this field's getters return an `Option`.
+    ///
+    /// Those of required fields do too in `no_panic` structs, where a required value
+    /// that went missing, e.g. through a leaked guard of a scoped take, can't panic.
+    pub fn has_optional_getter(&self) -> bool {
+        self.is_optional || self.config.no_panic
+    }
+
+    /// Returns the return type of this field's getter.
+    ///
+    /// Upgraded weak references are returned as their strong reference, transparent
+    /// fields as the type they wrap, and `Box<dyn Trait>` fields as the trait object.
+    pub fn getter_type(&self) -> TokenStream {
+        let inner_ty = &self.inner_ty;
+        if self.is_weak() {
+            return quote! { Option<<#inner_ty as ::structible::Upgrade>::Strong> };
+        }
+        if let Some(raw) = &self.config.transparent {
+            return if self.has_optional_getter() {
+                quote! { Option<#raw> }
+            } else {
+                quote! { #raw }
+            };
+        }
+        let target = self.getter_target();
+        if self.has_optional_getter() {
+            quote! { Option<&#target> }
+        } else {
+            quote! { &#target }
+        }
+    }
+
+    /// Returns the type of the value taken by this field's setter.
+    ///
+    /// Transparent fields take anything converting into their newtype.
+    pub fn setter_type(&self) -> TokenStream {
+        let value_ty = &self.inner_ty;
+        if self.is_transparent() {
+            quote! { impl ::std::convert::Into<#value_ty> }
+        } else {
+            quote! { #value_ty }
+        }
+    }
+
+    /// Returns true if equality doesn't compare this field's values.
+    ///
+    /// Fields declaring `no_eq` and fields of type `Box<dyn Trait>`, whose values
+    /// can't be compared, are compared by presence only, and fields declaring
+    /// `skip_eq` aren't compared at all.
+    pub fn skips_eq(&self) -> bool {
+        self.config.no_eq || self.config.skip_eq || boxed_trait_object(&self.inner_ty).is_some()
+    }
+
     /// Returns the key type for unknown fields, if this is an unknown field.
     pub fn unknown_key_type(&self) -> Option<&Type> {
         self.config.unknown_key.as_ref()
     }
 
+    /// Returns the element type `V` of a `multi` catch-all holding `Vec<V>` values.
+    pub fn multi_value_type(&self) -> Option<&Type> {
+        if !self.config.multi {
+            return None;
+        }
+        extract_vec_inner(&self.inner_ty)
+    }
+
+    /// Returns the owned catch-all `key`, normalized if the catch-all declares `normalize`.
+    pub fn normalized_key(&self, key: TokenStream) -> TokenStream {
+        match &self.config.normalize {
+            Some(normalize) => quote::quote! { #normalize(#key) },
+            None => key,
+        }
+    }
+
+    /// Returns the extra bound on `__Q` and the statements normalizing the borrowed `key`
+    /// of a catch-all lookup, if the catch-all declares `normalize`.
+    ///
+    /// The key is normalized into an owned key, which is then borrowed as `__Q` again,
+    /// so that the lookup itself is unchanged.
+    pub fn normalize_lookup(&self) -> (TokenStream, TokenStream) {
+        let (Some(normalize), Some(key_type)) = (&self.config.normalize, self.unknown_key_type())
+        else {
+            return (TokenStream::new(), TokenStream::new());
+        };
+        let bound = quote::quote! { ::std::borrow::ToOwned<Owned = #key_type> + };
+        let normalize = quote::quote! {
+            let normalized: #key_type = #normalize(::std::borrow::ToOwned::to_owned(key));
+            let key: &__Q = ::std::borrow::Borrow::borrow(&normalized);
+        };
+        (bound, normalize)
+    }
+
+    /// Returns the visibility of this field's generated accessors.
+    fn accessor_vis(&self) -> &Visibility {
+        self.config.vis.as_ref().unwrap_or(&self.vis)
+    }
+
+    /// Returns the visibility of methods reading this field.
+    pub fn getter_vis(&self) -> &Visibility {
+        self.config
+            .get_vis
+            .as_ref()
+            .unwrap_or_else(|| self.accessor_vis())
+    }
+
+    /// Returns the visibility of methods writing this field, including mutable getters.
+    pub fn setter_vis(&self) -> &Visibility {
+        self.config
+            .set_vis
+            .as_ref()
+            .unwrap_or_else(|| self.accessor_vis())
+    }
+
+    /// Returns the visibility of methods removing this field.
+    pub fn remover_vis(&self) -> &Visibility {
+        self.config
+            .remove_vis
+            .as_ref()
+            .unwrap_or_else(|| self.accessor_vis())
+    }
+
+    /// Returns the former names of this field, which decoders accept as keys.
+    pub fn alias_names(&self) -> Vec<String> {
+        self.config
+            .aliases
+            .iter()
+            .map(|alias| alias.unraw().to_string())
+            .collect()
+    }
+
+    /// Returns the attributes forwarded onto this field's generated methods.
+    pub fn method_attrs(&self) -> Vec<&Attribute> {
+        self.attrs.iter().filter(|a| is_method_attr(a)).collect()
+    }
+
+    /// Returns the attributes placed on this field's variant in the hidden field enum.
+    ///
+    /// Attributes forwarded onto the methods are left out; in particular `#[deprecated]`
+    /// would otherwise warn on every use of the variant inside the generated code.
+    pub fn variant_attrs(&self) -> Vec<&Attribute> {
+        self.attrs.iter().filter(|a| !is_method_attr(a)).collect()
+    }
+
+    /// Returns the name of the getter generated for this field.
+    pub fn getter_name(&self) -> Ident {
+        self.config.get.clone().unwrap_or_else(|| self.name.clone())
+    }
+
+    /// Returns the name of the mutable getter generated for this field.
+    pub fn getter_mut_name(&self) -> Ident {
+        self.config
+            .get_mut
+            .clone()
+            .unwrap_or_else(|| format_ident!("{}_mut", self.name))
+    }
+
+    /// Returns the name of the setter generated for this field.
+    pub fn setter_name(&self) -> Ident {
+        self.config
+            .set
+            .clone()
+            .unwrap_or_else(|| format_ident!("set_{}", self.name))
+    }
+
+    /// Returns the name of the remover generated for this (optional) field.
+    pub fn remover_name(&self) -> Ident {
+        self.config
+            .remove
+            .clone()
+            .unwrap_or_else(|| format_ident!("remove_{}", self.name))
+    }
+
+    /// Returns true if this field declares presence constraints.
+    pub fn has_constraints(&self) -> bool {
+        !self.config.requires.is_empty()
+            || !self.config.conflicts_with.is_empty()
+            || self.config.range.is_some()
+            || self.config.len.is_some()
+            || self.config.non_empty
+            || self.config.matches.is_some()
+    }
+
     pub fn from_field(field: &Field) -> syn::Result<Self> {
         let name = field.ident.clone().ok_or_else(|| {
             syn::Error::new_spanned(field, "structible only supports named fields")
@@ -201,6 +1046,19 @@ impl FieldInfo {
         // Parse field-level structible attributes
         let config = parse_field_config(&field.attrs)?;
 
+        // Lazy fields may be absent until loaded, so they are stored like optional fields
+        let is_optional = if let Some(loader) = &config.lazy {
+            if is_optional {
+                return Err(syn::Error::new_spanned(
+                    loader,
+                    "lazy fields are loaded when absent and must not be `Option`",
+                ));
+            }
+            true
+        } else {
+            is_optional
+        };
+
         // Filter out structible attributes from the preserved attrs
         let attrs: Vec<_> = field
             .attrs
@@ -248,6 +1106,123 @@ fn parse_field_config(attrs: &[Attribute]) -> syn::Result<FieldConfig> {
                     let _: Token![=] = meta.input.parse()?;
                     let key_type: Type = meta.input.parse()?;
                     config.unknown_key = Some(key_type);
+                } else if meta.path.is_ident("requires") {
+                    let _: Token![=] = meta.input.parse()?;
+                    let value: Ident = meta.input.parse()?;
+                    config.requires.push(value);
+                } else if meta.path.is_ident("conflicts_with") {
+                    let _: Token![=] = meta.input.parse()?;
+                    let value: Ident = meta.input.parse()?;
+                    config.conflicts_with.push(value);
+                } else if meta.path.is_ident("alias") {
+                    let _: Token![=] = meta.input.parse()?;
+                    let value: Ident = meta.input.parse()?;
+                    config.aliases.push(value);
+                } else if meta.path.is_ident("vis") {
+                    let _: Token![=] = meta.input.parse()?;
+                    let value: Visibility = meta.input.parse()?;
+                    config.vis = Some(value);
+                } else if meta.path.is_ident("get_vis") {
+                    let _: Token![=] = meta.input.parse()?;
+                    let value: Visibility = meta.input.parse()?;
+                    config.get_vis = Some(value);
+                } else if meta.path.is_ident("set_vis") {
+                    let _: Token![=] = meta.input.parse()?;
+                    let value: Visibility = meta.input.parse()?;
+                    config.set_vis = Some(value);
+                } else if meta.path.is_ident("remove_vis") {
+                    let _: Token![=] = meta.input.parse()?;
+                    let value: Visibility = meta.input.parse()?;
+                    config.remove_vis = Some(value);
+                } else if meta.path.is_ident("lazy") {
+                    let _: Token![=] = meta.input.parse()?;
+                    let value: Path = meta.input.parse()?;
+                    config.lazy = Some(value);
+                } else if meta.path.is_ident("upgrade") {
+                    config.upgrade = Some(true);
+                } else if meta.path.is_ident("no_upgrade") {
+                    config.upgrade = Some(false);
+                } else if meta.path.is_ident("upgrade_from") {
+                    let _: Token![=] = meta.input.parse()?;
+                    let value: Type = meta.input.parse()?;
+                    config.upgrade_from = Some(value);
+                } else if meta.path.is_ident("variants") {
+                    meta.parse_nested_meta(|variant| {
+                        config.variants.push(variant.path.require_ident()?.clone());
+                        Ok(())
+                    })?;
+                } else if meta.path.is_ident("range") {
+                    let _: Token![=] = meta.input.parse()?;
+                    let value: ExprRange = meta.input.parse()?;
+                    config.range = Some(value);
+                } else if meta.path.is_ident("len") {
+                    let _: Token![=] = meta.input.parse()?;
+                    let value: ExprRange = meta.input.parse()?;
+                    config.len = Some(value);
+                } else if meta.path.is_ident("matches") {
+                    let _: Token![=] = meta.input.parse()?;
+                    let value: LitStr = meta.input.parse()?;
+                    if let Err(err) = regex_syntax::Parser::new().parse(&value.value()) {
+                        return Err(syn::Error::new_spanned(
+                            &value,
+                            format!("invalid `matches` pattern: {}", err),
+                        ));
+                    }
+                    config.matches = Some(value);
+                } else if meta.path.is_ident("non_empty") {
+                    config.non_empty = true;
+                } else if meta.path.is_ident("collection") {
+                    config.collection = true;
+                } else if meta.path.is_ident("string") {
+                    config.string = true;
+                } else if meta.path.is_ident("numeric") {
+                    config.numeric = true;
+                } else if meta.path.is_ident("required_context") {
+                    let _: Token![=] = meta.input.parse()?;
+                    let value: LitStr = meta.input.parse()?;
+                    config.required_context = Some(value);
+                } else if meta.path.is_ident("transparent") {
+                    let _: Token![=] = meta.input.parse()?;
+                    let value: Type = meta.input.parse()?;
+                    config.transparent = Some(value);
+                } else if meta.path.is_ident("with") {
+                    let _: Token![=] = meta.input.parse()?;
+                    let value: Path = meta.input.parse()?;
+                    config.upgrade_with = Some(value);
+                } else if meta.path.is_ident("multi") {
+                    config.multi = true;
+                } else if meta.path.is_ident("redact") {
+                    config.redact = true;
+                } else if meta.path.is_ident("no_eq") {
+                    config.no_eq = true;
+                } else if meta.path.is_ident("skip_clone") {
+                    config.skip_clone = true;
+                } else if meta.path.is_ident("skip_eq") {
+                    config.skip_eq = true;
+                } else if meta.path.is_ident("promotable") {
+                    config.promotable = true;
+                } else if meta.path.is_ident("promote_known") {
+                    config.promote_known = true;
+                } else if meta.path.is_ident("validate_key") {
+                    let _: Token![=] = meta.input.parse()?;
+                    let value: Path = meta.input.parse()?;
+                    config.validate_key = Some(value);
+                } else if meta.path.is_ident("normalize") {
+                    let _: Token![=] = meta.input.parse()?;
+                    let value: Path = meta.input.parse()?;
+                    config.normalize = Some(value);
+                } else if meta.path.is_ident("example") {
+                    let _: Token![=] = meta.input.parse()?;
+                    let value: Expr = meta.input.parse()?;
+                    config.example = Some(value);
+                } else if meta.path.is_ident("fake") {
+                    let _: Token![=] = meta.input.parse()?;
+                    let value: LitStr = meta.input.parse()?;
+                    config.fake = Some(value.parse()?);
+                } else if meta.path.is_ident("merge_with") {
+                    let _: Token![=] = meta.input.parse()?;
+                    let value: Path = meta.input.parse()?;
+                    config.merge_with = Some(value);
                 } else {
                     return Err(meta.error(format!(
                         "unknown field attribute `{}`",
@@ -262,8 +1237,107 @@ fn parse_field_config(attrs: &[Attribute]) -> syn::Result<FieldConfig> {
     Ok(config)
 }
 
+/// Name of the catch-all field synthesized for unit structs.
+const UNIT_CATCH_ALL: &str = "extra";
+
+/// Synthesize the catch-all field of a unit struct from the struct-level `key` and `value`.
+///
+/// `#[structible(key = K, value = V)] struct Bag;` is treated like a struct with the
+/// single field `extra: Option<V>` marked `#[structible(key = K)]`. The value type
+/// defaults to `String`, so `#[structible(key = String)] struct Bag;` maps strings.
+fn unit_catch_all(item: &ItemStruct, config: &StructibleConfig) -> syn::Result<FieldInfo> {
+    let Some(key) = &config.unit_key else {
+        return Err(syn::Error::new_spanned(
+            item,
+            "unit structs require `key = K` to describe their catch-all",
+        ));
+    };
+    let value: Type = match &config.unit_value {
+        Some(value) => value.clone(),
+        None => syn::parse_quote! { ::std::string::String },
+    };
+
+    Ok(FieldInfo {
+        name: Ident::new(UNIT_CATCH_ALL, item.ident.span()),
+        ty: syn::parse_quote! { Option<#value> },
+        inner_ty: value,
+        is_optional: true,
+        vis: item.vis.clone(),
+        attrs: Vec::new(),
+        config: FieldConfig {
+            unknown_key: Some(key.clone()),
+            normalize: config.unit_normalize.clone(),
+            validate_key: config.unit_validate_key.clone(),
+            ..FieldConfig::default()
+        },
+    })
+}
+
+/// Apply the struct-level accessor visibilities to fields without their own `vis`.
+///
+/// A field's `vis` overrides all struct-level visibilities, and a field's per-kind
+/// visibilities (`get_vis`, ...) override the struct-level ones of the same kind.
+fn apply_accessor_vis(fields: &mut [FieldInfo], config: &StructibleConfig) {
+    for field in fields {
+        if field.config.vis.is_some() {
+            continue;
+        }
+        let defaults = [
+            (&mut field.config.get_vis, &config.get_vis),
+            (&mut field.config.set_vis, &config.set_vis),
+            (&mut field.config.remove_vis, &config.remove_vis),
+            (&mut field.config.vis, &config.accessor_vis),
+        ];
+        for (field_vis, struct_vis) in defaults {
+            if field_vis.is_none() {
+                field_vis.clone_from(struct_vis);
+            }
+        }
+    }
+}
+
+/// Name the accessors of known fields without their own names after the
+/// struct-level prefixes.
+///
+/// The catch-all's methods are always named after it, e.g. `insert_<catch-all>`.
+fn apply_accessor_prefixes(fields: &mut [FieldInfo], config: &StructibleConfig) {
+    for field in fields.iter_mut().filter(|f| !f.is_unknown_field()) {
+        let name = &field.name;
+        let names = [
+            (
+                &mut field.config.get,
+                config.getter_prefix.is_some(),
+                config.getter_name(name),
+            ),
+            (
+                &mut field.config.get_mut,
+                config.getter_prefix.is_some(),
+                config.getter_mut_name(name),
+            ),
+            (
+                &mut field.config.set,
+                config.setter_prefix.is_some(),
+                config.setter_name(name),
+            ),
+            (
+                &mut field.config.remove,
+                config.remover_prefix.is_some(),
+                config.remover_name(name),
+            ),
+        ];
+        for (field_name, prefixed, default) in names {
+            if prefixed && field_name.is_none() {
+                *field_name = Some(default);
+            }
+        }
+    }
+}
+
 /// Parse all fields from a struct.
-pub fn parse_struct_fields(item: &ItemStruct) -> syn::Result<Vec<FieldInfo>> {
+pub fn parse_struct_fields(
+    item: &ItemStruct,
+    config: &StructibleConfig,
+) -> syn::Result<Vec<FieldInfo>> {
     let fields = match &item.fields {
         syn::Fields::Named(named) => &named.named,
         syn::Fields::Unnamed(_) => {
@@ -273,18 +1347,42 @@ pub fn parse_struct_fields(item: &ItemStruct) -> syn::Result<Vec<FieldInfo>> {
             ));
         }
         syn::Fields::Unit => {
-            return Err(syn::Error::new_spanned(
-                item,
-                "structible does not support unit structs",
-            ));
+            let mut catch_all = unit_catch_all(item, config)?;
+            apply_accessor_vis(std::slice::from_mut(&mut catch_all), config);
+            return Ok(vec![catch_all]);
         }
     };
 
-    let parsed: Vec<FieldInfo> = fields
+    if let Some(ty) = config.unit_key.as_ref().or(config.unit_value.as_ref()) {
+        return Err(syn::Error::new_spanned(
+            ty,
+            "struct-level `key` and `value` are only supported on unit structs; \
+             use a field-level `key` instead",
+        ));
+    }
+    if let Some(path) = config
+        .unit_normalize
+        .as_ref()
+        .or(config.unit_validate_key.as_ref())
+    {
+        return Err(syn::Error::new_spanned(
+            path,
+            "struct-level `normalize` and `validate_key` are only supported on unit structs; \
+             declare them on the catch-all field instead",
+        ));
+    }
+
+    let mut parsed: Vec<FieldInfo> = fields
         .iter()
         .map(FieldInfo::from_field)
         .collect::<Result<_, _>>()?;
 
+    apply_accessor_vis(&mut parsed, config);
+    apply_accessor_prefixes(&mut parsed, config);
+    for field in &mut parsed {
+        field.config.no_panic = config.no_panic;
+    }
+
     // Validate: at most one unknown field
     let unknown_fields: Vec<_> = parsed.iter().filter(|f| f.is_unknown_field()).collect();
     if unknown_fields.len() > 1 {
@@ -304,5 +1402,193 @@ pub fn parse_struct_fields(item: &ItemStruct) -> syn::Result<Vec<FieldInfo>> {
         }
     }
 
+    // Validate: constraints must name known fields
+    for field in &parsed {
+        let constraints = [
+            ("requires", &field.config.requires),
+            ("conflicts_with", &field.config.conflicts_with),
+        ];
+        for (kind, targets) in constraints {
+            for target in targets {
+                let Some(target_field) = parsed.iter().find(|f| f.name == *target) else {
+                    return Err(syn::Error::new_spanned(
+                        target,
+                        format!("`{}` refers to unknown field `{}`", kind, target),
+                    ));
+                };
+                if target_field.is_unknown_field() {
+                    return Err(syn::Error::new_spanned(
+                        target,
+                        format!("`{}` cannot refer to the unknown fields catch-all", kind),
+                    ));
+                }
+                if target_field.name == field.name {
+                    return Err(syn::Error::new_spanned(
+                        target,
+                        format!("`{}` cannot refer to the field itself", kind),
+                    ));
+                }
+                if kind == "conflicts_with" && !(field.is_optional && target_field.is_optional) {
+                    return Err(syn::Error::new_spanned(
+                        target,
+                        "`conflicts_with` can only be used between optional fields",
+                    ));
+                }
+            }
+        }
+        if field.is_unknown_field() && !field.config.aliases.is_empty() {
+            return Err(syn::Error::new_spanned(
+                &field.config.aliases[0],
+                "the unknown fields catch-all cannot declare aliases",
+            ));
+        }
+        if field.config.upgrade == Some(true) && (field.is_unknown_field() || field.is_lazy()) {
+            return Err(syn::Error::new_spanned(
+                &field.name,
+                "`upgrade` is not supported on the unknown fields catch-all or lazy fields",
+            ));
+        }
+        if let Some(raw) = &field.config.transparent
+            && (field.is_unknown_field() || field.is_lazy() || field.is_weak())
+        {
+            return Err(syn::Error::new_spanned(
+                raw,
+                "`transparent` is not supported on the unknown fields catch-all, lazy fields, or weak references",
+            ));
+        }
+        if let Some(variant) = field.config.variants.first() {
+            if field.is_unknown_field()
+                || field.is_lazy()
+                || field.is_weak()
+                || field.is_transparent()
+            {
+                return Err(syn::Error::new_spanned(
+                    variant,
+                    "`variants` is not supported on the unknown fields catch-all, lazy, weak, or transparent fields",
+                ));
+            }
+            if enum_path(&field.inner_ty).is_none() {
+                return Err(syn::Error::new_spanned(
+                    &field.inner_ty,
+                    "`variants` requires the field's type to be a path to an enum",
+                ));
+            }
+        }
+        if let Some(context) = &field.config.required_context
+            && (!field.is_optional || field.is_unknown_field() || field.is_lazy())
+        {
+            return Err(syn::Error::new_spanned(
+                context,
+                "`required_context` is only supported on optional fields, other than the unknown fields catch-all and lazy fields",
+            ));
+        }
+        for (name, range) in [("range", &field.config.range), ("len", &field.config.len)] {
+            let Some(range) = range else {
+                continue;
+            };
+            if range.start.is_none() && range.end.is_none() {
+                return Err(syn::Error::new_spanned(
+                    range,
+                    format!("`{}` requires a lower or an upper bound", name),
+                ));
+            }
+            if matches!(range.limits, RangeLimits::HalfOpen(_)) && range.end.is_some() {
+                return Err(syn::Error::new_spanned(
+                    range,
+                    format!("`{}` requires an inclusive upper bound (`..=`)", name),
+                ));
+            }
+            if field.is_weak() || field.is_transparent() {
+                return Err(syn::Error::new_spanned(
+                    range,
+                    format!(
+                        "`{}` is not supported on weak references or transparent fields",
+                        name
+                    ),
+                ));
+            }
+        }
+        if field.config.non_empty {
+            if let Some(len) = &field.config.len {
+                return Err(syn::Error::new_spanned(
+                    len,
+                    "`non_empty` cannot be combined with `len`; give `len` a lower bound of 1 instead",
+                ));
+            }
+            if field.is_weak() || field.is_transparent() {
+                return Err(syn::Error::new_spanned(
+                    &field.name,
+                    "`non_empty` is not supported on weak references or transparent fields",
+                ));
+            }
+        }
+        if let Some(pattern) = &field.config.matches
+            && (field.is_weak() || field.is_transparent())
+        {
+            return Err(syn::Error::new_spanned(
+                pattern,
+                "`matches` is not supported on weak references or transparent fields",
+            ));
+        }
+        if field.config.promote_known && !field.is_unknown_field() {
+            return Err(syn::Error::new_spanned(
+                &field.name,
+                "`promote_known` can only be used on the unknown fields catch-all",
+            ));
+        }
+        if field.config.multi {
+            if !field.is_unknown_field() {
+                return Err(syn::Error::new_spanned(
+                    &field.name,
+                    "`multi` can only be used on the unknown fields catch-all",
+                ));
+            }
+            if field.multi_value_type().is_none() {
+                return Err(syn::Error::new_spanned(
+                    &field.inner_ty,
+                    "`multi` requires the catch-all's value type to be `Vec<V>`",
+                ));
+            }
+        }
+        if let Some(path) = field
+            .config
+            .normalize
+            .as_ref()
+            .or(field.config.validate_key.as_ref())
+            && !field.is_unknown_field()
+        {
+            return Err(syn::Error::new_spanned(
+                path,
+                "`normalize` and `validate_key` can only be used on the unknown fields catch-all",
+            ));
+        }
+        if field.config.skip_clone {
+            if field.is_unknown_field() {
+                return Err(syn::Error::new_spanned(
+                    &field.name,
+                    "`skip_clone` is not supported on the unknown fields catch-all",
+                ));
+            }
+            if config.no_clone {
+                return Err(syn::Error::new_spanned(
+                    &field.name,
+                    "`skip_clone` has no effect with `no_clone`",
+                ));
+            }
+        }
+        if field.config.skip_eq && config.no_partial_eq {
+            return Err(syn::Error::new_spanned(
+                &field.name,
+                "`skip_eq` has no effect with `no_partial_eq`",
+            ));
+        }
+        if field.is_unknown_field() && field.has_constraints() {
+            return Err(syn::Error::new_spanned(
+                &field.name,
+                "the unknown fields catch-all cannot declare constraints",
+            ));
+        }
+    }
+
     Ok(parsed)
 }
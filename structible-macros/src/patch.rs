@@ -0,0 +1,322 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::{Generics, Ident, Visibility};
+
+use crate::codegen::{field_enum_type, value_enum_name};
+use crate::instrument::trace_bounds;
+use crate::parse::{FieldInfo, StructibleConfig};
+use crate::promote::promote_bounds;
+use crate::util::{to_pascal_case, type_mentions_type_param};
+use crate::validate::has_validation;
+
+/// Returns the name of the patch builder type of a struct.
+pub fn patch_builder_name(struct_name: &Ident) -> Ident {
+    format_ident!("{}PatchBuilder", struct_name)
+}
+
+/// Checks that a struct with `patch` can apply and dry-run its patches.
+pub fn check_patch_config(struct_name: &Ident, config: &StructibleConfig) -> syn::Result<()> {
+    if config.patch && (config.no_clone || config.no_setters) {
+        let other = if config.no_clone {
+            "no_clone"
+        } else {
+            "no_setters"
+        };
+        return Err(syn::Error::new_spanned(
+            struct_name,
+            format!("`patch` cannot be combined with `{}`", other),
+        ));
+    }
+    Ok(())
+}
+
+/// Generate the patch builder type, if `patch` is set.
+///
+/// The builder collects `FieldChange`s through methods named like the struct's
+/// setters and removers, and applies them through those, so that patches are
+/// recorded, normalized, and routed like any other write. `check()` applies the
+/// patch to a clone and reports every violation of `validate_all()`.
+pub fn generate_patch_builder(
+    struct_name: &Ident,
+    vis: &Visibility,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    if !config.patch {
+        return quote! {};
+    }
+    let builder = patch_builder_name(struct_name);
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let change_ty = quote! { ::structible::FieldChange<#field_enum, #value_enum #ty_generics> };
+    let error_ty = config.error_type();
+    let struct_doc = format!(
+        "A set of changes to a [`{}`], which can be checked against its validation before it is applied.\n\n\
+         Changes are applied through the struct's setters and removers, so they are recorded, \
+         traced, and normalized like any other write. Without declared validation, every \
+         patch passes `check()`.",
+        struct_name
+    );
+
+    let known_fields: Vec<_> = fields.iter().filter(|f| !f.is_unknown_field()).collect();
+    let setters = known_fields.iter().map(|f| {
+        let setter_name = f.setter_name();
+        let vis = f.setter_vis();
+        let variant = to_pascal_case(&f.name);
+        let value_ty = if f.is_optional { &f.inner_ty } else { &f.ty };
+        let method_attrs = f.method_attrs();
+        let doc = format!(
+            "Sets the `{}` value when the patch is applied.",
+            f.name.unraw()
+        );
+        quote! {
+            #[doc = #doc]
+            #(#method_attrs)*
+            #vis fn #setter_name(mut self, value: #value_ty) -> Self {
+                self.changes.push(::structible::FieldChange::Set(
+                    #field_enum::#variant,
+                    #value_enum::#variant(value),
+                ));
+                self
+            }
+        }
+    });
+    let removers = known_fields
+        .iter()
+        .filter(|f| f.is_optional && !config.no_removers)
+        .map(|f| {
+            let remover_name = f.remover_name();
+            let vis = f.remover_vis();
+            let variant = to_pascal_case(&f.name);
+            let method_attrs = f.method_attrs();
+            let doc = format!(
+                "Removes the `{}` field when the patch is applied.",
+                f.name.unraw()
+            );
+            quote! {
+                #[doc = #doc]
+                #(#method_attrs)*
+                #vis fn #remover_name(mut self) -> Self {
+                    self.changes.push(::structible::FieldChange::Remove(#field_enum::#variant));
+                    self
+                }
+            }
+        });
+    let unknown_methods = fields.iter().find(|f| f.is_unknown_field()).map(|f| {
+        let name = &f.name;
+        let key_type = f.unknown_key_type().unwrap();
+        let value_type = &f.inner_ty;
+        let insert_method = format_ident!("insert_{}", name);
+        let remove_method = format_ident!("remove_{}", name);
+        let set_vis = f.setter_vis();
+        let remove_vis = f.remover_vis();
+        let method_attrs = f.method_attrs();
+        let insert_doc = format!(
+            "Inserts an unknown `{}` field when the patch is applied.",
+            name.unraw()
+        );
+        let remove_doc = format!(
+            "Removes the `{}` field for the given key when the patch is applied.",
+            name.unraw()
+        );
+        let remove = (!config.no_removers).then(|| {
+            quote! {
+                #[doc = #remove_doc]
+                #(#method_attrs)*
+                #remove_vis fn #remove_method(mut self, key: #key_type) -> Self {
+                    self.changes.push(::structible::FieldChange::Remove(#field_enum::Unknown(key)));
+                    self
+                }
+            }
+        });
+        quote! {
+            #[doc = #insert_doc]
+            #(#method_attrs)*
+            #set_vis fn #insert_method(mut self, key: #key_type, value: #value_type) -> Self {
+                self.changes.push(::structible::FieldChange::Set(
+                    #field_enum::Unknown(key),
+                    #value_enum::Unknown(value),
+                ));
+                self
+            }
+
+            #remove
+        }
+    });
+
+    // `no_panic` structs skip a change pairing a field with another's value, which
+    // the builder's methods never create
+    let unreachable = if config.no_panic {
+        quote! { {} }
+    } else {
+        quote! { ::std::unreachable!("patch changes are built by their field's methods") }
+    };
+
+    // Changes are applied through the struct's own setters and removers
+    let apply_arms = fields.iter().map(|f| {
+        if f.is_unknown_field() {
+            let insert_method = format_ident!("insert_{}", f.name);
+            let remove_method = format_ident!("remove_{}", f.name);
+            let remove = (!config.no_removers).then(|| {
+                quote! {
+                    ::structible::FieldChange::Remove(#field_enum::Unknown(key)) => {
+                        target.#remove_method(&key);
+                    }
+                }
+            });
+            quote! {
+                ::structible::FieldChange::Set(#field_enum::Unknown(key), #value_enum::Unknown(value)) => {
+                    target.#insert_method(key, value);
+                }
+                #remove
+            }
+        } else {
+            let variant = to_pascal_case(&f.name);
+            let setter_name = f.setter_name();
+            let remove = (f.is_optional && !config.no_removers).then(|| {
+                let remover_name = f.remover_name();
+                quote! {
+                    ::structible::FieldChange::Remove(#field_enum::#variant) => {
+                        target.#remover_name();
+                    }
+                }
+            });
+            quote! {
+                ::structible::FieldChange::Set(#field_enum::#variant, #value_enum::#variant(value)) => {
+                    target.#setter_name(value);
+                }
+                #remove
+            }
+        }
+    });
+
+    let dry_run = if has_validation(fields, config) {
+        quote! {
+            let mut patched = ::std::clone::Clone::clone(target);
+            Self::__structible_apply(::std::clone::Clone::clone(&self.changes), &mut patched);
+            patched.validate_all()
+        }
+    } else {
+        quote! {
+            let _ = target;
+            ::std::result::Result::Ok(())
+        }
+    };
+
+    // Dry runs clone the changes, and applying them goes through the struct's
+    // setters, which may need further bounds
+    let type_param_idents: Vec<_> = generics.type_params().map(|tp| &tp.ident).collect();
+    let inner_types = fields
+        .iter()
+        .map(|f| &f.inner_ty)
+        .filter(|ty| type_mentions_type_param(ty, &type_param_idents));
+    let clone_bounds = quote! { #(#inner_types: ::std::clone::Clone,)* };
+    let promote_bounds = promote_bounds(fields, generics);
+    let trace_bounds = trace_bounds(fields, config, generics);
+    let bounds = quote! { #clone_bounds #promote_bounds #trace_bounds };
+    let apply_where = match where_clause {
+        Some(wc) => {
+            let existing = &wc.predicates;
+            quote! { where #bounds #existing }
+        }
+        None if !bounds.is_empty() => quote! { where #bounds },
+        None => quote! {},
+    };
+    let clone_where = match where_clause {
+        Some(wc) => {
+            let existing = &wc.predicates;
+            quote! { where #change_ty: ::std::clone::Clone, #existing }
+        }
+        None => quote! { where #change_ty: ::std::clone::Clone },
+    };
+    let debug_where = match where_clause {
+        Some(wc) => {
+            let existing = &wc.predicates;
+            quote! { where #change_ty: ::std::fmt::Debug, #existing }
+        }
+        None => quote! { where #change_ty: ::std::fmt::Debug },
+    };
+    let builder_name_str = builder.to_string();
+
+    quote! {
+        #[doc = #struct_doc]
+        #vis struct #builder #generics #where_clause {
+            changes: ::std::vec::Vec<#change_ty>,
+        }
+
+        impl #impl_generics #builder #ty_generics #where_clause {
+            /// Creates an empty patch.
+            pub fn new() -> Self {
+                Self { changes: ::std::vec::Vec::new() }
+            }
+
+            /// Returns the changes of this patch, in order.
+            pub fn changes(&self) -> &[#change_ty] {
+                &self.changes
+            }
+
+            #(#setters)*
+            #(#removers)*
+            #unknown_methods
+        }
+
+        impl #impl_generics #builder #ty_generics #apply_where {
+            /// Checks the patch against `target` without changing it: the patch is
+            /// applied to a clone, which must pass `validate_all()`.
+            pub fn check(
+                &self,
+                target: &#struct_name #ty_generics,
+            ) -> ::std::result::Result<(), ::std::vec::Vec<#error_ty>> {
+                #dry_run
+            }
+
+            /// Applies the patch to `target` if it passes `check()`; otherwise returns
+            /// every violation and leaves `target` unchanged.
+            pub fn commit(
+                self,
+                target: &mut #struct_name #ty_generics,
+            ) -> ::std::result::Result<(), ::std::vec::Vec<#error_ty>> {
+                self.check(target)?;
+                Self::__structible_apply(self.changes, target);
+                ::std::result::Result::Ok(())
+            }
+
+            #[doc(hidden)]
+            #[allow(unreachable_patterns)]
+            fn __structible_apply(
+                changes: ::std::vec::Vec<#change_ty>,
+                target: &mut #struct_name #ty_generics,
+            ) {
+                for change in changes {
+                    match change {
+                        #(#apply_arms)*
+                        _ => #unreachable,
+                    }
+                }
+            }
+        }
+
+        impl #impl_generics ::std::default::Default for #builder #ty_generics #where_clause {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl #impl_generics ::std::clone::Clone for #builder #ty_generics #clone_where {
+            fn clone(&self) -> Self {
+                Self { changes: ::std::clone::Clone::clone(&self.changes) }
+            }
+        }
+
+        impl #impl_generics ::std::fmt::Debug for #builder #ty_generics #debug_where {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.debug_struct(#builder_name_str)
+                    .field("changes", &self.changes)
+                    .finish()
+            }
+        }
+    }
+}
@@ -0,0 +1,339 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::{Generics, Ident};
+
+use crate::changes::{insert_expr, remove_expr};
+use crate::codegen::{field_enum_type, value_enum_name};
+use crate::instrument::trace_bounds;
+use crate::parse::{FieldInfo, StructibleConfig};
+use crate::ttl::expired_guard;
+use crate::util::{
+    extract_doc_comments, format_method_doc, to_pascal_case, type_mentions_type_param,
+};
+
+/// Returns the catch-all if it is declared with `promote_known`.
+fn promoting_catch_all(fields: &[FieldInfo]) -> Option<&FieldInfo> {
+    fields
+        .iter()
+        .find(|f| f.is_unknown_field() && f.config.promote_known)
+}
+
+/// Checks that a catch-all with `promote_known` can route its entries.
+pub fn check_promote_config(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+) -> syn::Result<()> {
+    if promoting_catch_all(fields).is_some() && config.is_async {
+        return Err(syn::Error::new_spanned(
+            struct_name,
+            "`promote_known` is not supported with `async`",
+        ));
+    }
+    Ok(())
+}
+
+/// Returns the where predicates needed to convert catch-all values into the known
+/// fields' types, if the catch-all is declared with `promote_known`.
+///
+/// Like elsewhere, only types mentioning the struct's type parameters are bounded.
+pub fn promote_bounds(fields: &[FieldInfo], generics: &Generics) -> TokenStream {
+    let Some(catch_all) = promoting_catch_all(fields) else {
+        return quote! {};
+    };
+    let serde = quote! { ::structible::__private::serde };
+    let type_param_idents: Vec<_> = generics.type_params().map(|tp| &tp.ident).collect();
+    let value_bound =
+        type_mentions_type_param(&catch_all.inner_ty, &type_param_idents).then(|| {
+            let ty = &catch_all.inner_ty;
+            quote! { #ty: #serde::Serialize, }
+        });
+    let field_bounds = fields
+        .iter()
+        .filter(|f| !f.is_unknown_field())
+        .map(|f| &f.inner_ty)
+        .filter(|ty| type_mentions_type_param(ty, &type_param_idents))
+        .map(|ty| quote! { #ty: #serde::de::DeserializeOwned, });
+    quote! { #value_bound #(#field_bounds)* }
+}
+
+/// Returns `where_clause` extended with the bounds of `promote_bounds`.
+pub fn with_promote_bounds(
+    where_clause: Option<&syn::WhereClause>,
+    fields: &[FieldInfo],
+    generics: &Generics,
+) -> TokenStream {
+    let bounds = promote_bounds(fields, generics);
+    match where_clause {
+        Some(wc) => {
+            let existing = &wc.predicates;
+            quote! { where #bounds #existing }
+        }
+        None if !bounds.is_empty() => quote! { where #bounds },
+        None => quote! {},
+    }
+}
+
+/// Returns the `Structible::route_entry` override of a struct whose catch-all is
+/// declared with `promote_known`.
+pub fn route_entry_method(fields: &[FieldInfo]) -> TokenStream {
+    if promoting_catch_all(fields).is_none() {
+        return quote! {};
+    }
+    quote! {
+        fn route_entry(key: Self::Field, value: Self::Value) -> (Self::Field, Self::Value) {
+            Self::__structible_route_entry(key, value)
+        }
+    }
+}
+
+/// Generate the entry routing function and the routing catch-all insert, if the
+/// catch-all is declared with `promote_known`.
+///
+/// A catch-all entry is routed to the known field its key names if its value
+/// converts to the field's type through serde; otherwise it stays in the
+/// catch-all. They live in their own impl block, bounded by `promote_bounds`.
+pub fn generate_promote_impl(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    let Some(catch_all) = promoting_catch_all(fields) else {
+        return quote! {};
+    };
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    // The routing insert writes through `insert_expr`, which may trace the value
+    let bounds = promote_bounds(fields, generics);
+    let trace_bounds = trace_bounds(fields, config, generics);
+    let where_clause = match where_clause {
+        Some(wc) => {
+            let existing = &wc.predicates;
+            quote! { where #bounds #trace_bounds #existing }
+        }
+        None if !bounds.is_empty() || !trace_bounds.is_empty() => {
+            quote! { where #bounds #trace_bounds }
+        }
+        None => quote! {},
+    };
+
+    let route_arms = fields.iter().filter(|f| !f.is_unknown_field()).map(|f| {
+        let name_str = f.name.unraw().to_string();
+        let variant = to_pascal_case(&f.name);
+        quote! {
+            #name_str => {
+                if let ::std::option::Option::Some(value) = ::structible::serde_adapter::__convert(value) {
+                    return (#field_enum::#variant, #value_enum::#variant(value));
+                }
+            }
+        }
+    });
+
+    let insert_method = if config.no_setters {
+        quote! {}
+    } else {
+        let name = &catch_all.name;
+        let insert_method = format_ident!("insert_{}", name);
+        let key_type = catch_all.unknown_key_type().unwrap();
+        let value_type = &catch_all.inner_ty;
+        let set_vis = catch_all.setter_vis();
+        let method_attrs = catch_all.method_attrs();
+        let field_docs = extract_doc_comments(&catch_all.attrs);
+        let insert_doc = format_method_doc(
+            &format!(
+                "Inserts an unknown `{}` field with the given key and value. Returns the previous value if the key was already present.\n\nIf the key names a known field and the value converts to its type, the known field is set instead, and `None` is returned.",
+                name.unraw()
+            ),
+            &field_docs,
+        );
+        let key = catch_all.normalized_key(quote! { key });
+        let insert = insert_expr(config, quote! { self }, quote! { key }, quote! { value });
+        quote! {
+            #insert_doc
+            #(#method_attrs)*
+            #set_vis fn #insert_method(&mut self, key: #key_type, value: #value_type) -> Option<#value_type> {
+                let (key, value) = Self::__structible_route_entry(
+                    #field_enum::Unknown(#key),
+                    #value_enum::Unknown(value),
+                );
+                match #insert {
+                    Some(#value_enum::Unknown(v)) => Some(v),
+                    _ => None,
+                }
+            }
+        }
+    };
+
+    quote! {
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            #[doc(hidden)]
+            fn __structible_route_entry(
+                key: #field_enum,
+                value: #value_enum #ty_generics,
+            ) -> (#field_enum, #value_enum #ty_generics) {
+                if let (#field_enum::Unknown(name), #value_enum::Unknown(value)) = (&key, &value) {
+                    let name: &str = ::std::borrow::Borrow::borrow(name);
+                    match name {
+                        #(#route_arms)*
+                        _ => {}
+                    }
+                }
+                (key, value)
+            }
+
+            #insert_method
+        }
+    }
+}
+
+/// Checks that `promotable` fields can be converted to and from the catch-all.
+pub fn check_promotable_config(fields: &[FieldInfo], config: &StructibleConfig) -> syn::Result<()> {
+    let has_catch_all = fields.iter().any(|f| f.is_unknown_field());
+    for field in fields.iter().filter(|f| f.config.promotable) {
+        let problem = if field.is_unknown_field() {
+            "`promotable` is not supported on unknown fields catch-all"
+        } else if !has_catch_all {
+            "`promotable` requires an unknown fields catch-all to convert from and to"
+        } else if field.is_lazy() {
+            "`promotable` is not supported on lazy fields"
+        } else if config.is_async {
+            "`promotable` is not supported with `async`"
+        } else if config.no_setters || config.no_removers {
+            "`promotable` moves values and cannot be combined with `no_setters` or `no_removers`"
+        } else {
+            continue;
+        };
+        return Err(syn::Error::new_spanned(&field.name, problem));
+    }
+    Ok(())
+}
+
+/// Generate `promote_<catch-all>_to_<field>()` for `promotable` fields, and
+/// `demote_<field>_to_<catch-all>()` for optional ones.
+///
+/// The value is converted with `TryInto` from a clone, so that it stays where it
+/// was if the conversion fails; on success it is moved through the usual write path.
+pub fn generate_promotion_methods(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+) -> Vec<TokenStream> {
+    let Some(catch_all) = fields.iter().find(|f| f.is_unknown_field()) else {
+        return Vec::new();
+    };
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+    let catch_all_name = &catch_all.name;
+    let key_type = catch_all.unknown_key_type().unwrap();
+    let value_type = &catch_all.inner_ty;
+
+    fields
+        .iter()
+        .filter(|f| f.config.promotable)
+        .map(|f| {
+            let name = &f.name;
+            let variant = to_pascal_case(name);
+            let ty = &f.inner_ty;
+            let vis = f.setter_vis();
+            let method_attrs = f.method_attrs();
+            let field_docs = extract_doc_comments(&f.attrs);
+
+            let promote_method =
+                format_ident!("promote_{}_to_{}", catch_all_name.unraw(), name.unraw(), span = name.span());
+            let promote_doc = format_method_doc(
+                &format!(
+                    "Moves the `{}` entry for the given key into the `{}` field, converting it with `TryInto`. Returns `Ok(false)` if the key is absent; if the conversion fails, the entry is left in place.",
+                    catch_all_name.unraw(),
+                    name.unraw()
+                ),
+                &field_docs,
+            );
+            let owned_key = catch_all.normalized_key(quote! { key.to_owned() });
+            let remove_entry = remove_expr(config, quote! { field_key });
+            let insert_field = insert_expr(
+                config,
+                quote! { self },
+                quote! { #field_enum::#variant },
+                quote! { #value_enum::#variant(converted) },
+            );
+            let promote = quote! {
+                #promote_doc
+                #(#method_attrs)*
+                #vis fn #promote_method<__Q>(
+                    &mut self,
+                    key: &__Q,
+                ) -> ::std::result::Result<bool, <#value_type as ::std::convert::TryInto<#ty>>::Error>
+                where
+                    #key_type: ::std::borrow::Borrow<__Q>,
+                    __Q: ::std::borrow::ToOwned<Owned = #key_type> + ::std::hash::Hash + ::std::cmp::Eq + ?Sized,
+                    #value_type: ::std::clone::Clone + ::std::convert::TryInto<#ty>,
+                {
+                    let field_key = #field_enum::Unknown(#owned_key);
+                    let converted: #ty = match ::structible::BackingMap::get(&self.inner, &field_key) {
+                        Some(#value_enum::Unknown(value)) => {
+                            ::std::convert::TryInto::try_into(::std::clone::Clone::clone(value))?
+                        }
+                        _ => return ::std::result::Result::Ok(false),
+                    };
+                    #remove_entry;
+                    #insert_field;
+                    ::std::result::Result::Ok(true)
+                }
+            };
+
+            if !f.is_optional {
+                return promote;
+            }
+            let demote_method =
+                format_ident!("demote_{}_to_{}", name.unraw(), catch_all_name.unraw());
+            let demote_doc = format_method_doc(
+                &format!(
+                    "Moves the `{}` field into the `{}` entry for the given key, converting it with `TryInto`. Returns `Ok(false)` if the field is absent; if the conversion fails, the field is left in place.",
+                    name.unraw(),
+                    catch_all_name.unraw()
+                ),
+                &field_docs,
+            );
+            let expired = expired_guard(
+                config,
+                quote! { #field_enum::#variant },
+                quote! { ::std::result::Result::Ok(false) },
+            );
+            let key = catch_all.normalized_key(quote! { key });
+            let remove_field = remove_expr(config, quote! { #field_enum::#variant });
+            let insert_entry = insert_expr(
+                config,
+                quote! { self },
+                quote! { #field_enum::Unknown(#key) },
+                quote! { #value_enum::Unknown(converted) },
+            );
+            quote! {
+                #promote
+
+                #demote_doc
+                #(#method_attrs)*
+                #vis fn #demote_method(
+                    &mut self,
+                    key: #key_type,
+                ) -> ::std::result::Result<bool, <#ty as ::std::convert::TryInto<#value_type>>::Error>
+                where
+                    #ty: ::std::clone::Clone + ::std::convert::TryInto<#value_type>,
+                {
+                    #expired
+                    let converted: #value_type = match ::structible::BackingMap::get(&self.inner, &#field_enum::#variant) {
+                        Some(#value_enum::#variant(value)) => {
+                            ::std::convert::TryInto::try_into(::std::clone::Clone::clone(value))?
+                        }
+                        _ => return ::std::result::Result::Ok(false),
+                    };
+                    #remove_field;
+                    #insert_entry;
+                    ::std::result::Result::Ok(true)
+                }
+            }
+        })
+        .collect()
+}
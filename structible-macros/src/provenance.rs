@@ -0,0 +1,155 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::Ident;
+use syn::ext::IdentExt;
+
+use crate::codegen::field_enum_type;
+use crate::parse::{FieldInfo, StructibleConfig};
+use crate::ttl::expired_guard;
+use crate::util::{extract_doc_comments, format_method_doc, to_pascal_case};
+
+/// Returns the type of the source map of a struct with `provenance`.
+fn source_map_type(struct_name: &Ident, config: &StructibleConfig) -> TokenStream {
+    let field_enum = field_enum_type(struct_name, config);
+    let map_type = config.backing.to_tokens();
+    quote! { #map_type<#field_enum, ::structible::Source> }
+}
+
+/// Returns the source map field declaration, if `provenance` is set.
+///
+/// The source map holds the source of each value written with a source. Values
+/// written without one have no entry.
+pub fn source_field(struct_name: &Ident, config: &StructibleConfig) -> TokenStream {
+    if !config.provenance {
+        return quote! {};
+    }
+    let source_map = source_map_type(struct_name, config);
+    quote! { sources: #source_map, }
+}
+
+/// Returns the initializer of an empty source map, if `provenance` is set.
+pub fn source_init(struct_name: &Ident, config: &StructibleConfig) -> TokenStream {
+    if !config.provenance {
+        return quote! {};
+    }
+    let field_enum = field_enum_type(struct_name, config);
+    let source_map = source_map_type(struct_name, config);
+    quote! {
+        sources: <#source_map as ::structible::BackingMap<#field_enum, ::structible::Source>>::new(),
+    }
+}
+
+/// Returns the initializer cloning the source map, if `provenance` is set.
+pub fn source_clone(config: &StructibleConfig) -> TokenStream {
+    if !config.provenance {
+        return quote! {};
+    }
+    quote! { sources: ::std::clone::Clone::clone(&self.sources), }
+}
+
+/// Returns a statement clearing the source of the owned `key` of `recv`, if
+/// `provenance` is set.
+///
+/// Every write clears the source of its key, so that a value never carries the
+/// source of the value it replaced; `set_<field>_with_source` stores its source
+/// after the write.
+pub fn clear_source_stmt(config: &StructibleConfig, recv: &TokenStream) -> TokenStream {
+    if !config.provenance {
+        return quote! {};
+    }
+    quote! {
+        ::structible::BackingMap::remove(&mut #recv.sources, &key);
+    }
+}
+
+/// Returns a statement tagging the owned `key` of `self` with `source`, if
+/// `provenance` is set.
+pub fn set_source_stmt(config: &StructibleConfig, source: TokenStream) -> TokenStream {
+    if !config.provenance {
+        return quote! {};
+    }
+    quote! {
+        ::structible::BackingMap::insert(
+            &mut self.sources,
+            ::std::clone::Clone::clone(&key),
+            #source,
+        );
+    }
+}
+
+/// Generate the source setters and getters, if `provenance` is set.
+pub fn generate_provenance_methods(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+) -> TokenStream {
+    if !config.provenance {
+        return quote! {};
+    }
+    let field_enum = field_enum_type(struct_name, config);
+
+    let field_methods = fields.iter().filter(|f| !f.is_unknown_field()).map(|f| {
+        let variant = to_pascal_case(&f.name);
+        let inner_ty = &f.inner_ty;
+        let setter_name = f.setter_name();
+        let source_setter_name = format_ident!("{}_with_source", setter_name);
+        let source_name = format_ident!("{}_source", f.getter_name());
+        let get_vis = f.getter_vis();
+        let set_vis = f.setter_vis();
+        let field_docs = extract_doc_comments(&f.attrs);
+        let method_attrs = f.method_attrs();
+        let name_str = f.name.unraw().to_string();
+        let expired = if f.is_optional {
+            expired_guard(
+                config,
+                quote! { #field_enum::#variant },
+                quote! { ::std::option::Option::None },
+            )
+        } else {
+            quote! {}
+        };
+
+        let source_setter_doc = format_method_doc(
+            &format!(
+                "Sets the `{}` value, tagged with the source that supplied it.",
+                name_str
+            ),
+            &field_docs,
+        );
+        let source_doc = format_method_doc(
+            &format!(
+                "Returns the source of the `{}` value, if it is present and was set with a source.",
+                name_str
+            ),
+            &field_docs,
+        );
+
+        let source_setter = if config.no_setters {
+            quote! {}
+        } else {
+            quote! {
+                #source_setter_doc
+                #(#method_attrs)*
+                #set_vis fn #source_setter_name(&mut self, value: #inner_ty, source: ::structible::Source) {
+                    self.#setter_name(value);
+                    ::structible::BackingMap::insert(&mut self.sources, #field_enum::#variant, source);
+                }
+            }
+        };
+
+        quote! {
+            #source_setter
+
+            #source_doc
+            #(#method_attrs)*
+            #get_vis fn #source_name(&self) -> ::std::option::Option<::structible::Source> {
+                #expired
+                ::structible::BackingMap::get(&self.sources, &#field_enum::#variant).copied()
+            }
+        }
+    });
+
+    quote! {
+        #(#field_methods)*
+    }
+}
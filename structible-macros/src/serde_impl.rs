@@ -0,0 +1,170 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{Generics, Ident};
+
+use crate::codegen::{field_enum_type, value_enum_name};
+use crate::parse::{FieldInfo, StructibleConfig};
+use crate::snapshot::generate_map_serialize_impl;
+use crate::util::{is_string_type, to_pascal_case};
+
+/// Checks that a struct with `serde` gets exactly one pair of serde impls, and that
+/// its catch-all keys round-trip as map keys.
+pub fn check_serde_config(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+) -> syn::Result<()> {
+    if !config.serde {
+        return Ok(());
+    }
+    let conflict = [
+        (
+            config.no_structible,
+            "cannot be combined with `no_structible`",
+        ),
+        (
+            config.test_serialize,
+            "already implements `Serialize`, so it cannot be combined with `test_serialize`",
+        ),
+    ];
+    if let Some((_, message)) = conflict.iter().find(|(conflicts, _)| *conflicts) {
+        return Err(syn::Error::new_spanned(
+            struct_name,
+            format!("`serde` {}", message),
+        ));
+    }
+    // Catch-all keys are decoded from the names in the map, which are strings
+    let key_type = fields.iter().find_map(|f| f.unknown_key_type());
+    if let Some(key_type) = key_type.filter(|ty| !is_string_type(ty)) {
+        return Err(syn::Error::new_spanned(
+            key_type,
+            "`serde` requires the catch-all key to be `String`, since it is decoded from the map's keys",
+        ));
+    }
+    Ok(())
+}
+
+/// Generate the `Serialize` and `Deserialize` impls, if `serde` is set.
+///
+/// The struct is encoded as a map of its present fields, as with `test_serialize`,
+/// except that catch-all entries are written next to them. Decoding leaves absent
+/// optional fields out of the backing map, stores entries of unknown names in the
+/// catch-all, and fails on unknown names without one, duplicate fields, and missing
+/// required fields.
+pub fn generate_serde_impls(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    if !config.serde {
+        return quote! {};
+    }
+    let serialize_impl = generate_map_serialize_impl(struct_name, fields, config, generics, true);
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+    let (_, ty_generics, where_clause) = generics.split_for_impl();
+    let serde = quote! { ::structible::__private::serde };
+
+    let names: Vec<_> = fields
+        .iter()
+        .filter(|f| !f.is_unknown_field())
+        .map(|f| f.name.unraw().to_string())
+        .collect();
+    let decode_arms = fields
+        .iter()
+        .filter(|f| !f.is_unknown_field())
+        .zip(&names)
+        .map(|(f, name_str)| {
+            let inner_ty = &f.inner_ty;
+            let variant = to_pascal_case(&f.name);
+            // Former names of the field decode into it too
+            let aliases = f.alias_names();
+            // An optional field given as `null` is absent, as with `Option` fields
+            // of derived impls
+            if f.is_optional {
+                quote! {
+                    #name_str #(| #aliases)* => {
+                        let value: ::std::option::Option<#inner_ty> =
+                            #serde::de::MapAccess::next_value(map)?;
+                        if let ::std::option::Option::Some(value) = value {
+                            insert(#field_enum::#variant, #value_enum::#variant(value))?;
+                        }
+                    }
+                }
+            } else {
+                quote! {
+                    #name_str #(| #aliases)* => {
+                        let value: #inner_ty = #serde::de::MapAccess::next_value(map)?;
+                        insert(#field_enum::#variant, #value_enum::#variant(value))?;
+                    }
+                }
+            }
+        });
+
+    // Other names are keys of the catch-all, like with `#[serde(flatten)]`
+    let fallback_arm = match fields.iter().find(|f| f.is_unknown_field()) {
+        Some(f) => {
+            let key_type = f.unknown_key_type().unwrap();
+            let inner_ty = &f.inner_ty;
+            quote! {
+                _ => {
+                    let key: #key_type = #serde::Deserialize::deserialize(
+                        #serde::de::IntoDeserializer::<__A::Error>::into_deserializer(name),
+                    )?;
+                    let value: #inner_ty = #serde::de::MapAccess::next_value(map)?;
+                    insert(#field_enum::Unknown(key), #value_enum::Unknown(value))?;
+                }
+            }
+        }
+        None => quote! { _ => return ::std::result::Result::Ok(false), },
+    };
+
+    // Every field and catch-all key type must be deserializable, which also keeps
+    // the bounds from being trivial for structs without type parameters
+    let field_types: Vec<_> = fields
+        .iter()
+        .map(|f| &f.inner_ty)
+        .chain(fields.iter().filter_map(|f| f.unknown_key_type()))
+        .collect();
+    let existing = where_clause.map(|wc| &wc.predicates);
+
+    let mut de_generics = generics.clone();
+    de_generics.params.insert(0, syn::parse_quote!('__de));
+    let (de_impl_generics, _, _) = de_generics.split_for_impl();
+
+    quote! {
+        #serialize_impl
+
+        impl #de_impl_generics ::structible::serde_adapter::__DecodeFields<'__de> for #struct_name #ty_generics
+        where
+            #(#field_types: #serde::Deserialize<'__de>,)*
+            #existing
+        {
+            const FIELDS: &'static [&'static str] = &[#(#names),*];
+
+            fn __decode_field<__A: #serde::de::MapAccess<'__de>>(
+                name: &str,
+                map: &mut __A,
+                insert: &mut impl FnMut(Self::Field, Self::Value) -> ::std::result::Result<(), __A::Error>,
+            ) -> ::std::result::Result<bool, __A::Error> {
+                match name {
+                    #(#decode_arms)*
+                    #fallback_arm
+                }
+                ::std::result::Result::Ok(true)
+            }
+        }
+
+        impl #de_impl_generics #serde::Deserialize<'__de> for #struct_name #ty_generics
+        where
+            #(#field_types: #serde::Deserialize<'__de>,)*
+            #existing
+        {
+            fn deserialize<__D: #serde::Deserializer<'__de>>(deserializer: __D) -> ::std::result::Result<Self, __D::Error> {
+                ::structible::serde_adapter::__deserialize_fields(deserializer)
+            }
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{Generics, Ident};
+
+use crate::codegen::{field_enum_type, value_enum_name};
+use crate::parse::{FieldInfo, StructibleConfig};
+use crate::util::{to_pascal_case, type_mentions_type_param};
+
+/// Generate `field_paths()`, listing the names of all fields in declaration order.
+pub fn generate_field_paths(fields: &[FieldInfo]) -> TokenStream {
+    let names = fields.iter().map(|f| f.name.unraw().to_string());
+    quote! {
+        /// Returns the names of all fields in declaration order, including the catch-all's.
+        ///
+        /// They match the keys written by `test_serialize`, e.g. for building snapshot
+        /// redaction selectors.
+        pub fn field_paths() -> &'static [&'static str] {
+            &[#(#names),*]
+        }
+    }
+}
+
+/// Generate `fields_in_order()`, yielding the present known fields in declaration order.
+///
+/// The static list of known fields is probed with `BackingMap::get()`, so the order
+/// doesn't depend on the backing, and the backing doesn't have to be iterable.
+pub fn generate_fields_in_order(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+    let (_, ty_generics, _) = generics.split_for_impl();
+
+    let known: Vec<_> = fields
+        .iter()
+        .filter(|f| !f.is_unknown_field())
+        .map(|f| {
+            let variant = to_pascal_case(&f.name);
+            quote! { #field_enum::#variant }
+        })
+        .collect();
+    let count = known.len();
+    // Expired values read as absent, so they aren't yielded either
+    let expired_check = if config.ttl {
+        quote! {
+            if self.__structible_expired(&key) {
+                return ::std::option::Option::None;
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    quote! {
+        /// Returns the present known fields and their values, in declaration order.
+        ///
+        /// The order doesn't depend on the backing, e.g. for deterministic output from
+        /// a `HashMap`-backed struct. Catch-all entries are left out.
+        pub fn fields_in_order(
+            &self,
+        ) -> impl ::std::iter::Iterator<Item = (#field_enum, &#value_enum #ty_generics)> + '_ {
+            let known: [#field_enum; #count] = [#(#known),*];
+            ::std::iter::IntoIterator::into_iter(known).filter_map(move |key| {
+                #expired_check
+                let value = ::structible::BackingMap::get(&self.inner, &key)?;
+                ::std::option::Option::Some((key, value))
+            })
+        }
+    }
+}
+
+/// Generate a `Serialize` impl only compiled in tests, if `test_serialize` is set.
+pub fn generate_test_serialize_impl(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    if !config.test_serialize {
+        return quote! {};
+    }
+    let serialize_impl = generate_map_serialize_impl(struct_name, fields, config, generics, false);
+    quote! {
+        #[cfg(test)]
+        #serialize_impl
+    }
+}
+
+/// Generate a `Serialize` impl writing the struct as a map of its present fields.
+///
+/// Catch-all entries are written next to the fields if `flatten_catch_all` is set,
+/// and otherwise nested as a map under the catch-all's name.
+pub fn generate_map_serialize_impl(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    generics: &Generics,
+    flatten_catch_all: bool,
+) -> TokenStream {
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let serde = quote! { ::structible::__private::serde };
+
+    let known_entries = fields.iter().filter(|f| !f.is_unknown_field()).map(|f| {
+        let name_str = f.name.unraw().to_string();
+        let variant = to_pascal_case(&f.name);
+        // Expired values read as absent, so they aren't written either
+        let expired_check = if config.ttl && f.is_optional {
+            quote! { if !self.__structible_expired(&#field_enum::#variant) }
+        } else {
+            quote! {}
+        };
+        quote! {
+            if let Some(#value_enum::#variant(value)) = ::structible::BackingMap::get(&self.inner, &#field_enum::#variant) {
+                #expired_check {
+                    #serde::ser::SerializeMap::serialize_entry(&mut map, #name_str, value)?;
+                }
+            }
+        }
+    });
+
+    let unknown_entries = fields.iter().find(|f| f.is_unknown_field()).map(|f| {
+        if flatten_catch_all {
+            // A key naming a field would be written twice, and decode into the field
+            let names: Vec<_> = fields
+                .iter()
+                .filter(|f| !f.is_unknown_field())
+                .flat_map(|f| std::iter::once(f.name.unraw().to_string()).chain(f.alias_names()))
+                .collect();
+            let collision_check = if names.is_empty() {
+                quote! {}
+            } else {
+                quote! {
+                    if ::std::matches!(key.as_str(), #(#names)|*) {
+                        return ::std::result::Result::Err(#serde::ser::Error::custom(
+                            ::std::format_args!("catch-all key `{}` collides with a field", key),
+                        ));
+                    }
+                }
+            };
+            return quote! {
+                for (k, v) in ::structible::IterableMap::iter(&self.inner) {
+                    if let (#field_enum::Unknown(key), #value_enum::Unknown(value)) = (k, v) {
+                        #collision_check
+                        #serde::ser::SerializeMap::serialize_entry(&mut map, key, value)?;
+                    }
+                }
+            };
+        }
+        let name_str = f.name.unraw().to_string();
+        quote! {
+            let unknown: ::std::vec::Vec<_> = ::structible::IterableMap::iter(&self.inner)
+                .filter_map(|(k, v)| match (k, v) {
+                    (#field_enum::Unknown(key), #value_enum::Unknown(value)) => Some((key, value)),
+                    _ => None,
+                })
+                .collect();
+            if !unknown.is_empty() {
+                #serde::ser::SerializeMap::serialize_entry(
+                    &mut map,
+                    #name_str,
+                    &::structible::serde_adapter::__Entries(&unknown),
+                )?;
+            }
+        }
+    });
+
+    // Generic field and catch-all key types must be serializable
+    let type_param_idents: Vec<_> = generics.type_params().map(|tp| &tp.ident).collect();
+    let bounded_types: Vec<_> = fields
+        .iter()
+        .map(|f| &f.inner_ty)
+        .chain(fields.iter().filter_map(|f| f.unknown_key_type()))
+        .filter(|ty| type_mentions_type_param(ty, &type_param_idents))
+        .collect();
+    let bounds = quote! { #(#bounded_types: #serde::Serialize,)* };
+    let where_clause = if let Some(wc) = where_clause {
+        let existing = &wc.predicates;
+        quote! { where #bounds #existing }
+    } else if !bounded_types.is_empty() {
+        quote! { where #bounds }
+    } else {
+        quote! {}
+    };
+
+    quote! {
+        impl #impl_generics #serde::Serialize for #struct_name #ty_generics #where_clause {
+            fn serialize<__S: #serde::Serializer>(&self, serializer: __S) -> ::std::result::Result<__S::Ok, __S::Error> {
+                let mut map = #serde::Serializer::serialize_map(serializer, None)?;
+                #(#known_entries)*
+                #unknown_entries
+                #serde::ser::SerializeMap::end(map)
+            }
+        }
+    }
+}
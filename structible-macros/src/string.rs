@@ -0,0 +1,130 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::Ident;
+use syn::ext::IdentExt;
+
+use crate::codegen::{field_enum_type, value_enum_name, value_or_default_mut};
+use crate::instrument::read_counter;
+use crate::parse::{FieldInfo, StructibleConfig};
+use crate::util::{is_string_type, to_pascal_case};
+
+/// Checks that `string` fields store a `String` that can be modified in place.
+pub fn check_string_config(fields: &[FieldInfo], config: &StructibleConfig) -> syn::Result<()> {
+    for f in fields.iter().filter(|f| f.config.string) {
+        if f.is_unknown_field() || f.is_lazy() || f.is_weak() || f.is_transparent() {
+            return Err(syn::Error::new_spanned(
+                &f.name,
+                "`string` is not supported on the unknown fields catch-all, lazy, weak, or transparent fields",
+            ));
+        }
+        if !is_string_type(&f.inner_ty) {
+            return Err(syn::Error::new_spanned(
+                &f.inner_ty,
+                "`string` requires a `String` or `Option<String>` field",
+            ));
+        }
+        if config.no_setters {
+            return Err(syn::Error::new_spanned(
+                &f.name,
+                "`string` has no effect with `no_setters`",
+            ));
+        }
+        if config.is_async {
+            return Err(syn::Error::new_spanned(
+                &f.name,
+                "`string` is not supported with `async`",
+            ));
+        }
+        // Like mutable getters, the helpers modify the stored string in place
+        if config.records_writes() {
+            return Err(syn::Error::new_spanned(
+                &f.name,
+                "`string` is not supported with `track_changes`, `lww`, or `transactional`, which can't record writes in place",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Generate `append_<field>()` and `clear_<field>()` for fields declaring
+/// `string`, and `<setter>_str()` if they are optional.
+///
+/// `append_<field>()` creates an absent optional string first, while
+/// `clear_<field>()` leaves it absent rather than storing an empty one.
+pub fn generate_string_methods(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+) -> Vec<TokenStream> {
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+
+    fields
+        .iter()
+        .filter(|f| f.config.string)
+        .map(|f| {
+            let name = &f.name;
+            let variant = to_pascal_case(name);
+            let append_name = format_ident!("append_{}", name);
+            let clear_name = format_ident!("clear_{}", name);
+            let vis = f.setter_vis();
+            let method_attrs = f.method_attrs();
+            let count = read_counter(config, name);
+            let value_mut = value_or_default_mut(struct_name, f, config);
+            let name_str = name.unraw().to_string();
+
+            let mut append_doc = format!("Appends a string slice to the `{}` value.", name_str);
+            let mut clear_doc = format!("Truncates the `{}` value to an empty string.", name_str);
+            if f.is_optional {
+                append_doc.push_str("\n\nAn absent value is treated as an empty string.");
+                clear_doc.push_str("\n\nAn absent value is left absent.");
+            }
+            let clear = if f.is_optional {
+                quote! {
+                    if let Some(#value_enum::#variant(v)) =
+                        ::structible::BackingMap::get_mut(&mut self.inner, &#field_enum::#variant)
+                    {
+                        v.clear();
+                    }
+                }
+            } else {
+                quote! { #value_mut.clear(); }
+            };
+            let set_str = if f.is_optional {
+                let setter = f.setter_name();
+                let set_str_name = format_ident!("{}_str", setter);
+                let set_str_doc = format!(
+                    "Sets the `{}` value to an owned copy of a string slice.",
+                    name_str
+                );
+                quote! {
+                    #[doc = #set_str_doc]
+                    #(#method_attrs)*
+                    #vis fn #set_str_name(&mut self, value: &str) {
+                        self.#setter(::std::borrow::ToOwned::to_owned(value));
+                    }
+                }
+            } else {
+                quote! {}
+            };
+
+            quote! {
+                #[doc = #append_doc]
+                #(#method_attrs)*
+                #vis fn #append_name(&mut self, value: &str) {
+                    #count
+                    #value_mut.push_str(value);
+                }
+
+                #[doc = #clear_doc]
+                #(#method_attrs)*
+                #vis fn #clear_name(&mut self) {
+                    #count
+                    #clear
+                }
+
+                #set_str
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,122 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::Ident;
+use syn::ext::IdentExt;
+
+use crate::codegen::field_enum_type;
+use crate::parse::{FieldInfo, StructibleConfig};
+use crate::util::{extract_doc_comments, format_method_doc, to_pascal_case};
+
+/// Checks that a `clock` is only given to a struct with `timestamps`.
+pub fn check_timestamps_config(config: &StructibleConfig) -> syn::Result<()> {
+    match &config.clock {
+        Some(clock) if !config.timestamps => Err(syn::Error::new_spanned(
+            clock,
+            "`clock` requires `timestamps`",
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Returns the timestamp type of a struct with `timestamps`.
+fn instant_type(config: &StructibleConfig) -> TokenStream {
+    match &config.clock {
+        Some(clock) => quote! { <#clock as ::structible::Clock>::Instant },
+        None => quote! { <::structible::SystemClock as ::structible::Clock>::Instant },
+    }
+}
+
+/// Returns the type of the timestamp map of a struct with `timestamps`.
+fn timestamp_map_type(struct_name: &Ident, config: &StructibleConfig) -> TokenStream {
+    let field_enum = field_enum_type(struct_name, config);
+    let map_type = config.backing.to_tokens();
+    let instant = instant_type(config);
+    quote! { #map_type<#field_enum, #instant> }
+}
+
+/// Returns the timestamp map field declaration, if `timestamps` is set.
+///
+/// The timestamp map holds the time of the last write to each field that has
+/// been written since the struct was created.
+pub fn timestamp_field(struct_name: &Ident, config: &StructibleConfig) -> TokenStream {
+    if !config.timestamps {
+        return quote! {};
+    }
+    let timestamp_map = timestamp_map_type(struct_name, config);
+    quote! { modified: #timestamp_map, }
+}
+
+/// Returns the initializer of an empty timestamp map, if `timestamps` is set.
+pub fn timestamp_init(struct_name: &Ident, config: &StructibleConfig) -> TokenStream {
+    if !config.timestamps {
+        return quote! {};
+    }
+    let field_enum = field_enum_type(struct_name, config);
+    let timestamp_map = timestamp_map_type(struct_name, config);
+    let instant = instant_type(config);
+    quote! {
+        modified: <#timestamp_map as ::structible::BackingMap<#field_enum, #instant>>::new(),
+    }
+}
+
+/// Returns the initializer cloning the timestamp map, if `timestamps` is set.
+pub fn timestamp_clone(config: &StructibleConfig) -> TokenStream {
+    if !config.timestamps {
+        return quote! {};
+    }
+    quote! { modified: ::std::clone::Clone::clone(&self.modified), }
+}
+
+/// Returns a statement recording the current time as the last write to the owned
+/// `key` of `recv`, if `timestamps` is set.
+pub fn touch_stmt(config: &StructibleConfig, recv: &TokenStream) -> TokenStream {
+    if !config.timestamps {
+        return quote! {};
+    }
+    let now = match &config.clock {
+        Some(clock) => quote! { <#clock as ::structible::Clock>::now() },
+        None => quote! { <::structible::SystemClock as ::structible::Clock>::now() },
+    };
+    quote! {
+        ::structible::BackingMap::insert(&mut #recv.modified, ::std::clone::Clone::clone(&key), #now);
+    }
+}
+
+/// Generate the `<field>_modified_at()` getters, if `timestamps` is set.
+pub fn generate_timestamp_methods(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+) -> TokenStream {
+    if !config.timestamps {
+        return quote! {};
+    }
+    let field_enum = field_enum_type(struct_name, config);
+    let instant = instant_type(config);
+
+    let getters = fields.iter().filter(|f| !f.is_unknown_field()).map(|f| {
+        let variant = to_pascal_case(&f.name);
+        let getter_name = format_ident!("{}_modified_at", f.getter_name());
+        let vis = f.getter_vis();
+        let field_docs = extract_doc_comments(&f.attrs);
+        let method_attrs = f.method_attrs();
+        let doc_attr = format_method_doc(
+            &format!(
+                "Returns the time of the last write to the `{}` field, including removals.",
+                f.name.unraw()
+            ),
+            &field_docs,
+        );
+        quote! {
+            #doc_attr
+            #(#method_attrs)*
+            #vis fn #getter_name(&self) -> ::std::option::Option<#instant> {
+                ::structible::BackingMap::get(&self.modified, &#field_enum::#variant).copied()
+            }
+        }
+    });
+
+    quote! {
+        #(#getters)*
+    }
+}
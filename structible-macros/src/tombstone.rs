@@ -0,0 +1,150 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::Ident;
+use syn::ext::IdentExt;
+
+use crate::codegen::field_enum_type;
+use crate::parse::{FieldInfo, StructibleConfig};
+use crate::util::{extract_doc_comments, format_method_doc, to_pascal_case};
+
+/// Returns the fields that can be cleared, i.e. the optional fields other than the catch-all.
+fn clearable_fields(fields: &[FieldInfo]) -> impl Iterator<Item = &FieldInfo> {
+    fields
+        .iter()
+        .filter(|f| f.is_optional && !f.is_unknown_field())
+}
+
+/// Returns the type of the tombstone map of a struct with `tombstones`.
+fn tombstone_map_type(struct_name: &Ident, config: &StructibleConfig) -> TokenStream {
+    let field_enum = field_enum_type(struct_name, config);
+    let map_type = config.backing.to_tokens();
+    quote! { #map_type<#field_enum, ()> }
+}
+
+/// Returns the tombstone map field declaration, if `tombstones` is set.
+///
+/// The tombstone map holds a marker for each optional field cleared through its
+/// remover and not set since.
+pub fn tombstone_field(struct_name: &Ident, config: &StructibleConfig) -> TokenStream {
+    if !config.tombstones {
+        return quote! {};
+    }
+    let tombstone_map = tombstone_map_type(struct_name, config);
+    quote! { tombstones: #tombstone_map, }
+}
+
+/// Returns the initializer of an empty tombstone map, if `tombstones` is set.
+pub fn tombstone_init(struct_name: &Ident, config: &StructibleConfig) -> TokenStream {
+    if !config.tombstones {
+        return quote! {};
+    }
+    let field_enum = field_enum_type(struct_name, config);
+    let tombstone_map = tombstone_map_type(struct_name, config);
+    quote! {
+        tombstones: <#tombstone_map as ::structible::BackingMap<#field_enum, ()>>::new(),
+    }
+}
+
+/// Returns the initializer cloning the tombstone map, if `tombstones` is set.
+pub fn tombstone_clone(config: &StructibleConfig) -> TokenStream {
+    if !config.tombstones {
+        return quote! {};
+    }
+    quote! { tombstones: ::std::clone::Clone::clone(&self.tombstones), }
+}
+
+/// Returns a statement clearing the tombstone of the owned `key` of `recv`, if
+/// `tombstones` is set.
+///
+/// Every write clears the tombstone of its key, so that a field set again no
+/// longer reads as cleared.
+pub fn clear_tombstone_stmt(config: &StructibleConfig, recv: &TokenStream) -> TokenStream {
+    if !config.tombstones {
+        return quote! {};
+    }
+    quote! {
+        ::structible::BackingMap::remove(&mut #recv.tombstones, &key);
+    }
+}
+
+/// Returns a statement recording a tombstone for `key`, if `tombstones` is set.
+///
+/// Only the removers of optional fields record tombstones, whether or not the
+/// field was present; other removals, such as expiries, leave none.
+pub fn bury_stmt(config: &StructibleConfig, key: TokenStream) -> TokenStream {
+    if !config.tombstones {
+        return quote! {};
+    }
+    quote! {
+        ::structible::BackingMap::insert(&mut self.tombstones, #key, ());
+    }
+}
+
+/// Generate the `was_explicitly_cleared_*` queries, `tombstones()`, and
+/// `purge_tombstones()`, if `tombstones` is set.
+pub fn generate_tombstone_methods(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+) -> TokenStream {
+    if !config.tombstones {
+        return quote! {};
+    }
+    let field_enum = field_enum_type(struct_name, config);
+
+    let queries = clearable_fields(fields).map(|f| {
+        let variant = to_pascal_case(&f.name);
+        let query_name = format_ident!("was_explicitly_cleared_{}", f.getter_name());
+        let vis = f.getter_vis();
+        let field_docs = extract_doc_comments(&f.attrs);
+        let method_attrs = f.method_attrs();
+        let doc_attr = format_method_doc(
+            &format!(
+                "Returns true if the `{}` field was cleared through its remover and not set since.",
+                f.name.unraw()
+            ),
+            &field_docs,
+        );
+        quote! {
+            #doc_attr
+            #(#method_attrs)*
+            #vis fn #query_name(&self) -> bool {
+                ::structible::BackingMap::get(&self.tombstones, &#field_enum::#variant).is_some()
+            }
+        }
+    });
+
+    let keys: Vec<_> = clearable_fields(fields)
+        .map(|f| {
+            let variant = to_pascal_case(&f.name);
+            quote! { #field_enum::#variant }
+        })
+        .collect();
+    let count = keys.len();
+
+    quote! {
+        #(#queries)*
+
+        /// Returns the fields cleared through their removers and not set since, in
+        /// declaration order.
+        pub fn tombstones(&self) -> ::std::vec::Vec<#field_enum> {
+            let keys: [#field_enum; #count] = [#(#keys),*];
+            keys.into_iter()
+                .filter(|key| ::structible::BackingMap::get(&self.tombstones, key).is_some())
+                .collect()
+        }
+
+        /// Forgets every tombstone, e.g. once the deletions have been propagated, and
+        /// returns how many were forgotten.
+        pub fn purge_tombstones(&mut self) -> usize {
+            let keys: [#field_enum; #count] = [#(#keys),*];
+            let mut purged = 0;
+            for key in keys {
+                if ::structible::BackingMap::remove(&mut self.tombstones, &key).is_some() {
+                    purged += 1;
+                }
+            }
+            purged
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Generics, Ident};
+
+use crate::codegen::{field_enum_type, value_enum_name};
+use crate::parse::StructibleConfig;
+
+/// Checks that the writes of a `transactional` struct can be undone.
+pub fn check_transaction_config(struct_name: &Ident, config: &StructibleConfig) -> syn::Result<()> {
+    if !config.transactional {
+        return Ok(());
+    }
+    let conflict = if config.no_clone {
+        Some("`transactional` keeps cloned values and cannot be combined with `no_clone`")
+    } else if config.lww {
+        Some("`transactional` cannot undo `lww` stamps and cannot be combined with `lww`")
+    } else if config.ttl {
+        Some("`transactional` cannot undo `ttl` expiries and cannot be combined with `ttl`")
+    } else if config.tombstones {
+        Some("`transactional` cannot undo tombstones and cannot be combined with `tombstones`")
+    } else if config.provenance {
+        Some("`transactional` cannot undo sources and cannot be combined with `provenance`")
+    } else if config.timestamps {
+        Some("`transactional` cannot undo timestamps and cannot be combined with `timestamps`")
+    } else {
+        None
+    };
+    match conflict {
+        Some(message) => Err(syn::Error::new_spanned(struct_name, message)),
+        None => Ok(()),
+    }
+}
+
+/// Returns the undo log field declaration, if `transactional` is set.
+///
+/// The undo log is `Some` while a transaction runs, and holds the previous entry of
+/// every key written since it started, in order.
+pub fn undo_log_field(
+    struct_name: &Ident,
+    config: &StructibleConfig,
+    generics: &Generics,
+) -> TokenStream {
+    if !config.transactional {
+        return quote! {};
+    }
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+    let (_, ty_generics, _) = generics.split_for_impl();
+    quote! {
+        undo: ::std::option::Option<
+            ::std::vec::Vec<(#field_enum, ::std::option::Option<#value_enum #ty_generics>)>,
+        >,
+    }
+}
+
+/// Returns the initializer of an inactive undo log, if `transactional` is set.
+///
+/// New structs and clones are never inside a transaction.
+pub fn undo_log_init(config: &StructibleConfig) -> TokenStream {
+    if !config.transactional {
+        return quote! {};
+    }
+    quote! { undo: ::std::option::Option::None, }
+}
+
+/// Returns a statement saving the current entry of the owned `key` of `recv` to the
+/// undo log, if `transactional` is set and a transaction is running.
+pub fn undo_stmt(config: &StructibleConfig, recv: &TokenStream) -> TokenStream {
+    if !config.transactional {
+        return quote! {};
+    }
+    quote! {
+        if let ::std::option::Option::Some(undo) = &mut #recv.undo {
+            let previous = ::structible::BackingMap::get(&#recv.inner, &key);
+            undo.push((
+                ::std::clone::Clone::clone(&key),
+                ::std::option::Option::map(previous, ::std::clone::Clone::clone),
+            ));
+        }
+    }
+}
+
+/// Generate `transaction()`, if `transactional` is set.
+pub fn generate_transaction_methods(config: &StructibleConfig) -> TokenStream {
+    if !config.transactional {
+        return quote! {};
+    }
+    // The change log is rolled back along with the values
+    let (changes_start, changes_rollback) = if config.track_changes {
+        (
+            quote! { let changes_start = self.changes.len(); },
+            quote! { self.changes.truncate(changes_start); },
+        )
+    } else {
+        (quote! {}, quote! {})
+    };
+
+    quote! {
+        /// Runs `f` on the struct as a transaction, undoing every write it made if it
+        /// returns an error.
+        ///
+        /// Transactions may be nested, in which case an inner transaction that fails
+        /// only undoes its own writes. Writes are not undone if `f` panics.
+        pub fn transaction<__R, __E>(
+            &mut self,
+            f: impl ::std::ops::FnOnce(&mut Self) -> ::std::result::Result<__R, __E>,
+        ) -> ::std::result::Result<__R, __E> {
+            let outermost = self.undo.is_none();
+            let start = self.undo.get_or_insert_with(::std::vec::Vec::new).len();
+            #changes_start
+            let result = f(self);
+            if result.is_err() {
+                self.__structible_rollback(start);
+                #changes_rollback
+            }
+            if outermost {
+                self.undo = ::std::option::Option::None;
+            }
+            result
+        }
+
+        /// Restores the entries saved to the undo log since it had `len` entries,
+        /// latest first.
+        #[doc(hidden)]
+        fn __structible_rollback(&mut self, len: usize) {
+            let ::std::option::Option::Some(undo) = &mut self.undo else {
+                return;
+            };
+            for (key, previous) in undo.split_off(len).into_iter().rev() {
+                match previous {
+                    ::std::option::Option::Some(value) => {
+                        ::structible::BackingMap::insert(&mut self.inner, key, value);
+                    }
+                    ::std::option::Option::None => {
+                        ::structible::BackingMap::remove(&mut self.inner, &key);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::Ident;
+use syn::ext::IdentExt;
+
+use crate::changes::remove_expr;
+use crate::codegen::field_enum_type;
+use crate::parse::{FieldInfo, StructibleConfig};
+use crate::util::{extract_doc_comments, format_method_doc, to_pascal_case};
+
+/// Checks that the fields of a struct with `ttl` can expire.
+pub fn check_ttl_config(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+) -> syn::Result<()> {
+    if !config.ttl {
+        return Ok(());
+    }
+    if config.lww {
+        return Err(syn::Error::new_spanned(
+            struct_name,
+            "`ttl` expiries are local to a replica and cannot be combined with `lww`",
+        ));
+    }
+    if let Some(loader) = fields.iter().find_map(|f| f.config.lazy.as_ref()) {
+        return Err(syn::Error::new_spanned(
+            loader,
+            "lazy fields are not supported with `ttl`",
+        ));
+    }
+    Ok(())
+}
+
+/// Returns the fields that can expire, i.e. the optional fields other than the catch-all.
+fn expiring_fields(fields: &[FieldInfo]) -> impl Iterator<Item = &FieldInfo> {
+    fields
+        .iter()
+        .filter(|f| f.is_optional && !f.is_unknown_field())
+}
+
+/// Returns the type of the expiry map of a struct with `ttl`.
+fn expiry_map_type(struct_name: &Ident, config: &StructibleConfig) -> TokenStream {
+    let field_enum = field_enum_type(struct_name, config);
+    let map_type = config.backing.to_tokens();
+    quote! { #map_type<#field_enum, ::std::time::Instant> }
+}
+
+/// Returns the expiry map field declaration, if `ttl` is set.
+///
+/// The expiry map holds the instant at which each field set with a TTL expires.
+/// Fields without an entry never expire.
+pub fn expiry_field(struct_name: &Ident, config: &StructibleConfig) -> TokenStream {
+    if !config.ttl {
+        return quote! {};
+    }
+    let expiry_map = expiry_map_type(struct_name, config);
+    quote! { expiries: #expiry_map, }
+}
+
+/// Returns the initializer of an empty expiry map, if `ttl` is set.
+pub fn expiry_init(struct_name: &Ident, config: &StructibleConfig) -> TokenStream {
+    if !config.ttl {
+        return quote! {};
+    }
+    let field_enum = field_enum_type(struct_name, config);
+    let expiry_map = expiry_map_type(struct_name, config);
+    quote! {
+        expiries: <#expiry_map as ::structible::BackingMap<#field_enum, ::std::time::Instant>>::new(),
+    }
+}
+
+/// Returns the initializer cloning the expiry map, if `ttl` is set.
+pub fn expiry_clone(config: &StructibleConfig) -> TokenStream {
+    if !config.ttl {
+        return quote! {};
+    }
+    quote! { expiries: ::std::clone::Clone::clone(&self.expiries), }
+}
+
+/// Returns a statement returning `ret` if the field `key` has expired, if `ttl` is set.
+pub fn expired_guard(config: &StructibleConfig, key: TokenStream, ret: TokenStream) -> TokenStream {
+    if !config.ttl {
+        return quote! {};
+    }
+    quote! {
+        if self.__structible_expired(&#key) {
+            return #ret;
+        }
+    }
+}
+
+/// Returns a statement clearing the expiry of the owned `key` of `recv`, if `ttl` is set.
+///
+/// Every write clears the expiry of its key, so that a value set without a TTL
+/// never expires; `set_<field>_with_ttl` stores its expiry after the write.
+pub fn clear_expiry_stmt(config: &StructibleConfig, recv: &TokenStream) -> TokenStream {
+    if !config.ttl {
+        return quote! {};
+    }
+    quote! {
+        ::structible::BackingMap::remove(&mut #recv.expiries, &key);
+    }
+}
+
+/// Generate the TTL setters, the expiry getters, and `purge_expired()`, if `ttl` is set.
+pub fn generate_ttl_methods(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+) -> TokenStream {
+    if !config.ttl {
+        return quote! {};
+    }
+    let field_enum = field_enum_type(struct_name, config);
+
+    let field_methods = expiring_fields(fields).map(|f| {
+        let variant = to_pascal_case(&f.name);
+        let inner_ty = &f.inner_ty;
+        let setter_name = f.setter_name();
+        let ttl_setter_name = format_ident!("{}_with_ttl", setter_name);
+        let expires_at_name = format_ident!("{}_expires_at", f.getter_name());
+        let get_vis = f.getter_vis();
+        let set_vis = f.setter_vis();
+        let field_docs = extract_doc_comments(&f.attrs);
+        let method_attrs = f.method_attrs();
+        let name_str = f.name.unraw().to_string();
+
+        let ttl_setter_doc = format_method_doc(
+            &format!(
+                "Sets the `{}` value, which expires once `ttl` has elapsed.",
+                name_str
+            ),
+            &field_docs,
+        );
+        let expires_at_doc = format_method_doc(
+            &format!(
+                "Returns the instant at which the `{}` value expires, if it is present and set with a TTL.",
+                name_str
+            ),
+            &field_docs,
+        );
+
+        let ttl_setter = if config.no_setters {
+            quote! {}
+        } else {
+            quote! {
+                #ttl_setter_doc
+                #(#method_attrs)*
+                #set_vis fn #ttl_setter_name(&mut self, value: #inner_ty, ttl: ::std::time::Duration) {
+                    self.#setter_name(value);
+                    // A TTL too long to represent, like `Duration::MAX`, never expires
+                    if let ::std::option::Option::Some(at) = ::std::time::Instant::now().checked_add(ttl) {
+                        ::structible::BackingMap::insert(&mut self.expiries, #field_enum::#variant, at);
+                    }
+                }
+            }
+        };
+
+        quote! {
+            #ttl_setter
+
+            #expires_at_doc
+            #(#method_attrs)*
+            #get_vis fn #expires_at_name(&self) -> ::std::option::Option<::std::time::Instant> {
+                ::structible::BackingMap::get(&self.expiries, &#field_enum::#variant).copied()
+            }
+        }
+    });
+
+    let keys: Vec<_> = expiring_fields(fields)
+        .map(|f| {
+            let variant = to_pascal_case(&f.name);
+            quote! { #field_enum::#variant }
+        })
+        .collect();
+    let count = keys.len();
+    let remove = remove_expr(config, quote! { key });
+
+    quote! {
+        #[doc(hidden)]
+        fn __structible_expired(&self, key: &#field_enum) -> bool {
+            ::std::matches!(
+                ::structible::BackingMap::get(&self.expiries, key),
+                ::std::option::Option::Some(at) if *at <= ::std::time::Instant::now()
+            )
+        }
+
+        #[doc(hidden)]
+        fn __structible_expired_count(&self) -> usize {
+            let keys: [#field_enum; #count] = [#(#keys),*];
+            keys.iter()
+                .filter(|key| {
+                    self.__structible_expired(key)
+                        && ::structible::BackingMap::get(&self.inner, key).is_some()
+                })
+                .count()
+        }
+
+        #(#field_methods)*
+
+        /// Removes the fields whose TTL has elapsed and returns how many were removed.
+        pub fn purge_expired(&mut self) -> usize {
+            let keys: [#field_enum; #count] = [#(#keys),*];
+            let mut purged = 0;
+            for key in keys {
+                if self.__structible_expired(&key) {
+                    let removed = #remove;
+                    if removed.is_some() {
+                        purged += 1;
+                    }
+                }
+            }
+            purged
+        }
+    }
+}
+
+/// Returns the body of `PartialEq::eq` for a struct with `ttl`, which compares the
+/// fields as its getters see them, i.e. with expired values treated as absent.
+///
+/// Fields that skip equality aren't compared, as with `eq_body`.
+pub fn ttl_eq_body(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+) -> TokenStream {
+    let field_enum = field_enum_type(struct_name, config);
+    let keys: Vec<_> = fields
+        .iter()
+        .filter(|f| !f.is_unknown_field() && !f.config.skip_eq)
+        .map(|f| {
+            let variant = to_pascal_case(&f.name);
+            quote! { #field_enum::#variant }
+        })
+        .collect();
+    let count = keys.len();
+    // Catch-all entries never expire, so they are compared as they are stored
+    let unknown_check = fields
+        .iter()
+        .find(|f| f.is_unknown_field() && !f.config.skip_eq)
+        .map(|_| {
+            quote! {
+                let mut count = 0usize;
+                for (key, value) in ::structible::IterableMap::iter(&self.inner) {
+                    if ::std::matches!(key, #field_enum::Unknown(_)) {
+                        if ::structible::BackingMap::get(&other.inner, key) != ::std::option::Option::Some(value) {
+                            return false;
+                        }
+                        count += 1;
+                    }
+                }
+                let other_count = ::structible::IterableMap::iter(&other.inner)
+                    .filter(|(key, _)| ::std::matches!(key, #field_enum::Unknown(_)))
+                    .count();
+                if count != other_count {
+                    return false;
+                }
+            }
+        });
+    quote! {
+        let keys: [#field_enum; #count] = [#(#keys),*];
+        for key in &keys {
+            let mine = if self.__structible_expired(key) {
+                ::std::option::Option::None
+            } else {
+                ::structible::BackingMap::get(&self.inner, key)
+            };
+            let theirs = if other.__structible_expired(key) {
+                ::std::option::Option::None
+            } else {
+                ::structible::BackingMap::get(&other.inner, key)
+            };
+            if mine != theirs {
+                return false;
+            }
+        }
+        #unknown_check
+        true
+    }
+}
@@ -1,7 +1,12 @@
 use proc_macro2::TokenStream;
 use quote::quote;
-use syn::{Attribute, GenericArgument, Ident, PathArguments, Type};
+use syn::punctuated::Punctuated;
 use syn::visit::Visit;
+use syn::visit_mut::VisitMut;
+use syn::{
+    Attribute, GenericArgument, GenericParam, Generics, Ident, ItemStruct, Meta, PathArguments,
+    Token, Type, TypeTraitObject,
+};
 
 /// Extracts doc comment strings from a list of attributes.
 ///
@@ -12,18 +17,26 @@ pub fn extract_doc_comments(attrs: &[Attribute]) -> Vec<String> {
         .iter()
         .filter(|attr| attr.path().is_ident("doc"))
         .filter_map(|attr| {
-            if let syn::Meta::NameValue(meta) = &attr.meta {
-                if let syn::Expr::Lit(expr_lit) = &meta.value {
-                    if let syn::Lit::Str(lit_str) = &expr_lit.lit {
-                        return Some(lit_str.value());
-                    }
-                }
+            if let syn::Meta::NameValue(meta) = &attr.meta
+                && let syn::Expr::Lit(expr_lit) = &meta.value
+                && let syn::Lit::Str(lit_str) = &expr_lit.lit
+            {
+                return Some(lit_str.value());
             }
             None
         })
         .collect()
 }
 
+/// Returns the summary of a doc comment, its lines up to the first blank one.
+pub fn doc_summary(attrs: &[Attribute]) -> Vec<String> {
+    extract_doc_comments(attrs)
+        .into_iter()
+        .skip_while(|line| line.trim().is_empty())
+        .take_while(|line| !line.trim().is_empty())
+        .collect()
+}
+
 /// Formats method documentation with optional field documentation appended.
 ///
 /// If `field_docs` is non-empty, appends them under a "## Field Documentation" subheading.
@@ -42,6 +55,33 @@ pub fn format_method_doc(auto_doc: &str, field_docs: &[String]) -> TokenStream {
     }
 }
 
+/// Attributes that are meaningful on a generated method as well as on a field.
+const METHOD_ATTRS: &[&str] = &["allow", "warn", "deny", "forbid", "expect", "deprecated"];
+
+/// Returns `true` if a field attribute should be forwarded onto the field's generated methods.
+///
+/// Lint-level attributes and `#[deprecated]` are forwarded, as is `#[cfg_attr(...)]` when
+/// every attribute it expands to is one of those.
+pub fn is_method_attr(attr: &Attribute) -> bool {
+    let path = attr.path();
+    if METHOD_ATTRS.iter().any(|name| path.is_ident(name)) {
+        return true;
+    }
+    if !path.is_ident("cfg_attr") {
+        return false;
+    }
+
+    let Ok(args) = attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated) else {
+        return false;
+    };
+    // The first argument is the predicate, the rest are the conditional attributes
+    args.len() > 1
+        && args
+            .iter()
+            .skip(1)
+            .all(|meta| METHOD_ATTRS.iter().any(|name| meta.path().is_ident(name)))
+}
+
 /// Returns `true` if `ty` syntactically references any of the given type-parameter idents.
 ///
 /// This is used when deciding whether an explicit trait bound on `ty` is required in a
@@ -75,6 +115,79 @@ pub fn type_mentions_type_param(ty: &Type, type_params: &[&Ident]) -> bool {
     checker.found
 }
 
+/// Returns true if `ty` mentions any of the lifetime, type, or const parameters of
+/// `generics`.
+pub fn type_mentions_generics(ty: &Type, generics: &Generics) -> bool {
+    let params: Vec<&Ident> = generics
+        .params
+        .iter()
+        .map(|param| match param {
+            GenericParam::Lifetime(lp) => &lp.lifetime.ident,
+            GenericParam::Type(tp) => &tp.ident,
+            GenericParam::Const(cp) => &cp.ident,
+        })
+        .collect();
+    type_mentions_type_param(ty, &params)
+}
+
+/// Replaces `Self` in the generics and field types of a struct with the struct's
+/// own type.
+///
+/// The generics and field types are repeated on the generated enums, companion
+/// structs, and impls for other types, where `Self` would name something else.
+/// `Self::Name` is rejected, since it could name an associated item of any trait
+/// the struct implements; `<Self as Trait>::Name` is remapped like `Self`.
+pub fn remap_self(input: &mut ItemStruct) -> syn::Result<()> {
+    struct Remapper {
+        self_ty: Type,
+        error: Option<syn::Error>,
+    }
+
+    impl VisitMut for Remapper {
+        fn visit_type_mut(&mut self, ty: &mut Type) {
+            if let Type::Path(type_path) = ty
+                && type_path.qself.is_none()
+                && type_path
+                    .path
+                    .segments
+                    .first()
+                    .is_some_and(|s| s.ident == "Self")
+            {
+                if type_path.path.segments.len() == 1 {
+                    *ty = self.self_ty.clone();
+                } else {
+                    let error = syn::Error::new_spanned(
+                        &type_path.path,
+                        "ambiguous associated item in a `#[structible]` struct; \
+                         name the trait as in `<Self as Trait>::Name`",
+                    );
+                    match &mut self.error {
+                        Some(e) => e.combine(error),
+                        None => self.error = Some(error),
+                    }
+                }
+                return;
+            }
+            syn::visit_mut::visit_type_mut(self, ty);
+        }
+    }
+
+    let name = &input.ident;
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let mut remapper = Remapper {
+        self_ty: syn::parse_quote! { #name #ty_generics },
+        error: None,
+    };
+    remapper.visit_generics_mut(&mut input.generics);
+    for field in &mut input.fields {
+        remapper.visit_type_mut(&mut field.ty);
+    }
+    match remapper.error {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
 /// If `ty` is `Option<T>`, returns `Some(T)`. Otherwise returns `None`.
 pub fn extract_option_inner(ty: &Type) -> Option<&Type> {
     let Type::Path(type_path) = ty else {
@@ -110,6 +223,118 @@ pub fn extract_option_inner(ty: &Type) -> Option<&Type> {
     Some(inner)
 }
 
+/// If `ty` is named `Vec<T>`, in any path form, returns `Some(T)`. Otherwise returns `None`.
+pub fn extract_vec_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+    if type_path.qself.is_some() {
+        return None;
+    }
+    let segment = type_path.path.segments.last()?;
+    if segment.ident != "Vec" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first() {
+        Some(GenericArgument::Type(inner)) if args.args.len() == 1 => Some(inner),
+        _ => None,
+    }
+}
+
+/// Returns `true` if `ty` is `String`, detected from the last segment of its path.
+pub fn is_string_type(ty: &Type) -> bool {
+    let Type::Path(type_path) = ty else {
+        return false;
+    };
+    type_path.qself.is_none()
+        && type_path
+            .path
+            .segments
+            .last()
+            .is_some_and(|s| s.ident == "String" && matches!(s.arguments, PathArguments::None))
+}
+
+/// Returns `true` if `ty` is named `Weak<T>`, i.e. looks like a weak reference.
+///
+/// Both `std::sync::Weak` and `std::rc::Weak` match, in any path form.
+pub fn is_weak_type(ty: &Type) -> bool {
+    let Type::Path(type_path) = ty else {
+        return false;
+    };
+    if type_path.qself.is_some() {
+        return false;
+    }
+    let Some(segment) = type_path.path.segments.last() else {
+        return false;
+    };
+    segment.ident == "Weak"
+        && matches!(&segment.arguments, PathArguments::AngleBracketed(args) if args.args.len() == 1)
+}
+
+/// Returns the trait object of `ty` if it is named `Box<dyn Trait>`.
+pub fn boxed_trait_object(ty: &Type) -> Option<&TypeTraitObject> {
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+    if type_path.qself.is_some() {
+        return None;
+    }
+    let segment = type_path.path.segments.last()?;
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first() {
+        Some(GenericArgument::Type(Type::TraitObject(object)))
+            if segment.ident == "Box" && args.args.len() == 1 =>
+        {
+            Some(object)
+        }
+        _ => None,
+    }
+}
+
+/// Returns the path of an enum type usable in patterns, i.e. without the generic
+/// arguments of its last segment, or `None` if the type isn't a plain path.
+pub fn enum_path(ty: &Type) -> Option<syn::Path> {
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+    if type_path.qself.is_some() {
+        return None;
+    }
+    let mut path = type_path.path.clone();
+    path.segments.last_mut()?.arguments = syn::PathArguments::None;
+    Some(path)
+}
+
+/// Renders a type as source text for documentation, e.g. `Option<Vec<String>>`.
+pub fn type_text(ty: &Type) -> String {
+    quote::ToTokens::to_token_stream(ty)
+        .to_string()
+        .replace(" :: ", "::")
+        .replace(":: ", "::")
+        .replace(" <", "<")
+        .replace("< ", "<")
+        .replace(" >", ">")
+        .replace(" ,", ",")
+        .replace("& ", "&")
+}
+
+/// Returns the 64-bit FNV-1a hash of a string.
+///
+/// Unlike `std::hash`, this is stable across compiler versions and platforms.
+pub fn fnv1a(text: &str) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in text.bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
 /// Converts a snake_case identifier to PascalCase.
 ///
 /// Handles raw identifiers (e.g., `r#type`) by stripping the `r#` prefix.
@@ -131,6 +356,26 @@ pub fn to_pascal_case(ident: &syn::Ident) -> syn::Ident {
     syn::Ident::new(&pascal, ident.span())
 }
 
+/// Converts a PascalCase identifier to snake_case.
+///
+/// Handles raw identifiers (e.g., `r#Type`) by stripping the `r#` prefix.
+pub fn to_snake_case(ident: &syn::Ident) -> String {
+    let s = ident.to_string();
+    let s = s.strip_prefix("r#").unwrap_or(&s);
+    let mut snake = String::with_capacity(s.len());
+    for (i, c) in s.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                snake.push('_');
+            }
+            snake.extend(c.to_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -173,6 +418,23 @@ mod tests {
         assert_eq!(result.to_string(), expected.to_string());
     }
 
+    #[test]
+    fn test_is_method_attr() {
+        let forwarded: Vec<Attribute> = vec![
+            syn::parse_quote!(#[deprecated(note = "use `name`")]),
+            syn::parse_quote!(#[allow(clippy::too_many_arguments)]),
+            syn::parse_quote!(#[cfg_attr(test, allow(dead_code))]),
+        ];
+        assert!(forwarded.iter().all(is_method_attr));
+
+        let kept: Vec<Attribute> = vec![
+            syn::parse_quote!(#[doc = " Docs are handled separately"]),
+            syn::parse_quote!(#[serde(rename = "fullName")]),
+            syn::parse_quote!(#[cfg_attr(feature = "serde", serde(default))]),
+        ];
+        assert!(!kept.iter().any(is_method_attr));
+    }
+
     #[test]
     fn test_to_pascal_case() {
         let ident = syn::Ident::new("foo_bar_baz", proc_macro2::Span::call_site());
@@ -187,6 +449,96 @@ mod tests {
         assert_eq!(result.to_string(), "Type");
     }
 
+    #[test]
+    fn test_to_snake_case() {
+        let ident = syn::Ident::new("PendingReview", proc_macro2::Span::call_site());
+        assert_eq!(to_snake_case(&ident), "pending_review");
+        let ident = syn::Ident::new("Active", proc_macro2::Span::call_site());
+        assert_eq!(to_snake_case(&ident), "active");
+    }
+
+    #[test]
+    fn test_type_text() {
+        let ty: Type = syn::parse_quote!(Option<std::collections::HashMap<String, Vec<u8>>>);
+        assert_eq!(
+            type_text(&ty),
+            "Option<std::collections::HashMap<String, Vec<u8>>>"
+        );
+        let ty: Type = syn::parse_quote!(&'static str);
+        assert_eq!(type_text(&ty), "&'static str");
+    }
+
+    #[test]
+    fn test_fnv1a() {
+        assert_eq!(fnv1a(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a("a"), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn test_remap_self() {
+        let mut input: ItemStruct = syn::parse_quote! {
+            struct Node<T: From<Self>> where Self: Send, T: Into<<Self as Parent>::Child> {
+                value: T,
+                next: Option<Box<Self>>,
+            }
+        };
+        remap_self(&mut input).unwrap();
+        let next = &input.fields.iter().nth(1).unwrap().ty;
+        assert_eq!(type_text(next), "Option<Box<Node<T>>>");
+        let generics = &input.generics;
+        let where_clause = &generics.where_clause;
+        let text = quote! { #generics #where_clause }
+            .to_string()
+            .replace(' ', "");
+        assert_eq!(
+            text,
+            "<T:From<Node<T>>>whereNode<T>:Send,T:Into<<Node<T>asParent>::Child>"
+        );
+    }
+
+    #[test]
+    fn test_remap_self_rejects_associated_items() {
+        let mut input: ItemStruct = syn::parse_quote! {
+            struct Node<T> where T: Into<Self::Child> {
+                value: T,
+            }
+        };
+        let err = remap_self(&mut input).unwrap_err();
+        assert!(err.to_string().contains("<Self as Trait>::Name"));
+    }
+
+    #[test]
+    fn test_is_weak_type() {
+        let weak: Vec<Type> = vec![
+            syn::parse_quote!(Weak<Node>),
+            syn::parse_quote!(std::rc::Weak<RefCell<Node>>),
+        ];
+        assert!(weak.iter().all(is_weak_type));
+
+        let other: Vec<Type> = vec![
+            syn::parse_quote!(Arc<Node>),
+            syn::parse_quote!(Weak),
+            syn::parse_quote!(Option<Weak<Node>>),
+        ];
+        assert!(!other.iter().any(is_weak_type));
+    }
+
+    #[test]
+    fn test_boxed_trait_object() {
+        let boxed: Vec<Type> = vec![
+            syn::parse_quote!(Box<dyn Shape>),
+            syn::parse_quote!(std::boxed::Box<dyn Fn(u32) -> u32 + Send>),
+        ];
+        assert!(boxed.iter().all(|ty| boxed_trait_object(ty).is_some()));
+
+        let other: Vec<Type> = vec![
+            syn::parse_quote!(Box<Shape>),
+            syn::parse_quote!(Arc<dyn Shape>),
+            syn::parse_quote!(Option<Box<dyn Shape>>),
+        ];
+        assert!(other.iter().all(|ty| boxed_trait_object(ty).is_none()));
+    }
+
     #[test]
     fn test_extract_option_inner() {
         let ty: Type = syn::parse2(quote! { Option<String> }).unwrap();
@@ -0,0 +1,655 @@
+use proc_macro2::TokenStream;
+use quote::{ToTokens, format_ident, quote};
+use syn::ext::IdentExt;
+use syn::{Expr, ExprRange, Ident, RangeLimits};
+
+use crate::changes::insert_expr;
+use crate::codegen::{field_enum_name, field_enum_type, value_enum_name};
+use crate::parse::{FieldInfo, StructibleConfig};
+use crate::util::{extract_doc_comments, format_method_doc, to_pascal_case};
+
+/// Returns true if the struct declares anything for `validate()` to check.
+pub fn has_validation(fields: &[FieldInfo], config: &StructibleConfig) -> bool {
+    config.invariant.is_some()
+        || fields
+            .iter()
+            .any(|f| f.has_constraints() || f.config.validate_key.is_some())
+}
+
+/// What a generated check does with a violation.
+#[derive(Clone, Copy)]
+enum OnViolation {
+    /// Return the error, as in `validate()`.
+    Return,
+    /// Push the error to the local `issues` vector, as in `validate_all()`.
+    Collect,
+}
+
+impl OnViolation {
+    /// Returns a statement handling the violation `error`, converted with `From`.
+    fn fail(self, error: TokenStream) -> TokenStream {
+        match self {
+            Self::Return => quote! {
+                return ::std::result::Result::Err(::std::convert::From::from(#error));
+            },
+            Self::Collect => quote! {
+                issues.push(::std::convert::From::from(#error));
+            },
+        }
+    }
+
+    /// Returns a statement handling the error of the `Result` expression `result`.
+    fn check(self, result: TokenStream) -> TokenStream {
+        match self {
+            Self::Return => quote! { #result?; },
+            Self::Collect => quote! {
+                if let ::std::result::Result::Err(error) = #result {
+                    issues.push(::std::convert::From::from(error));
+                }
+            },
+        }
+    }
+}
+
+/// Returns statements that fail if the catch-all key `key` (a reference) is invalid.
+///
+/// A key is invalid if it is a known field's name, or if the catch-all's
+/// `validate_key` function rejects it.
+fn key_checks(fields: &[FieldInfo], catch_all: &FieldInfo, on: OnViolation) -> TokenStream {
+    let Some(validate_key) = &catch_all.config.validate_key else {
+        return quote! {};
+    };
+    let names = fields
+        .iter()
+        .filter(|f| !f.is_unknown_field())
+        .map(|f| f.name.unraw().to_string());
+    let reserved = on.fail(quote! { ::structible::ValidationError::ReservedKey { field } });
+    let check = on.check(quote! { #validate_key(key) });
+    quote! {
+        let name: &str = ::std::borrow::Borrow::borrow(key);
+        if let ::std::option::Option::Some(field) = [#(#names),*].into_iter().find(|field| *field == name) {
+            #reserved
+        } else {
+            #check
+        }
+    }
+}
+
+/// Returns the fields that conflict with `field`.
+///
+/// Conflicts are symmetric: a field conflicts both with the fields it names in
+/// `conflicts_with` and with the fields naming it.
+fn conflicts_of<'a>(field: &'a FieldInfo, fields: &'a [FieldInfo]) -> Vec<&'a Ident> {
+    let mut conflicts: Vec<&Ident> = field.config.conflicts_with.iter().collect();
+    for other in fields {
+        if other.config.conflicts_with.contains(&field.name) && !conflicts.contains(&&other.name) {
+            conflicts.push(&other.name);
+        }
+    }
+    conflicts
+}
+
+/// Returns a note for the docs of the plain setter (or, with `removing`, the remover)
+/// of `field`, if it can break a `requires` constraint without an error.
+pub fn unchecked_requires_note(
+    field: &FieldInfo,
+    fields: &[FieldInfo],
+    removing: bool,
+) -> Option<String> {
+    let quoted = |names: Vec<&Ident>| {
+        names
+            .iter()
+            .map(|name| format!("`{}`", name.unraw()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    if removing {
+        let dependents: Vec<_> = fields
+            .iter()
+            .filter(|other| other.config.requires.contains(&field.name))
+            .map(|other| &other.name)
+            .collect();
+        (!dependents.is_empty()).then(|| {
+            format!(
+                "\n\nThis doesn't check the fields that require it ({}); only `validate()` reports them.",
+                quoted(dependents)
+            )
+        })
+    } else {
+        (!field.config.requires.is_empty()).then(|| {
+            format!(
+                "\n\nThis doesn't check that {} is present, as `requires` demands; only `{}` and `validate()` enforce it.",
+                quoted(field.config.requires.iter().collect()),
+                format_ident!("try_{}", field.setter_name())
+            )
+        })
+    }
+}
+
+/// Checks that the validation-related configuration is coherent.
+pub fn check_validation_config(fields: &[FieldInfo], config: &StructibleConfig) -> syn::Result<()> {
+    if let Some(error) = &config.error
+        && !has_validation(fields, config)
+    {
+        return Err(syn::Error::new_spanned(
+            error,
+            "`error` requires an `invariant` or a field constraint to be declared",
+        ));
+    }
+    Ok(())
+}
+
+/// Returns an expression that is true if the given known field is present.
+fn presence_check(struct_name: &Ident, field: &Ident) -> TokenStream {
+    let field_enum = field_enum_name(struct_name);
+    let variant = to_pascal_case(field);
+    quote! {
+        ::structible::BackingMap::get(&self.inner, &#field_enum::#variant).is_some()
+    }
+}
+
+/// Returns a statement that fails with a `ValidationError` if `target` is absent.
+fn dependency_check(
+    struct_name: &Ident,
+    field: &Ident,
+    target: &Ident,
+    on: OnViolation,
+) -> TokenStream {
+    let field_str = field.unraw().to_string();
+    let target_str = target.unraw().to_string();
+    let target_present = presence_check(struct_name, target);
+    let fail = on.fail(quote! {
+        ::structible::ValidationError::MissingDependency {
+            field: #field_str,
+            requires: #target_str,
+        }
+    });
+    quote! {
+        if !(#target_present) {
+            #fail
+        }
+    }
+}
+
+/// Returns a statement that fails with a `ValidationError` if `target` is present.
+fn conflict_check(
+    struct_name: &Ident,
+    field: &Ident,
+    target: &Ident,
+    on: OnViolation,
+) -> TokenStream {
+    let field_str = field.unraw().to_string();
+    let target_str = target.unraw().to_string();
+    let target_present = presence_check(struct_name, target);
+    let fail = on.fail(quote! {
+        ::structible::ValidationError::Conflict {
+            field: #field_str,
+            conflicts_with: #target_str,
+        }
+    });
+    quote! {
+        if #target_present {
+            #fail
+        }
+    }
+}
+
+/// Returns the source text of a `range` or `len`, as reported by `ValidationError`.
+fn range_text(range: &ExprRange) -> String {
+    let bound = |bound: &Option<Box<Expr>>| {
+        bound
+            .as_ref()
+            .map(|expr| expr.to_token_stream().to_string().replace(' ', ""))
+            .unwrap_or_default()
+    };
+    let limits = match range.limits {
+        RangeLimits::HalfOpen(_) => "..",
+        RangeLimits::Closed(_) => "..=",
+    };
+    format!("{}{}{}", bound(&range.start), limits, bound(&range.end))
+}
+
+/// Returns an expression that is true if `subject` lies within the bounds of `range`.
+fn bounds_check(range: &ExprRange, subject: TokenStream) -> TokenStream {
+    if let (Some(start), Some(end)) = (&range.start, &range.end) {
+        return quote! { ((#start)..=(#end)).contains(&#subject) };
+    }
+    let above_start = range
+        .start
+        .as_ref()
+        .map(|start| quote! { #subject >= (#start) });
+    let below_end = range.end.as_ref().map(|end| quote! { #subject <= (#end) });
+    let in_range = above_start.into_iter().chain(below_end);
+    quote! { #(#in_range)&&* }
+}
+
+/// Returns a statement that fails with a `ValidationError` if the value behind the
+/// reference `value` violates the field's `range`, `len`, `non_empty`, or `matches`.
+fn value_checks(field: &FieldInfo, value: TokenStream, on: OnViolation) -> TokenStream {
+    let field_str = field.name.unraw().to_string();
+    let range = field.config.range.as_ref().map(|range| {
+        let range_str = range_text(range);
+        let in_range = bounds_check(range, quote! { *value });
+        let fail = on.fail(quote! {
+            ::structible::ValidationError::OutOfRange {
+                field: #field_str,
+                range: #range_str,
+            }
+        });
+        quote! {
+            if !(#in_range) {
+                #fail
+            }
+        }
+    });
+    let len_bounds = match &field.config.len {
+        Some(len) => Some((range_text(len), bounds_check(len, quote! { length }))),
+        None if field.config.non_empty => Some(("1..".to_string(), quote! { length >= 1 })),
+        None => None,
+    };
+    let len = len_bounds.map(|(len_str, in_range)| {
+        let fail = on.fail(quote! {
+            ::structible::ValidationError::InvalidLength {
+                field: #field_str,
+                len: #len_str,
+            }
+        });
+        quote! {
+            let length = ::structible::Length::length(value);
+            if !(#in_range) {
+                #fail
+            }
+        }
+    });
+    let pattern = field.config.matches.as_ref().map(|pattern| {
+        let pattern_fn = pattern_fn_name(field);
+        let fail = on.fail(quote! {
+            ::structible::ValidationError::PatternMismatch {
+                field: #field_str,
+                pattern: #pattern,
+            }
+        });
+        quote! {
+            if !Self::#pattern_fn(::std::convert::AsRef::<str>::as_ref(value)) {
+                #fail
+            }
+        }
+    });
+    if range.is_none() && len.is_none() && pattern.is_none() {
+        return quote! {};
+    }
+    quote! {
+        {
+            let value = #value;
+            #range
+            #len
+            #pattern
+        }
+    }
+}
+
+/// Returns the name of the hidden function matching a value against the `matches`
+/// pattern of a field.
+fn pattern_fn_name(field: &FieldInfo) -> Ident {
+    format_ident!("__structible_{}_pattern", field.name.unraw())
+}
+
+/// Generate the hidden functions matching values against the `matches` patterns
+/// of fields, compiling each pattern on first use so that every check shares it.
+///
+/// Patterns are checked when the macro expands, so compiling them only fails if
+/// they exceed the `regex` crate's size limits. That panics, except in `no_panic`
+/// structs, where such a pattern matches nothing.
+fn generate_pattern_fns(fields: &[FieldInfo], config: &StructibleConfig) -> Vec<TokenStream> {
+    let regex = quote! { ::structible::__private::regex };
+    fields
+        .iter()
+        .filter_map(|f| f.config.matches.as_ref().map(|pattern| (f, pattern)))
+        .map(|(f, pattern)| {
+            let pattern_fn = pattern_fn_name(f);
+            let body = if config.no_panic {
+                quote! {
+                    static PATTERN: ::std::sync::OnceLock<::std::option::Option<#regex::Regex>> =
+                        ::std::sync::OnceLock::new();
+                    PATTERN
+                        .get_or_init(|| #regex::Regex::new(#pattern).ok())
+                        .as_ref()
+                        .is_some_and(|pattern| pattern.is_match(value))
+                }
+            } else {
+                quote! {
+                    static PATTERN: ::std::sync::OnceLock<#regex::Regex> = ::std::sync::OnceLock::new();
+                    PATTERN
+                        .get_or_init(|| {
+                            #regex::Regex::new(#pattern).expect("`matches` pattern should compile")
+                        })
+                        .is_match(value)
+                }
+            };
+            quote! {
+                #[doc(hidden)]
+                fn #pattern_fn(value: &str) -> bool {
+                    #body
+                }
+            }
+        })
+        .collect()
+}
+
+/// Returns statements checking everything `validate()` checks, in order.
+fn validation_checks(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+    on: OnViolation,
+) -> TokenStream {
+    // Conflicts are only checked from the declaring side here, since checking
+    // both sides would report the same violation twice.
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+    let constraint_checks = fields.iter().filter(|f| f.has_constraints()).map(|f| {
+        let variant = to_pascal_case(&f.name);
+        let present = presence_check(struct_name, &f.name);
+        let value_check = value_checks(f, quote! { value }, on);
+        let value_check = (!value_check.is_empty()).then(|| {
+            quote! {
+                if let ::std::option::Option::Some(#value_enum::#variant(value)) =
+                    ::structible::BackingMap::get(&self.inner, &#field_enum::#variant)
+                {
+                    #value_check
+                }
+            }
+        });
+        let requires = f
+            .config
+            .requires
+            .iter()
+            .map(|target| dependency_check(struct_name, &f.name, target, on));
+        let conflicts = f
+            .config
+            .conflicts_with
+            .iter()
+            .map(|target| conflict_check(struct_name, &f.name, target, on));
+        let checks = requires.chain(conflicts);
+        quote! {
+            #value_check
+            if #present {
+                #(#checks)*
+            }
+        }
+    });
+
+    let catch_all_check = fields
+        .iter()
+        .find(|f| f.is_unknown_field() && f.config.validate_key.is_some())
+        .map(|f| {
+            let field_enum = field_enum_type(struct_name, config);
+            let checks = key_checks(fields, f, on);
+            quote! {
+                for (k, _) in ::structible::IterableMap::iter(&self.inner) {
+                    if let #field_enum::Unknown(key) = k {
+                        #checks
+                    }
+                }
+            }
+        });
+
+    let invariant_check = config
+        .invariant
+        .as_ref()
+        .map(|invariant| on.check(quote! { #invariant(self) }));
+
+    quote! {
+        #(#constraint_checks)*
+        #catch_all_check
+        #invariant_check
+    }
+}
+
+/// Generate `validate()`, the fallible constructor, and `try_set_*` methods.
+///
+/// These are only generated when the struct declares an invariant or a field
+/// constraint. The fallible constructor is named after the regular one with a
+/// `try_` prefix, so a struct with `constructor = create` gets `try_create`.
+pub fn generate_validation_methods(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+) -> TokenStream {
+    if !has_validation(fields, config) {
+        return quote! {};
+    }
+
+    let error_ty = config.error_type();
+    let checks = validation_checks(struct_name, fields, config, OnViolation::Return);
+    let collecting_checks = validation_checks(struct_name, fields, config, OnViolation::Collect);
+
+    let constructor_name = config.constructor_name(struct_name);
+    let try_constructor_name = format_ident!("try_{}", constructor_name);
+
+    let required: Vec<_> = fields
+        .iter()
+        .filter(|f| !f.is_optional && !f.is_unknown_field())
+        .collect();
+    let params: Vec<_> = required
+        .iter()
+        .map(|f| {
+            let name = &f.name;
+            let ty = &f.ty;
+            quote! { #name: #ty }
+        })
+        .collect();
+    let args: Vec<_> = required.iter().map(|f| &f.name).collect();
+
+    let try_setters = generate_try_setters(struct_name, fields, config);
+    let clamped_setters = generate_clamped_setters(fields, config);
+    let pattern_fns = generate_pattern_fns(fields, config);
+    let try_insert = generate_try_insert(struct_name, fields, config);
+
+    quote! {
+        /// Checks that this value satisfies the struct's declared constraints and invariants.
+        pub fn validate(&self) -> ::std::result::Result<(), #error_ty> {
+            #checks
+            ::std::result::Result::Ok(())
+        }
+
+        /// Checks this value like `validate()`, but reports every violation instead of
+        /// only the first one.
+        pub fn validate_all(&self) -> ::std::result::Result<(), ::std::vec::Vec<#error_ty>> {
+            let mut issues = ::std::vec::Vec::new();
+            #collecting_checks
+            if issues.is_empty() {
+                ::std::result::Result::Ok(())
+            } else {
+                ::std::result::Result::Err(issues)
+            }
+        }
+
+        /// Creates a new instance with all required fields, then checks it with `validate()`.
+        pub fn #try_constructor_name(#(#params),*) -> ::std::result::Result<Self, #error_ty> {
+            let value = Self::#constructor_name(#(#args),*);
+            value.validate()?;
+            ::std::result::Result::Ok(value)
+        }
+
+        #(#try_setters)*
+        #(#clamped_setters)*
+        #(#pattern_fns)*
+        #try_insert
+    }
+}
+
+/// Generate `try_insert_*` for a catch-all declaring `validate_key`.
+///
+/// The key is normalized first, if the catch-all declares `normalize`, so that
+/// the check sees the key that would be stored.
+fn generate_try_insert(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+) -> TokenStream {
+    if config.no_setters {
+        return quote! {};
+    }
+    let Some(f) = fields
+        .iter()
+        .find(|f| f.is_unknown_field() && f.config.validate_key.is_some())
+    else {
+        return quote! {};
+    };
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+    let error_ty = config.error_type();
+    let name = &f.name;
+    let try_insert_method = format_ident!("try_insert_{}", name);
+    let key_type = f.unknown_key_type().unwrap();
+    let value_type = &f.inner_ty;
+    let vis = f.setter_vis();
+    let normalized_key = f.normalized_key(quote! { key });
+    let checks = key_checks(fields, f, OnViolation::Return);
+    let insert = insert_expr(
+        config,
+        quote! { self },
+        quote! { #field_enum::Unknown(key) },
+        quote! { #value_enum::Unknown(value) },
+    );
+
+    let field_docs = extract_doc_comments(&f.attrs);
+    let method_attrs = f.method_attrs();
+    let auto_doc = format!(
+        "Inserts an unknown `{}` field if its key is valid; otherwise returns an error and leaves the struct unchanged. Returns the previous value if the key was already present.",
+        name.unraw()
+    );
+    let doc_attr = format_method_doc(&auto_doc, &field_docs);
+
+    quote! {
+        #doc_attr
+        #(#method_attrs)*
+        #vis fn #try_insert_method(&mut self, key: #key_type, value: #value_type) -> ::std::result::Result<Option<#value_type>, #error_ty> {
+            let key: #key_type = #normalized_key;
+            {
+                let key = &key;
+                #checks
+            }
+            ::std::result::Result::Ok(match #insert {
+                Some(#value_enum::Unknown(v)) => Some(v),
+                _ => None,
+            })
+        }
+    }
+}
+
+/// Generate `try_set_*` methods for fields with constraints.
+///
+/// A `try_set_*` method checks the field's constraints against the current
+/// state before inserting the value, and leaves the struct unchanged on error.
+/// Fields named by another field's `conflicts_with` get one as well.
+fn generate_try_setters(
+    struct_name: &Ident,
+    fields: &[FieldInfo],
+    config: &StructibleConfig,
+) -> Vec<TokenStream> {
+    if config.no_setters {
+        return Vec::new();
+    }
+    let field_enum = field_enum_type(struct_name, config);
+    let value_enum = value_enum_name(struct_name);
+    let error_ty = config.error_type();
+
+    fields
+        .iter()
+        .filter(|f| f.has_constraints() || !conflicts_of(f, fields).is_empty())
+        .map(|f| {
+            let name = &f.name;
+            let try_setter_name = format_ident!("try_{}", f.setter_name());
+            let variant = to_pascal_case(name);
+            let vis = f.setter_vis();
+            let value_ty = if f.is_optional { &f.inner_ty } else { &f.ty };
+            let requires = f
+                .config
+                .requires
+                .iter()
+                .map(|target| dependency_check(struct_name, name, target, OnViolation::Return));
+            let conflicts = conflicts_of(f, fields)
+                .into_iter()
+                .map(|target| conflict_check(struct_name, name, target, OnViolation::Return));
+            let value_check = value_checks(f, quote! { &value }, OnViolation::Return);
+            let checks = requires.chain(conflicts);
+            let insert = insert_expr(
+                config,
+                quote! { self },
+                quote! { #field_enum::#variant },
+                quote! { #value_enum::#variant(value) },
+            );
+
+            let field_docs = extract_doc_comments(&f.attrs);
+            let method_attrs = f.method_attrs();
+            let auto_doc = format!(
+                "Sets the `{}` value if its constraints are satisfied; otherwise returns an error and leaves the struct unchanged.",
+                name.unraw()
+            );
+            let doc_attr = format_method_doc(&auto_doc, &field_docs);
+
+            quote! {
+                #doc_attr
+                #(#method_attrs)*
+                #vis fn #try_setter_name(&mut self, value: #value_ty) -> ::std::result::Result<(), #error_ty> {
+                    #value_check
+                    #(#checks)*
+                    #insert;
+                    ::std::result::Result::Ok(())
+                }
+            }
+        })
+        .collect()
+}
+
+/// Generate `set_*_clamped` methods for fields declaring a `range`.
+///
+/// Values outside the range are replaced with the nearest bound before being
+/// set. Values that compare neither below nor above both bounds, like `NaN`,
+/// are set as they are.
+fn generate_clamped_setters(fields: &[FieldInfo], config: &StructibleConfig) -> Vec<TokenStream> {
+    if config.no_setters {
+        return Vec::new();
+    }
+    fields
+        .iter()
+        .filter_map(|f| f.config.range.as_ref().map(|range| (f, range)))
+        .map(|(f, range)| {
+            let setter_name = f.setter_name();
+            let clamped_setter_name = format_ident!("{}_clamped", setter_name);
+            let vis = f.setter_vis();
+            let value_ty = &f.inner_ty;
+            let clamp_start = range.start.as_ref().map(|start| {
+                quote! {
+                    let value = if value < (#start) { #start } else { value };
+                }
+            });
+            let clamp_end = range.end.as_ref().map(|end| {
+                quote! {
+                    let value = if value > (#end) { #end } else { value };
+                }
+            });
+
+            let field_docs = extract_doc_comments(&f.attrs);
+            let method_attrs = f.method_attrs();
+            let auto_doc = format!(
+                "Sets the `{}` value, clamped to `{}`.",
+                f.name.unraw(),
+                range_text(range)
+            );
+            let doc_attr = format_method_doc(&auto_doc, &field_docs);
+
+            quote! {
+                #doc_attr
+                #(#method_attrs)*
+                #vis fn #clamped_setter_name(&mut self, value: #value_ty) {
+                    #clamp_start
+                    #clamp_end
+                    self.#setter_name(value);
+                }
+            }
+        })
+        .collect()
+}
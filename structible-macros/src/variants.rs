@@ -0,0 +1,48 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+
+use crate::parse::FieldInfo;
+use crate::util::{enum_path, extract_doc_comments, format_method_doc, to_snake_case};
+
+/// Generate the `is_<field>_<variant>()` predicates of fields declaring `variants`.
+///
+/// Each predicate matches the field's getter against the variant, whatever its
+/// kind, so absent optional fields match no variant.
+pub fn generate_variant_predicates(fields: &[FieldInfo]) -> Vec<TokenStream> {
+    fields
+        .iter()
+        .filter(|f| !f.config.variants.is_empty())
+        .flat_map(|f| {
+            let getter = f.getter_name();
+            let path = enum_path(&f.inner_ty).unwrap();
+            let vis = f.getter_vis();
+            let field_docs = extract_doc_comments(&f.attrs);
+            let method_attrs = f.method_attrs();
+            f.config.variants.iter().map(move |variant| {
+                let predicate_name = format_ident!("is_{}_{}", getter, to_snake_case(variant));
+                let pattern = if f.has_optional_getter() {
+                    quote! { ::std::option::Option::Some(#path::#variant { .. }) }
+                } else {
+                    quote! { #path::#variant { .. } }
+                };
+                let doc_attr = format_method_doc(
+                    &format!(
+                        "Returns true if the `{}` value is `{}::{}`.",
+                        f.name.unraw(),
+                        path.segments.last().unwrap().ident,
+                        variant
+                    ),
+                    &field_docs,
+                );
+                quote! {
+                    #doc_attr
+                    #(#method_attrs)*
+                    #vis fn #predicate_name(&self) -> bool {
+                        ::std::matches!(self.#getter(), #pattern)
+                    }
+                }
+            })
+        })
+        .collect()
+}
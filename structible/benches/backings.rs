@@ -0,0 +1,39 @@
+//! Compares the accessors of the same fields over each backing map.
+//!
+//! Run with `cargo bench --features criterion`.
+
+use std::collections::BTreeMap;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use structible::structible;
+
+#[structible(bench)]
+pub struct HashMapUser {
+    pub name: String,
+    pub age: u32,
+    pub email: Option<String>,
+    pub tags: Option<Vec<String>>,
+}
+
+#[structible(backing = BTreeMap, bench)]
+pub struct BTreeMapUser {
+    pub name: String,
+    pub age: u32,
+    pub email: Option<String>,
+    pub tags: Option<Vec<String>>,
+}
+
+fn accessors(c: &mut Criterion) {
+    let mut user = HashMapUser::new("Alice".into(), 30);
+    user.set_email("alice@example.com".into());
+    user.set_tags(vec!["admin".into(), "ops".into()]);
+    HashMapUser::bench_accessors(c, &user);
+
+    let mut user = BTreeMapUser::new("Alice".into(), 30);
+    user.set_email("alice@example.com".into());
+    user.set_tags(vec!["admin".into(), "ops".into()]);
+    BTreeMapUser::bench_accessors(c, &user);
+}
+
+criterion_group!(benches, accessors);
+criterion_main!(benches);
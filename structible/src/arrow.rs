@@ -0,0 +1,190 @@
+//! Arrow columns of structible structs, see `#[structible(arrow)]`.
+//!
+//! Structs declared with `arrow` get `arrow_schema()`, with a column per known
+//! field, and a `<Struct>ArrayBuilder` that appends structs column-wise and
+//! finishes them into a `RecordBatch`:
+//!
+//! ```rust
+//! use arrow_array::Array;
+//! use structible::structible;
+//!
+//! #[structible(arrow)]
+//! pub struct Person {
+//!     pub name: String,
+//!     pub age: Option<u32>,
+//! }
+//!
+//! let mut builder = PersonArrayBuilder::new();
+//! builder.append(&Person::new("Alice".into()));
+//! let batch = builder.finish().unwrap();
+//! assert_eq!(batch.num_rows(), 1);
+//! assert!(batch.column(1).is_null(0));
+//! ```
+//!
+//! Columns are named after the fields as declared, in declaration order, and
+//! catch-all entries have no column. Optional fields are nullable, and absent
+//! or expired values are appended as nulls. `finish()` resets the builder, so
+//! that it can build the next batch.
+//!
+//! Field types choose their column type by implementing [`ArrowValue`], which
+//! is implemented for the primitive numeric types, `bool`, `String`, and `Vec<u8>`.
+//! The structs themselves implement [`ArrowRecord`], which also reads them back
+//! from record batches.
+
+use arrow_array::builder::{
+    ArrayBuilder, BinaryBuilder, BooleanBuilder, Float32Builder, Float64Builder, Int8Builder,
+    Int16Builder, Int32Builder, Int64Builder, StringBuilder, UInt8Builder, UInt16Builder,
+    UInt32Builder, UInt64Builder,
+};
+use arrow_array::types::{
+    Float32Type, Float64Type, Int8Type, Int16Type, Int32Type, Int64Type, UInt8Type, UInt16Type,
+    UInt32Type, UInt64Type,
+};
+use arrow_array::{Array, BinaryArray, BooleanArray, PrimitiveArray, RecordBatch, StringArray};
+use arrow_schema::{ArrowError, DataType, Schema};
+
+/// Trait for structs that can be converted to and from Arrow record batches.
+///
+/// It is implemented by `#[structible(arrow)]`.
+pub trait ArrowRecord: Sized {
+    /// Returns the schema of the struct's record batches.
+    fn arrow_schema() -> Schema;
+
+    /// Builds a record batch with a row per struct.
+    fn to_record_batch<'a, I>(records: I) -> Result<RecordBatch, ArrowError>
+    where
+        I: IntoIterator<Item = &'a Self>,
+        Self: 'a;
+
+    /// Reads a struct from every row of a record batch.
+    ///
+    /// Columns are found by name, so the batch may hold them in any order, and
+    /// columns of optional fields may be missing. Fails if a required field's
+    /// column is missing or null, or if a column has another data type.
+    fn from_record_batch(batch: &RecordBatch) -> Result<Vec<Self>, ArrowError>;
+}
+
+/// Trait for field types that can be stored in an Arrow column.
+pub trait ArrowValue {
+    /// The builder of the column's array.
+    type Builder: ArrayBuilder + Default;
+
+    /// Returns the data type of the column.
+    fn data_type() -> DataType;
+
+    /// Appends a value to the column, or a null for `None`.
+    fn append(builder: &mut Self::Builder, value: Option<&Self>);
+
+    /// Reads the value at `index` of a column, or `None` if it is null.
+    ///
+    /// Fails if the column has another data type.
+    fn read(array: &dyn Array, index: usize) -> Result<Option<Self>, ArrowError>
+    where
+        Self: Sized;
+}
+
+/// Downcasts a column to the array type `A` of the value type `T`.
+fn downcast<T: ArrowValue, A: Array + 'static>(array: &dyn Array) -> Result<&A, ArrowError> {
+    array.as_any().downcast_ref().ok_or_else(|| {
+        ArrowError::CastError(format!(
+            "expected a {} column, found {}",
+            T::data_type(),
+            array.data_type()
+        ))
+    })
+}
+
+macro_rules! impl_arrow_value {
+    ($($ty:ty => $builder:ty, $array:ty, $data_type:ident;)*) => {
+        $(
+            impl ArrowValue for $ty {
+                type Builder = $builder;
+
+                fn data_type() -> DataType {
+                    DataType::$data_type
+                }
+
+                fn append(builder: &mut Self::Builder, value: Option<&Self>) {
+                    builder.append_option(value.copied());
+                }
+
+                fn read(array: &dyn Array, index: usize) -> Result<Option<Self>, ArrowError> {
+                    let array = downcast::<Self, $array>(array)?;
+                    Ok(array.is_valid(index).then(|| array.value(index)))
+                }
+            }
+        )*
+    };
+}
+
+impl_arrow_value! {
+    i8 => Int8Builder, PrimitiveArray<Int8Type>, Int8;
+    i16 => Int16Builder, PrimitiveArray<Int16Type>, Int16;
+    i32 => Int32Builder, PrimitiveArray<Int32Type>, Int32;
+    i64 => Int64Builder, PrimitiveArray<Int64Type>, Int64;
+    u8 => UInt8Builder, PrimitiveArray<UInt8Type>, UInt8;
+    u16 => UInt16Builder, PrimitiveArray<UInt16Type>, UInt16;
+    u32 => UInt32Builder, PrimitiveArray<UInt32Type>, UInt32;
+    u64 => UInt64Builder, PrimitiveArray<UInt64Type>, UInt64;
+    f32 => Float32Builder, PrimitiveArray<Float32Type>, Float32;
+    f64 => Float64Builder, PrimitiveArray<Float64Type>, Float64;
+    bool => BooleanBuilder, BooleanArray, Boolean;
+}
+
+impl ArrowValue for String {
+    type Builder = StringBuilder;
+
+    fn data_type() -> DataType {
+        DataType::Utf8
+    }
+
+    fn append(builder: &mut Self::Builder, value: Option<&Self>) {
+        builder.append_option(value);
+    }
+
+    fn read(array: &dyn Array, index: usize) -> Result<Option<Self>, ArrowError> {
+        let array = downcast::<Self, StringArray>(array)?;
+        Ok(array.is_valid(index).then(|| array.value(index).to_owned()))
+    }
+}
+
+impl ArrowValue for Vec<u8> {
+    type Builder = BinaryBuilder;
+
+    fn data_type() -> DataType {
+        DataType::Binary
+    }
+
+    fn append(builder: &mut Self::Builder, value: Option<&Self>) {
+        builder.append_option(value);
+    }
+
+    fn read(array: &dyn Array, index: usize) -> Result<Option<Self>, ArrowError> {
+        let array = downcast::<Self, BinaryArray>(array)?;
+        Ok(array.is_valid(index).then(|| array.value(index).to_vec()))
+    }
+}
+
+/// Reads the column of a required field, failing if it is missing.
+#[doc(hidden)]
+pub fn __required_column<'a>(
+    batch: &'a RecordBatch,
+    name: &str,
+) -> Result<&'a dyn Array, ArrowError> {
+    batch
+        .column_by_name(name)
+        .map(|column| column.as_ref())
+        .ok_or_else(|| ArrowError::SchemaError(format!("missing column `{}`", name)))
+}
+
+/// Reads the value of a required field, failing if it is null.
+#[doc(hidden)]
+pub fn __read_required<T: ArrowValue>(
+    column: &dyn Array,
+    name: &str,
+    index: usize,
+) -> Result<T, ArrowError> {
+    T::read(column, index)?.ok_or_else(|| {
+        ArrowError::InvalidArgumentError(format!("null in required column `{}`", name))
+    })
+}
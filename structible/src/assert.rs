@@ -0,0 +1,82 @@
+//! Compile-time checks of field types, emitted by the macro.
+//!
+//! The value enum's `Debug`, `Clone`, and `PartialEq` impls only bound generic
+//! field types, so a concrete field type missing one of those traits would
+//! otherwise fail inside the hidden enum. Each trait here is implemented for
+//! every type with the corresponding std trait, and the macro bounds each field
+//! type by them in a where clause, with `Field` a marker struct named after the
+//! field, so that the error names both the field and its type.
+//!
+//! The catch-all's key type is also checked against the backing, for `HashMap`
+//! (`Eq` and `Hash`) and `BTreeMap` (`Ord`). Types mentioning the struct's
+//! generics are left to be checked where the struct is used.
+
+/// Declares a check trait and its blanket impl.
+macro_rules! field_check {
+    ($(#[$attr:meta])* $name:ident, $($bound:tt)+) => {
+        $(#[$attr])*
+        pub trait $name<Field> {}
+
+        impl<T: ?Sized + $($bound)+, Field> $name<Field> for T {}
+    };
+}
+
+field_check!(
+    #[diagnostic::on_unimplemented(
+        message = "field `{Field}` has type `{Self}`, which doesn't implement `Debug`",
+        label = "the generated `Debug` impls require this type to implement `Debug`",
+        note = "for a trait object, add `Debug` as a supertrait of the trait"
+    )]
+    FieldDebug,
+    ::std::fmt::Debug
+);
+
+field_check!(
+    #[diagnostic::on_unimplemented(
+        message = "field `{Field}` has type `{Self}`, which doesn't implement `Clone`",
+        label = "the generated `Clone` impls require this type to implement `Clone`",
+        note = "mark the field `#[structible(skip_clone)]`, or the struct `#[structible(no_clone)]`"
+    )]
+    FieldClone,
+    ::std::clone::Clone
+);
+
+field_check!(
+    #[diagnostic::on_unimplemented(
+        message = "field `{Field}` has type `{Self}`, which doesn't implement `Default`",
+        label = "fields with `skip_clone` are cloned as their default value",
+        note = "remove `skip_clone` from the field, or mark the struct `#[structible(no_clone)]`"
+    )]
+    FieldDefault,
+    ::std::default::Default
+);
+
+field_check!(
+    #[diagnostic::on_unimplemented(
+        message = "field `{Field}` has type `{Self}`, which doesn't implement `PartialEq`",
+        label = "the generated `PartialEq` impls require this type to implement `PartialEq`",
+        note = "mark the field `#[structible(skip_eq)]`, or the struct `#[structible(no_partial_eq)]`"
+    )]
+    FieldPartialEq,
+    ::std::cmp::PartialEq
+);
+
+field_check!(
+    #[diagnostic::on_unimplemented(
+        message = "the key type `{Self}` of catch-all `{Field}` doesn't implement `Eq` and `Hash`",
+        label = "`HashMap` keys must implement `Eq` and `Hash`",
+        note = "use a `backing` whose keys can be of this type, e.g. `BTreeMap` for `Ord` keys"
+    )]
+    KeyHash,
+    ::std::cmp::Eq + ::std::hash::Hash
+);
+
+field_check!(
+    #[diagnostic::on_unimplemented(
+        message = "the key type `{Self}` of catch-all `{Field}` doesn't implement `Ord`",
+        label = "`BTreeMap` keys must implement `Ord`",
+        note = "use a `backing` whose keys can be of this type, e.g. `HashMap` for `Hash` keys"
+    )]
+    KeyOrd,
+    ::std::cmp::Ord
+);
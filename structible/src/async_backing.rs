@@ -0,0 +1,69 @@
+use std::collections::{BTreeMap, HashMap};
+use std::convert::Infallible;
+use std::future::{Future, ready};
+use std::hash::Hash;
+
+/// Trait for maps that back a structible struct declared with `#[structible(async)]`.
+///
+/// Unlike [`BackingMap`](crate::BackingMap), every operation is asynchronous and
+/// fallible, and values are returned by value rather than by reference. This fits
+/// stores that live outside the process, such as Redis or a remote key-value
+/// service, where the entries cannot be borrowed.
+///
+/// It is also implemented for `HashMap` and `BTreeMap` (with [`Infallible`] errors),
+/// which is convenient for tests and local development.
+pub trait AsyncBackingMap<K, V> {
+    /// The error returned when the underlying store fails.
+    type Error;
+
+    /// Returns the value for the given key.
+    fn get(&self, key: &K) -> impl Future<Output = Result<Option<V>, Self::Error>> + Send;
+
+    /// Inserts a key-value pair, replacing any previous value.
+    fn insert(&mut self, key: K, value: V) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    /// Removes a key, returning its value if it was present.
+    fn remove(&mut self, key: &K) -> impl Future<Output = Result<Option<V>, Self::Error>> + Send;
+}
+
+impl<K, V> AsyncBackingMap<K, V> for HashMap<K, V>
+where
+    K: Eq + Hash,
+    V: Clone + Send,
+{
+    type Error = Infallible;
+
+    fn get(&self, key: &K) -> impl Future<Output = Result<Option<V>, Self::Error>> + Send {
+        ready(Ok(HashMap::get(self, key).cloned()))
+    }
+
+    fn insert(&mut self, key: K, value: V) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        HashMap::insert(self, key, value);
+        ready(Ok(()))
+    }
+
+    fn remove(&mut self, key: &K) -> impl Future<Output = Result<Option<V>, Self::Error>> + Send {
+        ready(Ok(HashMap::remove(self, key)))
+    }
+}
+
+impl<K, V> AsyncBackingMap<K, V> for BTreeMap<K, V>
+where
+    K: Ord,
+    V: Clone + Send,
+{
+    type Error = Infallible;
+
+    fn get(&self, key: &K) -> impl Future<Output = Result<Option<V>, Self::Error>> + Send {
+        ready(Ok(BTreeMap::get(self, key).cloned()))
+    }
+
+    fn insert(&mut self, key: K, value: V) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        BTreeMap::insert(self, key, value);
+        ready(Ok(()))
+    }
+
+    fn remove(&mut self, key: &K) -> impl Future<Output = Result<Option<V>, Self::Error>> + Send {
+        ready(Ok(BTreeMap::remove(self, key)))
+    }
+}
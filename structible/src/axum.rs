@@ -0,0 +1,185 @@
+//! Request extractors for structible structs, see `#[structible(axum)]`.
+//!
+//! Structs with `axum` implement [`FromRequest`](axum::extract::FromRequest),
+//! decoding a JSON object body with a property per known field, like the schema
+//! of `#[structible(openapi)]`. Properties that aren't named after a known field
+//! go to the catch-all, and are rejected if the struct has none:
+//!
+//! ```rust,ignore
+//! #[structible(axum)]
+//! pub struct CreatePerson {
+//!     pub name: String,
+//!     pub age: Option<u32>,
+//! }
+//!
+//! async fn create(person: CreatePerson) -> String {
+//!     format!("created {}", person.name())
+//! }
+//! ```
+//!
+//! Bodies that are not a JSON object are rejected like [`axum::Json`] rejects
+//! them. Objects that don't describe a struct are rejected with a
+//! `422 Unprocessable Entity` response listing every problem, see [`FieldErrors`].
+//!
+//! Properties are decoded with serde, and catch-all keys are decoded from the
+//! property name and normalized. Structs with validation are checked with
+//! `validate_all()`, whose errors are listed as violations.
+
+use std::fmt;
+
+use axum::Json;
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{FromRequest, Request};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+/// The rejection of the generated `FromRequest` impls.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Rejection {
+    /// The body is not a JSON object.
+    Json(JsonRejection),
+    /// The object doesn't describe a valid struct.
+    Fields(FieldErrors),
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rejection::Json(rejection) => write!(f, "{}", rejection.body_text()),
+            Rejection::Fields(errors) => write!(f, "{}", errors),
+        }
+    }
+}
+
+impl std::error::Error for Rejection {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Rejection::Json(rejection) => Some(rejection),
+            Rejection::Fields(errors) => Some(errors),
+        }
+    }
+}
+
+impl IntoResponse for Rejection {
+    fn into_response(self) -> Response {
+        match self {
+            Rejection::Json(rejection) => rejection.into_response(),
+            Rejection::Fields(errors) => errors.into_response(),
+        }
+    }
+}
+
+/// The problems found while decoding a struct from a JSON object.
+///
+/// As a response, it is a `422 Unprocessable Entity` with a JSON body holding a
+/// message and the non-empty lists, e.g.
+/// ``{"error":"missing required field `name`","missing":["name"]}``.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FieldErrors {
+    /// The required fields that were absent, in declaration order.
+    pub missing: Vec<&'static str>,
+    /// The properties whose key or value could not be decoded, with the error.
+    pub invalid: Vec<(String, String)>,
+    /// The properties that name no field of a struct without a catch-all.
+    pub unknown: Vec<String>,
+    /// The violations of the struct's declared validation.
+    pub violations: Vec<String>,
+}
+
+impl FieldErrors {
+    /// Returns true if no problem was found.
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty()
+            && self.invalid.is_empty()
+            && self.unknown.is_empty()
+            && self.violations.is_empty()
+    }
+}
+
+impl fmt::Display for FieldErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let missing = self
+            .missing
+            .iter()
+            .map(|field| format!("missing required field `{}`", field));
+        let invalid = self
+            .invalid
+            .iter()
+            .map(|(key, error)| format!("invalid property `{}`: {}", key, error));
+        let unknown = self
+            .unknown
+            .iter()
+            .map(|key| format!("unknown property `{}`", key));
+        let problems: Vec<_> = missing
+            .chain(invalid)
+            .chain(unknown)
+            .chain(self.violations.iter().cloned())
+            .collect();
+        if problems.is_empty() {
+            write!(f, "no field errors")
+        } else {
+            write!(f, "{}", problems.join("; "))
+        }
+    }
+}
+
+impl std::error::Error for FieldErrors {}
+
+impl IntoResponse for FieldErrors {
+    fn into_response(self) -> Response {
+        let mut body = Map::new();
+        body.insert("error".into(), Value::String(self.to_string()));
+        if !self.missing.is_empty() {
+            body.insert("missing".into(), self.missing.into());
+        }
+        if !self.invalid.is_empty() {
+            let invalid = self
+                .invalid
+                .into_iter()
+                .map(|(key, error)| (key, Value::String(error)))
+                .collect();
+            body.insert("invalid".into(), Value::Object(invalid));
+        }
+        if !self.unknown.is_empty() {
+            body.insert("unknown".into(), self.unknown.into());
+        }
+        if !self.violations.is_empty() {
+            body.insert("violations".into(), self.violations.into());
+        }
+        (StatusCode::UNPROCESSABLE_ENTITY, Json(Value::Object(body))).into_response()
+    }
+}
+
+/// Extracts the JSON object body of a request.
+#[doc(hidden)]
+pub async fn __json_object<S: Send + Sync>(
+    req: Request,
+    state: &S,
+) -> Result<Map<String, Value>, Rejection> {
+    let Json(object) = Json::<Map<String, Value>>::from_request(req, state)
+        .await
+        .map_err(Rejection::Json)?;
+    Ok(object)
+}
+
+/// Decodes the value of the property `key`, recording the error if it fails.
+#[doc(hidden)]
+pub fn __decode<T: DeserializeOwned>(
+    key: &str,
+    value: Value,
+    errors: &mut FieldErrors,
+) -> Option<T> {
+    serde_json::from_value(value)
+        .map_err(|err| errors.invalid.push((key.to_string(), err.to_string())))
+        .ok()
+}
+
+/// Decodes the property name `key` as a catch-all key, recording the error if it fails.
+#[doc(hidden)]
+pub fn __decode_key<K: DeserializeOwned>(key: &str, errors: &mut FieldErrors) -> Option<K> {
+    __decode(key, Value::String(key.to_string()), errors)
+}
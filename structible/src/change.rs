@@ -0,0 +1,22 @@
+/// A single operation on a struct's fields, as recorded in the change log of a struct
+/// with `track_changes` or collected by a patch builder.
+///
+/// `K` and `V` are the struct's field and value enums, which are nameable through
+/// the generated `<Struct>Field` and `<Struct>Value` aliases.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FieldChange<K, V> {
+    /// A field was set to a value.
+    Set(K, V),
+    /// A present field was removed.
+    Remove(K),
+}
+
+impl<K, V> FieldChange<K, V> {
+    /// Returns the key of the changed field.
+    pub fn key(&self) -> &K {
+        match self {
+            FieldChange::Set(key, _) | FieldChange::Remove(key) => key,
+        }
+    }
+}
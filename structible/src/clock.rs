@@ -0,0 +1,55 @@
+use std::time::Instant;
+
+/// A source of timestamps for the writes to a struct with `timestamps`.
+///
+/// Structs use [`SystemClock`] unless declared with `clock = MyClock`, e.g. to
+/// record wall-clock or hybrid logical times, or a mocked time in tests.
+///
+/// The constructor's writes are timestamped too, and so are removals that
+/// removed a value and writes merged with `lww`, but not catch-all entries.
+/// Timestamps are cloned, but ignored by `PartialEq` and `Debug`, and dropped
+/// by `into_backing()` and `freeze()`.
+///
+/// ```rust
+/// use structible::{Clock, structible};
+///
+/// pub struct UnixClock;
+///
+/// impl Clock for UnixClock {
+///     type Instant = u64;
+///
+///     fn now() -> u64 {
+///         std::time::SystemTime::now()
+///             .duration_since(std::time::UNIX_EPOCH)
+///             .unwrap()
+///             .as_secs()
+///     }
+/// }
+///
+/// #[structible(timestamps, clock = UnixClock)]
+/// pub struct Document {
+///     pub title: String,
+/// }
+///
+/// let doc = Document::new("Draft".into());
+/// assert!(doc.title_modified_at().is_some());
+/// ```
+pub trait Clock {
+    /// The timestamp of a write.
+    type Instant: Copy;
+
+    /// Returns the current time.
+    fn now() -> Self::Instant;
+}
+
+/// The monotonic clock of [`std::time::Instant`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    type Instant = Instant;
+
+    fn now() -> Instant {
+        Instant::now()
+    }
+}
@@ -0,0 +1,182 @@
+//! Field-by-field encoding of structible structs, for key-value stores.
+//!
+//! Structs declared with `value_codec` implement [`ValueCodec`], which names each
+//! field and encodes its value on its own, so that a record can be stored under
+//! one key per field (e.g. `person:42:name`) and read or written partially:
+//!
+//! ```rust
+//! use std::collections::BTreeMap;
+//! use structible::{ValueCodec, structible};
+//!
+//! #[structible(value_codec)]
+//! pub struct Person {
+//!     pub name: String,
+//!     pub age: Option<u32>,
+//! }
+//!
+//! let mut store = BTreeMap::new();
+//! let mut person = Person::new("Alice".into());
+//! person.set_age(30);
+//! for (field, bytes) in person.encode_entries().unwrap() {
+//!     store.insert(format!("person:42:{}", field), bytes);
+//! }
+//! assert_eq!(store["person:42:age"], b"30");
+//!
+//! let entries = store.iter().map(|(key, bytes)| (&key["person:42:".len()..], bytes));
+//! let person = Person::decode_entries(entries).unwrap();
+//! assert_eq!(person.age(), Some(&30));
+//! ```
+//!
+//! Values are encoded as JSON, without the field name wrapping them, so field
+//! types (and generic catch-all key types) must implement `Serialize` and
+//! `DeserializeOwned`. Entries of a catch-all field are named after the catch-all
+//! and their key, e.g. `extra.role`, with non-string keys in their JSON encoding.
+//!
+//! Combined with `track_changes`, only the changed fields need to be written, by
+//! naming each change's key with [`ValueCodec::field_name`] and encoding its value
+//! with [`ValueCodec::encode_value`]. [`ValueCodec::decode_entries`] fails with
+//! [`CodecError::Validation`] if a required field is missing.
+
+use std::fmt;
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::{BackingMap, IterableMap, Structible, ValidationError};
+
+/// Trait for structs whose fields can be stored one by one as bytes.
+///
+/// It is implemented by `#[structible(value_codec)]`.
+pub trait ValueCodec: Structible {
+    /// Returns the name under which the given field is stored.
+    fn field_name(key: &Self::Field) -> Result<String, CodecError>;
+
+    /// Returns the field stored under the given name.
+    fn parse_field_name(name: &str) -> Result<Self::Field, CodecError>;
+
+    /// Encodes a field's value, without the field's name.
+    fn encode_value(value: &Self::Value) -> Result<Vec<u8>, CodecError>;
+
+    /// Decodes a value of the given field.
+    fn decode_value(key: &Self::Field, bytes: &[u8]) -> Result<Self::Value, CodecError>;
+
+    /// Encodes all present fields as `(name, bytes)` pairs.
+    fn encode_entries(&self) -> Result<Vec<(String, Vec<u8>)>, CodecError>
+    where
+        Self::Backing: IterableMap<Self::Field, Self::Value>,
+    {
+        IterableMap::iter(self.backing())
+            .map(|(key, value)| Ok((Self::field_name(key)?, Self::encode_value(value)?)))
+            .collect()
+    }
+
+    /// Decodes one field stored under `name`.
+    fn decode_entry(name: &str, bytes: &[u8]) -> Result<(Self::Field, Self::Value), CodecError> {
+        let key = Self::parse_field_name(name)?;
+        let value = Self::decode_value(&key, bytes)?;
+        Ok(Self::route_entry(key, value))
+    }
+
+    /// Decodes a struct from `(name, bytes)` pairs, checking that all required
+    /// fields are present.
+    fn decode_entries<I, N, B>(entries: I) -> Result<Self, CodecError>
+    where
+        I: IntoIterator<Item = (N, B)>,
+        N: AsRef<str>,
+        B: AsRef<[u8]>,
+    {
+        let mut backing = Self::Backing::new();
+        for (name, bytes) in entries {
+            let (key, value) = Self::decode_entry(name.as_ref(), bytes.as_ref())?;
+            backing.insert(key, value);
+        }
+        Ok(Self::try_from_backing(backing)?)
+    }
+}
+
+/// The error type of [`ValueCodec`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum CodecError {
+    /// A name did not match any field.
+    UnknownField(String),
+    /// A value or catch-all key could not be encoded or decoded.
+    Json(serde_json::Error),
+    /// The decoded fields did not form a valid struct.
+    Validation(ValidationError),
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodecError::UnknownField(name) => write!(f, "unknown field `{}`", name),
+            CodecError::Json(err) => write!(f, "failed to encode or decode value: {}", err),
+            CodecError::Validation(err) => write!(f, "invalid record: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for CodecError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CodecError::UnknownField(_) => None,
+            CodecError::Json(err) => Some(err),
+            CodecError::Validation(err) => Some(err),
+        }
+    }
+}
+
+impl From<serde_json::Error> for CodecError {
+    fn from(err: serde_json::Error) -> Self {
+        CodecError::Json(err)
+    }
+}
+
+impl From<ValidationError> for CodecError {
+    fn from(err: ValidationError) -> Self {
+        CodecError::Validation(err)
+    }
+}
+
+#[doc(hidden)]
+pub fn __encode<T: Serialize>(value: &T) -> Result<Vec<u8>, CodecError> {
+    Ok(serde_json::to_vec(value)?)
+}
+
+#[doc(hidden)]
+pub fn __decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, CodecError> {
+    Ok(serde_json::from_slice(bytes)?)
+}
+
+/// Decodes a value as `T`, or else as the legacy type `O` converted with `with`.
+#[doc(hidden)]
+pub fn __decode_upgrade<T, O, E, F>(bytes: &[u8], with: F) -> Result<T, CodecError>
+where
+    T: DeserializeOwned,
+    O: DeserializeOwned,
+    E: fmt::Display,
+    F: FnOnce(O) -> Result<T, E>,
+{
+    let mut deserializer = serde_json::Deserializer::from_slice(bytes);
+    let value = crate::serde_adapter::__upgrade_from(&mut deserializer, with)?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+/// Returns the name of a catch-all entry, using the key itself if it encodes as a
+/// string and its JSON encoding otherwise.
+#[doc(hidden)]
+pub fn __unknown_field_name<K: Serialize>(catch_all: &str, key: &K) -> Result<String, CodecError> {
+    match serde_json::to_value(key)? {
+        serde_json::Value::String(key) => Ok(format!("{}.{}", catch_all, key)),
+        other => Ok(format!("{}.{}", catch_all, other)),
+    }
+}
+
+/// Parses the key part of a catch-all entry's name, see [`__unknown_field_name`].
+#[doc(hidden)]
+pub fn __parse_unknown_key<K: DeserializeOwned>(key: &str) -> Result<K, CodecError> {
+    serde_json::from_value(serde_json::Value::String(key.to_owned()))
+        .or_else(|_| serde_json::from_str(key))
+        .map_err(CodecError::from)
+}
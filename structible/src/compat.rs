@@ -0,0 +1,105 @@
+/// The description of a field, as declared in a structible struct.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FieldDescriptor {
+    /// The field's name, without any `r#` prefix.
+    pub name: &'static str,
+    /// The field's declared type, e.g. `Option<u32>`, as written in the struct.
+    pub ty: &'static str,
+    /// The key type of the catch-all field, or `None` for an ordinary field.
+    pub key: Option<&'static str>,
+    /// True if the field is optional.
+    pub optional: bool,
+}
+
+/// Trait for structs that describe their fields.
+///
+/// It is implemented for every structible struct not declared `minimal`, so
+/// that two versions of a struct can be compared with `compat_report()`.
+pub trait Schema {
+    /// The struct's name, without its generics.
+    const NAME: &'static str;
+
+    /// The struct's fields, in declaration order.
+    const FIELDS: &'static [FieldDescriptor];
+}
+
+/// The differences between the fields of two versions of a struct.
+///
+/// Fields are matched by name, and a field whose type, key type, or optionality
+/// differs between the versions is retyped. Types are compared as written, so
+/// renaming a type through an alias retypes its fields. Each list follows the
+/// declaration order of the version the fields are taken from.
+///
+/// ```rust
+/// use structible::structible;
+///
+/// mod v1 {
+///     #[structible::structible]
+///     pub struct Person {
+///         pub name: String,
+///         pub age: Option<u32>,
+///         pub nickname: Option<String>,
+///     }
+/// }
+///
+/// #[structible]
+/// pub struct Person {
+///     pub name: String,
+///     pub age: Option<u64>,
+///     pub email: Option<String>,
+/// }
+///
+/// let report = Person::compat_report::<v1::Person>();
+/// assert_eq!(report.added[0].name, "email");
+/// assert_eq!(report.removed[0].name, "nickname");
+/// assert_eq!(report.retyped[0].1.ty, "Option<u64>");
+/// assert!(!report.is_compatible());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompatReport {
+    /// Fields of the new version missing from the old one.
+    pub added: Vec<FieldDescriptor>,
+    /// Fields of the old version missing from the new one.
+    pub removed: Vec<FieldDescriptor>,
+    /// Fields of both versions that were retyped, as `(old, new)` pairs.
+    pub retyped: Vec<(FieldDescriptor, FieldDescriptor)>,
+}
+
+impl CompatReport {
+    /// Compares the fields of `Old` with those of `New`.
+    pub fn between<Old: Schema, New: Schema>() -> Self {
+        Self::from_fields(Old::FIELDS, New::FIELDS)
+    }
+
+    /// Compares two lists of field descriptors.
+    pub fn from_fields(old: &[FieldDescriptor], new: &[FieldDescriptor]) -> Self {
+        let find =
+            |fields: &[FieldDescriptor], name| fields.iter().find(|f| f.name == name).copied();
+        let mut report = Self::default();
+        for field in new {
+            match find(old, field.name) {
+                None => report.added.push(*field),
+                Some(previous) if previous != *field => report.retyped.push((previous, *field)),
+                Some(_) => {}
+            }
+        }
+        report.removed = old
+            .iter()
+            .filter(|f| find(new, f.name).is_none())
+            .copied()
+            .collect();
+        report
+    }
+
+    /// Returns true if both versions have the same fields.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.retyped.is_empty()
+    }
+
+    /// Returns true if no field was removed or retyped, and every added field is
+    /// optional, so that anything stored by the old version is a valid value of
+    /// the new one.
+    pub fn is_compatible(&self) -> bool {
+        self.removed.is_empty() && self.retyped.is_empty() && self.added.iter().all(|f| f.optional)
+    }
+}
@@ -0,0 +1,120 @@
+//! CSV rows of structible structs, see `#[structible(csv)]`.
+//!
+//! Structs declared with `csv` get `csv_headers()`, `to_csv_record(&headers)`, and
+//! `from_csv_record(&headers, &record)`, which map a struct to and from one row of a
+//! CSV file whose columns may come in any order:
+//!
+//! ```rust
+//! use csv::StringRecord;
+//! use structible::structible;
+//!
+//! #[structible(csv)]
+//! pub struct Person {
+//!     pub name: String,
+//!     pub age: Option<u32>,
+//! }
+//!
+//! let headers = StringRecord::from(vec!["age", "name"]);
+//! let person = Person::new("Alice".into());
+//! let record = person.to_csv_record(&headers).unwrap();
+//! assert_eq!(record, vec!["", "Alice"]);
+//!
+//! let record = StringRecord::from(vec!["30", "Bob"]);
+//! let person = Person::from_csv_record(&headers, &record).unwrap();
+//! assert_eq!(person.age(), Some(&30));
+//! ```
+//!
+//! Cells are written with `Display` and read with `FromStr`. Columns are named
+//! after the fields as declared, or any of their aliases, and catch-all entries
+//! after the catch-all and their key, e.g. `extra.role`. Each field may have only
+//! one column.
+//!
+//! Absent optional fields and catch-all entries are written as empty cells, and
+//! empty cells are read as absent, except in the columns of required fields.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::ValidationError;
+
+/// The error type of the generated CSV methods.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CsvError {
+    /// A header did not name any field.
+    UnknownColumn(String),
+    /// A header named the same field as an earlier one, e.g. through an alias.
+    DuplicateColumn(String),
+    /// A cell, or the key in a catch-all column's header, could not be parsed.
+    Parse {
+        /// The header of the cell's column.
+        column: String,
+        /// The parse error, as formatted by `Display`.
+        message: String,
+    },
+    /// A record had a different number of cells than the header row.
+    UnequalLengths {
+        /// The number of headers.
+        headers: usize,
+        /// The number of cells in the record.
+        cells: usize,
+    },
+    /// The decoded fields did not form a valid struct.
+    Validation(ValidationError),
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CsvError::UnknownColumn(column) => write!(f, "unknown column `{}`", column),
+            CsvError::DuplicateColumn(column) => write!(f, "duplicate column `{}`", column),
+            CsvError::Parse { column, message } => {
+                write!(f, "failed to parse column `{}`: {}", column, message)
+            }
+            CsvError::UnequalLengths { headers, cells } => write!(
+                f,
+                "record has {} cells, but there are {} headers",
+                cells, headers
+            ),
+            CsvError::Validation(err) => write!(f, "invalid record: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for CsvError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CsvError::Validation(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<ValidationError> for CsvError {
+    fn from(err: ValidationError) -> Self {
+        CsvError::Validation(err)
+    }
+}
+
+/// Parses the cell (or catch-all key) `text` of the column `column`.
+#[doc(hidden)]
+pub fn __parse<T>(column: &str, text: &str) -> Result<T, CsvError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    text.parse().map_err(|err: T::Err| CsvError::Parse {
+        column: column.to_owned(),
+        message: err.to_string(),
+    })
+}
+
+/// Checks that a record has a cell for every header.
+#[doc(hidden)]
+pub fn __check_lengths(headers: usize, cells: usize) -> Result<(), CsvError> {
+    if headers == cells {
+        Ok(())
+    } else {
+        Err(CsvError::UnequalLengths { headers, cells })
+    }
+}
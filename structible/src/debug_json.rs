@@ -0,0 +1,301 @@
+//! Deterministic, JSON-like rendering of values for golden-file tests.
+//!
+//! The output of `to_debug_json()` on structs with `#[structible(debug_json)]` is
+//! pretty-printed, and object keys are sorted, so that it doesn't depend on the
+//! iteration order of hash maps or hash sets.
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt::Write;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// Trait for values that can be rendered by `to_debug_json()`.
+///
+/// It is implemented for primitives, strings, `Option`, smart pointers, tuples,
+/// sequences, sets, and maps of such values, and by structs with
+/// `#[structible(debug_json)]`, so that they can be nested.
+pub trait DebugJson {
+    /// Writes the value to `out`, indenting continuation lines by `indent` levels.
+    fn write_debug_json(&self, out: &mut String, indent: usize);
+
+    /// Returns the value as an object key.
+    ///
+    /// Defaults to the rendered value, while strings are used as they are.
+    fn debug_json_key(&self) -> String {
+        to_string(self)
+    }
+}
+
+/// Renders a value as a string.
+pub fn to_string<T: DebugJson + ?Sized>(value: &T) -> String {
+    let mut out = String::new();
+    value.write_debug_json(&mut out, 0);
+    out
+}
+
+fn write_indent(out: &mut String, indent: usize) {
+    for _ in 0..indent {
+        out.push_str("  ");
+    }
+}
+
+fn write_str(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn write_array<'a>(
+    out: &mut String,
+    indent: usize,
+    items: impl ExactSizeIterator<Item = &'a dyn DebugJson>,
+) {
+    if items.len() == 0 {
+        out.push_str("[]");
+        return;
+    }
+    out.push('[');
+    let len = items.len();
+    for (i, item) in items.enumerate() {
+        out.push('\n');
+        write_indent(out, indent + 1);
+        item.write_debug_json(out, indent + 1);
+        if i + 1 < len {
+            out.push(',');
+        }
+    }
+    out.push('\n');
+    write_indent(out, indent);
+    out.push(']');
+}
+
+/// An object of named values, whose entries are sorted by name.
+#[doc(hidden)]
+pub struct __Object<'a>(Vec<(String, &'a dyn DebugJson)>);
+
+impl<'a> __Object<'a> {
+    pub fn new(mut entries: Vec<(String, &'a dyn DebugJson)>) -> Self {
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        Self(entries)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl DebugJson for __Object<'_> {
+    fn write_debug_json(&self, out: &mut String, indent: usize) {
+        if self.0.is_empty() {
+            out.push_str("{}");
+            return;
+        }
+        out.push('{');
+        for (i, (key, value)) in self.0.iter().enumerate() {
+            out.push('\n');
+            write_indent(out, indent + 1);
+            write_str(out, key);
+            out.push_str(": ");
+            value.write_debug_json(out, indent + 1);
+            if i + 1 < self.0.len() {
+                out.push(',');
+            }
+        }
+        out.push('\n');
+        write_indent(out, indent);
+        out.push('}');
+    }
+}
+
+macro_rules! impl_display {
+    ($($ty:ty),*) => {
+        $(
+            impl DebugJson for $ty {
+                fn write_debug_json(&self, out: &mut String, _indent: usize) {
+                    let _ = write!(out, "{}", self);
+                }
+            }
+        )*
+    };
+}
+
+impl_display!(
+    bool, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize
+);
+
+macro_rules! impl_float {
+    ($($ty:ty),*) => {
+        $(
+            impl DebugJson for $ty {
+                fn write_debug_json(&self, out: &mut String, _indent: usize) {
+                    let _ = write!(out, "{:?}", self);
+                }
+            }
+        )*
+    };
+}
+
+impl_float!(f32, f64);
+
+impl DebugJson for () {
+    fn write_debug_json(&self, out: &mut String, _indent: usize) {
+        out.push_str("null");
+    }
+}
+
+impl DebugJson for char {
+    fn write_debug_json(&self, out: &mut String, _indent: usize) {
+        write_str(out, self.encode_utf8(&mut [0; 4]));
+    }
+
+    fn debug_json_key(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl DebugJson for str {
+    fn write_debug_json(&self, out: &mut String, _indent: usize) {
+        write_str(out, self);
+    }
+
+    fn debug_json_key(&self) -> String {
+        self.to_owned()
+    }
+}
+
+impl DebugJson for String {
+    fn write_debug_json(&self, out: &mut String, indent: usize) {
+        self.as_str().write_debug_json(out, indent);
+    }
+
+    fn debug_json_key(&self) -> String {
+        self.clone()
+    }
+}
+
+impl<T: DebugJson> DebugJson for Option<T> {
+    fn write_debug_json(&self, out: &mut String, indent: usize) {
+        match self {
+            Some(value) => value.write_debug_json(out, indent),
+            None => out.push_str("null"),
+        }
+    }
+}
+
+macro_rules! impl_pointer {
+    ($($ty:ty),*) => {
+        $(
+            impl<T: DebugJson + ?Sized> DebugJson for $ty {
+                fn write_debug_json(&self, out: &mut String, indent: usize) {
+                    (**self).write_debug_json(out, indent);
+                }
+
+                fn debug_json_key(&self) -> String {
+                    (**self).debug_json_key()
+                }
+            }
+        )*
+    };
+}
+
+impl_pointer!(&T, Box<T>, Rc<T>, Arc<T>);
+
+impl<T: DebugJson + ToOwned + ?Sized> DebugJson for Cow<'_, T> {
+    fn write_debug_json(&self, out: &mut String, indent: usize) {
+        (**self).write_debug_json(out, indent);
+    }
+
+    fn debug_json_key(&self) -> String {
+        (**self).debug_json_key()
+    }
+}
+
+impl<T: DebugJson> DebugJson for [T] {
+    fn write_debug_json(&self, out: &mut String, indent: usize) {
+        write_array(out, indent, self.iter().map(|v| v as &dyn DebugJson));
+    }
+}
+
+impl<T: DebugJson, const N: usize> DebugJson for [T; N] {
+    fn write_debug_json(&self, out: &mut String, indent: usize) {
+        self.as_slice().write_debug_json(out, indent);
+    }
+}
+
+impl<T: DebugJson> DebugJson for Vec<T> {
+    fn write_debug_json(&self, out: &mut String, indent: usize) {
+        self.as_slice().write_debug_json(out, indent);
+    }
+}
+
+impl<T: DebugJson> DebugJson for VecDeque<T> {
+    fn write_debug_json(&self, out: &mut String, indent: usize) {
+        write_array(out, indent, self.iter().map(|v| v as &dyn DebugJson));
+    }
+}
+
+impl<T: DebugJson> DebugJson for BTreeSet<T> {
+    fn write_debug_json(&self, out: &mut String, indent: usize) {
+        write_array(out, indent, self.iter().map(|v| v as &dyn DebugJson));
+    }
+}
+
+impl<T: DebugJson, S> DebugJson for HashSet<T, S> {
+    fn write_debug_json(&self, out: &mut String, indent: usize) {
+        // Sort by the rendered items, since hash sets have no stable order
+        let mut items: Vec<_> = self.iter().map(|v| (to_string(v), v)).collect();
+        items.sort_by(|a, b| a.0.cmp(&b.0));
+        write_array(out, indent, items.iter().map(|(_, v)| *v as &dyn DebugJson));
+    }
+}
+
+impl<K: DebugJson, V: DebugJson> DebugJson for BTreeMap<K, V> {
+    fn write_debug_json(&self, out: &mut String, indent: usize) {
+        let entries = self
+            .iter()
+            .map(|(k, v)| (k.debug_json_key(), v as &dyn DebugJson))
+            .collect();
+        __Object::new(entries).write_debug_json(out, indent);
+    }
+}
+
+impl<K: DebugJson, V: DebugJson, S> DebugJson for HashMap<K, V, S> {
+    fn write_debug_json(&self, out: &mut String, indent: usize) {
+        let entries = self
+            .iter()
+            .map(|(k, v)| (k.debug_json_key(), v as &dyn DebugJson))
+            .collect();
+        __Object::new(entries).write_debug_json(out, indent);
+    }
+}
+
+macro_rules! impl_tuple {
+    ($($name:ident),+) => {
+        impl<$($name: DebugJson),+> DebugJson for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn write_debug_json(&self, out: &mut String, indent: usize) {
+                let ($($name,)+) = self;
+                let items: &[&dyn DebugJson] = &[$($name),+];
+                write_array(out, indent, items.iter().copied());
+            }
+        }
+    };
+}
+
+impl_tuple!(A);
+impl_tuple!(A, B);
+impl_tuple!(A, B, C);
+impl_tuple!(A, B, C, D);
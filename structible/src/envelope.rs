@@ -0,0 +1,144 @@
+//! Schema-checked message payloads, see `#[structible(envelope)]`.
+//!
+//! Structs with `envelope` get a `SCHEMA_FINGERPRINT` constant, a hash of their
+//! fields' names and types, and `to_envelope()`/`from_envelope()`, which prefix
+//! the struct's JSON encoding (as written by [`serde_adapter`](crate::serde_adapter))
+//! with it, so that a consumer rejects messages written with another schema
+//! instead of misreading them:
+//!
+//! ```rust
+//! use structible::envelope::EnvelopeError;
+//! use structible::structible;
+//!
+//! #[structible(serde_backing, envelope)]
+//! pub struct Person {
+//!     pub name: String,
+//! }
+//!
+//! #[structible(serde_backing, envelope)]
+//! pub struct Order {
+//!     pub id: u64,
+//! }
+//!
+//! let bytes = Person::new("Alice".into()).to_envelope().unwrap();
+//! assert_eq!(Person::from_envelope(&bytes).unwrap().name(), "Alice");
+//! assert!(matches!(
+//!     Order::from_envelope(&bytes),
+//!     Err(EnvelopeError::SchemaMismatch { .. })
+//! ));
+//! ```
+//!
+//! An envelope is a zero magic byte, the fingerprint as 8 big-endian bytes, and
+//! the payload.
+//!
+//! Adding, removing, renaming, or retyping a field changes the fingerprint, but
+//! reordering fields doesn't. Types are compared as written, so `Option<u32>`
+//! and `Option<::core::primitive::u32>` are different schemas.
+
+use std::fmt;
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::{IterableMap, Structible, serde_adapter};
+
+/// The first byte of every envelope.
+const MAGIC: u8 = 0;
+
+/// The length of the envelope header: the magic byte and the fingerprint.
+const HEADER_LEN: usize = 9;
+
+/// The error type of the generated envelope methods.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum EnvelopeError {
+    /// The bytes are too short for an envelope header, or don't start with the
+    /// magic byte.
+    Malformed,
+    /// The envelope was written with a different schema.
+    SchemaMismatch {
+        /// The fingerprint of the schema the reader expects.
+        expected: u64,
+        /// The fingerprint found in the envelope.
+        found: u64,
+    },
+    /// The payload could not be encoded or decoded.
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for EnvelopeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnvelopeError::Malformed => write!(f, "malformed envelope header"),
+            EnvelopeError::SchemaMismatch { expected, found } => write!(
+                f,
+                "envelope schema {:016x} does not match the expected schema {:016x}",
+                found, expected
+            ),
+            EnvelopeError::Json(err) => write!(f, "failed to encode or decode payload: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for EnvelopeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EnvelopeError::Json(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for EnvelopeError {
+    fn from(err: serde_json::Error) -> Self {
+        EnvelopeError::Json(err)
+    }
+}
+
+/// Returns the schema fingerprint of an envelope, without decoding its payload,
+/// e.g. to route messages of several schemas.
+pub fn schema_fingerprint(bytes: &[u8]) -> Result<u64, EnvelopeError> {
+    if bytes.len() < HEADER_LEN || bytes[0] != MAGIC {
+        return Err(EnvelopeError::Malformed);
+    }
+    let mut fingerprint = [0; 8];
+    fingerprint.copy_from_slice(&bytes[1..HEADER_LEN]);
+    Ok(u64::from_be_bytes(fingerprint))
+}
+
+/// Encodes a struct as an envelope carrying `fingerprint`.
+#[doc(hidden)]
+pub fn __encode<T>(fingerprint: u64, value: &T) -> Result<Vec<u8>, EnvelopeError>
+where
+    T: Structible,
+    T::Backing: IterableMap<T::Field, T::Value>,
+    T::Field: Serialize,
+    T::Value: Serialize,
+{
+    let mut bytes = Vec::with_capacity(HEADER_LEN);
+    bytes.push(MAGIC);
+    bytes.extend_from_slice(&fingerprint.to_be_bytes());
+    serde_adapter::serialize(value, &mut serde_json::Serializer::new(&mut bytes))?;
+    Ok(bytes)
+}
+
+/// Decodes a struct from an envelope, checking that it carries `fingerprint`.
+#[doc(hidden)]
+pub fn __decode<T>(fingerprint: u64, bytes: &[u8]) -> Result<T, EnvelopeError>
+where
+    T: Structible,
+    T::Field: DeserializeOwned,
+    T::Value: DeserializeOwned,
+{
+    let found = schema_fingerprint(bytes)?;
+    if found != fingerprint {
+        return Err(EnvelopeError::SchemaMismatch {
+            expected: fingerprint,
+            found,
+        });
+    }
+    let mut deserializer = serde_json::Deserializer::from_slice(&bytes[HEADER_LEN..]);
+    let value = serde_adapter::deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(value)
+}
@@ -0,0 +1,145 @@
+use std::borrow::Cow;
+use std::fmt;
+
+/// An error returned when a structible value violates one of its declared constraints.
+///
+/// This is the default error type of the generated `validate()` method and of the
+/// fallible constructor. Structs that declare their own error type with
+/// `#[structible(error = MyError)]` can still return `ValidationError` from their
+/// invariant function by implementing `From<ValidationError>` for `MyError`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ValidationError {
+    /// A struct-level invariant rejected the value.
+    Invariant(Cow<'static, str>),
+    /// A field was present without another field it requires.
+    MissingDependency {
+        /// The field declaring the `requires` constraint.
+        field: &'static str,
+        /// The required field that was absent.
+        requires: &'static str,
+    },
+    /// Two mutually exclusive fields were present at the same time.
+    Conflict {
+        /// The field being checked.
+        field: &'static str,
+        /// The conflicting field that was also present.
+        conflicts_with: &'static str,
+    },
+    /// A required field was absent, e.g. after applying a change log.
+    MissingRequired {
+        /// The absent required field.
+        field: &'static str,
+    },
+    /// A change set a field to a value belonging to a different field.
+    MismatchedChange {
+        /// The field named by the change's key.
+        field: &'static str,
+    },
+    /// A field's value lay outside its declared `range`.
+    OutOfRange {
+        /// The field whose value was out of range.
+        field: &'static str,
+        /// The declared range, as written in the attribute.
+        range: &'static str,
+    },
+    /// The length of a field's value lay outside its declared `len`, or a
+    /// `non_empty` field's value was empty.
+    InvalidLength {
+        /// The field whose value had an invalid length.
+        field: &'static str,
+        /// The declared length range, as written in the attribute, or `1..` for
+        /// `non_empty`.
+        len: &'static str,
+    },
+    /// A field's value didn't match its declared `matches` pattern.
+    PatternMismatch {
+        /// The field whose value didn't match.
+        field: &'static str,
+        /// The declared pattern.
+        pattern: &'static str,
+    },
+    /// A field requested as a metric label was absent.
+    MissingLabel {
+        /// The absent field, or the catch-all for an absent catch-all entry.
+        field: &'static str,
+    },
+    /// A catch-all key was the name of a known field.
+    ReservedKey {
+        /// The known field named by the key.
+        field: &'static str,
+    },
+    /// A catch-all's `validate_key` function rejected a key.
+    InvalidKey(Cow<'static, str>),
+    /// An optional field was absent when read with its `<getter>_or_err()`.
+    Absent {
+        /// The absent field.
+        field: &'static str,
+        /// Why the field was expected, as declared by its `required_context`.
+        context: &'static str,
+    },
+}
+
+impl ValidationError {
+    /// Creates an error for a failed struct-level invariant.
+    pub fn invariant(message: impl Into<Cow<'static, str>>) -> Self {
+        Self::Invariant(message.into())
+    }
+
+    /// Creates an error for a catch-all key rejected by its `validate_key` function.
+    pub fn invalid_key(message: impl Into<Cow<'static, str>>) -> Self {
+        Self::InvalidKey(message.into())
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invariant(message) => write!(f, "invariant violated: {}", message),
+            Self::MissingDependency { field, requires } => {
+                write!(f, "field `{}` requires `{}` to be present", field, requires)
+            }
+            Self::Conflict {
+                field,
+                conflicts_with,
+            } => write!(f, "field `{}` conflicts with `{}`", field, conflicts_with),
+            Self::MissingRequired { field } => write!(f, "required field `{}` is missing", field),
+            Self::MismatchedChange { field } => {
+                write!(
+                    f,
+                    "change to field `{}` carries another field's value",
+                    field
+                )
+            }
+            Self::OutOfRange { field, range } => {
+                write!(f, "field `{}` is outside the range `{}`", field, range)
+            }
+            Self::InvalidLength { field, len } => {
+                write!(
+                    f,
+                    "length of field `{}` is outside the range `{}`",
+                    field, len
+                )
+            }
+            Self::PatternMismatch { field, pattern } => {
+                write!(
+                    f,
+                    "field `{}` does not match the pattern `{}`",
+                    field, pattern
+                )
+            }
+            Self::MissingLabel { field } => {
+                write!(f, "label field `{}` is missing", field)
+            }
+            Self::ReservedKey { field } => {
+                write!(f, "key `{}` is reserved for the known field", field)
+            }
+            Self::InvalidKey(message) => write!(f, "invalid key: {}", message),
+            Self::Absent { field, context } => {
+                write!(f, "field `{}` is absent: {}", field, context)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
@@ -0,0 +1,368 @@
+//! C-compatible facades of structible structs, see `#[structible(ffi)]`.
+//!
+//! Structs declared with `ffi` get a `#[repr(C)]` facade named `<Struct>Ffi`, with
+//! one field per known field, and `to_ffi()` and `from_ffi()` converting to and
+//! from it:
+//!
+//! ```rust
+//! use structible::structible;
+//!
+//! #[structible(ffi)]
+//! pub struct Person {
+//!     pub name: String,
+//!     pub age: Option<u32>,
+//! }
+//!
+//! let mut person = Person::new("Alice".into());
+//! person.set_age(30);
+//!
+//! let ffi = person.to_ffi();
+//! assert_eq!(ffi.name.len, 5);
+//! assert_eq!(ffi.age.get(), Some(30));
+//!
+//! let copy = unsafe { Person::from_ffi(&ffi) }.unwrap();
+//! assert_eq!(copy, person);
+//! ```
+//!
+//! Field types are mapped by [`FfiValue`]: numbers are passed as is, `bool` as a
+//! `u8` read back as `!= 0`, strings as an [`FfiStr`], vectors of numbers as an
+//! [`FfiSlice`], and optional fields as an [`FfiOption`]. A facade borrows the
+//! struct it was created from, and `from_ffi()` copies the values it points to.
+//!
+//! A C header declaring the facade and its field types could read:
+//!
+//! ```c
+//! typedef struct { const uint8_t *ptr; size_t len; } FfiStr;
+//! typedef struct { uint8_t present; uint32_t value; } FfiOption_u32;
+//! typedef struct { FfiStr name; FfiOption_u32 age; } PersonFfi;
+//! ```
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem::MaybeUninit;
+use std::str::Utf8Error;
+
+/// The error type of `from_ffi()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FfiError {
+    /// A string or slice of non-zero length had a null pointer.
+    NullPointer,
+    /// A string was not valid UTF-8.
+    InvalidUtf8(Utf8Error),
+    /// The value of a field could not be read.
+    Field {
+        /// The name of the field.
+        name: &'static str,
+        /// The error reading its value.
+        error: Box<FfiError>,
+    },
+}
+
+impl FfiError {
+    /// Wraps this error as the error of the field `name`.
+    pub fn in_field(self, name: &'static str) -> Self {
+        FfiError::Field {
+            name,
+            error: Box::new(self),
+        }
+    }
+}
+
+impl fmt::Display for FfiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FfiError::NullPointer => write!(f, "null pointer with a non-zero length"),
+            FfiError::InvalidUtf8(err) => write!(f, "invalid UTF-8: {}", err),
+            FfiError::Field { name, error } => write!(f, "field `{}`: {}", name, error),
+        }
+    }
+}
+
+impl std::error::Error for FfiError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FfiError::InvalidUtf8(err) => Some(err),
+            FfiError::Field { error, .. } => Some(error),
+            FfiError::NullPointer => None,
+        }
+    }
+}
+
+/// A type that can be passed across a C boundary as its [`Repr`](FfiValue::Repr).
+///
+/// Implemented for numbers, `bool`, `String`, vectors of numbers, and structs
+/// declared with `#[structible(ffi)]`. Implementations must make `Repr` a type
+/// with a C-compatible layout.
+pub trait FfiValue: Sized {
+    /// The C-compatible representation, borrowing from the value for `'a`.
+    type Repr<'a>: Copy + fmt::Debug
+    where
+        Self: 'a;
+
+    /// Returns the C-compatible representation of this value.
+    fn to_ffi(&self) -> Self::Repr<'_>;
+
+    /// Reads a value back from its C-compatible representation, copying the data
+    /// it points to.
+    ///
+    /// # Safety
+    ///
+    /// Any pointer in `repr` with a non-zero length must be properly aligned and
+    /// valid for reads of that many initialized values of its element type, which
+    /// must not be mutated during the call.
+    unsafe fn from_ffi(repr: &Self::Repr<'_>) -> Result<Self, FfiError>;
+}
+
+macro_rules! ffi_primitive {
+    ($($ty:ty),*) => {
+        $(
+            impl FfiValue for $ty {
+                type Repr<'a> = $ty;
+
+                fn to_ffi(&self) -> $ty {
+                    *self
+                }
+
+                unsafe fn from_ffi(repr: &$ty) -> Result<Self, FfiError> {
+                    Ok(*repr)
+                }
+            }
+
+            impl FfiValue for Vec<$ty> {
+                type Repr<'a> = FfiSlice<'a, $ty>;
+
+                fn to_ffi(&self) -> FfiSlice<'_, $ty> {
+                    FfiSlice::new(self)
+                }
+
+                unsafe fn from_ffi(repr: &FfiSlice<'_, $ty>) -> Result<Self, FfiError> {
+                    // SAFETY: guaranteed by the caller
+                    unsafe { repr.as_slice() }.map(<[$ty]>::to_vec)
+                }
+            }
+        )*
+    };
+}
+
+ffi_primitive!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64);
+
+// A C caller may write any byte into a `bool`, which would be undefined behavior to
+// read as a Rust `bool`, so they cross as `u8` instead
+impl FfiValue for bool {
+    type Repr<'a> = u8;
+
+    fn to_ffi(&self) -> u8 {
+        u8::from(*self)
+    }
+
+    unsafe fn from_ffi(repr: &u8) -> Result<Self, FfiError> {
+        Ok(*repr != 0)
+    }
+}
+
+impl FfiValue for Vec<bool> {
+    type Repr<'a> = FfiSlice<'a, u8>;
+
+    fn to_ffi(&self) -> FfiSlice<'_, u8> {
+        // `bool` has the size and alignment of `u8`, and is always a valid `u8`
+        FfiSlice::from_raw_parts(self.as_ptr().cast(), self.len())
+    }
+
+    unsafe fn from_ffi(repr: &FfiSlice<'_, u8>) -> Result<Self, FfiError> {
+        // SAFETY: guaranteed by the caller
+        let bytes = unsafe { repr.as_slice() }?;
+        Ok(bytes.iter().map(|&byte| byte != 0).collect())
+    }
+}
+
+impl FfiValue for String {
+    type Repr<'a> = FfiStr<'a>;
+
+    fn to_ffi(&self) -> FfiStr<'_> {
+        FfiStr::new(self)
+    }
+
+    unsafe fn from_ffi(repr: &FfiStr<'_>) -> Result<Self, FfiError> {
+        // SAFETY: guaranteed by the caller
+        unsafe { repr.as_str() }.map(str::to_owned)
+    }
+}
+
+/// A borrowed slice, as a pointer and a length.
+#[repr(C)]
+pub struct FfiSlice<'a, T> {
+    /// The first element, which may be null if `len` is zero.
+    pub ptr: *const T,
+    /// The number of elements.
+    pub len: usize,
+    marker: PhantomData<&'a [T]>,
+}
+
+impl<'a, T> FfiSlice<'a, T> {
+    /// Borrows `slice`.
+    pub fn new(slice: &'a [T]) -> Self {
+        FfiSlice {
+            ptr: slice.as_ptr(),
+            len: slice.len(),
+            marker: PhantomData,
+        }
+    }
+
+    /// Creates a slice of `len` elements at `ptr`, e.g. one received from C.
+    pub fn from_raw_parts(ptr: *const T, len: usize) -> Self {
+        FfiSlice {
+            ptr,
+            len,
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns the slice pointed to.
+    ///
+    /// # Safety
+    ///
+    /// If `len` is non-zero, `ptr` must be null or properly aligned for `T` and
+    /// valid for reads of `len` consecutive, initialized values of `T`, which must
+    /// not be mutated for `'a`. The slice must not be larger than `isize::MAX` bytes.
+    pub unsafe fn as_slice(&self) -> Result<&'a [T], FfiError> {
+        if self.len == 0 {
+            return Ok(&[]);
+        }
+        if self.ptr.is_null() {
+            return Err(FfiError::NullPointer);
+        }
+        // SAFETY: guaranteed by the caller
+        Ok(unsafe { std::slice::from_raw_parts(self.ptr, self.len) })
+    }
+}
+
+impl<T> Clone for FfiSlice<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for FfiSlice<'_, T> {}
+
+impl<T> fmt::Debug for FfiSlice<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FfiSlice")
+            .field("ptr", &self.ptr)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+/// A borrowed UTF-8 string, as a pointer to its bytes and their length.
+///
+/// The bytes are not nul-terminated.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct FfiStr<'a> {
+    /// The first byte, which may be null if `len` is zero.
+    pub ptr: *const u8,
+    /// The number of bytes.
+    pub len: usize,
+    marker: PhantomData<&'a str>,
+}
+
+impl<'a> FfiStr<'a> {
+    /// Borrows `s`.
+    pub fn new(s: &'a str) -> Self {
+        FfiStr {
+            ptr: s.as_ptr(),
+            len: s.len(),
+            marker: PhantomData,
+        }
+    }
+
+    /// Creates a string of `len` bytes at `ptr`, e.g. one received from C.
+    pub fn from_raw_parts(ptr: *const u8, len: usize) -> Self {
+        FfiStr {
+            ptr,
+            len,
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns the string pointed to.
+    ///
+    /// The bytes need not be valid UTF-8: they are checked with
+    /// [`std::str::from_utf8`], failing with [`FfiError::InvalidUtf8`].
+    ///
+    /// # Safety
+    ///
+    /// If `len` is non-zero, `ptr` must be null or valid for reads of `len`
+    /// initialized bytes, which must not be mutated for `'a`. The string must not be
+    /// larger than `isize::MAX` bytes.
+    pub unsafe fn as_str(&self) -> Result<&'a str, FfiError> {
+        let bytes: FfiSlice<'a, u8> = FfiSlice::from_raw_parts(self.ptr, self.len);
+        // SAFETY: guaranteed by the caller
+        let bytes = unsafe { bytes.as_slice() }?;
+        std::str::from_utf8(bytes).map_err(FfiError::InvalidUtf8)
+    }
+}
+
+impl fmt::Debug for FfiStr<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FfiStr")
+            .field("ptr", &self.ptr)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+/// An optional value, as a `u8` tag followed by the value.
+///
+/// The value is zeroed when absent, and present if the tag is not zero. Its fields
+/// are private so that a present value is always initialized; C code sees them as
+/// `present` and `value`.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct FfiOption<T: Copy> {
+    present: u8,
+    value: MaybeUninit<T>,
+}
+
+impl<T: Copy> FfiOption<T> {
+    /// Returns a present value.
+    pub fn some(value: T) -> Self {
+        FfiOption {
+            present: 1,
+            value: MaybeUninit::new(value),
+        }
+    }
+
+    /// Returns an absent value.
+    pub fn none() -> Self {
+        FfiOption {
+            present: 0,
+            value: MaybeUninit::zeroed(),
+        }
+    }
+
+    /// Returns true if a value is present.
+    pub fn is_present(&self) -> bool {
+        self.present != 0
+    }
+
+    /// Returns the value, if present.
+    pub fn get(&self) -> Option<T> {
+        // SAFETY: the constructors initialize `value` when setting `present`
+        self.is_present()
+            .then(|| unsafe { self.value.assume_init() })
+    }
+}
+
+impl<T: Copy> From<Option<T>> for FfiOption<T> {
+    fn from(value: Option<T>) -> Self {
+        value.map_or_else(FfiOption::none, FfiOption::some)
+    }
+}
+
+impl<T: Copy + fmt::Debug> fmt::Debug for FfiOption<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("FfiOption").field(&self.get()).finish()
+    }
+}
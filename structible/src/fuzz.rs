@@ -0,0 +1,86 @@
+//! Round-trip fuzzing of structible structs, see `#[structible(fuzz)]`.
+//!
+//! Structs with `fuzz` implement [`arbitrary::Arbitrary`] and get a
+//! `fuzz_roundtrip(data)` function, which builds a struct from the fuzzer's bytes,
+//! encodes it as JSON with [`serde_adapter`](crate::serde_adapter), decodes it
+//! again, and checks that both are equal:
+//!
+//! ```rust,ignore
+//! #![no_main]
+//!
+//! libfuzzer_sys::fuzz_target!(|data: &[u8]| {
+//!     Person::fuzz_roundtrip(data).unwrap();
+//! });
+//! ```
+//!
+//! The struct needs `serde_backing`, and its field types (and catch-all key types)
+//! must implement `Arbitrary` and serde's traits. Validation is not run on
+//! arbitrary structs.
+
+use std::fmt::{self, Debug};
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::{IterableMap, Structible, serde_adapter};
+
+/// The error type of the generated `fuzz_roundtrip()` functions.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum RoundtripError {
+    /// The struct could not be encoded.
+    Serialize(serde_json::Error),
+    /// The encoded struct could not be decoded.
+    Deserialize(serde_json::Error),
+    /// The decoded struct differs from the original one.
+    Mismatch {
+        /// The `Debug` output of the original struct.
+        original: String,
+        /// The `Debug` output of the decoded struct.
+        decoded: String,
+    },
+}
+
+impl fmt::Display for RoundtripError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RoundtripError::Serialize(err) => write!(f, "failed to encode struct: {}", err),
+            RoundtripError::Deserialize(err) => write!(f, "failed to decode struct: {}", err),
+            RoundtripError::Mismatch { original, decoded } => {
+                write!(f, "decoded {} differs from original {}", decoded, original)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RoundtripError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RoundtripError::Serialize(err) | RoundtripError::Deserialize(err) => Some(err),
+            RoundtripError::Mismatch { .. } => None,
+        }
+    }
+}
+
+/// Encodes and decodes a struct, checking that the result is equal to it.
+#[doc(hidden)]
+pub fn __roundtrip<T>(value: &T) -> Result<(), RoundtripError>
+where
+    T: Structible + PartialEq + Debug,
+    T::Backing: IterableMap<T::Field, T::Value>,
+    T::Field: Serialize + DeserializeOwned,
+    T::Value: Serialize + DeserializeOwned,
+{
+    let mut bytes = Vec::new();
+    serde_adapter::serialize(value, &mut serde_json::Serializer::new(&mut bytes))
+        .map_err(RoundtripError::Serialize)?;
+    let decoded: T = serde_adapter::deserialize(&mut serde_json::Deserializer::from_slice(&bytes))
+        .map_err(RoundtripError::Deserialize)?;
+    if decoded != *value {
+        return Err(RoundtripError::Mismatch {
+            original: format!("{:?}", value),
+            decoded: format!("{:?}", decoded),
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,149 @@
+//! Golden files of struct schemas, see [`emit_schemas!`](crate::emit_schemas).
+//!
+//! Each struct's [`Schema`] is written to `<dir>/<Struct>.json`, listing its
+//! fields with their declared types in declaration order:
+//!
+//! ```json
+//! {
+//!   "fields": [
+//!     {
+//!       "key": null,
+//!       "name": "name",
+//!       "optional": false,
+//!       "type": "String"
+//!     }
+//!   ],
+//!   "name": "Person"
+//! }
+//! ```
+//!
+//! Object keys are sorted, so files only change with the schema, and unchanged
+//! files aren't rewritten. Generic structs are written under their name alone,
+//! so only one instantiation of each can be listed, and files of structs that
+//! are no longer listed are left in place.
+//!
+//! Committing the directory and diffing it in CI catches schema changes that
+//! weren't meant to happen.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::debug_json::{self, __Object, DebugJson};
+use crate::{FieldDescriptor, Schema};
+
+impl DebugJson for FieldDescriptor {
+    fn write_debug_json(&self, out: &mut String, indent: usize) {
+        __Object::new(vec![
+            ("name".into(), &self.name),
+            ("type".into(), &self.ty),
+            ("key".into(), &self.key),
+            ("optional".into(), &self.optional),
+        ])
+        .write_debug_json(out, indent);
+    }
+}
+
+/// Renders the schema of `S` as JSON, with sorted object keys.
+pub fn schema_json<S: Schema + ?Sized>() -> String {
+    render(S::NAME, S::FIELDS)
+}
+
+fn render(name: &str, fields: &[FieldDescriptor]) -> String {
+    let mut json = debug_json::to_string(&__Object::new(vec![
+        ("name".into(), &name),
+        ("fields".into(), &fields),
+    ]));
+    json.push('\n');
+    json
+}
+
+/// Writes the schema of `S` to `<dir>/<S::NAME>.json`, creating `dir` if needed,
+/// and returns the path of the file.
+///
+/// A file that already holds the same schema is left untouched.
+pub fn write_schema<S: Schema + ?Sized>(dir: &Path) -> io::Result<PathBuf> {
+    write(dir, S::NAME, S::FIELDS)
+}
+
+/// Writes the schema of every struct in [`registry`](crate::registry), like
+/// [`write_schema`].
+///
+/// Structs of the same name in different modules would overwrite each other's
+/// file, so they are rejected with an `InvalidInput` error.
+#[cfg(feature = "inventory")]
+pub fn write_registered(dir: impl AsRef<Path>) -> io::Result<()> {
+    let structs = crate::registry::structs();
+    let mut names: Vec<_> = structs.iter().map(|r| r.name).collect();
+    names.sort_unstable();
+    if let Some(name) = names.windows(2).find(|w| w[0] == w[1]).map(|w| w[0]) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("several registered structs are named `{}`", name),
+        ));
+    }
+    for registration in structs {
+        write(dir.as_ref(), registration.name, registration.fields)?;
+    }
+    Ok(())
+}
+
+fn write(dir: &Path, name: &str, fields: &[FieldDescriptor]) -> io::Result<PathBuf> {
+    let path = dir.join(format!("{}.json", name));
+    let json = render(name, fields);
+    if std::fs::read_to_string(&path).is_ok_and(|existing| existing == json) {
+        return Ok(path);
+    }
+    std::fs::create_dir_all(dir)?;
+    std::fs::write(&path, json)?;
+    Ok(path)
+}
+
+/// Calls each of `writers`, the `write_schema` of a struct, with `dir`.
+#[doc(hidden)]
+pub fn __write_all(
+    dir: impl AsRef<Path>,
+    writers: &[fn(&Path) -> io::Result<PathBuf>],
+) -> io::Result<()> {
+    for write in writers {
+        write(dir.as_ref())?;
+    }
+    Ok(())
+}
+
+/// Writes the schemas of the given structs to a directory, as golden files.
+///
+/// Each struct's schema goes to `<dir>/<Struct>.json`, see [`golden`](crate::golden).
+/// Evaluates to an `io::Result<()>`:
+///
+/// ```rust,no_run
+/// use structible::structible;
+///
+/// #[structible]
+/// pub struct Person {
+///     pub name: String,
+///     pub age: Option<u32>,
+/// }
+///
+/// #[structible]
+/// pub struct Order {
+///     pub id: u64,
+/// }
+///
+/// structible::emit_schemas!("schemas", Person, Order).unwrap();
+/// ```
+///
+/// With the `inventory` feature, `emit_schemas!(dir)` writes the schemas of
+/// every struct in [`registry`](crate::registry) instead, see
+/// [`write_registered`](crate::golden::write_registered).
+///
+/// Build scripts can't name the types of the crate they build, so this is
+/// usually run from a test, with the directory checked into version control.
+#[macro_export]
+macro_rules! emit_schemas {
+    ($dir:expr $(,)?) => {
+        $crate::golden::write_registered($dir)
+    };
+    ($dir:expr, $($ty:ty),+ $(,)?) => {
+        $crate::golden::__write_all($dir, &[$($crate::golden::write_schema::<$ty>),+])
+    };
+}
@@ -0,0 +1,69 @@
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+/// A required field taken out of a struct, which is put back when the guard is dropped.
+///
+/// Returned by the generated `take_<field>_scoped()` methods. The guard derefs to
+/// the owned value, which can be swapped with [`replace`](FieldGuard::replace), or
+/// moved out with [`into_inner`](FieldGuard::into_inner) by putting a replacement
+/// back, since a required field can't be left absent. The struct stays borrowed
+/// until the guard is dropped, so the missing field can't be observed. Putting the
+/// value back is a write, e.g. it is recorded with `track_changes`, and happens even
+/// when unwinding from a panic. Leaking the guard (e.g. with `mem::forget`) leaves
+/// the field absent.
+pub struct FieldGuard<'a, S, T> {
+    owner: &'a mut S,
+    value: Option<T>,
+    restore: fn(&mut S, T),
+}
+
+impl<'a, S, T> FieldGuard<'a, S, T> {
+    /// Creates a guard holding `value`, which calls `restore` on `owner` when dropped.
+    #[doc(hidden)]
+    pub fn __new(owner: &'a mut S, value: T, restore: fn(&mut S, T)) -> Self {
+        Self {
+            owner,
+            value: Some(value),
+            restore,
+        }
+    }
+
+    /// Replaces the value put back into the struct, returning the current one.
+    pub fn replace(&mut self, value: T) -> T {
+        std::mem::replace(&mut **self, value)
+    }
+
+    /// Consumes the guard, putting `replacement` back into the struct and returning
+    /// the owned value.
+    pub fn into_inner(mut self, replacement: T) -> T {
+        self.replace(replacement)
+    }
+}
+
+impl<S, T> Deref for FieldGuard<'_, S, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value.as_ref().expect("value is only taken on drop")
+    }
+}
+
+impl<S, T> DerefMut for FieldGuard<'_, S, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value.as_mut().expect("value is only taken on drop")
+    }
+}
+
+impl<S, T> Drop for FieldGuard<'_, S, T> {
+    fn drop(&mut self) {
+        if let Some(value) = self.value.take() {
+            (self.restore)(self.owner, value);
+        }
+    }
+}
+
+impl<S, T: fmt::Debug> fmt::Debug for FieldGuard<'_, S, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("FieldGuard").field(&**self).finish()
+    }
+}
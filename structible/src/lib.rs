@@ -3,7 +3,166 @@
 use std::collections::{BTreeMap, HashMap};
 use std::hash::Hash;
 
-pub use structible_macros::structible;
+#[cfg(feature = "arrow")]
+pub use arrow::{ArrowRecord, ArrowValue};
+pub use async_backing::AsyncBackingMap;
+pub use change::FieldChange;
+pub use clock::{Clock, SystemClock};
+#[cfg(feature = "codec")]
+pub use codec::ValueCodec;
+pub use compat::{CompatReport, FieldDescriptor, Schema};
+pub use debug_json::DebugJson;
+pub use error::ValidationError;
+pub use guard::FieldGuard;
+pub use length::Length;
+pub use mapper::FieldMapper;
+pub use source::Source;
+pub use stamp::Stamp;
+pub use structible_macros::{Structible, structible};
+pub use upgrade::Upgrade;
+
+#[cfg(feature = "arrow")]
+pub mod arrow;
+#[doc(hidden)]
+pub mod assert;
+mod async_backing;
+#[cfg(feature = "axum")]
+pub mod axum;
+mod change;
+mod clock;
+#[cfg(feature = "codec")]
+pub mod codec;
+mod compat;
+#[cfg(feature = "csv")]
+pub mod csv;
+pub mod debug_json;
+#[cfg(feature = "serde")]
+pub mod envelope;
+mod error;
+pub mod ffi;
+#[cfg(feature = "fuzz")]
+pub mod fuzz;
+pub mod golden;
+mod guard;
+mod length;
+mod mapper;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+#[cfg(feature = "tracing")]
+pub mod record;
+#[cfg(feature = "redis")]
+pub mod redis;
+#[cfg(feature = "inventory")]
+pub mod registry;
+#[cfg(feature = "serde")]
+pub mod serde_adapter;
+mod source;
+mod stamp;
+#[cfg(feature = "typescript")]
+pub mod typescript;
+mod upgrade;
+
+/// Registers a struct, if the `inventory` feature is enabled.
+#[cfg(not(feature = "inventory"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __register_schema {
+    ($ty:ident) => {};
+}
+
+/// Evaluates the body of a getter of a `no_panic` struct, checking that it can't
+/// panic if the `no-panic` feature is enabled.
+#[cfg(not(feature = "no-panic"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __no_panic {
+    ($name:literal, $body:expr) => {
+        ($body)()
+    };
+}
+
+/// Evaluates the body of a getter of a `no_panic` struct, checking that it can't
+/// panic if the `no-panic` feature is enabled.
+///
+/// The check needs optimizations, which builds with debug assertions usually
+/// lack, so it fails to compile in them rather than passing unchecked.
+#[cfg(all(feature = "no-panic", debug_assertions))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __no_panic {
+    ($name:literal, $body:expr) => {
+        ::core::compile_error!(
+            "the `no-panic` feature checks getters when linking optimized code, so it requires a build without debug assertions, e.g. `--release`"
+        )
+    };
+}
+
+/// Evaluates the body of a getter of a `no_panic` struct, checking that it can't
+/// panic if the `no-panic` feature is enabled.
+///
+/// A guard is dropped if the body unwinds, and its drop calls a function that isn't
+/// defined anywhere. Once optimized, the call only remains if the body can panic,
+/// in which case linking fails with an error naming the getter.
+#[cfg(all(feature = "no-panic", not(debug_assertions)))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __no_panic {
+    ($name:literal, $body:expr) => {{
+        struct __NoPanic;
+        impl ::core::ops::Drop for __NoPanic {
+            fn drop(&mut self) {
+                unsafe extern "C" {
+                    #[link_name = concat!("\n\nERROR[structible]: getter `", $name, "` may panic\n\n")]
+                    fn trigger() -> !;
+                }
+                unsafe { trigger() }
+            }
+        }
+        let guard = __NoPanic;
+        let result = ($body)();
+        ::core::mem::forget(guard);
+        result
+    }};
+}
+
+#[doc(hidden)]
+pub mod __private {
+    #[cfg(feature = "fuzz")]
+    pub use arbitrary;
+    #[cfg(feature = "arrow")]
+    pub use arrow_array;
+    #[cfg(feature = "arrow")]
+    pub use arrow_schema;
+    #[cfg(feature = "async-graphql")]
+    pub use async_graphql;
+    #[cfg(feature = "axum")]
+    pub use axum;
+    #[cfg(feature = "criterion")]
+    pub use criterion;
+    #[cfg(feature = "csv")]
+    pub use csv;
+    #[cfg(feature = "fake")]
+    pub use fake;
+    #[cfg(feature = "inventory")]
+    pub use inventory;
+    #[cfg(feature = "metrics")]
+    pub use metrics;
+    #[cfg(feature = "rand")]
+    pub use rand;
+    #[cfg(feature = "rayon")]
+    pub use rayon;
+    #[cfg(feature = "regex")]
+    pub use regex;
+    #[cfg(feature = "serde")]
+    pub use serde;
+    #[cfg(feature = "tracing")]
+    pub use tracing;
+    #[cfg(feature = "utoipa")]
+    pub use utoipa;
+
+    pub use crate::assert;
+    pub use crate::mapper::MapField;
+}
 
 /// Trait for types that can back a structible struct.
 ///
@@ -193,3 +352,143 @@ where
         BTreeMap::iter_mut(self)
     }
 }
+
+/// Trait implemented by every (non-async) `#[structible]` struct.
+///
+/// It names the pieces generated for a struct at the type level, so that generic
+/// code can be written over all structible structs:
+///
+/// ```rust
+/// use structible::{BackingMap, Structible, structible};
+///
+/// fn field_count<T: Structible>(value: &T) -> usize {
+///     value.backing().len()
+/// }
+///
+/// #[structible]
+/// pub struct Person {
+///     pub name: String,
+///     pub email: Option<String>,
+/// }
+///
+/// assert_eq!(field_count(&Person::new("Alice".into())), 1);
+/// ```
+///
+/// Since `Backing` is public, a public struct over a private backing type must
+/// opt out with `#[structible(no_structible)]`.
+pub trait Structible: Sized {
+    /// The backing map type, e.g. `HashMap<Self::Field, Self::Value>`.
+    type Backing: BackingMap<Self::Field, Self::Value>;
+
+    /// The field enum, with one variant per field, used as the map keys.
+    type Field;
+
+    /// The value enum, wrapping each field's type, used as the map values.
+    type Value;
+
+    /// Returns the backing map.
+    fn backing(&self) -> &Self::Backing;
+
+    /// Consumes the struct, returning its backing map.
+    fn into_backing(self) -> Self::Backing;
+
+    /// Creates a struct over the given backing map, checking that all required
+    /// fields are present.
+    ///
+    /// The entries are trusted to pair each key with a value of the same field
+    /// (see [`Structible::is_valid_entry`]), and the struct's declared validation
+    /// is not run. A struct with `track_changes` starts with an empty change log,
+    /// and one with `lww` starts with no stamps.
+    fn try_from_backing(backing: Self::Backing) -> Result<Self, ValidationError>;
+
+    /// Returns true if `value` is a value of the field `key`.
+    fn is_valid_entry(key: &Self::Field, value: &Self::Value) -> bool;
+
+    /// Maps an entry decoded from outside the struct before it is stored.
+    ///
+    /// This is called by the serde adapter and by `ValueCodec` for every
+    /// decoded entry, and by the axum extractors for every catch-all entry. The
+    /// default keeps entries as they are, while a catch-all declared with
+    /// `promote_known` moves entries whose key names a known field into that field.
+    fn route_entry(key: Self::Field, value: Self::Value) -> (Self::Field, Self::Value) {
+        (key, value)
+    }
+}
+
+/// Deprecating a field deprecates its generated accessors, so that each of them fails
+/// to compile under `deny(deprecated)`:
+///
+/// ```compile_fail
+/// # #![deny(deprecated)]
+/// # use structible::structible;
+/// # #[structible]
+/// # pub struct Account {
+/// #     pub id: u64,
+/// #     #[deprecated(note = "use `id` instead")]
+/// #     pub login: Option<String>,
+/// # }
+/// # let mut account = Account::new(1);
+/// account.login();
+/// ```
+///
+/// ```compile_fail
+/// # #![deny(deprecated)]
+/// # use structible::structible;
+/// # #[structible]
+/// # pub struct Account {
+/// #     pub id: u64,
+/// #     #[deprecated(note = "use `id` instead")]
+/// #     pub login: Option<String>,
+/// # }
+/// # let mut account = Account::new(1);
+/// account.login_mut();
+/// ```
+///
+/// ```compile_fail
+/// # #![deny(deprecated)]
+/// # use structible::structible;
+/// # #[structible]
+/// # pub struct Account {
+/// #     pub id: u64,
+/// #     #[deprecated(note = "use `id` instead")]
+/// #     pub login: Option<String>,
+/// # }
+/// # let mut account = Account::new(1);
+/// account.set_login("alice".into());
+/// ```
+///
+/// ```compile_fail
+/// # #![deny(deprecated)]
+/// # use structible::structible;
+/// # #[structible]
+/// # pub struct Account {
+/// #     pub id: u64,
+/// #     #[deprecated(note = "use `id` instead")]
+/// #     pub login: Option<String>,
+/// # }
+/// # let mut account = Account::new(1);
+/// account.remove_login();
+/// ```
+///
+/// while the same calls compile once the deprecation is allowed:
+///
+/// ```
+/// # #![deny(deprecated)]
+/// # use structible::structible;
+/// # #[structible]
+/// # pub struct Account {
+/// #     pub id: u64,
+/// #     #[deprecated(note = "use `id` instead")]
+/// #     pub login: Option<String>,
+/// # }
+/// # let mut account = Account::new(1);
+/// #[allow(deprecated)]
+/// {
+///     account.set_login("alice".into());
+///     account.login_mut().unwrap().push('!');
+///     assert_eq!(account.login().map(String::as_str), Some("alice!"));
+///     account.remove_login();
+/// }
+/// ```
+#[cfg(doctest)]
+pub struct DeprecatedAccessors;
@@ -0,0 +1,60 @@
+//! Typed dispatch over the fields of a struct.
+
+/// A function over the value of a field of type `T`, for the `map_value()` method
+/// of the field enums.
+///
+/// A mapper applied to any field of a struct must implement this trait for the
+/// types of all its fields, which is usually done with a blanket impl bounded by
+/// whatever the mapper needs:
+///
+/// ```rust
+/// use std::fmt::Display;
+///
+/// use structible::{FieldMapper, structible};
+///
+/// struct Render;
+///
+/// impl<T: Display + ?Sized> FieldMapper<T, String> for Render {
+///     fn map(self, name: &'static str, value: Option<&T>) -> String {
+///         match value {
+///             Some(value) => format!("{name}={value}"),
+///             None => format!("{name} unset"),
+///         }
+///     }
+/// }
+///
+/// #[structible]
+/// pub struct Person {
+///     pub name: String,
+///     pub age: Option<u32>,
+/// }
+///
+/// let person = Person::new("Alice".into());
+/// assert_eq!(PersonField::Name.map_value(&person, Render), "name=Alice");
+/// assert_eq!(PersonField::Age.map_value(&person, Render), "age unset");
+/// ```
+///
+/// Closures taking the name and the value implement it too, for structs whose
+/// fields all have the same type.
+pub trait FieldMapper<T: ?Sized, R> {
+    /// Maps the value of the field called `name`, or `None` if it is absent.
+    ///
+    /// Catch-all entries are passed the name of the catch-all.
+    fn map(self, name: &'static str, value: Option<&T>) -> R;
+}
+
+impl<T: ?Sized, R, F> FieldMapper<T, R> for F
+where
+    F: FnOnce(&'static str, Option<&T>) -> R,
+{
+    fn map(self, name: &'static str, value: Option<&T>) -> R {
+        self(name, value)
+    }
+}
+
+/// Applies a mapper to a field of the struct, implemented by the macro, which
+/// bounds the mapper by `FieldMapper` for every field type.
+#[doc(hidden)]
+pub trait MapField<F, M, R> {
+    fn map_field(&self, field: &F, mapper: M) -> R;
+}
@@ -0,0 +1,75 @@
+//! Parquet files of structible structs.
+//!
+//! Structs declared with `arrow` implement [`ArrowRecord`], so that
+//! [`write_parquet`] can write them to a Parquet file with a column per known
+//! field, and [`read_parquet`] can read them back:
+//!
+//! ```rust
+//! use structible::structible;
+//! use structible::parquet::{read_parquet, write_parquet};
+//!
+//! #[structible(arrow)]
+//! pub struct Person {
+//!     pub name: String,
+//!     pub age: Option<u32>,
+//! }
+//!
+//! let path = std::env::temp_dir().join("structible-parquet-doctest.parquet");
+//! let people = vec![Person::new("Alice".into()), Person::new("Bob".into())];
+//! write_parquet(&people, &path).unwrap();
+//!
+//! let read: Vec<Person> = read_parquet(&path).unwrap();
+//! assert_eq!(read, people);
+//! # std::fs::remove_file(&path).unwrap();
+//! ```
+
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+use parquet::arrow::ArrowWriter;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::errors::ParquetError;
+
+use crate::ArrowRecord;
+
+/// The number of structs written per record batch.
+const BATCH_ROWS: usize = 8192;
+
+/// Writes `records` to a new Parquet file at `path`, replacing any existing file.
+///
+/// The structs are written in record batches of up to 8192 rows, so that the
+/// whole file is never held in memory as Arrow arrays.
+pub fn write_parquet<'a, S, I, P>(records: I, path: P) -> Result<(), ParquetError>
+where
+    S: ArrowRecord + 'a,
+    I: IntoIterator<Item = &'a S>,
+    P: AsRef<Path>,
+{
+    let file = File::create(path)?;
+    let mut writer = ArrowWriter::try_new(file, Arc::new(S::arrow_schema()), None)?;
+    let mut records = records.into_iter().peekable();
+    while records.peek().is_some() {
+        let batch = S::to_record_batch(records.by_ref().take(BATCH_ROWS))?;
+        writer.write(&batch)?;
+    }
+    writer.close()?;
+    Ok(())
+}
+
+/// Reads every struct stored in the Parquet file at `path`.
+///
+/// Columns are matched to fields by name, see [`ArrowRecord::from_record_batch`].
+pub fn read_parquet<S, P>(path: P) -> Result<Vec<S>, ParquetError>
+where
+    S: ArrowRecord,
+    P: AsRef<Path>,
+{
+    let file = File::open(path)?;
+    let reader = ParquetRecordBatchReaderBuilder::try_new(file)?.build()?;
+    let mut records = Vec::new();
+    for batch in reader {
+        records.extend(S::from_record_batch(&batch?)?);
+    }
+    Ok(records)
+}
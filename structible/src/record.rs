@@ -0,0 +1,31 @@
+//! Recording structible structs as `tracing` fields, see `#[structible(record_fields)]`.
+//!
+//! Structs with `record_fields` have a `record_fields(&self, visitor)` method that
+//! hands each present field to a [`Visit`](tracing::field::Visit) as a field named
+//! after it, so that a visitor can keep the struct's structure instead of one
+//! formatted string:
+//!
+//! ```rust,ignore
+//! struct Collect(Vec<(&'static str, String)>);
+//!
+//! impl Visit for Collect {
+//!     fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+//!         self.0.push((field.name(), format!("{:?}", value)));
+//!     }
+//! }
+//!
+//! let mut collect = Collect(Vec::new());
+//! person.record_fields(&mut collect);
+//! ```
+
+use std::fmt;
+
+/// Formats the entries of a catch-all as a map.
+#[doc(hidden)]
+pub struct __Entries<'a, K, V>(pub Vec<(&'a K, &'a V)>);
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for __Entries<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.0.iter().copied()).finish()
+    }
+}
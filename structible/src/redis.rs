@@ -0,0 +1,198 @@
+//! A reference `AsyncBackingMap` storing a struct as a Redis hash.
+//!
+//! Structs using [`RedisHashMap`] must be declared with both `async` and
+//! `serde_backing`, so that their hidden key and value enums can be encoded:
+//!
+//! ```rust,ignore
+//! use structible::redis::RedisHashMap;
+//!
+//! #[structible(backing = RedisHashMap, async, serde_backing)]
+//! pub struct Session {
+//!     pub user_id: u64,
+//!     pub token: Option<String>,
+//! }
+//!
+//! let connection = client.get_multiplexed_async_connection().await?;
+//! let session = Session::new(RedisHashMap::new(connection, "session:42"), 42).await?;
+//! ```
+//!
+//! Each field is stored in the hash under its name (see [`RedisHashMap::hash_field`]),
+//! and each value is stored as JSON tagged with the field name, e.g.
+//! `{"user_id":42}`.
+
+use std::fmt;
+use std::future::Future;
+use std::marker::PhantomData;
+
+use redis::RedisError;
+use redis::aio::MultiplexedConnection;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::AsyncBackingMap;
+
+/// A backing that stores each field of a struct in one Redis hash.
+///
+/// The connection is cloned for each operation, which is cheap for a
+/// [`MultiplexedConnection`] and lets reads take `&self`.
+pub struct RedisHashMap<K, V> {
+    connection: MultiplexedConnection,
+    key: String,
+    _marker: PhantomData<fn() -> (K, V)>,
+}
+
+impl<K, V> RedisHashMap<K, V> {
+    /// Creates a backing over the hash stored at `key`.
+    pub fn new(connection: MultiplexedConnection, key: impl Into<String>) -> Self {
+        Self {
+            connection,
+            key: key.into(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the key of the Redis hash.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Returns the underlying connection.
+    pub fn connection(&self) -> &MultiplexedConnection {
+        &self.connection
+    }
+}
+
+impl<K: Serialize, V> RedisHashMap<K, V> {
+    /// Returns the name of the hash field storing the given key.
+    ///
+    /// Keys that serialize to a string, such as the keys of known fields, are
+    /// stored under that string. Other keys, such as those of a catch-all field,
+    /// are stored under their JSON encoding, e.g. `{"extra":"color"}`.
+    pub fn hash_field(key: &K) -> Result<String, RedisHashError> {
+        match serde_json::to_value(key)? {
+            serde_json::Value::String(name) => Ok(name),
+            other => Ok(other.to_string()),
+        }
+    }
+}
+
+impl<K, V> Clone for RedisHashMap<K, V> {
+    fn clone(&self) -> Self {
+        Self::new(self.connection.clone(), self.key.clone())
+    }
+}
+
+impl<K, V> fmt::Debug for RedisHashMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RedisHashMap")
+            .field("key", &self.key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<K, V> AsyncBackingMap<K, V> for RedisHashMap<K, V>
+where
+    K: Serialize,
+    V: Serialize + DeserializeOwned + Send,
+{
+    type Error = RedisHashError;
+
+    fn get(&self, key: &K) -> impl Future<Output = Result<Option<V>, Self::Error>> + Send {
+        let field = Self::hash_field(key);
+        let mut connection = self.connection.clone();
+        let key = self.key.clone();
+        async move {
+            let value: Option<String> = redis::cmd("HGET")
+                .arg(&key)
+                .arg(field?)
+                .query_async(&mut connection)
+                .await?;
+            decode(value)
+        }
+    }
+
+    fn insert(&mut self, key: K, value: V) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        let field = Self::hash_field(&key);
+        let value = serde_json::to_string(&value);
+        let mut connection = self.connection.clone();
+        let key = self.key.clone();
+        async move {
+            redis::cmd("HSET")
+                .arg(&key)
+                .arg(field?)
+                .arg(value?)
+                .query_async::<()>(&mut connection)
+                .await?;
+            Ok(())
+        }
+    }
+
+    fn remove(&mut self, key: &K) -> impl Future<Output = Result<Option<V>, Self::Error>> + Send {
+        let field = Self::hash_field(key);
+        let mut connection = self.connection.clone();
+        let key = self.key.clone();
+        async move {
+            let field = field?;
+            let (value,): (Option<String>,) = redis::pipe()
+                .atomic()
+                .cmd("HGET")
+                .arg(&key)
+                .arg(&field)
+                .cmd("HDEL")
+                .arg(&key)
+                .arg(&field)
+                .ignore()
+                .query_async(&mut connection)
+                .await?;
+            decode(value)
+        }
+    }
+}
+
+/// Decodes a JSON-encoded value read from a hash field.
+fn decode<V: DeserializeOwned>(value: Option<String>) -> Result<Option<V>, RedisHashError> {
+    value
+        .map(|json| serde_json::from_str(&json))
+        .transpose()
+        .map_err(RedisHashError::from)
+}
+
+/// The error type of [`RedisHashMap`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum RedisHashError {
+    /// The Redis command failed.
+    Redis(RedisError),
+    /// A key or value could not be encoded, or a stored value could not be decoded.
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for RedisHashError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RedisHashError::Redis(err) => write!(f, "redis error: {}", err),
+            RedisHashError::Json(err) => write!(f, "failed to encode or decode value: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for RedisHashError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RedisHashError::Redis(err) => Some(err),
+            RedisHashError::Json(err) => Some(err),
+        }
+    }
+}
+
+impl From<RedisError> for RedisHashError {
+    fn from(err: RedisError) -> Self {
+        RedisHashError::Redis(err)
+    }
+}
+
+impl From<serde_json::Error> for RedisHashError {
+    fn from(err: serde_json::Error) -> Self {
+        RedisHashError::Json(err)
+    }
+}
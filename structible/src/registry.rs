@@ -0,0 +1,69 @@
+//! A registry of the structible structs in a binary, with the `inventory` feature.
+//!
+//! Every struct with a [`Schema`] impl that has no generics registers itself
+//! when it is declared, so that admin UIs, migrations, and schema dumps can list
+//! all structs without maintaining the list by hand:
+//!
+//! ```rust
+//! use structible::structible;
+//!
+//! #[structible]
+//! pub struct Person {
+//!     pub name: String,
+//! }
+//!
+//! let person = structible::registry::structs()
+//!     .into_iter()
+//!     .find(|r| r.name == "Person")
+//!     .unwrap();
+//! assert_eq!(person.fields[0].name, "name");
+//! ```
+//!
+//! The registry works like the [`inventory`] crate it is built on, and is
+//! supported on the same platforms.
+
+use crate::{FieldDescriptor, Schema};
+
+/// A registered struct.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct Registration {
+    /// The struct's name, as in [`Schema::NAME`].
+    pub name: &'static str,
+    /// The path of the module declaring the struct, as in `module_path!()`.
+    pub module_path: &'static str,
+    /// The struct's fields, as in [`Schema::FIELDS`].
+    pub fields: &'static [FieldDescriptor],
+}
+
+impl Registration {
+    /// Returns the registration of `S`, declared in `module_path`.
+    #[doc(hidden)]
+    pub const fn __of<S: Schema>(module_path: &'static str) -> Self {
+        Self {
+            name: S::NAME,
+            module_path,
+            fields: S::FIELDS,
+        }
+    }
+}
+
+inventory::collect!(Registration);
+
+/// Returns every registered struct, sorted by module path and name.
+pub fn structs() -> Vec<&'static Registration> {
+    let mut structs: Vec<_> = inventory::iter::<Registration>.into_iter().collect();
+    structs.sort_by_key(|r| (r.module_path, r.name));
+    structs
+}
+
+/// Registers a struct, if the `inventory` feature is enabled.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __register_schema {
+    ($ty:ident) => {
+        $crate::__private::inventory::submit! {
+            $crate::registry::Registration::__of::<$ty>(::std::module_path!())
+        }
+    };
+}
@@ -0,0 +1,218 @@
+//! Serde support for any [`Structible`] struct, without generated serde impls.
+//!
+//! The functions in this module are meant for serde's `with` attribute on fields
+//! holding a structible struct, whose field and value enums must be declared with
+//! `serde_backing`:
+//!
+//! ```rust
+//! use serde::{Deserialize, Serialize};
+//! use structible::structible;
+//!
+//! #[structible(serde_backing)]
+//! pub struct Person {
+//!     pub name: String,
+//!     pub email: Option<String>,
+//! }
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Team {
+//!     #[serde(with = "structible::serde_adapter")]
+//!     lead: Person,
+//! }
+//!
+//! let team = Team { lead: Person::new("Alice".into()) };
+//! let json = serde_json::to_string(&team).unwrap();
+//! assert_eq!(json, r#"{"lead":[["name",{"name":"Alice"}]]}"#);
+//!
+//! let team: Team = serde_json::from_str(&json).unwrap();
+//! assert_eq!(team.lead.name(), "Alice");
+//! ```
+//!
+//! A struct is encoded as a sequence of `[field, value]` pairs, in the iteration
+//! order of its backing map. Pairs are used rather than a map, so that the keys of
+//! a catch-all field can be encoded in formats that only allow string map keys.
+//! Deserialization rejects values paired with another field's key, duplicate
+//! fields, and missing required fields.
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+
+use crate::{BackingMap, IterableMap, Structible, ValidationError};
+
+/// Serializes a structible struct as a sequence of `[field, value]` pairs.
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Structible,
+    T::Backing: IterableMap<T::Field, T::Value>,
+    T::Field: Serialize,
+    T::Value: Serialize,
+    S: Serializer,
+{
+    let backing = value.backing();
+    let mut seq = serializer.serialize_seq(Some(backing.len()))?;
+    for entry in IterableMap::iter(backing) {
+        seq.serialize_element(&entry)?;
+    }
+    seq.end()
+}
+
+/// Deserializes a structible struct from a sequence of `[field, value]` pairs.
+///
+/// Fails if a value belongs to another field than its key, if a field appears
+/// twice, or if a required field is missing. The struct's declared validation
+/// is not run; call `validate()` on the result if needed.
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: Structible,
+    T::Field: Deserialize<'de>,
+    T::Value: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_seq(EntriesVisitor(PhantomData))
+}
+
+/// The most memory preallocated for a backing from a size hint, as in serde's own
+/// impls; the hint comes from the input, which could otherwise force a huge
+/// allocation up front.
+const MAX_PREALLOC_BYTES: usize = 1024 * 1024;
+
+/// Returns the capacity to preallocate for `hint` entries of type `(K, V)`.
+fn cautious_capacity<K, V>(hint: Option<usize>) -> usize {
+    let entry_size = std::mem::size_of::<(K, V)>().max(1);
+    hint.unwrap_or(0).min(MAX_PREALLOC_BYTES / entry_size)
+}
+
+struct EntriesVisitor<T>(PhantomData<fn() -> T>);
+
+impl<'de, T> Visitor<'de> for EntriesVisitor<T>
+where
+    T: Structible,
+    T::Field: Deserialize<'de>,
+    T::Value: Deserialize<'de>,
+{
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a sequence of [field, value] pairs")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<T, A::Error> {
+        let capacity = cautious_capacity::<T::Field, T::Value>(seq.size_hint());
+        let mut backing = T::Backing::with_capacity(capacity);
+        while let Some((key, value)) = seq.next_element::<(T::Field, T::Value)>()? {
+            let (key, value) = T::route_entry(key, value);
+            if !T::is_valid_entry(&key, &value) {
+                return Err(de::Error::custom(
+                    "value belongs to another field than its key",
+                ));
+            }
+            if backing.insert(key, value).is_some() {
+                return Err(de::Error::custom("duplicate field"));
+            }
+        }
+        T::try_from_backing(backing).map_err(de::Error::custom)
+    }
+}
+
+/// Decodes fields by name, implemented for structs declared with `serde`.
+#[doc(hidden)]
+pub trait __DecodeFields<'de>: Structible {
+    /// The names of the known fields, for error messages.
+    const FIELDS: &'static [&'static str];
+
+    /// Decodes the value of the field called `name` from `map`, passing its entry to
+    /// `insert`. Names of no field are catch-all keys; without a catch-all, returns
+    /// false without reading the value.
+    fn __decode_field<A: MapAccess<'de>>(
+        name: &str,
+        map: &mut A,
+        insert: &mut impl FnMut(Self::Field, Self::Value) -> Result<(), A::Error>,
+    ) -> Result<bool, A::Error>;
+}
+
+/// Deserializes a struct declared with `serde` from a map of its present fields.
+#[doc(hidden)]
+pub fn __deserialize_fields<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: __DecodeFields<'de>,
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_map(FieldsVisitor(PhantomData))
+}
+
+struct FieldsVisitor<T>(PhantomData<fn() -> T>);
+
+impl<'de, T: __DecodeFields<'de>> Visitor<'de> for FieldsVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a map of fields")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<T, A::Error> {
+        let capacity = cautious_capacity::<T::Field, T::Value>(map.size_hint());
+        let mut backing = T::Backing::with_capacity(capacity);
+        while let Some(name) = map.next_key::<String>()? {
+            let mut insert = |key, value| {
+                let (key, value) = T::route_entry(key, value);
+                match backing.insert(key, value) {
+                    Some(_) => Err(de::Error::custom(format_args!("duplicate field `{name}`"))),
+                    None => Ok(()),
+                }
+            };
+            if !T::__decode_field(&name, &mut map, &mut insert)? {
+                return Err(de::Error::unknown_field(&name, T::FIELDS));
+            }
+        }
+        T::try_from_backing(backing).map_err(|e| match e {
+            ValidationError::MissingRequired { field } => de::Error::missing_field(field),
+            e => de::Error::custom(e),
+        })
+    }
+}
+
+/// Serializes catch-all entries as a map, for the impls generated by `test_serialize`.
+#[doc(hidden)]
+pub struct __Entries<'a, K, V>(pub &'a [(&'a K, &'a V)]);
+
+impl<K: Serialize, V: Serialize> Serialize for __Entries<'_, K, V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.iter().copied())
+    }
+}
+
+/// Deserializes a value as `T`, or else as the legacy type `O` converted with `with`,
+/// for the fields declared with `upgrade_from`.
+#[doc(hidden)]
+pub fn __upgrade_from<'de, D, T, O, E, F>(deserializer: D, with: F) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+    O: Deserialize<'de>,
+    E: fmt::Display,
+    F: FnOnce(O) -> Result<T, E>,
+{
+    match Upgradable::<T, O>::deserialize(deserializer)? {
+        Upgradable::Current(value) => Ok(value),
+        Upgradable::Legacy(legacy) => with(legacy).map_err(de::Error::custom),
+    }
+}
+
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum Upgradable<T, O> {
+    Current(T),
+    Legacy(O),
+}
+
+/// Converts a value into another type through its serde data model, for catch-alls
+/// declared with `promote_known`. Returns `None` if the value doesn't fit the type.
+#[doc(hidden)]
+pub fn __convert<V: Serialize, T: de::DeserializeOwned>(value: &V) -> Option<T> {
+    serde_json::to_value(value)
+        .and_then(serde_json::from_value)
+        .ok()
+}
@@ -0,0 +1,21 @@
+/// The layer that supplied a field's value, in a struct with `provenance`.
+///
+/// Values written with `set_<field>_with_source()` carry their source until the
+/// field is written again or removed; values merged from another replica with
+/// `lww` are tagged [`Source::Sync`].
+///
+/// Expired `ttl` values and catch-all entries have no source. Sources are
+/// cloned, but ignored by `PartialEq` and `Debug`, and dropped by
+/// `into_backing()` and `freeze()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Source {
+    /// A built-in default.
+    Default,
+    /// A value set by the user, e.g. from a configuration file or a form.
+    User,
+    /// A value received from another replica.
+    Sync,
+    /// A value produced while migrating older data.
+    Migration,
+}
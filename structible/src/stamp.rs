@@ -0,0 +1,20 @@
+/// The logical time of a write to a field of a struct with `lww`.
+///
+/// Stamps are ordered by `timestamp`, then by `actor`, so that two writes made
+/// by different replicas at the same logical time are still ordered the same way
+/// on every replica.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stamp {
+    /// The value of the writing replica's Lamport clock.
+    pub timestamp: u64,
+    /// The id of the writing replica.
+    pub actor: u64,
+}
+
+impl Stamp {
+    /// Creates a stamp for a write made by `actor` at `timestamp`.
+    pub const fn new(timestamp: u64, actor: u64) -> Self {
+        Self { timestamp, actor }
+    }
+}
@@ -0,0 +1,327 @@
+//! TypeScript definitions of struct schemas, with the `typescript` feature, see
+//! [`emit_typescript!`](crate::emit_typescript).
+//!
+//! Each struct's [`Schema`] is rendered as an interface describing its JSON
+//! object, as in the OpenAPI schemas of `#[structible(openapi)]`: known fields are
+//! properties, optional ones marked `?`, and catch-all entries an index signature:
+//!
+//! ```rust
+//! use structible::structible;
+//!
+//! #[structible]
+//! pub struct Person {
+//!     pub name: String,
+//!     pub age: Option<u32>,
+//!     pub tags: Vec<String>,
+//!     #[structible(key = String)]
+//!     pub extra: Option<bool>,
+//! }
+//!
+//! assert_eq!(
+//!     structible::typescript::interface::<Person>(),
+//!     "export interface Person {
+//!   name: string;
+//!   age?: number;
+//!   tags: string[];
+//!   [key: string]: boolean | string | number | string[] | undefined;
+//! }
+//! "
+//! );
+//! ```
+//!
+//! TypeScript requires every property to match the index signature, so its type
+//! also admits the types of the known fields.
+//!
+//! Field types are mapped from their text as declared: numbers to `number`,
+//! strings and `char` to `string`, `bool` to `boolean`, `Option` to `| null`,
+//! sequences and sets to arrays, maps to `Record`, tuples to tuples, and smart
+//! pointers to what they point to. Any other type is named as written, without
+//! its path, so that it refers to another emitted interface.
+
+use std::io;
+use std::path::Path;
+
+use crate::{FieldDescriptor, Schema};
+
+/// Renders the schema of `S` as an exported TypeScript interface.
+pub fn interface<S: Schema + ?Sized>() -> String {
+    render(S::NAME, S::FIELDS)
+}
+
+fn render(name: &str, fields: &[FieldDescriptor]) -> String {
+    let mut out = format!("export interface {} {{\n", name);
+    let mut index = None;
+    let mut known = Vec::new();
+    for field in fields {
+        let ty = Type::parse(field.ty);
+        // Optional fields are declared as `Option<T>`, but absent rather than null
+        let ty = match ty {
+            Type::Path(ref name, ref args)
+                if field.optional && name == "Option" && args.len() == 1 =>
+            {
+                args[0].clone()
+            }
+            ty => ty,
+        };
+        let ty = ty.to_typescript();
+        if let Some(key) = field.key {
+            let key = match Type::parse(key).to_typescript().as_str() {
+                "number" => "number",
+                _ => "string",
+            };
+            index = Some((key, ty));
+            continue;
+        }
+        let marker = if field.optional { "?" } else { "" };
+        out.push_str(&format!("  {}{}: {};\n", field.name, marker, ty));
+        known.push((ty, field.optional));
+    }
+    if let Some((key, value)) = index {
+        let mut types = vec![value];
+        for (ty, _) in &known {
+            if !types.contains(ty) {
+                types.push(ty.clone());
+            }
+        }
+        if known.iter().any(|(_, optional)| *optional) {
+            types.push("undefined".into());
+        }
+        out.push_str(&format!("  [key: {}]: {};\n", key, types.join(" | ")));
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// A type, parsed from its text as declared.
+#[derive(Debug, Clone, PartialEq)]
+enum Type {
+    /// The last segment of a path, with its generic arguments.
+    Path(String, Vec<Type>),
+    Tuple(Vec<Type>),
+    /// A slice or an array.
+    Slice(Box<Type>),
+}
+
+impl Type {
+    fn parse(text: &str) -> Self {
+        let mut chars = text.chars().peekable();
+        Self::parse_from(&mut chars)
+    }
+
+    fn parse_from(chars: &mut std::iter::Peekable<std::str::Chars<'_>>) -> Self {
+        skip_whitespace(chars);
+        match chars.peek() {
+            // References are serialized as what they point to
+            Some('&') => {
+                chars.next();
+                if chars.peek() == Some(&'\'') {
+                    while chars.next_if(|c| !c.is_whitespace()).is_some() {}
+                }
+                skip_whitespace(chars);
+                if chars.clone().take(4).collect::<String>() == "mut " {
+                    chars.nth(3);
+                }
+                Self::parse_from(chars)
+            }
+            Some('(') => {
+                chars.next();
+                Type::Tuple(Self::parse_list(chars, ')'))
+            }
+            Some('[') => {
+                chars.next();
+                let element = Self::parse_from(chars);
+                // Skip the length of an array
+                while chars.next_if(|&c| c != ']').is_some() {}
+                chars.next();
+                Type::Slice(Box::new(element))
+            }
+            _ => {
+                let mut path = String::new();
+                while let Some(c) = chars.next_if(|&c| c.is_alphanumeric() || c == '_' || c == ':')
+                {
+                    path.push(c);
+                }
+                if path.is_empty() {
+                    // Not a type, e.g. a stray token; skip it
+                    chars.next();
+                    return Type::Path(path, Vec::new());
+                }
+                // Trait objects are serialized as their first trait describes
+                if path == "dyn" || path == "impl" {
+                    let ty = Self::parse_from(chars);
+                    skip_whitespace(chars);
+                    while chars.next_if_eq(&'+').is_some() {
+                        Self::parse_from(chars);
+                        skip_whitespace(chars);
+                    }
+                    return ty;
+                }
+                let name = path.rsplit("::").next().unwrap_or_default().to_owned();
+                skip_whitespace(chars);
+                let args = if chars.next_if_eq(&'<').is_some() {
+                    Self::parse_list(chars, '>')
+                } else {
+                    Vec::new()
+                };
+                Type::Path(name, args)
+            }
+        }
+    }
+
+    /// Parses types separated by commas, up to and including `end`.
+    fn parse_list(chars: &mut std::iter::Peekable<std::str::Chars<'_>>, end: char) -> Vec<Type> {
+        let mut types = Vec::new();
+        loop {
+            skip_whitespace(chars);
+            match chars.peek() {
+                None => return types,
+                Some(&c) if c == end => {
+                    chars.next();
+                    return types;
+                }
+                Some(',') => {
+                    chars.next();
+                }
+                // Lifetimes carry no type
+                Some('\'') => while chars.next_if(|&c| c != ',' && c != end).is_some() {},
+                Some(_) => types.push(Self::parse_from(chars)),
+            }
+        }
+    }
+
+    fn to_typescript(&self) -> String {
+        match self {
+            Type::Tuple(types) if types.is_empty() => "null".into(),
+            Type::Tuple(types) => {
+                let types: Vec<_> = types.iter().map(Type::to_typescript).collect();
+                format!("[{}]", types.join(", "))
+            }
+            Type::Slice(element) => element.to_array(),
+            Type::Path(name, args) => match (name.as_str(), args.as_slice()) {
+                (
+                    "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32" | "u64"
+                    | "u128" | "usize" | "f32" | "f64",
+                    [],
+                ) => "number".into(),
+                ("bool", []) => "boolean".into(),
+                ("String" | "str" | "char", []) => "string".into(),
+                ("Value", []) => "unknown".into(),
+                ("Option", [inner]) => format!("{} | null", inner.to_typescript()),
+                ("Box" | "Rc" | "Arc" | "Cow", [inner]) => inner.to_typescript(),
+                (
+                    "Vec" | "VecDeque" | "LinkedList" | "HashSet" | "BTreeSet" | "IndexSet",
+                    [element],
+                ) => element.to_array(),
+                ("HashMap" | "BTreeMap" | "IndexMap", [key, value]) => {
+                    let key = match key.to_typescript().as_str() {
+                        "number" => "number",
+                        _ => "string",
+                    };
+                    format!("Record<{}, {}>", key, value.to_typescript())
+                }
+                (_, []) => name.clone(),
+                (_, args) => {
+                    let args: Vec<_> = args.iter().map(Type::to_typescript).collect();
+                    format!("{}<{}>", name, args.join(", "))
+                }
+            },
+        }
+    }
+
+    /// Renders an array of this type, parenthesizing unions.
+    fn to_array(&self) -> String {
+        let element = self.to_typescript();
+        if element.contains(" | ") {
+            format!("({})[]", element)
+        } else {
+            format!("{}[]", element)
+        }
+    }
+}
+
+fn skip_whitespace(chars: &mut std::iter::Peekable<std::str::Chars<'_>>) {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+}
+
+/// Writes `interfaces` to `path` as a TypeScript module, creating its directory
+/// if needed, and leaving a file that already holds them untouched.
+fn write(path: &Path, interfaces: &[String]) -> io::Result<()> {
+    let mut module =
+        String::from("// Generated by structible from the Rust structs. Do not edit.\n");
+    for interface in interfaces {
+        module.push('\n');
+        module.push_str(interface);
+    }
+    if std::fs::read_to_string(path).is_ok_and(|existing| existing == module) {
+        return Ok(());
+    }
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, module)
+}
+
+/// Writes the interface of each of `interfaces`, the `interface` of a struct, to `path`.
+#[doc(hidden)]
+pub fn __write_all(path: impl AsRef<Path>, interfaces: &[fn() -> String]) -> io::Result<()> {
+    let interfaces: Vec<_> = interfaces.iter().map(|interface| interface()).collect();
+    write(path.as_ref(), &interfaces)
+}
+
+/// Writes the interfaces of every struct in [`registry`](crate::registry) to
+/// `path`, like [`emit_typescript!`](crate::emit_typescript).
+///
+/// Structs of the same name in different modules would declare the same
+/// interface, so they are rejected with an `InvalidInput` error.
+#[cfg(feature = "inventory")]
+pub fn write_registered(path: impl AsRef<Path>) -> io::Result<()> {
+    let mut structs = crate::registry::structs();
+    structs.sort_by_key(|r| r.name);
+    if let Some(w) = structs.windows(2).find(|w| w[0].name == w[1].name) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("several registered structs are named `{}`", w[0].name),
+        ));
+    }
+    let interfaces: Vec<_> = structs.iter().map(|r| render(r.name, r.fields)).collect();
+    write(path.as_ref(), &interfaces)
+}
+
+/// Writes TypeScript interfaces of the given structs to a file.
+///
+/// The file is a module exporting one interface per struct, in the given order,
+/// see [`typescript`](crate::typescript). Evaluates to an `io::Result<()>`:
+///
+/// ```rust,no_run
+/// use structible::structible;
+///
+/// #[structible]
+/// pub struct Person {
+///     pub name: String,
+///     pub age: Option<u32>,
+/// }
+///
+/// #[structible]
+/// pub struct Team {
+///     pub lead: Person,
+///     pub members: Vec<Person>,
+/// }
+///
+/// structible::emit_typescript!("frontend/src/types.ts", Person, Team).unwrap();
+/// ```
+///
+/// With the `inventory` feature, `emit_typescript!(path)` writes the interfaces
+/// of every struct in [`registry`](crate::registry) instead, sorted by name, see
+/// [`write_registered`](crate::typescript::write_registered).
+///
+/// Like [`emit_schemas!`](crate::emit_schemas), this is usually run from a test,
+/// with the file checked into version control, so that CI catches drift.
+#[macro_export]
+macro_rules! emit_typescript {
+    ($path:expr $(,)?) => {
+        $crate::typescript::write_registered($path)
+    };
+    ($path:expr, $($ty:ty),+ $(,)?) => {
+        $crate::typescript::__write_all($path, &[$($crate::typescript::interface::<$ty>),+])
+    };
+}
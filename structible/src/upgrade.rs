@@ -0,0 +1,38 @@
+use std::rc::{self, Rc};
+use std::sync::{self, Arc};
+
+/// Trait for weak references that can be upgraded to strong ones.
+///
+/// Fields whose type implements it get getters returning the upgraded reference,
+/// see `#[structible(upgrade)]`. It is implemented for [`std::sync::Weak`] and
+/// [`std::rc::Weak`].
+///
+/// Fields whose type is named `Weak<T>` are upgraded by default, optional or not,
+/// and the getter of a required one returns an `Option` too. `upgrade` opts in a
+/// field whose type doesn't look like `Weak<T>`, e.g. a type alias, and
+/// `no_upgrade` keeps the plain getter. Setters, mutable getters, and removers
+/// still work on the weak reference, and `<getter>_weak()` returns it. The
+/// catch-all and lazy fields aren't upgraded, nor are `async` accessors.
+pub trait Upgrade {
+    /// The strong reference, e.g. `Arc<T>` for `sync::Weak<T>`.
+    type Strong;
+
+    /// Returns a strong reference if the value is still alive.
+    fn upgrade(&self) -> Option<Self::Strong>;
+}
+
+impl<T: ?Sized> Upgrade for sync::Weak<T> {
+    type Strong = Arc<T>;
+
+    fn upgrade(&self) -> Option<Arc<T>> {
+        sync::Weak::upgrade(self)
+    }
+}
+
+impl<T: ?Sized> Upgrade for rc::Weak<T> {
+    type Strong = Rc<T>;
+
+    fn upgrade(&self) -> Option<Rc<T>> {
+        rc::Weak::upgrade(self)
+    }
+}
@@ -0,0 +1,75 @@
+//! Tests for the struct-level `getter_prefix`, `setter_prefix`, and `remover_prefix`.
+
+#![allow(deprecated)]
+
+use structible::structible;
+
+#[structible(
+    getter_prefix = "get_",
+    setter_prefix = "with_",
+    remover_prefix = "clear_"
+)]
+pub struct Contact {
+    pub name: String,
+    #[structible(alias = mail)]
+    pub email: Option<String>,
+    #[structible(get = phone_number, set = change_phone)]
+    pub phone: Option<String>,
+    pub r#type: Option<u8>,
+    #[structible(key = String)]
+    pub extra: Option<String>,
+}
+
+#[structible(setter_prefix = "put_")]
+pub struct Partial {
+    pub id: u32,
+    pub label: Option<String>,
+}
+
+#[test]
+fn test_prefixed_accessors() {
+    let mut contact = Contact::new("Alice".into());
+    assert_eq!(contact.get_name(), "Alice");
+    contact.get_name_mut().push_str(" Smith");
+    contact.with_name("Bob".into());
+    assert_eq!(contact.get_name(), "Bob");
+
+    contact.with_email("bob@example.com".into());
+    assert_eq!(contact.get_email(), Some(&"bob@example.com".to_string()));
+    assert_eq!(contact.clear_email(), Some("bob@example.com".to_string()));
+
+    contact.with_type(3);
+    assert_eq!(contact.get_type(), Some(&3));
+}
+
+#[test]
+fn test_field_names_override_prefixes() {
+    let mut contact = Contact::new("Alice".into());
+    contact.change_phone("555-0100".into());
+    assert_eq!(contact.phone_number(), Some(&"555-0100".to_string()));
+    assert_eq!(contact.clear_phone(), Some("555-0100".to_string()));
+}
+
+#[test]
+fn test_catch_all_keeps_its_names() {
+    let mut contact = Contact::new("Alice".into());
+    contact.insert_extra("team".into(), "core".into());
+    assert_eq!(contact.extra("team"), Some(&"core".to_string()));
+    assert_eq!(contact.remove_extra("team"), Some("core".to_string()));
+}
+
+#[test]
+fn test_aliases_follow_prefixes() {
+    let mut contact = Contact::new("Alice".into());
+    contact.with_mail("alice@example.com".into());
+    assert_eq!(contact.get_mail(), Some(&"alice@example.com".to_string()));
+    assert_eq!(contact.clear_mail(), Some("alice@example.com".to_string()));
+}
+
+#[test]
+fn test_unset_prefixes_keep_defaults() {
+    let mut partial = Partial::new(1);
+    partial.put_label("first".into());
+    assert_eq!(partial.id(), &1);
+    assert_eq!(partial.remove_label(), Some("first".to_string()));
+}
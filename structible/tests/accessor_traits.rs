@@ -0,0 +1,176 @@
+//! Tests for `#[structible(accessor_traits)]`.
+
+use std::collections::HashMap;
+
+use structible::structible;
+
+#[structible(accessor_traits)]
+pub struct Person {
+    pub name: String,
+    pub age: u32,
+    pub email: Option<String>,
+    #[structible(get_vis = pub(crate))]
+    pub secret: Option<u64>,
+}
+
+#[structible(accessor_traits, no_setters, no_removers)]
+pub struct Config<T: Clone + PartialEq + std::fmt::Debug> {
+    pub value: T,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Meters(f64);
+
+impl From<f64> for Meters {
+    fn from(value: f64) -> Self {
+        Meters(value)
+    }
+}
+
+impl From<Meters> for f64 {
+    fn from(value: Meters) -> Self {
+        value.0
+    }
+}
+
+#[structible(accessor_traits)]
+pub struct Route {
+    #[structible(transparent = f64)]
+    pub distance: Meters,
+}
+
+/// A hand-written stand-in for a `Person`.
+#[derive(Default)]
+struct MockPerson {
+    calls: HashMap<&'static str, u32>,
+}
+
+impl PersonReader for MockPerson {
+    fn name(&self) -> &String {
+        static NAME: String = String::new();
+        &NAME
+    }
+
+    fn age(&self) -> &u32 {
+        &42
+    }
+
+    fn email(&self) -> Option<&String> {
+        None
+    }
+}
+
+impl PersonWriter for MockPerson {
+    fn set_name(&mut self, _value: String) {
+        *self.calls.entry("set_name").or_default() += 1;
+    }
+
+    fn set_age(&mut self, _value: u32) {
+        *self.calls.entry("set_age").or_default() += 1;
+    }
+
+    fn set_email(&mut self, _value: String) {}
+
+    fn remove_email(&mut self) -> Option<String> {
+        None
+    }
+
+    fn set_secret(&mut self, _value: u64) {}
+
+    fn remove_secret(&mut self) -> Option<u64> {
+        None
+    }
+}
+
+fn describe(person: &dyn PersonReader) -> String {
+    match person.email() {
+        Some(email) => format!("{} ({}) <{}>", person.name(), person.age(), email),
+        None => format!("{} ({})", person.name(), person.age()),
+    }
+}
+
+fn birthday(person: &mut impl PersonWriter, age: u32) {
+    person.set_age(age + 1);
+}
+
+#[test]
+fn test_reader_trait_object() {
+    let mut person = Person::new("Alice".into(), 30);
+    assert_eq!(describe(&person), "Alice (30)");
+
+    person.set_email("alice@example.com".into());
+    assert_eq!(describe(&person), "Alice (30) <alice@example.com>");
+}
+
+#[test]
+fn test_writer_generic() {
+    let mut person = Person::new("Alice".into(), 30);
+    birthday(&mut person, 30);
+    assert_eq!(person.age(), &31);
+
+    let writer: &mut dyn PersonWriter = &mut person;
+    writer.set_email("a@example.com".into());
+    assert_eq!(writer.remove_email(), Some("a@example.com".into()));
+}
+
+#[test]
+fn test_mock_implementation() {
+    let mut mock = MockPerson::default();
+    assert_eq!(describe(&mock), " (42)");
+
+    birthday(&mut mock, 1);
+    assert_eq!(mock.calls.get("set_age"), Some(&1));
+    assert_eq!(mock.calls.get("set_name"), None);
+}
+
+#[test]
+fn test_generic_reader() {
+    let config = Config::new(5u8);
+    let reader: &dyn ConfigReader<u8> = &config;
+    assert_eq!(reader.value(), &5);
+}
+
+#[test]
+fn test_transparent_writer_takes_newtype() {
+    let mut route = Route::new(1.0);
+    let writer: &mut dyn RouteWriter = &mut route;
+    writer.set_distance(Meters(2.5));
+
+    let reader: &dyn RouteReader = &route;
+    assert_eq!(reader.distance(), 2.5);
+}
+
+#[structible(accessor_traits, mock)]
+pub struct Account {
+    pub owner: String,
+    pub balance: i64,
+    pub note: Option<String>,
+}
+
+fn withdraw(account: &mut dyn AccountWriter, current: i64, amount: i64) {
+    account.set_balance(current - amount);
+}
+
+#[test]
+fn test_mock_reader() {
+    let mut mock = MockAccountReader::new();
+    mock.expect_owner().return_const("bob".to_string());
+    mock.expect_balance().return_const(10);
+    mock.expect_note().return_const(None);
+
+    let reader: &dyn AccountReader = &mock;
+    assert_eq!(reader.owner(), "bob");
+    assert_eq!(reader.balance(), &10);
+    assert_eq!(reader.note(), None);
+}
+
+#[test]
+fn test_mock_writer() {
+    let mut mock = MockAccountWriter::new();
+    mock.expect_set_balance()
+        .with(mockall::predicate::eq(7))
+        .times(1)
+        .return_const(());
+
+    withdraw(&mut mock, 10, 3);
+}
@@ -0,0 +1,129 @@
+//! Tests for deprecated alias accessors of renamed fields.
+
+#![allow(deprecated)]
+
+use structible::structible;
+
+#[structible]
+pub struct Contact {
+    #[structible(alias = full_name)]
+    pub name: String,
+    #[structible(alias = mail, alias = email_address)]
+    pub email: Option<String>,
+    #[structible(get = phone_number, alias = telephone)]
+    pub phone: Option<String>,
+}
+
+#[test]
+fn test_required_field_alias() {
+    let mut contact = Contact::new("Alice".into());
+    assert_eq!(contact.full_name(), "Alice");
+
+    contact.set_full_name("Bob".into());
+    assert_eq!(contact.name(), "Bob");
+
+    contact.full_name_mut().push_str(" Smith");
+    assert_eq!(contact.name(), "Bob Smith");
+}
+
+#[test]
+fn test_optional_field_aliases() {
+    let mut contact = Contact::new("Alice".into());
+    contact.set_mail("alice@example.com".into());
+    assert_eq!(contact.email(), Some(&"alice@example.com".to_string()));
+    assert_eq!(
+        contact.email_address(),
+        Some(&"alice@example.com".to_string())
+    );
+
+    assert_eq!(contact.remove_mail(), Some("alice@example.com".to_string()));
+    assert_eq!(contact.email(), None);
+}
+
+#[test]
+fn test_alias_delegates_to_custom_getter() {
+    let mut contact = Contact::new("Alice".into());
+    contact.set_telephone("555-0100".into());
+    assert_eq!(contact.telephone(), contact.phone_number());
+}
+
+#[cfg(feature = "serde")]
+mod serde_aliases {
+    use std::collections::BTreeMap;
+
+    use serde_json::json;
+    use structible::structible;
+
+    #[structible(serde)]
+    pub struct Contact {
+        #[structible(alias = full_name)]
+        pub name: String,
+        #[structible(alias = mail, alias = email_address)]
+        pub email: Option<String>,
+        #[structible(key = String)]
+        pub extra: Option<String>,
+    }
+
+    #[structible(backing = BTreeMap, serde_backing)]
+    pub struct Stored {
+        #[structible(alias = full_name)]
+        pub name: String,
+    }
+
+    #[test]
+    fn test_serde_accepts_aliases() {
+        let contact: Contact = serde_json::from_value(json!({
+            "full_name": "Alice",
+            "email_address": "alice@example.com",
+        }))
+        .unwrap();
+        assert_eq!(contact.name(), "Alice");
+        assert_eq!(
+            contact.email().map(String::as_str),
+            Some("alice@example.com")
+        );
+        assert_eq!(contact.extra_iter().count(), 0);
+
+        // Written under the current names, which read back the same
+        let json = serde_json::to_value(&contact).unwrap();
+        assert_eq!(json, json!({"name": "Alice", "email": "alice@example.com"}));
+        assert_eq!(serde_json::from_value::<Contact>(json).unwrap(), contact);
+    }
+
+    #[test]
+    fn test_serde_rejects_field_and_alias() {
+        let error = serde_json::from_value::<Contact>(json!({"name": "Alice", "full_name": "Bob"}))
+            .unwrap_err();
+        assert!(
+            error.to_string().contains("duplicate field `name`"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn test_serde_backing_accepts_aliases() {
+        let backing: BTreeMap<StoredField, StoredValue> =
+            serde_json::from_value(json!({"full_name": {"full_name": "Alice"}})).unwrap();
+        let stored = <Stored as structible::Structible>::try_from_backing(backing).unwrap();
+        assert_eq!(stored.name(), "Alice");
+    }
+}
+
+#[cfg(feature = "codec")]
+#[test]
+fn test_value_codec_parses_aliases() {
+    use structible::ValueCodec;
+
+    #[structible(value_codec)]
+    pub struct Renamed {
+        #[structible(alias = full_name)]
+        pub name: String,
+    }
+
+    assert_eq!(
+        Renamed::parse_field_name("full_name").unwrap(),
+        RenamedField::Name
+    );
+    let renamed = Renamed::decode_entries([("full_name", br#""Alice""#.as_slice())]).unwrap();
+    assert_eq!(renamed.name(), "Alice");
+}
@@ -0,0 +1,157 @@
+//! Tests for `#[structible(arrow)]` schemas and array builders.
+
+#![cfg(feature = "arrow")]
+
+use std::sync::Arc;
+
+use arrow_array::cast::AsArray;
+use arrow_array::types::{Float64Type, UInt32Type};
+use arrow_array::{Array, ArrayRef, RecordBatch, StringArray, UInt32Array};
+use arrow_schema::DataType;
+use structible::{ArrowRecord, ArrowValue, structible};
+
+#[structible(arrow)]
+pub struct Person {
+    pub name: String,
+    pub age: Option<u32>,
+    pub r#type: Option<String>,
+    #[structible(key = String)]
+    pub extra: Option<String>,
+}
+
+#[structible(arrow)]
+pub struct Tagged<T> {
+    pub tag: T,
+}
+
+#[test]
+fn test_schema() {
+    let schema = Person::arrow_schema();
+    let columns: Vec<_> = schema
+        .fields()
+        .iter()
+        .map(|f| (f.name().as_str(), f.data_type().clone(), f.is_nullable()))
+        .collect();
+    assert_eq!(
+        columns,
+        [
+            ("name", DataType::Utf8, false),
+            ("age", DataType::UInt32, true),
+            ("type", DataType::Utf8, true),
+        ]
+    );
+}
+
+#[test]
+fn test_builder_appends_column_wise() {
+    let mut alice = Person::new("Alice".into());
+    alice.set_age(30);
+    alice.insert_extra("team".into(), "core".into());
+    let mut bob = Person::new("Bob".into());
+    bob.set_type("admin".into());
+
+    let mut builder = PersonArrayBuilder::new();
+    builder.append(&alice);
+    builder.append(&bob);
+    assert_eq!(builder.len(), 2);
+
+    let batch = builder.finish().unwrap();
+    assert!(builder.is_empty());
+    assert_eq!(batch.num_rows(), 2);
+    assert_eq!(batch.num_columns(), 3);
+
+    let names = batch.column(0).as_string::<i32>();
+    assert_eq!(names.value(0), "Alice");
+    assert_eq!(names.value(1), "Bob");
+    let ages = batch.column(1).as_primitive::<UInt32Type>();
+    assert_eq!(ages.value(0), 30);
+    assert!(ages.is_null(1));
+    let types = batch.column(2).as_string::<i32>();
+    assert!(types.is_null(0));
+    assert_eq!(types.value(1), "admin");
+}
+
+#[test]
+fn test_empty_batch() {
+    let batch = PersonArrayBuilder::default().finish().unwrap();
+    assert_eq!(batch.num_rows(), 0);
+    assert_eq!(batch.schema().as_ref(), &Person::arrow_schema());
+}
+
+#[test]
+fn test_generic_fields() {
+    fn batch<T: ArrowValue>(values: &[Tagged<T>]) -> RecordBatch {
+        let mut builder = TaggedArrayBuilder::new();
+        for value in values {
+            builder.append(value);
+        }
+        builder.finish().unwrap()
+    }
+    let batch = batch(&[Tagged::new(1.5), Tagged::new(2.5)]);
+    let tags = batch.column(0).as_primitive::<Float64Type>();
+    assert_eq!(tags.values().to_vec(), [1.5, 2.5]);
+}
+
+#[test]
+fn test_record_batch_roundtrip() {
+    let mut alice = Person::new("Alice".into());
+    alice.set_age(30);
+    let mut bob = Person::new("Bob".into());
+    bob.set_type("admin".into());
+    let people = [alice, bob];
+
+    let batch = Person::to_record_batch(&people).unwrap();
+    assert_eq!(Person::from_record_batch(&batch).unwrap(), people);
+}
+
+#[test]
+fn test_from_record_batch_by_column_name() {
+    let batch = RecordBatch::try_from_iter([
+        (
+            "age",
+            Arc::new(UInt32Array::from(vec![Some(1), None])) as ArrayRef,
+        ),
+        (
+            "name",
+            Arc::new(StringArray::from(vec!["a", "b"])) as ArrayRef,
+        ),
+    ])
+    .unwrap();
+    let people = Person::from_record_batch(&batch).unwrap();
+    assert_eq!(people[0].name(), "a");
+    assert_eq!(people[0].age(), Some(&1));
+    assert_eq!(people[1].age(), None);
+    assert_eq!(people[1].r#type(), None);
+}
+
+#[test]
+fn test_from_record_batch_errors() {
+    let missing =
+        RecordBatch::try_from_iter([("age", Arc::new(UInt32Array::from(vec![1])) as ArrayRef)])
+            .unwrap();
+    let err = Person::from_record_batch(&missing).unwrap_err();
+    assert!(err.to_string().contains("missing column `name`"), "{}", err);
+
+    let null = RecordBatch::try_from_iter([(
+        "name",
+        Arc::new(StringArray::from(vec![None::<&str>])) as ArrayRef,
+    )])
+    .unwrap();
+    let err = Person::from_record_batch(&null).unwrap_err();
+    assert!(
+        err.to_string().contains("null in required column `name`"),
+        "{}",
+        err
+    );
+
+    let mistyped =
+        RecordBatch::try_from_iter([("name", Arc::new(UInt32Array::from(vec![1])) as ArrayRef)])
+            .unwrap();
+    let err = Person::from_record_batch(&mistyped).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("expected a Utf8 column, found UInt32"),
+        "{}",
+        err
+    );
+}
@@ -0,0 +1,166 @@
+//! Tests for `#[structible(async)]` over an `AsyncBackingMap`.
+
+use std::collections::HashMap;
+use std::future::{Future, ready};
+use std::hash::Hash;
+use std::pin::pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+use structible::{AsyncBackingMap, structible};
+
+/// Polls a future to completion; the backings in these tests never suspend.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+fn lowercase(key: String) -> String {
+    key.to_lowercase()
+}
+
+#[structible(backing = HashMap, async)]
+pub struct Session {
+    pub user_id: u64,
+    pub token: Option<String>,
+    #[structible(key = String, normalize = lowercase)]
+    pub claims: Option<String>,
+}
+
+#[test]
+fn test_async_accessors() {
+    block_on(async {
+        let mut session = Session::new(HashMap::new(), 42).await.unwrap();
+        assert_eq!(session.user_id().await, Ok(Some(42)));
+        assert_eq!(session.token().await, Ok(None));
+
+        session.set_token("abc".into()).await.unwrap();
+        assert_eq!(session.token().await, Ok(Some("abc".to_string())));
+
+        session.set_user_id(7).await.unwrap();
+        assert_eq!(session.user_id().await, Ok(Some(7)));
+
+        assert_eq!(session.remove_token().await, Ok(Some("abc".to_string())));
+        assert_eq!(session.token().await, Ok(None));
+    });
+}
+
+#[test]
+fn test_async_unknown_fields() {
+    block_on(async {
+        let mut session = Session::new(HashMap::new(), 42).await.unwrap();
+        session
+            .insert_claims("Role".into(), "admin".into())
+            .await
+            .unwrap();
+        assert_eq!(session.claims("role").await, Ok(Some("admin".to_string())));
+        assert_eq!(session.claims("ROLE").await, Ok(Some("admin".to_string())));
+        assert_eq!(session.claims("scope").await, Ok(None));
+        assert_eq!(
+            session.remove_claims("role").await,
+            Ok(Some("admin".to_string()))
+        );
+        assert_eq!(session.backing().len(), 1);
+    });
+}
+
+#[test]
+fn test_from_backing_may_lack_required_fields() {
+    block_on(async {
+        let session = Session::from_backing(HashMap::new());
+        assert_eq!(session.user_id().await, Ok(None));
+        assert!(session.into_backing().is_empty());
+    });
+}
+
+// A store shared between handles that can be taken offline
+#[derive(Debug, PartialEq)]
+pub struct Offline;
+
+pub struct SharedStore<K, V> {
+    entries: Arc<Mutex<HashMap<K, V>>>,
+    online: Arc<AtomicBool>,
+}
+
+impl<K, V> SharedStore<K, V> {
+    fn new() -> Self {
+        Self {
+            entries: Arc::default(),
+            online: Arc::new(AtomicBool::new(true)),
+        }
+    }
+
+    fn handle(&self) -> Self {
+        Self {
+            entries: Arc::clone(&self.entries),
+            online: Arc::clone(&self.online),
+        }
+    }
+
+    fn check(&self) -> Result<(), Offline> {
+        if self.online.load(Ordering::SeqCst) {
+            Ok(())
+        } else {
+            Err(Offline)
+        }
+    }
+}
+
+impl<K, V> AsyncBackingMap<K, V> for SharedStore<K, V>
+where
+    K: Eq + Hash,
+    V: Clone + Send,
+{
+    type Error = Offline;
+
+    fn get(&self, key: &K) -> impl Future<Output = Result<Option<V>, Offline>> + Send {
+        ready(
+            self.check()
+                .map(|()| self.entries.lock().unwrap().get(key).cloned()),
+        )
+    }
+
+    fn insert(&mut self, key: K, value: V) -> impl Future<Output = Result<(), Offline>> + Send {
+        ready(self.check().map(|()| {
+            self.entries.lock().unwrap().insert(key, value);
+        }))
+    }
+
+    fn remove(&mut self, key: &K) -> impl Future<Output = Result<Option<V>, Offline>> + Send {
+        ready(
+            self.check()
+                .map(|()| self.entries.lock().unwrap().remove(key)),
+        )
+    }
+}
+
+#[structible(backing = SharedStore, async, constructor = create)]
+pub struct Cart {
+    pub owner: String,
+    pub coupon: Option<String>,
+}
+
+#[test]
+fn test_custom_async_backing() {
+    block_on(async {
+        let store = SharedStore::new();
+        let mut cart = Cart::create(store.handle(), "alice".into()).await.unwrap();
+        cart.set_coupon("SAVE10".into()).await.unwrap();
+
+        // Another handle over the same store sees the writes
+        let other = Cart::from_backing(store.handle());
+        assert_eq!(other.owner().await, Ok(Some("alice".to_string())));
+        assert_eq!(other.coupon().await, Ok(Some("SAVE10".to_string())));
+
+        store.online.store(false, Ordering::SeqCst);
+        assert_eq!(other.coupon().await, Err(Offline));
+        assert_eq!(cart.remove_coupon().await, Err(Offline));
+        assert!(Cart::create(store.handle(), "bob".into()).await.is_err());
+    });
+}
@@ -0,0 +1,62 @@
+//! Tests that field attributes are forwarded onto the generated accessors, and
+//! struct attributes onto the generated struct.
+//!
+//! The crate-level `deny(deprecated)` turns any deprecation warning from the generated
+//! code itself into a build failure, while the tests below must opt in to calling the
+//! deprecated accessors. That the deprecation itself reaches every accessor is checked
+//! by the `compile_fail` doctests of `DeprecatedAccessors` in the crate root.
+
+#![deny(deprecated)]
+
+use structible::structible;
+
+#[structible]
+pub struct Account {
+    pub id: u64,
+    /// The account holder's login name.
+    #[deprecated(note = "use `id` instead")]
+    pub login: Option<String>,
+    #[cfg_attr(test, deprecated(note = "use `id` instead"))]
+    pub legacy_id: Option<u32>,
+    #[allow(non_snake_case)]
+    pub displayName: Option<String>,
+}
+
+#[test]
+fn test_non_deprecated_accessors_are_usable() {
+    let mut account = Account::new(1);
+    account.set_displayName("Alice".into());
+    assert_eq!(account.displayName(), Some(&"Alice".to_string()));
+    assert_eq!(*account.id(), 1);
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_deprecated_accessors_still_work() {
+    let mut account = Account::new(1);
+    account.set_login("alice".into());
+    account.set_legacy_id(7);
+    assert_eq!(account.login(), Some(&"alice".to_string()));
+    assert_eq!(account.remove_legacy_id(), Some(7));
+
+    let mut fields = account.into_fields();
+    assert_eq!(fields.take_login(), Some("alice".to_string()));
+}
+
+/// Minimal structs leave `Debug` and `Default` to the user, so they may be derived.
+#[structible(minimal)]
+#[derive(Debug)]
+#[cfg_attr(test, derive(Default))]
+#[must_use]
+pub struct Settings {
+    pub theme: Option<String>,
+    pub font_size: Option<u32>,
+}
+
+#[test]
+fn test_struct_attributes_apply_to_the_generated_struct() {
+    let mut settings = Settings::default();
+    assert_eq!(settings.theme(), None);
+    settings.set_font_size(12);
+    assert!(format!("{:?}", settings).contains("12"));
+}
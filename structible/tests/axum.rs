@@ -0,0 +1,141 @@
+//! Tests for `#[structible(axum)]` request extractors.
+
+#![cfg(feature = "axum")]
+
+use axum::body::Body;
+use axum::extract::{FromRequest, Request};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use serde_json::{Value, json};
+use structible::ValidationError;
+use structible::axum::{FieldErrors, Rejection};
+use structible::structible;
+
+fn check_age(person: &Person) -> Result<(), ValidationError> {
+    match person.age() {
+        Some(age) if *age > 150 => Err(ValidationError::invariant("age out of range")),
+        _ => Ok(()),
+    }
+}
+
+#[structible(axum, invariant = check_age)]
+pub struct Person {
+    pub name: String,
+    pub age: Option<u32>,
+    pub r#type: Option<String>,
+    #[structible(key = String)]
+    pub extra: Option<String>,
+}
+
+#[structible(axum)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+fn json_request(body: &str) -> Request {
+    Request::builder()
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+async fn extract<T: FromRequest<()>>(body: &str) -> Result<T, T::Rejection> {
+    T::from_request(json_request(body), &()).await
+}
+
+async fn field_errors<T: FromRequest<(), Rejection = Rejection>>(body: &str) -> FieldErrors {
+    match extract::<T>(body).await {
+        Err(Rejection::Fields(errors)) => errors,
+        Err(rejection) => panic!("unexpected rejection: {}", rejection),
+        Ok(_) => panic!("body was accepted"),
+    }
+}
+
+#[tokio::test]
+async fn test_extracts_known_fields_and_catch_all() {
+    let person: Person = extract(r#"{"name": "Alice", "age": 30, "type": "admin", "plan": "pro"}"#)
+        .await
+        .unwrap();
+    assert_eq!(person.name(), "Alice");
+    assert_eq!(person.age(), Some(&30));
+    assert_eq!(person.r#type(), Some(&"admin".to_string()));
+    assert_eq!(person.extra("plan"), Some(&"pro".to_string()));
+}
+
+#[tokio::test]
+async fn test_lists_every_problem() {
+    let errors = field_errors::<Person>(r#"{"age": "old", "plan": 1}"#).await;
+    assert_eq!(errors.missing, ["name"]);
+    let keys: Vec<_> = errors.invalid.iter().map(|(key, _)| key.as_str()).collect();
+    assert_eq!(keys, ["age", "plan"]);
+
+    let errors = field_errors::<Point>(r#"{"x": 1, "z": 2}"#).await;
+    assert_eq!(errors.missing, ["y"]);
+    assert_eq!(errors.unknown, ["z"]);
+}
+
+#[tokio::test]
+async fn test_validation_violations() {
+    let errors = field_errors::<Person>(r#"{"name": "Alice", "age": 200}"#).await;
+    assert_eq!(errors.violations, ["invariant violated: age out of range"]);
+}
+
+#[tokio::test]
+async fn test_rejection_response() {
+    let rejection = extract::<Point>(r#"{"x": 1}"#).await.unwrap_err();
+    let response = rejection.into_response();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(
+        body,
+        json!({ "error": "missing required field `y`", "missing": ["y"] })
+    );
+}
+
+#[tokio::test]
+async fn test_non_object_bodies_are_rejected_like_json() {
+    let rejection = extract::<Point>("[1, 2]").await.unwrap_err();
+    assert!(matches!(rejection, Rejection::Json(_)));
+
+    let request = Request::builder().body(Body::from("{}")).unwrap();
+    let rejection = Point::from_request(request, &()).await.unwrap_err();
+    assert_eq!(
+        rejection.into_response().status(),
+        StatusCode::UNSUPPORTED_MEDIA_TYPE
+    );
+}
+
+#[structible(axum)]
+pub struct Wrapper<T> {
+    pub value: T,
+}
+
+#[tokio::test]
+async fn test_generic_struct() {
+    let wrapper: Wrapper<bool> = extract(r#"{"value": true}"#).await.unwrap();
+    assert!(*wrapper.value());
+}
+
+#[test]
+fn test_usable_as_handler_argument() {
+    async fn create(person: Person) -> String {
+        person.name().clone()
+    }
+    let _: axum::Router = axum::Router::new().route("/people", axum::routing::post(create));
+}
+
+#[structible(axum)]
+pub struct Renamed {
+    #[structible(alias = full_name)]
+    pub name: String,
+}
+
+#[tokio::test]
+async fn test_extracts_aliases() {
+    let renamed: Renamed = extract(r#"{"full_name": "Alice"}"#).await.unwrap();
+    assert_eq!(renamed.name(), "Alice");
+}
@@ -0,0 +1,117 @@
+//! Tests for `#[structible(builder)]` builders.
+
+use std::collections::BTreeMap;
+
+use structible::{ValidationError, structible};
+
+#[structible(builder, backing = BTreeMap, track_changes)]
+pub struct Person<T: Clone> {
+    pub name: String,
+    pub email: Option<String>,
+    pub tag: Option<T>,
+    #[structible(key = String)]
+    pub extra: Option<String>,
+}
+
+#[test]
+fn test_build_sets_fields() {
+    let person = Person::builder()
+        .email("alice@example.com".into())
+        .name("Alice".into())
+        .tag(7u8)
+        .insert_extra("plan".into(), "pro".into())
+        .build()
+        .unwrap();
+
+    assert_eq!(person.name(), "Alice");
+    assert_eq!(
+        person.email().map(String::as_str),
+        Some("alice@example.com")
+    );
+    assert_eq!(person.tag(), Some(&7));
+    assert_eq!(person.extra("plan").map(String::as_str), Some("pro"));
+    // Built through the constructor and the setters, so the writes are recorded
+    assert_eq!(person.change_log().len(), 4);
+}
+
+#[test]
+fn test_build_leaves_unset_fields_absent() {
+    let person = PersonBuilder::<u8>::default()
+        .name("Alice".into())
+        .build()
+        .unwrap();
+    assert_eq!(person, Person::new("Alice".into()));
+}
+
+#[test]
+fn test_build_fails_on_missing_required() {
+    let result = Person::<u8>::builder()
+        .email("a@example.com".into())
+        .build();
+    assert_eq!(
+        result.unwrap_err(),
+        ValidationError::MissingRequired { field: "name" }
+    );
+}
+
+#[structible(builder = panic, constructor = create)]
+pub struct Limits {
+    pub soft: u64,
+    pub hard: u64,
+    pub burst: Option<u64>,
+}
+
+#[test]
+fn test_panicking_build() {
+    let limits = Limits::builder().hard(20).soft(10).build();
+    assert_eq!(limits, Limits::create(10, 20));
+}
+
+#[test]
+#[should_panic(expected = "missing required field `hard`")]
+fn test_panicking_build_panics_on_missing_required() {
+    Limits::builder().soft(10).burst(5).build();
+}
+
+#[structible(builder = typestate)]
+pub struct Borrowed<'a, T, const N: usize> {
+    pub label: &'a str,
+    pub values: [T; N],
+    pub note: Option<&'a str>,
+}
+
+#[test]
+fn test_typestate_build() {
+    let borrowed = Borrowed::builder()
+        .note("first")
+        .values([1, 2])
+        .label("pair")
+        .build();
+    assert_eq!(borrowed.label(), &"pair");
+    assert_eq!(borrowed.values(), &[1, 2]);
+    assert_eq!(borrowed.note(), Some(&"first"));
+}
+
+#[test]
+fn test_typestate_state_types() {
+    let start: BorrowedBuilder<'_, u8, 1> = BorrowedBuilder::new();
+    let labeled: BorrowedBuilder<'_, u8, 1, true, false> = start.label("one");
+    // Setting a field again keeps the state
+    let labeled: BorrowedBuilder<'_, u8, 1, true, false> = labeled.label("uno");
+    let borrowed = labeled.values([1]).build();
+    assert_eq!(borrowed.label(), &"uno");
+}
+
+#[structible(builder = typestate)]
+pub struct Settings {
+    pub verbose: Option<bool>,
+}
+
+#[test]
+fn test_typestate_without_required_fields() {
+    assert_eq!(Settings::builder().build(), Settings::default());
+    assert_eq!(
+        Settings::builder().verbose(true).build().verbose(),
+        Some(&true)
+    );
+}
@@ -0,0 +1,184 @@
+//! Tests for `#[structible(track_changes)]` and the change log.
+
+use structible::{FieldChange, ValidationError, structible};
+
+#[structible(track_changes)]
+pub struct Person {
+    pub name: String,
+    pub email: Option<String>,
+    #[structible(key = String)]
+    pub extra: Option<String>,
+}
+
+#[test]
+fn test_constructor_records_required_fields() {
+    let mut person = Person::new("Alice".into());
+    assert_eq!(
+        person.take_change_log(),
+        vec![FieldChange::Set(
+            PersonField::Name,
+            PersonValue::Name("Alice".into())
+        )]
+    );
+    assert!(person.change_log().is_empty());
+}
+
+#[test]
+fn test_records_sets_and_removes_in_order() {
+    let mut person = Person::new("Alice".into());
+    person.take_change_log();
+
+    person.set_email("alice@example.com".into());
+    person.insert_extra("team".into(), "core".into());
+    assert_eq!(person.remove_email(), Some("alice@example.com".into()));
+    assert_eq!(person.remove_extra("team"), Some("core".into()));
+
+    assert_eq!(
+        person.take_change_log(),
+        vec![
+            FieldChange::Set(
+                PersonField::Email,
+                PersonValue::Email("alice@example.com".into())
+            ),
+            FieldChange::Set(
+                PersonField::Unknown("team".into()),
+                PersonValue::Unknown("core".into())
+            ),
+            FieldChange::Remove(PersonField::Email),
+            FieldChange::Remove(PersonField::Unknown("team".into())),
+        ]
+    );
+}
+
+#[test]
+fn test_removing_absent_field_is_not_recorded() {
+    let mut person = Person::new("Bob".into());
+    person.take_change_log();
+    assert_eq!(person.remove_email(), None);
+    assert!(person.change_log().is_empty());
+}
+
+#[test]
+fn test_change_log_does_not_affect_equality() {
+    let mut a = Person::new("Alice".into());
+    let b = Person::new("Alice".into());
+    a.take_change_log();
+    assert_eq!(a, b);
+    assert_eq!(a.clone().change_log().len(), 0);
+    assert_eq!(b.clone().change_log().len(), 1);
+}
+
+#[test]
+fn test_change_key() {
+    let change: FieldChange<PersonField, PersonValue> = FieldChange::Remove(PersonField::Email);
+    assert_eq!(change.key(), &PersonField::Email);
+}
+
+// Generic structs need `Clone` field types to record changes
+#[structible(track_changes)]
+pub struct Wrapper<T> {
+    pub value: T,
+}
+
+#[test]
+fn test_generic_change_log() {
+    let mut wrapper = Wrapper::new(vec![1, 2]);
+    wrapper.set_value(vec![3]);
+    let log: Vec<FieldChange<WrapperField, WrapperValue<Vec<i32>>>> = wrapper.take_change_log();
+    assert_eq!(log.len(), 2);
+    assert_eq!(log[1].key(), &WrapperField::Value);
+}
+
+#[test]
+fn test_apply_changes_replays_log() {
+    let mut primary = Person::new("Alice".into());
+    primary.set_email("alice@example.com".into());
+    primary.insert_extra("team".into(), "core".into());
+    primary.remove_extra("team");
+    let log = primary.take_change_log();
+
+    let mut replica = Person::new(String::new());
+    replica.take_change_log();
+    replica.apply_changes(log.clone());
+    assert_eq!(replica, primary);
+
+    // Replayed changes are recorded in the replica's own log
+    assert_eq!(replica.take_change_log(), log);
+}
+
+#[test]
+fn test_try_apply_changes_rejects_missing_required_field() {
+    let mut person = Person::new("Alice".into());
+    person.take_change_log();
+
+    let err = person
+        .try_apply_changes([
+            FieldChange::Set(PersonField::Email, PersonValue::Email("a@b.c".into())),
+            FieldChange::Remove(PersonField::Name),
+        ])
+        .unwrap_err();
+    assert_eq!(err, ValidationError::MissingRequired { field: "name" });
+    assert_eq!(err.to_string(), "required field `name` is missing");
+
+    // The struct is left unchanged
+    assert_eq!(person.email(), None);
+    assert_eq!(person.name(), "Alice");
+    assert!(person.change_log().is_empty());
+}
+
+#[test]
+fn test_try_apply_changes_rejects_mismatched_change() {
+    let mut person = Person::new("Alice".into());
+    assert_eq!(
+        person.try_apply_changes([FieldChange::Set(
+            PersonField::Name,
+            PersonValue::Email("a@b.c".into())
+        )]),
+        Err(ValidationError::MismatchedChange { field: "name" })
+    );
+}
+
+#[test]
+#[should_panic(expected = "change to field `email` carries another field's value")]
+fn test_apply_changes_panics_on_mismatched_change() {
+    let mut person = Person::new("Alice".into());
+    person.apply_changes([FieldChange::Set(
+        PersonField::Email,
+        PersonValue::Name("Bob".into()),
+    )]);
+}
+
+// Replay runs the struct's declared validation as well
+fn check_order(order: &Order) -> Result<(), ValidationError> {
+    if *order.quantity() > 0 {
+        Ok(())
+    } else {
+        Err(ValidationError::invariant("quantity must be positive"))
+    }
+}
+
+#[structible(track_changes, invariant = check_order)]
+pub struct Order {
+    pub quantity: u32,
+}
+
+#[test]
+fn test_try_apply_changes_runs_validation() {
+    let mut order = Order::new(1);
+    assert_eq!(
+        order.try_apply_changes([FieldChange::Set(
+            OrderField::Quantity,
+            OrderValue::Quantity(0)
+        )]),
+        Err(ValidationError::invariant("quantity must be positive"))
+    );
+    assert_eq!(*order.quantity(), 1);
+
+    order
+        .try_apply_changes([FieldChange::Set(
+            OrderField::Quantity,
+            OrderValue::Quantity(5),
+        )])
+        .unwrap();
+    assert_eq!(*order.quantity(), 5);
+}
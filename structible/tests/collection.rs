@@ -0,0 +1,70 @@
+//! Tests for the `push_`, `extend_`, and `_contains` helpers of `collection` fields.
+
+use std::collections::{BTreeMap, HashSet, VecDeque};
+
+use structible::structible;
+
+#[structible]
+pub struct Page {
+    #[structible(collection)]
+    pub links: Vec<String>,
+    #[structible(collection)]
+    pub tags: Option<HashSet<String>>,
+    #[structible(collection, get = header)]
+    pub headers: Option<BTreeMap<String, String>>,
+    #[structible(collection)]
+    pub history: Option<VecDeque<u32>>,
+}
+
+#[structible]
+pub struct Stack<T> {
+    #[structible(collection)]
+    pub items: Option<Vec<T>>,
+}
+
+#[structible(no_setters)]
+pub struct Snapshot {
+    #[structible(collection)]
+    pub ids: Vec<u32>,
+}
+
+#[test]
+fn test_required_collection() {
+    let mut page = Page::new(Vec::new());
+    page.push_links("https://example.com".into());
+    page.extend_links(["https://example.org".to_string()]);
+    assert_eq!(page.links().len(), 2);
+    assert!(page.links_contains(&"https://example.org".to_string()));
+    assert!(!page.links_contains(&"https://example.net".to_string()));
+}
+
+#[test]
+fn test_optional_collections_are_created() {
+    let mut page = Page::new(Vec::new());
+    assert!(!page.tags_contains(&"rust".to_string()));
+    assert!(page.push_tags("rust".into()));
+    assert!(!page.push_tags("rust".into()));
+    assert!(page.tags_contains(&"rust".to_string()));
+
+    assert_eq!(page.push_headers("accept".into(), "text/html".into()), None);
+    page.extend_headers([("host".to_string(), "example.com".to_string())]);
+    assert!(page.header_contains(&"host".to_string()));
+    assert_eq!(page.header().unwrap().len(), 2);
+
+    page.extend_history([1, 2]);
+    page.push_history(3);
+    assert_eq!(page.history(), Some(&VecDeque::from([1, 2, 3])));
+}
+
+#[test]
+fn test_generic_collection() {
+    let mut stack = Stack::<f64>::new();
+    stack.push_items(1.5);
+    assert!(stack.items_contains(&1.5));
+}
+
+#[test]
+fn test_no_setters_keeps_contains() {
+    let snapshot = Snapshot::new(vec![7]);
+    assert!(snapshot.ids_contains(&7));
+}
@@ -0,0 +1,141 @@
+//! Tests for `compat_report()` and the generated `Schema` impls.
+
+use std::collections::HashMap;
+
+use structible::{CompatReport, FieldDescriptor, Schema, structible};
+
+mod v1 {
+    use structible::structible;
+
+    #[structible]
+    pub struct Person {
+        pub name: String,
+        pub age: Option<u32>,
+        pub nickname: Option<String>,
+        #[structible(key = String)]
+        pub extra: Option<String>,
+    }
+}
+
+mod v2 {
+    use structible::structible;
+
+    #[structible]
+    pub struct Person {
+        pub nickname: Option<String>,
+        pub name: String,
+        pub age: Option<u32>,
+        pub email: Option<String>,
+        #[structible(key = String)]
+        pub extra: Option<String>,
+    }
+}
+
+#[structible]
+pub struct Person {
+    pub name: String,
+    pub age: u32,
+    pub r#type: Option<String>,
+    #[structible(key = u32)]
+    pub extra: Option<String>,
+}
+
+#[structible]
+pub struct Wrapper<T> {
+    pub value: T,
+    pub labels: Option<HashMap<String, T>>,
+}
+
+#[test]
+fn test_descriptors() {
+    assert_eq!(
+        Person::FIELDS,
+        [
+            FieldDescriptor {
+                name: "name",
+                ty: "String",
+                key: None,
+                optional: false,
+            },
+            FieldDescriptor {
+                name: "age",
+                ty: "u32",
+                key: None,
+                optional: false,
+            },
+            FieldDescriptor {
+                name: "type",
+                ty: "Option<String>",
+                key: None,
+                optional: true,
+            },
+            FieldDescriptor {
+                name: "extra",
+                ty: "Option<String>",
+                key: Some("u32"),
+                optional: true,
+            },
+        ]
+    );
+    let types: Vec<_> = Wrapper::<u8>::FIELDS.iter().map(|f| f.ty).collect();
+    assert_eq!(types, ["T", "Option<HashMap<String, T>>"]);
+}
+
+#[test]
+fn test_identical_versions() {
+    let report = v1::Person::compat_report::<v1::Person>();
+    assert!(report.is_empty());
+    assert!(report.is_compatible());
+}
+
+#[test]
+fn test_added_optional_field_is_compatible() {
+    let report = v2::Person::compat_report::<v1::Person>();
+    let added: Vec<_> = report.added.iter().map(|f| f.name).collect();
+    assert_eq!(added, ["email"]);
+    assert!(report.removed.is_empty());
+    assert!(report.retyped.is_empty());
+    assert!(report.is_compatible());
+
+    let report = v1::Person::compat_report::<v2::Person>();
+    let removed: Vec<_> = report.removed.iter().map(|f| f.name).collect();
+    assert_eq!(removed, ["email"]);
+    assert!(!report.is_compatible());
+}
+
+#[test]
+fn test_retyped_fields() {
+    let report = Person::compat_report::<v1::Person>();
+    let retyped: Vec<_> = report
+        .retyped
+        .iter()
+        .map(|(old, new)| (old.name, old.ty, new.ty))
+        .collect();
+    assert_eq!(
+        retyped,
+        [
+            ("age", "Option<u32>", "u32"),
+            ("extra", "Option<String>", "Option<String>"),
+        ]
+    );
+    assert_eq!(report.retyped[1].0.key, Some("String"));
+    assert_eq!(report.retyped[1].1.key, Some("u32"));
+    assert_eq!(report.added.len(), 1);
+    assert_eq!(report.removed.len(), 1);
+    assert!(!report.is_compatible());
+}
+
+#[test]
+fn test_added_required_field_is_incompatible() {
+    let report = CompatReport::from_fields(
+        &[],
+        &[FieldDescriptor {
+            name: "id",
+            ty: "u64",
+            key: None,
+            optional: false,
+        }],
+    );
+    assert!(!report.is_empty());
+    assert!(!report.is_compatible());
+}
@@ -1,3 +1,6 @@
+// The hidden field and value enums are always `pub`, while these test types are private
+#![allow(private_interfaces)]
+
 use std::fmt::Debug;
 use std::marker::PhantomData;
 
@@ -178,3 +181,121 @@ fn test_default_type_param() {
     let s2 = WithDefault::new("hello".to_string());
     assert_eq!(s2.content(), "hello");
 }
+
+trait Parent {
+    type Child;
+}
+
+trait Owned<O> {}
+
+impl<O> Owned<O> for String {}
+
+#[structible]
+struct SelfBound<T>
+where
+    T: Owned<Self> + Clone + PartialEq + Debug,
+    Self: Send,
+{
+    pub name: String,
+    pub owned: Option<T>,
+}
+
+#[structible]
+struct QualifiedSelf<T>
+where
+    T: Parent<Child = u32> + Debug,
+    Self: Parent<Child = u32>,
+{
+    pub id: u64,
+    pub count: Option<<Self as Parent>::Child>,
+    pub marker: std::marker::PhantomData<T>,
+}
+
+impl<T: Parent<Child = u32> + Debug> Parent for QualifiedSelf<T> {
+    type Child = u32;
+}
+
+impl Parent for () {
+    type Child = u32;
+}
+
+#[test]
+fn test_self_in_where_clause() {
+    let mut s = SelfBound::<String>::new("a".into());
+    s.set_owned("b".into());
+    assert_eq!(s.owned(), Some(&"b".to_string()));
+    assert_eq!(s.clone(), s);
+
+    let mut fields = s.into_fields();
+    assert_eq!(fields.take_owned(), Some("b".to_string()));
+}
+
+#[test]
+fn test_qualified_self_in_where_clause() {
+    let mut s = QualifiedSelf::<()>::new(1, std::marker::PhantomData);
+    s.set_count(3);
+    assert_eq!(s.count(), Some(&3));
+}
+
+// Parameters used only by the catch-all's value type are carried by the value
+// enum, so the generated struct and companion types need no marker field
+#[structible(patch, cow, freeze, accessor_traits)]
+struct VendorOnly<'a, V> {
+    pub name: Option<String>,
+    #[structible(key = String)]
+    pub vendor_property: Option<(&'a str, V)>,
+}
+
+#[structible]
+struct VendorOnlyMulti<V> {
+    pub name: String,
+    #[structible(key = String, multi)]
+    pub vendor_property: Option<Vec<V>>,
+}
+
+#[test]
+fn test_params_only_in_catch_all_value() {
+    let mut s = VendorOnly::<bool>::new();
+    s.insert_vendor_property("example.com:foo".into(), ("foo", true));
+    assert_eq!(s.vendor_property("example.com:foo"), Some(&("foo", true)));
+
+    let frozen = s.clone().freeze();
+    assert_eq!(
+        frozen.vendor_property("example.com:foo"),
+        Some(&("foo", true))
+    );
+
+    let mut fields = s.into_fields();
+    let drained = fields.drain_vendor_property();
+    assert_eq!(drained["example.com:foo"], ("foo", true));
+
+    let mut s = VendorOnlyMulti::<u8>::new("a".into());
+    s.append_vendor_property("x".into(), 1);
+    s.append_vendor_property("x".into(), 2);
+    assert_eq!(s.vendor_property_all("x"), [1, 2]);
+}
+
+#[structible(patch, freeze, cow, accessor_traits)]
+struct WithDefaults<T: Clone = String, const N: usize = 2> {
+    pub name: Option<T>,
+    pub bytes: Option<[u8; N]>,
+    #[structible(key = String)]
+    pub extra: Option<T>,
+}
+
+#[test]
+fn test_default_parameters_on_generated_types() {
+    let mut s: WithDefaults = WithDefaults::new();
+    s.set_name("a".into());
+    s.set_bytes([1, 2]);
+
+    let value: WithDefaultsValue = WithDefaultsValue::Name("a".into());
+    let field: WithDefaultsField = WithDefaultsField::Name;
+    let backing = structible::Structible::backing(&s);
+    assert_eq!(structible::BackingMap::get(backing, &field), Some(&value));
+
+    let frozen: FrozenWithDefaults = s.clone().freeze();
+    assert_eq!(frozen.name(), Some(&"a".to_string()));
+    let mut fields: WithDefaultsFields = s.into_fields();
+    assert_eq!(fields.take_bytes(), Some([1, 2]));
+}
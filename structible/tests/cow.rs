@@ -0,0 +1,92 @@
+//! Tests for `#[structible(cow)]` copy-on-write handles.
+
+use structible::structible;
+
+#[structible(cow)]
+pub struct Document {
+    pub title: String,
+    pub author: Option<String>,
+    #[structible(key = String)]
+    pub meta: Option<String>,
+}
+
+fn document() -> DocumentCow {
+    let mut doc = Document::new("Draft".into());
+    doc.set_author("alice".into());
+    DocumentCow::new(doc)
+}
+
+#[test]
+fn test_clones_share_until_written() {
+    let original = document();
+    let mut copy = original.clone();
+    assert!(!copy.is_unique());
+    assert_eq!(copy, original);
+
+    copy.set_title("Final".into());
+    assert!(copy.is_unique());
+    assert!(original.is_unique());
+    assert_eq!(original.title(), "Draft");
+    assert_eq!(copy.title(), "Final");
+}
+
+#[test]
+fn test_unique_handle_is_not_cloned() {
+    let mut doc = document();
+    let before: *const Document = &*doc;
+    doc.set_title("Final".into());
+    assert_eq!(before, &*doc as *const Document);
+}
+
+#[test]
+fn test_delegated_writes() {
+    let original = document();
+    let mut copy = original.clone();
+    assert_eq!(copy.remove_author(), Some("alice".into()));
+    assert_eq!(copy.insert_meta("lang".into(), "en".into()), None);
+    assert_eq!(copy.meta("lang"), Some(&"en".to_string()));
+    assert_eq!(copy.remove_meta("lang"), Some("en".into()));
+
+    assert_eq!(original.author(), Some(&"alice".to_string()));
+    assert_eq!(original.meta("lang"), None);
+}
+
+#[test]
+fn test_make_mut() {
+    let original = document();
+    let mut copy = original.clone();
+    *copy.make_mut().title_mut() = "Edited".into();
+    assert_eq!(copy.title(), "Edited");
+    assert_eq!(original.title(), "Draft");
+}
+
+#[test]
+fn test_into_inner() {
+    let original = document();
+    let copy = original.clone();
+    let doc = copy.into_inner();
+    assert_eq!(doc.title(), "Draft");
+    assert!(original.is_unique());
+    assert_eq!(DocumentCow::from(doc), original);
+}
+
+#[test]
+fn test_debug() {
+    let doc = DocumentCow::from(Document::new("Draft".into()));
+    assert_eq!(format!("{:?}", doc), r#"Document { title: "Draft" }"#);
+}
+
+#[structible(cow)]
+pub struct Slot<T> {
+    pub value: Option<T>,
+}
+
+#[test]
+fn test_generic_handle() {
+    let mut slot = SlotCow::new(Slot::<u8>::default());
+    let shared = slot.clone();
+    slot.set_value(1);
+    assert_eq!(slot.value(), Some(&1));
+    assert_eq!(shared.value(), None);
+    assert_ne!(slot, shared);
+}
@@ -0,0 +1,133 @@
+//! Tests for `#[structible(csv)]` record encoding and decoding.
+
+#![cfg(feature = "csv")]
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use csv::StringRecord;
+use structible::csv::CsvError;
+use structible::{ValidationError, structible};
+
+#[structible(csv)]
+pub struct Person {
+    pub name: String,
+    pub age: Option<u32>,
+    pub r#type: Option<String>,
+    #[structible(key = String)]
+    pub extra: Option<String>,
+}
+
+#[structible(csv)]
+pub struct Contact {
+    #[structible(alias = mail)]
+    pub email: Option<String>,
+}
+
+#[structible(csv)]
+pub struct Tagged<T> {
+    pub tag: T,
+}
+
+fn record(cells: &[&str]) -> StringRecord {
+    StringRecord::from(cells.to_vec())
+}
+
+#[test]
+fn test_headers_in_declaration_order() {
+    assert_eq!(Person::csv_headers(), record(&["name", "age", "type"]));
+}
+
+#[test]
+fn test_to_csv_record_follows_headers() {
+    let mut person = Person::new("Alice".into());
+    person.set_age(30);
+    person.insert_extra("team".into(), "core".into());
+
+    let headers = record(&["type", "extra.team", "age", "name", "extra.role"]);
+    assert_eq!(
+        person.to_csv_record(&headers).unwrap(),
+        record(&["", "core", "30", "Alice", ""])
+    );
+    assert_eq!(
+        person.to_csv_record(&record(&["email"])),
+        Err(CsvError::UnknownColumn("email".into()))
+    );
+}
+
+#[test]
+fn test_from_csv_record() {
+    let headers = record(&["age", "name", "type", "extra.team"]);
+    let person = Person::from_csv_record(&headers, &record(&["30", "", "", "core"])).unwrap();
+    assert_eq!(person.name(), "");
+    assert_eq!(person.age(), Some(&30));
+    assert_eq!(person.r#type(), None);
+    assert_eq!(person.extra("team"), Some(&"core".to_string()));
+
+    let roundtrip = Person::from_csv_record(&headers, &person.to_csv_record(&headers).unwrap());
+    assert_eq!(roundtrip.unwrap(), person);
+}
+
+#[test]
+fn test_from_csv_record_errors() {
+    let headers = record(&["name", "age"]);
+    assert!(matches!(
+        Person::from_csv_record(&headers, &record(&["Alice", "old"])),
+        Err(CsvError::Parse { column, .. }) if column == "age"
+    ));
+    assert_eq!(
+        Person::from_csv_record(&headers, &record(&["Alice"])),
+        Err(CsvError::UnequalLengths {
+            headers: 2,
+            cells: 1
+        })
+    );
+    assert_eq!(
+        Person::from_csv_record(&record(&["age"]), &record(&["30"])),
+        Err(CsvError::Validation(ValidationError::MissingRequired {
+            field: "name"
+        }))
+    );
+}
+
+#[test]
+fn test_aliases_name_columns() {
+    let contact = Contact::from_csv_record(&record(&["mail"]), &record(&["a@b.c"])).unwrap();
+    assert_eq!(contact.email(), Some(&"a@b.c".to_string()));
+    assert_eq!(Contact::csv_headers(), record(&["email"]));
+    assert_eq!(
+        contact.to_csv_record(&record(&["mail"])).unwrap(),
+        record(&["a@b.c"])
+    );
+}
+
+#[test]
+fn test_duplicate_columns() {
+    let headers = record(&["email", "mail"]);
+    assert_eq!(
+        Contact::from_csv_record(&headers, &record(&["", "a@b.c"])),
+        Err(CsvError::DuplicateColumn("mail".into()))
+    );
+    assert_eq!(
+        Contact::new().to_csv_record(&headers),
+        Err(CsvError::DuplicateColumn("mail".into()))
+    );
+    assert_eq!(
+        Person::from_csv_record(&record(&["name", "name"]), &record(&["Alice", "Bob"])),
+        Err(CsvError::DuplicateColumn("name".into()))
+    );
+}
+
+#[test]
+fn test_generic_fields() {
+    fn roundtrip<T>(tagged: &Tagged<T>) -> Tagged<T>
+    where
+        T: Display + FromStr + Clone,
+        T::Err: Display,
+    {
+        let headers = Tagged::<T>::csv_headers();
+        let record = tagged.to_csv_record(&headers).unwrap();
+        Tagged::from_csv_record(&headers, &record).unwrap()
+    }
+    assert_eq!(roundtrip(&Tagged::new(1.5)).tag(), &1.5);
+}
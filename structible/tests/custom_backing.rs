@@ -3,7 +3,7 @@ use structible::{BackingMap, IterableMap, structible};
 
 // A simple wrapper around BTreeMap to demonstrate custom backing types
 #[derive(Debug, Clone, PartialEq)]
-struct MyMap<K, V> {
+pub struct MyMap<K, V> {
     inner: BTreeMap<K, V>,
 }
 
@@ -0,0 +1,129 @@
+//! Tests for `#[structible(debug_json)]` and the `DebugJson` trait.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::Duration;
+
+use structible::{DebugJson, structible};
+
+#[structible(debug_json)]
+pub struct Person {
+    pub name: String,
+    pub age: Option<u32>,
+    pub scores: HashMap<String, Vec<f64>>,
+    pub r#type: Option<char>,
+    #[structible(key = String)]
+    pub extra: Option<Option<bool>>,
+}
+
+#[test]
+fn test_present_fields_sorted() {
+    let mut scores = HashMap::new();
+    scores.insert("math".to_string(), vec![1.5, 2.0]);
+    scores.insert("art".to_string(), vec![]);
+    let mut person = Person::new("Alice \"A\"".into(), scores);
+    person.set_type('x');
+    person.insert_extra("zeta".into(), Some(true));
+    person.insert_extra("alpha".into(), None);
+
+    assert_eq!(
+        person.to_debug_json(),
+        r#"{
+  "extra": {
+    "alpha": null,
+    "zeta": true
+  },
+  "name": "Alice \"A\"",
+  "scores": {
+    "art": [],
+    "math": [
+      1.5,
+      2.0
+    ]
+  },
+  "type": "x"
+}"#
+    );
+}
+
+#[test]
+fn test_output_is_deterministic() {
+    let render = |keys: &[&str]| {
+        let scores: HashMap<_, _> = keys.iter().map(|k| (k.to_string(), vec![])).collect();
+        let mut person = Person::new("Bob".into(), scores);
+        for key in keys {
+            person.insert_extra(key.to_string(), Some(false));
+        }
+        person.to_debug_json()
+    };
+    let keys = ["d", "a", "c", "b", "f", "e", "h", "g"];
+    let mut reversed = keys;
+    reversed.reverse();
+    assert_eq!(render(&keys), render(&reversed));
+}
+
+#[structible(debug_json, backing = BTreeMap)]
+pub struct Team<T> {
+    pub lead: Person,
+    pub members: Vec<T>,
+    pub tags: Option<HashSet<u8>>,
+}
+
+#[test]
+fn test_nested_structs() {
+    let mut team = Team::new(Person::new("Carol".into(), HashMap::new()), vec![(1, "a")]);
+    team.set_tags([3, 1, 2].into());
+
+    assert_eq!(
+        team.to_debug_json(),
+        r#"{
+  "lead": {
+    "name": "Carol",
+    "scores": {}
+  },
+  "members": [
+    [
+      1,
+      "a"
+    ]
+  ],
+  "tags": [
+    1,
+    2,
+    3
+  ]
+}"#
+    );
+    assert_eq!(
+        structible::debug_json::to_string(&team),
+        team.to_debug_json()
+    );
+}
+
+#[structible(debug_json, ttl)]
+pub struct Session {
+    pub token: Option<String>,
+    pub user: Option<String>,
+}
+
+#[test]
+fn test_expired_fields_are_skipped() {
+    let mut session = Session::default();
+    session.set_token_with_ttl("abc".into(), Duration::ZERO);
+    session.set_user("dave".into());
+    assert_eq!(session.to_debug_json(), "{\n  \"user\": \"dave\"\n}");
+    assert_eq!(Session::default().to_debug_json(), "{}");
+}
+
+#[test]
+fn test_map_keys() {
+    let map: BTreeMap<u8, &str> = [(10, "ten"), (2, "two")].into();
+    assert_eq!(
+        structible::debug_json::to_string(&map),
+        "{\n  \"10\": \"ten\",\n  \"2\": \"two\"\n}"
+    );
+    assert_eq!("tab\there".debug_json_key(), "tab\there");
+    assert_eq!(
+        structible::debug_json::to_string("tab\there"),
+        r#""tab\there""#
+    );
+}
@@ -103,3 +103,58 @@ fn test_debug_alternate_format() {
     assert!(debug_str.contains('\n'));
     assert!(debug_str.contains("name: \"Diana\""));
 }
+
+#[test]
+fn test_debug_order_is_deterministic() {
+    let keys = [
+        "zeta", "alpha", "mu", "beta", "omega", "gamma", "delta", "kappa",
+    ];
+    let mut forward = WithUnknown::new("test".to_string());
+    for key in keys {
+        forward.insert_extra(key.to_string(), key.to_uppercase());
+    }
+    let mut backward = WithUnknown::new("test".to_string());
+    for key in keys.iter().rev() {
+        backward.insert_extra(key.to_string(), key.to_uppercase());
+    }
+
+    let debug_str = format!("{:?}", forward);
+    assert_eq!(debug_str, format!("{:?}", backward));
+    assert_eq!(
+        debug_str,
+        "WithUnknown { name: \"test\", \"alpha\": \"ALPHA\", \"beta\": \"BETA\", \
+         \"delta\": \"DELTA\", \"gamma\": \"GAMMA\", \"kappa\": \"KAPPA\", \"mu\": \"MU\", \
+         \"omega\": \"OMEGA\", \"zeta\": \"ZETA\" }"
+    );
+    assert_eq!(
+        format!("{:?}", forward.into_fields()),
+        debug_str.replacen("WithUnknown", "WithUnknownFields", 1)
+    );
+}
+
+#[test]
+fn test_debug_known_fields_in_declaration_order() {
+    let mut all = AllOptional::default();
+    all.set_third(true);
+    all.set_first("a".to_string());
+    all.set_second(2);
+    assert_eq!(
+        format!("{:?}", all),
+        "AllOptional { first: \"a\", second: 2, third: true }"
+    );
+}
+
+#[structible]
+struct Keyword {
+    r#type: String,
+}
+
+#[test]
+fn test_debug_unraws_field_names() {
+    let keyword = Keyword::new("fn".to_string());
+    assert_eq!(format!("{:?}", keyword), "Keyword { type: \"fn\" }");
+    assert_eq!(
+        format!("{:?}", keyword.into_fields()),
+        "KeywordFields { type: \"fn\" }"
+    );
+}
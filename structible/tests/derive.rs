@@ -0,0 +1,51 @@
+//! Tests for the `#[derive(Structible)]` entry point.
+
+use std::collections::BTreeMap;
+
+use structible::Structible;
+
+/// A person, declared through the derive.
+#[allow(dead_code)]
+#[derive(Structible)]
+#[structible(name = Person, backing = BTreeMap)]
+#[structible(with_len)]
+pub struct PersonDef {
+    pub name: String,
+    pub age: Option<u32>,
+    #[structible(key = String)]
+    pub extra: Option<String>,
+}
+
+#[allow(dead_code)]
+#[derive(Structible)]
+#[structible(name = Wrapper)]
+pub struct WrapperDef<T: Clone> {
+    pub value: T,
+    pub next: Option<Box<Self>>,
+}
+
+#[test]
+fn test_generated_struct() {
+    let mut person = Person::new("Alice".into());
+    assert_eq!(person.name(), "Alice");
+    assert_eq!(person.age(), None);
+    person.set_age(30);
+    person.insert_extra("team".into(), "core".into());
+    assert_eq!(person.age(), Some(&30));
+    assert_eq!(person.extra("team"), Some(&"core".to_string()));
+    assert_eq!(person.len(), 3);
+}
+
+#[test]
+fn test_struct_level_arguments_are_combined() {
+    let person = Person::new("Alice".into());
+    let _: &BTreeMap<_, _> = structible::Structible::backing(&person);
+    assert_eq!(person.len(), 1);
+}
+
+#[test]
+fn test_generics_and_self() {
+    let mut wrapper = Wrapper::new(1);
+    wrapper.set_next(Box::new(Wrapper::new(2)));
+    assert_eq!(wrapper.next().map(|next| *next.value()), Some(2));
+}
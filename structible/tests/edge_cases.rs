@@ -188,3 +188,36 @@ fn test_raw_identifiers() {
     obj.set_type("updated".into());
     assert_eq!(obj.r#type(), "updated");
 }
+
+// Test From<(T1, T2, ...)> over the required fields
+#[structible(from_tuple)]
+pub struct FromTuple {
+    pub name: String,
+    pub nickname: Option<String>,
+    pub age: u32,
+}
+
+#[test]
+fn test_from_tuple() {
+    let obj = FromTuple::from(("Alice".to_string(), 30));
+    assert_eq!(obj.name(), "Alice");
+    assert_eq!(*obj.age(), 30);
+    assert_eq!(obj.nickname(), None);
+
+    let all: Vec<FromTuple> = vec![("Bob".to_string(), 25), ("Carol".to_string(), 41)]
+        .into_iter()
+        .map(FromTuple::from)
+        .collect();
+    assert_eq!(all[1].name(), "Carol");
+}
+
+#[structible(from_tuple, constructor = create)]
+pub struct FromSingleTuple<T> {
+    pub value: T,
+}
+
+#[test]
+fn test_from_single_element_tuple() {
+    let obj: FromSingleTuple<i32> = (7,).into();
+    assert_eq!(*obj.value(), 7);
+}
@@ -0,0 +1,120 @@
+//! Tests for `#[structible(envelope)]` schema-checked payloads.
+
+#![cfg(feature = "serde")]
+
+use structible::envelope::{EnvelopeError, schema_fingerprint};
+use structible::structible;
+
+#[structible(serde_backing, envelope)]
+pub struct Person {
+    pub name: String,
+    pub age: Option<u32>,
+    #[structible(key = String)]
+    pub extra: Option<String>,
+}
+
+mod reordered {
+    use structible::structible;
+
+    #[structible(serde_backing, envelope)]
+    pub struct Person {
+        #[structible(key = String)]
+        pub extra: Option<String>,
+        pub age: Option<u32>,
+        pub name: String,
+    }
+}
+
+mod retyped {
+    use structible::structible;
+
+    #[structible(serde_backing, envelope)]
+    pub struct Person {
+        pub name: String,
+        pub age: Option<u64>,
+        #[structible(key = String)]
+        pub extra: Option<String>,
+    }
+}
+
+mod required {
+    use structible::structible;
+
+    #[structible(serde_backing, envelope)]
+    pub struct Person {
+        pub name: String,
+        pub age: u32,
+        #[structible(key = String)]
+        pub extra: Option<String>,
+    }
+}
+
+#[test]
+fn test_roundtrip() {
+    let mut person = Person::new("Alice".into());
+    person.set_age(30);
+    person.insert_extra("team".into(), "core".into());
+
+    let bytes = person.to_envelope().unwrap();
+    assert_eq!(bytes[0], 0);
+    assert_eq!(
+        schema_fingerprint(&bytes).unwrap(),
+        Person::SCHEMA_FINGERPRINT
+    );
+    assert_eq!(Person::from_envelope(&bytes).unwrap(), person);
+}
+
+#[test]
+fn test_fingerprint_ignores_field_order() {
+    assert_eq!(
+        Person::SCHEMA_FINGERPRINT,
+        reordered::Person::SCHEMA_FINGERPRINT
+    );
+    let bytes = Person::new("Alice".into()).to_envelope().unwrap();
+    assert_eq!(
+        reordered::Person::from_envelope(&bytes).unwrap().name(),
+        "Alice"
+    );
+}
+
+#[test]
+fn test_schema_mismatch() {
+    assert_ne!(
+        Person::SCHEMA_FINGERPRINT,
+        retyped::Person::SCHEMA_FINGERPRINT
+    );
+    assert_ne!(
+        Person::SCHEMA_FINGERPRINT,
+        required::Person::SCHEMA_FINGERPRINT
+    );
+
+    let bytes = Person::new("Alice".into()).to_envelope().unwrap();
+    match retyped::Person::from_envelope(&bytes) {
+        Err(EnvelopeError::SchemaMismatch { expected, found }) => {
+            assert_eq!(expected, retyped::Person::SCHEMA_FINGERPRINT);
+            assert_eq!(found, Person::SCHEMA_FINGERPRINT);
+        }
+        other => panic!("expected a schema mismatch, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_malformed_envelopes() {
+    assert!(matches!(
+        Person::from_envelope(&[0, 1, 2]),
+        Err(EnvelopeError::Malformed)
+    ));
+    let mut bytes = Person::new("Alice".into()).to_envelope().unwrap();
+    bytes[0] = b'{';
+    assert!(matches!(
+        Person::from_envelope(&bytes),
+        Err(EnvelopeError::Malformed)
+    ));
+
+    let mut bytes = Person::new("Alice".into()).to_envelope().unwrap();
+    bytes.truncate(bytes.len() - 1);
+    assert!(matches!(
+        Person::from_envelope(&bytes),
+        Err(EnvelopeError::Json(_))
+    ));
+}
@@ -0,0 +1,68 @@
+//! Tests for `example()`, generated by `#[structible(example)]`.
+
+use std::collections::BTreeMap;
+
+use structible::structible;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Meters(f64);
+
+impl From<f64> for Meters {
+    fn from(value: f64) -> Self {
+        Meters(value)
+    }
+}
+
+impl From<Meters> for f64 {
+    fn from(value: Meters) -> Self {
+        value.0
+    }
+}
+
+#[structible(example)]
+pub struct Person {
+    #[structible(example = "Alice".into())]
+    pub name: String,
+    pub age: u32,
+    #[structible(example = vec!["admin".to_string()])]
+    pub roles: Option<Vec<String>>,
+    pub nickname: Option<String>,
+    #[structible(transparent = f64, example = 1.5)]
+    pub height: Meters,
+    #[structible(transparent = f64, example = 2.0)]
+    pub reach: Option<Meters>,
+    #[structible(key = String)]
+    pub extra: Option<String>,
+}
+
+#[test]
+fn test_example_values() {
+    let person = Person::example();
+    assert_eq!(person.name(), "Alice");
+    assert_eq!(person.roles(), Some(&vec!["admin".to_string()]));
+    assert_eq!(person.height(), 1.5);
+    assert_eq!(person.reach(), Some(2.0));
+}
+
+#[test]
+fn test_fields_without_example() {
+    let person = Person::example();
+    assert_eq!(*person.age(), 0);
+    assert_eq!(person.nickname(), None);
+    assert_eq!(person.extra_iter().count(), 0);
+}
+
+#[structible(example, constructor = create, backing = BTreeMap, track_changes)]
+pub struct Tagged<T: Clone> {
+    pub tag: T,
+    #[structible(example = 3)]
+    pub count: Option<u8>,
+}
+
+#[test]
+fn test_generic_example_with_custom_constructor() {
+    let mut tagged = Tagged::<String>::example();
+    assert_eq!(tagged.tag(), "");
+    assert_eq!(tagged.count(), Some(&3));
+    assert_eq!(tagged.take_change_log().len(), 2);
+}
@@ -0,0 +1,88 @@
+//! Tests for `fake()`, generated by `#[structible(fake)]`.
+
+#![cfg(feature = "fake")]
+
+use fake::faker::internet::en::SafeEmail;
+use fake::faker::name::en::Name;
+use fake::rand::SeedableRng;
+use fake::rand::rngs::StdRng;
+use fake::{Dummy, Faker, Rng};
+use structible::structible;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Meters(f64);
+
+impl From<f64> for Meters {
+    fn from(value: f64) -> Self {
+        Meters(value)
+    }
+}
+
+impl From<Meters> for f64 {
+    fn from(value: Meters) -> Self {
+        value.0
+    }
+}
+
+#[structible(fake)]
+pub struct Person {
+    #[structible(fake = "Name()")]
+    pub name: String,
+    #[structible(fake = "18..100")]
+    pub age: u32,
+    #[structible(fake = "SafeEmail()")]
+    pub email: Option<String>,
+    pub nickname: Option<String>,
+    #[structible(transparent = f64, fake = "1.0..2.5")]
+    pub height: Meters,
+    #[structible(key = String)]
+    pub extra: Option<String>,
+}
+
+#[test]
+fn test_fakers() {
+    for _ in 0..20 {
+        let person = Person::fake();
+        assert!(person.name().contains(' '));
+        assert!((18..100).contains(person.age()));
+        assert!(person.email().unwrap().contains('@'));
+        assert!((1.0..2.5).contains(&person.height()));
+        assert_eq!(person.extra_iter().count(), 0);
+    }
+}
+
+#[test]
+fn test_fields_without_faker_are_sometimes_absent() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let people: Vec<_> = (0..50).map(|_| Person::fake_with_rng(&mut rng)).collect();
+    assert!(people.iter().any(|p| p.nickname().is_some()));
+    assert!(people.iter().any(|p| p.nickname().is_none()));
+}
+
+#[test]
+fn test_seeded_rng_is_reproducible() {
+    let first = Person::fake_with_rng(&mut StdRng::seed_from_u64(1));
+    let second = Person::fake_with_rng(&mut StdRng::seed_from_u64(1));
+    assert_eq!(first, second);
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tag(u8);
+
+impl Dummy<Faker> for Tag {
+    fn dummy_with_rng<R: Rng + ?Sized>(_: &Faker, rng: &mut R) -> Self {
+        Tag(rng.random_range(1..10))
+    }
+}
+
+#[structible(fake, constructor = create)]
+pub struct Tagged<T: Clone + std::fmt::Debug + PartialEq> {
+    pub tag: T,
+    pub tags: Option<Vec<T>>,
+}
+
+#[test]
+fn test_generic_fields_use_faker() {
+    let tagged = Tagged::<Tag>::fake();
+    assert!((1..10).contains(&tagged.tag().0));
+}
@@ -0,0 +1,107 @@
+//! Tests for `#[structible(ffi)]` C-compatible facades.
+
+use structible::ffi::{FfiError, FfiOption, FfiSlice, FfiStr, FfiValue};
+use structible::structible;
+
+#[structible(ffi)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+#[structible(ffi)]
+pub struct Plugin {
+    /// The plugin's name.
+    pub name: String,
+    pub version: u32,
+    pub enabled: Option<bool>,
+    pub description: Option<String>,
+    pub checksum: Option<Vec<u8>>,
+    pub origin: Option<Point>,
+}
+
+fn plugin() -> Plugin {
+    let mut plugin = Plugin::new("resize".into(), 3);
+    plugin.set_enabled(true);
+    plugin.set_checksum(vec![0xde, 0xad]);
+    plugin.set_origin(Point::new(1.0, 2.0));
+    plugin
+}
+
+// Stands in for a C plugin reading the facade through a pointer
+extern "C" fn name_len(plugin: *const PluginFfi<'_>) -> usize {
+    unsafe { (*plugin).name.len }
+}
+
+#[test]
+fn test_to_ffi() {
+    let plugin = plugin();
+    let ffi = plugin.to_ffi();
+    assert_eq!(unsafe { ffi.name.as_str() }, Ok("resize"));
+    assert_eq!(ffi.version, 3);
+    assert_eq!(ffi.enabled.get(), Some(1));
+    assert!(!ffi.description.is_present());
+    assert_eq!(
+        unsafe { ffi.checksum.get().unwrap().as_slice() },
+        Ok(&[0xde, 0xad][..])
+    );
+    assert_eq!(ffi.origin.get().map(|p| (p.x, p.y)), Some((1.0, 2.0)));
+    assert_eq!(name_len(&ffi), 6);
+}
+
+#[test]
+fn test_round_trip() {
+    let plugin = plugin();
+    let copy = unsafe { Plugin::from_ffi(&plugin.to_ffi()) }.unwrap();
+    assert_eq!(copy, plugin);
+    drop(plugin);
+    assert_eq!(copy.name(), "resize");
+}
+
+#[test]
+fn test_from_ffi_errors() {
+    let plugin = plugin();
+    let mut ffi = plugin.to_ffi();
+    ffi.description = FfiOption::some(FfiStr::from_raw_parts(std::ptr::null(), 4));
+    let err = unsafe { Plugin::from_ffi(&ffi) }.unwrap_err();
+    assert_eq!(err, FfiError::NullPointer.in_field("description"));
+    assert_eq!(
+        err.to_string(),
+        "field `description`: null pointer with a non-zero length"
+    );
+
+    let bytes = [0xff, 0xfe];
+    ffi.description = FfiOption::none();
+    ffi.name = FfiStr::from_raw_parts(bytes.as_ptr(), bytes.len());
+    let err = unsafe { Plugin::from_ffi(&ffi) }.unwrap_err();
+    assert!(matches!(
+        err,
+        FfiError::Field { name: "name", ref error } if matches!(**error, FfiError::InvalidUtf8(_))
+    ));
+}
+
+#[test]
+fn test_empty_values_may_be_null() {
+    let plugin = plugin();
+    let ffi = PluginFfi {
+        name: FfiStr::from_raw_parts(std::ptr::null(), 0),
+        ..plugin.to_ffi()
+    };
+    let plugin = unsafe { Plugin::from_ffi(&ffi) }.unwrap();
+    assert_eq!(plugin.name(), "");
+}
+
+#[test]
+fn test_bools_cross_as_bytes() {
+    let plugin = plugin();
+    let mut ffi = plugin.to_ffi();
+    ffi.enabled = FfiOption::some(2);
+    let copy = unsafe { Plugin::from_ffi(&ffi) }.unwrap();
+    assert_eq!(copy.enabled(), Some(&true));
+
+    let bytes = [0, 1, 0xff];
+    let flags = unsafe { Vec::<bool>::from_ffi(&FfiSlice::from_raw_parts(bytes.as_ptr(), 3)) };
+    assert_eq!(flags, Ok(vec![false, true, true]));
+    let flags = vec![true, false];
+    assert_eq!(unsafe { flags.to_ffi().as_slice() }, Ok(&[1, 0][..]));
+}
@@ -0,0 +1,92 @@
+//! Tests for `map_value()` on the field enums and `FieldMapper`.
+
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+use structible::{FieldMapper, structible};
+
+/// Formats a field's value with `Debug`.
+struct Describe;
+
+impl<T: Debug + ?Sized> FieldMapper<T, String> for Describe {
+    fn map(self, name: &'static str, value: Option<&T>) -> String {
+        match value {
+            Some(value) => format!("{name}: {value:?}"),
+            None => format!("{name}: -"),
+        }
+    }
+}
+
+#[structible(backing = BTreeMap)]
+pub struct Person<T> {
+    pub name: String,
+    pub age: Option<u32>,
+    pub tag: Option<T>,
+    #[structible(key = String)]
+    pub extra: Option<bool>,
+}
+
+#[test]
+fn test_map_value_passes_typed_values() {
+    let mut person = Person::new("Alice".into());
+    person.set_tag(vec!['x']);
+    person.insert_extra("admin".into(), true);
+
+    assert_eq!(
+        PersonField::Name.map_value(&person, Describe),
+        "name: \"Alice\""
+    );
+    assert_eq!(PersonField::Age.map_value(&person, Describe), "age: -");
+    assert_eq!(PersonField::Tag.map_value(&person, Describe), "tag: ['x']");
+    assert_eq!(
+        PersonField::Unknown("admin".into()).map_value(&person, Describe),
+        "extra: true"
+    );
+    assert_eq!(
+        PersonField::Unknown("root".into()).map_value(&person, Describe),
+        "extra: -"
+    );
+}
+
+#[structible]
+pub struct Limits {
+    pub soft: u64,
+    pub hard: Option<u64>,
+}
+
+#[test]
+fn test_closure_mapper() {
+    let mut limits = Limits::new(10);
+    let total = |limits: &Limits| {
+        [LimitsField::Soft, LimitsField::Hard]
+            .iter()
+            .map(|field| {
+                field.map_value(limits, |_, value: Option<&u64>| value.copied().unwrap_or(0))
+            })
+            .sum::<u64>()
+    };
+    assert_eq!(total(&limits), 10);
+    limits.set_hard(20);
+    assert_eq!(total(&limits), 30);
+}
+
+#[structible]
+pub struct Borrowed<'a, 'b> {
+    pub first: &'a str,
+    pub second: Option<&'b str>,
+}
+
+#[test]
+fn test_types_differing_in_lifetimes() {
+    let text = String::from("hello");
+    let mut borrowed = Borrowed::new(&text);
+    borrowed.set_second("world");
+    assert_eq!(
+        BorrowedField::First.map_value(&borrowed, Describe),
+        "first: \"hello\""
+    );
+    assert_eq!(
+        BorrowedField::Second.map_value(&borrowed, Describe),
+        "second: \"world\""
+    );
+}
@@ -0,0 +1,99 @@
+//! Tests for fields declared with `upgrade_from` and `with`.
+#![cfg(feature = "codec")]
+
+use std::collections::BTreeMap;
+
+use structible::{ValueCodec, structible};
+
+fn parse_legacy_bool(value: String) -> Result<bool, String> {
+    match value.as_str() {
+        "true" | "yes" | "1" => Ok(true),
+        "false" | "no" | "0" => Ok(false),
+        other => Err(format!("invalid legacy boolean `{}`", other)),
+    }
+}
+
+fn widen(value: u16) -> Result<u64, std::convert::Infallible> {
+    Ok(value.into())
+}
+
+#[structible(serde_backing, value_codec)]
+pub struct Settings {
+    pub name: String,
+    #[structible(upgrade_from = String, with = parse_legacy_bool)]
+    pub enabled: Option<bool>,
+    #[structible(upgrade_from = u16, with = widen)]
+    pub quota: u64,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Stored {
+    #[serde(with = "structible::serde_adapter")]
+    settings: Settings,
+}
+
+/// Decodes settings whose `enabled` field is stored as the given JSON value.
+fn decode(enabled: &str) -> Result<Settings, serde_json::Error> {
+    let json = format!(
+        r#"{{"settings":[["name",{{"name":"app"}}],["enabled",{{"enabled":{}}}],["quota",{{"quota":7}}]]}}"#,
+        enabled
+    );
+    serde_json::from_str::<Stored>(&json).map(|stored| stored.settings)
+}
+
+#[test]
+fn test_current_values_decode_directly() {
+    let settings = decode("true").unwrap();
+    assert_eq!(settings.enabled(), Some(&true));
+    assert_eq!(*settings.quota(), 7);
+}
+
+#[test]
+fn test_legacy_values_are_upgraded() {
+    assert_eq!(decode(r#""yes""#).unwrap().enabled(), Some(&true));
+    assert_eq!(decode(r#""0""#).unwrap().enabled(), Some(&false));
+}
+
+#[test]
+fn test_failed_upgrade_is_an_error() {
+    let err = decode(r#""maybe""#).unwrap_err();
+    assert!(err.to_string().contains("invalid legacy boolean `maybe`"));
+}
+
+#[test]
+fn test_upgraded_values_are_encoded_as_current() {
+    let settings = decode(r#""yes""#).unwrap();
+    let json = serde_json::to_string(&Stored { settings }).unwrap();
+    assert!(json.contains(r#"["enabled",{"enabled":true}]"#));
+}
+
+#[test]
+fn test_codec_upgrades_legacy_values() {
+    let entries = [
+        ("name", br#""app""#.as_slice()),
+        ("enabled", br#""no""#.as_slice()),
+        ("quota", b"3".as_slice()),
+    ];
+    let settings = Settings::decode_entries(entries).unwrap();
+    assert_eq!(settings.enabled(), Some(&false));
+    assert_eq!(*settings.quota(), 3);
+
+    assert!(Settings::decode_entry("enabled", br#""maybe""#).is_err());
+    assert!(Settings::decode_entry("enabled", br#""yes" 1"#).is_err());
+}
+
+#[structible(serde_backing, backing = BTreeMap)]
+pub struct Tagged<T: Ord> {
+    pub value: T,
+    #[structible(upgrade_from = String, with = parse_legacy_bool)]
+    pub flag: Option<bool>,
+}
+
+#[test]
+fn test_generic_struct() {
+    let mut deserializer =
+        serde_json::Deserializer::from_str(r#"[["value",{"value":1}],["flag",{"flag":"true"}]]"#);
+    let tagged: Tagged<u8> = structible::serde_adapter::deserialize(&mut deserializer).unwrap();
+    assert_eq!(*tagged.value(), 1u8);
+    assert_eq!(tagged.flag(), Some(&true));
+}
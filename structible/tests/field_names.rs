@@ -0,0 +1,45 @@
+//! Tests for the names and `Display` impl of the field enum.
+
+use structible::{Structible, structible};
+
+#[structible]
+pub struct Person {
+    pub name: String,
+    pub r#type: Option<String>,
+    #[structible(key = String)]
+    pub extra: Option<String>,
+}
+
+#[structible]
+pub struct Point {
+    pub x: i32,
+    pub y: Option<i32>,
+}
+
+#[test]
+fn test_as_str() {
+    assert_eq!(PersonField::Name.as_str(), "name");
+    assert_eq!(PersonField::Type.as_str(), "type");
+    assert_eq!(PersonField::Unknown("team".into()).as_str(), "extra");
+    assert_eq!(PointField::Y.as_str(), "y");
+}
+
+#[test]
+fn test_display() {
+    assert_eq!(PersonField::Type.to_string(), "type");
+    assert_eq!(
+        PersonField::Unknown("team".into()).to_string(),
+        "extra.team"
+    );
+    assert_eq!(PointField::X.to_string(), "x");
+}
+
+#[test]
+fn test_names_of_present_fields() {
+    let mut person = Person::new("Alice".into());
+    person.insert_extra("team".into(), "core".into());
+
+    let mut names: Vec<_> = person.backing().keys().map(|k| k.to_string()).collect();
+    names.sort();
+    assert_eq!(names, vec!["extra.team", "name"]);
+}
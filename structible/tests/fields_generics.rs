@@ -0,0 +1,199 @@
+//! Tests for the `Fields` companion of generic structs: `into_fields()`, the
+//! `take_*` methods, and the catch-all's `take`, `iter`, `iter_mut`, and `drain`
+//! methods, mirroring `complex_generics.rs`.
+
+// The hidden field and value enums are always `pub`, while these test types are private
+#![allow(private_interfaces)]
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use structible::structible;
+
+trait JsonValue {
+    type String: Clone + PartialEq + Debug;
+    type Object: Clone + PartialEq + Debug;
+}
+
+struct MyJson;
+
+impl JsonValue for MyJson {
+    type String = String;
+    type Object = BTreeMap<String, i32>;
+}
+
+#[structible]
+struct Table<V: JsonValue> {
+    pub string: V::String,
+    pub object: Option<V::Object>,
+}
+
+#[test]
+fn test_associated_types() {
+    let mut table = Table::<MyJson>::new("a".into());
+    table.set_object(BTreeMap::from([("x".into(), 1)]));
+
+    let mut fields = table.into_fields();
+    assert_eq!(fields.take_string(), Some("a".to_string()));
+    assert_eq!(fields.take_object().map(|o| o["x"]), Some(1));
+    assert_eq!(fields.take_object(), None);
+}
+
+#[structible]
+struct WithConstGeneric<const N: usize> {
+    pub data: [u8; N],
+    pub label: Option<String>,
+}
+
+#[test]
+fn test_const_generic() {
+    let mut fields = WithConstGeneric::<2>::new([1, 2]).into_fields();
+    assert_eq!(fields.take_data(), Some([1, 2]));
+    assert_eq!(fields.take_label(), None);
+}
+
+#[structible]
+struct MultipleLifetimes<'a, 'b> {
+    pub first: &'a str,
+    pub second: Option<&'b str>,
+    pub both: Option<(&'a str, &'b str)>,
+}
+
+#[test]
+fn test_multiple_lifetimes() {
+    let first = String::from("first");
+    let second = String::from("second");
+    let mut s = MultipleLifetimes::new(&first);
+    s.set_both((&first, &second));
+
+    let mut fields = s.into_fields();
+    assert_eq!(fields.take_first(), Some("first"));
+    assert_eq!(fields.take_second(), None);
+    assert_eq!(fields.take_both(), Some(("first", "second")));
+}
+
+#[structible]
+struct OutlivesBounds<'a, 'b: 'a, 'c>
+where
+    'c: 'b,
+{
+    pub nested: &'a &'b &'c str,
+    pub cow: Option<Cow<'c, str>>,
+}
+
+#[test]
+fn test_outlives_bounds() {
+    let text: &str = "text";
+    let reference = &text;
+    let mut s = OutlivesBounds::new(&reference);
+    s.set_cow(Cow::Borrowed("cow"));
+
+    let mut fields = s.into_fields();
+    assert_eq!(fields.take_nested().map(|n| **n), Some("text"));
+    assert_eq!(fields.take_cow(), Some(Cow::Borrowed("cow")));
+}
+
+#[structible]
+struct LifetimeBoundWhere<'a, T>
+where
+    T: ?Sized + Debug + PartialEq + 'a,
+{
+    pub borrowed: &'a T,
+    pub optional: Option<&'a T>,
+}
+
+#[test]
+fn test_lifetime_bound_where_clause() {
+    let value = String::from("owned");
+    let mut s = LifetimeBoundWhere::<str>::new(&value);
+    s.set_optional("literal");
+
+    let mut fields = s.into_fields();
+    assert_eq!(fields.take_borrowed(), Some("owned"));
+    assert_eq!(fields.take_optional(), Some("literal"));
+}
+
+#[structible]
+struct HigherRanked<F>
+where
+    F: for<'x> Fn(&'x str) -> &'x str + Clone,
+{
+    pub name: String,
+    pub transform: Option<F>,
+}
+
+#[test]
+fn test_higher_ranked_where_clause() {
+    let mut s = HigherRanked::new("a".into());
+    s.set_transform(|text: &str| text.trim());
+
+    let mut fields = s.into_fields();
+    let transform = fields.take_transform().unwrap();
+    assert_eq!(transform(" b "), "b");
+}
+
+#[structible]
+struct BorrowedCatchAll<'a, 'b: 'a> {
+    pub name: Option<&'a str>,
+    #[structible(key = &'a str)]
+    pub extra: Option<&'b str>,
+}
+
+#[test]
+fn test_borrowed_catch_all() {
+    let mut s = BorrowedCatchAll::new();
+    s.insert_extra("a", "1");
+    s.insert_extra("b", "2");
+
+    let mut fields = s.into_fields();
+    assert_eq!(fields.clone(), fields);
+    assert!(format!("{:?}", fields).contains("\"a\": \"1\""));
+    assert_eq!(fields.take_extra(&"a"), Some("1"));
+    for (_, value) in fields.extra_iter_mut() {
+        *value = "3";
+    }
+    assert_eq!(fields.extra_iter().collect::<Vec<_>>(), [(&"b", &"3")]);
+    assert_eq!(fields.drain_extra()["b"], "3");
+    assert_eq!(fields.extra_iter().count(), 0);
+}
+
+#[structible(backing = BTreeMap)]
+struct GenericKey<'a, K: Ord + Hash + Clone + Debug, V: Clone + Debug + PartialEq> {
+    pub name: Option<&'a str>,
+    #[structible(key = K)]
+    pub extra: Option<&'a V>,
+}
+
+#[test]
+fn test_generic_key_with_lifetimes() {
+    let one = 1u8;
+    let mut s = GenericKey::<String, u8>::new();
+    s.insert_extra("one".into(), &one);
+    s.set_name("name");
+
+    let mut fields = s.into_fields();
+    assert_eq!(fields.take_name(), Some("name"));
+    assert_eq!(fields.take_extra("one"), Some(&1));
+    assert_eq!(fields.take_extra("one"), None);
+}
+
+#[structible]
+struct MultiCatchAll<'a, T: Clone + Debug + PartialEq> {
+    pub name: Option<String>,
+    #[structible(key = &'a str, multi)]
+    pub extra: Option<Vec<&'a T>>,
+}
+
+#[test]
+fn test_multi_catch_all_with_lifetimes() {
+    let values = [1, 2];
+    let mut s = MultiCatchAll::new();
+    s.append_extra("k", &values[0]);
+    s.append_extra("k", &values[1]);
+
+    let mut fields = s.into_fields();
+    assert_eq!(fields.extra_iter().count(), 1);
+    assert_eq!(fields.drain_extra()[&"k"], [&1, &2]);
+}
@@ -0,0 +1,51 @@
+//! Tests for `fields_in_order()`.
+
+use std::time::Duration;
+
+use structible::structible;
+
+#[structible]
+pub struct Person<T> {
+    pub name: String,
+    pub email: Option<String>,
+    pub age: Option<u32>,
+    pub tag: Option<T>,
+    #[structible(key = String)]
+    pub extra: Option<String>,
+}
+
+#[test]
+fn test_fields_in_declaration_order() {
+    let mut person = Person::new("Alice".into());
+    person.set_tag('x');
+    person.set_age(30);
+    person.insert_extra("plan".into(), "pro".into());
+
+    let fields: Vec<_> = person.fields_in_order().collect();
+    assert_eq!(
+        fields,
+        [
+            (PersonField::Name, &PersonValue::Name("Alice".into())),
+            (PersonField::Age, &PersonValue::Age(30)),
+            (PersonField::Tag, &PersonValue::Tag('x')),
+        ]
+    );
+
+    person.remove_age();
+    let names: Vec<_> = person.fields_in_order().map(|(f, _)| f.as_str()).collect();
+    assert_eq!(names, ["name", "tag"]);
+}
+
+#[structible(ttl)]
+pub struct Session {
+    pub user: String,
+    pub token: Option<String>,
+}
+
+#[test]
+fn test_expired_fields_left_out() {
+    let mut session = Session::new("alice".into());
+    session.set_token_with_ttl("abc".into(), Duration::ZERO);
+    let fields: Vec<_> = session.fields_in_order().map(|(f, _)| f).collect();
+    assert_eq!(fields, [SessionField::User]);
+}
@@ -0,0 +1,99 @@
+//! Tests for `#[structible(freeze)]` immutable snapshots.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+use structible::structible;
+
+#[structible(freeze)]
+pub struct Query {
+    pub table: String,
+    pub limit: Option<u32>,
+    #[structible(key = String)]
+    pub params: Option<String>,
+}
+
+fn hash_of<T: Hash>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn query(params: &[(&str, &str)]) -> Query {
+    let mut query = Query::new("users".into());
+    query.set_limit(10);
+    for (key, value) in params {
+        query.insert_params(key.to_string(), value.to_string());
+    }
+    query
+}
+
+#[test]
+fn test_frozen_getters() {
+    let frozen = query(&[("id", "1")]).freeze();
+    assert_eq!(frozen.table(), "users");
+    assert_eq!(frozen.limit(), Some(&10));
+    assert_eq!(frozen.params("id"), Some(&"1".to_string()));
+    assert_eq!(frozen.params_iter().count(), 1);
+}
+
+#[test]
+fn test_equal_snapshots_hash_equally() {
+    let params: Vec<_> = (0..20)
+        .map(|i| (format!("k{}", i), format!("v{}", i)))
+        .collect();
+    let forward: Vec<_> = params
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .collect();
+    let backward: Vec<_> = forward.iter().rev().copied().collect();
+
+    let a = query(&forward).freeze();
+    let b = query(&backward).freeze();
+    assert_eq!(a, b);
+    assert_eq!(hash_of(&a), hash_of(&b));
+
+    let c = query(&[("k0", "other")]).freeze();
+    assert_ne!(a, c);
+}
+
+#[test]
+fn test_snapshot_as_cache_key() {
+    let mut cache = HashMap::new();
+    cache.insert(query(&[("id", "1")]).freeze(), "alice");
+    assert_eq!(cache.get(&query(&[("id", "1")]).freeze()), Some(&"alice"));
+    assert_eq!(cache.get(&query(&[("id", "2")]).freeze()), None);
+
+    let set: HashSet<_> = [query(&[]).freeze(), query(&[]).freeze()].into();
+    assert_eq!(set.len(), 1);
+}
+
+#[test]
+fn test_thaw() {
+    let mut query = query(&[]).freeze().thaw();
+    query.set_limit(20);
+    assert_eq!(query.limit(), Some(&20));
+}
+
+#[test]
+fn test_debug() {
+    let frozen = Query::new("users".into()).freeze();
+    assert_eq!(format!("{:?}", frozen), r#"FrozenQuery { table: "users" }"#);
+}
+
+#[structible(freeze, backing = BTreeMap)]
+pub struct Pair<T> {
+    pub left: T,
+    pub right: Option<T>,
+}
+
+#[test]
+fn test_generic_snapshot() {
+    let mut pair = Pair::new(1u8);
+    pair.set_right(2);
+    let frozen = pair.freeze();
+    assert_eq!(frozen.clone(), frozen);
+    assert_eq!(*frozen.left(), 1);
+    let _: BTreeMap<_, _> = [(1, frozen.clone())].into();
+}
@@ -0,0 +1,77 @@
+//! Tests for `#[structible(fuzz)]` round-trip helpers.
+#![cfg(feature = "fuzz")]
+
+use std::collections::BTreeMap;
+
+use arbitrary::{Arbitrary, Unstructured};
+use serde::{Deserialize, Serialize};
+use structible::fuzz::RoundtripError;
+use structible::structible;
+
+#[structible(fuzz, serde_backing)]
+pub struct Person {
+    pub name: String,
+    pub age: Option<u32>,
+    pub tags: Vec<String>,
+    #[structible(key = String)]
+    pub extra: Option<i64>,
+}
+
+fn inputs() -> impl Iterator<Item = Vec<u8>> {
+    // A simple deterministic byte generator standing in for a fuzzer
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    (0..200).map(move |len| {
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect()
+    })
+}
+
+#[test]
+fn test_roundtrip_arbitrary_inputs() {
+    for data in inputs() {
+        Person::fuzz_roundtrip(&data).unwrap();
+    }
+    Person::fuzz_roundtrip(&[]).unwrap();
+}
+
+#[test]
+fn test_arbitrary_impl() {
+    let data: Vec<u8> = inputs().nth(150).unwrap();
+    let mut u = Unstructured::new(&data);
+    let person = Person::arbitrary(&mut u).unwrap();
+    let _ = person.name();
+}
+
+/// A value whose encoding loses information.
+#[derive(Debug, Clone, PartialEq, Arbitrary, Deserialize)]
+pub struct Lossy(u8);
+
+impl Serialize for Lossy {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(0)
+    }
+}
+
+#[structible(fuzz, serde_backing, backing = BTreeMap)]
+pub struct Holder<T> {
+    pub value: T,
+}
+
+#[test]
+fn test_mismatch_is_reported() {
+    let err = Holder::<Lossy>::fuzz_roundtrip(&[7]).unwrap_err();
+    match &err {
+        RoundtripError::Mismatch { original, decoded } => {
+            assert_eq!(original, "Holder { value: Lossy(7) }");
+            assert_eq!(decoded, "Holder { value: Lossy(0) }");
+        }
+        other => panic!("unexpected error: {}", other),
+    }
+    Holder::<u16>::fuzz_roundtrip(&[1, 2]).unwrap();
+}
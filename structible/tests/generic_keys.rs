@@ -0,0 +1,65 @@
+//! Tests for catch-all key types that mention the struct's generics.
+
+use structible::structible;
+
+#[structible]
+pub struct Labels<K: std::hash::Hash + Eq + Clone> {
+    pub name: String,
+    #[structible(key = K)]
+    pub extra: Option<String>,
+}
+
+#[structible]
+pub struct Borrowed<'a> {
+    pub name: String,
+    #[structible(key = &'a str)]
+    pub extra: Option<u32>,
+}
+
+#[structible]
+pub struct Tagged<const N: usize> {
+    pub name: String,
+    #[structible(key = [u8; N])]
+    pub extra: Option<String>,
+}
+
+#[test]
+fn test_type_param_key() {
+    let mut labels = Labels::<u32>::new("a".into());
+    assert_eq!(labels.insert_extra(7, "seven".into()), None);
+    labels.insert_extra(3, "three".into());
+
+    assert_eq!(labels.extra(&7), Some(&"seven".to_string()));
+    assert_eq!(labels.remove_extra(&3), Some("three".to_string()));
+    let keys: Vec<_> = labels.extra_iter().map(|(k, _)| *k).collect();
+    assert_eq!(keys, vec![7]);
+}
+
+#[test]
+fn test_type_param_key_traits() {
+    let mut labels = Labels::<u32>::new("a".into());
+    labels.insert_extra(1, "one".into());
+
+    let copy = labels.clone();
+    assert_eq!(copy, labels);
+    assert!(format!("{:?}", labels).contains("one"));
+}
+
+#[test]
+fn test_lifetime_key() {
+    let key = String::from("color");
+    let mut borrowed = Borrowed::new("a".into());
+    borrowed.insert_extra(&key, 3);
+    assert_eq!(borrowed.extra(&"color"), Some(&3));
+
+    let mut fields = borrowed.into_fields();
+    assert_eq!(fields.take_extra(&"color"), Some(3));
+}
+
+#[test]
+fn test_const_param_key() {
+    let mut tagged = Tagged::<2>::new("a".into());
+    tagged.insert_extra([1, 2], "x".into());
+    assert_eq!(tagged.extra(&[1, 2]), Some(&"x".to_string()));
+    assert_eq!(tagged.extra(&[2, 1]), None);
+}
@@ -0,0 +1,86 @@
+//! Tests for `emit_schemas!` and the schema golden files.
+
+use std::path::Path;
+
+use structible::golden::{schema_json, write_schema};
+use structible::{Schema, structible};
+
+#[structible]
+pub struct Person {
+    pub name: String,
+    pub r#type: Option<String>,
+    #[structible(key = String)]
+    pub extra: Option<u32>,
+}
+
+#[structible(backing = std::collections::BTreeMap)]
+pub struct Pair<T: Ord + Clone + std::fmt::Debug> {
+    pub left: T,
+    pub right: Option<T>,
+}
+
+#[test]
+fn test_schema_json() {
+    assert_eq!(Person::NAME, "Person");
+    assert_eq!(
+        schema_json::<Person>(),
+        r#"{
+  "fields": [
+    {
+      "key": null,
+      "name": "name",
+      "optional": false,
+      "type": "String"
+    },
+    {
+      "key": null,
+      "name": "type",
+      "optional": true,
+      "type": "Option<String>"
+    },
+    {
+      "key": "String",
+      "name": "extra",
+      "optional": true,
+      "type": "Option<u32>"
+    }
+  ],
+  "name": "Person"
+}
+"#
+    );
+}
+
+#[test]
+fn test_emit_schemas() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("golden_schemas");
+    let _ = std::fs::remove_dir_all(&dir);
+
+    structible::emit_schemas!(&dir, Person, Pair<u8>,).unwrap();
+    let person = std::fs::read_to_string(dir.join("Person.json")).unwrap();
+    assert_eq!(person, schema_json::<Person>());
+    let pair = std::fs::read_to_string(dir.join("Pair.json")).unwrap();
+    assert!(pair.contains(r#""type": "T""#));
+}
+
+#[test]
+fn test_unchanged_schema_is_not_rewritten() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("golden_unchanged");
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let path = write_schema::<Person>(&dir).unwrap();
+    let modified = std::fs::metadata(&path).unwrap().modified().unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    write_schema::<Person>(&dir).unwrap();
+    assert_eq!(
+        std::fs::metadata(&path).unwrap().modified().unwrap(),
+        modified
+    );
+
+    std::fs::write(&path, "stale").unwrap();
+    write_schema::<Person>(&dir).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        schema_json::<Person>()
+    );
+}
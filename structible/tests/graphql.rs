@@ -0,0 +1,91 @@
+//! Tests for `#[structible(graphql)]` resolvers.
+
+#![cfg(feature = "async-graphql")]
+
+use async_graphql::{EmptyMutation, EmptySubscription, Object, Schema};
+use serde_json::json;
+use structible::structible;
+
+#[structible(graphql)]
+pub struct Person {
+    /// The person's full name.
+    pub full_name: String,
+    pub age: Option<u32>,
+    pub r#type: Option<String>,
+    #[structible(key = String)]
+    pub extra: Option<String>,
+}
+
+struct Query;
+
+#[Object]
+impl Query {
+    async fn person(&self) -> Person {
+        let mut person = Person::new("Alice".into());
+        person.set_type("admin".into());
+        person.insert_extra("plan".into(), "pro".into());
+        person
+    }
+}
+
+async fn execute(query: &str) -> serde_json::Value {
+    let schema = Schema::new(Query, EmptyMutation, EmptySubscription);
+    let response = schema.execute(query).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    response.data.into_json().unwrap()
+}
+
+#[tokio::test]
+async fn test_fields_resolve_through_getters() {
+    let data = execute(
+        r#"{ person { fullName age type plan: extra(key: "plan") color: extra(key: "color") } }"#,
+    )
+    .await;
+    assert_eq!(
+        data,
+        json!({
+            "person": {
+                "fullName": "Alice",
+                "age": null,
+                "type": "admin",
+                "plan": "pro",
+                "color": null,
+            }
+        })
+    );
+}
+
+#[test]
+fn test_schema() {
+    let sdl = Schema::new(Query, EmptyMutation, EmptySubscription).sdl();
+    assert!(sdl.contains("type Person {"), "{}", sdl);
+    assert!(sdl.contains("fullName: String!"), "{}", sdl);
+    assert!(sdl.contains("The person's full name."), "{}", sdl);
+    assert!(sdl.contains("age: Int\n"), "{}", sdl);
+    assert!(sdl.contains("extra(key: String!): String\n"), "{}", sdl);
+}
+
+#[structible(graphql)]
+pub struct Wrapper<T> {
+    pub value: T,
+    pub other: Option<T>,
+}
+
+struct WrapperQuery;
+
+#[Object]
+impl WrapperQuery {
+    async fn wrapper(&self) -> Wrapper<i32> {
+        Wrapper::new(7)
+    }
+}
+
+#[tokio::test]
+async fn test_generic_struct() {
+    let schema = Schema::new(WrapperQuery, EmptyMutation, EmptySubscription);
+    let response = schema.execute("{ wrapper { value other } }").await;
+    assert_eq!(
+        response.data.into_json().unwrap(),
+        json!({ "wrapper": { "value": 7, "other": null } })
+    );
+}
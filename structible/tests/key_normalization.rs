@@ -0,0 +1,64 @@
+//! Tests for catch-all key normalization with `normalize`.
+
+use structible::structible;
+
+fn lowercase(key: String) -> String {
+    key.to_lowercase()
+}
+
+#[structible(freeze)]
+pub struct Event {
+    pub name: String,
+    #[structible(key = String, normalize = lowercase)]
+    pub props: Option<String>,
+}
+
+#[test]
+fn test_insert_normalizes_keys() {
+    let mut event = Event::new("deploy".into());
+    event.insert_props("X-Vendor-Id".into(), "1".into());
+    let keys: Vec<_> = event.props_iter().map(|(k, _)| k.as_str()).collect();
+    assert_eq!(keys, ["x-vendor-id"]);
+
+    // The same key in another case replaces the value
+    let prev = event.insert_props("x-VENDOR-id".into(), "2".into());
+    assert_eq!(prev, Some("1".into()));
+    assert_eq!(event.props_iter().count(), 1);
+}
+
+#[test]
+fn test_lookups_normalize_keys() {
+    let mut event = Event::new("deploy".into());
+    event.insert_props("x-vendor-id".into(), "1".into());
+    assert_eq!(event.props("X-Vendor-Id"), Some(&"1".to_string()));
+    assert_eq!(event.props("x-vendor-id"), Some(&"1".to_string()));
+
+    *event.props_mut("X-VENDOR-ID").unwrap() = "2".into();
+    assert_eq!(event.props("x-vendor-id"), Some(&"2".to_string()));
+
+    assert_eq!(
+        event.clone().freeze().props("X-Vendor-ID"),
+        Some(&"2".to_string())
+    );
+
+    assert_eq!(event.remove_props("X-Vendor-Id"), Some("2".into()));
+    assert_eq!(event.props("x-vendor-id"), None);
+}
+
+#[test]
+fn test_take_normalizes_keys() {
+    let mut event = Event::new("deploy".into());
+    event.insert_props("Region".into(), "eu".into());
+    let mut fields = event.into_fields();
+    assert_eq!(fields.take_props("REGION"), Some("eu".into()));
+}
+
+#[structible(key = String, value = u32, normalize = lowercase)]
+pub struct Counters;
+
+#[test]
+fn test_unit_struct() {
+    let mut counters = Counters::default();
+    counters.insert_extra("Hits".into(), 3);
+    assert_eq!(counters.extra("HITS"), Some(&3));
+}
@@ -0,0 +1,94 @@
+//! Tests for catch-all key validation with `validate_key`.
+
+use structible::{ValidationError, structible};
+
+/// Vendor-specific properties must be prefixed with a domain name, as in RFC 8984.
+fn check_vendor_key(key: &str) -> Result<(), ValidationError> {
+    match key.split_once(':') {
+        Some((domain, name)) if domain.contains('.') && !name.is_empty() => Ok(()),
+        _ => Err(ValidationError::invalid_key(format!(
+            "`{}` lacks a domain prefix",
+            key
+        ))),
+    }
+}
+
+#[structible]
+pub struct Location {
+    pub name: String,
+    pub description: Option<String>,
+    #[structible(key = String, validate_key = check_vendor_key)]
+    pub vendor: Option<String>,
+}
+
+#[test]
+fn test_try_insert_accepts_valid_keys() {
+    let mut location = Location::new("Office".into());
+    let prev = location.try_insert_vendor("example.com:floor".into(), "3".into());
+    assert_eq!(prev, Ok(None));
+    let prev = location.try_insert_vendor("example.com:floor".into(), "4".into());
+    assert_eq!(prev, Ok(Some("3".into())));
+    assert_eq!(location.vendor("example.com:floor"), Some(&"4".to_string()));
+}
+
+#[test]
+fn test_try_insert_rejects_known_field_names() {
+    let mut location = Location::new("Office".into());
+    assert_eq!(
+        location.try_insert_vendor("description".into(), "x".into()),
+        Err(ValidationError::ReservedKey {
+            field: "description"
+        })
+    );
+    assert_eq!(location.vendor_iter().count(), 0);
+}
+
+#[test]
+fn test_try_insert_rejects_malformed_keys() {
+    let mut location = Location::new("Office".into());
+    let err = location
+        .try_insert_vendor("floor".into(), "3".into())
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid key: `floor` lacks a domain prefix"
+    );
+    assert_eq!(location.vendor("floor"), None);
+}
+
+#[test]
+fn test_validate_checks_existing_keys() {
+    let mut location = Location::new("Office".into());
+    location.insert_vendor("example.com:floor".into(), "3".into());
+    assert_eq!(location.validate(), Ok(()));
+
+    // The plain insert doesn't check keys
+    location.insert_vendor("name".into(), "x".into());
+    assert_eq!(
+        location.validate(),
+        Err(ValidationError::ReservedKey { field: "name" })
+    );
+}
+
+fn lowercase(key: String) -> String {
+    key.to_lowercase()
+}
+
+fn reject_empty(key: &str) -> Result<(), ValidationError> {
+    if key.is_empty() {
+        Err(ValidationError::invalid_key("empty key"))
+    } else {
+        Ok(())
+    }
+}
+
+#[structible(key = String, value = u32, normalize = lowercase, validate_key = reject_empty)]
+pub struct Counters;
+
+#[test]
+fn test_unit_struct_checks_normalized_keys() {
+    let mut counters = Counters::default();
+    assert_eq!(counters.try_insert_extra("Hits".into(), 1), Ok(None));
+    assert_eq!(counters.extra("hits"), Some(&1));
+    assert!(counters.try_insert_extra(String::new(), 1).is_err());
+}
@@ -0,0 +1,76 @@
+//! Tests for `#[structible(labels)]` metric label values.
+
+use std::borrow::Cow;
+use std::fmt::Display;
+
+use structible::{ValidationError, structible};
+
+#[structible(labels)]
+pub struct Request {
+    pub method: String,
+    pub status: u16,
+    pub region: Option<String>,
+    #[structible(key = String)]
+    pub extra: Option<u32>,
+}
+
+#[structible(labels)]
+pub struct Tagged<T> {
+    pub tag: T,
+}
+
+#[test]
+fn test_label_values_in_requested_order() {
+    let request = Request::new("GET".into(), 200);
+    let labels = request
+        .label_values(&[RequestField::Status, RequestField::Method])
+        .unwrap();
+    assert_eq!(labels, ["200", "GET"]);
+    assert!(matches!(labels[1], Cow::Borrowed("GET")));
+    assert!(matches!(labels[0], Cow::Owned(_)));
+}
+
+#[test]
+fn test_absent_fields_are_errors() {
+    let mut request = Request::new("GET".into(), 200);
+    assert_eq!(
+        request.label_values(&[RequestField::Method, RequestField::Region]),
+        Err(ValidationError::MissingLabel { field: "region" })
+    );
+
+    request.set_region("eu".into());
+    assert_eq!(
+        request.label_values(&[RequestField::Region]).unwrap(),
+        ["eu"]
+    );
+}
+
+#[test]
+fn test_catch_all_entries_by_key() {
+    let mut request = Request::new("GET".into(), 200);
+    request.insert_extra("shard".into(), 3);
+    assert_eq!(
+        request
+            .label_values(&[RequestField::Unknown("shard".into())])
+            .unwrap(),
+        ["3"]
+    );
+    assert_eq!(
+        request.label_values(&[RequestField::Unknown("tenant".into())]),
+        Err(ValidationError::MissingLabel { field: "extra" })
+    );
+    assert!(request.label_values(&[]).unwrap().is_empty());
+}
+
+#[test]
+fn test_generic_fields() {
+    fn labels<T: Display>(tagged: &Tagged<T>) -> Vec<String> {
+        tagged
+            .label_values(&[TaggedField::Tag])
+            .unwrap()
+            .into_iter()
+            .map(Cow::into_owned)
+            .collect()
+    }
+    assert_eq!(labels(&Tagged::new(1.5)), ["1.5"]);
+}
@@ -0,0 +1,130 @@
+//! Tests for lazy fields loaded from a context on first access.
+
+use std::cell::Cell;
+
+use structible::{FieldChange, structible};
+
+/// Counts loader calls, to check that loaded values are cached.
+pub struct ProfileContext {
+    pub user_id: u64,
+    pub loads: Cell<u32>,
+}
+
+impl ProfileContext {
+    fn new(user_id: u64) -> Self {
+        Self {
+            user_id,
+            loads: Cell::new(0),
+        }
+    }
+}
+
+fn load_avatar(context: &ProfileContext) -> String {
+    context.loads.set(context.loads.get() + 1);
+    format!("avatars/{}.png", context.user_id)
+}
+
+fn load_follower_count(context: &ProfileContext) -> u64 {
+    context.loads.set(context.loads.get() + 1);
+    context.user_id * 10
+}
+
+#[structible(context = ProfileContext)]
+pub struct Profile {
+    pub name: String,
+    /// The path of the user's avatar.
+    #[structible(lazy = load_avatar)]
+    pub avatar: String,
+    #[structible(lazy = load_follower_count, get = followers)]
+    pub follower_count: u64,
+}
+
+#[test]
+fn test_lazy_fields_are_not_in_constructor() {
+    let profile = Profile::new("Alice".into());
+    assert_eq!(profile.avatar_if_loaded(), None);
+    assert_eq!(profile.followers_if_loaded(), None);
+}
+
+#[test]
+fn test_getter_loads_once() {
+    let context = ProfileContext::new(7);
+    let mut profile = Profile::new("Alice".into());
+
+    assert_eq!(profile.avatar(&context), "avatars/7.png");
+    assert_eq!(profile.avatar(&context), "avatars/7.png");
+    assert_eq!(context.loads.get(), 1);
+    assert_eq!(
+        profile.avatar_if_loaded(),
+        Some(&"avatars/7.png".to_string())
+    );
+
+    assert_eq!(*profile.followers(&context), 70);
+    assert_eq!(context.loads.get(), 2);
+}
+
+#[test]
+fn test_set_value_is_not_reloaded() {
+    let context = ProfileContext::new(7);
+    let mut profile = Profile::new("Alice".into());
+    profile.set_avatar("custom.png".into());
+    assert_eq!(profile.avatar(&context), "custom.png");
+    assert_eq!(context.loads.get(), 0);
+}
+
+#[test]
+fn test_remove_invalidates_cache() {
+    let context = ProfileContext::new(7);
+    let mut profile = Profile::new("Alice".into());
+    profile.avatar(&context);
+    assert_eq!(profile.remove_avatar(), Some("avatars/7.png".into()));
+    assert_eq!(profile.avatar_if_loaded(), None);
+
+    profile.avatar(&context);
+    assert_eq!(context.loads.get(), 2);
+}
+
+#[test]
+fn test_mutable_getter_loads() {
+    let context = ProfileContext::new(3);
+    let mut profile = Profile::new("Alice".into());
+    *profile.follower_count_mut(&context) += 1;
+    assert_eq!(profile.followers_if_loaded(), Some(&31));
+}
+
+#[test]
+fn test_default_and_into_fields() {
+    // Lazy fields count as optional, so they don't prevent `Default`
+    #[structible(context = ProfileContext)]
+    pub struct Stats {
+        #[structible(lazy = load_follower_count)]
+        pub followers: u64,
+    }
+
+    let context = ProfileContext::new(2);
+    let mut stats = Stats::default();
+    assert_eq!(stats.followers_if_loaded(), None);
+    stats.followers(&context);
+    assert_eq!(stats.into_fields().take_followers(), Some(20));
+}
+
+#[structible(context = ProfileContext, track_changes)]
+pub struct Tracked {
+    #[structible(lazy = load_avatar)]
+    pub avatar: String,
+}
+
+#[test]
+fn test_loads_are_recorded() {
+    let context = ProfileContext::new(1);
+    let mut tracked = Tracked::new();
+    tracked.avatar(&context);
+    tracked.avatar(&context);
+    assert_eq!(
+        tracked.take_change_log(),
+        vec![FieldChange::Set(
+            TrackedField::Avatar,
+            TrackedValue::Avatar("avatars/1.png".into())
+        )]
+    );
+}
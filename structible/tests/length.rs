@@ -0,0 +1,69 @@
+//! Tests for `#[structible(len = ...)]` and `#[structible(non_empty)]` constraints.
+
+use structible::{ValidationError, structible};
+
+#[structible]
+pub struct Event {
+    #[structible(len = 1..=8)]
+    pub title: String,
+    #[structible(non_empty)]
+    pub locations: Option<Vec<String>>,
+    #[structible(len = ..=2)]
+    pub keywords: Option<Vec<String>>,
+}
+
+#[test]
+fn test_try_set_checks_len() {
+    let mut event = Event::new("Standup".into());
+    event.try_set_title("Retro".into()).unwrap();
+
+    let err = event.try_set_title(String::new()).unwrap_err();
+    assert_eq!(
+        err,
+        ValidationError::InvalidLength {
+            field: "title",
+            len: "1..=8",
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "length of field `title` is outside the range `1..=8`"
+    );
+    assert!(event.try_set_title("Quarterly".into()).is_err());
+    assert_eq!(event.title(), "Retro");
+}
+
+#[test]
+fn test_strings_are_measured_in_chars() {
+    let mut event = Event::new("Standup".into());
+    event.try_set_title("Ünïcödé!".into()).unwrap();
+    assert_eq!(event.title().len(), 12);
+}
+
+#[test]
+fn test_non_empty() {
+    let mut event = Event::new("Standup".into());
+    assert_eq!(
+        event.try_set_locations(Vec::new()),
+        Err(ValidationError::InvalidLength {
+            field: "locations",
+            len: "1..",
+        })
+    );
+    event.try_set_locations(vec!["Room 1".into()]).unwrap();
+    assert_eq!(event.locations().map(Vec::len), Some(1));
+}
+
+#[test]
+fn test_validate_checks_len() {
+    assert!(Event::try_new(String::new()).is_err());
+
+    let mut event = Event::try_new("Standup".into()).unwrap();
+    event.set_locations(Vec::new());
+    event.set_keywords(vec!["a".into(), "b".into(), "c".into()]);
+    assert_eq!(event.validate_all().unwrap_err().len(), 2);
+
+    event.remove_locations();
+    event.remove_keywords();
+    assert!(event.validate().is_ok());
+}
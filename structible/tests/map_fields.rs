@@ -0,0 +1,68 @@
+//! Tests for the `map_<field>()` in-place transformations.
+
+use std::time::Duration;
+
+use structible::{FieldChange, structible};
+
+#[structible]
+pub struct Person {
+    pub name: String,
+    pub nickname: Option<String>,
+    pub history: Vec<String>,
+}
+
+#[test]
+fn test_map_required_field() {
+    let mut person = Person::new("alice".into(), vec![]);
+    person.map_name(|name| name.to_uppercase());
+    assert_eq!(person.name(), "ALICE");
+
+    person.map_history(|mut history| {
+        history.push("renamed".into());
+        history
+    });
+    assert_eq!(person.history(), &["renamed"]);
+}
+
+#[test]
+fn test_map_optional_field() {
+    let mut person = Person::new("alice".into(), vec![]);
+    person.map_nickname(|_| unreachable!("absent fields aren't mapped"));
+    assert_eq!(person.nickname(), None);
+
+    person.set_nickname("al".into());
+    person.map_nickname(|nickname| format!("{}!", nickname));
+    assert_eq!(person.nickname(), Some(&"al!".to_string()));
+}
+
+#[structible(track_changes)]
+pub struct Counter {
+    pub count: u32,
+}
+
+#[test]
+fn test_map_is_recorded_as_set() {
+    let mut counter = Counter::new(1);
+    counter.take_change_log();
+    counter.map_count(|count| count + 1);
+    assert_eq!(
+        counter.take_change_log(),
+        vec![FieldChange::Set(
+            CounterField::Count,
+            CounterValue::Count(2)
+        )]
+    );
+}
+
+#[structible(ttl)]
+pub struct Cache {
+    pub entry: Option<u32>,
+}
+
+#[test]
+fn test_expired_value_is_not_mapped() {
+    let mut cache = Cache::default();
+    cache.set_entry_with_ttl(1, Duration::ZERO);
+    cache.map_entry(|_| unreachable!("expired fields aren't mapped"));
+    assert_eq!(cache.entry(), None);
+}
@@ -0,0 +1,189 @@
+//! Tests for `#[structible(lww)]` and `merge()`.
+
+use std::collections::BTreeSet;
+
+use structible::{FieldChange, Stamp, structible};
+
+#[structible(lww)]
+pub struct Contact {
+    pub name: String,
+    pub phone: Option<String>,
+    #[structible(key = String)]
+    pub extra: Option<String>,
+}
+
+/// Returns two replicas of the same contact, made by actors 1 and 2.
+fn replicas() -> (Contact, Contact) {
+    let mut alice = Contact::new("Alice".into());
+    alice.set_actor_id(1);
+    let mut bob = alice.clone();
+    bob.set_actor_id(2);
+    (alice, bob)
+}
+
+#[test]
+fn test_writes_are_stamped() {
+    let mut contact = Contact::new("Alice".into());
+    assert_eq!(
+        contact.field_stamp(&ContactField::Name),
+        Some(Stamp::new(1, 0))
+    );
+    assert_eq!(contact.field_stamp(&ContactField::Phone), None);
+
+    contact.set_actor_id(7);
+    assert_eq!(contact.actor_id(), 7);
+    assert_eq!(
+        contact.field_stamp(&ContactField::Name),
+        Some(Stamp::new(1, 0))
+    );
+
+    contact.set_phone("555-0100".into());
+    assert_eq!(
+        contact.field_stamp(&ContactField::Phone),
+        Some(Stamp::new(2, 7))
+    );
+
+    // Removals are stamped, but only if they removed something
+    contact.remove_phone();
+    contact.remove_phone();
+    assert_eq!(
+        contact.field_stamp(&ContactField::Phone),
+        Some(Stamp::new(3, 7))
+    );
+}
+
+#[test]
+fn test_with_actor_id_restamps_existing_writes() {
+    let contact = Contact::new("Alice".into()).with_actor_id(3);
+    assert_eq!(contact.actor_id(), 3);
+    assert_eq!(
+        contact.field_stamp(&ContactField::Name),
+        Some(Stamp::new(1, 3))
+    );
+
+    // Independently created replicas still converge
+    let mut other = Contact::new("Alicia".into()).with_actor_id(4);
+    let mut merged = contact.clone();
+    merged.merge(&other);
+    other.merge(&contact);
+    assert_eq!(merged.name(), "Alicia");
+    assert!(merged == other);
+}
+
+#[test]
+fn test_later_write_wins() {
+    let (mut alice, mut bob) = replicas();
+    alice.set_phone("555-0100".into());
+    bob.set_phone("555-0199".into());
+    bob.set_phone("555-0123".into());
+
+    alice.merge(&bob);
+    assert_eq!(alice.phone(), Some(&"555-0123".to_string()));
+    bob.merge(&alice);
+    assert!(alice == bob);
+}
+
+#[test]
+fn test_concurrent_writes_are_ordered_by_actor() {
+    let (mut alice, mut bob) = replicas();
+    alice.set_name("Alicia".into());
+    bob.set_name("Ally".into());
+
+    let mut merged_by_alice = alice.clone();
+    merged_by_alice.merge(&bob);
+    let mut merged_by_bob = bob.clone();
+    merged_by_bob.merge(&alice);
+
+    assert_eq!(merged_by_alice.name(), "Ally");
+    assert!(merged_by_alice == merged_by_bob);
+}
+
+#[test]
+fn test_later_removal_wins() {
+    let (mut alice, mut bob) = replicas();
+    alice.set_phone("555-0100".into());
+    bob.merge(&alice);
+    bob.remove_phone();
+    alice.insert_extra("team".into(), "core".into());
+
+    alice.merge(&bob);
+    assert_eq!(alice.phone(), None);
+    assert_eq!(alice.extra("team"), Some(&"core".to_string()));
+
+    // A write made after seeing the removal wins over it
+    alice.set_phone("555-0142".into());
+    bob.merge(&alice);
+    assert_eq!(bob.phone(), Some(&"555-0142".to_string()));
+}
+
+#[test]
+fn test_merge_is_idempotent() {
+    let (mut alice, mut bob) = replicas();
+    bob.set_phone("555-0100".into());
+    alice.merge(&bob);
+    let once = alice.clone();
+    alice.merge(&bob);
+    assert!(alice == once);
+    assert_eq!(
+        alice.field_stamp(&ContactField::Phone),
+        bob.field_stamp(&ContactField::Phone)
+    );
+}
+
+fn union(ours: &BTreeSet<String>, theirs: &BTreeSet<String>) -> BTreeSet<String> {
+    ours.union(theirs).cloned().collect()
+}
+
+#[structible(lww, track_changes)]
+pub struct Document {
+    pub title: String,
+    #[structible(merge_with = union)]
+    pub tags: Option<BTreeSet<String>>,
+}
+
+#[test]
+fn test_resolver_combines_concurrent_values() {
+    let mut ours = Document::new("Draft".into());
+    ours.set_actor_id(1);
+    let mut theirs = ours.clone();
+    theirs.set_actor_id(2);
+
+    ours.set_tags(BTreeSet::from(["a".to_string()]));
+    theirs.set_tags(BTreeSet::from(["b".to_string()]));
+    ours.take_change_log();
+    ours.merge(&theirs);
+    theirs.merge(&ours);
+
+    let expected = BTreeSet::from(["a".to_string(), "b".to_string()]);
+    assert_eq!(ours.tags(), Some(&expected));
+    assert!(ours == theirs);
+    assert_eq!(
+        ours.field_stamp(&DocumentField::Tags),
+        theirs.field_stamp(&DocumentField::Tags)
+    );
+
+    // Merged writes are recorded in the change log
+    assert_eq!(
+        ours.take_change_log(),
+        vec![FieldChange::Set(
+            DocumentField::Tags,
+            DocumentValue::Tags(expected)
+        )]
+    );
+}
+
+#[test]
+fn test_resolver_is_not_used_for_missing_values() {
+    let mut ours = Document::new("Draft".into());
+    ours.set_actor_id(1);
+    let mut theirs = ours.clone();
+    theirs.set_actor_id(2);
+
+    ours.set_tags(BTreeSet::from(["a".to_string()]));
+    theirs.set_tags(BTreeSet::from(["b".to_string()]));
+    theirs.set_tags(BTreeSet::from(["c".to_string()]));
+    theirs.remove_tags();
+
+    ours.merge(&theirs);
+    assert_eq!(ours.tags(), None);
+}
@@ -0,0 +1,138 @@
+//! Tests for `#[structible(instrument)]` metrics counters.
+
+#![cfg(feature = "metrics")]
+
+use std::collections::BTreeMap;
+
+use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+use structible::structible;
+
+#[structible(instrument)]
+pub struct Person {
+    pub name: String,
+    pub age: Option<u32>,
+    #[structible(key = String)]
+    pub extra: Option<String>,
+}
+
+#[structible]
+pub struct Plain {
+    pub name: String,
+}
+
+/// Runs `f` with a local recorder, returning the counters it incremented, keyed by
+/// metric name, struct, and field.
+fn record(f: impl FnOnce()) -> BTreeMap<(String, String, String), u64> {
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    metrics::with_local_recorder(&recorder, f);
+    snapshotter
+        .snapshot()
+        .into_vec()
+        .into_iter()
+        .map(|(key, _, _, value)| {
+            let key = key.key();
+            let label = |name: &str| {
+                key.labels()
+                    .find(|l| l.key() == name)
+                    .map(|l| l.value().to_string())
+                    .unwrap()
+            };
+            let DebugValue::Counter(count) = value else {
+                panic!("expected a counter");
+            };
+            (
+                (key.name().to_string(), label("struct"), label("field")),
+                count,
+            )
+        })
+        .collect()
+}
+
+fn reads(field: &str) -> (String, String, String) {
+    (
+        "structible_field_reads_total".into(),
+        "Person".into(),
+        field.into(),
+    )
+}
+
+fn writes(field: &str) -> (String, String, String) {
+    (
+        "structible_field_writes_total".into(),
+        "Person".into(),
+        field.into(),
+    )
+}
+
+#[test]
+fn test_getters_count_reads() {
+    let mut person = Person::new("Alice".into());
+    let counts = record(|| {
+        person.name();
+        person.name();
+        person.age();
+        person.age_mut();
+    });
+    assert_eq!(counts[&reads("name")], 2);
+    assert_eq!(counts[&reads("age")], 2);
+    assert!(!counts.contains_key(&writes("name")));
+}
+
+#[test]
+fn test_setters_and_removers_count_writes() {
+    let mut person = Person::new("Alice".into());
+    let counts = record(|| {
+        person.set_name("Bob".into());
+        person.set_age(30);
+        person.remove_age();
+        // Removing an absent field doesn't write
+        person.remove_age();
+    });
+    assert_eq!(counts[&writes("name")], 1);
+    assert_eq!(counts[&writes("age")], 2);
+}
+
+#[test]
+fn test_unknown_fields_are_labeled_by_catch_all() {
+    let mut person = Person::new("Alice".into());
+    let counts = record(|| {
+        person.insert_extra("a".into(), "1".into());
+        person.insert_extra("b".into(), "2".into());
+        person.extra("a");
+    });
+    assert_eq!(counts[&writes("extra")], 2);
+    assert_eq!(counts[&reads("extra")], 1);
+}
+
+#[test]
+fn test_uninstrumented_structs_are_not_counted() {
+    let mut plain = Plain::new("Alice".into());
+    let counts = record(|| {
+        plain.set_name("Bob".into());
+        plain.name();
+    });
+    assert!(counts.is_empty());
+}
+
+#[structible(instrument, track_changes)]
+pub struct Tagged<T> {
+    pub value: T,
+    pub tag: Option<String>,
+}
+
+#[test]
+fn test_generic_tracked_struct() {
+    let mut tagged = Tagged::new(1u8);
+    let counts = record(|| {
+        tagged.set_value(2);
+        tagged.set_tag("a".into());
+        tagged.value();
+    });
+    let key =
+        |metric: &str, field: &str| (metric.to_string(), "Tagged".to_string(), field.to_string());
+    assert_eq!(counts[&key("structible_field_writes_total", "value")], 1);
+    assert_eq!(counts[&key("structible_field_writes_total", "tag")], 1);
+    assert_eq!(counts[&key("structible_field_reads_total", "value")], 1);
+    assert_eq!(tagged.take_change_log().len(), 3);
+}
@@ -0,0 +1,108 @@
+//! Tests for `#[structible(minimal)]` structs generating only their accessors.
+
+use std::fmt;
+
+use structible::{FieldChange, structible};
+
+#[structible(minimal)]
+pub struct Person {
+    pub name: String,
+    pub age: Option<u32>,
+}
+
+// Minimal structs don't generate `Debug` or `Default`, so they can be written by hand
+impl fmt::Debug for Person {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Person({})", self.name())
+    }
+}
+
+impl Default for Person {
+    fn default() -> Self {
+        Person::new("Anonymous".into())
+    }
+}
+
+#[structible(minimal, track_changes)]
+pub struct Account {
+    pub owner: String,
+    pub balance: Option<i64>,
+}
+
+#[structible(minimal, freeze)]
+pub struct Config {
+    pub host: String,
+    #[structible(requires = host)]
+    pub port: Option<u16>,
+}
+
+#[structible(minimal)]
+pub struct Wrapper<T> {
+    pub value: T,
+    pub label: Option<String>,
+}
+
+#[test]
+fn test_accessors() {
+    let mut person = Person::new("Alice".into());
+    assert_eq!(person.name(), "Alice");
+    assert_eq!(person.age(), None);
+
+    person.set_age(30);
+    *person.name_mut() = "Bob".into();
+    assert_eq!(person.name(), "Bob");
+    assert_eq!(person.age(), Some(&30));
+
+    assert_eq!(person.remove_age(), Some(30));
+    assert_eq!(person.age(), None);
+}
+
+#[test]
+fn test_handwritten_impls() {
+    let person = Person::default();
+    assert_eq!(format!("{:?}", person), "Person(Anonymous)");
+}
+
+#[test]
+fn test_clone_and_eq() {
+    let mut person = Person::new("Alice".into());
+    let copy = person.clone();
+    assert_eq!(person, copy);
+
+    person.set_age(30);
+    assert_ne!(person, copy);
+}
+
+#[test]
+fn test_track_changes() {
+    let mut account = Account::new("Alice".into());
+    account.take_change_log();
+    account.set_balance(100);
+    account.remove_balance();
+
+    assert_eq!(
+        account.take_change_log(),
+        vec![
+            FieldChange::Set(AccountField::Balance, AccountValue::Balance(100)),
+            FieldChange::Remove(AccountField::Balance),
+        ]
+    );
+}
+
+#[test]
+fn test_freeze() {
+    let mut config = Config::new("localhost".into());
+    config.set_port(8080);
+
+    let frozen = config.freeze();
+    assert_eq!(frozen.host(), "localhost");
+    assert_eq!(frozen.port(), Some(&8080));
+}
+
+#[test]
+fn test_generic() {
+    let mut wrapper = Wrapper::new(42u8);
+    wrapper.set_label("answer".into());
+    assert_eq!(*wrapper.value(), 42);
+    assert_eq!(wrapper.label().map(String::as_str), Some("answer"));
+}
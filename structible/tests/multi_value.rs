@@ -0,0 +1,63 @@
+//! Tests for `multi` catch-alls holding repeated values per key.
+
+use structible::structible;
+
+fn lowercase(key: String) -> String {
+    key.to_lowercase()
+}
+
+#[structible(track_changes, cow)]
+pub struct Request {
+    pub path: String,
+    #[structible(key = String, multi, normalize = lowercase)]
+    pub headers: Option<Vec<String>>,
+}
+
+#[test]
+fn test_append_keeps_repeated_values() {
+    let mut request = Request::new("/".into());
+    request.append_headers("Accept".into(), "text/html".into());
+    request.append_headers("accept".into(), "application/json".into());
+    request.append_headers("Host".into(), "example.com".into());
+
+    assert_eq!(
+        request.headers_all("ACCEPT"),
+        ["text/html", "application/json"]
+    );
+    assert_eq!(request.headers_all("host"), ["example.com"]);
+    assert!(request.headers_all("cookie").is_empty());
+}
+
+#[test]
+fn test_single_value_methods_see_all_values() {
+    let mut request = Request::new("/".into());
+    request.append_headers("accept".into(), "text/html".into());
+    assert_eq!(
+        request.headers("accept"),
+        Some(&vec!["text/html".to_string()])
+    );
+
+    request.insert_headers("accept".into(), vec!["*/*".into()]);
+    assert_eq!(request.headers_all("accept"), ["*/*"]);
+
+    assert_eq!(request.remove_headers("accept"), Some(vec!["*/*".into()]));
+    assert!(request.headers_all("accept").is_empty());
+}
+
+#[test]
+fn test_appends_are_recorded() {
+    let mut request = Request::new("/".into());
+    request.take_change_log();
+    request.append_headers("accept".into(), "text/html".into());
+    request.append_headers("accept".into(), "application/json".into());
+    assert_eq!(request.take_change_log().len(), 2);
+}
+
+#[test]
+fn test_cow_append() {
+    let mut handle = RequestCow::new(Request::new("/".into()));
+    let shared = handle.clone();
+    handle.append_headers("accept".into(), "text/html".into());
+    assert_eq!(handle.headers_all("accept"), ["text/html"]);
+    assert!(shared.headers_all("accept").is_empty());
+}
@@ -0,0 +1,53 @@
+//! Tests for `#[structible(no_panic)]`.
+//!
+//! The getters are only checked not to panic in release builds with the
+//! `no-panic` feature, e.g. `cargo test --release --features no-panic`, which
+//! fails to compile with debug assertions.
+
+#![cfg(not(all(feature = "no-panic", debug_assertions)))]
+
+use structible::structible;
+
+#[structible(no_panic)]
+pub struct Person<T> {
+    pub name: String,
+    pub email: Option<String>,
+    pub tag: Option<T>,
+}
+
+/// Returns a person whose required `name` was taken without being put back.
+fn person_without_name() -> Person<u8> {
+    let mut person = Person::new("Alice".into());
+    std::mem::forget(person.take_name_scoped());
+    person
+}
+
+#[test]
+fn test_getters() {
+    let mut person = Person::new("Alice".into());
+    person.set_tag(7u8);
+    assert_eq!(person.name().map(String::as_str), Some("Alice"));
+    assert_eq!(person.email(), None);
+    assert_eq!(person.tag(), Some(&7));
+
+    *person.email_or_default_mut().unwrap() = "a@example.com".into();
+    assert_eq!(person.email().map(String::as_str), Some("a@example.com"));
+
+    person.name_mut().unwrap().push('!');
+    person.map_name(|name| name.to_uppercase());
+    assert_eq!(person.name().map(String::as_str), Some("ALICE!"));
+}
+
+#[test]
+fn test_missing_required_is_none() {
+    let mut person = person_without_name();
+    assert_eq!(person.name(), None);
+    assert_eq!(person.name_mut(), None);
+    assert!(person.take_name_scoped().is_none());
+
+    person.map_name(|_| unreachable!());
+    assert_eq!(person.name(), None);
+
+    person.set_name("Bob".into());
+    assert_eq!(person.name().map(String::as_str), Some("Bob"));
+}
@@ -0,0 +1,25 @@
+//! Tests for `#[structible(non_exhaustive)]` field and value enums.
+
+use structible::structible;
+
+#[structible(non_exhaustive, track_changes)]
+pub struct Person {
+    pub name: String,
+    pub age: Option<u32>,
+}
+
+#[test]
+fn test_enums_work_as_usual() {
+    let mut person = Person::new("Ada".into());
+    person.set_age(36);
+
+    let names: Vec<_> = person
+        .take_change_log()
+        .into_iter()
+        .map(|change| match change {
+            structible::FieldChange::Set(field, _) => field.as_str(),
+            structible::FieldChange::Remove(field) => field.as_str(),
+        })
+        .collect();
+    assert_eq!(names, ["name", "age"]);
+}
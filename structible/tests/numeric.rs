@@ -0,0 +1,48 @@
+//! Tests for the `incr_` and `decr_` helpers of `numeric` fields.
+
+use structible::structible;
+
+#[structible]
+pub struct Counters {
+    #[structible(numeric)]
+    pub hits: u32,
+    #[structible(numeric)]
+    pub misses: Option<u8>,
+    #[structible(numeric)]
+    pub balance: Option<f64>,
+}
+
+#[test]
+fn test_required_counter() {
+    let mut counters = Counters::new(0);
+    counters.incr_hits(3);
+    counters.decr_hits(1);
+    assert_eq!(counters.hits(), &2);
+
+    counters.saturating_decr_hits(5);
+    assert_eq!(counters.hits(), &0);
+    assert_eq!(counters.checked_decr_hits(1), None);
+    assert_eq!(counters.checked_incr_hits(4), Some(4));
+    assert_eq!(counters.hits(), &4);
+}
+
+#[test]
+fn test_optional_counter() {
+    let mut counters = Counters::new(0);
+    assert_eq!(counters.checked_decr_misses(1), None);
+    assert_eq!(counters.misses(), None);
+
+    counters.incr_misses(250);
+    counters.saturating_incr_misses(10);
+    assert_eq!(counters.misses(), Some(&u8::MAX));
+    assert_eq!(counters.checked_incr_misses(1), None);
+    assert_eq!(counters.misses(), Some(&u8::MAX));
+}
+
+#[test]
+fn test_float_counter() {
+    let mut counters = Counters::new(0);
+    counters.incr_balance(2.5);
+    counters.decr_balance(1.0);
+    assert_eq!(counters.balance(), Some(&1.5));
+}
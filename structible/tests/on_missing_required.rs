@@ -0,0 +1,54 @@
+//! Tests for the struct-level `on_missing_required` hook.
+
+use std::sync::Mutex;
+
+use structible::structible;
+
+static MISSING: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+fn report_missing(field: &'static str) -> ! {
+    MISSING.lock().unwrap().push(field);
+    panic!("custom policy for `{}`", field)
+}
+
+#[structible(on_missing_required = report_missing)]
+pub struct Person {
+    pub name: String,
+    pub r#type: u8,
+    pub email: Option<String>,
+}
+
+/// Returns a person whose required `name` was taken without being put back.
+fn person_without_name() -> Person {
+    let mut person = Person::new("Alice".into(), 1);
+    std::mem::forget(person.take_name_scoped());
+    person
+}
+
+#[test]
+fn test_present_fields_are_unaffected() {
+    let person = Person::new("Alice".into(), 1);
+    assert_eq!(person.name(), "Alice");
+    assert_eq!(person.r#type(), &1);
+}
+
+#[test]
+#[should_panic(expected = "custom policy for `name`")]
+fn test_getter_calls_hook() {
+    person_without_name().name();
+}
+
+#[test]
+#[should_panic(expected = "custom policy for `name`")]
+fn test_mut_getter_calls_hook() {
+    person_without_name().name_mut();
+}
+
+#[test]
+fn test_hook_receives_field_name() {
+    let result = std::panic::catch_unwind(|| {
+        person_without_name().map_name(|name| name + "!");
+    });
+    assert!(result.is_err());
+    assert!(MISSING.lock().unwrap().contains(&"name"));
+}
@@ -0,0 +1,132 @@
+//! Tests for `#[structible(openapi)]` schemas.
+
+#![cfg(feature = "utoipa")]
+
+use serde_json::json;
+use structible::structible;
+use utoipa::{PartialSchema, ToSchema};
+
+#[derive(Clone, Debug, PartialEq, ToSchema)]
+pub struct Address {
+    pub street: String,
+}
+
+#[derive(Clone, Debug, PartialEq, ToSchema)]
+pub struct Location {
+    pub address: Address,
+}
+
+#[structible(openapi)]
+pub struct Person {
+    pub name: String,
+    pub age: Option<u32>,
+    pub r#type: Option<String>,
+    pub location: Option<Location>,
+    #[structible(key = String)]
+    pub extra: Option<String>,
+}
+
+#[structible(openapi)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+fn schema_json<T: PartialSchema>() -> serde_json::Value {
+    serde_json::to_value(T::schema()).unwrap()
+}
+
+#[test]
+fn test_required_and_optional_fields() {
+    let schema = schema_json::<Person>();
+    assert_eq!(schema["type"], "object");
+    assert_eq!(schema["required"], json!(["name"]));
+    assert_eq!(schema["properties"]["name"]["type"], "string");
+    assert_eq!(schema["properties"]["age"]["type"], "integer");
+    assert_eq!(schema["properties"]["type"]["type"], "string");
+    assert_eq!(
+        schema["properties"]["location"]["properties"]["address"]["$ref"],
+        "#/components/schemas/Address"
+    );
+    assert!(schema["properties"].get("extra").is_none());
+}
+
+#[test]
+fn test_catch_all_is_additional_properties() {
+    assert_eq!(
+        schema_json::<Person>()["additionalProperties"],
+        json!({ "type": "string" })
+    );
+    assert_eq!(schema_json::<Point>()["additionalProperties"], json!(false));
+    assert_eq!(schema_json::<Point>()["required"], json!(["x", "y"]));
+}
+
+#[test]
+fn test_referenced_schemas_are_collected() {
+    assert_eq!(<Person as ToSchema>::name(), "Person");
+    let mut schemas = Vec::new();
+    Person::schemas(&mut schemas);
+    let names: Vec<_> = schemas.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, ["Address"]);
+}
+
+#[structible(openapi)]
+pub struct Wrapper<T> {
+    pub value: T,
+    pub tags: Option<Vec<T>>,
+}
+
+#[test]
+fn test_generic_struct() {
+    let schema = schema_json::<Wrapper<bool>>();
+    assert_eq!(schema["properties"]["value"]["type"], "boolean");
+    assert_eq!(schema["properties"]["tags"]["items"]["type"], "boolean");
+}
+
+#[structible(openapi)]
+pub struct Reading {
+    #[structible(range = 0..=150)]
+    pub age: u8,
+    #[structible(range = -1.5..)]
+    pub offset: Option<f64>,
+}
+
+#[test]
+fn test_range_bounds() {
+    let schema = schema_json::<Reading>();
+    assert_eq!(schema["properties"]["age"]["minimum"], 0);
+    assert_eq!(schema["properties"]["age"]["maximum"], 150);
+    assert_eq!(schema["properties"]["offset"]["minimum"], -1.5);
+    assert!(schema["properties"]["offset"].get("maximum").is_none());
+}
+
+#[structible(openapi)]
+pub struct Tagged {
+    #[structible(len = 1..=64)]
+    pub title: String,
+    #[structible(non_empty)]
+    pub tags: Option<Vec<String>>,
+}
+
+#[test]
+fn test_length_bounds() {
+    let schema = schema_json::<Tagged>();
+    assert_eq!(schema["properties"]["title"]["minLength"], 1);
+    assert_eq!(schema["properties"]["title"]["maxLength"], 64);
+    assert_eq!(schema["properties"]["tags"]["minItems"], 1);
+    assert!(schema["properties"]["tags"].get("maxItems").is_none());
+}
+
+#[cfg(feature = "regex")]
+#[structible(openapi)]
+pub struct Slug {
+    #[structible(matches = "^[a-z-]+$")]
+    pub slug: String,
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn test_pattern() {
+    let schema = schema_json::<Slug>();
+    assert_eq!(schema["properties"]["slug"]["pattern"], "^[a-z-]+$");
+}
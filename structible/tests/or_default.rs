@@ -0,0 +1,75 @@
+//! Tests for the `<getter>_or_default_mut()` methods of optional fields.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use structible::structible;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+#[structible]
+pub struct Stats {
+    pub name: String,
+    pub samples: Option<Vec<u32>>,
+    pub counts: Option<HashMap<String, usize>>,
+    #[structible(get = label)]
+    pub title: Option<String>,
+    // No `Default`, so no `origin_or_default_mut()` can be called, but the struct compiles
+    pub origin: Option<Point>,
+}
+
+#[structible]
+pub struct Bag<T> {
+    pub items: Option<Vec<T>>,
+}
+
+#[structible(ttl)]
+pub struct Session {
+    pub log: Option<Vec<String>>,
+}
+
+#[test]
+fn test_inserts_default_when_absent() {
+    let mut stats = Stats::new("latency".into());
+    stats.samples_or_default_mut().push(3);
+    stats.samples_or_default_mut().push(5);
+    assert_eq!(stats.samples(), Some(&vec![3, 5]));
+
+    *stats
+        .counts_or_default_mut()
+        .entry("hits".into())
+        .or_default() += 1;
+    assert_eq!(stats.counts().unwrap()["hits"], 1);
+
+    stats.label_or_default_mut().push_str("p99");
+    assert_eq!(stats.label(), Some(&"p99".to_string()));
+    assert_eq!(stats.origin(), None);
+}
+
+#[test]
+fn test_keeps_present_value() {
+    let mut stats = Stats::new("latency".into());
+    stats.set_samples(vec![1]);
+    stats.samples_or_default_mut().push(2);
+    assert_eq!(stats.samples(), Some(&vec![1, 2]));
+}
+
+#[test]
+fn test_generic_field() {
+    let mut bag = Bag::<&str>::new();
+    bag.items_or_default_mut().push("apple");
+    assert_eq!(bag.items(), Some(&vec!["apple"]));
+}
+
+#[test]
+fn test_replaces_expired_value() {
+    let mut session = Session::new();
+    session.set_log_with_ttl(vec!["old".into()], Duration::ZERO);
+    session.log_or_default_mut().push("new".into());
+    assert_eq!(session.log(), Some(&vec!["new".to_string()]));
+    assert_eq!(session.log_expires_at(), None);
+}
@@ -0,0 +1,87 @@
+//! Tests for `#[structible(par_visit)]` parallel field visiting.
+#![cfg(feature = "rayon")]
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use structible::structible;
+
+#[structible(par_visit)]
+pub struct Media {
+    pub title: String,
+    pub thumbnail: Option<Vec<u8>>,
+    pub preview: Option<Vec<u8>>,
+    #[structible(key = String)]
+    pub blobs: Option<Vec<u8>>,
+}
+
+#[test]
+fn test_visits_present_fields() {
+    let mut media = Media::new("clip".into());
+    media.set_thumbnail(vec![0; 16]);
+    media.insert_blobs("raw".into(), vec![0; 64]);
+
+    let seen = Mutex::new(Vec::new());
+    media.par_visit_fields(|field, value| {
+        let size = match value {
+            MediaValue::Title(title) => title.len(),
+            MediaValue::Thumbnail(bytes) | MediaValue::Unknown(bytes) => bytes.len(),
+            MediaValue::Preview(_) => unreachable!("absent fields aren't visited"),
+        };
+        seen.lock().unwrap().push((field.clone(), size));
+    });
+
+    let mut seen = seen.into_inner().unwrap();
+    seen.sort();
+    assert_eq!(
+        seen,
+        vec![
+            (MediaField::Title, 4),
+            (MediaField::Thumbnail, 16),
+            (MediaField::Unknown("raw".into()), 64),
+        ]
+    );
+}
+
+#[structible(par_visit, ttl)]
+pub struct Cache {
+    pub hot: Option<u32>,
+    pub stale: Option<u32>,
+}
+
+#[test]
+fn test_skips_expired_fields() {
+    let mut cache = Cache::default();
+    cache.set_hot(1);
+    cache.set_stale_with_ttl(2, Duration::ZERO);
+
+    let visited = AtomicUsize::new(0);
+    cache.par_visit_fields(|field, _| {
+        assert_eq!(*field, CacheField::Hot);
+        visited.fetch_add(1, Ordering::Relaxed);
+    });
+    assert_eq!(visited.into_inner(), 1);
+}
+
+#[structible(par_visit, backing = BTreeMap)]
+pub struct Samples<T> {
+    pub first: T,
+    pub second: Option<T>,
+}
+
+#[test]
+fn test_generic_fields() {
+    let mut samples = Samples::new(2u64);
+    samples.set_second(3);
+
+    let sum = AtomicUsize::new(0);
+    samples.par_visit_fields(|_, value| {
+        let n = match value {
+            SamplesValue::First(n) | SamplesValue::Second(n) => *n,
+        };
+        sum.fetch_add(n as usize, Ordering::Relaxed);
+    });
+    assert_eq!(sum.into_inner(), 5);
+}
@@ -0,0 +1,90 @@
+//! Tests for writing and reading Parquet files of `#[structible(arrow)]` structs.
+
+#![cfg(feature = "parquet")]
+
+use std::path::PathBuf;
+
+use structible::parquet::{read_parquet, write_parquet};
+use structible::structible;
+
+#[structible(arrow)]
+pub struct Person {
+    pub name: String,
+    pub age: Option<u32>,
+    pub active: Option<bool>,
+}
+
+#[structible(arrow)]
+pub struct Label {
+    pub text: Option<String>,
+}
+
+#[structible(arrow)]
+pub struct Named {
+    pub name: String,
+}
+
+/// A file in the temporary directory, removed when dropped.
+struct TempFile(PathBuf);
+
+impl TempFile {
+    fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!(
+            "structible-{}-{}.parquet",
+            name,
+            std::process::id()
+        ));
+        TempFile(path)
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+#[test]
+fn test_roundtrip() {
+    let file = TempFile::new("roundtrip");
+    let people: Vec<_> = (0..10_000)
+        .map(|i| {
+            let mut person = Person::new(format!("person-{}", i));
+            if i % 3 == 0 {
+                person.set_age(i);
+            }
+            if i % 5 == 0 {
+                person.set_active(i % 2 == 0);
+            }
+            person
+        })
+        .collect();
+
+    write_parquet(&people, &file.0).unwrap();
+    let read: Vec<Person> = read_parquet(&file.0).unwrap();
+    assert_eq!(read, people);
+}
+
+#[test]
+fn test_empty_file() {
+    let file = TempFile::new("empty");
+    write_parquet::<Person, _, _>([], &file.0).unwrap();
+    let read: Vec<Person> = read_parquet(&file.0).unwrap();
+    assert!(read.is_empty());
+}
+
+#[test]
+fn test_missing_columns() {
+    let file = TempFile::new("missing");
+    write_parquet(&[Named::new("Alice".into())], &file.0).unwrap();
+
+    // Optional fields without a column are absent
+    let read: Vec<Label> = read_parquet(&file.0).unwrap();
+    assert_eq!(read, [Label::new()]);
+
+    // Required fields need their column
+    let file = TempFile::new("labels");
+    write_parquet(&[Label::new()], &file.0).unwrap();
+    let err = read_parquet::<Named, _>(&file.0).unwrap_err();
+    assert!(err.to_string().contains("missing column `name`"), "{}", err);
+}
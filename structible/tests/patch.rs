@@ -0,0 +1,134 @@
+//! Tests for `#[structible(patch)]` patch builders and `validate_all()`.
+
+use structible::{FieldChange, ValidationError, structible};
+
+fn check_age(person: &Person) -> Result<(), ValidationError> {
+    match person.age() {
+        Some(age) if *age > 150 => Err(ValidationError::invariant("age out of range")),
+        _ => Ok(()),
+    }
+}
+
+#[structible(patch, invariant = check_age)]
+pub struct Person {
+    pub name: String,
+    pub age: Option<u32>,
+    #[structible(requires = city)]
+    pub street: Option<String>,
+    pub city: Option<String>,
+    #[structible(conflicts_with = email)]
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    #[structible(key = String)]
+    pub extra: Option<String>,
+}
+
+#[test]
+fn test_commit_applies_changes() {
+    let mut person = Person::new("Alice".into());
+    person.set_email("alice@example.com".into());
+
+    PersonPatchBuilder::new()
+        .set_name("Bob".into())
+        .set_age(30)
+        .remove_email()
+        .insert_extra("plan".into(), "pro".into())
+        .commit(&mut person)
+        .unwrap();
+
+    assert_eq!(person.name(), "Bob");
+    assert_eq!(person.age(), Some(&30));
+    assert_eq!(person.email(), None);
+    assert_eq!(person.extra("plan"), Some(&"pro".to_string()));
+}
+
+#[test]
+fn test_check_reports_every_violation() {
+    let mut person = Person::new("Alice".into());
+    person.set_email("alice@example.com".into());
+
+    let patch = PersonPatchBuilder::new()
+        .set_age(200)
+        .set_street("Main St".into())
+        .set_phone("555".into());
+    let issues = patch.check(&person).unwrap_err();
+    assert_eq!(
+        issues,
+        vec![
+            ValidationError::MissingDependency {
+                field: "street",
+                requires: "city",
+            },
+            ValidationError::Conflict {
+                field: "phone",
+                conflicts_with: "email",
+            },
+            ValidationError::invariant("age out of range"),
+        ]
+    );
+
+    // Neither the dry run nor a failed commit changes the struct
+    assert_eq!(patch.commit(&mut person).unwrap_err().len(), 3);
+    assert_eq!(person.age(), None);
+    assert_eq!(person.street(), None);
+}
+
+#[test]
+fn test_patch_can_fix_violations() {
+    let patch = PersonPatchBuilder::new()
+        .set_street("Main St".into())
+        .set_city("Springfield".into());
+    let mut person = Person::new("Alice".into());
+    assert!(patch.check(&person).is_ok());
+    patch.commit(&mut person).unwrap();
+    assert_eq!(person.city(), Some(&"Springfield".to_string()));
+}
+
+#[test]
+fn test_validate_all() {
+    let mut person = Person::new("Alice".into());
+    assert_eq!(person.validate_all(), Ok(()));
+    person.set_street("Main St".into());
+    person.set_age(151);
+    assert_eq!(person.validate_all().unwrap_err().len(), 2);
+    assert_eq!(
+        person.validate(),
+        Err(ValidationError::MissingDependency {
+            field: "street",
+            requires: "city",
+        })
+    );
+}
+
+#[test]
+fn test_changes() {
+    let patch = PersonPatchBuilder::new()
+        .set_age(1)
+        .remove_extra("plan".into());
+    assert_eq!(
+        patch.changes(),
+        &[
+            FieldChange::Set(PersonField::Age, PersonValue::Age(1)),
+            FieldChange::Remove(PersonField::Unknown("plan".into())),
+        ]
+    );
+    assert_eq!(PersonPatchBuilder::default().changes(), &[]);
+}
+
+#[structible(patch, track_changes)]
+pub struct Pair<T> {
+    pub left: T,
+    pub right: Option<T>,
+}
+
+#[test]
+fn test_generic_unvalidated_struct() {
+    let mut pair = Pair::new(1u8);
+    pair.take_change_log();
+    let patch = PairPatchBuilder::new().set_left(2).set_right(3);
+    assert!(patch.check(&pair).is_ok());
+    patch.clone().commit(&mut pair).unwrap();
+    assert_eq!((*pair.left(), pair.right()), (2, Some(&3)));
+    assert_eq!(pair.take_change_log().len(), 2);
+    assert!(format!("{:?}", patch).starts_with("PairPatchBuilder { changes: ["));
+}
@@ -0,0 +1,54 @@
+//! Tests for `#[structible(matches = "...")]` pattern constraints.
+
+#![cfg(feature = "regex")]
+
+use structible::{ValidationError, structible};
+
+#[structible]
+pub struct Package {
+    #[structible(matches = "^[a-z0-9-]+$")]
+    pub name: String,
+    #[structible(matches = r"^\d+\.\d+\.\d+$")]
+    pub version: Option<String>,
+}
+
+#[test]
+fn test_try_set_checks_pattern() {
+    let mut package = Package::new("structible".into());
+    package.try_set_name("structible-macros".into()).unwrap();
+
+    let err = package.try_set_name("Structible".into()).unwrap_err();
+    assert_eq!(
+        err,
+        ValidationError::PatternMismatch {
+            field: "name",
+            pattern: "^[a-z0-9-]+$",
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "field `name` does not match the pattern `^[a-z0-9-]+$`"
+    );
+    assert_eq!(package.name(), "structible-macros");
+}
+
+#[test]
+fn test_optional_field_pattern() {
+    let mut package = Package::new("structible".into());
+    package.try_set_version("0.5.0".into()).unwrap();
+    assert!(package.try_set_version("latest".into()).is_err());
+    assert_eq!(package.version(), Some(&"0.5.0".to_string()));
+}
+
+#[test]
+fn test_construction_checks_pattern() {
+    assert!(Package::try_new("structible".into()).is_ok());
+    assert!(matches!(
+        Package::try_new("not valid".into()),
+        Err(ValidationError::PatternMismatch { field: "name", .. })
+    ));
+
+    let mut package = Package::new("structible".into());
+    package.set_version("next".into());
+    assert!(package.validate().is_err());
+}
@@ -0,0 +1,101 @@
+//! Tests for catch-alls declared with `promote_known`.
+#![cfg(feature = "serde")]
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use structible::structible;
+
+#[structible(serde_backing, track_changes, cow)]
+pub struct Contact {
+    pub name: String,
+    pub email: Option<String>,
+    pub age: Option<u32>,
+    #[structible(key = String, promote_known)]
+    pub extra: Option<serde_json::Value>,
+}
+
+#[test]
+fn test_insert_routes_known_keys() {
+    let mut contact = Contact::new("Alice".into());
+    let prev = contact.insert_extra("email".into(), "alice@example.com".into());
+    assert_eq!(prev, None);
+    assert_eq!(contact.email(), Some(&"alice@example.com".to_string()));
+    assert_eq!(contact.extra_iter().count(), 0);
+
+    contact.insert_extra("age".into(), 30.into());
+    assert_eq!(contact.age(), Some(&30));
+
+    // The change log records the routed write
+    let changes = contact.take_change_log();
+    assert_eq!(changes.len(), 3);
+}
+
+#[test]
+fn test_unconvertible_values_stay_in_catch_all() {
+    let mut contact = Contact::new("Alice".into());
+    contact.insert_extra("age".into(), "thirty".into());
+    assert_eq!(contact.age(), None);
+    assert_eq!(contact.extra("age"), Some(&"thirty".into()));
+
+    contact.insert_extra("team".into(), "core".into());
+    assert_eq!(contact.extra("team"), Some(&"core".into()));
+}
+
+#[test]
+fn test_cow_insert_routes_known_keys() {
+    let mut handle = ContactCow::new(Contact::new("Alice".into()));
+    handle.insert_extra("email".into(), "alice@example.com".into());
+    assert_eq!(handle.email(), Some(&"alice@example.com".to_string()));
+}
+
+#[derive(Serialize, Deserialize)]
+struct Stored {
+    #[serde(with = "structible::serde_adapter")]
+    contact: Contact,
+}
+
+#[test]
+fn test_deserialization_routes_known_keys() {
+    let json = r#"{"contact":[
+        ["name",{"name":"Alice"}],
+        [{"extra":"email"},{"extra":"alice@example.com"}],
+        [{"extra":"team"},{"extra":"core"}]
+    ]}"#;
+    let contact = serde_json::from_str::<Stored>(json).unwrap().contact;
+    assert_eq!(contact.email(), Some(&"alice@example.com".to_string()));
+    assert_eq!(contact.extra("team"), Some(&"core".into()));
+    assert_eq!(contact.extra("email"), None);
+}
+
+#[structible(serde_backing, backing = BTreeMap)]
+pub struct Tagged<V: Ord> {
+    pub label: Option<String>,
+    #[structible(key = String, promote_known)]
+    pub tags: Option<V>,
+}
+
+#[test]
+fn test_generic_catch_all() {
+    let mut tagged = Tagged::<String>::default();
+    tagged.insert_tags("label".into(), "primary".into());
+    assert_eq!(tagged.label(), Some(&"primary".to_string()));
+}
+
+#[cfg(feature = "codec")]
+#[structible(value_codec)]
+pub struct Profile {
+    pub email: Option<String>,
+    #[structible(key = String, promote_known)]
+    pub extra: Option<String>,
+}
+
+#[cfg(feature = "codec")]
+#[test]
+fn test_codec_routes_known_keys() {
+    use structible::ValueCodec;
+
+    let profile = Profile::decode_entries([("extra.email", br#""a@example.com""#)]).unwrap();
+    assert_eq!(profile.email(), Some(&"a@example.com".to_string()));
+    assert_eq!(profile.extra("email"), None);
+}
@@ -0,0 +1,101 @@
+//! Tests for `promotable` fields moving values to and from the catch-all.
+
+use structible::structible;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Property {
+    Text(String),
+    Number(i64),
+}
+
+impl TryFrom<Property> for String {
+    type Error = Property;
+
+    fn try_from(value: Property) -> Result<Self, Self::Error> {
+        match value {
+            Property::Text(text) => Ok(text),
+            other => Err(other),
+        }
+    }
+}
+
+impl From<String> for Property {
+    fn from(value: String) -> Self {
+        Property::Text(value)
+    }
+}
+
+#[structible(track_changes)]
+pub struct Contact {
+    #[structible(promotable)]
+    pub name: String,
+    #[structible(promotable)]
+    pub email: Option<String>,
+    #[structible(key = String)]
+    pub extra: Option<Property>,
+}
+
+#[test]
+fn test_promote() {
+    let mut contact = Contact::new("Alice".into());
+    contact.insert_extra("email".into(), Property::Text("alice@example.com".into()));
+
+    assert_eq!(contact.promote_extra_to_email("email"), Ok(true));
+    assert_eq!(contact.email(), Some(&"alice@example.com".to_string()));
+    assert_eq!(contact.extra("email"), None);
+
+    // Absent keys promote nothing
+    assert_eq!(contact.promote_extra_to_email("email"), Ok(false));
+}
+
+#[test]
+fn test_failed_promotion_keeps_entry() {
+    let mut contact = Contact::new("Alice".into());
+    contact.insert_extra("email".into(), Property::Number(7));
+
+    assert_eq!(
+        contact.promote_extra_to_email("email"),
+        Err(Property::Number(7))
+    );
+    assert_eq!(contact.email(), None);
+    assert_eq!(contact.extra("email"), Some(&Property::Number(7)));
+}
+
+#[test]
+fn test_promote_required_field() {
+    let mut contact = Contact::new("Alice".into());
+    contact.insert_extra("full_name".into(), Property::Text("Alice Smith".into()));
+    assert_eq!(contact.promote_extra_to_name("full_name"), Ok(true));
+    assert_eq!(contact.name(), "Alice Smith");
+}
+
+#[test]
+fn test_demote() {
+    let mut contact = Contact::new("Alice".into());
+    contact.set_email("alice@example.com".into());
+
+    assert_eq!(
+        contact.demote_email_to_extra("legacy_email".into()),
+        Ok(true)
+    );
+    assert_eq!(contact.email(), None);
+    assert_eq!(
+        contact.extra("legacy_email"),
+        Some(&Property::Text("alice@example.com".into()))
+    );
+
+    assert_eq!(
+        contact.demote_email_to_extra("legacy_email".into()),
+        Ok(false)
+    );
+}
+
+#[test]
+fn test_moves_are_recorded() {
+    let mut contact = Contact::new("Alice".into());
+    contact.set_email("alice@example.com".into());
+    contact.take_change_log();
+
+    contact.demote_email_to_extra("email".into()).unwrap();
+    assert_eq!(contact.take_change_log().len(), 2);
+}
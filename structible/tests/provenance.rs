@@ -0,0 +1,86 @@
+//! Tests for `#[structible(provenance)]` value sources.
+
+use std::time::Duration;
+
+use structible::{Source, structible};
+
+#[structible(provenance)]
+pub struct Settings {
+    pub theme: String,
+    pub font_size: Option<u32>,
+    pub language: Option<String>,
+}
+
+#[structible(provenance, ttl)]
+pub struct Cache {
+    pub id: String,
+    pub value: Option<String>,
+}
+
+#[structible(provenance, lww)]
+pub struct Replica {
+    pub name: String,
+    pub email: Option<String>,
+}
+
+#[test]
+fn test_set_with_source() {
+    let mut settings = Settings::new("light".into());
+    settings.set_theme_with_source("dark".into(), Source::User);
+    settings.set_font_size_with_source(12, Source::Default);
+
+    assert_eq!(settings.theme(), "dark");
+    assert_eq!(settings.theme_source(), Some(Source::User));
+    assert_eq!(settings.font_size_source(), Some(Source::Default));
+    assert_eq!(settings.language_source(), None);
+}
+
+#[test]
+fn test_plain_write_clears_source() {
+    let mut settings = Settings::new("light".into());
+    assert_eq!(settings.theme_source(), None);
+
+    settings.set_font_size_with_source(12, Source::Migration);
+    settings.set_font_size(14);
+    assert_eq!(settings.font_size_source(), None);
+}
+
+#[test]
+fn test_remove_clears_source() {
+    let mut settings = Settings::new("light".into());
+    settings.set_language_with_source("en".into(), Source::Sync);
+    settings.remove_language();
+    assert_eq!(settings.language_source(), None);
+}
+
+#[test]
+fn test_sources_cloned_but_not_compared() {
+    let mut settings = Settings::new("light".into());
+    settings.set_font_size_with_source(12, Source::Default);
+
+    let mut other = Settings::new("light".into());
+    other.set_font_size(12);
+    assert_eq!(settings, other);
+    assert_eq!(settings.clone().font_size_source(), Some(Source::Default));
+}
+
+#[test]
+fn test_expired_value_has_no_source() {
+    let mut cache = Cache::new("a".into());
+    cache.set_value_with_source("b".into(), Source::Sync);
+    cache.set_value_with_ttl("c".into(), Duration::ZERO);
+    assert_eq!(cache.value_source(), None);
+}
+
+#[test]
+fn test_merged_values_are_synced() {
+    let mut ours = Replica::new("Alice".into()).with_actor_id(1);
+    let mut theirs = Replica::new("Alice".into()).with_actor_id(2);
+    ours.set_email_with_source("alice@example.com".into(), Source::User);
+    theirs.set_email("alice@example.org".into());
+    theirs.set_email("alice@example.net".into());
+
+    ours.merge(&theirs);
+    assert_eq!(ours.email(), Some(&"alice@example.net".to_string()));
+    assert_eq!(ours.email_source(), Some(Source::Sync));
+}
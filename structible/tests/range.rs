@@ -0,0 +1,81 @@
+//! Tests for `#[structible(range = ...)]` numeric bounds.
+
+use structible::{ValidationError, structible};
+
+const MAX_RATIO: f64 = 1.0;
+
+#[structible]
+pub struct Profile {
+    pub name: String,
+    #[structible(range = 0..=150)]
+    pub age: u8,
+    #[structible(range = 0.0..=MAX_RATIO)]
+    pub ratio: Option<f64>,
+    #[structible(range = -40..)]
+    pub temperature: Option<i32>,
+}
+
+#[test]
+fn test_try_set_checks_range() {
+    let mut profile = Profile::new("Ada".into(), 36);
+    profile.try_set_age(150).unwrap();
+    assert_eq!(*profile.age(), 150);
+
+    let err = profile.try_set_age(151).unwrap_err();
+    assert_eq!(
+        err,
+        ValidationError::OutOfRange {
+            field: "age",
+            range: "0..=150",
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "field `age` is outside the range `0..=150`"
+    );
+    assert_eq!(*profile.age(), 150);
+}
+
+#[test]
+fn test_try_set_optional_and_open_ranges() {
+    let mut profile = Profile::new("Ada".into(), 36);
+    profile.try_set_ratio(0.5).unwrap();
+    assert!(profile.try_set_ratio(1.5).is_err());
+    assert!(profile.try_set_ratio(f64::NAN).is_err());
+    assert_eq!(profile.ratio(), Some(&0.5));
+
+    profile.try_set_temperature(1000).unwrap();
+    assert_eq!(
+        profile.try_set_temperature(-41),
+        Err(ValidationError::OutOfRange {
+            field: "temperature",
+            range: "-40..",
+        })
+    );
+}
+
+#[test]
+fn test_clamped_setters() {
+    let mut profile = Profile::new("Ada".into(), 36);
+    profile.set_age_clamped(200);
+    assert_eq!(*profile.age(), 150);
+
+    profile.set_ratio_clamped(-0.5);
+    assert_eq!(profile.ratio(), Some(&0.0));
+    profile.set_ratio_clamped(0.25);
+    assert_eq!(profile.ratio(), Some(&0.25));
+
+    profile.set_temperature_clamped(-100);
+    assert_eq!(profile.temperature(), Some(&-40));
+}
+
+#[test]
+fn test_validate_checks_range() {
+    let mut profile = Profile::try_new("Ada".into(), 36).unwrap();
+    assert!(Profile::try_new("Ada".into(), 200).is_err());
+
+    profile.set_age(200);
+    profile.set_ratio(2.0);
+    assert!(profile.validate().is_err());
+    assert_eq!(profile.validate_all().unwrap_err().len(), 2);
+}
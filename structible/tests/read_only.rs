@@ -0,0 +1,82 @@
+//! Tests for `no_setters`, `no_removers`, and `no_mut_getters`.
+//!
+//! Skipped methods are checked by defining methods of the same names, which would
+//! otherwise conflict with the generated ones.
+
+use structible::structible;
+
+/// A read-only value object.
+#[structible(no_setters, no_removers, no_mut_getters)]
+pub struct Money {
+    pub amount: i64,
+    pub currency: Option<String>,
+    #[structible(alias = old_note)]
+    pub note: Option<String>,
+    #[structible(key = String)]
+    pub tags: Option<String>,
+}
+
+impl Money {
+    pub fn set_amount(&self) -> &'static str {
+        "user-defined"
+    }
+
+    pub fn currency_mut(&self) -> &'static str {
+        "user-defined"
+    }
+
+    pub fn remove_currency(&self) -> &'static str {
+        "user-defined"
+    }
+
+    pub fn take_amount_scoped(&self) {}
+
+    pub fn map_currency(&self) {}
+
+    pub fn set_old_note(&self) {}
+
+    pub fn remove_old_note(&self) {}
+
+    pub fn insert_tags(&self) {}
+
+    pub fn remove_tags(&self) {}
+
+    pub fn tags_iter_mut(&self) {}
+}
+
+#[test]
+fn test_read_only_struct() {
+    let money = Money::new(100);
+    assert_eq!(*money.amount(), 100);
+    assert_eq!(money.currency(), None);
+    assert_eq!(money.tags_iter().count(), 0);
+    assert_eq!(money.set_amount(), "user-defined");
+    assert_eq!(money.currency_mut(), "user-defined");
+    assert_eq!(money.remove_currency(), "user-defined");
+}
+
+#[test]
+fn test_fields_can_still_be_taken() {
+    let mut fields = Money::new(5).into_fields();
+    assert_eq!(fields.take_amount(), Some(5));
+}
+
+#[structible(no_removers)]
+pub struct Settings {
+    pub theme: Option<String>,
+}
+
+impl Settings {
+    pub fn remove_theme(&mut self) {
+        self.set_theme("default".into());
+    }
+}
+
+#[test]
+fn test_single_family_is_skipped() {
+    let mut settings = Settings::default();
+    settings.set_theme("dark".into());
+    *settings.theme_mut().unwrap() = "light".into();
+    settings.remove_theme();
+    assert_eq!(settings.theme(), Some(&"default".to_string()));
+}
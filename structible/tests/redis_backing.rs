@@ -0,0 +1,71 @@
+//! Tests for the `RedisHashMap` backing.
+//!
+//! The round-trip test talks to a live server and only runs when `REDIS_URL`
+//! is set, e.g. `REDIS_URL=redis://127.0.0.1 cargo test --features redis`.
+#![cfg(feature = "redis")]
+
+use structible::redis::RedisHashMap;
+use structible::structible;
+
+#[structible(backing = RedisHashMap, async, serde_backing)]
+pub struct Session {
+    pub user_id: u64,
+    pub token: Option<String>,
+    #[structible(key = String)]
+    pub claims: Option<String>,
+}
+
+type SessionHash = RedisHashMap<__StructibleField_Session, __StructibleValue_Session>;
+
+#[test]
+fn test_hash_field_names() {
+    assert_eq!(
+        SessionHash::hash_field(&__StructibleField_Session::UserId).unwrap(),
+        "user_id"
+    );
+    assert_eq!(
+        SessionHash::hash_field(&__StructibleField_Session::Unknown("role".into())).unwrap(),
+        r#"{"claims":"role"}"#
+    );
+}
+
+#[test]
+fn test_value_encoding() {
+    let value = __StructibleValue_Session::UserId(42);
+    assert_eq!(serde_json::to_string(&value).unwrap(), r#"{"user_id":42}"#);
+
+    let decoded: __StructibleValue_Session = serde_json::from_str(r#"{"token":"abc"}"#).unwrap();
+    assert!(matches!(decoded, __StructibleValue_Session::Token(t) if t == "abc"));
+}
+
+#[tokio::test]
+async fn test_redis_round_trip() {
+    let Ok(url) = std::env::var("REDIS_URL") else {
+        return;
+    };
+    let client = redis::Client::open(url).unwrap();
+    let connection = client.get_multiplexed_async_connection().await.unwrap();
+    let hash = RedisHashMap::new(connection, "structible:test:session");
+
+    let mut session = Session::new(hash.clone(), 42).await.unwrap();
+    session.set_token("abc".into()).await.unwrap();
+    session
+        .insert_claims("role".into(), "admin".into())
+        .await
+        .unwrap();
+
+    let other = Session::from_backing(hash);
+    assert_eq!(other.user_id().await.unwrap(), Some(42));
+    assert_eq!(other.token().await.unwrap(), Some("abc".to_string()));
+    assert_eq!(
+        other.claims("role").await.unwrap(),
+        Some("admin".to_string())
+    );
+
+    assert_eq!(
+        session.remove_token().await.unwrap(),
+        Some("abc".to_string())
+    );
+    assert_eq!(other.token().await.unwrap(), None);
+    session.remove_claims("role").await.unwrap();
+}
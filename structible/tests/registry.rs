@@ -0,0 +1,73 @@
+//! Tests for the `inventory`-backed registry of structs.
+
+#![cfg(feature = "inventory")]
+
+use std::path::Path;
+
+use structible::golden::schema_json;
+use structible::registry::{self, Registration};
+use structible::structible;
+
+#[structible]
+pub struct Person {
+    pub name: String,
+    pub age: Option<u32>,
+}
+
+mod orders {
+    #[structible::structible(backing = std::collections::BTreeMap)]
+    pub struct Order {
+        pub id: u64,
+    }
+}
+
+#[structible]
+pub struct Wrapper<T: Clone + std::fmt::Debug + PartialEq> {
+    pub value: T,
+}
+
+#[structible(minimal)]
+pub struct Small {
+    pub value: u8,
+}
+
+#[test]
+fn test_registered_structs() {
+    let structs = registry::structs();
+    let names: Vec<_> = structs.iter().map(|r| (r.module_path, r.name)).collect();
+    // `Local` is declared in a test below; generic and minimal structs aren't registered
+    assert_eq!(
+        names,
+        [
+            ("registry", "Local"),
+            ("registry", "Person"),
+            ("registry::orders", "Order")
+        ]
+    );
+    assert_eq!(structs[1].fields[1].name, "age");
+    assert!(structs[1].fields[1].optional);
+}
+
+#[test]
+fn test_struct_declared_in_function_is_registered() {
+    #[structible]
+    #[allow(dead_code)]
+    struct Local {
+        value: u8,
+    }
+
+    let local: Option<&Registration> = registry::structs().into_iter().find(|r| r.name == "Local");
+    assert_eq!(local.unwrap().fields[0].ty, "u8");
+}
+
+#[test]
+fn test_emit_registered_schemas() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("registered_schemas");
+    let _ = std::fs::remove_dir_all(&dir);
+
+    structible::emit_schemas!(&dir).unwrap();
+    let person = std::fs::read_to_string(dir.join("Person.json")).unwrap();
+    assert_eq!(person, schema_json::<Person>());
+    assert!(dir.join("Order.json").exists());
+    assert!(!dir.join("Wrapper.json").exists());
+}
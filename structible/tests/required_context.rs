@@ -0,0 +1,75 @@
+//! Tests for the `<getter>_or_err()` methods of fields declaring `required_context`.
+
+use std::rc::{Rc, Weak};
+
+use structible::{ValidationError, structible};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cents(u64);
+
+impl From<Cents> for u64 {
+    fn from(cents: Cents) -> Self {
+        cents.0
+    }
+}
+
+impl From<u64> for Cents {
+    fn from(value: u64) -> Self {
+        Cents(value)
+    }
+}
+
+#[structible]
+pub struct Customer {
+    pub name: String,
+    #[structible(required_context = "an email is needed to send the invoice")]
+    pub email: Option<String>,
+    #[structible(get = balance, transparent = u64, required_context = "the account was never billed")]
+    pub amount_due: Option<Cents>,
+    #[structible(skip_eq, required_context = "the customer was referred")]
+    pub referrer: Option<Weak<String>>,
+}
+
+#[test]
+fn test_present_value() {
+    let mut customer = Customer::new("Alice".into());
+    customer.set_email("alice@example.com".into());
+    assert_eq!(
+        customer.email_or_err(),
+        Ok(&"alice@example.com".to_string())
+    );
+}
+
+#[test]
+fn test_absent_value() {
+    let customer = Customer::new("Alice".into());
+    let err = customer.email_or_err().unwrap_err();
+    assert_eq!(
+        err,
+        ValidationError::Absent {
+            field: "email",
+            context: "an email is needed to send the invoice",
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "field `email` is absent: an email is needed to send the invoice"
+    );
+}
+
+#[test]
+fn test_follows_getter() {
+    let mut customer = Customer::new("Alice".into());
+    assert!(customer.balance_or_err().is_err());
+    customer.set_amount_due(250);
+    assert_eq!(customer.balance_or_err(), Ok(250));
+
+    let referrer = Rc::new("Bob".to_string());
+    customer.set_referrer(Rc::downgrade(&referrer));
+    assert_eq!(customer.referrer_or_err(), Ok(referrer.clone()));
+    drop(referrer);
+    assert_eq!(
+        customer.referrer_or_err().unwrap_err().to_string(),
+        "field `referrer` is absent: the customer was referred"
+    );
+}
@@ -0,0 +1,70 @@
+//! Tests for `sample_partial()`, generated by `#[structible(example, sample)]`.
+
+#![cfg(feature = "rand")]
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use structible::structible;
+
+#[structible(example, sample, with_len)]
+pub struct Person {
+    #[structible(example = "Alice".into())]
+    pub name: String,
+    pub age: u32,
+    #[structible(example = "ally".into())]
+    pub nickname: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub score: Option<u64>,
+    #[structible(key = String)]
+    pub extra: Option<String>,
+}
+
+#[test]
+fn test_required_fields_hold_examples() {
+    let person = Person::sample_partial(&mut StdRng::seed_from_u64(0), 0.5);
+    assert_eq!(person.name(), "Alice");
+    assert_eq!(*person.age(), 0);
+    assert_eq!(person.extra_iter().count(), 0);
+}
+
+#[test]
+fn test_density_bounds() {
+    let mut rng = StdRng::seed_from_u64(1);
+    assert_eq!(Person::sample_partial(&mut rng, 0.0).len(), 2);
+
+    let full = Person::sample_partial(&mut rng, 1.0);
+    assert_eq!(full.len(), 6);
+    assert_eq!(full.nickname().map(String::as_str), Some("ally"));
+    assert_eq!(full.email().map(String::as_str), Some(""));
+    assert_eq!(full.score(), Some(&0));
+}
+
+#[test]
+fn test_density_controls_presence() {
+    let mut rng = StdRng::seed_from_u64(2);
+    let present: usize = (0..1000)
+        .map(|_| Person::sample_partial(&mut rng, 0.25).len() - 2)
+        .sum();
+    // 4 optional fields with probability 0.25 each, over 1000 samples
+    assert!((800..1200).contains(&present), "{}", present);
+}
+
+#[test]
+#[should_panic]
+fn test_density_out_of_range_panics() {
+    Person::sample_partial(&mut StdRng::seed_from_u64(3), 1.5);
+}
+
+#[structible(example, sample)]
+pub struct Tagged<T: Clone + std::fmt::Debug + PartialEq + Default> {
+    pub tag: T,
+    pub extra_tag: Option<T>,
+}
+
+#[test]
+fn test_generic_fields_use_defaults() {
+    let tagged = Tagged::<u8>::sample_partial(&mut StdRng::seed_from_u64(4), 1.0);
+    assert_eq!(tagged.tag(), &0);
+    assert_eq!(tagged.extra_tag(), Some(&0));
+}
@@ -0,0 +1,103 @@
+//! Tests for the `take_<field>_scoped()` guards of required fields.
+
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+use structible::{FieldChange, structible};
+
+#[structible]
+pub struct Document {
+    pub title: String,
+    pub body: Vec<String>,
+    pub author: Option<String>,
+}
+
+fn document() -> Document {
+    Document::new("Draft".into(), vec!["a".into(), "b".into()])
+}
+
+#[test]
+fn test_value_is_put_back() {
+    let mut doc = document();
+    {
+        let mut body = doc.take_body_scoped();
+        let owned: Vec<String> = std::mem::take(&mut *body);
+        *body = owned.into_iter().rev().collect();
+    }
+    assert_eq!(doc.body(), &vec!["b".to_string(), "a".to_string()]);
+    assert_eq!(doc.title(), "Draft");
+}
+
+#[test]
+fn test_replace() {
+    let mut doc = document();
+    let mut title = doc.take_title_scoped();
+    assert_eq!(title.replace("Final".into()), "Draft");
+    assert_eq!(*title, "Final");
+    drop(title);
+    assert_eq!(doc.title(), "Final");
+}
+
+#[test]
+fn test_into_inner() {
+    let mut doc = document();
+    let body = doc.take_body_scoped().into_inner(vec!["c".into()]);
+    assert_eq!(body, vec!["a".to_string(), "b".to_string()]);
+    assert_eq!(doc.body(), &vec!["c".to_string()]);
+}
+
+#[test]
+fn test_value_is_put_back_on_panic() {
+    let mut doc = document();
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let mut title = doc.take_title_scoped();
+        title.push_str(" (edited)");
+        panic!("algorithm failed");
+    }));
+    assert!(result.is_err());
+    assert_eq!(doc.title(), "Draft (edited)");
+}
+
+#[structible(track_changes)]
+pub struct Tracked {
+    pub count: u32,
+}
+
+#[test]
+fn test_put_back_is_recorded_as_set() {
+    let mut tracked = Tracked::new(1);
+    tracked.take_change_log();
+    *tracked.take_count_scoped() += 1;
+    assert_eq!(
+        tracked.take_change_log(),
+        vec![FieldChange::Set(
+            TrackedField::Count,
+            TrackedValue::Count(2)
+        )]
+    );
+}
+
+#[test]
+fn test_into_inner_records_the_replacement() {
+    let mut tracked = Tracked::new(1);
+    tracked.take_change_log();
+    assert_eq!(tracked.take_count_scoped().into_inner(5), 1);
+    assert_eq!(
+        tracked.take_change_log(),
+        vec![FieldChange::Set(
+            TrackedField::Count,
+            TrackedValue::Count(5)
+        )]
+    );
+}
+
+#[structible]
+pub struct Wrapper<T> {
+    pub inner: T,
+}
+
+#[test]
+fn test_generic_field() {
+    let mut wrapper = Wrapper::new(Box::new(5));
+    **wrapper.take_inner_scoped() *= 2;
+    assert_eq!(**wrapper.inner(), 10);
+}
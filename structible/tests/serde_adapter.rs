@@ -0,0 +1,123 @@
+//! Tests for the `serde_adapter` module and `Structible::try_from_backing`.
+#![cfg(feature = "serde")]
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+use structible::{Structible, ValidationError, structible};
+
+#[structible(backing = BTreeMap, serde_backing)]
+pub struct Person {
+    pub name: String,
+    pub age: Option<u32>,
+    #[structible(key = String)]
+    pub extra: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Team {
+    #[serde(with = "structible::serde_adapter")]
+    lead: Person,
+}
+
+#[test]
+fn test_round_trip() {
+    let mut lead = Person::new("Alice".into());
+    lead.set_age(30);
+    lead.insert_extra("role".into(), "admin".into());
+
+    let json = serde_json::to_string(&Team { lead }).unwrap();
+    assert_eq!(
+        json,
+        r#"{"lead":[["name",{"name":"Alice"}],["age",{"age":30}],[{"extra":"role"},{"extra":"admin"}]]}"#
+    );
+
+    let team: Team = serde_json::from_str(&json).unwrap();
+    assert_eq!(team.lead.name(), "Alice");
+    assert_eq!(team.lead.age(), Some(&30));
+    assert_eq!(team.lead.extra("role"), Some(&"admin".to_string()));
+}
+
+#[test]
+fn test_missing_required_field() {
+    let err = serde_json::from_str::<Team>(r#"{"lead":[["age",{"age":30}]]}"#)
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("required field `name` is missing"));
+}
+
+#[test]
+fn test_mismatched_entry() {
+    let err = serde_json::from_str::<Team>(r#"{"lead":[["name",{"age":30}]]}"#)
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("another field"));
+}
+
+#[test]
+fn test_duplicate_field() {
+    let json = r#"{"lead":[["name",{"name":"Alice"}],["name",{"name":"Bob"}]]}"#;
+    let err = serde_json::from_str::<Team>(json).err().unwrap();
+    assert!(err.to_string().contains("duplicate field"));
+}
+
+#[test]
+fn test_try_from_backing() {
+    let person = Person::new("Alice".into());
+    let person = Person::try_from_backing(person.into_backing()).unwrap();
+    assert_eq!(person.name(), "Alice");
+
+    assert!(matches!(
+        Person::try_from_backing(BTreeMap::new()),
+        Err(ValidationError::MissingRequired { field: "name" })
+    ));
+}
+
+#[structible(serde_backing, track_changes)]
+pub struct Settings<T> {
+    pub theme: Option<T>,
+}
+
+#[test]
+fn test_generic_struct() {
+    let mut settings = Settings::new();
+    settings.set_theme("dark".to_string());
+    let json =
+        structible::serde_adapter::serialize(&settings, serde_json::value::Serializer).unwrap();
+
+    let decoded: Settings<String> = structible::serde_adapter::deserialize(json).unwrap();
+    assert_eq!(decoded.theme(), Some(&"dark".to_string()));
+    assert!(decoded.change_log().is_empty());
+    let _: HashMap<_, _> = decoded.into_backing();
+}
+
+/// A sequence of no elements claiming to have `usize::MAX`.
+struct LyingSeq;
+
+impl<'de> serde::de::SeqAccess<'de> for LyingSeq {
+    type Error = serde::de::value::Error;
+
+    fn next_element_seed<S: serde::de::DeserializeSeed<'de>>(
+        &mut self,
+        _seed: S,
+    ) -> Result<Option<S::Value>, Self::Error> {
+        Ok(None)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(usize::MAX)
+    }
+}
+
+#[structible(serde_backing)]
+pub struct Tags {
+    pub first: Option<String>,
+    pub second: Option<String>,
+}
+
+#[test]
+fn test_size_hint_is_not_trusted() {
+    let deserializer = serde::de::value::SeqAccessDeserializer::new(LyingSeq);
+    let tags: Tags = structible::serde_adapter::deserialize(deserializer).unwrap();
+    assert_eq!(tags, Tags::default());
+}
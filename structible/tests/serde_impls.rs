@@ -0,0 +1,132 @@
+//! Tests for `#[structible(serde)]`.
+#![cfg(feature = "serde")]
+
+use std::collections::BTreeMap;
+
+use serde_json::json;
+use structible::{Structible, structible};
+
+#[structible(serde)]
+pub struct Person {
+    pub name: String,
+    pub r#type: Option<String>,
+    pub age: Option<u32>,
+}
+
+#[test]
+fn test_absent_optionals_are_omitted() {
+    let mut person = Person::new("Alice".into());
+    assert_eq!(
+        serde_json::to_value(&person).unwrap(),
+        json!({ "name": "Alice" })
+    );
+    person.set_type("admin".into());
+    assert_eq!(
+        serde_json::to_value(&person).unwrap(),
+        json!({ "name": "Alice", "type": "admin" })
+    );
+}
+
+#[test]
+fn test_roundtrip() {
+    let mut person = Person::new("Alice".into());
+    person.set_age(30);
+    let json = serde_json::to_string(&person).unwrap();
+    let decoded: Person = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded, person);
+}
+
+#[test]
+fn test_missing_optionals_are_absent() {
+    let person: Person = serde_json::from_str(r#"{"name":"Bob"}"#).unwrap();
+    assert_eq!(person.name(), "Bob");
+    assert_eq!(person.age(), None);
+    assert_eq!(person.backing().len(), 1);
+}
+
+#[test]
+fn test_null_optionals_are_absent() {
+    let person: Person = serde_json::from_str(r#"{"name":"x","age":null}"#).unwrap();
+    assert_eq!(person.age(), None);
+    assert_eq!(person.backing().len(), 1);
+}
+
+#[test]
+fn test_decoding_errors() {
+    let err = serde_json::from_str::<Person>(r#"{"age":3}"#).unwrap_err();
+    assert!(err.to_string().contains("missing field `name`"), "{err}");
+
+    let err = serde_json::from_str::<Person>(r#"{"name":"Bob","nickname":"B"}"#).unwrap_err();
+    assert!(
+        err.to_string().contains("unknown field `nickname`"),
+        "{err}"
+    );
+
+    let err = serde_json::from_str::<Person>(r#"{"name":"Bob","name":"Rob"}"#).unwrap_err();
+    assert!(err.to_string().contains("duplicate field `name`"), "{err}");
+
+    assert!(serde_json::from_str::<Person>(r#"{"name":1}"#).is_err());
+}
+
+#[structible(serde, backing = BTreeMap)]
+pub struct Event<T> {
+    pub title: String,
+    pub payload: Option<T>,
+    #[structible(key = String)]
+    pub extra: Option<u32>,
+}
+
+#[test]
+fn test_generic_btree_backing_with_catch_all() {
+    let mut event = Event::new("launch".into());
+    event.set_payload(vec![1, 2]);
+    event.insert_extra("b".into(), 2);
+    event.insert_extra("a".into(), 1);
+
+    let value = serde_json::to_value(&event).unwrap();
+    assert_eq!(
+        value,
+        json!({ "title": "launch", "payload": [1, 2], "a": 1, "b": 2 })
+    );
+    let decoded: Event<Vec<u8>> = serde_json::from_value(value).unwrap();
+    assert_eq!(decoded, event);
+}
+
+#[structible(serde)]
+pub struct Location {
+    pub name: Option<String>,
+    pub coordinates: Option<String>,
+    #[structible(key = String)]
+    pub vendor_extensions: Option<serde_json::Value>,
+}
+
+#[test]
+fn test_unknown_keys_roundtrip_through_catch_all() {
+    let json = json!({
+        "name": "Office",
+        "example.com:floor": 3,
+        "example.com:badge": { "required": true },
+    });
+    let location: Location = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(location.name().map(String::as_str), Some("Office"));
+    assert_eq!(
+        location.vendor_extensions("example.com:floor"),
+        Some(&json!(3))
+    );
+    assert_eq!(serde_json::to_value(&location).unwrap(), json);
+
+    let err = serde_json::from_str::<Location>(r#"{"x":1,"x":2}"#).unwrap_err();
+    assert!(err.to_string().contains("duplicate field `x`"), "{err}");
+}
+
+#[test]
+fn test_catch_all_key_naming_a_field_fails_to_serialize() {
+    let mut location = Location::new();
+    location.insert_vendor_extensions("name".into(), json!("Shadow"));
+    let err = serde_json::to_value(&location).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("catch-all key `name` collides with a field"),
+        "{err}"
+    );
+}
@@ -0,0 +1,86 @@
+//! Tests for `#[structible(skip_clone)]` and `#[structible(skip_eq)]`.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+
+use structible::structible;
+
+/// A cache that can't be cloned or compared.
+#[derive(Debug, Default)]
+pub struct Cache(pub Mutex<Vec<u32>>);
+
+#[structible]
+pub struct Record {
+    pub name: String,
+    #[structible(skip_clone, skip_eq)]
+    pub cache: Option<Cache>,
+    #[structible(skip_clone, skip_eq)]
+    pub hits: Cache,
+    #[structible(skip_eq)]
+    pub seen_at: Option<u64>,
+}
+
+#[structible(freeze)]
+pub struct Label {
+    pub text: String,
+    #[structible(skip_eq)]
+    pub revision: Option<u32>,
+    #[structible(key = String, skip_eq)]
+    pub extra: Option<String>,
+}
+
+fn hash_of(value: &impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[test]
+fn test_skip_clone() {
+    let mut record = Record::new("a".into(), Cache::default());
+    record.set_cache(Cache::default());
+    record.hits().0.lock().unwrap().push(1);
+    record.set_seen_at(5);
+
+    let copy = record.clone();
+    assert_eq!(copy.name(), "a");
+    assert!(copy.cache().is_none());
+    assert!(copy.hits().0.lock().unwrap().is_empty());
+    assert_eq!(copy.seen_at(), Some(&5));
+}
+
+#[test]
+fn test_skip_eq_ignores_presence_and_value() {
+    let mut a = Record::new("a".into(), Cache::default());
+    let b = Record::new("a".into(), Cache::default());
+    a.set_cache(Cache::default());
+    a.set_seen_at(5);
+    assert_eq!(a, b);
+
+    a.set_name("b".into());
+    assert_ne!(a, b);
+}
+
+#[test]
+fn test_skip_eq_catch_all() {
+    let mut a = Label::new("a".into());
+    let mut b = Label::new("a".into());
+    a.insert_extra("lang".into(), "en".into());
+    a.set_revision(2);
+    assert_eq!(a, b);
+
+    b.set_text("b".into());
+    assert_ne!(a, b);
+}
+
+#[test]
+fn test_frozen_skip_eq() {
+    let mut a = Label::new("a".into());
+    a.set_revision(1);
+    a.insert_extra("lang".into(), "en".into());
+    let a = a.freeze();
+    let b = Label::new("a".into()).freeze();
+    assert_eq!(a, b);
+    assert_eq!(hash_of(&a), hash_of(&b));
+}
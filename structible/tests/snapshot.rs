@@ -0,0 +1,71 @@
+//! Tests for `field_paths()` and `#[structible(test_serialize)]`.
+#![cfg(feature = "serde")]
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use serde_json::json;
+use structible::structible;
+
+#[structible(test_serialize, backing = BTreeMap)]
+pub struct Account {
+    pub id: u64,
+    pub r#type: Option<String>,
+    pub secret: Option<String>,
+    #[structible(key = String)]
+    pub labels: Option<u32>,
+}
+
+#[test]
+fn test_field_paths() {
+    assert_eq!(Account::field_paths(), &["id", "type", "secret", "labels"]);
+}
+
+#[test]
+fn test_serialize_present_fields() {
+    let mut account = Account::new(7);
+    account.set_secret("hunter2".into());
+    account.insert_labels("b".into(), 2);
+    account.insert_labels("a".into(), 1);
+
+    assert_eq!(
+        serde_json::to_value(&account).unwrap(),
+        json!({ "id": 7, "secret": "hunter2", "labels": { "a": 1, "b": 2 } })
+    );
+    assert_eq!(
+        serde_json::to_value(Account::new(1)).unwrap(),
+        json!({ "id": 1 })
+    );
+}
+
+#[test]
+fn test_paths_select_serialized_keys() {
+    let mut account = Account::new(7);
+    account.set_type("admin".into());
+    account.set_secret("hunter2".into());
+    account.insert_labels("a".into(), 1);
+
+    let value = serde_json::to_value(&account).unwrap();
+    let object = value.as_object().unwrap();
+    for key in object.keys() {
+        assert!(Account::field_paths().contains(&key.as_str()));
+    }
+    assert_eq!(object.len(), Account::field_paths().len());
+}
+
+#[structible(test_serialize, ttl)]
+pub struct Session<T> {
+    pub token: Option<T>,
+}
+
+#[test]
+fn test_generic_and_expired() {
+    let mut session = Session::default();
+    session.set_token_with_ttl("abc", Duration::ZERO);
+    assert_eq!(serde_json::to_string(&session).unwrap(), "{}");
+    session.set_token("def");
+    assert_eq!(
+        serde_json::to_string(&session).unwrap(),
+        r#"{"token":"def"}"#
+    );
+}
@@ -0,0 +1,37 @@
+//! Tests for the `append_`, `clear_`, and `_str` helpers of `string` fields.
+
+use structible::structible;
+
+#[structible]
+pub struct Message {
+    #[structible(string)]
+    pub body: String,
+    #[structible(string, set = rename)]
+    pub subject: Option<String>,
+}
+
+#[test]
+fn test_required_string() {
+    let mut message = Message::new("Hello".into());
+    message.append_body(", world");
+    assert_eq!(message.body(), "Hello, world");
+    message.clear_body();
+    assert_eq!(message.body(), "");
+}
+
+#[test]
+fn test_optional_string() {
+    let mut message = Message::new(String::new());
+    message.clear_subject();
+    assert_eq!(message.subject(), None);
+
+    message.append_subject("Re: ");
+    message.append_subject("lunch");
+    assert_eq!(message.subject().map(String::as_str), Some("Re: lunch"));
+
+    message.clear_subject();
+    assert_eq!(message.subject().map(String::as_str), Some(""));
+
+    message.rename_str("Fwd: lunch");
+    assert_eq!(message.subject().map(String::as_str), Some("Fwd: lunch"));
+}
@@ -0,0 +1,136 @@
+//! Tests for the generated `Structible` impls.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Debug;
+
+use structible::{IterableMap, Structible, structible};
+
+#[structible]
+pub struct Person {
+    pub name: String,
+    pub email: Option<String>,
+}
+
+#[structible(backing = BTreeMap)]
+pub struct Point<T: Ord> {
+    pub x: T,
+    pub y: T,
+    #[structible(key = String)]
+    pub labels: Option<T>,
+}
+
+#[structible(track_changes)]
+pub struct Tracked {
+    pub id: u32,
+}
+
+/// A blanket impl over all structible structs.
+trait Describe {
+    fn describe(&self) -> Vec<String>;
+}
+
+impl<T> Describe for T
+where
+    T: Structible,
+    T::Backing: IterableMap<T::Field, T::Value>,
+    T::Field: Debug + Ord,
+{
+    fn describe(&self) -> Vec<String> {
+        let mut keys: Vec<_> = IterableMap::iter(self.backing()).map(|(k, _)| k).collect();
+        keys.sort();
+        keys.iter().map(|k| format!("{:?}", k)).collect()
+    }
+}
+
+fn assert_backing<T, B>()
+where
+    T: Structible<Backing = B>,
+{
+}
+
+#[test]
+fn test_associated_types() {
+    assert_backing::<Person, HashMap<<Person as Structible>::Field, <Person as Structible>::Value>>(
+    );
+    assert_backing::<
+        Point<i32>,
+        BTreeMap<<Point<i32> as Structible>::Field, <Point<i32> as Structible>::Value>,
+    >();
+}
+
+#[test]
+fn test_aliases_name_the_same_types() {
+    let key: <Tracked as Structible>::Field = TrackedField::Id;
+    let value: <Tracked as Structible>::Value = TrackedValue::Id(1);
+    let tracked = Tracked::new(1);
+    assert_eq!(tracked.backing().get(&key), Some(&value));
+}
+
+#[test]
+fn test_backing_access() {
+    let mut person = Person::new("Alice".into());
+    assert_eq!(person.backing().len(), 1);
+    person.set_email("alice@example.com".into());
+    assert_eq!(person.backing().len(), 2);
+
+    let backing = person.into_backing();
+    assert_eq!(backing.len(), 2);
+}
+
+#[test]
+fn test_blanket_impl() {
+    let mut point = Point::new(1, 2);
+    point.insert_labels("origin".into(), 0);
+    assert_eq!(point.describe(), vec!["X", "Y", "Unknown(\"origin\")"]);
+    assert_eq!(Tracked::new(7).describe(), vec!["Id"]);
+}
+
+mod private_backing {
+    use std::collections::BTreeMap;
+
+    use structible::{BackingMap, structible};
+
+    // Implementing `Structible` would leak this private type through `Backing`
+    #[derive(Debug, Clone, PartialEq)]
+    struct PrivateMap<K, V>(BTreeMap<K, V>);
+
+    impl<K: Ord, V> BackingMap<K, V> for PrivateMap<K, V> {
+        fn new() -> Self {
+            Self(BTreeMap::new())
+        }
+
+        fn insert(&mut self, key: K, value: V) -> Option<V> {
+            self.0.insert(key, value)
+        }
+
+        fn get(&self, key: &K) -> Option<&V> {
+            self.0.get(key)
+        }
+
+        fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+            self.0.get_mut(key)
+        }
+
+        fn remove(&mut self, key: &K) -> Option<V> {
+            self.0.remove(key)
+        }
+
+        fn len(&self) -> usize {
+            self.0.len()
+        }
+
+        fn is_empty(&self) -> bool {
+            self.0.is_empty()
+        }
+    }
+
+    #[structible(backing = PrivateMap, no_structible)]
+    pub struct Secret {
+        pub value: String,
+    }
+
+    #[test]
+    fn test_no_structible() {
+        assert_eq!(Secret::new("hidden".into()).value(), "hidden");
+    }
+}
@@ -0,0 +1,87 @@
+//! Tests for `#[structible(timestamps)]` per-field write times.
+
+use std::cell::Cell;
+use std::time::Instant;
+
+use structible::{Clock, structible};
+
+thread_local! {
+    static TICKS: Cell<u64> = const { Cell::new(0) };
+}
+
+/// A clock that advances by one on every reading.
+pub struct TickClock;
+
+impl Clock for TickClock {
+    type Instant = u64;
+
+    fn now() -> u64 {
+        TICKS.with(|ticks| {
+            ticks.set(ticks.get() + 1);
+            ticks.get()
+        })
+    }
+}
+
+#[structible(timestamps)]
+pub struct Document {
+    pub title: String,
+    pub body: Option<String>,
+}
+
+#[structible(timestamps, clock = TickClock)]
+pub struct Entry {
+    pub name: String,
+    pub note: Option<String>,
+    pub tag: Option<String>,
+}
+
+#[test]
+fn test_writes_record_time() {
+    let before = Instant::now();
+    let mut doc = Document::new("Draft".into());
+    assert!(doc.title_modified_at().is_some_and(|at| at >= before));
+    assert_eq!(doc.body_modified_at(), None);
+
+    doc.set_body("Hello".into());
+    let set_at = doc.body_modified_at().unwrap();
+    assert!(set_at >= before);
+
+    doc.remove_body();
+    assert!(doc.body_modified_at().unwrap() >= set_at);
+}
+
+#[test]
+fn test_custom_clock() {
+    let mut entry = Entry::new("a".into());
+    let created = entry.name_modified_at().unwrap();
+
+    entry.set_note("first".into());
+    entry.set_tag("x".into());
+    assert_eq!(entry.note_modified_at(), Some(created + 1));
+    assert_eq!(entry.tag_modified_at(), Some(created + 2));
+
+    entry.set_note("second".into());
+    assert_eq!(entry.note_modified_at(), Some(created + 3));
+}
+
+#[test]
+fn test_removing_absent_field_is_not_a_write() {
+    let mut entry = Entry::new("a".into());
+    entry.remove_note();
+    assert_eq!(entry.note_modified_at(), None);
+}
+
+#[test]
+fn test_timestamps_cloned_but_not_compared() {
+    let mut entry = Entry::new("a".into());
+    entry.set_note("n".into());
+
+    let copy = entry.clone();
+    assert_eq!(copy.note_modified_at(), entry.note_modified_at());
+
+    let mut other = Entry::new("a".into());
+    other.set_note("n".into());
+    assert_ne!(other.note_modified_at(), entry.note_modified_at());
+    assert_eq!(other, entry);
+}
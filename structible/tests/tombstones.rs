@@ -0,0 +1,119 @@
+//! Tests for `#[structible(tombstones)]` recording explicitly cleared fields.
+
+use structible::{Structible, structible};
+
+#[structible(tombstones, track_changes)]
+pub struct Contact {
+    pub name: String,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    #[structible(key = String)]
+    pub extra: Option<String>,
+}
+
+#[structible(tombstones, ttl, freeze)]
+pub struct Session {
+    pub user: String,
+    pub token: Option<String>,
+}
+
+#[test]
+fn test_remover_records_tombstone() {
+    let mut contact = Contact::new("Alice".into());
+    contact.set_email("alice@example.com".into());
+    assert!(!contact.was_explicitly_cleared_email());
+
+    assert_eq!(contact.remove_email(), Some("alice@example.com".into()));
+    assert!(contact.was_explicitly_cleared_email());
+    assert!(!contact.was_explicitly_cleared_phone());
+    assert_eq!(contact.email(), None);
+}
+
+#[test]
+fn test_clearing_absent_field_records_tombstone() {
+    let mut contact = Contact::new("Alice".into());
+    assert_eq!(contact.remove_phone(), None);
+    assert!(contact.was_explicitly_cleared_phone());
+}
+
+#[test]
+fn test_set_clears_tombstone() {
+    let mut contact = Contact::new("Alice".into());
+    contact.remove_email();
+    contact.set_email("alice@example.com".into());
+    assert!(!contact.was_explicitly_cleared_email());
+}
+
+#[test]
+fn test_tombstones_in_declaration_order() {
+    let mut contact = Contact::new("Alice".into());
+    contact.remove_phone();
+    contact.remove_email();
+    assert_eq!(
+        contact.tombstones(),
+        vec![ContactField::Email, ContactField::Phone]
+    );
+}
+
+#[test]
+fn test_purge_tombstones() {
+    let mut contact = Contact::new("Alice".into());
+    contact.remove_email();
+    contact.remove_phone();
+
+    assert_eq!(contact.purge_tombstones(), 2);
+    assert!(contact.tombstones().is_empty());
+    assert!(!contact.was_explicitly_cleared_email());
+    assert_eq!(contact.purge_tombstones(), 0);
+}
+
+#[test]
+fn test_tombstones_cloned_but_not_compared() {
+    let mut contact = Contact::new("Alice".into());
+    contact.remove_email();
+
+    let copy = contact.clone();
+    assert!(copy.was_explicitly_cleared_email());
+    assert_eq!(contact, Contact::new("Alice".into()));
+}
+
+#[test]
+fn test_rebuilt_struct_has_no_tombstones() {
+    let mut contact = Contact::new("Alice".into());
+    contact.remove_email();
+
+    let rebuilt = Contact::try_from_backing(contact.into_backing()).unwrap();
+    assert!(rebuilt.tombstones().is_empty());
+}
+
+#[test]
+fn test_expiry_leaves_no_tombstone() {
+    let mut session = Session::new("alice".into());
+    session.set_token_with_ttl("secret".into(), std::time::Duration::ZERO);
+    assert_eq!(session.purge_expired(), 1);
+    assert!(!session.was_explicitly_cleared_token());
+
+    session.remove_token();
+    assert!(session.was_explicitly_cleared_token());
+    let thawed = session.freeze().thaw();
+    assert!(!thawed.was_explicitly_cleared_token());
+}
+
+#[structible(tombstones, lww)]
+pub struct Replica {
+    pub name: String,
+    pub email: Option<String>,
+}
+
+#[test]
+fn test_merged_value_clears_tombstone() {
+    let mut ours = Replica::new("Alice".into()).with_actor_id(1);
+    let mut theirs = Replica::new("Alice".into()).with_actor_id(2);
+    ours.remove_email();
+    theirs.set_email("alice@example.com".into());
+    theirs.set_email("alice@example.org".into());
+
+    ours.merge(&theirs);
+    assert_eq!(ours.email(), Some(&"alice@example.org".to_string()));
+    assert!(!ours.was_explicitly_cleared_email());
+}
@@ -0,0 +1,211 @@
+//! Tests for `#[structible(trace)]` trace events.
+
+#![cfg(feature = "tracing")]
+
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+
+use structible::structible;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+#[structible(trace)]
+pub struct Account {
+    pub owner: String,
+    pub balance: Option<i64>,
+    #[structible(redact)]
+    pub password: Option<String>,
+    #[structible(key = String)]
+    pub extra: Option<String>,
+}
+
+#[structible]
+pub struct Plain {
+    pub name: String,
+}
+
+type Fields = BTreeMap<String, String>;
+
+/// A subscriber recording the fields of every event.
+#[derive(Clone, Default)]
+struct Recorder(Arc<Mutex<Vec<Fields>>>);
+
+/// A visitor collecting an event's fields as strings.
+struct Visitor<'a>(&'a mut Fields);
+
+impl Visit for Visitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{:?}", value));
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, _: &Attributes<'_>) -> Id {
+        Id::from_u64(1)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        assert_eq!(event.metadata().target(), "structible");
+        let mut fields = Fields::new();
+        event.record(&mut Visitor(&mut fields));
+        self.0.lock().unwrap().push(fields);
+    }
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+/// Runs `f` with a recording subscriber, returning the fields of its events.
+fn record(f: impl FnOnce()) -> Vec<Fields> {
+    let recorder = Recorder::default();
+    tracing::subscriber::with_default(recorder.clone(), f);
+    recorder.0.lock().unwrap().clone()
+}
+
+fn event(pairs: &[(&str, &str)]) -> Fields {
+    pairs
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_setters_and_removers_are_traced() {
+    let mut account = Account::new("alice".into());
+    let events = record(|| {
+        account.set_balance(10);
+        account.remove_balance();
+        // Removing an absent field doesn't write
+        account.remove_balance();
+    });
+    assert_eq!(
+        events,
+        vec![
+            event(&[
+                ("message", "set"),
+                ("struct", "Account"),
+                ("field", "balance"),
+                ("value", "10"),
+            ]),
+            event(&[
+                ("message", "remove"),
+                ("struct", "Account"),
+                ("field", "balance"),
+                ("value", "10"),
+            ]),
+        ]
+    );
+}
+
+#[test]
+fn test_redacted_values() {
+    let mut account = Account::new("alice".into());
+    let events = record(|| account.set_password("hunter2".into()));
+    assert_eq!(events[0]["field"], "password");
+    assert_eq!(events[0]["value"], "<redacted>");
+}
+
+#[test]
+fn test_unknown_fields_carry_their_key() {
+    let mut account = Account::new("alice".into());
+    let events = record(|| {
+        account.insert_extra("plan".into(), "pro".into());
+    });
+    assert_eq!(
+        events,
+        vec![event(&[
+            ("message", "set"),
+            ("struct", "Account"),
+            ("field", "extra"),
+            ("key", r#""plan""#),
+            ("value", r#""pro""#),
+        ])]
+    );
+}
+
+#[test]
+fn test_untraced_structs_emit_nothing() {
+    let mut plain = Plain::new("alice".into());
+    let events = record(|| plain.set_name("bob".into()));
+    assert!(events.is_empty());
+}
+
+#[structible(trace)]
+pub struct Pair<T, S> {
+    pub left: T,
+    #[structible(redact)]
+    pub secret: Option<S>,
+}
+
+/// A type without a `Debug` impl, which is fine for redacted fields.
+#[derive(Clone, PartialEq)]
+pub struct Opaque;
+
+#[test]
+fn test_generic_struct() {
+    let mut pair = Pair::new(1u8);
+    let events = record(|| {
+        pair.set_left(2);
+        pair.set_secret(Opaque);
+    });
+    assert_eq!(events[0]["value"], "2");
+    assert_eq!(events[1]["value"], "<redacted>");
+}
+
+#[structible(record_fields)]
+pub struct Profile<T> {
+    pub name: String,
+    pub age: Option<T>,
+    #[structible(redact)]
+    pub token: Option<String>,
+    #[structible(key = String)]
+    pub extra: Option<u32>,
+}
+
+fn recorded<T: Debug>(profile: &Profile<T>) -> Fields {
+    let mut fields = Fields::new();
+    profile.record_fields(&mut Visitor(&mut fields));
+    fields
+}
+
+#[test]
+fn test_record_fields() {
+    let mut profile = Profile::<u32>::new("alice".into());
+    assert_eq!(recorded(&profile), event(&[("name", r#""alice""#)]));
+
+    profile.set_age(30);
+    profile.set_token("secret".into());
+    profile.insert_extra("b".into(), 2);
+    profile.insert_extra("a".into(), 1);
+    let fields = recorded(&profile);
+    assert_eq!(fields["age"], "30");
+    assert_eq!(fields["token"], "<redacted>");
+    assert!(fields["extra"].contains(r#""a": 1"#));
+    assert!(fields["extra"].contains(r#""b": 2"#));
+}
+
+#[test]
+fn test_record_fields_outside_events() {
+    // Recording doesn't go through the subscriber
+    let profile = Profile::<u32>::new("alice".into());
+    let events = record(|| {
+        recorded(&profile);
+    });
+    assert!(events.is_empty());
+}
@@ -0,0 +1,91 @@
+//! Tests for `Box<dyn Trait>` fields and `#[structible(no_eq)]`.
+
+use std::fmt::Debug;
+
+use structible::structible;
+
+pub trait Shape: Debug {
+    fn area(&self) -> f64;
+}
+
+#[derive(Debug)]
+pub struct Square(f64);
+
+impl Shape for Square {
+    fn area(&self) -> f64 {
+        self.0 * self.0
+    }
+}
+
+#[derive(Debug)]
+pub struct Circle(f64);
+
+impl Shape for Circle {
+    fn area(&self) -> f64 {
+        3.0 * self.0 * self.0
+    }
+}
+
+#[structible(no_clone)]
+pub struct Drawing {
+    pub name: String,
+    pub outline: Box<dyn Shape>,
+    #[structible(alias = fill_shape)]
+    pub fill: Option<Box<dyn Shape + Send>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Handle(pub u32);
+
+#[structible]
+pub struct Session {
+    pub user: String,
+    #[structible(no_eq)]
+    pub handle: Option<Handle>,
+}
+
+#[test]
+fn test_getters_borrow_trait_object() {
+    let mut drawing = Drawing::new("a".into(), Box::new(Square(2.0)));
+    let outline: &dyn Shape = drawing.outline();
+    assert_eq!(outline.area(), 4.0);
+    assert!(drawing.fill().is_none());
+
+    drawing.set_fill(Box::new(Circle(1.0)));
+    assert_eq!(drawing.fill().map(|s| s.area()), Some(3.0));
+
+    *drawing.outline_mut() = Box::new(Circle(2.0));
+    assert_eq!(drawing.outline().area(), 12.0);
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_alias_getter_borrows_trait_object() {
+    let mut drawing = Drawing::new("a".into(), Box::new(Square(2.0)));
+    drawing.set_fill(Box::new(Square(1.0)));
+    assert_eq!(drawing.fill_shape().map(|s| s.area()), Some(1.0));
+}
+
+#[test]
+fn test_trait_object_values_not_compared() {
+    let mut a = Drawing::new("a".into(), Box::new(Square(2.0)));
+    let b = Drawing::new("a".into(), Box::new(Circle(1.0)));
+    assert_eq!(a, b);
+
+    a.set_fill(Box::new(Square(1.0)));
+    assert_ne!(a, b);
+}
+
+#[test]
+fn test_no_eq_compares_presence() {
+    let mut a = Session::new("alice".into());
+    let mut b = a.clone();
+    a.set_handle(Handle(1));
+    assert_ne!(a, b);
+
+    b.set_handle(Handle(2));
+    assert_eq!(a, b);
+
+    b.set_user("bob".into());
+    assert_ne!(a, b);
+}
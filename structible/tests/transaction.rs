@@ -0,0 +1,122 @@
+//! Tests for `#[structible(transactional)]` and `transaction()`.
+
+use structible::{FieldChange, ValidationError, structible};
+
+fn check_range(range: &Range) -> Result<(), ValidationError> {
+    if range.start() < range.end() {
+        Ok(())
+    } else {
+        Err(ValidationError::invariant("start must precede end"))
+    }
+}
+
+#[structible(transactional, invariant = check_range)]
+pub struct Range {
+    pub start: u32,
+    pub end: u32,
+    pub label: Option<String>,
+    #[structible(conflicts_with = label)]
+    pub note: Option<String>,
+    #[structible(key = String)]
+    pub extra: Option<String>,
+}
+
+#[test]
+fn test_commit() {
+    let mut range = Range::new(1, 5);
+    let result: Result<(), ValidationError> = range.transaction(|tx| {
+        tx.set_start(2);
+        tx.set_end(8);
+        tx.validate()
+    });
+    assert!(result.is_ok());
+    assert_eq!((*range.start(), *range.end()), (2, 8));
+}
+
+#[test]
+fn test_rollback_restores_every_write() {
+    let mut range = Range::new(1, 5);
+    range.set_label("old".into());
+    range.insert_extra("kept".into(), "1".into());
+
+    let result = range.transaction(|tx| {
+        tx.set_end(10);
+        tx.set_end(0);
+        tx.remove_label();
+        tx.insert_extra("added".into(), "2".into());
+        tx.remove_extra("kept");
+        tx.validate()
+    });
+    assert_eq!(
+        result,
+        Err(ValidationError::invariant("start must precede end"))
+    );
+    assert_eq!(*range.end(), 5);
+    assert_eq!(range.label(), Some(&"old".to_string()));
+    assert_eq!(range.extra("kept"), Some(&"1".to_string()));
+    assert_eq!(range.extra("added"), None);
+}
+
+#[test]
+fn test_try_setters_in_transaction() {
+    let mut range = Range::new(1, 5);
+    let result = range.transaction(|tx| {
+        tx.set_label("label".into());
+        tx.try_set_note("note".into())?;
+        Ok::<_, ValidationError>("done")
+    });
+    assert!(result.is_err());
+    assert_eq!(range.label(), None);
+    assert_eq!(range.note(), None);
+}
+
+#[test]
+fn test_nested_transaction_rolls_back_its_own_writes() {
+    let mut range = Range::new(1, 5);
+    let result: Result<(), ()> = range.transaction(|tx| {
+        tx.set_label("outer".into());
+        let inner: Result<(), ()> = tx.transaction(|tx| {
+            tx.set_label("inner".into());
+            Err(())
+        });
+        assert!(inner.is_err());
+        assert_eq!(tx.label(), Some(&"outer".to_string()));
+        Ok(())
+    });
+    assert!(result.is_ok());
+    assert_eq!(range.label(), Some(&"outer".to_string()));
+
+    // Writes after a transaction are not logged for undo
+    range.set_label("after".into());
+    let _: Result<(), ()> = range.transaction(|_| Err(()));
+    assert_eq!(range.label(), Some(&"after".to_string()));
+}
+
+#[structible(transactional, track_changes)]
+pub struct Tracked<T> {
+    pub value: T,
+}
+
+#[test]
+fn test_rollback_truncates_change_log() {
+    let mut tracked = Tracked::new(1u8);
+    tracked.take_change_log();
+    let _: Result<(), ()> = tracked.transaction(|tx| {
+        tx.set_value(2);
+        Err(())
+    });
+    assert_eq!(*tracked.value(), 1);
+    assert!(tracked.change_log().is_empty());
+
+    let _: Result<(), ()> = tracked.transaction(|tx| {
+        tx.set_value(3);
+        Ok(())
+    });
+    assert_eq!(
+        tracked.take_change_log(),
+        vec![FieldChange::Set(
+            TrackedField::Value,
+            TrackedValue::Value(3)
+        )]
+    );
+}
@@ -0,0 +1,97 @@
+//! Tests for `#[structible(transparent = T)]` newtype fields.
+
+use structible::structible;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Meters(f64);
+
+impl From<f64> for Meters {
+    fn from(value: f64) -> Self {
+        Meters(value)
+    }
+}
+
+impl From<Meters> for f64 {
+    fn from(value: Meters) -> Self {
+        value.0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label(String);
+
+impl From<String> for Label {
+    fn from(value: String) -> Self {
+        Label(value)
+    }
+}
+
+impl From<&str> for Label {
+    fn from(value: &str) -> Self {
+        Label(value.to_string())
+    }
+}
+
+impl From<Label> for String {
+    fn from(value: Label) -> Self {
+        value.0
+    }
+}
+
+#[structible]
+pub struct Route {
+    #[structible(transparent = f64)]
+    pub distance: Meters,
+    #[structible(transparent = f64)]
+    pub climb: Option<Meters>,
+    #[structible(transparent = String, alias = title)]
+    pub label: Option<Label>,
+}
+
+#[test]
+fn test_getters_convert() {
+    let mut route = Route::new(1200.0);
+    assert_eq!(route.distance(), 1200.0);
+    assert_eq!(route.climb(), None);
+
+    route.set_climb(85.5);
+    assert_eq!(route.climb(), Some(85.5));
+}
+
+#[test]
+fn test_newtype_getters() {
+    let mut route = Route::new(Meters(1200.0));
+    route.set_climb(Meters(85.5));
+    assert_eq!(route.distance_newtype(), &Meters(1200.0));
+    assert_eq!(route.climb_newtype(), Some(&Meters(85.5)));
+}
+
+#[test]
+fn test_setters_accept_conversions() {
+    let mut route = Route::new(1200.0);
+    route.set_distance(1500.0);
+    route.set_label("Ridge");
+    assert_eq!(route.distance(), 1500.0);
+    assert_eq!(route.label(), Some("Ridge".to_string()));
+
+    route.set_label(String::from("Valley"));
+    assert_eq!(route.label_newtype(), Some(&Label("Valley".into())));
+}
+
+#[test]
+fn test_other_accessors_use_newtype() {
+    let mut route = Route::new(1200.0);
+    *route.distance_mut() = Meters(900.0);
+    assert_eq!(route.distance(), 900.0);
+
+    route.set_climb(85.5);
+    assert_eq!(route.remove_climb(), Some(Meters(85.5)));
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_alias_accessors_convert() {
+    let mut route = Route::new(1200.0);
+    route.set_title("Ridge");
+    assert_eq!(route.title(), Some("Ridge".to_string()));
+}
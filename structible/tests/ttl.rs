@@ -0,0 +1,145 @@
+//! Tests for `#[structible(ttl)]` expiring optional fields.
+
+use std::time::{Duration, Instant};
+
+use structible::{FieldChange, structible};
+
+const HOUR: Duration = Duration::from_secs(3600);
+
+#[structible(ttl)]
+pub struct Session {
+    pub user: String,
+    pub token: Option<String>,
+    #[structible(get = cached_score, set = store_score)]
+    pub score: Option<u32>,
+    #[structible(key = String)]
+    pub extra: Option<String>,
+}
+
+#[test]
+fn test_unexpired_value_is_present() {
+    let mut session = Session::new("alice".into());
+    let before = Instant::now();
+    session.set_token_with_ttl("abc".into(), HOUR);
+
+    assert_eq!(session.token(), Some(&"abc".to_string()));
+    let expires_at = session.token_expires_at().unwrap();
+    assert!(expires_at >= before + HOUR);
+}
+
+#[test]
+fn test_expired_value_reads_as_absent() {
+    let mut session = Session::new("alice".into());
+    session.set_token_with_ttl("abc".into(), Duration::ZERO);
+
+    assert_eq!(session.token(), None);
+    assert_eq!(session.token_mut(), None);
+    assert_eq!(session.remove_token(), None);
+    assert_eq!(session.token_expires_at(), None);
+}
+
+#[test]
+fn test_plain_set_clears_expiry() {
+    let mut session = Session::new("alice".into());
+    session.set_token_with_ttl("abc".into(), Duration::ZERO);
+    session.set_token("def".into());
+
+    assert_eq!(session.token(), Some(&"def".to_string()));
+    assert_eq!(session.token_expires_at(), None);
+}
+
+#[test]
+fn test_custom_accessor_names() {
+    let mut session = Session::new("alice".into());
+    session.store_score_with_ttl(3, HOUR);
+    assert_eq!(session.cached_score(), Some(&3));
+    assert!(session.cached_score_expires_at().is_some());
+}
+
+#[test]
+fn test_purge_expired() {
+    let mut session = Session::new("alice".into());
+    session.set_token_with_ttl("abc".into(), Duration::ZERO);
+    session.store_score_with_ttl(3, HOUR);
+    session.insert_extra("lang".into(), "en".into());
+
+    assert_eq!(session.purge_expired(), 1);
+    assert_eq!(session.purge_expired(), 0);
+    assert_eq!(session.cached_score(), Some(&3));
+
+    let mut fields = session.into_fields();
+    assert_eq!(fields.take_token(), None);
+    assert_eq!(fields.take_extra("lang"), Some("en".into()));
+}
+
+#[test]
+fn test_unrepresentable_ttl_never_expires() {
+    let mut session = Session::new("alice".into());
+    session.set_token_with_ttl("abc".into(), Duration::MAX);
+    assert_eq!(session.token(), Some(&"abc".to_string()));
+    assert_eq!(session.token_expires_at(), None);
+}
+
+#[structible(ttl, with_len)]
+pub struct Counted {
+    pub id: u32,
+    pub label: Option<String>,
+}
+
+#[test]
+fn test_expired_values_are_absent_everywhere() {
+    let mut counted = Counted::new(1);
+    counted.set_label_with_ttl("old".into(), Duration::ZERO);
+
+    assert_eq!(counted.len(), 1);
+    assert!(!counted.is_empty());
+    assert_eq!(format!("{:?}", counted), "Counted { id: 1 }");
+    assert_eq!(counted, Counted::new(1));
+
+    let mut fields = counted.into_fields();
+    assert_eq!(fields.take_label(), None);
+}
+
+#[test]
+fn test_equality_compares_catch_all_entries() {
+    let mut session = Session::new("alice".into());
+    session.insert_extra("lang".into(), "en".into());
+    assert_ne!(session, Session::new("alice".into()));
+    assert_eq!(session.clone(), session);
+}
+
+#[test]
+fn test_clone_keeps_expiries() {
+    let mut session = Session::new("alice".into());
+    session.set_token_with_ttl("abc".into(), HOUR);
+    let cloned = session.clone();
+    assert_eq!(cloned.token_expires_at(), session.token_expires_at());
+}
+
+#[structible(ttl, track_changes)]
+pub struct Cache {
+    pub entry: Option<u32>,
+}
+
+#[test]
+fn test_purge_is_recorded() {
+    let mut cache = Cache::default();
+    cache.set_entry_with_ttl(1, Duration::ZERO);
+    cache.purge_expired();
+    assert_eq!(
+        cache.take_change_log(),
+        vec![
+            FieldChange::Set(CacheField::Entry, CacheValue::Entry(1)),
+            FieldChange::Remove(CacheField::Entry),
+        ]
+    );
+}
+
+#[test]
+fn test_replayed_set_clears_expiry() {
+    let mut cache = Cache::default();
+    cache.set_entry_with_ttl(1, Duration::ZERO);
+    cache.apply_changes([FieldChange::Set(CacheField::Entry, CacheValue::Entry(2))]);
+    assert_eq!(cache.entry(), Some(&2));
+    assert_eq!(cache.entry_expires_at(), None);
+}
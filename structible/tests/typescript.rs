@@ -0,0 +1,121 @@
+//! Tests for `emit_typescript!` and the generated TypeScript interfaces.
+#![cfg(feature = "typescript")]
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+
+use structible::structible;
+use structible::typescript::interface;
+
+pub mod geo {
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Point;
+}
+
+#[structible]
+pub struct Shapes {
+    pub id: u64,
+    pub label: Cow<'static, str>,
+    pub origin: geo::Point,
+    pub corners: Vec<(f32, f32)>,
+    pub outline: Option<Box<[geo::Point]>>,
+    pub names: Option<Arc<HashSet<String>>>,
+    pub weights: BTreeMap<u8, Vec<Option<f64>>>,
+    pub parent: Option<Option<String>>,
+    pub nothing: Option<()>,
+}
+
+#[structible(backing = std::collections::BTreeMap)]
+pub struct Counts {
+    pub total: usize,
+    #[structible(key = u32)]
+    pub buckets: Option<usize>,
+}
+
+#[structible]
+pub struct Person {
+    pub name: String,
+    pub r#type: Option<String>,
+    #[structible(key = String)]
+    pub extra: Option<serde_json::Value>,
+}
+
+#[test]
+fn test_field_types() {
+    assert_eq!(
+        interface::<Shapes>(),
+        "export interface Shapes {
+  id: number;
+  label: string;
+  origin: Point;
+  corners: [number, number][];
+  outline?: Point[];
+  names?: string[];
+  weights: Record<number, (number | null)[]>;
+  parent?: string | null;
+  nothing?: null;
+}
+"
+    );
+}
+
+#[test]
+fn test_index_signatures() {
+    // Only the catch-all and required fields, so no `undefined`
+    assert_eq!(
+        interface::<Counts>(),
+        "export interface Counts {
+  total: number;
+  [key: number]: number;
+}
+"
+    );
+    assert_eq!(
+        interface::<Person>(),
+        "export interface Person {
+  name: string;
+  type?: string;
+  [key: string]: unknown | string | undefined;
+}
+"
+    );
+}
+
+#[test]
+fn test_emit_typescript() {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("typescript/types.ts");
+    let _ = std::fs::remove_file(&path);
+
+    structible::emit_typescript!(&path, Counts, Person,).unwrap();
+    let module = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(
+        module,
+        format!(
+            "// Generated by structible from the Rust structs. Do not edit.\n\n{}\n{}",
+            interface::<Counts>(),
+            interface::<Person>()
+        )
+    );
+
+    let modified = std::fs::metadata(&path).unwrap().modified().unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    structible::emit_typescript!(&path, Counts, Person).unwrap();
+    assert_eq!(
+        std::fs::metadata(&path).unwrap().modified().unwrap(),
+        modified
+    );
+}
+
+#[cfg(feature = "inventory")]
+#[test]
+fn test_emit_registered_typescript() {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("typescript/registered.ts");
+    structible::emit_typescript!(&path).unwrap();
+    let module = std::fs::read_to_string(&path).unwrap();
+    let counts = module.find("export interface Counts").unwrap();
+    let person = module.find("export interface Person").unwrap();
+    let shapes = module.find("export interface Shapes").unwrap();
+    assert!(counts < person && person < shapes);
+}
@@ -211,3 +211,64 @@ fn test_into_fields_drain_unknown() {
     // After drain, extra_iter returns nothing
     assert_eq!(fields.extra_iter().count(), 0);
 }
+
+// A struct whose only field is the catch-all
+#[structible]
+pub struct Properties {
+    #[structible(key = String)]
+    pub props: Option<i64>,
+}
+
+#[test]
+fn test_catch_all_only_struct() {
+    let mut props = Properties::new();
+    assert_eq!(props, Properties::default());
+
+    props.insert_props("width".into(), 640);
+    props.insert_props("height".into(), 480);
+    assert_eq!(props.props("width"), Some(&640));
+    assert_eq!(props.props_iter().count(), 2);
+
+    let mut fields = props.into_fields();
+    assert_eq!(fields.take_props("height"), Some(480));
+}
+
+// A unit struct used as a pure extension map
+#[structible(backing = std::collections::BTreeMap, key = String, value = V)]
+pub struct Bag<V>;
+
+#[test]
+fn test_unit_struct_catch_all() {
+    let mut bag = Bag::<u32>::new();
+    assert_eq!(bag.insert_extra("b".into(), 2), None);
+    assert_eq!(bag.insert_extra("a".into(), 1), None);
+    assert_eq!(bag.insert_extra("a".into(), 10), Some(1));
+
+    assert_eq!(bag.extra("a"), Some(&10));
+    *bag.extra_mut("b").unwrap() += 1;
+
+    let entries: Vec<_> = bag.extra_iter().collect();
+    assert_eq!(
+        entries,
+        vec![(&"a".to_string(), &10), (&"b".to_string(), &3)]
+    );
+
+    assert_eq!(bag.remove_extra("a"), Some(10));
+    assert_eq!(format!("{:?}", bag), "Bag { \"b\": 3 }");
+    assert_eq!(bag.clone(), bag);
+}
+
+// A unit struct declaring only its key type holds `String` values
+mod key_only {
+    use structible::structible;
+
+    #[structible(key = String)]
+    struct Bag;
+
+    #[test]
+    fn test_unit_struct_value_defaults_to_string() {
+        let mut bag = Bag::new();
+        bag.insert_extra("color".into(), "red".into());
+        assert_eq!(bag.extra("color"), Some(&"red".to_string()));
+    }
+}
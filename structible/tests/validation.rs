@@ -0,0 +1,236 @@
+//! Tests for struct-level invariants, `validate()`, and the fallible constructor.
+
+use structible::{ValidationError, structible};
+
+fn check_range(range: &Range) -> Result<(), ValidationError> {
+    if range.start() < range.end() {
+        Ok(())
+    } else {
+        Err(ValidationError::invariant("start must precede end"))
+    }
+}
+
+#[structible(invariant = check_range)]
+pub struct Range {
+    pub start: u32,
+    pub end: u32,
+    pub label: Option<String>,
+}
+
+#[test]
+fn test_try_new_accepts_valid_value() {
+    let range = Range::try_new(1, 5).unwrap();
+    assert_eq!(*range.start(), 1);
+    assert_eq!(*range.end(), 5);
+}
+
+#[test]
+fn test_try_new_rejects_invalid_value() {
+    let err = Range::try_new(5, 1).unwrap_err();
+    assert_eq!(err, ValidationError::invariant("start must precede end"));
+    assert_eq!(
+        err.to_string(),
+        "invariant violated: start must precede end"
+    );
+}
+
+#[test]
+fn test_validate_after_mutation() {
+    let mut range = Range::new(1, 5);
+    assert!(range.validate().is_ok());
+
+    range.set_start(10);
+    assert!(range.validate().is_err());
+}
+
+// Custom error type and custom constructor name
+#[derive(Debug, PartialEq)]
+pub enum WindowError {
+    TooSmall { width: u32 },
+}
+
+mod checks {
+    pub fn window_is_wide_enough(window: &super::Window) -> Result<(), super::WindowError> {
+        if *window.width() < 100 {
+            return Err(super::WindowError::TooSmall {
+                width: *window.width(),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[structible(constructor = create, invariant = checks::window_is_wide_enough, error = WindowError)]
+pub struct Window {
+    pub width: u32,
+    pub title: Option<String>,
+}
+
+#[test]
+fn test_custom_error_type() {
+    assert_eq!(
+        Window::try_create(50).unwrap_err(),
+        WindowError::TooSmall { width: 50 }
+    );
+
+    let window = Window::try_create(640).unwrap();
+    assert_eq!(*window.width(), 640);
+    assert_eq!(window.validate(), Ok(()));
+}
+
+// Field dependency constraints
+#[structible]
+pub struct Location {
+    pub name: String,
+    pub coordinates: Option<String>,
+    #[structible(requires = coordinates)]
+    pub relative_to: Option<String>,
+}
+
+#[test]
+fn test_try_set_rejects_missing_dependency() {
+    let mut location = Location::new("Sydney".into());
+    let err = location.try_set_relative_to("start".into()).unwrap_err();
+    assert_eq!(
+        err,
+        ValidationError::MissingDependency {
+            field: "relative_to",
+            requires: "coordinates",
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "field `relative_to` requires `coordinates` to be present"
+    );
+    assert_eq!(location.relative_to(), None);
+}
+
+#[test]
+fn test_try_set_accepts_present_dependency() {
+    let mut location = Location::new("Sydney".into());
+    location.set_coordinates("geo:-33.8,151.2".into());
+    location.try_set_relative_to("start".into()).unwrap();
+    assert_eq!(location.relative_to(), Some(&"start".to_string()));
+    assert!(location.validate().is_ok());
+}
+
+#[test]
+fn test_validate_detects_removed_dependency() {
+    let mut location = Location::try_new("Sydney".into()).unwrap();
+    location.set_coordinates("geo:-33.8,151.2".into());
+    location.set_relative_to("start".into());
+    assert!(location.validate().is_ok());
+
+    location.remove_coordinates();
+    assert!(matches!(
+        location.validate(),
+        Err(ValidationError::MissingDependency { .. })
+    ));
+}
+
+// Constraints combined with an invariant and a custom error type
+#[derive(Debug, PartialEq)]
+pub enum EventError {
+    Invalid(ValidationError),
+    EmptyTitle,
+}
+
+impl From<ValidationError> for EventError {
+    fn from(err: ValidationError) -> Self {
+        EventError::Invalid(err)
+    }
+}
+
+fn title_not_empty(event: &Event) -> Result<(), EventError> {
+    if event.title().is_empty() {
+        Err(EventError::EmptyTitle)
+    } else {
+        Ok(())
+    }
+}
+
+#[structible(invariant = title_not_empty, error = EventError)]
+pub struct Event {
+    pub title: String,
+    pub start: Option<u64>,
+    #[structible(requires = start)]
+    pub duration: Option<u64>,
+}
+
+#[test]
+fn test_constraints_with_custom_error() {
+    assert_eq!(
+        Event::try_new(String::new()).unwrap_err(),
+        EventError::EmptyTitle
+    );
+
+    let mut event = Event::new("Standup".into());
+    assert_eq!(
+        event.try_set_duration(15),
+        Err(EventError::Invalid(ValidationError::MissingDependency {
+            field: "duration",
+            requires: "start",
+        }))
+    );
+    event.set_start(900);
+    assert_eq!(event.try_set_duration(15), Ok(()));
+}
+
+// Mutually exclusive fields
+#[structible]
+pub struct Attachment {
+    pub name: String,
+    pub url: Option<String>,
+    #[structible(conflicts_with = url)]
+    pub inline_content: Option<Vec<u8>>,
+}
+
+#[test]
+fn test_try_set_rejects_conflict_from_either_side() {
+    let mut attachment = Attachment::new("logo.png".into());
+    attachment
+        .try_set_url("https://example.com/logo.png".into())
+        .unwrap();
+
+    let err = attachment
+        .try_set_inline_content(vec![1, 2, 3])
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ValidationError::Conflict {
+            field: "inline_content",
+            conflicts_with: "url",
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "field `inline_content` conflicts with `url`"
+    );
+    assert_eq!(attachment.inline_content(), None);
+
+    attachment.remove_url();
+    attachment.try_set_inline_content(vec![1, 2, 3]).unwrap();
+    assert_eq!(
+        attachment.try_set_url("https://example.com/logo.png".into()),
+        Err(ValidationError::Conflict {
+            field: "url",
+            conflicts_with: "inline_content",
+        })
+    );
+}
+
+#[test]
+fn test_validate_detects_conflict() {
+    let mut attachment = Attachment::new("logo.png".into());
+    attachment.set_url("https://example.com/logo.png".into());
+    assert!(attachment.validate().is_ok());
+
+    attachment.set_inline_content(vec![1, 2, 3]);
+    assert_eq!(
+        attachment.validate(),
+        Err(ValidationError::Conflict {
+            field: "inline_content",
+            conflicts_with: "url",
+        })
+    );
+}
@@ -0,0 +1,143 @@
+//! Tests for `#[structible(value_codec)]` and the `ValueCodec` trait.
+#![cfg(feature = "codec")]
+
+use std::collections::BTreeMap;
+
+use structible::codec::CodecError;
+use structible::{FieldChange, ValidationError, ValueCodec, structible};
+
+#[structible(value_codec, track_changes)]
+pub struct Person {
+    pub name: String,
+    pub age: Option<u32>,
+    pub r#type: Option<String>,
+    #[structible(key = String)]
+    pub extra: Option<String>,
+}
+
+/// An in-memory key-value store holding one entry per field.
+#[derive(Default)]
+struct Store(BTreeMap<String, Vec<u8>>);
+
+impl Store {
+    fn write(&mut self, id: u32, person: &Person) {
+        for (field, bytes) in person.encode_entries().unwrap() {
+            self.0.insert(format!("person:{}:{}", id, field), bytes);
+        }
+    }
+
+    fn read(&self, id: u32) -> Result<Person, CodecError> {
+        let prefix = format!("person:{}:", id);
+        Person::decode_entries(
+            self.0
+                .iter()
+                .filter_map(|(key, bytes)| Some((key.strip_prefix(&prefix)?, bytes))),
+        )
+    }
+}
+
+#[test]
+fn test_field_names() {
+    assert_eq!(Person::field_name(&PersonField::Name).unwrap(), "name");
+    assert_eq!(Person::field_name(&PersonField::Type).unwrap(), "type");
+    assert_eq!(
+        Person::field_name(&PersonField::Unknown("role".into())).unwrap(),
+        "extra.role"
+    );
+    assert_eq!(Person::parse_field_name("age").unwrap(), PersonField::Age);
+    assert_eq!(
+        Person::parse_field_name("extra.role").unwrap(),
+        PersonField::Unknown("role".into())
+    );
+    assert!(matches!(
+        Person::parse_field_name("email"),
+        Err(CodecError::UnknownField(name)) if name == "email"
+    ));
+}
+
+#[test]
+fn test_round_trip_through_store() {
+    let mut person = Person::new("Alice".into());
+    person.set_age(30);
+    person.set_type("admin".into());
+    person.insert_extra("team".into(), "core".into());
+
+    let mut store = Store::default();
+    store.write(1, &person);
+    assert_eq!(store.0["person:1:name"], br#""Alice""#);
+    assert_eq!(store.0["person:1:age"], b"30");
+    assert_eq!(store.0["person:1:extra.team"], br#""core""#);
+
+    let read = store.read(1).unwrap();
+    assert_eq!(read.name(), "Alice");
+    assert_eq!(read.age(), Some(&30));
+    assert_eq!(read.r#type(), Some(&"admin".to_string()));
+    assert_eq!(read.extra("team"), Some(&"core".to_string()));
+}
+
+#[test]
+fn test_partial_writes_from_change_log() {
+    let mut store = Store::default();
+    let mut person = Person::new("Alice".into());
+    store.write(1, &person);
+    person.take_change_log();
+
+    person.set_age(31);
+    for change in person.take_change_log() {
+        let key = format!("person:1:{}", Person::field_name(change.key()).unwrap());
+        match change {
+            FieldChange::Set(_, value) => {
+                store.0.insert(key, Person::encode_value(&value).unwrap());
+            }
+            FieldChange::Remove(_) => {
+                store.0.remove(&key);
+            }
+        }
+    }
+    assert_eq!(store.0.len(), 2);
+
+    // A single field can be read on its own
+    let (key, value) = Person::decode_entry("age", &store.0["person:1:age"]).unwrap();
+    assert_eq!(key, PersonField::Age);
+    assert_eq!(value, PersonValue::Age(31));
+}
+
+#[test]
+fn test_decode_errors() {
+    assert!(matches!(
+        Person::decode_entry("age", b"\"thirty\""),
+        Err(CodecError::Json(_))
+    ));
+    assert!(matches!(
+        Person::decode_entries([("age", b"30")]),
+        Err(CodecError::Validation(ValidationError::MissingRequired {
+            field: "name"
+        }))
+    ));
+}
+
+#[structible(value_codec, backing = BTreeMap)]
+pub struct Scores<T> {
+    pub best: Option<T>,
+    #[structible(key = u32)]
+    pub rounds: Option<T>,
+}
+
+#[test]
+fn test_generic_struct_with_non_string_keys() {
+    let mut scores = Scores::<f64>::new();
+    scores.set_best(9.5);
+    scores.insert_rounds(2, 8.0);
+
+    let entries = scores.encode_entries().unwrap();
+    assert_eq!(
+        entries,
+        vec![
+            ("best".to_string(), b"9.5".to_vec()),
+            ("rounds.2".to_string(), b"8.0".to_vec()),
+        ]
+    );
+
+    let decoded = Scores::<f64>::decode_entries(entries).unwrap();
+    assert_eq!(decoded.rounds(&2), Some(&8.0));
+}
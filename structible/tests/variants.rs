@@ -0,0 +1,61 @@
+//! Tests for `#[structible(variants(...))]` variant predicates.
+
+use structible::structible;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Status {
+    Active,
+    Suspended { reason: String },
+    Closed(u32),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tier<T> {
+    Free,
+    Paid(T),
+}
+
+#[structible]
+pub struct Account {
+    #[structible(variants(Active, Suspended, Closed))]
+    pub status: Status,
+    #[structible(variants(Free, Paid))]
+    pub tier: Option<Tier<u32>>,
+    #[structible(variants(Closed), get = previous)]
+    pub previous_status: Option<Status>,
+}
+
+#[test]
+fn test_required_field_predicates() {
+    let mut account = Account::new(Status::Active);
+    assert!(account.is_status_active());
+    assert!(!account.is_status_suspended());
+
+    account.set_status(Status::Suspended {
+        reason: "spam".into(),
+    });
+    assert!(account.is_status_suspended());
+
+    account.set_status(Status::Closed(7));
+    assert!(account.is_status_closed());
+    assert!(!account.is_status_active());
+}
+
+#[test]
+fn test_optional_field_predicates() {
+    let mut account = Account::new(Status::Active);
+    assert!(!account.is_tier_free());
+    assert!(!account.is_tier_paid());
+
+    account.set_tier(Tier::Paid(10));
+    assert!(account.is_tier_paid());
+    assert!(!account.is_tier_free());
+}
+
+#[test]
+fn test_predicates_follow_getter_name() {
+    let mut account = Account::new(Status::Active);
+    assert!(!account.is_previous_closed());
+    account.set_previous_status(Status::Closed(3));
+    assert!(account.is_previous_closed());
+}
@@ -0,0 +1,92 @@
+//! Tests for overriding the visibility of generated accessors.
+
+mod model {
+    use structible::structible;
+
+    #[structible]
+    pub struct Profile {
+        pub id: u64,
+        #[structible(vis = pub(super))]
+        pub nickname: Option<String>,
+        #[structible(vis = pub(self))]
+        pub secret: Option<String>,
+    }
+
+    #[structible(accessor_vis = pub(crate))]
+    pub struct Settings {
+        pub theme: String,
+        #[structible(vis = pub)]
+        pub language: Option<String>,
+    }
+
+    #[structible(set_vis = pub(self), remove_vis = pub(self))]
+    pub struct Account {
+        pub owner: String,
+        #[structible(set_vis = pub)]
+        pub email: Option<String>,
+        pub balance: Option<u64>,
+        #[structible(key = String)]
+        pub tags: Option<String>,
+    }
+
+    pub fn open_account(owner: &str, balance: u64) -> Account {
+        let mut account = Account::new(owner.into());
+        account.set_balance(balance);
+        account.insert_tags("tier".into(), "gold".into());
+        account
+    }
+
+    pub fn close_account(account: &mut Account) -> Option<u64> {
+        account.remove_tags("tier");
+        account.remove_balance()
+    }
+
+    pub fn set_secret(profile: &mut Profile, secret: &str) {
+        profile.set_secret(secret.into());
+    }
+
+    pub fn has_secret(profile: &Profile) -> bool {
+        profile.secret().is_some()
+    }
+}
+
+use model::{Profile, Settings};
+
+#[test]
+fn test_field_level_vis() {
+    let mut profile = Profile::new(7);
+    profile.set_nickname("al".into());
+    assert_eq!(profile.nickname(), Some(&"al".to_string()));
+
+    // `secret` accessors are private to `model`
+    model::set_secret(&mut profile, "hunter2");
+    assert!(model::has_secret(&profile));
+}
+
+#[test]
+fn test_struct_level_accessor_vis() {
+    let mut settings = Settings::new("dark".into());
+    settings.set_theme("light".into());
+    settings.set_language("en".into());
+    assert_eq!(settings.theme(), "light");
+    assert_eq!(settings.language(), Some(&"en".to_string()));
+}
+
+#[test]
+fn test_read_public_write_private() {
+    let mut account = model::open_account("alice", 100);
+
+    // Getters stay public while setters and removers are private to `model`
+    assert_eq!(account.owner(), "alice");
+    assert_eq!(account.balance(), Some(&100));
+    assert_eq!(account.tags("tier"), Some(&"gold".to_string()));
+    assert_eq!(account.tags_iter().count(), 1);
+
+    // A field-level `set_vis` overrides the struct-level default
+    account.set_email("alice@example.com".into());
+    assert_eq!(account.email(), Some(&"alice@example.com".to_string()));
+
+    assert_eq!(model::close_account(&mut account), Some(100));
+    assert_eq!(account.balance(), None);
+    assert_eq!(account.tags_iter().count(), 0);
+}
@@ -0,0 +1,70 @@
+//! Tests for getters upgrading weak reference fields.
+
+use std::cell::RefCell;
+use std::rc::{self, Rc};
+use std::sync::{Arc, Weak};
+
+use structible::structible;
+
+#[structible(no_partial_eq)]
+pub struct Node {
+    pub name: String,
+    pub parent: Option<Weak<Node>>,
+    #[structible(get = owner)]
+    pub tree: Weak<String>,
+}
+
+#[test]
+fn test_getters_upgrade() {
+    let tree = Arc::new("tree".to_string());
+    let root = Arc::new(Node::new("root".into(), Arc::downgrade(&tree)));
+
+    let mut child = Node::new("child".into(), Arc::downgrade(&tree));
+    assert!(child.parent().is_none());
+    child.set_parent(Arc::downgrade(&root));
+
+    let parent = child.parent().unwrap();
+    assert_eq!(parent.name(), "root");
+    assert_eq!(child.owner().as_deref(), Some(&"tree".to_string()));
+}
+
+#[test]
+fn test_failed_upgrade_reads_as_absent() {
+    let tree = Arc::new("tree".to_string());
+    let mut child = Node::new("child".into(), Arc::downgrade(&tree));
+    {
+        let root = Arc::new(Node::new("root".into(), Arc::downgrade(&tree)));
+        child.set_parent(Arc::downgrade(&root));
+    }
+    drop(tree);
+
+    assert!(child.parent().is_none());
+    assert!(child.owner().is_none());
+    // The dead references are still stored
+    assert!(child.parent_weak().is_some());
+    assert_eq!(child.owner_weak().strong_count(), 0);
+}
+
+/// A type alias hides the `Weak` name, so the field opts in with `upgrade`.
+type Link = rc::Weak<RefCell<Vec<u32>>>;
+
+#[structible(no_partial_eq)]
+pub struct Item {
+    #[structible(upgrade)]
+    pub list: Option<Link>,
+    #[structible(no_upgrade)]
+    pub raw: Option<rc::Weak<u32>>,
+}
+
+#[test]
+fn test_upgrade_and_no_upgrade() {
+    let list = Rc::new(RefCell::new(vec![1]));
+    let count = Rc::new(7);
+    let mut item = Item::default();
+    item.set_list(Rc::downgrade(&list));
+    item.set_raw(Rc::downgrade(&count));
+
+    item.list().unwrap().borrow_mut().push(2);
+    assert_eq!(*list.borrow(), vec![1, 2]);
+    assert_eq!(item.raw().and_then(rc::Weak::upgrade), Some(count));
+}